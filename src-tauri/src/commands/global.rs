@@ -1,10 +1,33 @@
+use crate::commands::library::emit_to;
+use crate::config::data_dir;
+use crate::core::bepinex;
+use crate::core::confirmation;
+use crate::core::consistency;
+use crate::core::library_discovery;
 use crate::core::library_service;
+use crate::core::manifest_history;
+use crate::core::metrics;
+use crate::core::progress::Progress;
 use crate::core::registry::AppRegistry;
+use crate::core::shared_state::SharedState;
+use crate::core::version;
+use crate::models::bepinex::{BepInExState, BepInExStatus};
+use crate::models::confirmation::{Confirmation, DestructiveAction};
 use crate::models::error::SError;
-use crate::models::global::LibrarySwitch;
-use crate::models::library::LibraryCreationRequirement;
-use camino::Utf8PathBuf;
+use crate::models::global::{
+    ChecklistPolicy, CrashGuardPolicy, DataDirInfo, FrameworkPolicy, LibrarySwitch,
+    LinkFailurePolicy,
+};
+use crate::models::install_size::InstallSizeLimits;
+use crate::models::library::{
+    DiscoveredLibrary, LibraryComparison, LibraryCreationRequirement, LibraryDTO,
+};
+use crate::models::metrics::OperationMetric;
+use crate::models::sync_hook::SyncHooks;
+use camino::{Utf8Path, Utf8PathBuf};
 use tauri::{AppHandle, Manager, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tracing::warn;
 
 #[tauri::command]
 #[specta::specta]
@@ -14,31 +37,49 @@ pub async fn open_library(
 ) -> Result<LibrarySwitch, SError> {
     let path_buf = Utf8PathBuf::from(path);
 
-    // Clone BOTH handles to move them into the blocking thread
-    let config_handle = state.global_config.clone();
-    let instance_handle = state.active_instance.clone();
+    // Clone the shared handles to move them into the blocking thread
+    let shared = state.shared.clone();
 
-    tauri::async_runtime::spawn_blocking(move || {
-        // 1. Lock Config, Load Library (IO), Update Config
-        // We scope this block so we can release the config lock before acquiring the instance lock
-        // (though keeping it held is also safe here since the order is fixed).
-        let (lib, switch_dto) = {
-            let mut config = config_handle.lock();
-            let lib = library_service::open_library(&mut config, &path_buf)?;
-            let switch = library_service::to_library_switch(&config, Some(&lib));
-            (lib, switch)
-        };
+    tauri::async_runtime::spawn_blocking(move || switch_to(&shared, &path_buf))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))? // Unwraps JoinError
+}
 
-        // 2. Lock Instance and Swap
-        // IMPORTANT: This drops the *old* Library instance.
-        // Doing this here ensures any heavy resource cleanup (closing files, freeing RAM)
-        // happens on this blocking thread, not the async runtime.
-        *instance_handle.lock() = Some(lib);
+/// Restores the manifest of a library that fails to open from its latest readable generation,
+/// then opens it. A manifest that reads fine is left alone.
+#[tauri::command]
+#[specta::specta]
+pub async fn repair_library(
+    state: State<'_, AppRegistry>,
+    path: String,
+) -> Result<LibrarySwitch, SError> {
+    let path_buf = Utf8PathBuf::from(path);
+    let shared = state.shared.clone();
 
-        Ok(switch_dto)
+    tauri::async_runtime::spawn_blocking(move || {
+        manifest_history::repair(&path_buf)?;
+        switch_to(&shared, &path_buf)
     })
     .await
-    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))? // Unwraps JoinError
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+fn switch_to(shared: &SharedState, path: &Utf8Path) -> Result<LibrarySwitch, SError> {
+    // 1. Lock Config, Load Library (IO), Update Config
+    // The config lock is released before acquiring the instance lock.
+    let (lib, switch_dto) = shared.config(|config| {
+        let lib = library_service::open_library(config, path)?;
+        let switch = library_service::to_library_switch(config, Some(&lib));
+        Ok::<_, SError>((lib, switch))
+    })?;
+
+    // 2. Lock Instance and Swap
+    // IMPORTANT: This drops the *old* Library instance.
+    // Doing this here ensures any heavy resource cleanup (closing files, freeing RAM)
+    // happens on this blocking thread, not the async runtime.
+    shared.instance(|instance| *instance = Some(lib));
+
+    Ok(switch_dto)
 }
 
 #[tauri::command]
@@ -47,22 +88,24 @@ pub async fn create_library(
     state: State<'_, AppRegistry>,
     requirement: LibraryCreationRequirement,
 ) -> Result<LibrarySwitch, SError> {
-    // Clone handles to move into the blocking thread
-    let config_handle = state.global_config.clone();
-    let instance_handle = state.active_instance.clone();
+    // Clone the shared handles to move them into the blocking thread
+    let shared = state.shared.clone();
 
     tauri::async_runtime::spawn_blocking(move || {
         // 1. Lock Config, Create Library on disk, Update MRU
-        let (lib, switch) = {
-            let mut config = config_handle.lock();
-            let lib = library_service::create_library(&mut config, requirement)?;
-            let switch = library_service::to_library_switch(&config, Some(&lib));
-            (lib, switch)
-        };
+        let (lib, mut switch) = shared.config(|config| {
+            let lib = library_service::create_library(config, requirement)?;
+            let switch = library_service::to_library_switch(config, Some(&lib));
+            Ok::<_, SError>((lib, switch))
+        })?;
+        // Client mods would fail to load silently without BepInEx
+        if let Some(active) = switch.active.as_mut() {
+            active.warnings = bepinex::warnings(&lib.game_root);
+        }
 
         // 2. Lock Instance and Swap
         // This overwrites the old instance, triggering its Drop (cleanup) on this worker thread.
-        *instance_handle.lock() = Some(lib);
+        shared.instance(|instance| *instance = Some(lib));
 
         Ok(switch)
     })
@@ -70,6 +113,47 @@ pub async fn create_library(
     .map_err(|e| SError::AsyncRuntimeError(e.to_string()))? // Unwrap the JoinHandle error
 }
 
+/// Whether BepInEx is set up in a game root, e.g. before creating a library for it.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_bepinex_status(game_root: String) -> Result<BepInExStatus, SError> {
+    let game_root = Utf8PathBuf::from(game_root);
+    tauri::async_runtime::spawn_blocking(move || bepinex::status(&game_root))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))
+}
+
+/// Downloads the BepInEx build shipped for the SPT version of the game root and adds its
+/// missing files.
+#[tauri::command]
+#[specta::specta]
+pub async fn install_bepinex(
+    state: State<'_, AppRegistry>,
+    game_root: String,
+) -> Result<BepInExState, SError> {
+    if state.is_game_or_server_running() {
+        return Err(SError::GameOrServerRunning);
+    }
+    let game_root = Utf8PathBuf::from(game_root);
+    let spt_version = version::detect(&game_root)?;
+    let release = bepinex::release_for(&spt_version)?;
+
+    let scratch = Utf8PathBuf::from_path_buf(std::env::temp_dir())
+        .map_err(|p| SError::ParseError(p.to_string_lossy().to_string()))?;
+    let archive = scratch.join(format!("bepinex-{}.zip", release.version));
+    bepinex::download(release, &archive).await?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = bepinex::install(&game_root, &archive, &scratch);
+        if let Err(e) = std::fs::remove_file(&archive) {
+            warn!("Failed to remove the BepInEx download {archive}: {e}");
+        }
+        result
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn init(
@@ -82,17 +166,12 @@ pub async fn init(
         .store(true, std::sync::atomic::Ordering::Relaxed);
 
     // Get current state (library already loaded in background thread)
-    let config_handle = state.global_config.clone();
-    let instance_handle = state.active_instance.clone();
+    let shared = state.shared.clone();
 
-    let result = tauri::async_runtime::spawn_blocking(move || {
-        let config = config_handle.lock();
-        let instance_guard = instance_handle.lock();
-        let active_library = instance_guard.as_ref();
-        Ok(library_service::to_library_switch(&config, active_library))
-    })
-    .await
-    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?;
+    let switch =
+        tauri::async_runtime::spawn_blocking(move || library_service::current_switch(&shared))
+            .await
+            .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?;
 
     // Show the window
     if let Some(window) = app_handle.get_webview_window("main") {
@@ -101,7 +180,7 @@ pub async fn init(
             .map_err(|e| SError::IOError(format!("Failed to show window: {}", e)))?;
     }
 
-    result
+    Ok(switch)
 }
 
 #[tauri::command]
@@ -111,80 +190,404 @@ pub async fn close_library(
     repo_root: String,
 ) -> Result<LibrarySwitch, SError> {
     let path_buf = Utf8PathBuf::from(repo_root);
-    let config_handle = state.global_config.clone();
-    let instance_handle = state.active_instance.clone();
+    let shared = state.shared.clone();
 
     tauri::async_runtime::spawn_blocking(move || {
         // Check if this is the active library
-        let is_active = {
-            let instance_guard = instance_handle.lock();
-            instance_guard
+        let is_active = shared.instance(|instance| {
+            instance
                 .as_ref()
-                .map(|lib| lib.repo_root == path_buf)
-                .unwrap_or(false)
-        };
+                .is_some_and(|lib| lib.repo_root == path_buf)
+        });
 
         // Close library via service
-        let mut config = config_handle.lock();
-        library_service::close_library(&mut config, &path_buf)?;
-        drop(config);
+        shared.config(|config| library_service::close_library(config, &path_buf))?;
 
         // If closing active library, clear the instance
         if is_active {
-            *instance_handle.lock() = None;
+            shared.instance(|instance| *instance = None);
         }
 
         // Return updated switch
-        let config = config_handle.lock();
-        let instance_guard = instance_handle.lock();
-        let active_lib = instance_guard.as_ref();
-        Ok(library_service::to_library_switch(&config, active_lib))
+        Ok(library_service::current_switch(&shared))
     })
     .await
     .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
 }
 
+/// Asks the user to confirm `action` in a native dialog and, once accepted, hands out a
+/// one-time token that the destructive command takes as proof. `None` when declined.
+/// Tokens expire after `confirmation::TOKEN_TTL`.
+#[tauri::command]
+#[specta::specta]
+pub async fn request_confirmation(
+    app_handle: AppHandle,
+    state: State<'_, AppRegistry>,
+    action: DestructiveAction,
+) -> Result<Option<Confirmation>, SError> {
+    let confirmations = state.confirmations.clone();
+    // The dialog blocks until answered, which must not happen on the main thread
+    tauri::async_runtime::spawn_blocking(move || {
+        let prompt = confirmation::prompt(&action);
+        let accepted = app_handle
+            .dialog()
+            .message(prompt.message)
+            .title(prompt.title)
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                prompt.confirm.to_string(),
+                "Cancel".to_string(),
+            ))
+            .blocking_show();
+        accepted.then(|| confirmations.issue(action))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))
+}
+
+/// Needs a token from `request_confirmation` for this library.
 #[tauri::command]
 #[specta::specta]
 pub async fn remove_library(
     state: State<'_, AppRegistry>,
     repo_root: String,
+    confirmation: String,
 ) -> Result<LibrarySwitch, SError> {
     // Check if game/server is running before proceeding
     if state.is_game_or_server_running() {
         return Err(SError::GameOrServerRunning);
     }
+    let action = DestructiveAction::RemoveLibrary {
+        repo_root: repo_root.clone(),
+    };
+    state.confirmations.consume(Some(&confirmation), &action)?;
 
     let path_buf = Utf8PathBuf::from(repo_root);
-    let config_handle = state.global_config.clone();
-    let instance_handle = state.active_instance.clone();
+    let shared = state.shared.clone();
 
     tauri::async_runtime::spawn_blocking(move || {
         // Check if this is the active library
-        let is_active = {
-            let instance_guard = instance_handle.lock();
-            instance_guard
+        let is_active = shared.instance(|instance| {
+            instance
                 .as_ref()
-                .map(|lib| lib.repo_root == path_buf)
-                .unwrap_or(false)
-        };
+                .is_some_and(|lib| lib.repo_root == path_buf)
+        });
 
         // Remove library via service (unlinks mods, removes from config, deletes directory)
-        let mut config = config_handle.lock();
-        library_service::remove_library(&mut config, &path_buf)?;
-        drop(config);
+        shared.config(|config| library_service::remove_library(config, &path_buf))?;
 
         // If removing active library, clear the instance
         if is_active {
-            *instance_handle.lock() = None;
+            shared.instance(|instance| *instance = None);
         }
 
         // Return updated switch
-        let config = config_handle.lock();
-        let instance_guard = instance_handle.lock();
-        let active_lib = instance_guard.as_ref();
-        Ok(library_service::to_library_switch(&config, active_lib))
+        Ok(library_service::current_switch(&shared))
     })
     .await
     .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
 }
+
+/// Lists how the mods of two libraries differ, see `library_service::compare_libraries`.
+#[tauri::command]
+#[specta::specta]
+pub async fn compare_libraries(
+    repo_a: String,
+    repo_b: String,
+) -> Result<LibraryComparison, SError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        library_service::compare_libraries(&Utf8PathBuf::from(repo_a), &Utf8PathBuf::from(repo_b))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Searches the drives for libraries, e.g. after the app was reinstalled. Without drives,
+/// every mounted drive is searched.
+#[tauri::command]
+#[specta::specta]
+pub async fn discover_existing_libraries(
+    app_handle: AppHandle,
+    state: State<'_, AppRegistry>,
+    drives: Vec<String>,
+) -> Result<Vec<DiscoveredLibrary>, SError> {
+    let config = state.shared.config(|config| config.clone());
+    let roots = match drives.is_empty() {
+        true => library_discovery::default_roots(),
+        false => drives.into_iter().map(Utf8PathBuf::from).collect(),
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let emit = emit_to(&app_handle);
+        let progress = Progress::new("discover_existing_libraries", &emit);
+        progress.finish(Ok(library_discovery::discover(&roots, &config, progress)))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Adds discovered libraries to the known ones and returns the updated summary.
+#[tauri::command]
+#[specta::specta]
+pub async fn register_libraries(
+    state: State<'_, AppRegistry>,
+    paths: Vec<String>,
+) -> Result<Vec<LibraryDTO>, SError> {
+    let paths: Vec<Utf8PathBuf> = paths.into_iter().map(Utf8PathBuf::from).collect();
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.config(|config| library_discovery::register(config, &paths))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_framework_policy(
+    state: State<'_, AppRegistry>,
+) -> Result<FrameworkPolicy, SError> {
+    Ok(state.shared.config(|config| config.framework_policy))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_framework_policy(
+    state: State<'_, AppRegistry>,
+    policy: FrameworkPolicy,
+) -> Result<FrameworkPolicy, SError> {
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.config(|config| {
+            config.framework_policy = policy;
+            config.save();
+            Ok(config.framework_policy)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_checklist_policy(
+    state: State<'_, AppRegistry>,
+) -> Result<ChecklistPolicy, SError> {
+    Ok(state.shared.config(|config| config.checklist_policy))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_checklist_policy(
+    state: State<'_, AppRegistry>,
+    policy: ChecklistPolicy,
+) -> Result<ChecklistPolicy, SError> {
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.config(|config| {
+            config.checklist_policy = policy;
+            config.save();
+            Ok(config.checklist_policy)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_link_failure_policy(
+    state: State<'_, AppRegistry>,
+) -> Result<LinkFailurePolicy, SError> {
+    Ok(state.shared.config(|config| config.link_failure_policy))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_link_failure_policy(
+    state: State<'_, AppRegistry>,
+    policy: LinkFailurePolicy,
+) -> Result<LinkFailurePolicy, SError> {
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.config(|config| {
+            config.link_failure_policy = policy;
+            config.save();
+            Ok(config.link_failure_policy)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_crash_guard_policy(
+    state: State<'_, AppRegistry>,
+) -> Result<CrashGuardPolicy, SError> {
+    Ok(state.shared.config(|config| config.crash_guard))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_crash_guard_policy(
+    state: State<'_, AppRegistry>,
+    policy: CrashGuardPolicy,
+) -> Result<CrashGuardPolicy, SError> {
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.config(|config| {
+            config.crash_guard = policy;
+            config.save();
+            Ok(config.crash_guard)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Opts into checking the library after every change; debug builds always do.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_consistency_checks(
+    state: State<'_, AppRegistry>,
+    enabled: bool,
+) -> Result<bool, SError> {
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.config(|config| {
+            config.consistency_checks = enabled;
+            config.save();
+            consistency::set_enabled(enabled);
+            Ok(config.consistency_checks)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_auto_sync_after_add(state: State<'_, AppRegistry>) -> Result<bool, SError> {
+    Ok(state.shared.config(|config| config.auto_sync_after_add))
+}
+
+/// Opts into syncing right after installing mods, within the same task.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_auto_sync_after_add(
+    state: State<'_, AppRegistry>,
+    enabled: bool,
+) -> Result<bool, SError> {
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.config(|config| {
+            config.auto_sync_after_add = enabled;
+            config.save();
+            Ok(config.auto_sync_after_add)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_database_diff_enabled(state: State<'_, AppRegistry>) -> Result<bool, SError> {
+    Ok(state.shared.config(|config| config.database_diff))
+}
+
+/// Opts into the database diff of server mods, meant for troubleshooting overlapping mods.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_database_diff_enabled(
+    state: State<'_, AppRegistry>,
+    enabled: bool,
+) -> Result<bool, SError> {
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.config(|config| {
+            config.database_diff = enabled;
+            config.save();
+            Ok(config.database_diff)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_sync_hooks(state: State<'_, AppRegistry>) -> Result<SyncHooks, SError> {
+    Ok(state.shared.config(|config| config.sync_hooks.clone()))
+}
+
+/// Stores the commands run around a sync. They only run once `enabled` is set.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_sync_hooks(
+    state: State<'_, AppRegistry>,
+    hooks: SyncHooks,
+) -> Result<SyncHooks, SError> {
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.config(|config| {
+            config.sync_hooks = hooks;
+            config.save();
+            Ok(config.sync_hooks.clone())
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_install_size_limits(
+    state: State<'_, AppRegistry>,
+) -> Result<InstallSizeLimits, SError> {
+    Ok(state.shared.config(|config| config.install_size_limits))
+}
+
+/// Stores when the analysis before an install warns about its size.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_install_size_limits(
+    state: State<'_, AppRegistry>,
+    limits: InstallSizeLimits,
+) -> Result<InstallSizeLimits, SError> {
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.config(|config| {
+            config.install_size_limits = limits;
+            config.save();
+            Ok(config.install_size_limits)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Rolling timing averages of core operations.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_performance_metrics() -> Result<Vec<OperationMetric>, SError> {
+    Ok(metrics::summary())
+}
+
+/// Where the global config and metrics are stored, and whether that was overridden.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_data_dir() -> Result<DataDirInfo, SError> {
+    Ok(data_dir::describe())
+}
@@ -1,62 +1,349 @@
+use crate::core::drop_queue::{Offer, QueuedDrop};
+use crate::core::progress::Progress;
 use crate::core::registry::AppRegistry;
+use crate::core::shared_state::SharedState;
 use crate::core::{
-    cleanup, deployment, dto_builder, library_service, mod_backup, mod_documentation, mod_manager,
-    mod_stager,
+    actionable_issues, batch_import, cache_store, cleanup, compat_notes, consistency, crash_guard,
+    database_diff, dedicated_server, dependency, dev_watch, download, dto_builder, file_overrides,
+    file_search, game_scan, install_size, launch_checklist, library_service, local_edits, lockfile,
+    logging, mod_backup, mod_documentation, mod_icon, mod_manager, mod_packager, mod_patches,
+    mod_scaffold, mod_stager, modpack, ownership, plan_store, profile_wipe, profiles,
+    recommendations, remote_target, repo_history, server_task, statistics, support_bundle,
+    sync_hook, update_checker, volume,
 };
+use crate::models::actionable_issue::ActionableIssue;
+use crate::models::batch_import::BatchImportReport;
+use crate::models::compat_note::CompatNote;
+use crate::models::confirmation::DestructiveAction;
+use crate::models::consistency::{CacheRebuildReport, ConsistencyIssue};
+use crate::models::database_diff::{DatabaseOverlap, RecordChange};
+use crate::models::dedicated_server::ServerHealth;
+use crate::models::deployment_plan::{DeploymentPlan, SavedPlan, SyncScope};
 use crate::models::error::SError;
+use crate::models::events::{DropQueued, QueuedDropInstalled, TaskStatus};
+use crate::models::file_search::FileMatch;
+use crate::models::game_scan::UnmanagedEntry;
 use crate::models::global::LibrarySwitch;
-use crate::models::library::LibraryDTO;
-use crate::models::mod_backup::ModBackup;
-use crate::utils::thread::{with_lib_arc, with_lib_arc_mut};
+use crate::models::install_size::InstallEstimate;
+use crate::models::launch_checklist::LaunchChecklist;
+use crate::models::library::{DeploymentMode, LibraryDTO, LibraryMode};
+use crate::models::lockfile::LockfileDiff;
+use crate::models::logging::OperationLog;
+use crate::models::mod_backup::{BackupRetention, BackupUsage, ModBackup};
+use crate::models::mod_dto::{Appearance, Mod, ModMetadataUpdate, ModPage, VersionBump};
+use crate::models::mod_patch::PatchGroup;
+use crate::models::ownership::OwnershipReport;
+use crate::models::recommendation::Recommendation;
+use crate::models::remote_target::RemoteStatus;
+use crate::models::repo_history::RepoCommit;
+use crate::models::scaffold::ScaffoldOptions;
+use crate::models::server_task::ServerTask;
+use crate::models::statistics::LibraryStatistics;
+use crate::models::update_info::UpdateInfo;
+use crate::models::volume::VolumeStatus;
+use crate::models::warning::OperationWarning;
+use crate::utils::context::Pipeline;
+use crate::utils::file::FileUtils;
+use crate::utils::http;
+use crate::utils::time::get_unix_timestamp;
 use camino::Utf8PathBuf;
-use tauri::State;
-use tracing::{debug, info};
+use tauri::{AppHandle, Manager, State};
+use tauri_specta::Event;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
 
+/// Commits listed when the frontend does not ask for a number.
+const REPO_HISTORY_LIMIT: u32 = 50;
+
+/// Unpacked size of the files about to be installed and the space left for them, so huge
+/// installs can be called off before anything is extracted.
+#[tauri::command]
+#[specta::specta]
+pub async fn analyze_install(
+    state: State<'_, AppRegistry>,
+    paths: Vec<String>,
+) -> Result<InstallEstimate, SError> {
+    let shared = state.shared.clone();
+    let inputs: Vec<Utf8PathBuf> = paths.into_iter().map(Utf8PathBuf::from).collect();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let limits = shared.config(|config| config.install_size_limits);
+        shared.with_lib(|inst| {
+            Ok(install_size::estimate(
+                &inputs,
+                &inst.lib_paths.mods,
+                limits,
+            ))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Installs the files, then syncs incrementally under the same lock when
+/// `auto_sync_after_add` is set and the game is not running.
 #[tauri::command]
 #[specta::specta]
 pub async fn add_mods(
+    app_handle: AppHandle,
     state: State<'_, AppRegistry>,
     paths: Vec<String>,
     unknown_mod_name: String,
 ) -> Result<LibraryDTO, SError> {
-    let inputs = paths
-        .into_iter()
-        .map(Utf8PathBuf::from)
-        .collect::<Vec<Utf8PathBuf>>();
+    let drop = QueuedDrop {
+        paths: paths.into_iter().map(Utf8PathBuf::from).collect(),
+        unknown_mod_name,
+    };
+    install_dropped(app_handle, &state, drop).await
+}
+
+/// Downloads an archive, or the latest release of a Forge mod page, into the staging folder
+/// and installs it like a dropped file. The download is checked against `size` and `sha256`
+/// when given, and reported as task `install_from_url`.
+#[tauri::command]
+#[specta::specta]
+pub async fn install_from_url(
+    app_handle: AppHandle,
+    state: State<'_, AppRegistry>,
+    url: String,
+    size: Option<u64>,
+    sha256: Option<String>,
+    unknown_mod_name: String,
+) -> Result<LibraryDTO, SError> {
+    let expected = download::Expected { size, sha256 };
+    let shared = state.shared.clone();
+    let dir = tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| {
+            inst.ensure_writable()?;
+            Ok::<_, SError>(
+                inst.lib_paths
+                    .staging
+                    .join(format!("download-{}", Uuid::new_v4())),
+            )
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))???;
+
+    let emitter = app_handle.clone();
+    let target = dir.clone();
+    let archive = tauri::async_runtime::spawn_blocking(move || {
+        let emit = emit_to(&emitter);
+        let progress = Progress::new("install_from_url", &emit);
+        let run = || -> Result<Utf8PathBuf, SError> {
+            let client = http::blocking_client()?;
+            let file_url = download::resolve(&client, &url)?;
+            info!("Downloading {file_url}");
+            download::fetch(&client, &file_url, &target, &expected, progress)
+        };
+        progress.finish(run())
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))
+    .and_then(|result| result);
+
+    let result = match archive {
+        Ok(archive) => {
+            let drop = QueuedDrop {
+                paths: vec![archive],
+                unknown_mod_name,
+            };
+            install_dropped(app_handle, &state, drop).await
+        }
+        Err(e) => Err(e),
+    };
+    if dir.exists() {
+        if let Err(e) = FileUtils::remove_recursive(&dir) {
+            warn!("Failed to remove the download {dir}: {e}");
+        }
+    }
+    result
+}
+
+/// Installs files dropped on the window, or queues them while a long task runs, e.g. a sync.
+/// Queued files are installed once the task ends and the result arrives as
+/// `QueuedDropInstalled`; `None` is returned for them.
+#[tauri::command]
+#[specta::specta]
+pub async fn drop_files(
+    app_handle: AppHandle,
+    state: State<'_, AppRegistry>,
+    paths: Vec<String>,
+    unknown_mod_name: String,
+) -> Result<Option<LibraryDTO>, SError> {
+    let drop = QueuedDrop {
+        paths: paths.iter().map(Utf8PathBuf::from).collect(),
+        unknown_mod_name,
+    };
+    match state.drops.offer(drop) {
+        Offer::Ready(drop) => install_dropped(app_handle, &state, drop).await.map(Some),
+        Offer::Queued { pending } => {
+            if let Err(e) = (DropQueued { paths, pending }).emit(&app_handle) {
+                warn!("Failed to emit queued drop: {e}");
+            }
+            Ok(None)
+        }
+    }
+}
+
+/// Marks a long task as running for the drop queue; drops queued meanwhile are installed
+/// when the last running task ends.
+struct RunningTask {
+    app_handle: AppHandle,
+}
+
+impl RunningTask {
+    fn start(app_handle: &AppHandle, state: &AppRegistry) -> Self {
+        state.drops.begin();
+        Self {
+            app_handle: app_handle.clone(),
+        }
+    }
+}
+
+impl Drop for RunningTask {
+    fn drop(&mut self) {
+        let drops = self.app_handle.state::<AppRegistry>().drops.end();
+        if drops.is_empty() {
+            return;
+        }
+        let app_handle = self.app_handle.clone();
+        tauri::async_runtime::spawn(install_queued(app_handle, drops));
+    }
+}
+
+async fn install_queued(app_handle: AppHandle, drops: Vec<QueuedDrop>) {
+    let state = app_handle.state::<AppRegistry>();
+    for drop in drops {
+        // A failure was already reported through the task status of the install
+        let Ok(library) = install_dropped(app_handle.clone(), &state, drop).await else {
+            continue;
+        };
+        if let Err(e) = (QueuedDropInstalled { library }).emit(&app_handle) {
+            warn!("Failed to emit installed drop: {e}");
+        }
+    }
+}
+
+async fn install_dropped(
+    app_handle: AppHandle,
+    state: &AppRegistry,
+    drop: QueuedDrop,
+) -> Result<LibraryDTO, SError> {
+    let _running = RunningTask::start(&app_handle, state);
+    let QueuedDrop {
+        paths: inputs,
+        unknown_mod_name,
+    } = drop;
 
-    let material = state.get_stage_material(unknown_mod_name.clone())?;
+    let material = state.get_stage_material(unknown_mod_name)?;
     debug!("staging_material: {:?}", material);
+    let game_running = state.is_game_or_server_running();
+    let (auto_sync, policy, link_policy, hooks) = state.shared.config(|config| {
+        (
+            config.auto_sync_after_add && !game_running,
+            config.checklist_policy,
+            config.link_failure_policy,
+            config.sync_hooks.clone(),
+        )
+    });
+    let stages: &[&str] = match auto_sync {
+        true => &["stage", "install", "sync"],
+        false => &["stage", "install"],
+    };
 
     // Clone the Arc handle so we can move it into the 'static blocking thread.
     // 'state' cannot be moved, but the Arc inside it can be cloned.
-    let instance_handle = state.active_instance.clone();
+    let shared = state.shared.clone();
 
     tauri::async_runtime::spawn_blocking(move || {
-        info!("Staging mod files");
-        // 1. Resolve (Heavy Compute/IO)
-        // We do this here to avoid blocking the async runtime
-        let staged_mods = mod_stager::resolve(&inputs, &material)?;
-        debug!("staged_mods: {:?}", staged_mods);
+        let emit = emit_to(&app_handle);
+        let pipeline = Pipeline::new("add_mods", &emit, stages);
+        let progress = pipeline.progress();
+        let run = || -> Result<LibraryDTO, SError> {
+            info!("Staging mod files");
+            // 1. Resolve (Heavy Compute/IO)
+            // We do this here to avoid blocking the async runtime
+            let staged_mods = pipeline.stage("stage", |_| {
+                mod_stager::resolve(&inputs, &material, progress)
+            })?;
+            debug!("staged_mods: {:?}", staged_mods);
 
-        with_lib_arc_mut(instance_handle, |inst| {
-            info!("Adding mods to library");
-            // 3. Install & Cleanup
-            // Using try_for_each for early exit on error
-            staged_mods
-                .into_iter()
-                .try_for_each(|staged| {
-                    debug!("current: {:?}", staged);
-                    // Extract cleanup data before moving staged into add_mod
-                    let is_staging = staged.is_staging;
-                    let source_path = staged.source_path.clone();
-                    mod_manager::add_mod(inst, staged)
-                        .and_then(|_| mod_stager::clean_up(is_staging, &source_path))
+            let dto = shared.with_lib_mut(|inst| -> Result<LibraryDTO, SError> {
+                // 2. Install & Cleanup, collecting non-fatal warnings along the way
+                let mut warnings = pipeline.stage("install", |_| {
+                    info!("Adding mods to library");
+                    mod_manager::add_staged(inst, staged_mods, progress)
+                })?;
+                // 3. Deploy within the same lock, so nothing changes in between
+                if auto_sync {
+                    let game_root = inst.game_root.clone();
+                    warnings.extend(pipeline.stage("sync", |_| {
+                        sync_hook::around(&hooks, &game_root, progress, || {
+                            progress.linking(inst.mods.values().filter(|m| m.is_active).count());
+                            library_service::sync_incremental(inst, policy, link_policy)
+                        })
+                    })?);
+                }
+                Ok(LibraryDTO {
+                    warnings,
+                    ..dto_builder::build_frontend_dto(inst)
                 })
-                .map(|_| dto_builder::build_frontend_dto(inst))
-        })
+            })??;
+
+            // 4. Report warnings for the post-operation summary
+            progress.warnings(&dto.warnings);
+            Ok(dto)
+        };
+        pipeline.finish(run())
     })
     .await
-    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Installs every archive of a folder, e.g. a downloads stash, see `batch_import::install`.
+#[tauri::command]
+#[specta::specta]
+pub async fn batch_import(
+    app_handle: AppHandle,
+    state: State<'_, AppRegistry>,
+    path: String,
+    unknown_mod_name: String,
+) -> Result<BatchImportReport, SError> {
+    let _running = RunningTask::start(&app_handle, &state);
+    let dir = Utf8PathBuf::from(path);
+    let material = state.get_stage_material(unknown_mod_name)?;
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let emit = emit_to(&app_handle);
+        let pipeline = Pipeline::new("batch_import", &emit, &["stage", "install"]);
+        let progress = pipeline.progress();
+        let run = || -> Result<BatchImportReport, SError> {
+            let staged = pipeline.stage("stage", |_| {
+                let archives = batch_import::find_archives(&dir)?;
+                Ok(batch_import::stage(&archives, &material, progress))
+            })?;
+            let report = pipeline.stage("install", |_| {
+                shared.with_lib_mut(|inst| batch_import::install(inst, staged, progress))
+            })?;
+
+            progress.warnings(&report.warnings);
+            Ok(report)
+        };
+        pipeline.finish(run())
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Forwards task progress to the frontend; a failed emit only loses that update.
+pub(crate) fn emit_to(app_handle: &AppHandle) -> impl Fn(TaskStatus) + '_ {
+    move |event| {
+        if let Err(e) = event.emit(app_handle) {
+            warn!("Failed to emit {event:?}: {e}");
+        }
+    }
 }
 
 #[tauri::command]
@@ -65,10 +352,10 @@ pub async fn remove_mods(
     state: State<'_, AppRegistry>,
     ids: Vec<String>,
 ) -> Result<LibraryDTO, SError> {
-    let instance_handle = state.active_instance.clone();
+    let shared = state.shared.clone();
     // Offload synchronous file IO and locking to a blocking thread
     tauri::async_runtime::spawn_blocking(move || {
-        with_lib_arc_mut(instance_handle, |inst| -> Result<LibraryDTO, SError> {
+        shared.with_lib_mut(|inst| -> Result<LibraryDTO, SError> {
             ids.iter()
                 .try_for_each(|mod_id| {
                     debug!("Removing mod {}", mod_id);
@@ -84,15 +371,155 @@ pub async fn remove_mods(
 
 #[tauri::command]
 #[specta::specta]
-pub async fn sync_mods(state: State<'_, AppRegistry>) -> Result<LibraryDTO, SError> {
+pub async fn sync_mods(
+    app_handle: AppHandle,
+    state: State<'_, AppRegistry>,
+    scope: SyncScope,
+) -> Result<LibraryDTO, SError> {
     if state.is_game_or_server_running() {
         return Err(SError::GameOrServerRunning.into());
     }
+    let _running = RunningTask::start(&app_handle, &state);
+
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let emit = emit_to(&app_handle);
+        let progress = Progress::new("sync_mods", &emit);
+        let (policy, link_policy, hooks) = shared.config(|config| {
+            (
+                config.checklist_policy,
+                config.link_failure_policy,
+                config.sync_hooks.clone(),
+            )
+        });
+        let run = || -> Result<LibraryDTO, SError> {
+            let game_root = shared.with_lib(|inst| inst.game_root.clone())?;
+            let warnings = sync_hook::around(&hooks, &game_root, progress, || {
+                shared.with_lib_mut(|inst| {
+                    progress.linking(inst.mods.values().filter(|m| m.is_active).count());
+                    library_service::sync_scoped(inst, policy, link_policy, scope)
+                })?
+            })?;
+            let dto = shared.with_lib(|inst| LibraryDTO {
+                warnings,
+                ..dto_builder::build_frontend_dto(inst)
+            })?;
+
+            progress.warnings(&dto.warnings);
+            Ok(dto)
+        };
+        progress.finish(run())
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Activates exactly the given mods and deploys them, relinking only what changed.
+#[tauri::command]
+#[specta::specta]
+pub async fn switch_active_mods(
+    app_handle: AppHandle,
+    state: State<'_, AppRegistry>,
+    ids: Vec<String>,
+) -> Result<LibraryDTO, SError> {
+    if state.is_game_or_server_running() {
+        return Err(SError::GameOrServerRunning);
+    }
+    let _running = RunningTask::start(&app_handle, &state);
+
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let emit = emit_to(&app_handle);
+        let progress = Progress::new("switch_active_mods", &emit);
+        let (policy, link_policy, hooks) = shared.config(|config| {
+            (
+                config.checklist_policy,
+                config.link_failure_policy,
+                config.sync_hooks.clone(),
+            )
+        });
+        let run = || -> Result<LibraryDTO, SError> {
+            let game_root = shared.with_lib(|inst| inst.game_root.clone())?;
+            let warnings = sync_hook::around(&hooks, &game_root, progress, || {
+                shared.with_lib_mut(|inst| {
+                    progress.linking(ids.len());
+                    library_service::switch_active(inst, &ids, policy, link_policy)
+                })?
+            })?;
+            let dto = shared.with_lib(|inst| LibraryDTO {
+                warnings,
+                ..dto_builder::build_frontend_dto(inst)
+            })?;
+
+            progress.warnings(&dto.warnings);
+            Ok(dto)
+        };
+        progress.finish(run())
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Deactivates a mod the server keeps crashing on and syncs without it, as suggested by a
+/// `RepeatedCrash` event.
+#[tauri::command]
+#[specta::specta]
+pub async fn disable_crashing_mod(
+    app_handle: AppHandle,
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+) -> Result<LibraryDTO, SError> {
+    if state.is_game_or_server_running() {
+        return Err(SError::GameOrServerRunning);
+    }
+    let _running = RunningTask::start(&app_handle, &state);
+
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let emit = emit_to(&app_handle);
+        let progress = Progress::new("disable_crashing_mod", &emit);
+        let (policy, link_policy, hooks) = shared.config(|config| {
+            (
+                config.checklist_policy,
+                config.link_failure_policy,
+                config.sync_hooks.clone(),
+            )
+        });
+        let run = || -> Result<LibraryDTO, SError> {
+            let game_root = shared.with_lib(|inst| inst.game_root.clone())?;
+            let warnings = sync_hook::around(&hooks, &game_root, progress, || {
+                shared
+                    .with_lib_mut(|inst| crash_guard::disable(inst, &mod_id, policy, link_policy))?
+            })?;
+            let dto = shared.with_lib(|inst| LibraryDTO {
+                warnings,
+                ..dto_builder::build_frontend_dto(inst)
+            })?;
+
+            progress.warnings(&dto.warnings);
+            Ok(dto)
+        };
+        progress.finish(run())
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Registers the folders managed beyond the server mods and client plugins.
+/// Deployed mods are purged first and the library is left dirty until the next sync.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_managed_roots(
+    state: State<'_, AppRegistry>,
+    roots: Vec<String>,
+) -> Result<LibraryDTO, SError> {
+    if state.is_game_or_server_running() {
+        return Err(SError::GameOrServerRunning);
+    }
 
-    let instance_handle = state.active_instance.clone();
+    let shared = state.shared.clone();
     tauri::async_runtime::spawn_blocking(move || {
-        with_lib_arc_mut(instance_handle, |inst| {
-            // 1. Purge existing managed links
+        shared.with_lib_mut(|inst| {
             cleanup::purge(
                 &inst.game_root,
                 &inst.repo_root,
@@ -100,88 +527,208 @@ pub async fn sync_mods(state: State<'_, AppRegistry>) -> Result<LibraryDTO, SErr
                 &inst.lib_paths,
                 &inst.cache,
             )?;
+            inst.set_managed_roots(roots.into_iter().map(Utf8PathBuf::from).collect())?;
+            Ok(dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
 
-            // 2. Deploy active mods
-            deployment::deploy(
-                &inst.game_root,
-                &inst.lib_paths,
-                &inst.spt_rules,
-                &inst.mods,
-                &inst.cache,
-            )?;
+/// Prerequisites the active mods declare before launching, such as a server wipe.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_launch_checklist(
+    state: State<'_, AppRegistry>,
+) -> Result<LaunchChecklist, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(launch_checklist::build))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
 
-            inst.mark_clean();
-            inst.persist()?;
-            Ok(dto_builder::build_frontend_dto(inst))
+#[tauri::command]
+#[specta::specta]
+pub async fn acknowledge_launch_checklist(
+    state: State<'_, AppRegistry>,
+) -> Result<LaunchChecklist, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib_mut(launch_checklist::acknowledge))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_sync(
+    state: State<'_, AppRegistry>,
+    export: bool,
+) -> Result<DeploymentPlan, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| {
+            let mut plan = library_service::preview_sync(inst)?;
+
+            if export {
+                inst.ensure_writable()?;
+                plan_store::save(&inst.lib_paths, &mut plan)?;
+            }
+            Ok(plan)
         })
     })
     .await
     .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
 }
 
+/// Deploys the active mods into a scratch folder to validate the whole link plan, returning
+/// the warnings a real sync would raise. The game root is left untouched.
 #[tauri::command]
 #[specta::specta]
-pub async fn get_library(state: State<'_, AppRegistry>) -> Result<LibraryDTO, SError> {
-    let instance_handle = state.active_instance.clone();
+pub async fn sandbox_sync(state: State<'_, AppRegistry>) -> Result<Vec<OperationWarning>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let (policy, link_policy) =
+            shared.config(|config| (config.checklist_policy, config.link_failure_policy));
+        shared.with_lib(|inst| library_service::sandbox_sync(inst, policy, link_policy))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Backs up the server profiles and deletes them, returning the backup folder if any.
+#[tauri::command]
+#[specta::specta]
+pub async fn reset_profiles(state: State<'_, AppRegistry>) -> Result<Option<String>, SError> {
+    if state.is_game_or_server_running() {
+        return Err(SError::GameOrServerRunning);
+    }
+
+    let shared = state.shared.clone();
     tauri::async_runtime::spawn_blocking(move || {
-        with_lib_arc(instance_handle, |inst| {
-            dto_builder::build_frontend_dto(inst)
+        shared.with_lib(|inst| {
+            inst.ensure_writable()?;
+            let backup = profile_wipe::reset_profiles(inst)?;
+            if let Some(backup) = &backup {
+                info!("Reset server profiles, backup at {}", backup);
+            }
+            Ok(backup.map(Utf8PathBuf::into_string))
         })
     })
     .await
-    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn toggle_mod(
+pub async fn list_plans(state: State<'_, AppRegistry>) -> Result<Vec<SavedPlan>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| plan_store::list(&inst.lib_paths))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn load_plan(
     state: State<'_, AppRegistry>,
-    id: String,
-    is_active: bool,
+    timestamp: String,
+) -> Result<DeploymentPlan, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| plan_store::load(&inst.lib_paths, &timestamp))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Builds a distribution zip for a mod and returns the archive path.
+#[tauri::command]
+#[specta::specta]
+pub async fn package_mod(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+    output_path: String,
+    bump: Option<VersionBump>,
+) -> Result<String, SError> {
+    let output = Utf8PathBuf::from(output_path);
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            mod_packager::package_mod(inst, &mod_id, &output, bump).map(|p| p.to_string())
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Writes a support bundle for bug reports and returns the archive path.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_support_bundle(
+    state: State<'_, AppRegistry>,
+    output_path: String,
+) -> Result<String, SError> {
+    let output = Utf8PathBuf::from(output_path);
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| support_bundle::write(inst, &output).map(|p| p.to_string()))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Deploys `path` from `winner` instead of failing on the other active mods providing it.
+/// Without a winner, the override is dropped.
+#[tauri::command]
+#[specta::specta]
+pub async fn resolve_conflict(
+    state: State<'_, AppRegistry>,
+    path: String,
+    winner: Option<String>,
 ) -> Result<LibraryDTO, SError> {
-    let instance_handle = state.active_instance.clone();
+    let shared = state.shared.clone();
     tauri::async_runtime::spawn_blocking(move || {
-        with_lib_arc_mut(instance_handle, |inst| {
-            mod_manager::toggle_mod(inst, &id, is_active)
-                .map(|_| dto_builder::build_frontend_dto(inst))
+        shared.with_lib_mut(|inst| {
+            file_overrides::resolve(inst, Utf8PathBuf::from(path), winner)?;
+            Ok(dto_builder::build_frontend_dto(inst))
         })
     })
     .await
     .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
 }
 
+/// Adds or replaces the compatibility note of a mod pair.
 #[tauri::command]
 #[specta::specta]
-pub async fn get_backups(
+pub async fn set_compat_note(
     state: State<'_, AppRegistry>,
-    mod_id: String,
-) -> Result<Vec<ModBackup>, SError> {
-    let instance_handle = state.active_instance.clone();
-    tauri::async_runtime::spawn_blocking(move || {
-        let lib_paths = instance_handle
-            .lock()
-            .as_ref()
-            .ok_or(SError::NoActiveLibrary)?
-            .lib_paths
-            .clone();
-        mod_backup::list_backups(&lib_paths, &mod_id)
+    note: CompatNote,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            compat_notes::upsert(inst, note)?;
+            Ok(dto_builder::build_frontend_dto(inst))
+        })
     })
     .await
-    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
 }
 
 #[tauri::command]
 #[specta::specta]
-pub async fn restore_backup(
+pub async fn remove_compat_note(
     state: State<'_, AppRegistry>,
-    mod_id: String,
-    timestamp: String,
+    mod_a: String,
+    mod_b: String,
 ) -> Result<LibraryDTO, SError> {
-    let instance_handle = state.active_instance.clone();
+    let shared = state.shared.clone();
     tauri::async_runtime::spawn_blocking(move || {
-        with_lib_arc_mut(instance_handle, |inst| {
-            mod_backup::restore_backup(inst, &mod_id, &timestamp)
-                .map(|_| dto_builder::build_frontend_dto(inst))
+        shared.with_lib_mut(|inst| {
+            compat_notes::remove(inst, &mod_a, &mod_b)?;
+            Ok(dto_builder::build_frontend_dto(inst))
         })
     })
     .await
@@ -190,40 +737,1176 @@ pub async fn restore_backup(
 
 #[tauri::command]
 #[specta::specta]
-pub async fn get_mod_documentation(
+pub async fn export_compat_notes(
     state: State<'_, AppRegistry>,
-    mod_id: String,
+    output_path: String,
+) -> Result<(), SError> {
+    let output = Utf8PathBuf::from(output_path);
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| compat_notes::export(inst, &output))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Writes the lockfile of the active mods, shared alongside a modlist.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_lockfile(
+    state: State<'_, AppRegistry>,
+    output_path: String,
+) -> Result<(), SError> {
+    let output = Utf8PathBuf::from(output_path);
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| lockfile::export(inst, &output))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Writes the library setup to a `.mkpack` file, with the mod files when `include_payloads`,
+/// and returns where it was written, see `modpack::export`.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_modpack(
+    state: State<'_, AppRegistry>,
+    output_path: String,
+    include_payloads: bool,
 ) -> Result<String, SError> {
-    let instance_handle = state.active_instance.clone();
+    let output = Utf8PathBuf::from(output_path);
+    let shared = state.shared.clone();
     tauri::async_runtime::spawn_blocking(move || {
-        with_lib_arc(instance_handle, |inst| {
-            mod_documentation::read_documentation(inst, &mod_id)
+        shared.with_lib(|inst| {
+            modpack::export(inst, &output, include_payloads).map(|path| path.to_string())
         })
     })
     .await
     .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
 }
 
+/// Installs the mods bundled in a `.mkpack` file and recreates its activation and profile,
+/// see `modpack::import`. The library is left to be synced.
 #[tauri::command]
 #[specta::specta]
-pub async fn rename_library(
+pub async fn import_modpack(
+    app_handle: AppHandle,
     state: State<'_, AppRegistry>,
-    name: String,
-) -> Result<LibrarySwitch, SError> {
-    let config_handle = state.global_config.clone();
-    let instance_handle = state.active_instance.clone();
+    input_path: String,
+    unknown_mod_name: String,
+) -> Result<LibraryDTO, SError> {
+    let _running = RunningTask::start(&app_handle, &state);
+    let input = Utf8PathBuf::from(input_path);
+    let material = state.get_stage_material(unknown_mod_name)?;
+    let shared = state.shared.clone();
 
     tauri::async_runtime::spawn_blocking(move || {
-        // Update library name via service
-        with_lib_arc_mut(instance_handle.clone(), |inst| {
-            library_service::rename_library(inst, name)
-        })??;
+        let emit = emit_to(&app_handle);
+        let progress = Progress::new("import_modpack", &emit);
+        let run = || -> Result<LibraryDTO, SError> {
+            let dto = shared.with_lib_mut(|inst| {
+                modpack::import(inst, &input, &material, progress).map(|warnings| LibraryDTO {
+                    warnings,
+                    ..dto_builder::build_frontend_dto(inst)
+                })
+            })??;
+            progress.warnings(&dto.warnings);
+            Ok(dto)
+        };
+        progress.finish(run())
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
 
-        // Return updated switch
-        let config = config_handle.lock();
-        let instance_guard = instance_handle.lock();
-        let active_lib = instance_guard.as_ref();
-        Ok(library_service::to_library_switch(&config, active_lib))
+/// Mod folders and DLLs in the game root that the library does not manage, see `game_scan`.
+#[tauri::command]
+#[specta::specta]
+pub async fn scan_game_directory(
+    state: State<'_, AppRegistry>,
+) -> Result<Vec<UnmanagedEntry>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(game_scan::scan))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Moves unmanaged entries found by `scan_game_directory` into the library; the next sync
+/// links them back into the game root.
+#[tauri::command]
+#[specta::specta]
+pub async fn adopt_mods(
+    app_handle: AppHandle,
+    state: State<'_, AppRegistry>,
+    paths: Vec<String>,
+) -> Result<LibraryDTO, SError> {
+    if state.is_game_or_server_running() {
+        return Err(SError::GameOrServerRunning);
+    }
+    let _running = RunningTask::start(&app_handle, &state);
+    let paths: Vec<Utf8PathBuf> = paths.into_iter().map(Utf8PathBuf::from).collect();
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let emit = emit_to(&app_handle);
+        let progress = Progress::new("adopt_mods", &emit);
+        let run = || -> Result<LibraryDTO, SError> {
+            let dto = shared.with_lib_mut(|inst| {
+                game_scan::adopt(inst, &paths, progress).map(|warnings| LibraryDTO {
+                    warnings,
+                    ..dto_builder::build_frontend_dto(inst)
+                })
+            })??;
+            progress.warnings(&dto.warnings);
+            Ok(dto)
+        };
+        progress.finish(run())
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Checks the installed mods against a lockfile, see `lockfile::verify`.
+#[tauri::command]
+#[specta::specta]
+pub async fn verify_lockfile(
+    state: State<'_, AppRegistry>,
+    input_path: String,
+) -> Result<Vec<OperationWarning>, SError> {
+    let input = Utf8PathBuf::from(input_path);
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| lockfile::verify(inst, &input))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Compares the library with a friend's lockfile, see `lockfile::check_against`.
+#[tauri::command]
+#[specta::specta]
+pub async fn check_against_lockfile(
+    state: State<'_, AppRegistry>,
+    input_path: String,
+) -> Result<LockfileDiff, SError> {
+    let input = Utf8PathBuf::from(input_path);
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| lockfile::check_against(inst, &input))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Activates and deactivates mods so the active set matches a lockfile.
+#[tauri::command]
+#[specta::specta]
+pub async fn fix_lockfile_activation(
+    state: State<'_, AppRegistry>,
+    input_path: String,
+) -> Result<LibraryDTO, SError> {
+    let input = Utf8PathBuf::from(input_path);
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            lockfile::fix_activation(inst, &input).map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Merges a compatibility notes file shared by another user.
+#[tauri::command]
+#[specta::specta]
+pub async fn import_compat_notes(
+    state: State<'_, AppRegistry>,
+    path: String,
+) -> Result<LibraryDTO, SError> {
+    let input = Utf8PathBuf::from(path);
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            let count = compat_notes::import(inst, &input)?;
+            info!("Imported {count} compatibility note(s) from {input}");
+            Ok(dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_library(state: State<'_, AppRegistry>) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(dto_builder::build_frontend_dto))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Pages through the mods in id order, see `dto_builder::build_mod_page`.
+#[tauri::command]
+#[specta::specta]
+pub async fn query_mods(
+    state: State<'_, AppRegistry>,
+    cursor: Option<String>,
+    limit: u32,
+    appearance: Option<Appearance>,
+) -> Result<ModPage, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| {
+            dto_builder::build_mod_page(
+                inst,
+                cursor.as_deref(),
+                limit,
+                &appearance.unwrap_or_default(),
+            )
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// The problems of the library worth a banner, most urgent first, with what resolves each.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_actionable_issues(
+    state: State<'_, AppRegistry>,
+) -> Result<Vec<ActionableIssue>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let limits = shared.config(|config| config.install_size_limits);
+        shared.with_lib(|inst| Ok(actionable_issues::collect(inst, limits)))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Reverse lookup of the mods providing a file, e.g. a DLL named in an error log.
+#[tauri::command]
+#[specta::specta]
+pub async fn find_mods_by_file(
+    state: State<'_, AppRegistry>,
+    pattern: String,
+) -> Result<Vec<FileMatch>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| file_search::find_mods_by_file(inst, &pattern))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Ids of the mods whose name, author or description matches every word of `query`.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_mods(
+    state: State<'_, AppRegistry>,
+    query: String,
+) -> Result<Vec<String>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| file_search::find_mods(inst, &query))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Starts tracking the library repo with git, committing after every add, remove and sync.
+#[tauri::command]
+#[specta::specta]
+pub async fn enable_repo_history(state: State<'_, AppRegistry>) -> Result<Vec<RepoCommit>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            repo_history::enable(&inst.repo_root)?;
+            repo_history::history(&inst.repo_root, REPO_HISTORY_LIMIT)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_repo_history(
+    state: State<'_, AppRegistry>,
+    limit: Option<u32>,
+) -> Result<Vec<RepoCommit>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| {
+            repo_history::history(&inst.repo_root, limit.unwrap_or(REPO_HISTORY_LIMIT))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Brings the mods back to a commit of the repo history; the next sync deploys them.
+#[tauri::command]
+#[specta::specta]
+pub async fn checkout_state(
+    state: State<'_, AppRegistry>,
+    commit: String,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            repo_history::checkout(inst, &commit).map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Checks the active library for drift between its manifest, cache and repo folders.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_consistency_report(
+    state: State<'_, AppRegistry>,
+) -> Result<Vec<ConsistencyIssue>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(consistency::check))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Sets the mounted server mods folder of a remote server, or clears it with `None`.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_remote_server(
+    state: State<'_, AppRegistry>,
+    path: Option<String>,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            remote_target::set_target(inst, path.map(Utf8PathBuf::from))
+                .map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Saves the active mods as a new profile and switches to it.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_profile(
+    state: State<'_, AppRegistry>,
+    name: String,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            profiles::create(inst, &name).map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Activates the mods of a profile; they are deployed by the next sync.
+#[tauri::command]
+#[specta::specta]
+pub async fn switch_profile(
+    state: State<'_, AppRegistry>,
+    name: String,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            profiles::switch(inst, &name).map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_profile(
+    state: State<'_, AppRegistry>,
+    name: String,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            profiles::delete(inst, &name).map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn duplicate_profile(
+    state: State<'_, AppRegistry>,
+    source: String,
+    name: String,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            profiles::duplicate(inst, &source, &name).map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Switches between linking and copying mod files into the game root.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_deployment_mode(
+    state: State<'_, AppRegistry>,
+    mode: DeploymentMode,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            library_service::set_deployment_mode(inst, mode)
+                .map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Drives of the game root and the repo, with the deployment mode they call for.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_volume_status(state: State<'_, AppRegistry>) -> Result<VolumeStatus, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(volume::status))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Rebuilds the cache of the active library from its repo folders, also when it failed to load.
+#[tauri::command]
+#[specta::specta]
+pub async fn rebuild_cache(state: State<'_, AppRegistry>) -> Result<CacheRebuildReport, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        // Not `with_lib_mut`, which loads the cache first
+        shared.instance(|instance| {
+            let library = instance.as_mut().ok_or(SError::NoActiveLibrary)?;
+            library_service::rebuild_cache(library)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// The latest sync, add and remove logs of the library, newest first, for troubleshooting.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_operation_logs(
+    state: State<'_, AppRegistry>,
+    limit: Option<u32>,
+) -> Result<Vec<OperationLog>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|lib| logging::read(&lib.lib_paths.logs, limit.map(|l| l as usize)))?
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Switches between a regular install and a dedicated Fika server.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_library_mode(
+    state: State<'_, AppRegistry>,
+    mode: LibraryMode,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            dedicated_server::set_mode(inst, mode).map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Health check of the library as a dedicated Fika server.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_server_health(state: State<'_, AppRegistry>) -> Result<ServerHealth, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(dedicated_server::check))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Registers a scheduled task starting the server of the library through this app.
+#[tauri::command]
+#[specta::specta]
+pub async fn install_server_task(state: State<'_, AppRegistry>) -> Result<ServerTask, SError> {
+    let exe = Utf8PathBuf::from_path_buf(std::env::current_exe()?)
+        .map_err(|e| SError::IOError(format!("Failed to convert path: {}", e.display())))?;
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| server_task::install(inst, &exe))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn remove_server_task(state: State<'_, AppRegistry>) -> Result<ServerTask, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(server_task::remove))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_server_task_status(state: State<'_, AppRegistry>) -> Result<ServerTask, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(server_task::status))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Copies the active server mods to the remote server.
+#[tauri::command]
+#[specta::specta]
+pub async fn push_remote_server(state: State<'_, AppRegistry>) -> Result<RemoteStatus, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| {
+            inst.ensure_writable()?;
+            remote_target::push(inst)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn purge_remote_server(state: State<'_, AppRegistry>) -> Result<RemoteStatus, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| {
+            inst.ensure_writable()?;
+            remote_target::purge(inst)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_remote_server_status(
+    state: State<'_, AppRegistry>,
+) -> Result<Option<RemoteStatus>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(remote_target::status))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_mod_statistics(
+    state: State<'_, AppRegistry>,
+) -> Result<LibraryStatistics, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(statistics::compute))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Links in the mod roots per mod, and the ones of other mod managers that are left alone.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_ownership_report(
+    state: State<'_, AppRegistry>,
+) -> Result<OwnershipReport, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(ownership::report))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_mod_details(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+    appearance: Option<Appearance>,
+) -> Result<Mod, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| {
+            dto_builder::build_mod_details(inst, &mod_id, &appearance.unwrap_or_default())
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn toggle_mod(
+    state: State<'_, AppRegistry>,
+    id: String,
+    is_active: bool,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            mod_manager::toggle_mod(inst, &id, is_active)?;
+            Ok(LibraryDTO {
+                warnings: mod_patches::activation_offers(inst, &[id]),
+                ..dto_builder::build_frontend_dto(inst)
+            })
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn toggle_mods(
+    state: State<'_, AppRegistry>,
+    ids: Vec<String>,
+    is_active: bool,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            mod_manager::toggle_mods(inst, &ids, is_active)?;
+            Ok(LibraryDTO {
+                warnings: mod_patches::activation_offers(inst, &ids),
+                ..dto_builder::build_frontend_dto(inst)
+            })
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// What a server mod changes in the SPT database. Opt-in, see `set_database_diff_enabled`.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_database_diff(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+) -> Result<Vec<RecordChange>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        ensure_database_diff(&shared)?;
+        shared.with_lib(|lib| database_diff::diff(lib, &mod_id))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Database records several active server mods ship data for.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_database_overlaps(
+    state: State<'_, AppRegistry>,
+) -> Result<Vec<DatabaseOverlap>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        ensure_database_diff(&shared)?;
+        shared.with_lib(database_diff::overlaps)
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+fn ensure_database_diff(shared: &SharedState) -> Result<(), SError> {
+    match shared.config(|config| config.database_diff) {
+        true => Ok(()),
+        false => Err(SError::FeatureDisabled("database diff".to_string())),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_patch_groups(state: State<'_, AppRegistry>) -> Result<Vec<PatchGroup>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || shared.with_lib(mod_patches::groups))
+        .await
+        .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Applies the property editor's changes to the selected mods in one write.
+#[tauri::command]
+#[specta::specta]
+pub async fn bulk_update_mod_metadata(
+    state: State<'_, AppRegistry>,
+    updates: Vec<ModMetadataUpdate>,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            mod_manager::update_metadata(inst, updates)
+                .map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Asks the update source of every mod that declares one for its latest release, and returns
+/// the cached results of all of them. Sources are queried concurrently and at most once per
+/// `update_checker::TTL_SECS` unless `force` is set.
+#[tauri::command]
+#[specta::specta]
+pub async fn check_for_updates(
+    state: State<'_, AppRegistry>,
+    force: bool,
+) -> Result<Vec<UpdateInfo>, SError> {
+    let shared = state.shared.clone();
+    let now = get_unix_timestamp();
+    let lookup = shared.clone();
+    let pending = tauri::async_runtime::spawn_blocking(move || {
+        lookup.with_lib(|inst| update_checker::due(inst, now, force))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??;
+
+    let client = update_checker::client()?;
+    let queries: Vec<_> = pending
+        .into_iter()
+        .map(|check| {
+            let client = client.clone();
+            tauri::async_runtime::spawn(async move {
+                let result = update_checker::fetch(&client, &check).await;
+                (check, result)
+            })
+        })
+        .collect();
+    let mut results = Vec::with_capacity(queries.len());
+    for query in queries {
+        results.push(
+            query
+                .await
+                .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?,
+        );
+    }
+
+    // The results are only a cache, so a read-only library keeps them in memory
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.instance(|instance| {
+            let inst = instance.as_mut().ok_or(SError::NoActiveLibrary)?;
+            inst.ensure_loaded()?;
+            results
+                .into_iter()
+                .for_each(|(check, result)| update_checker::record(inst, check, result, now));
+            if !inst.is_read_only() {
+                inst.persist()?;
+            }
+            Ok(update_checker::report(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Sets the free-text note of a mod, e.g. why it is disabled; an empty note clears it.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_mod_note(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+    note: String,
+) -> Result<LibraryDTO, SError> {
+    let update = ModMetadataUpdate {
+        mod_id,
+        note: Some(note),
+        ..Default::default()
+    };
+    bulk_update_mod_metadata(state, vec![update]).await
+}
+
+/// Replaces the tags, categories and color label of a mod; an empty color clears it.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_mod_tags(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+    tags: Vec<String>,
+    categories: Vec<String>,
+    color: String,
+) -> Result<LibraryDTO, SError> {
+    let update = ModMetadataUpdate {
+        mod_id,
+        tags: Some(tags),
+        categories: Some(categories),
+        color: Some(color),
+        ..Default::default()
+    };
+    bulk_update_mod_metadata(state, vec![update]).await
+}
+
+/// Shows a copy of the image instead of the manifest icon of the mod, `None` restores it.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_mod_icon(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+    image_path: Option<Utf8PathBuf>,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            mod_icon::set_icon(inst, &mod_id, image_path.as_deref())
+                .map(|_| dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn keep_local_edits(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            local_edits::keep(inst, &mod_id)?;
+            Ok(dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn revert_local_edits(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            local_edits::revert(inst, &mod_id)?;
+            Ok(dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_backup_disk_usage(state: State<'_, AppRegistry>) -> Result<BackupUsage, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|lib| mod_backup::disk_usage(&lib.lib_paths))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_backup_retention(
+    state: State<'_, AppRegistry>,
+    retention: BackupRetention,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            mod_backup::set_retention(inst, retention)?;
+            Ok(dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_backups(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+) -> Result<Vec<ModBackup>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let lib_paths = shared
+            .instance(|instance| instance.as_ref().map(|lib| lib.lib_paths.clone()))
+            .ok_or(SError::NoActiveLibrary)?;
+        mod_backup::list_backups(&lib_paths, &mod_id)
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+/// Deletes one backup of a mod and returns the ones left.
+/// Needs a token from `request_confirmation`.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_backup(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+    timestamp: String,
+    confirmation: String,
+) -> Result<Vec<ModBackup>, SError> {
+    let action = DestructiveAction::DeleteBackup {
+        mod_id: mod_id.clone(),
+        timestamp: timestamp.clone(),
+    };
+    state.confirmations.consume(Some(&confirmation), &action)?;
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|lib| {
+            lib.ensure_writable()?;
+            mod_backup::delete_backup(&lib.lib_paths, &mod_id, &timestamp)?;
+            mod_backup::list_backups(&lib.lib_paths, &mod_id)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Deletes every backup of a mod. Needs a token from `request_confirmation`.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_all_backups(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+    confirmation: String,
+) -> Result<(), SError> {
+    let action = DestructiveAction::DeleteAllBackups {
+        mod_id: mod_id.clone(),
+    };
+    state.confirmations.consume(Some(&confirmation), &action)?;
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|lib| {
+            lib.ensure_writable()?;
+            mod_backup::delete_all_backups(&lib.lib_paths, &mod_id)
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Restoring over local edits of the mod needs a token from `request_confirmation`.
+#[tauri::command]
+#[specta::specta]
+pub async fn restore_backup(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+    timestamp: String,
+    confirmation: Option<String>,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    let confirmations = state.confirmations.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib_mut(|inst| {
+            // Restoring over files edited in place loses the edits
+            if inst.cache.local_edits.contains_key(&mod_id) {
+                let action = DestructiveAction::RestoreBackup {
+                    mod_id: mod_id.clone(),
+                    timestamp: timestamp.clone(),
+                };
+                confirmations.consume(confirmation.as_deref(), &action)?;
+            }
+            mod_backup::restore_backup(inst, &mod_id, &timestamp).map(|warnings| LibraryDTO {
+                warnings,
+                ..dto_builder::build_frontend_dto(inst)
+            })
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_mod_documentation(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+) -> Result<String, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| mod_documentation::read_documentation(inst, &mod_id))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Companions suggested for a mod that are not active, see `recommendations::for_mod`.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recommendations(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+) -> Result<Vec<Recommendation>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| recommendations::for_mod(inst, &mod_id))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Mods to enable together with a mod so its dependencies are met; pass them to `toggle_mods`.
+#[tauri::command]
+#[specta::specta]
+pub async fn resolve_mod_dependencies(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+) -> Result<Vec<String>, SError> {
+    let shared = state.shared.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| dependency::resolve_activation(inst, &mod_id))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??
+}
+
+/// Marks the active library read-only, or lifts the flag when its repo is writable.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_library_read_only(
+    state: State<'_, AppRegistry>,
+    read_only: bool,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    // Not through with_lib_mut, which refuses read-only libraries
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.instance(|instance| {
+            let inst = instance.as_mut().ok_or(SError::NoActiveLibrary)?;
+            inst.set_read_only(read_only)?;
+            Ok(dto_builder::build_frontend_dto(inst))
+        })
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_library(
+    state: State<'_, AppRegistry>,
+    name: String,
+) -> Result<LibrarySwitch, SError> {
+    let shared = state.shared.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        // Update library name via service
+        shared.with_lib_mut(|inst| library_service::rename_library(inst, name))??;
+
+        // Return updated switch
+        Ok(library_service::current_switch(&shared))
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn watch_mod_source(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+    path: String,
+) -> Result<LibraryDTO, SError> {
+    let source = Utf8PathBuf::from(path);
+    if !source.is_dir() {
+        return Err(SError::FileOrDirectoryNotFound(source.to_string()));
+    }
+
+    let shared = state.shared.clone();
+    let (handle, id, src) = (shared.clone(), mod_id.clone(), source.clone());
+    let (dto, repo_root) = tauri::async_runtime::spawn_blocking(move || {
+        // Initial import so the link takes effect right away
+        handle.with_lib_mut(|inst| {
+            dev_watch::reimport(inst, &id, &src).map(|_| {
+                (
+                    dto_builder::build_frontend_dto(inst),
+                    inst.repo_root.clone(),
+                )
+            })
+        })?
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??;
+
+    let watcher = dev_watch::spawn_watcher(shared, repo_root, mod_id.clone(), source);
+    // Replacing an existing handle drops it, which stops the previous watcher
+    state.dev_watches.lock().insert(mod_id, watcher);
+
+    Ok(dto)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn unwatch_mod_source(
+    state: State<'_, AppRegistry>,
+    mod_id: String,
+) -> Result<bool, SError> {
+    Ok(state.dev_watches.lock().remove(&mod_id).is_some())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn scaffold_mod(
+    state: State<'_, AppRegistry>,
+    options: ScaffoldOptions,
+) -> Result<LibraryDTO, SError> {
+    let shared = state.shared.clone();
+    let handle = shared.clone();
+    let (dto, repo_root, mod_id, source) = tauri::async_runtime::spawn_blocking(move || {
+        handle.with_lib_mut(|inst| {
+            mod_scaffold::create(inst, &options).map(|(id, root)| {
+                (
+                    dto_builder::build_frontend_dto(inst),
+                    inst.repo_root.clone(),
+                    id,
+                    root,
+                )
+            })
+        })?
+    })
+    .await
+    .map_err(|e| SError::AsyncRuntimeError(e.to_string()))??;
+
+    info!("Scaffolded {mod_id} in {source}");
+    // The scaffold is meant to be edited in place, so keep it dev-linked
+    let watcher = dev_watch::spawn_watcher(shared, repo_root, mod_id.clone(), source);
+    state.dev_watches.lock().insert(mod_id, watcher);
+
+    Ok(dto)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn export_cache_toml(
+    state: State<'_, AppRegistry>,
+    path: Option<String>,
+) -> Result<String, SError> {
+    let shared = state.shared.clone();
+    let dest = path.map(Utf8PathBuf::from);
+    tauri::async_runtime::spawn_blocking(move || {
+        shared.with_lib(|inst| {
+            cache_store::export_toml(&inst.lib_paths, &inst.cache, dest.as_deref())
+                .map(|p| p.to_string())
+        })?
     })
     .await
     .map_err(|e| SError::AsyncRuntimeError(e.to_string()))?
@@ -0,0 +1,76 @@
+use crate::models::global::{DataDirInfo, DataDirSource};
+use camino::Utf8PathBuf;
+use std::sync::OnceLock;
+
+/// Folder overriding where the global config and metrics are stored.
+pub const DATA_DIR_ENV: &str = "MOD_KEEPER_DATA_DIR";
+/// Stores app data in a `data` folder next to the executable.
+pub const PORTABLE_FLAG: &str = "--portable";
+const PORTABLE_FOLDER: &str = "data";
+const APP_NAME: &str = "mod_keeper";
+
+static DATA_DIR: OnceLock<DataDirInfo> = OnceLock::new();
+
+/// Picks the data directory for this run from the process arguments (without the program
+/// name) and returns the remaining arguments. The portable flag wins over the environment.
+/// Only the first call has an effect; call it before anything reads the config.
+pub fn init(args: Vec<String>) -> Vec<String> {
+    let portable = args.iter().any(|arg| arg == PORTABLE_FLAG);
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Utf8PathBuf::from_path_buf(exe).ok())
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()));
+    let env = std::env::var(DATA_DIR_ENV).ok();
+
+    DATA_DIR.get_or_init(|| resolve(portable, env.as_deref(), exe_dir));
+    args.into_iter()
+        .filter(|arg| arg != PORTABLE_FLAG)
+        .collect()
+}
+
+/// Resolves the data directory from its possible sources, in order of precedence.
+pub fn resolve(portable: bool, env: Option<&str>, exe_dir: Option<Utf8PathBuf>) -> DataDirInfo {
+    let env = env.map(str::trim).filter(|dir| !dir.is_empty());
+    match (portable, env) {
+        (true, _) => DataDirInfo {
+            source: DataDirSource::Portable,
+            path: exe_dir.map(|dir| dir.join(PORTABLE_FOLDER)),
+        },
+        (false, Some(dir)) => DataDirInfo {
+            source: DataDirSource::Environment,
+            path: Some(Utf8PathBuf::from(dir)),
+        },
+        (false, None) => DataDirInfo::default(),
+    }
+}
+
+/// The data directory of this run, from the environment alone when `init` was not called.
+pub fn current() -> &'static DataDirInfo {
+    DATA_DIR.get_or_init(|| resolve(false, std::env::var(DATA_DIR_ENV).ok().as_deref(), None))
+}
+
+/// Location of a TOML file stored in the data directory.
+pub fn file_path(name: &str) -> Option<Utf8PathBuf> {
+    let info = current();
+    if info.source != DataDirSource::Default {
+        return info
+            .path
+            .as_ref()
+            .map(|dir| dir.join(format!("{name}.toml")));
+    }
+
+    confy::get_configuration_file_path(APP_NAME, name)
+        .ok()
+        .and_then(|path| Utf8PathBuf::from_path_buf(path).ok())
+}
+
+/// Where the data directory is and why, for the settings page.
+pub fn describe() -> DataDirInfo {
+    let info = current();
+    DataDirInfo {
+        source: info.source,
+        path: info.path.clone().or_else(|| {
+            file_path("config").and_then(|file| file.parent().map(|dir| dir.to_path_buf()))
+        }),
+    }
+}
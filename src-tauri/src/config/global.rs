@@ -1,9 +1,48 @@
+use crate::config::data_dir;
+use crate::models::global::{
+    ChecklistPolicy, CrashGuardPolicy, FrameworkPolicy, LinkFailurePolicy,
+};
+use crate::models::install_size::InstallSizeLimits;
+use crate::models::sync_hook::SyncHooks;
 use camino::{Utf8Path, Utf8PathBuf};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tracing::{info, warn};
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct GlobalConfig {
+    /// Most recently used first, so the first entry is the last opened library.
+    #[serde(default)]
     pub known_libraries: Vec<Utf8PathBuf>,
+    #[serde(default)]
+    pub framework_policy: FrameworkPolicy,
+    #[serde(default)]
+    pub checklist_policy: ChecklistPolicy,
+    #[serde(default)]
+    pub link_failure_policy: LinkFailurePolicy,
+    /// Check the library after every change in release builds too, see `core::consistency`.
+    #[serde(default)]
+    pub consistency_checks: bool,
+    /// Opt-in shell commands run around a sync, see `core::sync_hook`.
+    #[serde(default)]
+    pub sync_hooks: SyncHooks,
+    /// Chain an incremental sync into every install, see `commands::library::add_mods`.
+    #[serde(default)]
+    pub auto_sync_after_add: bool,
+    /// When the analysis before an install warns about its size, see `core::install_size`.
+    #[serde(default)]
+    pub install_size_limits: InstallSizeLimits,
+    /// Opt-in diff of what server mods change in the SPT database, see `core::database_diff`.
+    #[serde(default)]
+    pub database_diff: bool,
+    /// What happens to a mod that keeps crashing the server, see `core::crash_guard`.
+    #[serde(default)]
+    pub crash_guard: CrashGuardPolicy,
+    /// Keys written before instances were renamed to libraries; read by `migrate`, never written.
+    #[serde(default, alias = "known_instances", skip_serializing)]
+    known_instance_paths: Vec<Utf8PathBuf>,
+    #[serde(default, alias = "last_opened", skip_serializing)]
+    last_opened_instance: Option<Utf8PathBuf>,
 }
 
 #[cfg(debug_assertions)]
@@ -12,13 +51,74 @@ const CONFIG_NAME: &str = "config_debug";
 #[cfg(not(debug_assertions))]
 const CONFIG_NAME: &str = "config";
 
+#[cfg(debug_assertions)]
+const METRICS_NAME: &str = "metrics_debug";
+
+#[cfg(not(debug_assertions))]
+const METRICS_NAME: &str = "metrics";
+
 impl GlobalConfig {
+    /// Loads the config, converting legacy keys and storing the result right away.
     pub fn load() -> GlobalConfig {
-        confy::load("mod_keeper", CONFIG_NAME).unwrap_or_default()
+        let mut config: GlobalConfig = data_dir::file_path(CONFIG_NAME)
+            .and_then(|path| confy::load_path(path).ok())
+            .unwrap_or_default();
+        if config.migrate() {
+            config.save();
+        }
+        config
+    }
+
+    /// Moves legacy instance keys into `known_libraries` after the current entries, the last
+    /// opened instance first, then drops duplicates and paths that cannot be a library root.
+    /// Missing folders are kept, they may live on a drive that is not mounted.
+    /// Returns whether the stored config needs rewriting.
+    pub fn migrate(&mut self) -> bool {
+        let legacy: Vec<Utf8PathBuf> = self
+            .last_opened_instance
+            .take()
+            .into_iter()
+            .chain(std::mem::take(&mut self.known_instance_paths))
+            .collect();
+        let has_legacy = !legacy.is_empty();
+        if has_legacy {
+            info!("Migrating {} legacy instance path(s)", legacy.len());
+        }
+
+        let before = self.known_libraries.len() + legacy.len();
+        let mut seen = HashSet::new();
+        self.known_libraries = std::mem::take(&mut self.known_libraries)
+            .into_iter()
+            .chain(legacy)
+            .filter(|path| {
+                let valid = path.is_absolute();
+                if !valid {
+                    warn!("Dropping invalid library path from config: {path:?}");
+                }
+                valid
+            })
+            .filter(|path| seen.insert(path.clone()))
+            .collect();
+
+        has_legacy || self.known_libraries.len() != before
     }
 
     pub fn save(&self) {
-        let _ = confy::store("mod_keeper", CONFIG_NAME, self);
+        if let Some(path) = data_dir::file_path(CONFIG_NAME) {
+            let _ = confy::store_path(path, self);
+        }
+    }
+
+    /// Location of the stored performance metrics, next to the config file in the data directory.
+    pub fn metrics_path() -> Option<Utf8PathBuf> {
+        data_dir::file_path(METRICS_NAME)
+    }
+
+    /// Folder of the daily app logs, next to the config file in the data directory.
+    pub fn logs_path() -> Option<Utf8PathBuf> {
+        data_dir::file_path(CONFIG_NAME)?
+            .parent()
+            .map(|dir| dir.join("logs"))
     }
 
     pub(crate) fn update_recent(&mut self, path: &Utf8Path) {
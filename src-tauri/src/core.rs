@@ -1,15 +1,74 @@
+pub mod actionable_issues;
+pub mod batch;
+pub mod batch_import;
+pub mod bepinex;
+pub mod bundled_framework;
 pub mod cache;
+pub mod cache_store;
 pub mod cleanup;
+pub mod compat_notes;
+pub mod config_adoption;
+pub mod confirmation;
+pub mod consistency;
+pub mod crash_guard;
+pub mod database_diff;
 pub mod decompression;
+pub mod dedicated_server;
+pub mod dependency;
+pub mod deploy_journal;
+pub mod deploy_ledger;
 pub mod deployment;
+pub mod dev_watch;
+pub mod download;
+pub mod drop_queue;
 pub mod dto_builder;
+pub mod file_overrides;
+pub mod file_search;
+pub mod fs_watch;
+pub mod game_scan;
+pub mod health_watch;
+pub mod idle_hasher;
+pub mod install_size;
+pub mod launch_checklist;
 pub mod library;
+pub mod library_discovery;
+pub mod library_lock;
 pub mod library_service;
 pub mod linker;
+pub mod local_edits;
+pub mod lockfile;
+pub mod logging;
+pub mod manifest_history;
+pub mod metrics;
 pub mod mod_backup;
 pub mod mod_documentation;
 pub mod mod_fs;
+pub mod mod_icon;
 pub mod mod_manager;
+pub mod mod_packager;
+pub mod mod_patches;
+pub mod mod_scaffold;
 pub mod mod_stager;
+pub mod modpack;
+pub mod ownership;
+pub mod plan_store;
+pub mod process_watch;
+pub mod profile_wipe;
+pub mod profiles;
+pub mod progress;
+pub mod recommendations;
 pub mod registry;
+pub mod remote_target;
+pub mod repo_history;
+pub mod repo_store;
+pub mod search_index;
+pub mod server_task;
+pub mod shared_state;
+pub mod staging_handler;
+pub mod statistics;
+pub mod support_bundle;
+pub mod sync_hook;
+pub mod sync_index;
+pub mod update_checker;
 pub mod version;
+pub mod volume;
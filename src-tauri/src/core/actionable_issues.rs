@@ -0,0 +1,119 @@
+use crate::core::library::Library;
+use crate::core::{
+    dedicated_server, dependency, deployment, file_overrides, install_size, sync_index,
+    update_checker,
+};
+use crate::models::actionable_issue::{ActionableIssue, IssueAction, IssueKind};
+use crate::models::error::SError;
+use crate::models::install_size::InstallSizeLimits;
+use crate::models::warning::WarningKind;
+use std::collections::BTreeSet;
+
+/// The problems of the library worth a banner, ranked by `IssueKind`, each with what resolves it.
+/// Only cached state and the game root are read; update sources are not queried.
+pub fn collect(library: &Library, limits: InstallSizeLimits) -> Vec<ActionableIssue> {
+    let mut issues: Vec<ActionableIssue> = [
+        broken_links(library),
+        corrupted_mods(library),
+        incompatible_mods(library),
+        unsynced_changes(library),
+        low_disk_space(library, limits),
+        updates_available(library),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    issues.sort_by_key(|issue| issue.kind);
+    issues
+}
+
+fn broken_links(library: &Library) -> Option<ActionableIssue> {
+    issue(
+        IssueKind::BrokenLinks,
+        broken_mods(library),
+        IssueAction::SyncMods,
+    )
+}
+
+/// Mods with missing deployed files, only looked for while the game root should match the library.
+pub fn broken_mods(library: &Library) -> Vec<String> {
+    if library.is_dirty || sync_index::read(&library.lib_paths).is_none() {
+        return Vec::new();
+    }
+    let mods = dedicated_server::deployable_mods(library);
+    let cache = file_overrides::deployable_cache(library);
+    deployment::verify(&library.game_root, &mods, &cache)
+        .into_iter()
+        .map(|warning| warning.subject)
+        .collect()
+}
+
+fn incompatible_mods(library: &Library) -> Option<ActionableIssue> {
+    let Err(SError::UnmetDependencies(unmet)) = dependency::ensure_satisfied(library) else {
+        return None;
+    };
+    let subjects: BTreeSet<String> = unmet.into_iter().map(|i| i.mod_id).collect();
+    issue(
+        IssueKind::IncompatibleMods,
+        subjects.into_iter().collect(),
+        IssueAction::ResolveModDependencies,
+    )
+}
+
+fn unsynced_changes(library: &Library) -> Option<ActionableIssue> {
+    let subjects = match library.is_dirty {
+        true => vec![library.name.clone()],
+        false => Vec::new(),
+    };
+    issue(IssueKind::UnsyncedChanges, subjects, IssueAction::SyncMods)
+}
+
+/// Compares against the free space to keep, as an install of nothing.
+fn low_disk_space(library: &Library, limits: InstallSizeLimits) -> Option<ActionableIssue> {
+    let repo = &library.lib_paths.mods;
+    let available = install_size::available_space(repo);
+    let subjects: Vec<String> = install_size::check(repo, 0, available, limits)
+        .into_iter()
+        .filter(|warning| warning.kind == WarningKind::LowDiskSpace)
+        .map(|warning| warning.subject)
+        .collect();
+    issue(
+        IssueKind::LowDiskSpace,
+        subjects,
+        IssueAction::FreeDiskSpace,
+    )
+}
+
+fn corrupted_mods(library: &Library) -> Option<ActionableIssue> {
+    let subjects: Vec<String> = update_checker::report(library)
+        .into_iter()
+        .filter(|update| !update.corrupted_files.is_empty())
+        .map(|update| update.mod_id)
+        .collect();
+    issue(
+        IssueKind::CorruptedMods,
+        subjects,
+        IssueAction::InstallFromUrl,
+    )
+}
+
+fn updates_available(library: &Library) -> Option<ActionableIssue> {
+    let subjects: Vec<String> = update_checker::report(library)
+        .into_iter()
+        .filter(|update| update.is_newer)
+        .map(|update| update.mod_id)
+        .collect();
+    issue(
+        IssueKind::UpdatesAvailable,
+        subjects,
+        IssueAction::InstallFromUrl,
+    )
+}
+
+fn issue(kind: IssueKind, subjects: Vec<String>, action: IssueAction) -> Option<ActionableIssue> {
+    (!subjects.is_empty()).then_some(ActionableIssue {
+        kind,
+        subjects,
+        action,
+    })
+}
@@ -0,0 +1,165 @@
+use crate::config::global::GlobalConfig;
+use crate::core::library::Library;
+use crate::core::progress::Progress;
+use crate::core::{library_service, mod_manager, mod_stager, server_task, sync_hook};
+use crate::models::error::SError;
+use crate::models::warning::OperationWarning;
+use crate::utils::process::ProcessChecker;
+use camino::Utf8PathBuf;
+use sysinfo::System;
+
+pub const EXIT_OK: i32 = 0;
+/// The operation itself failed.
+pub const EXIT_FAILED: i32 = 1;
+/// The arguments could not be understood.
+pub const EXIT_USAGE: i32 = 2;
+/// The game or server of the library is running.
+pub const EXIT_GAME_RUNNING: i32 = 3;
+/// The server was not started since the library has unsynced changes.
+pub const EXIT_NOT_SYNCED: i32 = 4;
+/// Another process, usually the app, has the library open.
+pub const EXIT_LIBRARY_LOCKED: i32 = 5;
+
+/// Name given to loose files without a manifest, the frontend normally provides a translation.
+const UNKNOWN_MOD_NAME: &str = "Unknown mod";
+
+const USAGE: &str =
+    "Usage: mod_keeper [--portable] --sync <repo> | --serve <repo> | --add <archive>... [--repo <repo>]";
+
+/// Operation requested on the command line, run without opening the window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchTask {
+    Sync {
+        repo: Utf8PathBuf,
+    },
+    /// Runs the server of a synced library until it exits, see `server_task`.
+    Serve {
+        repo: Utf8PathBuf,
+    },
+    /// Without a repo, mods are added to the most recently used library.
+    Add {
+        repo: Option<Utf8PathBuf>,
+        archives: Vec<Utf8PathBuf>,
+    },
+}
+
+/// Reads the batch task from the process arguments (without the program name).
+/// Returns None when the first argument is not a batch flag, so the app starts normally.
+pub fn parse(args: &[String]) -> Option<Result<BatchTask, String>> {
+    let (flag, rest) = args.split_first()?;
+    match flag.as_str() {
+        "--sync" => Some(match rest {
+            [repo] => Ok(BatchTask::Sync { repo: repo.into() }),
+            _ => Err(USAGE.to_string()),
+        }),
+        "--serve" => Some(match rest {
+            [repo] => Ok(BatchTask::Serve { repo: repo.into() }),
+            _ => Err(USAGE.to_string()),
+        }),
+        "--add" => Some(parse_add(rest)),
+        _ => None,
+    }
+}
+
+fn parse_add(rest: &[String]) -> Result<BatchTask, String> {
+    let (archives, repo) = match rest.iter().position(|arg| arg == "--repo") {
+        Some(i) => match &rest[i + 1..] {
+            [repo] => (&rest[..i], Some(Utf8PathBuf::from(repo))),
+            _ => return Err(USAGE.to_string()),
+        },
+        None => (rest, None),
+    };
+    if archives.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    Ok(BatchTask::Add {
+        repo,
+        archives: archives.iter().map(Utf8PathBuf::from).collect(),
+    })
+}
+
+/// Runs a task to completion and returns the process exit code.
+/// Results go to stdout and failures to stderr, for scheduled tasks and scripts.
+pub fn run(task: BatchTask) -> i32 {
+    match execute(task) {
+        Ok(warnings) => {
+            warnings.iter().for_each(|w| {
+                println!(
+                    "warning: {:?} {} {}",
+                    w.kind,
+                    w.subject,
+                    w.details.join(", ")
+                )
+            });
+            EXIT_OK
+        }
+        Err(SError::GameOrServerRunning) => {
+            eprintln!("{}", SError::GameOrServerRunning);
+            EXIT_GAME_RUNNING
+        }
+        Err(SError::LibraryNotSynced) => {
+            eprintln!("{}", SError::LibraryNotSynced);
+            EXIT_NOT_SYNCED
+        }
+        Err(e @ SError::LibraryLocked(_)) => {
+            eprintln!("{e}");
+            EXIT_LIBRARY_LOCKED
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            EXIT_FAILED
+        }
+    }
+}
+
+fn execute(task: BatchTask) -> Result<Vec<OperationWarning>, SError> {
+    let config = GlobalConfig::load();
+
+    match task {
+        BatchTask::Sync { repo } => {
+            let mut library = load_idle(&repo)?;
+            let game_root = library.game_root.clone();
+            let warnings =
+                sync_hook::around(&config.sync_hooks, &game_root, Progress::silent(), || {
+                    library_service::sync(
+                        &mut library,
+                        config.checklist_policy,
+                        config.link_failure_policy,
+                    )
+                })?;
+            println!("Synced {}", library.name);
+            Ok(warnings)
+        }
+        BatchTask::Serve { repo } => {
+            let library = load_idle(&repo)?;
+            server_task::serve(&library)?;
+            Ok(Vec::new())
+        }
+        BatchTask::Add { repo, archives } => {
+            let repo = repo
+                .or_else(|| config.known_libraries.first().cloned())
+                .ok_or(SError::NoActiveLibrary)?;
+            let mut library = load_idle(&repo)?;
+            let material =
+                library.stage_material(UNKNOWN_MOD_NAME.to_string(), config.framework_policy);
+
+            let staged_mods = mod_stager::resolve(&archives, &material, Progress::silent())?;
+            let warnings = mod_manager::add_staged(&mut library, staged_mods, Progress::silent())?;
+            println!("Added {} input(s) to {}", archives.len(), library.name);
+            Ok(warnings)
+        }
+    }
+}
+
+/// Loads and locks a library, refusing to touch it while the app has it open or its game or
+/// server is running.
+fn load_idle(repo: &Utf8PathBuf) -> Result<Library, SError> {
+    let mut library = Library::load(repo)?;
+    library.lock()?;
+    if ProcessChecker::is_running(&mut System::new(), &library.spt_canonical_paths()) {
+        return Err(SError::GameOrServerRunning);
+    }
+    library_service::recover_interrupted(&library);
+    Ok(library)
+}
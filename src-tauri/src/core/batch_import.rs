@@ -0,0 +1,158 @@
+use crate::core::library::Library;
+use crate::core::mod_manager::{self, AddOutcome};
+use crate::core::mod_stager::{self, StageMaterial, StagedMod};
+use crate::core::progress::Progress;
+use crate::core::repo_history;
+use crate::models::batch_import::{BatchImportReport, ImportEntry, ImportOutcome};
+use crate::models::error::SError;
+use crate::models::warning::OperationWarning;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::collections::HashMap;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// An archive and the mods staged from it.
+pub type StagedArchive = (Utf8PathBuf, Result<Vec<StagedMod>, SError>);
+
+/// Archives directly inside `dir`, oldest first so the newest download of a mod wins.
+pub fn find_archives(dir: &Utf8Path) -> Result<Vec<Utf8PathBuf>, SError> {
+    let mut archives: Vec<(SystemTime, Utf8PathBuf)> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| Utf8PathBuf::from_path_buf(entry.path()).ok())
+        .filter(|path| path.is_file() && mod_stager::is_archive(path))
+        .map(|path| (modified(&path), path))
+        .collect();
+    archives.sort();
+    Ok(archives.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Stages every archive on its own, so a broken download only fails its own entry.
+pub fn stage(
+    archives: &[Utf8PathBuf],
+    material: &StageMaterial,
+    progress: Progress,
+) -> Vec<StagedArchive> {
+    archives
+        .iter()
+        .enumerate()
+        .map(|(index, archive)| {
+            progress.staging(archive, index, archives.len());
+            let staged =
+                mod_stager::resolve(std::slice::from_ref(archive), material, Progress::silent());
+            (archive.clone(), staged)
+        })
+        .collect()
+}
+
+/// Installs the staged mods one after another. A mod provided again by a later archive is
+/// superseded by it, one already in the library with identical content is skipped, and a
+/// failure is reported without stopping the others. Staging copies are removed either way.
+pub fn install(
+    library: &mut Library,
+    staged: Vec<StagedArchive>,
+    progress: Progress,
+) -> BatchImportReport {
+    // Index of the last archive providing each mod, which is the one installed
+    let newest: HashMap<String, usize> = staged
+        .iter()
+        .enumerate()
+        .flat_map(|(index, (_, result))| {
+            result
+                .iter()
+                .flatten()
+                .map(move |m| (m.fs.id.clone(), index))
+        })
+        .collect();
+    let total = staged.len();
+    let items = staged
+        .into_iter()
+        .enumerate()
+        .flat_map(|(index, (input, result))| {
+            let items: Vec<Result<StagedMod, SError>> = match result {
+                Ok(mods) => mods.into_iter().map(Ok).collect(),
+                Err(e) => vec![Err(e)],
+            };
+            items
+                .into_iter()
+                .map(move |item| (index, input.clone(), item))
+        });
+
+    let mut report = BatchImportReport::default();
+    for (index, input, item) in items {
+        progress.copying(input.file_name().unwrap_or(input.as_str()), index, total);
+        let is_newest = item
+            .as_ref()
+            .is_ok_and(|staged| newest.get(&staged.fs.id) == Some(&index));
+        let (mod_name, outcome) =
+            import_one(library, item, is_newest, &mut report.warnings, progress);
+        report.entries.push(ImportEntry {
+            input,
+            mod_name,
+            outcome,
+        });
+    }
+
+    let imported = report
+        .entries
+        .iter()
+        .filter(|e| matches!(e.outcome, ImportOutcome::Installed | ImportOutcome::Updated))
+        .count();
+    if imported > 0 {
+        repo_history::record(&library.repo_root, &format!("Import {imported} mod(s)"));
+    }
+    report
+}
+
+fn import_one(
+    library: &mut Library,
+    item: Result<StagedMod, SError>,
+    is_newest: bool,
+    warnings: &mut Vec<OperationWarning>,
+    progress: Progress,
+) -> (Option<String>, ImportOutcome) {
+    let staged = match item {
+        Ok(staged) => staged,
+        Err(error) => return (None, ImportOutcome::Failed { error }),
+    };
+    let name = staged.name.clone();
+    let (is_staging, source_path) = (staged.is_staging, staged.source_path.clone());
+
+    let outcome = match is_newest {
+        false => ImportOutcome::Superseded,
+        true => install_staged(library, staged, warnings, progress),
+    };
+
+    if let Err(e) = mod_stager::clean_up(is_staging, &source_path) {
+        warn!("Failed to remove the staging copy {source_path}: {e}");
+    }
+    (Some(name), outcome)
+}
+
+fn install_staged(
+    library: &mut Library,
+    mut staged: StagedMod,
+    warnings: &mut Vec<OperationWarning>,
+    progress: Progress,
+) -> ImportOutcome {
+    let mut staged_warnings = std::mem::take(&mut staged.warnings);
+    let result = mod_manager::add_mod_reported(library, staged, progress);
+    if result.is_ok() {
+        warnings.append(&mut staged_warnings);
+    }
+    result.map_or_else(|error| ImportOutcome::Failed { error }, outcome_of)
+}
+
+fn outcome_of(outcome: AddOutcome) -> ImportOutcome {
+    match outcome {
+        AddOutcome::Installed | AddOutcome::Migrated => ImportOutcome::Installed,
+        AddOutcome::Updated => ImportOutcome::Updated,
+        AddOutcome::Unchanged => ImportOutcome::AlreadyInstalled,
+    }
+}
+
+fn modified(path: &Utf8Path) -> SystemTime {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .unwrap_or(UNIX_EPOCH)
+}
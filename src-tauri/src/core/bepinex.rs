@@ -0,0 +1,146 @@
+use crate::core::decompression;
+use crate::core::dedicated_server;
+use crate::core::library::Library;
+use crate::core::progress::Progress;
+use crate::core::version;
+use crate::models::bepinex::{BepInExState, BepInExStatus};
+use crate::models::deployment_plan::SyncScope;
+use crate::models::error::SError;
+use crate::models::warning::{OperationWarning, WarningKind};
+use crate::utils::file::FileUtils;
+use crate::utils::{http, scan};
+use camino::Utf8Path;
+use semver::{Version, VersionReq};
+use std::fs;
+use std::time::Duration;
+use uuid::Uuid;
+
+const FRAMEWORK_DIR: &str = "BepInEx";
+/// Core assembly of BepInEx 5, loaded by the doorstop proxy.
+const CORE_ASSEMBLY: &str = "BepInEx/core/BepInEx.dll";
+/// Doorstop proxy next to the game executable that starts BepInEx.
+const DOORSTOP_PROXY: &str = "winhttp.dll";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A BepInEx build shipped for a range of SPT versions.
+pub struct Release {
+    pub spt: &'static str,
+    pub version: &'static str,
+    pub url: &'static str,
+}
+
+/// Builds the SPT installer ships, newest SPT first.
+const RELEASES: &[Release] = &[Release {
+    spt: "^4",
+    version: "5.4.23.2",
+    url: "https://github.com/BepInEx/BepInEx/releases/download/v5.4.23.2/BepInEx_win_x64_5.4.23.2.zip",
+}];
+
+/// Whether BepInEx is set up in the game root. Client mods only load once it is.
+pub fn state(game_root: &Utf8Path) -> BepInExState {
+    if !game_root.join(FRAMEWORK_DIR).is_dir() {
+        return BepInExState::Missing;
+    }
+    match game_root.join(CORE_ASSEMBLY).is_file() && game_root.join(DOORSTOP_PROXY).is_file() {
+        true => BepInExState::Installed,
+        false => BepInExState::Incomplete,
+    }
+}
+
+/// State of the game root with the build to install for its SPT version.
+pub fn status(game_root: &Utf8Path) -> BepInExStatus {
+    let recommended = version::detect(game_root)
+        .and_then(|spt| release_for(&spt))
+        .ok()
+        .map(|release| release.version.to_string());
+    BepInExStatus {
+        state: state(game_root),
+        recommended,
+    }
+}
+
+/// The build the SPT installer ships for `spt_version`.
+pub fn release_for(spt_version: &str) -> Result<&'static Release, SError> {
+    let version = Version::parse(spt_version)?;
+    RELEASES
+        .iter()
+        .find(|release| VersionReq::parse(release.spt).is_ok_and(|req| req.matches(&version)))
+        .ok_or_else(|| SError::UnsupportedSPTVersion(spt_version.to_string()))
+}
+
+/// Warns that client mods will not load when BepInEx is not set up in the game root.
+pub fn warnings(game_root: &Utf8Path) -> Vec<OperationWarning> {
+    match state(game_root) {
+        BepInExState::Installed => Vec::new(),
+        _ => vec![OperationWarning::new(
+            WarningKind::BepInExMissing,
+            game_root.as_str(),
+        )],
+    }
+}
+
+/// Warns when the sync deployed plugins into a game root without BepInEx.
+pub fn sync_warnings(library: &Library, scope: SyncScope) -> Vec<OperationWarning> {
+    let plugins = &library.spt_rules.client_plugins;
+    let deploys_plugins = scope != SyncScope::ServerOnly
+        && dedicated_server::deployable_mods(library)
+            .values()
+            .filter(|m| m.is_active)
+            .filter_map(|m| library.cache.mods.get(&m.id))
+            .any(|fs| fs.files.iter().any(|rel| rel.starts_with(plugins)));
+    match deploys_plugins {
+        true => warnings(&library.game_root),
+        false => Vec::new(),
+    }
+}
+
+pub async fn download(release: &Release, archive: &Utf8Path) -> Result<(), SError> {
+    let bytes = http::client(DOWNLOAD_TIMEOUT)?
+        .get(release.url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    fs::write(archive, bytes)?;
+    Ok(())
+}
+
+/// Unpacks a BepInEx archive into the game root through a scratch folder in `staging`.
+/// Only missing files are added, so an incomplete install keeps its configs and the
+/// links deployed into it.
+pub fn install(
+    game_root: &Utf8Path,
+    archive: &Utf8Path,
+    staging: &Utf8Path,
+) -> Result<BepInExState, SError> {
+    let scratch = staging.join(format!("bepinex-{}", Uuid::new_v4()));
+    let result = decompression::extract(archive, &scratch, Progress::silent()).and_then(|_| {
+        match scratch.join(CORE_ASSEMBLY).is_file() {
+            true => add_missing(&scratch, game_root),
+            false => Err(SError::CorruptArchive(archive.to_string())),
+        }
+    });
+    if scratch.exists() {
+        FileUtils::remove_recursive(&scratch)?;
+    }
+    result.map(|_| state(game_root))
+}
+
+fn add_missing(src: &Utf8Path, game_root: &Utf8Path) -> Result<(), SError> {
+    scan::walk(src)
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| Utf8Path::from_path(e.path()).map(Utf8Path::to_path_buf))
+        .try_for_each(|path| {
+            let dst = game_root.join(path.strip_prefix(src)?);
+            if dst.exists() || dst.is_symlink() {
+                return Ok(());
+            }
+            if let Some(parent) = dst.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&path, &dst)?;
+            Ok(())
+        })
+}
@@ -0,0 +1,110 @@
+use crate::core::mod_fs::ModFS;
+use crate::core::mod_stager::{StageMaterial, StagedMod};
+use crate::models::error::SError;
+use crate::models::global::FrameworkPolicy;
+use crate::models::warning::{OperationWarning, WarningKind};
+use crate::utils::file::FileUtils;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::fs;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Folder holding the BepInEx runtime itself.
+const FRAMEWORK_CORE: &str = "BepInEx/core";
+/// Doorstop loader entry point, only ever shipped with the framework.
+const FRAMEWORK_LOADER: &str = "winhttp.dll";
+/// Files installed next to the runtime that would overwrite the game's own copies.
+const FRAMEWORK_FILES: [&str; 5] = [
+    FRAMEWORK_LOADER,
+    "doorstop_config.ini",
+    ".doorstop_version",
+    "changelog.txt",
+    "BepInEx/config/BepInEx.cfg",
+];
+
+/// Lists the BepInEx framework files bundled with a mod.
+/// Only reports anything when the runtime or its loader is present, so plain plugins
+/// shipping a `changelog.txt` are left alone.
+pub fn detect(files: &[Utf8PathBuf]) -> Vec<Utf8PathBuf> {
+    let bundled: Vec<Utf8PathBuf> = files
+        .iter()
+        .filter(|f| {
+            f.starts_with(FRAMEWORK_CORE)
+                || FRAMEWORK_FILES
+                    .iter()
+                    .any(|name| f.as_path() == Utf8Path::new(name))
+        })
+        .cloned()
+        .collect();
+
+    let has_runtime = bundled
+        .iter()
+        .any(|f| f.starts_with(FRAMEWORK_CORE) || f.as_path() == Utf8Path::new(FRAMEWORK_LOADER));
+
+    if has_runtime {
+        bundled
+    } else {
+        Vec::new()
+    }
+}
+
+/// Removes bundled framework files from a staged mod so only the plugin payload is installed.
+/// Files are deleted or moved to `quarantine/{mod_id}` depending on the policy.
+/// Sources outside of staging are copied first; the user's folder is never modified.
+pub fn strip(staged: StagedMod, material: &StageMaterial) -> Result<StagedMod, SError> {
+    let bundled = detect(&staged.fs.files);
+    if bundled.is_empty() {
+        return Ok(staged);
+    }
+
+    let staged = into_staging(staged, &material.root)?;
+    let quarantine = material.quarantine.join(&staged.fs.id);
+
+    for file in &bundled {
+        let src = staged.source_path.join(file);
+        match material.framework_policy {
+            FrameworkPolicy::Strip => fs::remove_file(&src)?,
+            FrameworkPolicy::Quarantine => {
+                let dst = quarantine.join(file);
+                if let Some(parent) = dst.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(&src, &dst)?;
+            }
+        }
+    }
+
+    warn!(
+        "Mod {} bundles {} BepInEx framework file(s); applied {:?}",
+        staged.name,
+        bundled.len(),
+        material.framework_policy
+    );
+
+    let mut warnings = staged.warnings;
+    warnings.push(
+        OperationWarning::new(WarningKind::BundledFramework, &staged.name).with_details(&bundled),
+    );
+
+    Ok(StagedMod {
+        fs: ModFS::new(&staged.source_path, &material.rules)?,
+        framework_files: bundled,
+        warnings,
+        ..staged
+    })
+}
+
+fn into_staging(staged: StagedMod, staging_root: &Utf8Path) -> Result<StagedMod, SError> {
+    if staged.is_staging {
+        return Ok(staged);
+    }
+
+    let dest_dir = staging_root.join(Uuid::new_v4().to_string());
+    FileUtils::copy_recursive(&staged.source_path, &dest_dir)?;
+
+    Ok(StagedMod {
+        source_path: dest_dir,
+        is_staging: true,
+        ..staged
+    })
+}
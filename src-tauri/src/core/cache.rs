@@ -1,15 +1,27 @@
 use crate::core::mod_fs::ModFS;
+use crate::core::update_checker::CachedUpdate;
 use crate::models::error::SError;
 use crate::models::mod_dto::ModManifest;
 use crate::models::paths::{ModPaths, SPTPathRules};
 use camino::{Utf8Path, Utf8PathBuf};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct LibraryCache {
     pub mods: BTreeMap<String, ModFS>,
     pub manifests: BTreeMap<String, ModManifest>,
+    /// Last update check of each mod, see `update_checker`.
+    #[serde(default)]
+    pub updates: BTreeMap<String, CachedUpdate>,
+    /// Digest of each entry as last read from or written to the cache store,
+    /// so persisting only rewrites the mods that changed.
+    #[serde(skip)]
+    pub(crate) stored: RefCell<BTreeMap<String, blake3::Hash>>,
+    /// Files of each mod edited in the repo, found when the library was loaded, see `local_edits`.
+    #[serde(skip)]
+    pub local_edits: BTreeMap<String, Vec<Utf8PathBuf>>,
 }
 
 impl LibraryCache {
@@ -31,7 +43,11 @@ impl LibraryCache {
         Ok(cache)
     }
 
-    pub fn add(&mut self, root: &Utf8Path, fs: ModFS) {
+    /// Adds or replaces the entry of a mod. Its files are stamped later by `idle_hasher`,
+    /// so installs do not wait for them to be hashed.
+    pub fn add(&mut self, root: &Utf8Path, mut fs: ModFS) {
+        fs.stamps.clear();
+        self.local_edits.remove(&fs.id);
         if let Ok(m) = ModFS::read_manifest(&ModPaths::new(root).file) {
             self.manifests.insert(fs.id.clone(), m);
         }
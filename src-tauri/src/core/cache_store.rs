@@ -0,0 +1,190 @@
+use crate::core::cache::LibraryCache;
+use crate::core::metrics;
+use crate::core::mod_fs::ModFS;
+use crate::core::update_checker::CachedUpdate;
+use crate::models::error::SError;
+use crate::models::metrics::Operation;
+use crate::models::mod_dto::ModManifest;
+use crate::models::paths::LibPathRules;
+use crate::utils::file::FileUtils;
+use crate::utils::id::hash_id;
+use crate::utils::msgpack::MsgPack;
+use crate::utils::toml::Toml;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use tracing::info;
+
+const ENTRY_EXTENSION: &str = "msgpack";
+
+#[derive(Serialize)]
+struct EntryRef<'a> {
+    id: &'a str,
+    fs: &'a ModFS,
+    manifest: Option<&'a ModManifest>,
+    update: Option<&'a CachedUpdate>,
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    id: String,
+    fs: ModFS,
+    manifest: Option<ModManifest>,
+    #[serde(default)]
+    update: Option<CachedUpdate>,
+}
+
+/// Reads the cache store, with one binary entry per mod.
+/// A legacy `cache.toml` is migrated transparently on first read.
+pub fn read(lib_paths: &LibPathRules) -> Result<LibraryCache, SError> {
+    let replaced = sibling(lib_paths, "replaced");
+    if !lib_paths.cache_store.exists() && replaced.exists() {
+        // `replace` stopped between its two renames, the old store is still whole
+        fs::rename(&replaced, &lib_paths.cache_store)?;
+    }
+    if !lib_paths.cache_store.exists() {
+        return migrate(lib_paths);
+    }
+
+    fs::read_dir(&lib_paths.cache_store)?
+        .flatten()
+        .filter_map(|entry| Utf8PathBuf::from_path_buf(entry.path()).ok())
+        .filter(|path| path.extension() == Some(ENTRY_EXTENSION))
+        .try_fold(LibraryCache::default(), |mut cache, path| {
+            let bytes = fs::read(&path)?;
+            let entry: Entry = MsgPack::from_slice(&bytes)?;
+
+            cache
+                .stored
+                .get_mut()
+                .insert(entry.id.clone(), blake3::hash(&bytes));
+            if let Some(update) = entry.update {
+                cache.updates.insert(entry.id.clone(), update);
+            }
+            if let Some(manifest) = entry.manifest {
+                cache.manifests.insert(entry.id.clone(), manifest);
+            }
+            cache.mods.insert(entry.id, entry.fs);
+            Ok(cache)
+        })
+}
+
+/// Writes entries whose content changed since the last read/write and removes stale ones.
+pub fn write(lib_paths: &LibPathRules, cache: &LibraryCache) -> Result<(), SError> {
+    let _timer = metrics::Timer::start(Operation::PersistCache);
+    fs::create_dir_all(&lib_paths.cache_store)?;
+    let mut stored = cache.stored.borrow_mut();
+
+    for (id, mod_fs) in &cache.mods {
+        let bytes = MsgPack::to_vec(&EntryRef {
+            id,
+            fs: mod_fs,
+            manifest: cache.manifests.get(id),
+            update: cache.updates.get(id),
+        })?;
+        let digest = blake3::hash(&bytes);
+        if stored.get(id) == Some(&digest) {
+            continue;
+        }
+
+        FileUtils::write_atomic(&entry_path(lib_paths, id), &bytes)?;
+        stored.insert(id.clone(), digest);
+    }
+
+    let stale: Vec<String> = stored
+        .keys()
+        .filter(|id| !cache.mods.contains_key(*id))
+        .cloned()
+        .collect();
+    for id in stale {
+        remove_if_exists(&entry_path(lib_paths, &id))?;
+        stored.remove(&id);
+    }
+
+    Ok(())
+}
+
+/// Replaces the whole store with `cache`. The new store is written beside the old one and
+/// swapped in, so the store on disk is always complete.
+pub fn replace(lib_paths: &LibPathRules, cache: &LibraryCache) -> Result<(), SError> {
+    let building = sibling(lib_paths, "building");
+    let replaced = sibling(lib_paths, "replaced");
+    for leftover in [&building, &replaced] {
+        if leftover.exists() {
+            FileUtils::remove_recursive(leftover)?;
+        }
+    }
+
+    cache.stored.borrow_mut().clear();
+    write(
+        &LibPathRules {
+            cache_store: building.clone(),
+            ..lib_paths.clone()
+        },
+        cache,
+    )?;
+
+    if lib_paths.cache_store.exists() {
+        fs::rename(&lib_paths.cache_store, &replaced)?;
+    }
+    if let Err(e) = fs::rename(&building, &lib_paths.cache_store) {
+        if replaced.exists() {
+            fs::rename(&replaced, &lib_paths.cache_store)?;
+        }
+        return Err(e.into());
+    }
+    if replaced.exists() {
+        FileUtils::remove_recursive(&replaced)?;
+    }
+    Ok(())
+}
+
+/// Dumps the cache as TOML for debugging, to `cache_export` unless told otherwise.
+pub fn export_toml(
+    lib_paths: &LibPathRules,
+    cache: &LibraryCache,
+    dest: Option<&Utf8Path>,
+) -> Result<Utf8PathBuf, SError> {
+    let dest = dest.map_or_else(|| lib_paths.cache_export.clone(), Utf8Path::to_path_buf);
+    Toml::write(&dest, cache)?;
+    Ok(dest)
+}
+
+/// Moves a legacy `cache.toml` into the store. The TOML is only removed once the store is in
+/// place, so an interrupted migration runs again on the next read.
+fn migrate(lib_paths: &LibPathRules) -> Result<LibraryCache, SError> {
+    let cache: LibraryCache = Toml::read(&lib_paths.cache)?;
+    replace(lib_paths, &cache)?;
+    fs::remove_file(&lib_paths.cache)?;
+
+    info!(
+        "Migrated {} cache entries from {} to {}",
+        cache.mods.len(),
+        lib_paths.cache,
+        lib_paths.cache_store
+    );
+    Ok(cache)
+}
+
+/// Mod ids come from manifests, so they are hashed into safe file names.
+fn entry_path(lib_paths: &LibPathRules, id: &str) -> Utf8PathBuf {
+    lib_paths
+        .cache_store
+        .join(format!("{}.{}", hash_id(id), ENTRY_EXTENSION))
+}
+
+/// A folder next to the store, for the stages of `replace`.
+fn sibling(lib_paths: &LibPathRules, stage: &str) -> Utf8PathBuf {
+    let name = lib_paths.cache_store.file_name().unwrap_or("cache");
+    lib_paths
+        .cache_store
+        .with_file_name(format!("{name}.{stage}"))
+}
+
+fn remove_if_exists(path: &Utf8Path) -> Result<(), SError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
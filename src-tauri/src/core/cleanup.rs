@@ -1,15 +1,23 @@
 use crate::core::cache::LibraryCache;
+use crate::core::deploy_journal::{self, Action};
+use crate::core::deploy_ledger;
 use crate::core::deployment;
 use crate::core::linker;
+use crate::core::metrics;
+use crate::core::repo_store;
+use crate::models::deployment_plan::SyncScope;
 use crate::models::error::SError;
+use crate::models::metrics::Operation;
 use crate::models::paths::{LibPathRules, SPTPathRules};
+use crate::utils::scan;
 use camino::{Utf8Path, Utf8PathBuf};
 use file_id::FileId;
-use std::collections::HashSet;
-use walkdir::WalkDir;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use tracing::warn;
 
 /// Entry point for the cleanup logic.
 /// Scans the game directory and removes managed files, links, or empty folders.
+/// Copies are removed from the deploy ledger, see `deploy_ledger::remove_copies`.
 pub fn purge(
     game_root: &Utf8Path,
     repo_root: &Utf8Path,
@@ -17,13 +25,39 @@ pub fn purge(
     lib_paths: &LibPathRules,
     cache: &LibraryCache,
 ) -> Result<(), SError> {
+    purge_scoped(
+        game_root,
+        repo_root,
+        spt_rules,
+        lib_paths,
+        cache,
+        SyncScope::All,
+    )
+}
+
+/// Like `purge`, limited to the mod roots within `scope`.
+/// External files are recorded as client side and only purged outside of `ServerOnly`.
+pub fn purge_scoped(
+    game_root: &Utf8Path,
+    repo_root: &Utf8Path,
+    spt_rules: &SPTPathRules,
+    lib_paths: &LibPathRules,
+    cache: &LibraryCache,
+    scope: SyncScope,
+) -> Result<(), SError> {
+    let _timer = metrics::Timer::start(Operation::Purge);
     let managed_scope = build_managed_scope(cache);
     let managed_ids = build_managed_ids(lib_paths, cache);
+    purge_copies(game_root, spt_rules, lib_paths, scope)?;
 
-    let roots = deployment::get_protected_paths_absolute(game_root, spt_rules);
+    let roots: Vec<Utf8PathBuf> = deployment::get_protected_paths(spt_rules)
+        .into_iter()
+        .filter(|rel| scope.covers(rel, spt_rules))
+        .map(|rel| game_root.join(rel))
+        .collect();
 
     for root in roots.iter().filter(|r| r.exists()) {
-        let mut it = WalkDir::new(root).contents_first(false).into_iter();
+        let mut it = scan::walk(root);
 
         while let Some(entry) = it.next() {
             let entry = entry.map_err(|e| SError::IOError(e.to_string()))?;
@@ -37,6 +71,7 @@ pub fn purge(
             if process_entry(
                 path,
                 game_root,
+                lib_paths,
                 repo_root,
                 &managed_scope,
                 &managed_ids,
@@ -46,7 +81,134 @@ pub fn purge(
             }
         }
     }
-    Ok(())
+
+    if scope == SyncScope::ServerOnly {
+        return Ok(());
+    }
+    purge_external(game_root, repo_root, lib_paths, &managed_ids)
+}
+
+/// Managed links and copies `purge_scoped` would remove, relative to the game root, without touching
+/// anything. The folders it would remove once they are empty are not listed.
+pub fn plan_purge(
+    game_root: &Utf8Path,
+    repo_root: &Utf8Path,
+    spt_rules: &SPTPathRules,
+    lib_paths: &LibPathRules,
+    cache: &LibraryCache,
+    scope: SyncScope,
+) -> Result<BTreeSet<Utf8PathBuf>, SError> {
+    let managed_ids = build_managed_ids(lib_paths, cache);
+    let is_managed = |path: &Utf8Path| is_managed_link(path, repo_root, &managed_ids);
+
+    let mut links = BTreeSet::new();
+    let roots = deployment::get_protected_paths(spt_rules)
+        .into_iter()
+        .filter(|rel| scope.covers(rel, spt_rules))
+        .map(|rel| game_root.join(rel));
+    for root in roots.filter(|r| r.exists()) {
+        // Links are yielded as leaves, so nothing below a managed one is visited
+        for entry in scan::walk(&root) {
+            let entry = entry.map_err(|e| SError::IOError(e.to_string()))?;
+            let path = Utf8Path::from_path(entry.path()).ok_or(SError::Unexpected)?;
+            if path != root && is_managed(path) {
+                links.insert(path.strip_prefix(game_root)?.to_path_buf());
+            }
+        }
+    }
+
+    let ledger = deploy_ledger::read(lib_paths)?;
+    links.extend(
+        ledger
+            .copies
+            .iter()
+            .filter(|(rel, copy)| deploy_ledger::is_unchanged(&game_root.join(rel), copy))
+            .map(|(rel, _)| rel.clone())
+            .filter(|rel| scope.covers(rel, spt_rules)),
+    );
+    if scope == SyncScope::ServerOnly {
+        return Ok(links);
+    }
+    let recorded = ledger.links.into_keys();
+    links.extend(recorded.filter(|rel| is_managed(&game_root.join(rel))));
+    Ok(links)
+}
+
+/// Removes the copies within `scope` before the scan, which then drops the folders they
+/// leave empty. Copies outside of the mod roots are client side, like other external files.
+fn purge_copies(
+    game_root: &Utf8Path,
+    spt_rules: &SPTPathRules,
+    lib_paths: &LibPathRules,
+    scope: SyncScope,
+) -> Result<(), SError> {
+    let mut ledger = deploy_ledger::read(lib_paths)?;
+    if ledger.copies.is_empty() {
+        return Ok(());
+    }
+    deploy_ledger::remove_copies(game_root, spt_rules, &mut ledger, |rel, _| {
+        scope.covers(rel, spt_rules)
+    });
+    deploy_ledger::write(lib_paths, &ledger)
+}
+
+/// Removes what deployment recorded outside of the mod roots, which the scan above never visits.
+/// Leftovers are kept in the ledger and reported, so the game root can be verified as restored.
+fn purge_external(
+    game_root: &Utf8Path,
+    repo_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    managed_ids: &HashMap<FileId, Utf8PathBuf>,
+) -> Result<(), SError> {
+    let ledger = deploy_ledger::read(lib_paths)?;
+    if ledger.is_empty() {
+        return Ok(());
+    }
+
+    ledger
+        .links
+        .keys()
+        .map(|rel| game_root.join(rel))
+        .filter_map(|path| managed_source(&path, repo_root, managed_ids).map(|s| (path, s)))
+        .try_for_each(|(path, source)| {
+            deploy_journal::record(lib_paths, Action::Unlink { path, source })
+        })?;
+    let remaining = deploy_ledger::remove_recorded(game_root, ledger, |path| {
+        is_managed_link(path, repo_root, managed_ids)
+    })?;
+    if !remaining.is_empty() {
+        warn!(
+            "Could not restore the game root: {} link(s) and {} folder(s) remain",
+            remaining.links.len(),
+            remaining.created_dirs.len()
+        );
+    }
+    deploy_ledger::write(lib_paths, &remaining)
+}
+
+/// Whether the path links into the repo, as a junction or symlink, or as a hardlink of a
+/// repo file.
+fn is_managed_link(
+    path: &Utf8Path,
+    repo_root: &Utf8Path,
+    managed_ids: &HashMap<FileId, Utf8PathBuf>,
+) -> bool {
+    managed_source(path, repo_root, managed_ids).is_some()
+}
+
+/// The repo path a managed link points to, see `is_managed_link`.
+fn managed_source(
+    path: &Utf8Path,
+    repo_root: &Utf8Path,
+    managed_ids: &HashMap<FileId, Utf8PathBuf>,
+) -> Option<Utf8PathBuf> {
+    linker::read_link_target(path)
+        .ok()
+        .filter(|target| target.starts_with(repo_root))
+        .or_else(|| {
+            let id = linker::get_id(path).ok()?;
+            managed_ids.get(&id).cloned()
+        })
 }
 
 /// Processes a single filesystem entry to determine if it should be unlinked or removed.
@@ -54,9 +216,10 @@ pub fn purge(
 fn process_entry(
     path: &Utf8Path,
     game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
     repo_root: &Utf8Path,
     managed_scope: &HashSet<Utf8PathBuf>,
-    managed_ids: &HashSet<FileId>,
+    managed_ids: &HashMap<FileId, Utf8PathBuf>,
     entry: &walkdir::DirEntry,
 ) -> Result<bool, SError> {
     let meta = entry.path().symlink_metadata()?;
@@ -68,7 +231,7 @@ fn process_entry(
         };
 
         if target.starts_with(repo_root) {
-            linker::unlink(path)?;
+            unlink_journaled(lib_paths, path, target)?;
             return Ok(true);
         }
     }
@@ -79,8 +242,8 @@ fn process_entry(
             return Ok(false);
         };
 
-        if managed_ids.contains(&id) {
-            linker::unlink(path)?;
+        if let Some(source) = managed_ids.get(&id) {
+            unlink_journaled(lib_paths, path, source.clone())?;
         }
         return Ok(false);
     }
@@ -112,19 +275,38 @@ fn build_managed_scope(cache: &LibraryCache) -> HashSet<Utf8PathBuf> {
         .collect()
 }
 
-fn build_managed_ids(lib_paths: &LibPathRules, cache: &LibraryCache) -> HashSet<FileId> {
+/// Physical file id of every repo file -> its path, to recognise hardlinks.
+fn build_managed_ids(
+    lib_paths: &LibPathRules,
+    cache: &LibraryCache,
+) -> HashMap<FileId, Utf8PathBuf> {
+    let store = repo_store::open(lib_paths);
     cache
         .mods
         .iter()
         .flat_map(|(id, fs)| {
-            fs.files
-                .iter()
-                .map(move |f| lib_paths.mods.join(id).join(f))
+            let root = store.payload_dir(id);
+            fs.files.iter().map(move |f| root.join(f))
         })
-        .filter_map(|p| linker::get_id(&p).ok())
+        .filter_map(|p| linker::get_id(&p).ok().map(|id| (id, p)))
         .collect()
 }
 
+/// Unlinks a managed link, journaled so a failing sync can put it back, see `deploy_journal`.
+fn unlink_journaled(
+    lib_paths: &LibPathRules,
+    path: &Utf8Path,
+    source: Utf8PathBuf,
+) -> Result<(), SError> {
+    let action = Action::Unlink {
+        path: path.to_path_buf(),
+        source,
+    };
+    deploy_journal::record(lib_paths, action)?;
+    linker::unlink(path)?;
+    Ok(())
+}
+
 fn is_dir_empty(path: &Utf8Path) -> bool {
     std::fs::read_dir(path)
         .map(|mut i| i.next().is_none())
@@ -144,7 +326,7 @@ pub fn unlink_mod(
     spt_rules: &SPTPathRules,
 ) -> Result<Vec<Utf8PathBuf>, SError> {
     let mut unlinked = Vec::new();
-    let mod_source_dir = lib_paths.mods.join(mod_id);
+    let mod_source_dir = repo_store::open(lib_paths).payload_dir(mod_id);
     let protected_paths = deployment::get_protected_paths_absolute(game_root, spt_rules);
 
     // Get the mod's file IDs for hard link matching
@@ -185,6 +367,14 @@ pub fn unlink_mod(
         }
     }
 
+    // Copies are matched by the deploy ledger rather than by file id
+    let mut ledger = deploy_ledger::read(lib_paths)?;
+    let copies = deploy_ledger::remove_copies(game_root, spt_rules, &mut ledger, |_, copy| {
+        copy.mod_id == mod_id
+    });
+    deploy_ledger::write(lib_paths, &ledger)?;
+    unlinked.extend(copies.into_iter().map(|rel| game_root.join(rel)));
+
     // Clean up empty shared directories (walk from deepest to shallowest)
     let mut sorted_shared_dirs: Vec<_> = shared_dirs.iter().collect();
     sorted_shared_dirs.sort_by(|a, b| b.components().count().cmp(&a.components().count()));
@@ -0,0 +1,85 @@
+use crate::core::library::Library;
+use crate::models::compat_note::{CompatNote, CompatNotesFile};
+use crate::models::error::SError;
+use crate::utils::toml::Toml;
+use camino::Utf8PathBuf;
+
+/// Adds a note, replacing the one already stored for the same pair.
+pub fn upsert(library: &mut Library, note: CompatNote) -> Result<(), SError> {
+    insert(&mut library.compat_notes, note)?;
+    library.persist_manifest()
+}
+
+/// Removes the note for a pair. Returns whether one existed.
+pub fn remove(library: &mut Library, mod_a: &str, mod_b: &str) -> Result<bool, SError> {
+    let before = library.compat_notes.len();
+    library.compat_notes.retain(|n| !n.is_pair(mod_a, mod_b));
+    library.persist_manifest()?;
+    Ok(library.compat_notes.len() != before)
+}
+
+/// Points the notes of a mod at its new id, see `mod_manager::add_mod`.
+/// Notes that would pair the mod with itself are dropped.
+pub fn rename_mod(library: &mut Library, old: &str, new: &str) {
+    let rename = |id: String| if id == old { new.to_string() } else { id };
+    library.compat_notes = std::mem::take(&mut library.compat_notes)
+        .into_iter()
+        .map(|n| {
+            CompatNote {
+                mod_a: rename(n.mod_a),
+                mod_b: rename(n.mod_b),
+                ..n
+            }
+            .normalized()
+        })
+        .filter(|n| n.mod_a != n.mod_b)
+        .collect();
+}
+
+/// Notes about pairs of mods that are both active, shown next to deployment conflicts.
+pub fn for_active(library: &Library) -> Vec<CompatNote> {
+    let is_active = |id: &str| library.mods.get(id).is_some_and(|m| m.is_active);
+    library
+        .compat_notes
+        .iter()
+        .filter(|n| is_active(&n.mod_a) && is_active(&n.mod_b))
+        .cloned()
+        .collect()
+}
+
+pub fn export(library: &Library, output: &Utf8PathBuf) -> Result<(), SError> {
+    Toml::write(
+        output,
+        &CompatNotesFile {
+            notes: library.compat_notes.clone(),
+        },
+    )
+}
+
+/// Merges notes shared by another user; imported notes win for pairs already annotated.
+/// Mod ids come from manifests or file layouts, so they match across libraries.
+/// Returns the number of imported notes.
+pub fn import(library: &mut Library, input: &Utf8PathBuf) -> Result<usize, SError> {
+    let file: CompatNotesFile = Toml::read(input)?;
+    let count = file.notes.len();
+    file.notes
+        .into_iter()
+        .try_for_each(|note| insert(&mut library.compat_notes, note))?;
+    library.persist_manifest()?;
+    Ok(count)
+}
+
+fn insert(notes: &mut Vec<CompatNote>, note: CompatNote) -> Result<(), SError> {
+    let note = note.normalized();
+    if note.mod_a == note.mod_b {
+        return Err(SError::ParseError(format!(
+            "A compatibility note needs two different mods: {}",
+            note.mod_a
+        )));
+    }
+
+    notes.retain(|n| !n.is_pair(&note.mod_a, &note.mod_b));
+    notes.push(note);
+    notes.sort_by(|a, b| (&a.mod_a, &a.mod_b).cmp(&(&b.mod_a, &b.mod_b)));
+    Ok(())
+}
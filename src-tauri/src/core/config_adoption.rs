@@ -0,0 +1,105 @@
+use crate::core::library::Library;
+use crate::utils::scan;
+use camino::Utf8PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::info;
+
+const CONFIG_EXTENSION: &str = "cfg";
+/// The framework's own config, never owned by a mod.
+const FRAMEWORK_CONFIG: &str = "BepInEx.cfg";
+
+/// Adopts the config files already present under `BepInEx/config` that belong to a mod of the
+/// library, so an existing install has accurate ownership from its first sync.
+/// A file is matched to a mod by its id, name or plugin DLL; files matching several mods are
+/// left alone. Returns the adopted files, relative to the game root.
+pub fn adopt(library: &mut Library) -> Vec<Utf8PathBuf> {
+    let config_root = library.game_root.join(&library.spt_rules.client_config);
+    let keys = mod_keys(library);
+
+    let adopted: Vec<(Utf8PathBuf, String)> = scan::walk(&config_root)
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| Utf8PathBuf::from_path_buf(entry.into_path()).ok())
+        .filter(|path| path.extension() == Some(CONFIG_EXTENSION))
+        .filter(|path| path.file_name() != Some(FRAMEWORK_CONFIG))
+        .filter_map(|path| {
+            let rel = path.strip_prefix(&library.game_root).ok()?.to_path_buf();
+            let owner = owner_of(path.file_stem()?, &keys)?;
+            Some((rel, owner))
+        })
+        .filter(|(rel, _)| !library.config_owners.contains_key(rel))
+        .collect();
+
+    if adopted.is_empty() {
+        return Vec::new();
+    }
+
+    info!("Adopted {} existing config file(s)", adopted.len());
+    library.config_owners.extend(adopted.iter().cloned());
+    library.mark_dirty();
+    adopted.into_iter().map(|(rel, _)| rel).collect()
+}
+
+/// Drops the configs owned by a removed mod; the files themselves belong to the user.
+pub fn release(library: &mut Library, mod_id: &str) {
+    library.config_owners.retain(|_, owner| owner != mod_id);
+}
+
+/// Moves the configs of a mod to its new id.
+pub fn rename_mod(library: &mut Library, old: &str, new: &str) {
+    library
+        .config_owners
+        .values_mut()
+        .filter(|owner| *owner == old)
+        .for_each(|owner| *owner = new.to_string());
+}
+
+/// Normalized names each mod may use for its configs: id, display name and plugin DLL stems.
+fn mod_keys(library: &Library) -> BTreeMap<String, BTreeSet<String>> {
+    library
+        .mods
+        .values()
+        .map(|m| {
+            let dlls = library
+                .cache
+                .mods
+                .get(&m.id)
+                .into_iter()
+                .flat_map(|fs| fs.files.iter())
+                .filter(|f| f.starts_with(&library.spt_rules.client_plugins))
+                .filter(|f| f.extension() == Some("dll"))
+                .filter_map(|f| f.file_stem().map(normalize));
+            let keys = [normalize(&m.id), normalize(&m.name)]
+                .into_iter()
+                .chain(dlls)
+                .filter(|key| !key.is_empty())
+                .collect();
+            (m.id.clone(), keys)
+        })
+        .collect()
+}
+
+/// BepInEx names configs after the plugin GUID, often `com.author.plugin`, so the last
+/// segment is tried as well as the full stem.
+fn owner_of(stem: &str, keys: &BTreeMap<String, BTreeSet<String>>) -> Option<String> {
+    let full = normalize(stem);
+    let last = stem.rsplit('.').next().map(normalize).unwrap_or_default();
+
+    let mut owners = keys
+        .iter()
+        .filter(|(_, keys)| keys.contains(&full) || keys.contains(&last))
+        .map(|(id, _)| id);
+
+    match (owners.next(), owners.next()) {
+        (Some(id), None) => Some(id.clone()),
+        _ => None,
+    }
+}
+
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
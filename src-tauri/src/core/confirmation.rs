@@ -0,0 +1,104 @@
+use crate::models::confirmation::{Confirmation, DestructiveAction};
+use crate::models::error::SError;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How long a token stays valid, long enough for a confirmation dialog.
+pub const TOKEN_TTL: Duration = Duration::from_secs(60);
+
+struct Pending {
+    action: DestructiveAction,
+    expires: Instant,
+}
+
+/// Tokens handed out once the user accepted the dialog of `request_confirmation`, each good
+/// for one run of its action, so a destructive command cannot run without the user having
+/// confirmed it first, whatever the frontend does.
+#[derive(Clone)]
+pub struct Confirmations {
+    pending: Arc<Mutex<HashMap<String, Pending>>>,
+    ttl: Duration,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self::with_ttl(TOKEN_TTL)
+    }
+}
+
+impl Confirmations {
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            pending: Arc::default(),
+            ttl,
+        }
+    }
+
+    /// Hands out a token for `action`; only call it once the user confirmed `prompt(action)`.
+    /// Expired tokens are dropped on the way.
+    pub fn issue(&self, action: DestructiveAction) -> Confirmation {
+        let now = Instant::now();
+        let token = Uuid::new_v4().to_string();
+        let mut pending = self.pending.lock();
+        pending.retain(|_, p| p.expires > now);
+        pending.insert(
+            token.clone(),
+            Pending {
+                action: action.clone(),
+                expires: now + self.ttl,
+            },
+        );
+
+        Confirmation {
+            token,
+            action,
+            expires_in_secs: self.ttl.as_secs() as u32,
+        }
+    }
+
+    /// Uses up `token`, failing with `ConfirmationRequired` unless it was issued for this very
+    /// action and has not expired. A token is gone once presented, whether it matched or not.
+    pub fn consume(&self, token: Option<&str>, action: &DestructiveAction) -> Result<(), SError> {
+        let pending = token.and_then(|token| self.pending.lock().remove(token));
+        match pending {
+            Some(p) if p.action == *action && p.expires > Instant::now() => Ok(()),
+            _ => Err(SError::ConfirmationRequired(format!("{action:?}"))),
+        }
+    }
+}
+
+/// What the confirmation dialog of an action asks.
+pub struct Prompt {
+    pub title: &'static str,
+    pub message: String,
+    /// Label of the button that confirms.
+    pub confirm: &'static str,
+}
+
+pub fn prompt(action: &DestructiveAction) -> Prompt {
+    match action {
+        DestructiveAction::RemoveLibrary { repo_root } => Prompt {
+            title: "Remove library",
+            message: format!("Remove the library at {repo_root}? Its folder is deleted."),
+            confirm: "Remove",
+        },
+        DestructiveAction::RestoreBackup { mod_id, timestamp } => Prompt {
+            title: "Restore backup",
+            message: format!("Restore backup {timestamp} of {mod_id} over its edited files?"),
+            confirm: "Restore",
+        },
+        DestructiveAction::DeleteBackup { mod_id, timestamp } => Prompt {
+            title: "Delete backup",
+            message: format!("Delete backup {timestamp} of {mod_id}?"),
+            confirm: "Delete",
+        },
+        DestructiveAction::DeleteAllBackups { mod_id } => Prompt {
+            title: "Delete backups",
+            message: format!("Delete every backup of {mod_id}?"),
+            confirm: "Delete all",
+        },
+    }
+}
@@ -0,0 +1,84 @@
+use crate::core::library::Library;
+use crate::core::repo_store;
+use crate::models::consistency::{ConsistencyIssue, ConsistencyIssueKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+/// Staging folders older than this cannot belong to a task that is still running.
+const STAGING_GRACE: Duration = Duration::from_secs(60 * 60);
+
+/// Runtime opt-in from the global config; debug builds always check.
+static ENABLED: AtomicBool = AtomicBool::new(cfg!(debug_assertions));
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(cfg!(debug_assertions) || enabled, Ordering::Relaxed);
+}
+
+/// Checks the library after a mutating operation when enabled and logs every violation,
+/// so drift between the manifest, the cache and the repo shows up where it started.
+pub fn after_mutation(library: &Library) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    check(library)
+        .iter()
+        .for_each(|issue| warn!("Consistency check: {:?} {}", issue.kind, issue.subject));
+}
+
+/// Cheap invariants of a loaded library; nothing is repaired.
+pub fn check(library: &Library) -> Vec<ConsistencyIssue> {
+    if !library.is_loaded() {
+        return Vec::new();
+    }
+
+    let store = repo_store::open(&library.lib_paths);
+    let missing_folders = library
+        .cache
+        .mods
+        .keys()
+        .filter(|id| !store.payload_dir(id).is_dir())
+        .map(|id| issue(ConsistencyIssueKind::MissingRepoFolder, id));
+    let missing_entries = library
+        .mods
+        .keys()
+        .filter(|id| !library.cache.mods.contains_key(*id))
+        .map(|id| issue(ConsistencyIssueKind::MissingCacheEntry, id));
+
+    missing_folders
+        .chain(missing_entries)
+        .chain(staging_leftovers(library))
+        .collect()
+}
+
+fn staging_leftovers(library: &Library) -> Vec<ConsistencyIssue> {
+    let Ok(entries) = std::fs::read_dir(&library.lib_paths.staging) else {
+        return Vec::new();
+    };
+    let now = SystemTime::now();
+
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|meta| meta.modified())
+                .is_ok_and(|modified| {
+                    now.duration_since(modified).unwrap_or_default() > STAGING_GRACE
+                })
+        })
+        .map(|entry| {
+            issue(
+                ConsistencyIssueKind::StagingLeftover,
+                &entry.file_name().to_string_lossy(),
+            )
+        })
+        .collect()
+}
+
+fn issue(kind: ConsistencyIssueKind, subject: &str) -> ConsistencyIssue {
+    ConsistencyIssue {
+        kind,
+        subject: subject.to_string(),
+    }
+}
@@ -0,0 +1,131 @@
+use crate::core::library::Library;
+use crate::core::{library_service, mod_manager, repo_history};
+use crate::models::error::SError;
+use crate::models::global::{ChecklistPolicy, LinkFailurePolicy};
+use crate::models::warning::OperationWarning;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::time::SystemTime;
+use tracing::info;
+use walkdir::WalkDir;
+
+/// Crashes blamed on the same mod before it counts as the cause.
+pub const REPEATED_CRASHES: u32 = 2;
+
+/// Bytes read from the end of the server log, enough for the errors that ended it.
+const LOG_TAIL: u64 = 64 * 1024;
+
+/// Server crashes per blamed mod, since the app started.
+#[derive(Default)]
+pub struct CrashCounter {
+    counts: BTreeMap<String, u32>,
+}
+
+impl CrashCounter {
+    /// Counts a crash blamed on `mod_id`. Returns whether it now crashed repeatedly, which
+    /// starts its count over.
+    pub fn count(&mut self, mod_id: &str) -> bool {
+        let count = self.counts.entry(mod_id.to_string()).or_default();
+        *count += 1;
+        if *count < REPEATED_CRASHES {
+            return false;
+        }
+        self.counts.remove(mod_id);
+        true
+    }
+}
+
+/// The active mod the errors at the end of the newest server log point at, when they point
+/// at exactly one. Errors name a mod through the folder it is loaded from, e.g.
+/// `user\mods\Loot\src\mod.js` in a stack trace, or through one of its assemblies.
+pub fn attribute(library: &Library) -> Option<String> {
+    let log = newest_log(&library.game_root.join(&library.spt_rules.server_logs))?;
+    let tail = read_tail(&log).ok()?;
+    let errors: Vec<String> = tail
+        .lines()
+        .map(|line| line.replace('\\', "/").to_lowercase())
+        .filter(|line| line.contains("error") || line.contains("exception"))
+        .collect();
+
+    let mut blamed = library
+        .mods
+        .values()
+        .filter(|m| m.is_active)
+        .filter(|m| {
+            markers(library, &m.id)
+                .iter()
+                .any(|marker| errors.iter().any(|line| line.contains(marker)))
+        })
+        .map(|m| m.id.clone());
+    let suspect = blamed.next()?;
+    blamed.next().is_none().then_some(suspect)
+}
+
+/// Deactivates a mod blamed for repeated crashes, records it in the repo history and deploys
+/// the active mods without it.
+pub fn disable(
+    library: &mut Library,
+    mod_id: &str,
+    policy: ChecklistPolicy,
+    link_policy: LinkFailurePolicy,
+) -> Result<Vec<OperationWarning>, SError> {
+    mod_manager::toggle_mod(library, mod_id, false)?;
+    info!("Disabled {mod_id} after repeated server crashes");
+    repo_history::record(
+        &library.repo_root,
+        &format!("Disable {mod_id} after repeated crashes"),
+    );
+    library_service::sync_incremental(library, policy, link_policy)
+}
+
+/// What names a mod in server errors, lowercased: the folders it deploys into the server mods,
+/// as `mods/{folder}/`, and its assemblies.
+fn markers(library: &Library, mod_id: &str) -> BTreeSet<String> {
+    let Some(fs) = library.cache.mods.get(mod_id) else {
+        return BTreeSet::new();
+    };
+    fs.files
+        .iter()
+        .filter_map(|file| file.strip_prefix(&library.spt_rules.server_mods).ok())
+        .flat_map(|rel| {
+            let mut components = rel.components();
+            let folder = components
+                .next()
+                .filter(|_| components.next().is_some())
+                .map(|folder| format!("mods/{folder}/"));
+            let assembly = rel
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("dll"))
+                .then(|| rel.file_name().map(str::to_string))
+                .flatten();
+            folder.into_iter().chain(assembly)
+        })
+        .map(|marker| marker.to_lowercase())
+        .collect()
+}
+
+/// The most recently written log below `logs`, which each SPT generation lays out differently.
+fn newest_log(logs: &Utf8Path) -> Option<Utf8PathBuf> {
+    WalkDir::new(logs)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            let path = Utf8PathBuf::from_path_buf(entry.into_path()).ok()?;
+            (path.extension() == Some("log")).then_some((modified, path))
+        })
+        .max_by_key(|(modified, _): &(SystemTime, Utf8PathBuf)| *modified)
+        .map(|(_, path)| path)
+}
+
+fn read_tail(path: &Utf8Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(LOG_TAIL)))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
@@ -0,0 +1,213 @@
+use crate::core::library::Library;
+use crate::core::repo_store;
+use crate::models::database_diff::{DatabaseOverlap, FieldChange, RecordChange, RecordChangeKind};
+use crate::models::error::SError;
+use camino::Utf8Path;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Database files read for the diff, relative to the database folder.
+const KEY_FILES: [&str; 5] = [
+    "globals.json",
+    "templates/items.json",
+    "templates/handbook.json",
+    "templates/prices.json",
+    "templates/quests.json",
+];
+/// Fields naming the id of a record held in an array.
+const ID_FIELDS: [&str; 3] = ["_id", "Id", "id"];
+
+/// A record found in a JSON document: its id, the file it is in and its value.
+type Record<'a> = (String, &'a str, &'a Value);
+
+/// What a server mod changes in the SPT database, as far as the JSON data it ships tells.
+/// Server mods apply their changes in memory when the server starts, so the database files
+/// never show them; instead, every record of the mod's JSON files is compared with the record
+/// of the same id in the key database files. Changes made in code alone are not seen.
+pub fn diff(library: &Library, mod_id: &str) -> Result<Vec<RecordChange>, SError> {
+    let database = read_database(library)?;
+    let index = index(&database);
+    let shipped = read_mod(library, mod_id)?;
+
+    Ok(records_of(&shipped)
+        .into_iter()
+        .filter_map(|(id, source, value)| change(&index, id, source, value))
+        .collect())
+}
+
+/// Records several active server mods ship data for, ordered by id. Read from the mods alone,
+/// so it works without the database too.
+pub fn overlaps(library: &Library) -> Result<Vec<DatabaseOverlap>, SError> {
+    let touched = library
+        .mods
+        .values()
+        .filter(|m| m.is_active)
+        .map(|m| {
+            let shipped = read_mod(library, &m.id)?;
+            let ids: BTreeSet<String> = records_of(&shipped)
+                .into_iter()
+                .map(|(id, _, _)| id)
+                .collect();
+            Ok((m.id.clone(), ids))
+        })
+        .collect::<Result<Vec<_>, SError>>()?;
+
+    let by_record = touched.into_iter().fold(
+        BTreeMap::<String, Vec<String>>::new(),
+        |mut acc, (mod_id, ids)| {
+            ids.into_iter()
+                .for_each(|id| acc.entry(id).or_default().push(mod_id.clone()));
+            acc
+        },
+    );
+    Ok(by_record
+        .into_iter()
+        .filter(|(_, mods)| mods.len() > 1)
+        .map(|(id, mods)| DatabaseOverlap { id, mods })
+        .collect())
+}
+
+fn read_database(library: &Library) -> Result<Vec<(String, Value)>, SError> {
+    let root = library.game_root.join(&library.spt_rules.server_database);
+    if !root.is_dir() {
+        return Err(SError::FileOrDirectoryNotFound(root.to_string()));
+    }
+    KEY_FILES
+        .iter()
+        .map(|rel| (rel.to_string(), root.join(rel)))
+        .filter(|(_, path)| path.is_file())
+        .map(|(rel, path)| Ok((rel, read_json(&path)?)))
+        .collect()
+}
+
+/// JSON files of the server part of a mod, relative to its root. Unparsable files are skipped.
+fn read_mod(library: &Library, mod_id: &str) -> Result<Vec<(String, Value)>, SError> {
+    let mod_fs = library
+        .cache
+        .mods
+        .get(mod_id)
+        .ok_or_else(|| SError::ModNotFound(mod_id.to_string()))?;
+    let root = repo_store::open(&library.lib_paths).payload_dir(mod_id);
+
+    Ok(mod_fs
+        .files
+        .iter()
+        .filter(|rel| rel.starts_with(&library.spt_rules.server_mods))
+        .filter(|rel| rel.extension() == Some("json"))
+        .filter_map(|rel| Some((rel.to_string(), read_json(&root.join(rel)).ok()?)))
+        .collect())
+}
+
+fn read_json(path: &Utf8Path) -> Result<Value, SError> {
+    Ok(serde_json::from_reader(std::io::BufReader::new(
+        std::fs::File::open(path)?,
+    ))?)
+}
+
+/// Database records by id; an item has one in each of several files.
+fn index(database: &[(String, Value)]) -> BTreeMap<String, Vec<(&str, &Value)>> {
+    records_of(database)
+        .into_iter()
+        .fold(BTreeMap::new(), |mut acc, (id, file, value)| {
+            acc.entry(id).or_default().push((file, value));
+            acc
+        })
+}
+
+fn records_of(documents: &[(String, Value)]) -> Vec<Record<'_>> {
+    let mut records = Vec::new();
+    for (file, value) in documents {
+        collect(value, file, &mut records);
+    }
+    records
+}
+
+/// Collects the objects keyed by a record id, or carrying one in an id field, without
+/// descending into records: their nested ids belong to them.
+fn collect<'a>(value: &'a Value, file: &'a str, records: &mut Vec<Record<'a>>) {
+    match value {
+        Value::Object(map) => map.iter().for_each(|(key, child)| match is_record_id(key) {
+            true => records.push((key.clone(), file, child)),
+            false => collect(child, file, records),
+        }),
+        Value::Array(items) => items.iter().for_each(|item| match id_field(item) {
+            Some(id) => records.push((id.to_string(), file, item)),
+            None => collect(item, file, records),
+        }),
+        _ => {}
+    }
+}
+
+fn id_field(value: &Value) -> Option<&str> {
+    ID_FIELDS
+        .iter()
+        .find_map(|field| value.get(field)?.as_str())
+        .filter(|id| is_record_id(id))
+}
+
+/// Records of the SPT database are keyed by 24 hex digit ids.
+fn is_record_id(key: &str) -> bool {
+    key.len() == 24 && key.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn change(
+    index: &BTreeMap<String, Vec<(&str, &Value)>>,
+    id: String,
+    source: &str,
+    value: &Value,
+) -> Option<RecordChange> {
+    let Some(candidates) = index.get(&id) else {
+        return Some(RecordChange {
+            id,
+            kind: RecordChangeKind::Added,
+            file: None,
+            source: source.to_string(),
+            fields: Vec::new(),
+        });
+    };
+
+    // The record of the file the mod data is shaped like, e.g. the item rather than its price
+    let (file, before) = candidates
+        .iter()
+        .max_by_key(|(_, before)| likeness(before, value))?;
+    let mut fields = Vec::new();
+    changed_fields(Some(before), value, String::new(), &mut fields);
+
+    (!fields.is_empty()).then(|| RecordChange {
+        id,
+        kind: RecordChangeKind::Modified,
+        file: Some(file.to_string()),
+        source: source.to_string(),
+        fields,
+    })
+}
+
+fn likeness(a: &Value, b: &Value) -> usize {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => 1 + b.keys().filter(|k| a.contains_key(*k)).count(),
+        _ => usize::from(std::mem::discriminant(a) == std::mem::discriminant(b)),
+    }
+}
+
+/// Fields of `after` that differ from `before`; fields only `before` has are kept by the mod.
+fn changed_fields(
+    before: Option<&Value>,
+    after: &Value,
+    pointer: String,
+    changes: &mut Vec<FieldChange>,
+) {
+    match (before, after) {
+        (Some(Value::Object(before)), Value::Object(after)) => {
+            after.iter().for_each(|(key, value)| {
+                let pointer = format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"));
+                changed_fields(before.get(key), value, pointer, changes)
+            })
+        }
+        (Some(before), after) if before == after => {}
+        (before, after) => changes.push(FieldChange {
+            pointer,
+            before: before.map(Value::to_string),
+            after: after.to_string(),
+        }),
+    }
+}
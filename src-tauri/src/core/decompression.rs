@@ -1,13 +1,138 @@
+use crate::core::linker;
+use crate::core::progress::{Progress, TransferMeter};
 use crate::models::error::SError;
-use camino::Utf8Path;
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use derive_more::Display;
+use sevenz_rust::{Password, SevenZReader};
 use std::fs::{self, File};
-use std::io;
+use std::io::{self, Read};
 
-pub fn extract(archive_path: &Utf8Path, destination: &Utf8Path) -> Result<(), SError> {
+/// Archive formats mods are shipped in, told apart by their signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum ArchiveFormat {
+    Zip,
+    SevenZip,
+    Rar,
+}
+
+const SIGNATURES: [(&[u8], ArchiveFormat); 4] = [
+    (b"PK\x03\x04", ArchiveFormat::Zip),
+    // An empty zip starts with its end of central directory record
+    (b"PK\x05\x06", ArchiveFormat::Zip),
+    (b"7z\xBC\xAF\x27\x1C", ArchiveFormat::SevenZip),
+    // RAR 4 and RAR 5 share this prefix
+    (b"Rar!\x1A\x07", ArchiveFormat::Rar),
+];
+
+impl ArchiveFormat {
+    /// Reads the signature at the start of the file; `None` for folders and other files.
+    /// Downloads are not always named after their format, so the extension is not trusted.
+    pub fn detect(path: &Utf8Path) -> Option<Self> {
+        let mut head = Vec::with_capacity(8);
+        File::open(path).ok()?.take(8).read_to_end(&mut head).ok()?;
+        SIGNATURES
+            .iter()
+            .find(|(signature, _)| head.starts_with(signature))
+            .map(|(_, format)| *format)
+    }
+
+    /// Format the file is named after, so a broken download is still reported as one.
+    pub fn from_extension(path: &Utf8Path) -> Option<Self> {
+        match path.extension()?.to_ascii_lowercase().as_str() {
+            "zip" => Some(Self::Zip),
+            "7z" => Some(Self::SevenZip),
+            "rar" => Some(Self::Rar),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum SkipReason {
+    #[display("path escapes the destination")]
+    UnsafePath,
+    #[display("symbolic link")]
+    Symlink,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+#[display("{name} ({reason})")]
+pub struct SkippedEntry {
+    pub name: String,
+    pub reason: SkipReason,
+}
+
+/// Outcome of an extraction, so partially unpacked archives can be reported.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExtractReport {
+    pub skipped: Vec<SkippedEntry>,
+    pub extracted_bytes: u64,
+}
+
+/// Extracts the archive into `destination`, reporting the unpacked bytes as they are written.
+/// Entries that cannot be extracted safely are skipped and listed in the report.
+pub fn extract(
+    archive_path: &Utf8Path,
+    destination: &Utf8Path,
+    progress: Progress,
+) -> Result<ExtractReport, SError> {
+    let item = archive_path.file_name().unwrap_or(archive_path.as_str());
+    let result = match ArchiveFormat::detect(archive_path) {
+        Some(ArchiveFormat::Zip) => extract_zip(archive_path, destination, progress, item),
+        Some(ArchiveFormat::SevenZip) => extract_7z(archive_path, destination, progress, item),
+        Some(ArchiveFormat::Rar) => extract_rar(archive_path, destination, progress, item),
+        None => Err(SError::UnhandledCompression(archive_path.to_string())),
+    };
+    result.map(|(report, meter)| {
+        meter.finish();
+        report
+    })
+}
+
+/// Size of the archive content once unpacked, read from its listing.
+pub fn unpacked_size(archive_path: &Utf8Path) -> Result<u64, SError> {
+    match ArchiveFormat::detect(archive_path) {
+        Some(ArchiveFormat::Zip) => Ok(zip::ZipArchive::new(File::open(archive_path)?)?
+            .decompressed_size()
+            .unwrap_or(0) as u64),
+        Some(ArchiveFormat::SevenZip) => {
+            let archive = SevenZReader::open(archive_path, Password::empty())?;
+            Ok(seven_zip_size(&archive))
+        }
+        Some(ArchiveFormat::Rar) => rar_size(archive_path),
+        None => Err(SError::UnhandledCompression(archive_path.to_string())),
+    }
+}
+
+fn seven_zip_size<R: Read + io::Seek>(archive: &SevenZReader<R>) -> u64 {
+    archive
+        .archive()
+        .files
+        .iter()
+        .map(|entry| entry.size())
+        .sum()
+}
+
+fn rar_size(archive_path: &Utf8Path) -> Result<u64, SError> {
+    Ok(unrar::Archive::new(archive_path)
+        .open_for_listing()?
+        .map(|entry| entry.map(|entry| entry.unpacked_size))
+        .sum::<Result<u64, _>>()?)
+}
+
+fn extract_zip<'a>(
+    archive_path: &Utf8Path,
+    destination: &Utf8Path,
+    progress: Progress<'a>,
+    item: &str,
+) -> Result<(ExtractReport, TransferMeter<'a>), SError> {
     // 1. Open the archive file
     let file = File::open(archive_path)?;
 
     let mut archive = zip::ZipArchive::new(file)?;
+    let mut report = ExtractReport::default();
+    let total = archive.decompressed_size().unwrap_or(0) as u64;
+    let mut meter = progress.meter(item, total);
 
     // 2. Iterate through all files in the archive
     for i in 0..archive.len() {
@@ -17,8 +142,16 @@ pub fn extract(archive_path: &Utf8Path, destination: &Utf8Path) -> Result<(), SE
         // enclosed_name() ensures the path is valid and inside the target directory
         let safe_path = match file.enclosed_name() {
             Some(path) => path.to_owned(),
-            None => continue, // Skip unsafe paths
+            None => {
+                report.skip(file.name(), SkipReason::UnsafePath);
+                continue;
+            }
         };
+        // Links would be written as plain files holding their target
+        if file.is_symlink() {
+            report.skip(file.name(), SkipReason::Symlink);
+            continue;
+        }
 
         let output_path = destination.as_std_path().join(&safe_path);
 
@@ -37,7 +170,13 @@ pub fn extract(archive_path: &Utf8Path, destination: &Utf8Path) -> Result<(), SE
 
             let mut outfile = File::create(&output_path)?;
 
-            io::copy(&mut file, &mut outfile)?;
+            // The reader verifies the entry's CRC32 from the zip metadata once it hits EOF
+            let written = io::copy(&mut meter.reader(&mut file), &mut outfile)
+                .map_err(|e| corrupt_or_io(e, archive_path, file.name()))?;
+            if written != file.size() {
+                return Err(corrupt(archive_path, file.name(), "size mismatch"));
+            }
+            report.extracted_bytes += written;
         }
 
         // 6. (Optional) Preserve Permissions on Unix/Linux/Mac
@@ -51,5 +190,171 @@ pub fn extract(archive_path: &Utf8Path, destination: &Utf8Path) -> Result<(), SE
         }
     }
 
-    Ok(())
+    Ok((report, meter))
+}
+
+fn extract_7z<'a>(
+    archive_path: &Utf8Path,
+    destination: &Utf8Path,
+    progress: Progress<'a>,
+    item: &str,
+) -> Result<(ExtractReport, TransferMeter<'a>), SError> {
+    let mut report = ExtractReport::default();
+    // The callback can only fail with the library's error, so ours is kept aside
+    let mut failure = None;
+
+    let from_7z = |e| match e {
+        sevenz_rust::Error::ChecksumVerificationFailed => {
+            corrupt(archive_path, "", "checksum mismatch")
+        }
+        e => SError::from(e),
+    };
+    let mut archive = SevenZReader::open(archive_path, Password::empty()).map_err(from_7z)?;
+    let mut meter = progress.meter(item, seven_zip_size(&archive));
+
+    let result = archive.for_each_entries(|entry, reader| {
+        // Deletion markers of an update archive, nothing to write
+        if entry.is_anti_item() {
+            return Ok(true);
+        }
+        let Some(safe_path) = enclosed_name(entry.name()) else {
+            report.skip(entry.name(), SkipReason::UnsafePath);
+            return Ok(true);
+        };
+        if is_unix_symlink(entry) {
+            report.skip(entry.name(), SkipReason::Symlink);
+            return Ok(true);
+        }
+
+        let output_path = destination.join(safe_path);
+        let written = match entry.is_directory() {
+            true => fs::create_dir_all(&output_path)
+                .map(|_| 0)
+                .map_err(SError::from),
+            false => write_entry(&mut meter.reader(reader), &output_path)
+                .map_err(|e| corrupt_or_io(e, archive_path, entry.name())),
+        };
+        match written {
+            Ok(written) if entry.is_directory() || written == entry.size() => {
+                report.extracted_bytes += written;
+                Ok(true)
+            }
+            Ok(_) => {
+                failure = Some(corrupt(archive_path, entry.name(), "size mismatch"));
+                Ok(false)
+            }
+            Err(e) => {
+                failure = Some(e);
+                Ok(false)
+            }
+        }
+    });
+
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    result.map_err(from_7z)?;
+    Ok((report, meter))
+}
+
+fn extract_rar<'a>(
+    archive_path: &Utf8Path,
+    destination: &Utf8Path,
+    progress: Progress<'a>,
+    item: &str,
+) -> Result<(ExtractReport, TransferMeter<'a>), SError> {
+    let mut report = ExtractReport::default();
+    // unrar writes the files itself, so the meter advances once per entry
+    let mut meter = progress.meter(item, rar_size(archive_path)?);
+    let mut archive = unrar::Archive::new(archive_path).open_for_processing()?;
+
+    while let Some(header) = archive.read_header()? {
+        let entry = header.entry();
+        let name = entry.filename.to_string_lossy().to_string();
+        let Some(safe_path) = enclosed_name(&name) else {
+            report.skip(&name, SkipReason::UnsafePath);
+            archive = header.skip()?;
+            continue;
+        };
+
+        let output_path = destination.join(safe_path);
+        if entry.is_directory() {
+            fs::create_dir_all(&output_path)?;
+            archive = header.skip()?;
+            continue;
+        }
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // unrar verifies the entry's CRC while extracting
+        let size = entry.unpacked_size;
+        archive = header.extract_to(&output_path).map_err(|e| match e.code {
+            unrar::error::Code::BadData => corrupt(archive_path, &name, "checksum mismatch"),
+            _ => e.into(),
+        })?;
+        // unrar recreates links itself, so they can only be caught once written
+        if fs::symlink_metadata(&output_path).is_ok_and(|meta| meta.is_symlink()) {
+            linker::unlink(&output_path)?;
+            report.skip(&name, SkipReason::Symlink);
+            continue;
+        }
+        if fs::metadata(&output_path)?.len() != size {
+            return Err(corrupt(archive_path, &name, "size mismatch"));
+        }
+        report.extracted_bytes += size;
+        meter.add(size);
+    }
+
+    Ok((report, meter))
+}
+
+/// Entry name as a path inside the destination, `None` when it would escape it.
+/// Archives made on Windows may separate folders with backslashes.
+fn enclosed_name(name: &str) -> Option<Utf8PathBuf> {
+    let path = Utf8PathBuf::from(name.replace('\\', "/"));
+    path.components()
+        .all(|c| matches!(c, Utf8Component::Normal(_) | Utf8Component::CurDir))
+        .then_some(path)
+}
+
+/// Archives made on Unix keep the file mode in the upper half of the attributes.
+fn is_unix_symlink(entry: &sevenz_rust::SevenZArchiveEntry) -> bool {
+    const UNIX_EXTENSION: u32 = 0x8000;
+    const S_IFMT: u32 = 0o170000;
+    const S_IFLNK: u32 = 0o120000;
+    let attributes = entry.windows_attributes();
+    entry.has_windows_attributes
+        && attributes & UNIX_EXTENSION != 0
+        && (attributes >> 16) & S_IFMT == S_IFLNK
+}
+
+fn write_entry(reader: &mut dyn Read, output_path: &Utf8Path) -> io::Result<u64> {
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    io::copy(reader, &mut File::create(output_path)?)
+}
+
+/// Checksum and decompression failures mean the download is broken, not the disk.
+fn corrupt_or_io(e: io::Error, archive_path: &Utf8Path, entry: &str) -> SError {
+    match e.kind() {
+        io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+            corrupt(archive_path, entry, &e.to_string())
+        }
+        _ => e.into(),
+    }
+}
+
+fn corrupt(archive_path: &Utf8Path, entry: &str, reason: &str) -> SError {
+    SError::CorruptArchive(format!("{archive_path}: {entry} ({reason})"))
+}
+
+impl ExtractReport {
+    fn skip(&mut self, name: &str, reason: SkipReason) {
+        self.skipped.push(SkippedEntry {
+            name: name.to_string(),
+            reason,
+        });
+    }
 }
@@ -0,0 +1,94 @@
+use crate::core::library::Library;
+use crate::models::dedicated_server::ServerHealth;
+use crate::models::error::SError;
+use crate::models::library::LibraryMode;
+use crate::models::mod_dto::{Mod, ModType};
+use crate::models::warning::{OperationWarning, WarningKind};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Server mod folders a Fika dedicated server cannot run without.
+pub const REQUIRED_SERVER_MODS: &[&str] = &["fika-server"];
+
+/// Switches the library between a regular install and a dedicated server.
+/// The library turns dirty, as the mods to deploy change with the mode.
+pub fn set_mode(library: &mut Library, mode: LibraryMode) -> Result<(), SError> {
+    if library.mode == mode {
+        return Ok(());
+    }
+    library.mode = mode;
+    library.mark_dirty();
+    library.persist_manifest()
+}
+
+/// The mods to deploy. A dedicated server leaves client-only mods out, even when active.
+pub fn deployable_mods(library: &Library) -> Cow<'_, BTreeMap<String, Mod>> {
+    if library.mode != LibraryMode::DedicatedServer {
+        return Cow::Borrowed(&library.mods);
+    }
+
+    let mods = library
+        .mods
+        .iter()
+        .map(|(id, m)| {
+            let mut m = m.clone();
+            m.is_active &= m.mod_type != ModType::Client;
+            (id.clone(), m)
+        })
+        .collect();
+    Cow::Owned(mods)
+}
+
+/// What keeps the library from running as a dedicated server, in any mode.
+pub fn check(library: &Library) -> ServerHealth {
+    let ignored_client_mods = library
+        .mods
+        .values()
+        .filter(|m| m.is_active && m.mod_type == ModType::Client)
+        .map(|m| m.id.clone())
+        .collect();
+
+    let deployed = active_server_folders(library);
+    let missing_server_mods = REQUIRED_SERVER_MODS
+        .iter()
+        .filter(|folder| !deployed.contains(**folder))
+        .map(|folder| folder.to_string())
+        .collect();
+
+    ServerHealth {
+        ignored_client_mods,
+        missing_server_mods,
+    }
+}
+
+/// Warnings raised by a sync of a dedicated server; none for a regular install.
+pub fn sync_warnings(library: &Library) -> Vec<OperationWarning> {
+    if library.mode != LibraryMode::DedicatedServer {
+        return Vec::new();
+    }
+
+    let health = check(library);
+    let ignored = health.ignored_client_mods.iter().map(|id| {
+        let name = library.mods.get(id).map_or(id.as_str(), |m| &m.name);
+        OperationWarning::new(WarningKind::ClientModIgnored, name)
+    });
+    let missing = health
+        .missing_server_mods
+        .iter()
+        .map(|folder| OperationWarning::new(WarningKind::RequiredServerModMissing, folder));
+    ignored.chain(missing).collect()
+}
+
+/// Top-level folders the active mods place in the server mods folder.
+fn active_server_folders(library: &Library) -> BTreeSet<String> {
+    let server_mods = &library.spt_rules.server_mods;
+    library
+        .mods
+        .values()
+        .filter(|m| m.is_active)
+        .filter_map(|m| library.cache.mods.get(&m.id))
+        .flat_map(|fs| fs.files.iter())
+        .filter_map(|rel| rel.strip_prefix(server_mods).ok()?.components().next())
+        .map(|folder| folder.as_str().to_string())
+        .collect()
+}
@@ -0,0 +1,228 @@
+use crate::core::library::Library;
+use crate::models::error::{DependencyIssue, DependencyIssueKind, SError};
+use crate::models::mod_dto::{Dependencies, ModManifest};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Required mods and conflicts between the mods of a library, read from their manifests.
+/// References name a mod by its library id or its manifest id; unknown ones stay as written.
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    /// Mod id -> required mod references; optional dependencies are recommendations instead.
+    pub requires: BTreeMap<String, Vec<String>>,
+    /// Mod id -> mods it cannot run with, in both directions.
+    pub conflicts: BTreeMap<String, BTreeSet<String>>,
+}
+
+pub fn graph(library: &Library) -> DependencyGraph {
+    let manifests = || {
+        library
+            .mods
+            .keys()
+            .filter_map(|id| Some((id, library.cache.manifests.get(id)?)))
+    };
+
+    let requires = manifests()
+        .map(|(id, manifest)| {
+            let required = required_references(manifest)
+                .map(|reference| resolve(library, reference).unwrap_or(reference).to_string())
+                .collect();
+            (id.clone(), required)
+        })
+        .collect();
+
+    let conflicts = manifests()
+        .flat_map(|(id, manifest)| {
+            manifest
+                .conflicts_with
+                .iter()
+                .flatten()
+                .filter_map(|reference| resolve(library, reference))
+                .flat_map(move |other| [(id.as_str(), other), (other, id.as_str())])
+        })
+        .fold(
+            BTreeMap::<String, BTreeSet<String>>::new(),
+            |mut acc, (a, b)| {
+                acc.entry(a.to_string()).or_default().insert(b.to_string());
+                acc
+            },
+        );
+
+    DependencyGraph {
+        requires,
+        conflicts,
+    }
+}
+
+/// Fails when activating `ids` alongside the active mods leaves a dependency of theirs
+/// missing or inactive, or activates two conflicting mods.
+pub fn check_activation(library: &Library, ids: &[String]) -> Result<(), SError> {
+    let active: BTreeSet<&str> = active_ids(library)
+        .chain(ids.iter().map(String::as_str))
+        .collect();
+    ensure_none(issues(
+        library,
+        &graph(library),
+        &active,
+        ids.iter().map(String::as_str),
+    ))
+}
+
+/// Fails when an active mod misses a dependency or conflicts with another active mod,
+/// so a deployment never links a half-working set.
+pub fn ensure_satisfied(library: &Library) -> Result<(), SError> {
+    let active: BTreeSet<&str> = active_ids(library).collect();
+    ensure_none(issues(
+        library,
+        &graph(library),
+        &active,
+        active.iter().copied(),
+    ))
+}
+
+/// Like `ensure_satisfied`, for exactly `ids` being the active mods.
+pub fn check_active_set(library: &Library, ids: &[String]) -> Result<(), SError> {
+    let active: BTreeSet<&str> = ids.iter().map(String::as_str).collect();
+    ensure_none(issues(
+        library,
+        &graph(library),
+        &active,
+        active.iter().copied(),
+    ))
+}
+
+/// The mods to enable for `mod_id` to run: the mod and every inactive mod it requires,
+/// directly or not, ordered by id. Fails when a requirement is not installed or the set
+/// conflicts with the active mods.
+pub fn resolve_activation(library: &Library, mod_id: &str) -> Result<Vec<String>, SError> {
+    if !library.mods.contains_key(mod_id) {
+        return Err(SError::ModNotFound(mod_id.to_string()));
+    }
+    let graph = graph(library);
+
+    let mut pending = vec![mod_id.to_string()];
+    let mut required = BTreeSet::new();
+    while let Some(id) = pending.pop() {
+        if !required.insert(id.clone()) {
+            continue;
+        }
+        pending.extend(
+            graph
+                .requires
+                .get(&id)
+                .into_iter()
+                .flatten()
+                .filter(|other| library.mods.get(*other).is_some_and(|m| !m.is_active))
+                .cloned(),
+        );
+    }
+
+    let ids: Vec<String> = required
+        .into_iter()
+        .filter(|id| id == mod_id || library.mods.get(id).is_some_and(|m| !m.is_active))
+        .collect();
+    let active: BTreeSet<&str> = active_ids(library)
+        .chain(ids.iter().map(String::as_str))
+        .collect();
+    ensure_none(issues(
+        library,
+        &graph,
+        &active,
+        ids.iter().map(String::as_str),
+    ))?;
+    Ok(ids)
+}
+
+fn issues<'a>(
+    library: &Library,
+    graph: &DependencyGraph,
+    active: &BTreeSet<&str>,
+    subjects: impl Iterator<Item = &'a str>,
+) -> Vec<DependencyIssue> {
+    subjects
+        .flat_map(|id| {
+            let unmet = graph
+                .requires
+                .get(id)
+                .into_iter()
+                .flatten()
+                .filter_map(|other| {
+                    let kind = match (
+                        library.mods.contains_key(other),
+                        active.contains(other.as_str()),
+                    ) {
+                        (false, _) => DependencyIssueKind::Missing,
+                        (true, false) => DependencyIssueKind::Inactive,
+                        (true, true) => return None,
+                    };
+                    Some(issue(id, other, kind))
+                });
+            let conflicting = graph
+                .conflicts
+                .get(id)
+                .into_iter()
+                .flatten()
+                .filter(|other| active.contains(other.as_str()))
+                .map(|other| issue(id, other, DependencyIssueKind::Conflict));
+            unmet.chain(conflicting).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn ensure_none(issues: Vec<DependencyIssue>) -> Result<(), SError> {
+    match issues.is_empty() {
+        true => Ok(()),
+        false => Err(SError::UnmetDependencies(issues)),
+    }
+}
+
+fn issue(mod_id: &str, other: &str, kind: DependencyIssueKind) -> DependencyIssue {
+    DependencyIssue {
+        mod_id: mod_id.to_string(),
+        other: other.to_string(),
+        kind,
+    }
+}
+
+fn active_ids(library: &Library) -> impl Iterator<Item = &str> {
+    library
+        .mods
+        .values()
+        .filter(|m| m.is_active)
+        .map(|m| m.id.as_str())
+}
+
+fn required_references(manifest: &ModManifest) -> impl Iterator<Item = &str> {
+    let references: Vec<&str> = match &manifest.dependencies {
+        Some(Dependencies::Object(deps)) => deps.keys().map(String::as_str).collect(),
+        Some(Dependencies::Array(deps)) => deps
+            .iter()
+            .filter(|dependency| dependency.optional != Some(true))
+            .map(|dependency| dependency.id.as_str())
+            .collect(),
+        None => Vec::new(),
+    };
+    references.into_iter()
+}
+
+/// Library ids of the installed mods a manifest requires, in manifest order.
+pub(crate) fn installed_requirements<'a>(
+    library: &'a Library,
+    manifest: &ModManifest,
+) -> Vec<&'a str> {
+    required_references(manifest)
+        .filter_map(|reference| resolve(library, reference))
+        .collect()
+}
+
+/// Library id of the mod a manifest reference names.
+fn resolve<'a>(library: &'a Library, reference: &str) -> Option<&'a str> {
+    if let Some((id, _)) = library.mods.get_key_value(reference) {
+        return Some(id);
+    }
+    library
+        .cache
+        .manifests
+        .iter()
+        .find(|(id, manifest)| manifest.id == reference && library.mods.contains_key(*id))
+        .map(|(id, _)| id.as_str())
+}
@@ -0,0 +1,159 @@
+use crate::core::linker;
+use crate::core::local_edits;
+use crate::models::error::SError;
+use crate::models::paths::LibPathRules;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use tracing::warn;
+
+/// Changes made to the game root by a sync, in the order they were made, with absolute paths.
+/// Each one is appended before it is attempted, so the journal of an interrupted sync may end
+/// with a change that never happened; undoing checks what is really there.
+/// Stored as one JSON object per line, so a crash while appending only tears the last one.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Journal {
+    pub actions: Vec<Action>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// A folder created for the links below it.
+    CreateDir { path: Utf8PathBuf },
+    /// A link to `source` in the repo.
+    Link {
+        path: Utf8PathBuf,
+        source: Utf8PathBuf,
+    },
+    /// A copy of `source`, see `DeploymentMode::Copy`.
+    Copy {
+        path: Utf8PathBuf,
+        source: Utf8PathBuf,
+    },
+    /// A link to `source` that was removed.
+    Unlink {
+        path: Utf8PathBuf,
+        source: Utf8PathBuf,
+    },
+}
+
+/// Runs `f` as a transaction over the game root: every change recorded while it runs is undone
+/// when it fails, so a sync failing halfway leaves the game root as it found it.
+/// Copies removed by the purge cannot be brought back and stay removed.
+/// A journal left by an interrupted sync is rolled back first.
+pub fn run<T>(
+    lib_paths: &LibPathRules,
+    f: impl FnOnce() -> Result<T, SError>,
+) -> Result<T, SError> {
+    recover(lib_paths);
+    fs::write(&lib_paths.journal, "")?;
+
+    let result = f();
+    if result.is_err() {
+        rollback(lib_paths);
+    }
+    if let Err(e) = fs::remove_file(&lib_paths.journal) {
+        warn!("Failed to remove the sync journal: {e}");
+    }
+    result
+}
+
+/// Rolls back a sync that was interrupted, e.g. by a crash, if it left its journal.
+/// Called when a library is opened and before each sync.
+pub fn recover(lib_paths: &LibPathRules) {
+    if !lib_paths.journal.exists() {
+        return;
+    }
+    warn!("Rolling back a sync that was interrupted");
+    rollback(lib_paths);
+    if let Err(e) = fs::remove_file(&lib_paths.journal) {
+        warn!("Failed to remove the sync journal: {e}");
+    }
+}
+
+/// Appends a change about to be made. Outside of `run` nothing is recorded.
+pub fn record(lib_paths: &LibPathRules, action: Action) -> io::Result<()> {
+    let mut file = match fs::OpenOptions::new().append(true).open(&lib_paths.journal) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let mut entry = serde_json::to_string(&action).map_err(io::Error::other)?;
+    entry.push('\n');
+    file.write_all(entry.as_bytes())
+}
+
+/// Reads the journal. A missing one means no sync is running.
+/// Reading stops at the first entry that does not parse: only the last one can be torn, by a
+/// crash while it was appended, and the change it announced was never attempted.
+pub fn read(lib_paths: &LibPathRules) -> Result<Journal, SError> {
+    let content = match fs::read_to_string(&lib_paths.journal) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Journal::default()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let lines: Vec<&str> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let actions: Vec<Action> = lines
+        .iter()
+        .map_while(|line| serde_json::from_str(line).ok())
+        .collect();
+    if actions.len() < lines.len() {
+        warn!(
+            "Ignoring {} torn sync journal entries",
+            lines.len() - actions.len()
+        );
+    }
+    Ok(Journal { actions })
+}
+
+/// Undoes the recorded changes, latest first. What cannot be undone is logged and left.
+fn rollback(lib_paths: &LibPathRules) {
+    let journal = match read(lib_paths) {
+        Ok(journal) => journal,
+        Err(e) => {
+            warn!("Failed to read the sync journal, nothing was rolled back: {e}");
+            return;
+        }
+    };
+
+    journal
+        .actions
+        .iter()
+        .rev()
+        .filter_map(|action| undo(action).err().map(|e| (action, e)))
+        .for_each(|(action, e)| warn!("Failed to roll back {action:?}: {e}"));
+}
+
+fn undo(action: &Action) -> io::Result<()> {
+    match action {
+        Action::CreateDir { path } => match path.is_dir() {
+            true => fs::remove_dir(path),
+            false => Ok(()),
+        },
+        Action::Link { path, source } if links_to(path, source) => linker::unlink(path),
+        Action::Copy { path, source } if is_copy_of(path, source) => fs::remove_file(path),
+        Action::Unlink { path, source } if fs::symlink_metadata(path).is_err() => {
+            linker::link(source, path).map(|_| ())
+        }
+        // Never made, or replaced by something that is not ours since
+        _ => Ok(()),
+    }
+}
+
+fn links_to(path: &Utf8Path, source: &Utf8Path) -> bool {
+    linker::read_link_target(path).is_ok_and(|target| target == source)
+        || (path.is_file() && linker::is_same_file(path, source))
+}
+
+fn is_copy_of(path: &Utf8Path, source: &Utf8Path) -> bool {
+    match (local_edits::stamp_of(path), local_edits::stamp_of(source)) {
+        (Ok(a), Ok(b)) => a.digest == b.digest,
+        _ => false,
+    }
+}
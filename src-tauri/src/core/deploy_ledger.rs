@@ -0,0 +1,189 @@
+use crate::core::linker;
+use crate::core::local_edits;
+use crate::models::error::SError;
+use crate::models::paths::{LibPathRules, SPTPathRules};
+use crate::utils::msgpack::MsgPack;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use tracing::warn;
+
+/// Paths created by deployment outside of the mod roots, relative to the game root.
+/// The cleanup scan only walks the mod roots, so files scattered by external tools are
+/// recorded here before they are created and removed from here symmetrically.
+/// Copies have no link back to the repo, so every one of them is recorded as well.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeployLedger {
+    /// Link point -> owning mod id.
+    pub links: BTreeMap<Utf8PathBuf, String>,
+    /// Directories that did not exist before deployment.
+    pub created_dirs: BTreeSet<Utf8PathBuf>,
+    /// Files deployed as copies, inside the mod roots or not.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub copies: BTreeMap<Utf8PathBuf, DeployedCopy>,
+}
+
+/// A file copied into the game root, see `DeploymentMode::Copy`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeployedCopy {
+    pub mod_id: String,
+    /// Digest of the content as copied, telling our copy apart from a file edited since.
+    pub digest: String,
+}
+
+impl DeployLedger {
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty() && self.created_dirs.is_empty() && self.copies.is_empty()
+    }
+
+    /// Adds the external part of a deployment about to happen.
+    pub fn record<'a>(
+        &mut self,
+        spt_rules: &SPTPathRules,
+        links: impl IntoIterator<Item = (&'a str, &'a Utf8Path)>,
+        created_dirs: impl IntoIterator<Item = &'a Utf8Path>,
+    ) {
+        self.links.extend(
+            links
+                .into_iter()
+                .filter(|(_, rel)| is_external(rel, spt_rules))
+                .map(|(id, rel)| (rel.to_path_buf(), id.to_string())),
+        );
+        self.created_dirs.extend(
+            created_dirs
+                .into_iter()
+                .filter(|rel| is_external(rel, spt_rules))
+                .map(Utf8Path::to_path_buf),
+        );
+    }
+}
+
+/// True for paths neither inside a mod root nor above one.
+pub fn is_external(rel: &Utf8Path, spt_rules: &SPTPathRules) -> bool {
+    !spt_rules
+        .mod_roots()
+        .any(|root| rel.starts_with(root) || root.starts_with(rel))
+}
+
+/// Reads the ledger. A missing one means nothing was deployed outside the mod roots.
+pub fn read(lib_paths: &LibPathRules) -> Result<DeployLedger, SError> {
+    match fs::read(&lib_paths.deploy_ledger) {
+        Ok(bytes) => MsgPack::from_slice(&bytes),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(DeployLedger::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes the ledger, removing the file once everything it recorded is gone.
+pub fn write(lib_paths: &LibPathRules, ledger: &DeployLedger) -> Result<(), SError> {
+    if !ledger.is_empty() {
+        fs::write(&lib_paths.deploy_ledger, MsgPack::to_vec(ledger)?)?;
+        return Ok(());
+    }
+
+    match fs::remove_file(&lib_paths.deploy_ledger) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Removes the recorded links that still point to the repo and the created directories
+/// that are empty again, deepest first.
+/// `is_managed` decides whether an existing path is one of our links; anything else
+/// was replaced by the user and is left alone.
+/// Returns what could not be removed, which stays recorded for the next cleanup.
+pub fn remove_recorded(
+    game_root: &Utf8Path,
+    ledger: DeployLedger,
+    is_managed: impl Fn(&Utf8Path) -> bool,
+) -> Result<DeployLedger, SError> {
+    let mut remaining = DeployLedger {
+        copies: ledger.copies,
+        ..DeployLedger::default()
+    };
+
+    for (rel, id) in ledger.links {
+        let path = game_root.join(&rel);
+        if fs::symlink_metadata(&path).is_err() || !is_managed(&path) {
+            continue;
+        }
+        if let Err(e) = linker::unlink(&path) {
+            warn!("Failed to remove {path} deployed by {id}: {e}");
+            remaining.links.insert(rel, id);
+        }
+    }
+
+    for rel in ledger.created_dirs.into_iter().rev() {
+        let path = game_root.join(&rel);
+        if !path.is_dir() {
+            continue;
+        }
+        if fs::remove_dir(&path).is_err() {
+            remaining.created_dirs.insert(rel);
+        }
+    }
+
+    Ok(remaining)
+}
+
+/// Whether the file at `path` is still the copy that was deployed.
+pub fn is_unchanged(path: &Utf8Path, copy: &DeployedCopy) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|meta| meta.is_file())
+        && local_edits::stamp_of(path).is_ok_and(|stamp| stamp.digest == copy.digest)
+}
+
+/// Removes the recorded copies `is_selected` picks, along with the folders they leave empty
+/// inside the mod roots. A copy edited since it was deployed belongs to the user now: it is
+/// kept and no longer recorded. Copies that cannot be removed stay recorded.
+/// Returns the removed paths, relative to the game root.
+pub fn remove_copies(
+    game_root: &Utf8Path,
+    spt_rules: &SPTPathRules,
+    ledger: &mut DeployLedger,
+    is_selected: impl Fn(&Utf8Path, &DeployedCopy) -> bool,
+) -> Vec<Utf8PathBuf> {
+    let selected: Vec<(Utf8PathBuf, DeployedCopy)> = ledger
+        .copies
+        .iter()
+        .filter(|(rel, copy)| is_selected(rel, copy))
+        .map(|(rel, copy)| (rel.clone(), copy.clone()))
+        .collect();
+
+    let mut removed = Vec::new();
+    for (rel, copy) in selected {
+        let path = game_root.join(&rel);
+        if !is_unchanged(&path, &copy) {
+            if path.exists() {
+                warn!(
+                    "Kept {path} deployed by {}, which was edited since",
+                    copy.mod_id
+                );
+            }
+            ledger.copies.remove(&rel);
+            continue;
+        }
+        if let Err(e) = fs::remove_file(&path) {
+            warn!("Failed to remove {path} deployed by {}: {e}", copy.mod_id);
+            continue;
+        }
+        ledger.copies.remove(&rel);
+        remove_empty_parents(game_root, spt_rules, &rel);
+        removed.push(rel);
+    }
+    removed
+}
+
+/// Stops at the first folder still holding something, and never goes up to a mod root.
+fn remove_empty_parents(game_root: &Utf8Path, spt_rules: &SPTPathRules, rel: &Utf8Path) {
+    let _ = rel
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| {
+            spt_rules
+                .mod_roots()
+                .any(|root| dir.starts_with(root) && *dir != root)
+        })
+        .try_for_each(|dir| fs::remove_dir(game_root.join(dir)));
+}
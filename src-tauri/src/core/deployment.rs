@@ -1,20 +1,45 @@
 use crate::core::cache::LibraryCache;
-use crate::core::linker;
-use crate::models::error::SError;
+use crate::core::deploy_journal::{self, Action};
+use crate::core::deploy_ledger::{self, DeployLedger, DeployedCopy};
+use crate::core::linker::{self, LinkKind};
+use crate::core::local_edits;
+use crate::core::metrics;
+use crate::core::ownership;
+use crate::core::profile_wipe;
+use crate::core::repo_store::{self, RepoStore};
+use crate::core::sync_index::{self, SyncIndex};
+use crate::models::deployment_plan::{DeploymentPlan, PlannedLink, SyncScope};
+use crate::models::error::{LinkFailure, SError};
+use crate::models::global::LinkFailurePolicy;
+use crate::models::library::DeploymentMode;
+use crate::models::metrics::Operation;
 use crate::models::mod_dto::Mod;
 use crate::models::paths::{LibPathRules, SPTPathRules};
+use crate::models::warning::{OperationWarning, WarningKind};
+use crate::utils::naming;
+use crate::utils::time::get_unix_timestamp;
 use camino::{Utf8Path, Utf8PathBuf};
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io;
+use tracing::warn;
 
 type OwnershipMap = HashMap<Utf8PathBuf, Vec<String>>;
 
+/// Link points (mod id, relative path) and the shared directories above them.
+#[derive(Default)]
+struct LinkLayout<'a> {
+    links: BTreeSet<(&'a str, Utf8PathBuf)>,
+    shared_dirs: BTreeSet<Utf8PathBuf>,
+}
+
 // --- Protected Path Helpers ---
 
 /// Returns a vector of protected system root paths (relative paths).
 /// These paths should never be removed during mod operations.
 /// This is the single source of truth for protected paths - add new paths here.
 pub fn get_protected_paths(spt_rules: &SPTPathRules) -> Vec<&Utf8Path> {
-    vec![&spt_rules.server_mods, &spt_rules.client_plugins]
+    spt_rules.mod_roots().collect()
 }
 
 /// Returns a vector of protected system root paths as absolute paths.
@@ -50,23 +75,320 @@ pub fn is_protected_path_absolute(
 }
 
 /// Entry point for deployment logic.
-/// Performs conflict detection and recursive linking of active mods.
+/// Performs conflict detection and recursive linking of active mods, by mod then path, or
+/// copies every active file in `DeploymentMode::Copy`.
+/// Failed links end in a single `SError::LinkFailed`, see `LinkFailurePolicy`.
+/// Returns a warning per mod whose files vanished right after being linked, see `verify`.
 pub fn deploy(
     game_root: &Utf8Path,
     lib_paths: &LibPathRules,
     spt_rules: &SPTPathRules,
     mods: &BTreeMap<String, Mod>,
     cache: &LibraryCache,
-) -> Result<(), SError> {
-    check_file_collisions(mods, cache)?;
+    policy: LinkFailurePolicy,
+    mode: DeploymentMode,
+) -> Result<Vec<OperationWarning>, SError> {
+    let warnings = deploy_active(game_root, lib_paths, spt_rules, mods, cache, policy, mode)?;
+
+    // A stale index only widens the next check, so failing to update it is not fatal
+    if let Err(e) = sync_index::write(lib_paths, &SyncIndex::build(mods, cache)) {
+        warn!("Failed to update the sync index: {e}");
+    }
+    Ok(warnings)
+}
+
+/// Deploys only the active files within `scope`, see `SyncScope`.
+/// The sync index describes full deployments, so it is dropped after a partial one and the
+/// next switch falls back to a full sync.
+#[allow(clippy::too_many_arguments)]
+pub fn deploy_scoped(
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    spt_rules: &SPTPathRules,
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+    policy: LinkFailurePolicy,
+    scope: SyncScope,
+    mode: DeploymentMode,
+) -> Result<Vec<OperationWarning>, SError> {
+    if scope == SyncScope::All {
+        return deploy(game_root, lib_paths, spt_rules, mods, cache, policy, mode);
+    }
+
+    let scoped = scope_cache(cache, spt_rules, scope);
+    let warnings = deploy_active(game_root, lib_paths, spt_rules, mods, &scoped, policy, mode)?;
+    sync_index::clear(lib_paths)?;
+    Ok(warnings)
+}
+
+fn deploy_active(
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    spt_rules: &SPTPathRules,
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+    policy: LinkFailurePolicy,
+    mode: DeploymentMode,
+) -> Result<Vec<OperationWarning>, SError> {
+    let _timer = metrics::Timer::start(Operation::Deploy);
+    let index = sync_index::read(lib_paths);
+    check_file_collisions(mods, cache, index.as_ref())?;
+
+    if mode == DeploymentMode::Copy {
+        execute_copy(game_root, lib_paths, spt_rules, mods, cache, policy)?;
+        return Ok(verify(game_root, mods, cache));
+    }
 
     let folder_ownership = build_folder_ownership_map(spt_rules, mods, cache);
+    let layout = resolve_link_layout(mods, cache, &folder_ownership)?;
+
+    record_external(game_root, lib_paths, spt_rules, &layout)?;
+    let mut warnings = execute_recursive_link(game_root, lib_paths, &layout, policy)?;
+    warnings.extend(verify(game_root, mods, cache));
+    Ok(warnings)
+}
+
+/// Copy of the cache listing only the files within `scope`.
+pub fn scope_cache(
+    cache: &LibraryCache,
+    spt_rules: &SPTPathRules,
+    scope: SyncScope,
+) -> LibraryCache {
+    let mut scoped = cache.clone();
+    scoped
+        .mods
+        .values_mut()
+        .for_each(|fs| fs.files.retain(|rel| scope.covers(rel, spt_rules)));
+    scoped
+}
+
+/// Moves the deployment from the mods of the last sync to the active ones, only unlinking and
+/// linking the difference so switching between sets of mods stays near-instant.
+/// Returns `None` when the last deployment is unknown or the file lists of its mods changed
+/// since, in which case a full purge and deploy is needed.
+/// Copies are compared with the ledger instead, see `copy_delta`.
+pub fn deploy_delta(
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    spt_rules: &SPTPathRules,
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+    policy: LinkFailurePolicy,
+    mode: DeploymentMode,
+) -> Result<Option<Vec<OperationWarning>>, SError> {
+    let _timer = metrics::Timer::start(Operation::Deploy);
+    let Some(index) = sync_index::read(lib_paths) else {
+        return Ok(None);
+    };
+
+    let deployed: BTreeMap<String, Mod> = mods
+        .iter()
+        .map(|(id, m)| {
+            let is_active = index.digests.contains_key(id);
+            (
+                id.clone(),
+                Mod {
+                    is_active,
+                    ..m.clone()
+                },
+            )
+        })
+        .collect();
+    let known = index
+        .digests
+        .keys()
+        .all(|id| deployed.contains_key(id) && cache.mods.contains_key(id));
+    if !known || !index.changed_mods(&deployed, cache).is_empty() {
+        return Ok(None);
+    }
 
-    execute_recursive_link(game_root, lib_paths, mods, cache, &folder_ownership)
+    check_file_collisions(mods, cache, Some(&index))?;
+    let mut warnings = match mode {
+        DeploymentMode::Link => link_delta(
+            game_root, lib_paths, spt_rules, mods, &deployed, cache, policy,
+        )?,
+        DeploymentMode::Copy => {
+            copy_delta(game_root, lib_paths, spt_rules, mods, cache, policy)?;
+            Vec::new()
+        }
+    };
+
+    if let Err(e) = sync_index::write(lib_paths, &SyncIndex::build(mods, cache)) {
+        warn!("Failed to update the sync index: {e}");
+    }
+    warnings.extend(verify(game_root, mods, cache));
+    Ok(Some(warnings))
+}
+
+/// Unlinks what the last sync linked and the active mods no longer need, then links the rest.
+fn link_delta(
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    spt_rules: &SPTPathRules,
+    mods: &BTreeMap<String, Mod>,
+    deployed: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+    policy: LinkFailurePolicy,
+) -> Result<Vec<OperationWarning>, SError> {
+    let old_ownership = build_folder_ownership_map(spt_rules, deployed, cache);
+    let old = resolve_link_layout(deployed, cache, &old_ownership)?;
+    let new_ownership = build_folder_ownership_map(spt_rules, mods, cache);
+    let new = resolve_link_layout(mods, cache, &new_ownership)?;
+
+    // Another manager may have replaced a link since, which is then left to it
+    let store = repo_store::open(lib_paths);
+    old.links
+        .difference(&new.links)
+        .map(|(id, rel)| (store.payload_dir(id).join(rel), game_root.join(rel)))
+        .filter(|(_, dst)| !ownership::is_foreign(dst, store.root()))
+        .try_for_each(|(source, path)| unlink_journaled(lib_paths, source, &path))?;
+    // Children sort after their parents, so reversing empties folders before removing them
+    let dropped_dirs: Vec<&Utf8PathBuf> = old.shared_dirs.difference(&new.shared_dirs).collect();
+    dropped_dirs
+        .into_iter()
+        .rev()
+        .map(|dir| game_root.join(dir))
+        .filter_map(|dir| linker::unlink(&dir).err().map(|e| (dir, e)))
+        .for_each(|(dir, e)| warn!("Kept {dir}, which is no longer needed: {e}"));
+
+    record_external(game_root, lib_paths, spt_rules, &new)?;
+    let kept = |link: &(&str, Utf8PathBuf)| old.links.contains(link);
+    execute_link(game_root, lib_paths, &new, kept, policy)
+}
+
+/// Runs the linking part of `deploy` into `scratch` instead of the game root, so collisions,
+/// permissions and link failures surface exactly as a real sync would report them.
+/// Path lengths are checked against the game root, where the links would really go.
+/// Neither the deploy ledger nor the sync index is touched.
+pub fn deploy_into(
+    scratch: &Utf8Path,
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    spt_rules: &SPTPathRules,
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+    policy: LinkFailurePolicy,
+) -> Result<Vec<OperationWarning>, SError> {
+    let index = sync_index::read(lib_paths);
+    check_file_collisions(mods, cache, index.as_ref())?;
+
+    let files: Vec<Utf8PathBuf> = iter_active_files(mods, cache)
+        .map(|(rel, _)| rel.to_path_buf())
+        .collect();
+    naming::ensure_fits(game_root, &files)?;
+
+    let folder_ownership = build_folder_ownership_map(spt_rules, mods, cache);
+    let layout = resolve_link_layout(mods, cache, &folder_ownership)?;
+    let mut warnings = execute_recursive_link(scratch, lib_paths, &layout, policy)?;
+    warnings.extend(verify(scratch, mods, cache));
+    Ok(warnings)
+}
+
+/// Computes what `deploy` would link without touching the game root.
+/// Collisions are reported in the plan instead of failing, along with changes that call for a profile wipe.
+pub fn plan(
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    spt_rules: &SPTPathRules,
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+) -> Result<DeploymentPlan, SError> {
+    let index = sync_index::read(lib_paths);
+    let collisions = find_file_collisions(mods, cache, index.as_ref());
+    let folder_ownership = build_folder_ownership_map(spt_rules, mods, cache);
+    let layout = resolve_link_layout(mods, cache, &folder_ownership)?;
+
+    let store = repo_store::open(lib_paths);
+    let links: Vec<PlannedLink> = layout
+        .links
+        .into_iter()
+        .map(|(id, rel)| PlannedLink {
+            mod_id: id.to_string(),
+            source: store.payload_dir(id).join(&rel),
+            target: game_root.join(rel),
+        })
+        .collect();
+    let created_dirs = links
+        .iter()
+        .flat_map(|link| {
+            link.target
+                .ancestors()
+                .skip(1)
+                .take_while(|dir| *dir != game_root)
+        })
+        .filter(|dir| !dir.exists())
+        .map(Utf8Path::to_path_buf)
+        .collect::<BTreeSet<Utf8PathBuf>>();
+
+    Ok(DeploymentPlan {
+        timestamp: get_unix_timestamp().to_string(),
+        links,
+        unlinks: Vec::new(),
+        created_dirs: created_dirs.into_iter().collect(),
+        collisions: collisions.into_iter().collect(),
+        warnings: profile_wipe::detect(mods, cache, index.as_ref()),
+        compat_notes: Vec::new(),
+    })
 }
 
 /// Validates that no two active mods provide the same file.
-fn check_file_collisions(mods: &BTreeMap<String, Mod>, cache: &LibraryCache) -> Result<(), SError> {
+fn check_file_collisions(
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+    index: Option<&SyncIndex>,
+) -> Result<(), SError> {
+    let collisions = find_file_collisions(mods, cache, index);
+
+    if collisions.is_empty() {
+        return Ok(());
+    }
+
+    Err(SError::FileCollision(collisions.into_iter().collect()))
+}
+
+/// Lists files provided by more than one active mod.
+/// With the index of the last successful sync, only files of mods that changed since then are
+/// checked: the unchanged mods were already collision free among themselves.
+fn find_file_collisions(
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+    index: Option<&SyncIndex>,
+) -> BTreeSet<String> {
+    let Some(index) = index else {
+        return find_all_file_collisions(mods, cache);
+    };
+
+    let changed = index.changed_mods(mods, cache);
+    let is_unchanged_active =
+        |id: &str| !changed.contains(id) && mods.get(id).is_some_and(|m| m.is_active);
+
+    let mut owners: HashMap<&Utf8Path, &str> = HashMap::new();
+    let mut collisions = BTreeSet::new();
+
+    for (path, current_id) in iter_active_files(mods, cache).filter(|(_, id)| changed.contains(*id))
+    {
+        // Against the other changed mods
+        if let Some(existing_owner) = owners.insert(path, current_id).filter(|o| *o != current_id) {
+            collisions.insert(collision_message(path, existing_owner, current_id));
+        }
+
+        // Against the unchanged mods deployed by the last sync
+        if let Some(indexed_owner) = index
+            .owners
+            .get(path)
+            .filter(|o| o.as_str() != current_id && is_unchanged_active(o))
+        {
+            collisions.insert(collision_message(path, indexed_owner, current_id));
+        }
+    }
+
+    collisions
+}
+
+fn find_all_file_collisions(
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+) -> BTreeSet<String> {
     let mut owners: HashMap<Utf8PathBuf, String> = HashMap::new();
     let mut collisions = BTreeSet::new();
 
@@ -76,18 +398,20 @@ fn check_file_collisions(mods: &BTreeMap<String, Mod>, cache: &LibraryCache) ->
         };
 
         if existing_owner != current_id {
-            collisions.insert(format!(
-                "File Conflict: '{}' is provided by both '{}' and '{}'.",
-                path, existing_owner, current_id
-            ));
+            collisions.insert(collision_message(path, &existing_owner, current_id));
         }
     }
 
-    if collisions.is_empty() {
-        return Ok(());
-    }
+    collisions
+}
 
-    Err(SError::FileCollision(collisions.into_iter().collect()))
+/// Mod ids are ordered so both checks report a pair the same way.
+fn collision_message(path: &Utf8Path, a: &str, b: &str) -> String {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    format!(
+        "File Conflict: '{}' is provided by both '{}' and '{}'.",
+        path, first, second
+    )
 }
 
 fn build_folder_ownership_map(
@@ -114,44 +438,339 @@ fn build_folder_ownership_map(
     acc
 }
 
+/// Walks every active file from the root down and stops at the first path owned by a single mod.
+/// That path becomes the link point; the shared ancestors above it must exist as real directories.
+fn resolve_link_layout<'a>(
+    mods: &'a BTreeMap<String, Mod>,
+    cache: &'a LibraryCache,
+    ownership: &OwnershipMap,
+) -> Result<LinkLayout<'a>, SError> {
+    let mut layout = LinkLayout::default();
+
+    for (file_path, id) in iter_active_files(mods, cache) {
+        let mut current_path = Utf8PathBuf::new();
+
+        for component in file_path.components() {
+            current_path.push(component);
+
+            let owners = ownership.get(&current_path).ok_or_else(|| {
+                SError::ParseError(format!("Missing ownership for '{}'", current_path))
+            })?;
+
+            // Case A: Unique Ownership -> Link high level directory/file and exit file loop
+            if owners.len() == 1 {
+                layout.links.insert((id, current_path.clone()));
+                break;
+            }
+
+            // Case B: Shared -> This is a parent directory. It must physically exist.
+            layout.shared_dirs.insert(current_path.clone());
+        }
+    }
+
+    Ok(layout)
+}
+
+/// Records links and directories about to be created outside of the mod roots before
+/// touching the game root, so an interrupted deployment can still be cleaned up.
+fn record_external(
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    spt_rules: &SPTPathRules,
+    layout: &LinkLayout,
+) -> Result<(), SError> {
+    let mut ledger = deploy_ledger::read(lib_paths)?;
+    ledger.record(
+        spt_rules,
+        layout.links.iter().map(|(id, rel)| (*id, rel.as_path())),
+        layout
+            .shared_dirs
+            .iter()
+            .map(Utf8PathBuf::as_path)
+            .filter(|dir| !game_root.join(dir).exists()),
+    );
+    deploy_ledger::write(lib_paths, &ledger)
+}
+
 fn execute_recursive_link(
     game_root: &Utf8Path,
     lib_paths: &LibPathRules,
+    layout: &LinkLayout,
+    policy: LinkFailurePolicy,
+) -> Result<Vec<OperationWarning>, SError> {
+    execute_link(game_root, lib_paths, layout, |_| false, policy)
+}
+
+/// Creates the shared directories and links of a layout.
+/// Returns a warning per mod whose files fell back to symlinks, see `LinkKind::CrossDeviceSymlink`.
+/// Links already deployed by the previous layout are replaced when stale: hard links keep
+/// pointing at the old payload once a mod folder was swapped in the repo.
+/// Links are made in `LinkLayout` order, so the failures reported are the same on every run.
+fn execute_link(
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    layout: &LinkLayout,
+    is_deployed: impl Fn(&(&str, Utf8PathBuf)) -> bool,
+    policy: LinkFailurePolicy,
+) -> Result<Vec<OperationWarning>, SError> {
+    // BTreeSet ordering guarantees parents are created before their children
+    layout
+        .shared_dirs
+        .iter()
+        .map(|dir| game_root.join(dir))
+        .filter(|dir| !dir.exists())
+        .try_for_each(|path| {
+            deploy_journal::record(lib_paths, Action::CreateDir { path: path.clone() })?;
+            fs::create_dir_all(path)
+        })?;
+
+    let store = repo_store::open(lib_paths);
+    let mut fallbacks = BTreeMap::<&str, Vec<&Utf8Path>>::new();
+    let mut failures = Vec::new();
+    for link in &layout.links {
+        let (id, rel) = link;
+        let src = store.payload_dir(id).join(rel);
+        let dst = game_root.join(rel);
+        let result = match link_journaled(lib_paths, &src, &dst) {
+            Err(e)
+                if e.kind() == io::ErrorKind::AlreadyExists
+                    && is_deployed(link)
+                    && !ownership::is_foreign(&dst, store.root()) =>
+            {
+                unlink_journaled(lib_paths, src.clone(), &dst)
+                    .and_then(|_| link_journaled(lib_paths, &src, &dst))
+            }
+            result => result,
+        };
+        match result {
+            Ok(LinkKind::CrossDeviceSymlink) => fallbacks.entry(id).or_default().push(rel),
+            Ok(_) => {}
+            Err(e) => {
+                warn!("Failed to link {rel} of {id}: {e}");
+                failures.push(LinkFailure {
+                    mod_id: id.to_string(),
+                    path: rel.to_string(),
+                    error: e.to_string(),
+                });
+                if policy == LinkFailurePolicy::Abort {
+                    break;
+                }
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(SError::LinkFailed(failures));
+    }
+    Ok(fallback_warnings(fallbacks))
+}
+
+/// Links `dst` to `src`, journaling the link when it is new, see `deploy_journal`.
+/// An existing link is not journaled, so rolling back never removes what was there before.
+fn link_journaled(
+    lib_paths: &LibPathRules,
+    src: &Utf8Path,
+    dst: &Utf8Path,
+) -> io::Result<LinkKind> {
+    if fs::symlink_metadata(dst).is_err() {
+        deploy_journal::record(
+            lib_paths,
+            Action::Link {
+                path: dst.to_path_buf(),
+                source: src.to_path_buf(),
+            },
+        )?;
+    }
+    linker::link(src, dst)
+}
+
+fn unlink_journaled(
+    lib_paths: &LibPathRules,
+    source: Utf8PathBuf,
+    path: &Utf8Path,
+) -> io::Result<()> {
+    let action = Action::Unlink {
+        path: path.to_path_buf(),
+        source,
+    };
+    deploy_journal::record(lib_paths, action)?;
+    linker::unlink(path)
+}
+
+/// A warning per mod whose files were symlinked because the repo is on another volume.
+fn fallback_warnings(fallbacks: BTreeMap<&str, Vec<&Utf8Path>>) -> Vec<OperationWarning> {
+    fallbacks
+        .into_iter()
+        .map(|(id, files)| {
+            warn!(
+                "{} file(s) of {id} were symlinked across volumes",
+                files.len()
+            );
+            OperationWarning::new(WarningKind::CrossDeviceLinks, id).with_details(&files)
+        })
+        .collect()
+}
+
+/// Copies every active file into the game root, recording each copy in the deploy ledger with
+/// its digest so cleanup can tell it apart from files put there by the user.
+/// An existing file is only replaced when it is a recorded copy left as it was deployed;
+/// anything else fails like a link would. Copies that are already up to date are skipped.
+/// The ledger is written even when a copy fails, so cleanup still finds the others.
+fn execute_copy(
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    spt_rules: &SPTPathRules,
     mods: &BTreeMap<String, Mod>,
     cache: &LibraryCache,
-    ownership: &OwnershipMap,
+    policy: LinkFailurePolicy,
 ) -> Result<(), SError> {
-    cache
-        .mods
+    let files: Vec<(&Utf8Path, &str)> = iter_active_files(mods, cache).collect();
+    let mut ledger = deploy_ledger::read(lib_paths)?;
+    let created_dirs: BTreeSet<&Utf8Path> = files
         .iter()
-        .filter(|(id, _)| mods.get(*id).map_or(false, |m| m.is_active))
-        .flat_map(|(id, m_fs)| m_fs.files.iter().map(move |f| (id, f)))
-        .try_for_each(|(id, file_path)| {
-            let mut current_path = Utf8PathBuf::new();
-
-            for component in file_path.components() {
-                current_path.push(component);
-
-                let owners = ownership.get(&current_path).ok_or_else(|| {
-                    SError::ParseError(format!("Missing ownership for '{}'", current_path))
-                })?;
-
-                // Case A: Unique Ownership -> Link high level directory/file and exit file loop
-                if owners.len() == 1 {
-                    let src = lib_paths.mods.join(id).join(&current_path);
-                    let dst = game_root.join(&current_path);
-                    linker::link(&src, &dst)?;
-                    return Ok(());
-                }
+        .flat_map(|(rel, _)| rel.ancestors().skip(1))
+        .filter(|dir| !dir.as_str().is_empty() && !game_root.join(dir).exists())
+        .collect();
+    ledger.record(spt_rules, std::iter::empty(), created_dirs.iter().copied());
+    deploy_ledger::write(lib_paths, &ledger)?;
+    created_dirs
+        .into_iter()
+        .map(|dir| Action::CreateDir {
+            path: game_root.join(dir),
+        })
+        .try_for_each(|action| deploy_journal::record(lib_paths, action))?;
+
+    let store = repo_store::open(lib_paths);
+    let mut failures = Vec::new();
+    for (rel, id) in files {
+        let copied = copy_file(
+            game_root,
+            lib_paths,
+            store.as_ref(),
+            cache,
+            &mut ledger,
+            rel,
+            id,
+        );
+        let Err(e) = copied else {
+            continue;
+        };
+        warn!("Failed to copy {rel} of {id}: {e}");
+        failures.push(LinkFailure {
+            mod_id: id.to_string(),
+            path: rel.to_string(),
+            error: e.to_string(),
+        });
+        if policy == LinkFailurePolicy::Abort {
+            break;
+        }
+    }
 
-                // Case B: Shared -> This is a parent directory. Ensure physical dir exists.
-                let shared_dir = game_root.join(&current_path);
-                if !shared_dir.exists() {
-                    std::fs::create_dir_all(&shared_dir)?;
-                }
-            }
-            Ok(())
+    deploy_ledger::write(lib_paths, &ledger)?;
+    if failures.is_empty() {
+        return Ok(());
+    }
+    Err(SError::LinkFailed(failures))
+}
+
+fn copy_file(
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    store: &dyn RepoStore,
+    cache: &LibraryCache,
+    ledger: &mut DeployLedger,
+    rel: &Utf8Path,
+    id: &str,
+) -> Result<(), SError> {
+    let src = store.payload_dir(id).join(rel);
+    let dst = game_root.join(rel);
+    // The idle hasher usually knows the digest already
+    let digest = match cache.mods.get(id).and_then(|fs| fs.stamps.get(rel)) {
+        Some(stamp) if local_edits::is_current(&src, stamp) => stamp.digest.clone(),
+        _ => local_edits::stamp_of(&src)?.digest,
+    };
+
+    if fs::symlink_metadata(&dst).is_ok() {
+        let recorded = ledger
+            .copies
+            .get(rel)
+            .filter(|copy| deploy_ledger::is_unchanged(&dst, copy));
+        match recorded {
+            Some(copy) if copy.mod_id == id && copy.digest == digest => return Ok(()),
+            Some(_) => {}
+            None => return Err(io::Error::from(io::ErrorKind::AlreadyExists).into()),
+        }
+    }
+
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    deploy_journal::record(
+        lib_paths,
+        Action::Copy {
+            path: dst.clone(),
+            source: src.clone(),
+        },
+    )?;
+    fs::copy(&src, &dst)?;
+    ledger.copies.insert(
+        rel.to_path_buf(),
+        DeployedCopy {
+            mod_id: id.to_string(),
+            digest,
+        },
+    );
+    Ok(())
+}
+
+/// Removes the copies no longer deployed by the mod that copied them, then copies what is
+/// missing or out of date.
+fn copy_delta(
+    game_root: &Utf8Path,
+    lib_paths: &LibPathRules,
+    spt_rules: &SPTPathRules,
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+    policy: LinkFailurePolicy,
+) -> Result<(), SError> {
+    let owners: HashMap<&Utf8Path, &str> = iter_active_files(mods, cache).collect();
+    let mut ledger = deploy_ledger::read(lib_paths)?;
+    deploy_ledger::remove_copies(game_root, spt_rules, &mut ledger, |rel, copy| {
+        owners.get(rel) != Some(&copy.mod_id.as_str())
+    });
+    deploy_ledger::write(lib_paths, &ledger)?;
+    execute_copy(game_root, lib_paths, spt_rules, mods, cache, policy)
+}
+
+/// Checks that every file of the active mods resolves in the game root.
+/// Real-time scanners tend to delete freshly linked DLLs (or their source in the repo), which
+/// leaves a deploy that looks successful but is missing files. Returns a warning per affected mod.
+pub fn verify(
+    game_root: &Utf8Path,
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+) -> Vec<OperationWarning> {
+    let missing = iter_active_files(mods, cache)
+        .filter(|(rel, _)| !game_root.join(rel).exists())
+        .fold(
+            BTreeMap::<&str, Vec<&Utf8Path>>::new(),
+            |mut acc, (rel, id)| {
+                acc.entry(id).or_default().push(rel);
+                acc
+            },
+        );
+
+    missing
+        .into_iter()
+        .map(|(id, files)| {
+            warn!(
+                "{} file(s) of {id} disappeared after deploy, possibly removed by an antivirus: {files:?}",
+                files.len()
+            );
+            OperationWarning::new(WarningKind::PossibleAntivirusInterference, id)
+                .with_details(&files)
         })
+        .collect()
 }
 
 // --- Iteration Helpers ---
@@ -0,0 +1,130 @@
+use crate::core::library::Library;
+use crate::core::mod_fs::ModFS;
+use crate::core::progress::Progress;
+use crate::core::repo_store;
+use crate::core::shared_state::SharedState;
+use crate::models::error::SError;
+use crate::utils::scan;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{error, info};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Changes must settle this long before reimporting, so a build writing many files
+/// triggers a single reimport.
+const DEBOUNCE: Duration = Duration::from_millis(1500);
+
+/// Relative file path -> (size, modification time)
+pub type SourceSnapshot = BTreeMap<Utf8PathBuf, (u64, Option<SystemTime>)>;
+
+/// Captures the size and modification time of every file under the source folder.
+pub fn snapshot(source: &Utf8Path) -> SourceSnapshot {
+    scan::walk(source)
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            let path = Utf8Path::from_path(e.path())?;
+            let rel = path.strip_prefix(source).ok()?.to_path_buf();
+            Some((rel, (meta.len(), meta.modified().ok())))
+        })
+        .collect()
+}
+
+/// Replaces the repo copy of a mod with the content of its development source folder.
+/// Unlike `add_mod`, no backup is created: dev iterations would flood the backups folder.
+pub fn reimport(library: &mut Library, mod_id: &str, source: &Utf8Path) -> Result<(), SError> {
+    if !library.mods.contains_key(mod_id) {
+        return Err(SError::ModNotFound(mod_id.to_string()));
+    }
+
+    // The source is explicitly linked to this mod, so keep its ID even if the build output
+    // would resolve to a different one (e.g. no manifest yet).
+    let mut fs = ModFS::new(source, &library.spt_rules)?;
+    fs.id = mod_id.to_string();
+
+    let store = repo_store::open(&library.lib_paths);
+    store.put(mod_id, source, Progress::silent(), mod_id)?;
+
+    if let Some(mod_entry) = library.mods.get_mut(mod_id) {
+        mod_entry.mod_type = fs.mod_type.clone();
+        mod_entry.icon_data = None;
+    }
+
+    library.cache.add(&store.payload_dir(mod_id), fs);
+    library.mark_dirty();
+    library.persist()
+}
+
+/// Stops the watcher thread when dropped.
+pub struct WatchHandle {
+    pub source: Utf8PathBuf,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Polls the source folder and reimports the mod into the active library once changes settle.
+/// The watcher exits on its own when the active library is switched.
+pub fn spawn_watcher(
+    shared: SharedState,
+    repo_root: Utf8PathBuf,
+    mod_id: String,
+    source: Utf8PathBuf,
+) -> WatchHandle {
+    let stop = Arc::new(AtomicBool::new(false));
+    let handle = WatchHandle {
+        source: source.clone(),
+        stop: stop.clone(),
+    };
+
+    std::thread::spawn(move || {
+        let mut last = snapshot(&source);
+        let mut pending_since: Option<Instant> = None;
+
+        while !stop.load(Ordering::Relaxed) {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let current = snapshot(&source);
+            if current != last {
+                last = current;
+                pending_since = Some(Instant::now());
+                continue;
+            }
+
+            let Some(since) = pending_since else {
+                continue;
+            };
+            if since.elapsed() < DEBOUNCE {
+                continue;
+            }
+            pending_since = None;
+
+            let result = shared
+                .with_lib_mut(|lib| {
+                    if lib.repo_root != repo_root {
+                        return Err(SError::NoActiveLibrary);
+                    }
+                    reimport(lib, &mod_id, &source)
+                })
+                .and_then(|r| r);
+
+            match result {
+                Ok(_) => info!("Reimported {mod_id} from {source}"),
+                Err(SError::NoActiveLibrary) => break,
+                Err(e) => error!("Failed to reimport {mod_id} from {source}: {e}"),
+            }
+        }
+
+        info!("Stopped watching {source} for {mod_id}");
+    });
+
+    handle
+}
@@ -0,0 +1,112 @@
+use crate::core::progress::Progress;
+use crate::core::update_checker;
+use crate::models::error::SError;
+use crate::utils::naming;
+use camino::{Utf8Path, Utf8PathBuf};
+use regex::Regex;
+use reqwest::blocking::Client;
+use reqwest::header::ACCEPT;
+use reqwest::Url;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+
+/// Size and digest published next to a download, checked once it arrived.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Expected {
+    pub size: Option<u64>,
+    /// Hex encoded SHA-256.
+    pub sha256: Option<String>,
+}
+
+/// The file behind a URL: itself for a direct link, the latest release for a Forge mod page.
+pub fn resolve(client: &Client, url: &str) -> Result<String, SError> {
+    let Some(hub_id) = forge_id(url) else {
+        return Ok(url.to_string());
+    };
+    let body: Value = client
+        .get(update_checker::forge_versions_url(hub_id))
+        .header(ACCEPT, "application/json")
+        .send()?
+        .error_for_status()?
+        .json()?;
+    update_checker::parse(&body)
+        .and_then(|release| release.download_url)
+        .ok_or_else(|| SError::DownloadFailed(format!("No download offered at {url}")))
+}
+
+/// Hub id of a Forge mod page, e.g. `https://forge.sp-tarkov.com/mod/1234/some-mod`.
+pub fn forge_id(url: &str) -> Option<u64> {
+    let re = Regex::new(r"^https?://forge\.sp-tarkov\.com/mods?/(\d+)").ok()?;
+    re.captures(url)?.get(1)?.as_str().parse().ok()
+}
+
+/// Downloads `url` into `dir`, named after the last segment of the URL, reporting the
+/// received bytes. The file is verified against `expected` and removed when it does not match.
+pub fn fetch(
+    client: &Client,
+    url: &str,
+    dir: &Utf8Path,
+    expected: &Expected,
+    progress: Progress,
+) -> Result<Utf8PathBuf, SError> {
+    let mut response = client.get(url).send()?.error_for_status()?;
+    let path = dir.join(file_name(url));
+    let total = response.content_length().or(expected.size).unwrap_or(0);
+
+    fs::create_dir_all(dir)?;
+    let mut meter = progress.meter(path.file_name().unwrap_or(url), total);
+    io::copy(
+        &mut meter.reader(&mut response),
+        &mut fs::File::create(&path)?,
+    )?;
+    meter.finish();
+
+    verify(&path, expected).inspect_err(|_| {
+        let _ = fs::remove_file(&path);
+    })?;
+    Ok(path)
+}
+
+/// Checks the size and SHA-256 of a downloaded file, when they are known.
+pub fn verify(path: &Utf8Path, expected: &Expected) -> Result<(), SError> {
+    if let Some(size) = expected.size {
+        let actual = path.metadata()?.len();
+        if actual != size {
+            return Err(SError::CorruptArchive(format!(
+                "{path}: {actual} bytes instead of {size}"
+            )));
+        }
+    }
+    let Some(sha256) = &expected.sha256 else {
+        return Ok(());
+    };
+    let actual = sha256_of(path)?;
+    match actual.eq_ignore_ascii_case(sha256.trim()) {
+        true => Ok(()),
+        false => Err(SError::CorruptArchive(format!(
+            "{path}: SHA-256 {actual} instead of {sha256}"
+        ))),
+    }
+}
+
+/// Hex encoded SHA-256 of a file.
+pub fn sha256_of(path: &Utf8Path) -> Result<String, SError> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Last path segment of the URL made safe for a file name.
+fn file_name(url: &str) -> String {
+    let url = Url::parse(url).ok();
+    let segment = url
+        .as_ref()
+        .and_then(|url| url.path_segments()?.rfind(|s| !s.is_empty()))
+        .unwrap_or_default();
+    match naming::sanitize(segment, &['.', '_', '-']).trim_matches('.') {
+        "" => "download".to_string(),
+        name => name.to_string(),
+    }
+}
@@ -0,0 +1,59 @@
+use camino::Utf8PathBuf;
+use parking_lot::Mutex;
+
+/// Files dropped on the window, with the name used for mods that have none.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueuedDrop {
+    pub paths: Vec<Utf8PathBuf>,
+    pub unknown_mod_name: String,
+}
+
+/// What became of an offered drop.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Offer {
+    /// No long task runs, the drop can be installed right away.
+    Ready(QueuedDrop),
+    /// Held until the running tasks end; `pending` counts the drops waiting, this one included.
+    Queued { pending: u32 },
+}
+
+/// Drops arriving while long tasks run, e.g. a sync, kept in order instead of being lost.
+/// They are handed back once the last running task ends.
+#[derive(Default)]
+pub struct DropQueue {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    running: u32,
+    pending: Vec<QueuedDrop>,
+}
+
+impl DropQueue {
+    /// Counts a long task as running until the matching `end`.
+    pub fn begin(&self) {
+        self.inner.lock().running += 1;
+    }
+
+    /// Ends a task counted by `begin`. Returns the queued drops once no task runs anymore.
+    pub fn end(&self) -> Vec<QueuedDrop> {
+        let mut inner = self.inner.lock();
+        inner.running = inner.running.saturating_sub(1);
+        match inner.running {
+            0 => std::mem::take(&mut inner.pending),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn offer(&self, drop: QueuedDrop) -> Offer {
+        let mut inner = self.inner.lock();
+        if inner.running == 0 {
+            return Offer::Ready(drop);
+        }
+        inner.pending.push(drop);
+        Offer::Queued {
+            pending: inner.pending.len() as u32,
+        }
+    }
+}
@@ -1,25 +1,137 @@
 use crate::core::library::Library;
+use crate::core::mod_icon;
+use crate::core::mod_patches;
+use crate::core::repo_store;
+use crate::models::error::SError;
 use crate::models::library::LibraryDTO;
+use crate::models::mod_dto::{Appearance, Mod, ModManifest, ModPage};
 use crate::utils::icon::load_icon_as_data_uri;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use camino::Utf8Path;
+use std::ops::Bound;
+
+const MAX_PAGE_SIZE: u32 = 500;
 
 /// Builds a frontend DTO with enriched data (manifests and icons).
 /// This is the DTO sent to the frontend with all necessary display information.
 pub fn build_frontend_dto(library: &Library) -> LibraryDTO {
-    let mut dto = library.to_dto();
+    let mut dto = LibraryDTO {
+        read_only: library.is_read_only(),
+        ..library.to_dto()
+    };
 
     for (id, m) in &mut dto.mods {
-        m.manifest = library.cache.manifests.get(id).cloned();
-
-        // Load icon data if manifest specifies an icon
-        m.icon_data = m
-            .manifest
-            .as_ref()
-            .and_then(|manifest| manifest.icon.as_ref())
-            .and_then(|icon_filename| {
-                let icon_path = library.lib_paths.mods.join(id).join(icon_filename);
-                load_icon_as_data_uri(&icon_path)
-            });
+        enrich_mod(library, id, m, &Appearance::default());
     }
 
     dto
 }
+
+/// Builds the enriched DTO of a single mod for the details view.
+/// The icon variant is picked according to the frontend appearance.
+pub fn build_mod_details(
+    library: &Library,
+    mod_id: &str,
+    appearance: &Appearance,
+) -> Result<Mod, SError> {
+    let mut m = library
+        .mods
+        .get(mod_id)
+        .cloned()
+        .ok_or_else(|| SError::ModNotFound(mod_id.to_string()))?;
+
+    enrich_mod(library, mod_id, &mut m, appearance);
+    Ok(m)
+}
+
+/// Builds one page of enriched mods for virtualized lists.
+/// Pages are keyed by the last mod id rather than an offset, so mods added or removed
+/// while the list is scrolled never cause duplicates or gaps in the pages that follow.
+pub fn build_mod_page(
+    library: &Library,
+    cursor: Option<&str>,
+    limit: u32,
+    appearance: &Appearance,
+) -> Result<ModPage, SError> {
+    let after = cursor.map(decode_cursor).transpose()?;
+    let limit = limit.clamp(1, MAX_PAGE_SIZE) as usize;
+
+    let range = match &after {
+        Some(id) => library
+            .mods
+            .range::<str, _>((Bound::Excluded(id.as_str()), Bound::Unbounded)),
+        None => library.mods.range::<str, _>(..),
+    };
+    let mut mods: Vec<Mod> = range
+        .take(limit + 1)
+        .map(|(id, m)| {
+            let mut m = m.clone();
+            enrich_mod(library, id, &mut m, appearance);
+            m
+        })
+        .collect();
+
+    let has_more = mods.len() > limit;
+    mods.truncate(limit);
+    let next_cursor = has_more
+        .then(|| mods.last().map(|m| encode_cursor(&m.id)))
+        .flatten();
+
+    Ok(ModPage {
+        mods,
+        next_cursor,
+        total: library.mods.len() as u32,
+    })
+}
+
+fn encode_cursor(id: &str) -> String {
+    URL_SAFE_NO_PAD.encode(id)
+}
+
+fn decode_cursor(cursor: &str) -> Result<String, SError> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| SError::ParseError(format!("Invalid cursor: {}", cursor)))
+}
+
+fn enrich_mod(library: &Library, id: &str, m: &mut Mod, appearance: &Appearance) {
+    m.manifest = library.cache.manifests.get(id).cloned();
+    m.modified_files = library
+        .cache
+        .local_edits
+        .get(id)
+        .cloned()
+        .unwrap_or_default();
+    m.patch_of = mod_patches::base_of(library, id);
+
+    // A custom icon wins over the one the manifest specifies
+    m.icon_data = mod_icon::custom_icon_path(library, id)
+        .and_then(|path| load_icon_as_data_uri(&path))
+        .or_else(|| {
+            m.manifest.as_ref().and_then(|manifest| {
+                let root = repo_store::open(&library.lib_paths).payload_dir(id);
+                resolve_icon(&root, manifest, appearance)
+            })
+        });
+}
+
+/// Loads the icon matching the appearance, falling back to the other variant
+/// when the preferred one is not declared or cannot be read.
+pub fn resolve_icon(
+    mod_root: &Utf8Path,
+    manifest: &ModManifest,
+    appearance: &Appearance,
+) -> Option<String> {
+    let (preferred, fallback) = match appearance {
+        Appearance::Light => (&manifest.icon, &manifest.icon_dark),
+        Appearance::Dark => (&manifest.icon_dark, &manifest.icon),
+    };
+
+    [preferred, fallback]
+        .into_iter()
+        .flatten()
+        .find_map(|icon_filename| load_icon_as_data_uri(&mod_root.join(icon_filename)))
+}
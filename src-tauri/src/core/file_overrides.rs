@@ -0,0 +1,69 @@
+use crate::core::cache::LibraryCache;
+use crate::core::library::Library;
+use crate::models::error::SError;
+use camino::Utf8PathBuf;
+use std::borrow::Cow;
+
+/// Picks the mod deploying `path` when several active mods provide it, or drops the choice
+/// with `None` so the collision fails the sync again. Applies from the next sync.
+pub fn resolve(
+    library: &mut Library,
+    path: Utf8PathBuf,
+    winner: Option<String>,
+) -> Result<(), SError> {
+    let Some(winner) = winner else {
+        library.overrides.remove(&path);
+        library.mark_dirty();
+        return library.persist_manifest();
+    };
+
+    let files = library
+        .cache
+        .mods
+        .get(&winner)
+        .filter(|_| library.mods.contains_key(&winner))
+        .ok_or_else(|| SError::ModNotFound(winner.clone()))?;
+    if !files.files.contains(&path) {
+        return Err(SError::FileOrDirectoryNotFound(format!(
+            "{path} of {winner}"
+        )));
+    }
+
+    library.overrides.insert(path, winner);
+    library.mark_dirty();
+    library.persist_manifest()
+}
+
+/// The cache to deploy from: an overridden file is left out of every mod but its winner.
+/// Overrides whose winner is inactive have no effect.
+pub fn deployable_cache(library: &Library) -> Cow<'_, LibraryCache> {
+    let is_active = |id: &str| library.mods.get(id).is_some_and(|m| m.is_active);
+    if !library.overrides.values().any(|winner| is_active(winner)) {
+        return Cow::Borrowed(&library.cache);
+    }
+
+    let mut cache = library.cache.clone();
+    for (id, fs) in cache.mods.iter_mut() {
+        fs.files.retain(|rel| {
+            !library
+                .overrides
+                .get(rel)
+                .is_some_and(|winner| winner != id && is_active(winner))
+        });
+    }
+    Cow::Owned(cache)
+}
+
+/// Drops the overrides won by a removed mod.
+pub fn release(library: &mut Library, mod_id: &str) {
+    library.overrides.retain(|_, winner| winner != mod_id);
+}
+
+/// Moves the overrides won by a mod to its new id.
+pub fn rename_mod(library: &mut Library, old: &str, new: &str) {
+    library
+        .overrides
+        .values_mut()
+        .filter(|winner| *winner == old)
+        .for_each(|winner| *winner = new.to_string());
+}
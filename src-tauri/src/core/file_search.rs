@@ -0,0 +1,109 @@
+use crate::core::library::Library;
+use crate::core::search_index::{self, SearchIndex};
+use crate::models::error::SError;
+use crate::models::file_search::FileMatch;
+use camino::Utf8PathBuf;
+use regex::{Regex, RegexBuilder};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Lists the mods providing files that match `pattern`, active ones first.
+/// Patterns are case-insensitive globs: `*` and `?` stay within a folder and `**` crosses them.
+/// Without a `/`, the pattern is matched against file names only, e.g. `ConfigurationManager.dll`.
+/// Files are looked up in the search index, see `search_index`.
+pub fn find_mods_by_file(library: &Library, pattern: &str) -> Result<Vec<FileMatch>, SError> {
+    let pattern = pattern.trim().replace('\\', "/");
+    if pattern.is_empty() {
+        return Ok(Vec::new());
+    }
+    let matcher = glob_to_regex(&pattern)?;
+    let by_name = !pattern.contains('/');
+
+    let hits = search_index::with_index(library, |index| matching_files(index, &matcher, by_name));
+    let mut matches: Vec<FileMatch> = hits
+        .into_iter()
+        .filter_map(|(id, mut files)| {
+            let m = library.mods.get(&id)?;
+            files.sort();
+            Some(FileMatch {
+                mod_id: id,
+                mod_name: m.name.clone(),
+                is_active: m.is_active,
+                files,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.is_active
+            .cmp(&a.is_active)
+            .then(a.mod_name.cmp(&b.mod_name))
+    });
+    Ok(matches)
+}
+
+/// Mod id -> its files matching, by name only unless `by_name` is false.
+fn matching_files(
+    index: &SearchIndex,
+    matcher: &Regex,
+    by_name: bool,
+) -> BTreeMap<String, Vec<Utf8PathBuf>> {
+    index
+        .names
+        .iter()
+        .filter(|(name, _)| !by_name || matcher.is_match(name))
+        .flat_map(|(_, files)| files)
+        .filter(|(_, file)| by_name || matcher.is_match(file.as_str()))
+        .fold(BTreeMap::new(), |mut acc, (id, file)| {
+            acc.entry(id.clone())
+                .or_insert_with(Vec::new)
+                .push(file.clone());
+            acc
+        })
+}
+
+/// Ids of the mods whose name, author or description has a word starting with each word of
+/// `query`, active ones first, then by name.
+pub fn find_mods(library: &Library, query: &str) -> Vec<String> {
+    let words: Vec<String> = search_index::words(query).collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let found = search_index::with_index(library, |index| {
+        words
+            .iter()
+            .map(|word| {
+                index
+                    .terms
+                    .range(word.clone()..)
+                    .take_while(|(term, _)| term.starts_with(word.as_str()))
+                    .flat_map(|(_, ids)| ids.iter().cloned())
+                    .collect::<BTreeSet<String>>()
+            })
+            .reduce(|acc, ids| acc.intersection(&ids).cloned().collect())
+            .unwrap_or_default()
+    });
+
+    let mut mods: Vec<_> = found.iter().filter_map(|id| library.mods.get(id)).collect();
+    mods.sort_by(|a, b| b.is_active.cmp(&a.is_active).then(a.name.cmp(&b.name)));
+    mods.into_iter().map(|m| m.id.clone()).collect()
+}
+
+fn glob_to_regex(pattern: &str) -> Result<Regex, SError> {
+    let mut chars = pattern.chars().peekable();
+    let mut source = String::from("^");
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.next_if_eq(&'*').is_some() => source.push_str(".*"),
+            '*' => source.push_str("[^/]*"),
+            '?' => source.push_str("[^/]"),
+            c => source.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    source.push('$');
+
+    RegexBuilder::new(&source)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| SError::ParseError(format!("Invalid pattern: {e}")))
+}
@@ -0,0 +1,202 @@
+use crate::core::actionable_issues;
+use crate::core::library::Library;
+use crate::core::local_edits;
+use crate::core::repo_store;
+use crate::core::shared_state::SharedState;
+use crate::models::events::ExternalChangesDetected;
+use camino::{Utf8Path, Utf8PathBuf};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use std::collections::BTreeSet;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use tracing::{error, info, warn};
+
+/// Changes must settle this long before they are looked at, so a tool rewriting a mod is
+/// handled once.
+const DEBOUNCE: Duration = Duration::from_millis(1500);
+
+/// Watcher of the folders of the active library, held by the `AppRegistry`.
+#[derive(Clone, Default)]
+pub struct FsWatch(Arc<Mutex<Option<Watched>>>);
+
+struct Watched {
+    watcher: RecommendedWatcher,
+    roots: Vec<Utf8PathBuf>,
+}
+
+impl FsWatch {
+    /// Watches exactly `roots`. Returns whether they changed.
+    fn retarget(&self, roots: Vec<Utf8PathBuf>) -> bool {
+        let mut guard = self.0.lock();
+        let Some(watched) = guard.as_mut() else {
+            return false;
+        };
+        if watched.roots == roots {
+            return false;
+        }
+
+        for root in &watched.roots {
+            // Gone with its drive, or never watched because it did not exist
+            let _ = watched.watcher.unwatch(root.as_std_path());
+        }
+        for root in &roots {
+            if let Err(e) = watched
+                .watcher
+                .watch(root.as_std_path(), RecursiveMode::Recursive)
+            {
+                warn!("Failed to watch {root}: {e}");
+            }
+        }
+        watched.roots = roots;
+        true
+    }
+}
+
+/// The repo mods folder and the mod roots of the game.
+pub fn watched_roots(library: &Library) -> Vec<Utf8PathBuf> {
+    std::iter::once(repo_store::open(&library.lib_paths).root().to_path_buf())
+        .chain(
+            library
+                .spt_rules
+                .mod_roots()
+                .map(|root| library.game_root.join(root)),
+        )
+        .filter(|root| root.is_dir())
+        .collect()
+}
+
+/// What changed paths did to the library: mods whose repo folder no longer matches the cache,
+/// and deployed mods missing files in the game root.
+/// Changes made by the app itself leave the library consistent, so they never show up here.
+pub fn detect(library: &Library, paths: &[Utf8PathBuf]) -> ExternalChangesDetected {
+    let store = repo_store::open(&library.lib_paths);
+    let changed_mods: BTreeSet<String> = paths
+        .iter()
+        .filter_map(|path| path.strip_prefix(store.root()).ok())
+        .filter_map(|rel| {
+            let mut components = rel.components();
+            let id = components.next()?.as_str();
+            Some((id, components.as_path()))
+        })
+        .filter(|(id, rel)| is_changed(library, id, rel))
+        .map(|(id, _)| id.to_string())
+        .collect();
+
+    let touches_game = paths
+        .iter()
+        .any(|path| path.starts_with(&library.game_root));
+    let broken_mods = match touches_game {
+        true => actionable_issues::broken_mods(library),
+        false => Vec::new(),
+    };
+    ExternalChangesDetected {
+        changed_mods: changed_mods.into_iter().collect(),
+        broken_mods,
+    }
+}
+
+/// Content is compared by stamp; files the idle hasher has not stamped yet only count when
+/// they are gone.
+fn is_changed(library: &Library, id: &str, rel: &Utf8Path) -> bool {
+    let dir = repo_store::open(&library.lib_paths).payload_dir(id);
+    let Some(mod_fs) = library.cache.mods.get(id) else {
+        // A mod folder put there by another tool
+        return dir.exists();
+    };
+    if rel.as_str().is_empty() {
+        return !dir.exists();
+    }
+
+    let path = dir.join(rel);
+    if !mod_fs.files.iter().any(|file| file == rel) {
+        // Folders of the mod change along with their files
+        return path.is_file();
+    }
+    match mod_fs.stamps.get(rel) {
+        Some(stamp) => !local_edits::is_current(&path, stamp),
+        None => !path.exists(),
+    }
+}
+
+/// Flags the library dirty when `paths` changed it, see `detect`.
+pub fn flag(library: &mut Library, paths: &[Utf8PathBuf]) -> Option<ExternalChangesDetected> {
+    let changes = detect(library, paths);
+    if changes.changed_mods.is_empty() && changes.broken_mods.is_empty() {
+        return None;
+    }
+
+    info!("External changes: {changes:?}");
+    library.mark_dirty();
+    if let Err(e) = library.persist_manifest() {
+        warn!("Failed to persist the dirty flag: {e}");
+    }
+    Some(changes)
+}
+
+/// Watches the folders of whichever library is active for the lifetime of the app and emits
+/// `ExternalChangesDetected` when something else changed them, e.g. another tool updated a
+/// mod or a plugin was deleted by hand.
+/// Changes are looked at once they settle and the library is idle, so the ones made by the
+/// app itself are already reflected in the library.
+pub fn spawn(app: AppHandle, shared: SharedState, watch: FsWatch) {
+    let (tx, rx) = mpsc::channel();
+    let watcher = match notify::recommended_watcher(tx) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            error!("File watching is unavailable: {e}");
+            return;
+        }
+    };
+    *watch.0.lock() = Some(Watched {
+        watcher,
+        roots: Vec::new(),
+    });
+
+    std::thread::spawn(move || {
+        let mut pending = BTreeSet::<Utf8PathBuf>::new();
+
+        loop {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(Ok(event)) if !event.kind.is_access() => {
+                    let paths = event.paths.into_iter();
+                    pending.extend(paths.filter_map(|p| Utf8PathBuf::from_path_buf(p).ok()));
+                    continue;
+                }
+                Ok(Ok(_)) => continue,
+                Ok(Err(e)) => {
+                    warn!("File watcher error: {e}");
+                    continue;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            let roots = shared
+                .instance(|instance| instance.as_ref().map(watched_roots).unwrap_or_default());
+            // Changes seen before a switch belong to the previous library
+            if watch.retarget(roots) {
+                pending.clear();
+            }
+            if pending.is_empty() {
+                continue;
+            }
+
+            let paths: Vec<Utf8PathBuf> = pending.iter().cloned().collect();
+            // Retried on the next round while an operation holds the library
+            let Some(changes) = shared.try_with_lib_mut(|library| flag(library, &paths)) else {
+                continue;
+            };
+            pending.clear();
+            let Some(changes) = changes else {
+                continue;
+            };
+            if let Err(e) = changes.emit(&app) {
+                warn!("Failed to emit {changes:?}: {e}");
+            }
+        }
+    });
+}
@@ -0,0 +1,183 @@
+use crate::core::library::Library;
+use crate::core::linker;
+use crate::core::mod_fs::ModFS;
+use crate::core::mod_manager;
+use crate::core::mod_stager::StagedMod;
+use crate::core::ownership;
+use crate::core::progress::Progress;
+use crate::models::error::SError;
+use crate::models::game_scan::UnmanagedEntry;
+use crate::models::mod_dto::ModType;
+use crate::models::warning::OperationWarning;
+use crate::utils::file::FileUtils;
+use crate::utils::scan;
+use camino::{Utf8Path, Utf8PathBuf};
+use file_id::FileId;
+use std::collections::HashMap;
+use std::fs;
+use uuid::Uuid;
+
+/// Suffix of entries disabled by renaming, matched regardless of case.
+const DISABLED_SUFFIX: &str = ".disabled";
+/// Prefix of entries disabled by renaming.
+const DISABLED_PREFIX: char = '!';
+
+/// Lists the entries of the server mods and plugins folders that hold files the library did
+/// not link there. Entries holding any link, of the library or of another manager, are left
+/// out, as are SPT's own client patches, disabled or not.
+pub fn scan(library: &Library) -> Result<Vec<UnmanagedEntry>, SError> {
+    let owners = ownership::hardlink_owners(&library.lib_paths, &library.cache);
+    let rules = &library.spt_rules;
+    let roots = [
+        (&rules.server_mods, ModType::Server),
+        (&rules.client_plugins, ModType::Client),
+    ];
+
+    let mut entries = Vec::new();
+    for (root, mod_type) in roots {
+        let Ok(children) = fs::read_dir(library.game_root.join(root)) else {
+            continue;
+        };
+        for child in children {
+            let path = Utf8PathBuf::from_path_buf(child?.path()).map_err(|_| SError::Unexpected)?;
+            let name = path.file_name().unwrap_or_default();
+            let is_spt = library
+                .spt_generation
+                .is_client_patch(enabled_name(name).unwrap_or(name));
+            if root == &rules.client_plugins && is_spt {
+                continue;
+            }
+            let Some(bytes) = unmanaged_size(&path, &owners) else {
+                continue;
+            };
+            entries.push(UnmanagedEntry {
+                path: path.strip_prefix(&library.game_root)?.to_path_buf(),
+                mod_type: mod_type.clone(),
+                bytes,
+                disabled: enabled_name(name).is_some(),
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Copies entries listed by `scan` into the library, one mod each, and removes the originals
+/// so the next sync links them back in. The library stays dirty until then.
+/// Entries disabled by renaming are installed under their name without the marker and stay
+/// inactive; the others are active.
+/// Returns the warnings of the install.
+pub fn adopt(
+    library: &mut Library,
+    paths: &[Utf8PathBuf],
+    progress: Progress,
+) -> Result<Vec<OperationWarning>, SError> {
+    let unmanaged = scan(library)?;
+    if let Some(path) = paths
+        .iter()
+        .find(|path| !unmanaged.iter().any(|entry| &entry.path == *path))
+    {
+        return Err(SError::FileOrDirectoryNotFound(path.to_string()));
+    }
+
+    let staged = paths
+        .iter()
+        .map(|rel| stage(library, rel))
+        .collect::<Result<Vec<StagedMod>, SError>>()?;
+    let active: HashMap<String, bool> = staged
+        .iter()
+        .zip(paths)
+        .map(|(staged, rel)| (staged.fs.id.clone(), !is_disabled(rel)))
+        .collect();
+    let warnings = mod_manager::add_staged(library, staged, progress)?;
+
+    // Enabled ones were loaded by the game already, so they stay active
+    library
+        .mods
+        .iter_mut()
+        .filter_map(|(id, m)| Some((m, *active.get(id)?)))
+        .for_each(|(m, is_active)| m.is_active = is_active);
+    library.mark_dirty();
+    library.persist_manifest()?;
+
+    for rel in paths {
+        let original = library.game_root.join(rel);
+        match original.is_dir() {
+            true => FileUtils::remove_recursive(&original)?,
+            false => fs::remove_file(&original)?,
+        }
+    }
+    Ok(warnings)
+}
+
+/// Size of the files below `path`, `None` when it holds no file or any link.
+fn unmanaged_size(path: &Utf8Path, owners: &HashMap<FileId, String>) -> Option<u64> {
+    let mut bytes = 0;
+    let mut files = 0;
+    for entry in scan::walk(path) {
+        let entry = entry.ok()?;
+        let file = Utf8Path::from_path(entry.path())?;
+        if linker::read_link_target(file).is_ok() {
+            return None;
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if linker::get_id(file).is_ok_and(|id| owners.contains_key(&id)) {
+            return None;
+        }
+        bytes += entry.metadata().ok()?.len();
+        files += 1;
+    }
+    (files > 0).then_some(bytes)
+}
+
+/// The name an entry disabled by renaming had before, `None` when it bears no marker.
+pub fn enabled_name(name: &str) -> Option<&str> {
+    let enabled = name.strip_prefix(DISABLED_PREFIX).or_else(|| {
+        let split = name.len().checked_sub(DISABLED_SUFFIX.len())?;
+        name.get(split..)
+            .filter(|suffix| suffix.eq_ignore_ascii_case(DISABLED_SUFFIX))?;
+        name.get(..split)
+    })?;
+    (!enabled.is_empty()).then_some(enabled)
+}
+
+fn is_disabled(rel: &Utf8Path) -> bool {
+    rel.file_name().and_then(enabled_name).is_some()
+}
+
+/// Copies an entry into staging at its place in the game layout, as a drop of it would be,
+/// without its disable marker.
+fn stage(library: &Library, rel: &Utf8Path) -> Result<StagedMod, SError> {
+    let root = library
+        .lib_paths
+        .staging
+        .join(format!("adopt-{}", Uuid::new_v4()));
+    let original = library.game_root.join(rel);
+    let enabled = match rel.file_name().and_then(enabled_name) {
+        Some(name) => rel.with_file_name(name),
+        None => rel.to_path_buf(),
+    };
+    let copy = root.join(&enabled);
+    let name = match original.is_dir() {
+        true => {
+            FileUtils::copy_recursive(&original, &copy)?;
+            enabled.file_name()
+        }
+        false => {
+            fs::create_dir_all(copy.parent().ok_or(SError::Unexpected)?)?;
+            fs::copy(&original, &copy)?;
+            enabled.file_stem()
+        }
+    };
+
+    Ok(StagedMod {
+        fs: ModFS::new(&root, &library.spt_rules)?,
+        name: name.unwrap_or(rel.as_str()).to_string(),
+        source_path: root,
+        is_staging: true,
+        framework_files: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
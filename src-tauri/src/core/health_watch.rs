@@ -0,0 +1,92 @@
+use crate::core::actionable_issues;
+use crate::core::library::Library;
+use crate::core::shared_state::SharedState;
+use crate::models::events::LibraryHealthChanged;
+use std::time::{Duration, SystemTime};
+use tauri::AppHandle;
+use tauri_specta::Event;
+use tracing::{info, warn};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// A check running this much later than planned means the system slept in between.
+const RESUME_GAP: Duration = Duration::from_secs(60);
+
+/// Whether the folders of a library can be reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Availability {
+    pub game_root: bool,
+    pub repo: bool,
+}
+
+impl Availability {
+    pub fn of(library: &Library) -> Self {
+        Self {
+            game_root: library.game_root.is_dir(),
+            repo: library.lib_paths.mods.is_dir(),
+        }
+    }
+
+    fn is_complete(self) -> bool {
+        self.game_root && self.repo
+    }
+}
+
+/// Whether the wall clock moved past a check by more than the process could have been
+/// delayed, which happens when the system sleeps; the monotonic clock may stop meanwhile.
+pub fn has_resumed(planned: SystemTime, now: SystemTime) -> bool {
+    now.duration_since(planned)
+        .is_ok_and(|late| late > RESUME_GAP)
+}
+
+/// Revalidates the library. Deployed files are only checked on `deep` runs, and only when
+/// both folders are reachable.
+pub fn check(library: &Library, resumed: bool, deep: bool) -> LibraryHealthChanged {
+    let availability = Availability::of(library);
+    let broken_links = match deep && availability.is_complete() {
+        true => actionable_issues::broken_mods(library),
+        false => Vec::new(),
+    };
+    LibraryHealthChanged {
+        game_root_available: availability.game_root,
+        repo_available: availability.repo,
+        resumed,
+        broken_links,
+    }
+}
+
+/// Re-checks the active library for the lifetime of the app and emits `LibraryHealthChanged`
+/// when its folders come or go, and after every resume from sleep.
+/// Deployed files are checked again whenever links may have gone stale: after a resume and
+/// once the folders are back.
+pub fn spawn(app: AppHandle, shared: SharedState) {
+    std::thread::spawn(move || {
+        let mut last: Option<Availability> = None;
+        let mut planned = SystemTime::now() + CHECK_INTERVAL;
+
+        loop {
+            std::thread::sleep(CHECK_INTERVAL);
+            let now = SystemTime::now();
+            let resumed = has_resumed(planned, now);
+            planned = now + CHECK_INTERVAL;
+
+            let health = shared.instance(|instance| {
+                let library = instance.as_ref()?;
+                let current = Availability::of(library);
+                let remounted =
+                    last.is_some_and(|prev| !prev.is_complete()) && current.is_complete();
+                // The first check only reports a problem
+                let changed = last.map_or(!current.is_complete(), |prev| prev != current);
+                last = Some(current);
+                (resumed || changed).then(|| check(library, resumed, resumed || remounted))
+            });
+            let Some(health) = health else {
+                continue;
+            };
+
+            info!("Library health: {health:?}");
+            if let Err(e) = health.emit(&app) {
+                warn!("Failed to emit {health:?}: {e}");
+            }
+        }
+    });
+}
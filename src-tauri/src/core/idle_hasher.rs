@@ -0,0 +1,91 @@
+use crate::core::cache_store;
+use crate::core::library::Library;
+use crate::core::local_edits;
+use crate::core::mod_fs::FileStamp;
+use crate::core::repo_store;
+use crate::core::shared_state::SharedState;
+use camino::Utf8PathBuf;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+const IDLE_INTERVAL: Duration = Duration::from_secs(5);
+/// Files hashed per batch; the library is only locked to pick a batch and to store it.
+const BATCH_FILES: usize = 64;
+
+/// A file waiting for its stamp: mod id, path relative to the mod and absolute path.
+type Pending = (String, Utf8PathBuf, Utf8PathBuf);
+
+/// Stamps the files installs left unstamped, see `local_edits`, for the lifetime of the app.
+/// Runs while the app is idle and never waits for the library lock.
+pub fn spawn(shared: SharedState) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(IDLE_INTERVAL);
+        while run_once(&shared) > 0 {}
+    });
+}
+
+/// Hashes one batch of unstamped files and persists their stamps with the cache.
+/// Gives up the batch as soon as an operation wants the library. Returns the files stamped.
+pub fn run_once(shared: &SharedState) -> usize {
+    let Some(batch) = shared.try_with_lib_mut(|lib| pending(lib, BATCH_FILES)) else {
+        return 0;
+    };
+
+    let mut stamped = Vec::new();
+    for (mod_id, rel, path) in batch {
+        if shared.is_in_demand() {
+            debug!("Idle hashing paused for a user operation");
+            return 0;
+        }
+        if let Ok(stamp) = local_edits::stamp_of(&path) {
+            stamped.push((mod_id, rel, path, stamp));
+        }
+    }
+
+    shared
+        .try_with_lib_mut(|lib| store(lib, stamped))
+        .unwrap_or(0)
+}
+
+fn pending(library: &Library, limit: usize) -> Vec<Pending> {
+    let store = repo_store::open(&library.lib_paths);
+    library
+        .cache
+        .mods
+        .iter()
+        .flat_map(|(id, mod_fs)| {
+            let root = store.payload_dir(id);
+            mod_fs
+                .files
+                .iter()
+                .filter(|rel| !mod_fs.stamps.contains_key(*rel))
+                .map(move |rel| (id.clone(), rel.clone(), root.join(rel)))
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Stores the stamps of files that are still listed, unstamped and unchanged since hashed;
+/// a mod reinstalled meanwhile is picked up again by the next batch.
+fn store(
+    library: &mut Library,
+    stamped: Vec<(String, Utf8PathBuf, Utf8PathBuf, FileStamp)>,
+) -> usize {
+    let count = stamped
+        .into_iter()
+        .filter(|(_, _, path, stamp)| local_edits::is_current(path, stamp))
+        .filter_map(|(mod_id, rel, _, stamp)| {
+            let mod_fs = library.cache.mods.get_mut(&mod_id)?;
+            (mod_fs.files.contains(&rel) && !mod_fs.stamps.contains_key(&rel))
+                .then(|| mod_fs.stamps.insert(rel, stamp))
+        })
+        .count();
+
+    if count > 0 {
+        if let Err(e) = cache_store::write(&library.lib_paths, &library.cache) {
+            warn!("Failed to persist file stamps: {e}");
+            return 0;
+        }
+    }
+    count
+}
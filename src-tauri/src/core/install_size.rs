@@ -0,0 +1,91 @@
+use crate::core::decompression;
+use crate::core::mod_stager;
+use crate::models::error::SError;
+use crate::models::install_size::{InputSize, InstallEstimate, InstallSizeLimits};
+use crate::models::warning::{OperationWarning, WarningKind};
+use crate::utils::scan;
+use camino::{Utf8Path, Utf8PathBuf};
+use sysinfo::Disks;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Unpacked size of the inputs and the free space left for them on the library drive.
+/// Archives are measured from their listing, folders by walking them; nothing is extracted.
+pub fn estimate(
+    inputs: &[Utf8PathBuf],
+    repo_root: &Utf8Path,
+    limits: InstallSizeLimits,
+) -> InstallEstimate {
+    let inputs: Vec<InputSize> = inputs
+        .iter()
+        .map(|path| InputSize {
+            path: path.clone(),
+            bytes: input_size(path).ok(),
+        })
+        .collect();
+    let total_bytes = inputs.iter().filter_map(|input| input.bytes).sum();
+    let available_bytes = available_space(repo_root);
+    let largest = inputs
+        .iter()
+        .max_by_key(|input| input.bytes)
+        .map_or(repo_root, |input| &input.path);
+
+    InstallEstimate {
+        warnings: check(largest, total_bytes, available_bytes, limits),
+        inputs,
+        total_bytes,
+        available_bytes,
+    }
+}
+
+/// Warns when the install exceeds the size limit, or when the drive would fall below the
+/// free space to keep. Staging holds a second copy until the install finishes, so the install
+/// needs twice its size for a while.
+pub fn check(
+    subject: &Utf8Path,
+    total_bytes: u64,
+    available_bytes: Option<u64>,
+    limits: InstallSizeLimits,
+) -> Vec<OperationWarning> {
+    let limit = limits.warn_above_mib as u64 * MIB;
+    let needed = total_bytes * 2 + limits.min_free_mib as u64 * MIB;
+
+    let too_large = (total_bytes > limit).then(|| {
+        OperationWarning::new(WarningKind::LargeInstall, subject.as_str())
+            .with_details(&[total_bytes, limit])
+    });
+    let low_space = available_bytes
+        .filter(|available| *available < needed)
+        .map(|available| {
+            OperationWarning::new(WarningKind::LowDiskSpace, subject.as_str())
+                .with_details(&[needed, available])
+        });
+    too_large.into_iter().chain(low_space).collect()
+}
+
+/// Unpacked size of an archive or folder.
+pub fn input_size(path: &Utf8Path) -> Result<u64, SError> {
+    if path.is_dir() {
+        return Ok(scan::walk(path)
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| e.metadata().ok())
+            .map(|meta| meta.len())
+            .sum());
+    }
+    match mod_stager::is_archive(path) {
+        true => decompression::unpacked_size(path),
+        false => Ok(path.metadata()?.len()),
+    }
+}
+
+/// Free space of the drive holding `path`, from the disk mounted deepest above it.
+pub(crate) fn available_space(path: &Utf8Path) -> Option<u64> {
+    let path = dunce::canonicalize(path).ok()?;
+    Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
@@ -0,0 +1,73 @@
+use crate::core::library::Library;
+use crate::models::error::SError;
+use crate::models::global::ChecklistPolicy;
+use crate::models::launch_checklist::{ChecklistItem, LaunchChecklist};
+
+/// Collects the prerequisites declared in the manifests of the active mods.
+/// An acknowledgement only holds for the exact set of items it was given for,
+/// so activating another mod with prerequisites asks again.
+pub fn build(library: &Library) -> LaunchChecklist {
+    let items: Vec<ChecklistItem> = library
+        .mods
+        .values()
+        .filter(|m| m.is_active)
+        .filter_map(|m| {
+            library
+                .cache
+                .manifests
+                .get(&m.id)
+                .and_then(|manifest| manifest.prerequisites.as_ref())
+                .map(|prerequisites| (m, prerequisites))
+        })
+        .flat_map(|(m, prerequisites)| {
+            prerequisites.iter().map(|p| ChecklistItem {
+                mod_id: m.id.clone(),
+                mod_name: m.name.clone(),
+                kind: p.kind.clone(),
+                note: p.note.clone(),
+            })
+        })
+        .collect();
+
+    let acknowledged =
+        items.is_empty() || library.acknowledged_checklist.as_deref() == Some(&digest(&items));
+    LaunchChecklist {
+        items,
+        acknowledged,
+    }
+}
+
+/// Records the current checklist as acknowledged.
+pub fn acknowledge(library: &mut Library) -> Result<LaunchChecklist, SError> {
+    let checklist = build(library);
+    library.acknowledged_checklist = Some(digest(&checklist.items));
+    library.persist_manifest()?;
+    Ok(LaunchChecklist {
+        acknowledged: true,
+        ..checklist
+    })
+}
+
+/// Fails when the policy blocks on a checklist that has not been acknowledged.
+pub fn ensure_acknowledged(library: &Library, policy: ChecklistPolicy) -> Result<(), SError> {
+    if policy == ChecklistPolicy::Inform || build(library).acknowledged {
+        return Ok(());
+    }
+    Err(SError::ChecklistNotAcknowledged)
+}
+
+fn digest(items: &[ChecklistItem]) -> String {
+    let content = items
+        .iter()
+        .map(|item| {
+            format!(
+                "{}\0{:?}\0{}",
+                item.mod_id,
+                item.kind,
+                item.note.as_deref().unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    blake3::hash(content.as_bytes()).to_hex().to_string()
+}
@@ -1,12 +1,25 @@
 use crate::core::cache::LibraryCache;
+use crate::core::cache_store;
+use crate::core::library_lock::LibraryLock;
+use crate::core::local_edits;
+use crate::core::manifest_history;
 use crate::core::mod_stager::StageMaterial;
+use crate::core::profiles;
+use crate::core::search_index::SearchIndex;
 use crate::core::version;
+use crate::core::volume;
+use crate::models::compat_note::CompatNote;
 use crate::models::error::SError;
-use crate::models::library::{LibraryCreationRequirement, LibraryDTO};
+use crate::models::global::FrameworkPolicy;
+use crate::models::library::{DeploymentMode, LibraryCreationRequirement, LibraryDTO, LibraryMode};
+use crate::models::mod_backup::BackupRetention;
 use crate::models::mod_dto::Mod;
-use crate::models::paths::{LibPathRules, SPTPathCanonical, SPTPathRules};
+use crate::models::paths::{LibPathRules, SPTPathCanonical, SPTPathRules, SptGeneration};
+use crate::models::profile::ModProfile;
+use crate::utils::file::FileUtils;
 use crate::utils::toml::Toml;
 use camino::{Utf8Path, Utf8PathBuf};
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::default::Default;
 use std::path::PathBuf;
@@ -21,8 +34,42 @@ pub struct Library {
     pub spt_paths_canonical: SPTPathCanonical,
     pub cache: LibraryCache,
     pub spt_version: String,
+    /// Layout of the game root, detected when the library is created.
+    pub spt_generation: SptGeneration,
     pub mods: BTreeMap<String, Mod>,
     pub(crate) is_dirty: bool,
+    /// Digest of the acknowledged launch checklist, see `launch_checklist`.
+    pub acknowledged_checklist: Option<String>,
+    /// Notes about how pairs of mods behave together, see `compat_notes`.
+    pub compat_notes: Vec<CompatNote>,
+    /// Config files relative to the game root -> owning mod id, see `config_adoption`.
+    pub config_owners: BTreeMap<Utf8PathBuf, String>,
+    /// Files relative to the game root -> mod deploying them over the other active mods
+    /// providing them, see `file_overrides`.
+    pub overrides: BTreeMap<Utf8PathBuf, String>,
+    /// Set by the user to browse a library without changing it.
+    pub read_only: bool,
+    /// Mounted server mods folder of a remote server, see `remote_target`.
+    pub remote_server: Option<Utf8PathBuf>,
+    /// Whether the library deploys to a dedicated server, see `dedicated_server`.
+    pub mode: LibraryMode,
+    /// Whether mods are linked or copied into the game root.
+    pub deployment_mode: DeploymentMode,
+    /// Named activation sets, see `profiles`. The active one is refreshed when it is left.
+    pub profiles: BTreeMap<String, ModProfile>,
+    pub active_profile: Option<String>,
+    /// Backups kept after every backup, see `mod_backup::prune`.
+    pub backup_retention: BackupRetention,
+    /// False when the repo cannot be written, e.g. on a share mounted read-only.
+    pub(crate) is_writable: bool,
+    /// False until the cache is read and the game version validated, see `ensure_loaded`.
+    pub(crate) is_loaded: bool,
+    /// Digest of the last manifest written, to skip rewriting identical content.
+    manifest_digest: RefCell<Option<blake3::Hash>>,
+    /// Loaded on the first search, see `search_index::with_index`.
+    pub(crate) search_index: RefCell<Option<SearchIndex>>,
+    /// Held while the library is open for changes, see `lock`.
+    lock: Option<LibraryLock>,
 }
 
 impl Library {
@@ -43,8 +90,11 @@ impl Library {
             std::fs::create_dir_all(dir)?;
         }
 
-        let spt_paths = SPTPathRules::new(&requirement.game_root);
-        let spt_version = version::fetch_and_validate(&spt_paths)?;
+        let spt_generation = SptGeneration::detect(&requirement.game_root);
+        let spt_paths = SPTPathRules::new_for(&requirement.game_root, spt_generation);
+        let spt_version = version::fetch_and_validate(&spt_paths, spt_generation)?;
+        let deployment_mode =
+            volume::status_of(&requirement.game_root, &repo_root).recommended_mode;
 
         let inst = Self {
             id: uuid::Uuid::new_v4().to_string(),
@@ -56,8 +106,25 @@ impl Library {
             mods: Default::default(),
             spt_paths_canonical: SPTPathCanonical::from_spt_paths(spt_paths.clone())?,
             lib_paths,
-            spt_rules: SPTPathRules::default(),
+            spt_rules: SPTPathRules::for_generation(spt_generation),
+            spt_generation,
             is_dirty: false,
+            acknowledged_checklist: None,
+            compat_notes: Vec::new(),
+            config_owners: BTreeMap::new(),
+            overrides: BTreeMap::new(),
+            read_only: false,
+            remote_server: None,
+            mode: LibraryMode::Standard,
+            deployment_mode,
+            profiles: BTreeMap::new(),
+            active_profile: None,
+            backup_retention: BackupRetention::default(),
+            is_writable: true,
+            is_loaded: true,
+            manifest_digest: RefCell::new(None),
+            search_index: RefCell::new(None),
+            lock: None,
         };
 
         inst.persist()?;
@@ -65,35 +132,105 @@ impl Library {
     }
 
     pub fn load(repo_root: &Utf8Path) -> Result<Self, SError> {
+        let mut library = Self::open(repo_root)?;
+        library.ensure_loaded()?;
+        Ok(library)
+    }
+
+    /// Loads the library again from disk, e.g. after its files were rewound, keeping its lock.
+    pub fn reload(&mut self) -> Result<(), SError> {
+        let mut reloaded = Self::load(&self.repo_root)?;
+        reloaded.lock = self.lock.take();
+        *self = reloaded;
+        Ok(())
+    }
+
+    /// Opens a library from its manifest only, which is enough for the switcher and mod list.
+    /// Reading the cache and validating the installed game are deferred to `ensure_loaded`.
+    pub fn open(repo_root: &Utf8Path) -> Result<Self, SError> {
         let dto = Self::read_library_manifest(repo_root)?;
 
         // Validate historical version
-        version::validate_string(&dto.spt_version)?;
+        version::validate_string(&dto.spt_version, dto.spt_generation)?;
 
         let lib_paths = LibPathRules::new(repo_root);
-        let spt_paths = SPTPathRules::new(&dto.game_root);
-        // Validate current physical version using the game_root from the loaded library
-        let spt_version = version::fetch_and_validate(&spt_paths)?;
+        let spt_paths = SPTPathRules::new_for(&dto.game_root, dto.spt_generation);
 
         Ok(Self {
             id: dto.id,
             name: dto.name,
             repo_root: repo_root.to_owned(),
-            spt_paths_canonical: SPTPathCanonical::from_spt_paths(spt_paths.clone())?,
+            spt_paths_canonical: SPTPathCanonical::from_spt_paths(spt_paths)?,
             game_root: dto.game_root,
-            spt_rules: SPTPathRules::default(),
-            cache: Toml::read(&lib_paths.cache)?,
+            spt_rules: SPTPathRules::for_generation(dto.spt_generation)
+                .with_managed_roots(dto.managed_roots)?,
+            spt_generation: dto.spt_generation,
+            cache: LibraryCache::default(),
             lib_paths,
-            spt_version,
+            spt_version: dto.spt_version,
             mods: dto.mods,
             is_dirty: false,
+            acknowledged_checklist: dto.acknowledged_checklist,
+            compat_notes: dto.compat_notes,
+            config_owners: dto.config_owners,
+            overrides: dto.overrides,
+            read_only: dto.read_only,
+            remote_server: dto.remote_server,
+            mode: dto.mode,
+            deployment_mode: dto.deployment_mode,
+            profiles: dto.profiles,
+            active_profile: dto.active_profile,
+            backup_retention: dto.backup_retention,
+            is_writable: FileUtils::is_writable(repo_root),
+            is_loaded: false,
+            manifest_digest: RefCell::new(None),
+            search_index: RefCell::new(None),
+            lock: None,
         })
     }
 
+    /// Completes loading of a library created by `open`. Does nothing once loaded.
+    pub fn ensure_loaded(&mut self) -> Result<(), SError> {
+        if self.is_loaded {
+            return Ok(());
+        }
+        let cache = Self::read_cache(&self.lib_paths)?;
+        self.attach_cache(cache)
+    }
+
+    /// Reads the cache without touching a library instance, so it can happen outside of its lock.
+    pub fn read_cache(lib_paths: &LibPathRules) -> Result<LibraryCache, SError> {
+        cache_store::read(lib_paths)
+    }
+
+    /// Validates the installed game version and installs a cache read by `read_cache`.
+    pub fn attach_cache(&mut self, cache: LibraryCache) -> Result<(), SError> {
+        if self.is_loaded {
+            return Ok(());
+        }
+        // Validate current physical version using the game_root from the loaded library
+        self.spt_version = version::fetch_and_validate(
+            &SPTPathRules::new_for(&self.game_root, self.spt_generation),
+            self.spt_generation,
+        )?;
+        self.cache = cache;
+        self.cache.local_edits = local_edits::detect(&self.cache, &self.lib_paths);
+        self.is_loaded = true;
+        Ok(())
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.is_loaded
+    }
+
     pub fn read_library_manifest(lib_root: &Utf8Path) -> Result<LibraryDTO, SError> {
         Toml::read::<LibraryDTO>(&LibPathRules::new(lib_root).manifest)
     }
 
+    pub fn parse_library_manifest(content: &str) -> Result<LibraryDTO, SError> {
+        Toml::parse::<LibraryDTO>(content)
+    }
+
     pub fn to_dto(&self) -> LibraryDTO {
         LibraryDTO {
             id: self.id.to_owned(),
@@ -103,17 +240,87 @@ impl Library {
             spt_version: self.spt_version.to_owned(),
             mods: self.mods.to_owned(),
             is_dirty: self.is_dirty,
+            mod_count: self.mods.len() as u32,
+            warnings: Vec::new(),
+            acknowledged_checklist: self.acknowledged_checklist.clone(),
+            managed_roots: self.spt_rules.managed_roots.clone(),
+            compat_notes: self.compat_notes.clone(),
+            config_owners: self.config_owners.clone(),
+            overrides: self.overrides.clone(),
+            read_only: self.read_only,
+            remote_server: self.remote_server.clone(),
+            mode: self.mode,
+            profiles: profiles::snapshot(self),
+            active_profile: self.active_profile.clone(),
+            backup_retention: self.backup_retention,
+            spt_generation: self.spt_generation,
+            deployment_mode: self.deployment_mode,
         }
     }
 
-    pub fn stage_material(&self, unknown_mod_name: String) -> StageMaterial {
+    /// Whether commands must leave the library as it is.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only || !self.is_writable
+    }
+
+    /// Takes the exclusive lock of the repo for as long as this instance lives, failing with
+    /// `LibraryLocked` while another process has it open. A read-only library needs none, so
+    /// guests can browse a shared library while its owner has it open.
+    pub fn lock(&mut self) -> Result<(), SError> {
+        if !self.is_read_only() && self.lock.is_none() {
+            self.lock = Some(LibraryLock::acquire(&self.lib_paths)?);
+        }
+        Ok(())
+    }
+
+    /// Fails with `LibraryReadOnly` before anything is changed on a read-only library.
+    pub fn ensure_writable(&self) -> Result<(), SError> {
+        match self.is_read_only() {
+            true => Err(SError::LibraryReadOnly),
+            false => Ok(()),
+        }
+    }
+
+    /// Sets the read-only flag. It cannot be lifted while the repo itself is not writable, nor
+    /// while another process holds the lock. Setting it releases the lock.
+    pub fn set_read_only(&mut self, read_only: bool) -> Result<(), SError> {
+        if !self.is_writable {
+            return Err(SError::LibraryReadOnly);
+        }
+        if !read_only && self.lock.is_none() {
+            // Editable again, so it has to be the only writer
+            self.lock = Some(LibraryLock::acquire(&self.lib_paths)?);
+        }
+        self.read_only = read_only;
+        self.persist_manifest()?;
+        if read_only {
+            self.lock = None;
+        }
+        Ok(())
+    }
+
+    pub fn stage_material(
+        &self,
+        unknown_mod_name: String,
+        framework_policy: FrameworkPolicy,
+    ) -> StageMaterial {
         StageMaterial {
             rules: self.spt_rules.clone(),
             root: self.lib_paths.staging.clone(),
             name: unknown_mod_name,
+            quarantine: self.lib_paths.quarantine.clone(),
+            framework_policy,
         }
     }
 
+    /// Replaces the managed roots. Links under a dropped root would no longer be cleaned up,
+    /// so callers purge the game root before changing them.
+    pub fn set_managed_roots(&mut self, roots: Vec<Utf8PathBuf>) -> Result<(), SError> {
+        self.spt_rules = self.spt_rules.clone().with_managed_roots(roots)?;
+        self.mark_dirty();
+        self.persist_manifest()
+    }
+
     pub fn spt_canonical_paths(&self) -> Vec<PathBuf> {
         vec![
             self.spt_paths_canonical.client_exe.clone(),
@@ -132,9 +339,28 @@ impl Library {
     }
 
     /// Persists the library manifest and cache to disk.
+    /// Only content that changed since the last write reaches the disk.
     pub fn persist(&self) -> Result<(), SError> {
-        Toml::write(&self.lib_paths.manifest, &self.to_dto())?;
-        Toml::write(&self.lib_paths.cache, &self.cache)?;
+        self.persist_manifest()?;
+        // An unloaded library holds an empty placeholder cache that must not replace the real one
+        if self.is_loaded {
+            cache_store::write(&self.lib_paths, &self.cache)?;
+        }
+        Ok(())
+    }
+
+    /// Persists the manifest alone, for changes that cannot affect the cache (toggles, renames).
+    /// Each new content is kept as a generation, see `manifest_history::repair`.
+    pub fn persist_manifest(&self) -> Result<(), SError> {
+        let content = Toml::to_string(&self.to_dto())?;
+        let digest = blake3::hash(content.as_bytes());
+        if *self.manifest_digest.borrow() == Some(digest) {
+            return Ok(());
+        }
+
+        FileUtils::write_atomic(&self.lib_paths.manifest, &content)?;
+        manifest_history::record(&self.lib_paths, &content);
+        self.manifest_digest.replace(Some(digest));
         Ok(())
     }
 }
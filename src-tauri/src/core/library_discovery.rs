@@ -0,0 +1,94 @@
+use crate::config::global::GlobalConfig;
+use crate::core::library::Library;
+use crate::core::library_service;
+use crate::core::progress::Progress;
+use crate::models::library::{DiscoveredLibrary, LibraryDTO};
+use crate::models::paths::SPTPathRules;
+use camino::{Utf8Path, Utf8PathBuf};
+use walkdir::WalkDir;
+
+/// Folders below a root searched for a library, e.g. `D:/Games/SPT/.mod_keeper`.
+pub const MAX_DEPTH: usize = 4;
+
+/// Folders at most this deep are reported as progress, deeper ones are too many to show.
+const PROGRESS_DEPTH: usize = 2;
+
+/// Searches the roots for game folders holding a valid library, without following links.
+/// Folders that cannot be read are skipped. Ordered by repo root.
+pub fn discover(
+    roots: &[Utf8PathBuf],
+    config: &GlobalConfig,
+    progress: Progress,
+) -> Vec<DiscoveredLibrary> {
+    let library_default = SPTPathRules::default().library_default;
+    let mut found: Vec<DiscoveredLibrary> = roots
+        .iter()
+        .flat_map(|root| {
+            WalkDir::new(root)
+                .max_depth(MAX_DEPTH)
+                .follow_links(false)
+                .into_iter()
+                .filter_entry(|entry| {
+                    entry.file_type().is_dir() && entry.file_name() != library_default.as_str()
+                })
+                .filter_map(Result::ok)
+        })
+        .filter_map(|entry| {
+            let dir = Utf8Path::from_path(entry.path())?;
+            if entry.depth() <= PROGRESS_DEPTH {
+                progress.scanning(dir);
+            }
+            inspect(&dir.join(&library_default), config)
+        })
+        .collect();
+
+    found.sort_by(|a, b| a.repo_root.cmp(&b.repo_root));
+    found.dedup_by(|a, b| a.repo_root == b.repo_root);
+    found
+}
+
+/// Adds the libraries to the known ones, after those already there, and returns the summary.
+/// Paths that do not hold a valid library are skipped.
+pub fn register(config: &mut GlobalConfig, paths: &[Utf8PathBuf]) -> Vec<LibraryDTO> {
+    let added: Vec<Utf8PathBuf> = paths
+        .iter()
+        .filter(|path| !config.known_libraries.contains(path))
+        .filter(|path| library_service::validate_library_structure(path).is_ok())
+        .cloned()
+        .collect();
+    if !added.is_empty() {
+        config.known_libraries.extend(added);
+        config.save();
+    }
+    library_service::get_known_library_summary(config)
+}
+
+/// Drives to search when the user picked none: every mounted drive letter on Windows, the home
+/// folder elsewhere.
+pub fn default_roots() -> Vec<Utf8PathBuf> {
+    if cfg!(windows) {
+        return ('A'..='Z')
+            .map(|letter| Utf8PathBuf::from(format!("{letter}:/")))
+            .filter(|root| root.is_dir())
+            .collect();
+    }
+    directories::UserDirs::new()
+        .and_then(|dirs| Utf8PathBuf::from_path_buf(dirs.home_dir().to_path_buf()).ok())
+        .into_iter()
+        .collect()
+}
+
+fn inspect(repo_root: &Utf8Path, config: &GlobalConfig) -> Option<DiscoveredLibrary> {
+    library_service::validate_library_structure(repo_root).ok()?;
+    let dto = Library::read_library_manifest(repo_root).ok()?;
+    Some(DiscoveredLibrary {
+        repo_root: repo_root.to_path_buf(),
+        name: dto.name,
+        game_root: dto.game_root,
+        mod_count: dto.mods.len() as u32,
+        is_known: config
+            .known_libraries
+            .iter()
+            .any(|known| known == repo_root),
+    })
+}
@@ -0,0 +1,48 @@
+use crate::models::error::SError;
+use crate::models::paths::LibPathRules;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Weak};
+
+/// Locks held by this process, so opening a library it already holds shares the lock.
+static HELD: LazyLock<Mutex<HashMap<PathBuf, Weak<File>>>> = LazyLock::new(Mutex::default);
+
+/// Exclusive lock on a library repo, held by the process that has it open so the app and a
+/// headless task never write the same repo at once.
+/// Released once every handle is dropped, or by the OS when the process exits.
+#[derive(Clone)]
+pub struct LibraryLock {
+    _file: Arc<File>,
+}
+
+impl LibraryLock {
+    /// Takes the lock of the library at `lib_paths`, failing with `LibraryLocked` while another
+    /// process holds it.
+    pub fn acquire(lib_paths: &LibPathRules) -> Result<Self, SError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lib_paths.lock)?;
+        let key = dunce::canonicalize(&lib_paths.lock)?;
+
+        let mut held = HELD.lock();
+        if let Some(file) = held.get(&key).and_then(Weak::upgrade) {
+            return Ok(Self { _file: file });
+        }
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(SError::LibraryLocked(lib_paths.lock.to_string()))
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        let file = Arc::new(file);
+        held.retain(|_, file| file.strong_count() > 0);
+        held.insert(key, Arc::downgrade(&file));
+        Ok(Self { _file: file })
+    }
+}
@@ -1,12 +1,31 @@
 use crate::config::global::GlobalConfig;
-use crate::core::dto_builder;
+use crate::core::cache::LibraryCache;
 use crate::core::library::Library;
+use crate::core::mod_fs::ModFS;
+use crate::core::shared_state::SharedState;
+use crate::core::{
+    bepinex, cache_store, cleanup, compat_notes, config_adoption, dedicated_server, dependency,
+    deploy_journal, deployment, dto_builder, file_overrides, launch_checklist, logging, ownership,
+    plan_store, repo_history, repo_store, search_index, sync_index, volume,
+};
+use crate::models::consistency::CacheRebuildReport;
+use crate::models::deployment_plan::{DeploymentPlan, SyncScope};
 use crate::models::error::SError;
-use crate::models::global::LibrarySwitch;
-use crate::models::library::{LibraryCreationRequirement, LibraryDTO};
+use crate::models::events::LibraryReady;
+use crate::models::global::{ChecklistPolicy, LibrarySwitch, LinkFailurePolicy};
+use crate::models::library::{
+    ComparedMod, DeploymentMode, LibraryComparison, LibraryCreationRequirement, LibraryDTO,
+    ModDifference,
+};
+use crate::models::logging::LoggedOperation;
 use crate::models::paths::LibPathRules;
+use crate::models::warning::OperationWarning;
+use crate::utils::file::FileUtils;
+use crate::utils::naming;
 use camino::{Utf8Path, Utf8PathBuf};
-use tracing::error;
+use std::collections::{BTreeMap, HashSet};
+use tracing::{error, info, warn};
+use uuid::Uuid;
 
 /// Service for managing library lifecycle operations.
 /// Handles opening, creating, and querying libraries while updating global configuration.
@@ -70,7 +89,9 @@ pub fn open_library(config: &mut GlobalConfig, path: &Utf8Path) -> Result<Librar
     // 1. Attempt to load the library first.
     // If this fails (e.g., path invalid, manifest missing), we propagate the error
     // and do NOT update the configuration.
-    let library = Library::load(path)?;
+    let mut library = Library::load(path)?;
+    library.lock()?;
+    recover_interrupted(&library);
 
     config.update_recent(path);
 
@@ -80,6 +101,16 @@ pub fn open_library(config: &mut GlobalConfig, path: &Utf8Path) -> Result<Librar
     Ok(library)
 }
 
+/// Puts back what a crash interrupted in the repo and the game root, once a library is open.
+/// A read-only library is left alone, e.g. a shared one is recovered by its owner.
+pub fn recover_interrupted(library: &Library) {
+    if library.is_read_only() {
+        return;
+    }
+    repo_store::recover(&library.lib_paths);
+    deploy_journal::recover(&library.lib_paths);
+}
+
 /// Creates a new library and updates the global configuration.
 /// Derives repo_root from game_root as game_root/.mod_keeper if not provided.
 /// If the library already exists and is valid, opens it instead of creating.
@@ -113,7 +144,8 @@ pub fn create_library(
     let mut updated_requirement = requirement;
     updated_requirement.repo_root = Some(repo_root.clone());
 
-    let library = Library::create(updated_requirement.clone())?;
+    let mut library = Library::create(updated_requirement.clone())?;
+    library.lock()?;
 
     // Update config only on success
     config.update_recent(&repo_root);
@@ -122,6 +154,37 @@ pub fn create_library(
     Ok(library)
 }
 
+/// Finishes loading a library installed with `Library::open`.
+/// The cache is read without holding the instance lock so the UI stays responsive;
+/// returns None when another library became active in the meantime.
+pub fn finish_loading(
+    shared: &SharedState,
+    repo_root: &Utf8Path,
+) -> Result<Option<LibraryReady>, SError> {
+    let is_active = shared.instance(|instance| {
+        instance
+            .as_ref()
+            .is_some_and(|lib| lib.repo_root == repo_root)
+    });
+    if !is_active {
+        return Ok(None);
+    }
+
+    let cache = Library::read_cache(&LibPathRules::new(repo_root))?;
+
+    shared.instance(|instance| {
+        let Some(library) = instance.as_mut().filter(|lib| lib.repo_root == repo_root) else {
+            return Ok(None);
+        };
+        library.attach_cache(cache)?;
+
+        Ok(Some(LibraryReady {
+            id: library.id.clone(),
+            mod_count: library.mods.len() as u32,
+        }))
+    })
+}
+
 /// Returns a summary of all known libraries.
 pub fn get_known_library_summary(config: &GlobalConfig) -> Vec<LibraryDTO> {
     config
@@ -131,7 +194,8 @@ pub fn get_known_library_summary(config: &GlobalConfig) -> Vec<LibraryDTO> {
             // Attempt to read the manifest for each known path
             match Library::read_library_manifest(path) {
                 Ok(mut dto) => {
-                    // Clear the mods field as requested (lightweight DTO)
+                    // Clear the mods field as requested (lightweight DTO), keeping only the count
+                    dto.mod_count = dto.mods.len() as u32;
                     dto.mods.clear();
                     Some(dto)
                 }
@@ -145,6 +209,55 @@ pub fn get_known_library_summary(config: &GlobalConfig) -> Vec<LibraryDTO> {
         .collect()
 }
 
+/// Compares the mods of two libraries by id, e.g. an old and a new game version, or a local
+/// and a server library. Only their manifests and caches are read, neither needs to be open.
+pub fn compare_libraries(
+    repo_a: &Utf8Path,
+    repo_b: &Utf8Path,
+) -> Result<LibraryComparison, SError> {
+    let a = compared_mods(repo_a)?;
+    let b = compared_mods(repo_b)?;
+    let only_in = |from: &BTreeMap<String, ComparedMod>, other: &BTreeMap<String, ComparedMod>| {
+        from.values()
+            .filter(|m| !other.contains_key(&m.id))
+            .cloned()
+            .collect()
+    };
+
+    Ok(LibraryComparison {
+        only_in_a: only_in(&a, &b),
+        only_in_b: only_in(&b, &a),
+        differences: a
+            .values()
+            .filter_map(|m| b.get(&m.id).map(|other| (m, other)))
+            .filter(|(a, b)| a.version != b.version || a.is_active != b.is_active)
+            .map(|(a, b)| ModDifference {
+                a: a.clone(),
+                b: b.clone(),
+            })
+            .collect(),
+    })
+}
+
+/// Mods of a library by id, with the version of their manifest when they ship one.
+fn compared_mods(repo_root: &Utf8Path) -> Result<BTreeMap<String, ComparedMod>, SError> {
+    let mods = Library::read_library_manifest(repo_root)?.mods;
+    let cache = Library::read_cache(&LibPathRules::new(repo_root))?;
+
+    Ok(mods
+        .into_values()
+        .map(|m| {
+            let compared = ComparedMod {
+                version: cache.manifests.get(&m.id).map(|mf| mf.version.clone()),
+                id: m.id.clone(),
+                name: m.name,
+                is_active: m.is_active,
+            };
+            (m.id, compared)
+        })
+        .collect())
+}
+
 /// Returns the manifest for the currently active library, if any.
 /// Uses the first item in known_libraries (most recently used).
 pub fn get_active_library_manifest(config: &GlobalConfig) -> Option<LibraryDTO> {
@@ -154,6 +267,11 @@ pub fn get_active_library_manifest(config: &GlobalConfig) -> Option<LibraryDTO>
         .and_then(|path| Library::read_library_manifest(path).ok())
 }
 
+/// Builds the LibrarySwitch DTO for the current config and active library.
+pub fn current_switch(shared: &SharedState) -> LibrarySwitch {
+    shared.both(|config, instance| to_library_switch(config, instance.as_ref()))
+}
+
 /// Converts the global configuration state into a LibrarySwitch DTO.
 /// When active_library is provided, uses build_frontend_dto to enrich the active library DTO
 /// with manifest and icon data. Otherwise falls back to reading from manifest file.
@@ -168,10 +286,342 @@ pub fn to_library_switch(config: &GlobalConfig, active_library: Option<&Library>
     }
 }
 
+/// Replaces the deployed mods with the active ones and marks the library clean.
+/// Refused while the launch checklist blocks, see `launch_checklist`.
+/// The first sync adopts the configs an existing install already has for the library's mods.
+/// A sync failing halfway is rolled back, see `deploy_journal::run`.
+/// Each sync writes an operation log, see `logging::operation`.
+/// Returns the warnings raised while verifying the deployed links.
+pub fn sync(
+    library: &mut Library,
+    policy: ChecklistPolicy,
+    link_policy: LinkFailurePolicy,
+) -> Result<Vec<OperationWarning>, SError> {
+    sync_scoped(library, policy, link_policy, SyncScope::All)
+}
+
+/// Like `sync`, limited to one side of the install, see `SyncScope`.
+/// The library stays dirty after a partial sync, as the other side was left as it was.
+pub fn sync_scoped(
+    library: &mut Library,
+    policy: ChecklistPolicy,
+    link_policy: LinkFailurePolicy,
+    scope: SyncScope,
+) -> Result<Vec<OperationWarning>, SError> {
+    logging::operation(logging::dir(library), LoggedOperation::Sync, || {
+        launch_checklist::ensure_acknowledged(library, policy)?;
+        dependency::ensure_satisfied(library)?;
+
+        // Adopted configs live on the client side
+        if scope != SyncScope::ServerOnly && sync_index::read(&library.lib_paths).is_none() {
+            config_adoption::adopt(library);
+        }
+
+        // Both steps are rolled back together when either fails
+        let mut warnings = deploy_journal::run(&library.lib_paths, || {
+            // 1. Purge existing managed links
+            info!("Purging deployed mods ({scope:?})");
+            cleanup::purge_scoped(
+                &library.game_root,
+                &library.repo_root,
+                &library.spt_rules,
+                &library.lib_paths,
+                &library.cache,
+                scope,
+            )?;
+
+            // 2. Deploy active mods
+            info!("Deploying active mods ({scope:?})");
+            deployment::deploy_scoped(
+                &library.game_root,
+                &library.lib_paths,
+                &library.spt_rules,
+                &dedicated_server::deployable_mods(library),
+                &file_overrides::deployable_cache(library),
+                link_policy,
+                scope,
+                library.deployment_mode,
+            )
+        })?;
+        warnings.extend(dedicated_server::sync_warnings(library));
+        warnings.extend(bepinex::sync_warnings(library, scope));
+        warnings.extend(ownership::sync_warnings(library, scope));
+        warnings.extend(volume::sync_warnings(library));
+
+        if scope == SyncScope::All {
+            library.mark_clean();
+        }
+        library.persist()?;
+        record_sync(library, scope);
+        Ok(warnings)
+    })
+}
+
+/// Deploys the active mods into a scratch folder instead of the game root and returns what
+/// `sync` would, without changing the install or the library. The scratch folder is removed
+/// afterwards.
+pub fn sandbox_sync(
+    library: &Library,
+    policy: ChecklistPolicy,
+    link_policy: LinkFailurePolicy,
+) -> Result<Vec<OperationWarning>, SError> {
+    library.ensure_writable()?;
+    launch_checklist::ensure_acknowledged(library, policy)?;
+
+    let scratch = library
+        .lib_paths
+        .staging
+        .join(format!("sandbox-{}", Uuid::new_v4()));
+    let result = deployment::deploy_into(
+        &scratch,
+        &library.game_root,
+        &library.lib_paths,
+        &library.spt_rules,
+        &dedicated_server::deployable_mods(library),
+        &file_overrides::deployable_cache(library),
+        link_policy,
+    );
+
+    // Links are removed without following them, leaving the repo untouched
+    let cleanup = match scratch.exists() {
+        true => FileUtils::remove_recursive(&scratch),
+        false => Ok(()),
+    };
+    if let Err(e) = cleanup {
+        warn!("Failed to remove the sandbox {scratch}: {e}");
+    }
+    result
+}
+
+/// What `sync` would do to the game root, computed without changing it: the links the purge
+/// removes and deployment does not put back, the links deployment creates, the folders it
+/// creates for them and the collisions that would fail it.
+pub fn preview_sync(library: &Library) -> Result<DeploymentPlan, SError> {
+    let plan = deployment::plan(
+        &library.game_root,
+        &library.lib_paths,
+        &library.spt_rules,
+        &dedicated_server::deployable_mods(library),
+        &file_overrides::deployable_cache(library),
+    )?;
+    let relinked: HashSet<&Utf8Path> = plan
+        .links
+        .iter()
+        .filter_map(|link| link.target.strip_prefix(&library.game_root).ok())
+        .collect();
+    let unlinks = cleanup::plan_purge(
+        &library.game_root,
+        &library.repo_root,
+        &library.spt_rules,
+        &library.lib_paths,
+        &library.cache,
+        SyncScope::All,
+    )?
+    .into_iter()
+    .filter(|rel| !relinked.contains(rel.as_path()))
+    .map(|rel| library.game_root.join(rel))
+    .collect();
+
+    Ok(DeploymentPlan {
+        unlinks,
+        compat_notes: compat_notes::for_active(library),
+        ..plan
+    })
+}
+
+/// Makes exactly `ids` the active mods and deploys them.
+/// Only the difference to the last sync is unlinked and linked, falling back to a full sync
+/// when the deployed state is unknown, see `deployment::deploy_delta`.
+pub fn switch_active(
+    library: &mut Library,
+    ids: &[String],
+    policy: ChecklistPolicy,
+    link_policy: LinkFailurePolicy,
+) -> Result<Vec<OperationWarning>, SError> {
+    if let Some(id) = ids.iter().find(|id| !library.mods.contains_key(*id)) {
+        return Err(SError::ModNotFound(id.to_string()));
+    }
+    dependency::check_active_set(library, ids)?;
+
+    // The checklist is that of the new set, so a blocked switch is undone before it is saved
+    let previous: BTreeMap<String, bool> = library
+        .mods
+        .iter()
+        .map(|(id, m)| (id.clone(), m.is_active))
+        .collect();
+    library
+        .mods
+        .iter_mut()
+        .for_each(|(id, m)| m.is_active = ids.contains(id));
+    if let Err(e) = launch_checklist::ensure_acknowledged(library, policy) {
+        library
+            .mods
+            .iter_mut()
+            .for_each(|(id, m)| m.is_active = previous[id]);
+        return Err(e);
+    }
+
+    library.mark_dirty();
+    library.persist_manifest()?;
+    deploy_incremental(library, policy, link_policy)
+}
+
+/// Like `sync`, relinking only what changed since the last sync when its file lists still
+/// hold, e.g. after installing new mods; falls back to a full sync otherwise.
+pub fn sync_incremental(
+    library: &mut Library,
+    policy: ChecklistPolicy,
+    link_policy: LinkFailurePolicy,
+) -> Result<Vec<OperationWarning>, SError> {
+    launch_checklist::ensure_acknowledged(library, policy)?;
+    dependency::ensure_satisfied(library)?;
+    deploy_incremental(library, policy, link_policy)
+}
+
+fn deploy_incremental(
+    library: &mut Library,
+    policy: ChecklistPolicy,
+    link_policy: LinkFailurePolicy,
+) -> Result<Vec<OperationWarning>, SError> {
+    logging::operation(logging::dir(library), LoggedOperation::Sync, || {
+        info!("Relinking what changed since the last sync");
+        let delta = deploy_journal::run(&library.lib_paths, || {
+            deployment::deploy_delta(
+                &library.game_root,
+                &library.lib_paths,
+                &library.spt_rules,
+                &dedicated_server::deployable_mods(library),
+                &file_overrides::deployable_cache(library),
+                link_policy,
+                library.deployment_mode,
+            )
+        })?;
+        let Some(mut warnings) = delta else {
+            info!("The file lists changed, falling back to a full sync");
+            return sync(library, policy, link_policy);
+        };
+
+        warnings.extend(dedicated_server::sync_warnings(library));
+        warnings.extend(bepinex::sync_warnings(library, SyncScope::All));
+        warnings.extend(ownership::sync_warnings(library, SyncScope::All));
+        warnings.extend(volume::sync_warnings(library));
+        library.mark_clean();
+        library.persist()?;
+        record_sync(library, SyncScope::All);
+        Ok(warnings)
+    })
+}
+
+fn record_sync(library: &Library, scope: SyncScope) {
+    let active = library.mods.values().filter(|m| m.is_active).count();
+    let side = match scope {
+        SyncScope::All => "",
+        SyncScope::ClientOnly => " (client only)",
+        SyncScope::ServerOnly => " (server only)",
+    };
+    let summary = format!("Sync {active} active mod(s){side}");
+    info!("{summary}");
+
+    // The sync is done, so only the support bundle misses out if the plan cannot be kept
+    let deployed = deployment::plan(
+        &library.game_root,
+        &library.lib_paths,
+        &library.spt_rules,
+        &dedicated_server::deployable_mods(library),
+        &deployment::scope_cache(
+            &file_overrides::deployable_cache(library),
+            &library.spt_rules,
+            scope,
+        ),
+    )
+    .and_then(|plan| plan_store::record_last_sync(&library.lib_paths, &plan));
+    if let Err(e) = deployed {
+        warn!("Failed to keep the plan of the last sync: {e}");
+    }
+    repo_history::record(&library.repo_root, &summary);
+}
+
+/// Switches between linking and copying mod files into the game root.
+/// Purge removes links and copies alike, so only the sync index is dropped: the next sync
+/// starts over instead of moving from what the other mode deployed.
+pub fn set_deployment_mode(library: &mut Library, mode: DeploymentMode) -> Result<(), SError> {
+    if library.deployment_mode == mode {
+        return Ok(());
+    }
+    sync_index::clear(&library.lib_paths)?;
+    library.deployment_mode = mode;
+    library.mark_dirty();
+    library.persist_manifest()
+}
+
+/// Rebuilds the cache from the mod folders in the repo, for when it is missing, unreadable or
+/// drifted from them; works on a library whose cache failed to load.
+/// Folders and manifest entries without a counterpart are reported and left as they are.
+pub fn rebuild_cache(library: &mut Library) -> Result<CacheRebuildReport, SError> {
+    library.ensure_writable()?;
+    let store = repo_store::open(&library.lib_paths);
+    let stored = store.ids()?;
+    let mut report = CacheRebuildReport {
+        untracked_folders: stored
+            .iter()
+            .filter(|id| !library.mods.contains_key(*id))
+            .cloned()
+            .collect(),
+        missing_folders: library
+            .mods
+            .keys()
+            .filter(|id| !stored.contains(*id))
+            .cloned()
+            .collect(),
+        ..Default::default()
+    };
+
+    let mut cache = LibraryCache::default();
+    for id in stored.iter().filter(|id| library.mods.contains_key(*id)) {
+        let path = store.payload_dir(id);
+        let mut mod_fs = ModFS::new(&path, &library.spt_rules)?;
+        // The folder is named after the id the mod was installed under, which an earlier
+        // path id scheme may have derived
+        if mod_fs.id != *id {
+            mod_fs.canonical_id = match mod_fs.canonical_id {
+                Some(_) => ModFS::canonical_path_ids(&library.spt_rules, &mod_fs.files)?
+                    .into_iter()
+                    .find(|canonical| canonical.hash() == *id),
+                None => None,
+            };
+            mod_fs.id = id.clone();
+        }
+        cache.add(&path, mod_fs);
+        report.rebuilt.push(id.clone());
+    }
+    // Update checks still hold for the mods that kept their entry
+    cache.updates = std::mem::take(&mut library.cache.updates)
+        .into_iter()
+        .filter(|(id, _)| cache.mods.contains_key(id))
+        .collect();
+
+    match library.is_loaded() {
+        true => library.cache = cache,
+        false => library.attach_cache(cache)?,
+    }
+    // Swapped in whole, entries of the old store would survive a write
+    cache_store::replace(&library.lib_paths, &library.cache)?;
+    library.persist()?;
+    search_index::rebuild(library);
+
+    info!(
+        "Rebuilt {} cache entries, {} untracked folder(s), {} mod(s) without a folder",
+        report.rebuilt.len(),
+        report.untracked_folders.len(),
+        report.missing_folders.len()
+    );
+    Ok(report)
+}
+
 /// Renames the active library and persists the change.
 pub fn rename_library(library: &mut Library, name: String) -> Result<(), SError> {
-    library.name = name;
-    library.persist()?;
+    library.name = naming::sanitize_name(&name)?;
+    library.persist_manifest()?;
     Ok(())
 }
 
@@ -1,3 +1,4 @@
+use crate::utils::retry;
 use camino::{Utf8Path, Utf8PathBuf};
 use file_id::{get_file_id, FileId};
 use std::fs;
@@ -23,10 +24,34 @@ pub fn read_link_target(path: &Utf8Path) -> io::Result<Utf8PathBuf> {
     Ok(Utf8PathBuf::from(target.to_string_lossy().to_string()))
 }
 
+/// How `link` connected a target to its source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// The target was already linked to the source.
+    Existing,
+    /// Hard link to a file, Windows only.
+    HardLink,
+    /// Junction to a directory, Windows only.
+    Junction,
+    /// Symbolic link, used for everything on Unix.
+    Symlink,
+    /// Symbolic link made because a hard link cannot cross volumes, e.g. a library on another
+    /// drive than the game. Cleanup still recognises it by its target.
+    CrossDeviceSymlink,
+}
+
+/// Whether the error comes from a hard link or rename across filesystems.
+pub fn is_cross_device(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::CrossesDevices
+}
+
 /// Creates a link from source to target.
-/// - Windows: Uses Hard Links for files, Junctions for directories.
+/// - Windows: Uses Hard Links for files, Junctions for directories. Files on another volume
+///   than the target get a Symbolic Link instead, which needs Developer Mode or admin rights.
 /// - Unix: Uses Symbolic Links for everything.
-pub fn link(source: &Utf8Path, target: &Utf8Path) -> io::Result<()> {
+///
+/// Creation is retried while the source is transiently locked, see `retry::io`.
+pub fn link(source: &Utf8Path, target: &Utf8Path) -> io::Result<LinkKind> {
     // 1. Ensure parent directory exists
     if let Some(parent) = target.parent() {
         fs::create_dir_all(parent)?;
@@ -39,13 +64,13 @@ pub fn link(source: &Utf8Path, target: &Utf8Path) -> io::Result<()> {
             if let Ok(existing_target) = read_link_target(target) {
                 // Normalize paths for comparison (optional but recommended)
                 if existing_target == source {
-                    return Ok(()); // Already linked correctly
+                    return Ok(LinkKind::Existing); // Already linked correctly
                 }
             }
         }
         // Case B: It's a File/Hard Link
         else if is_same_file(source, target) {
-            return Ok(()); // Already linked correctly
+            return Ok(LinkKind::Existing); // Already linked correctly
         }
 
         // Case C: Collision
@@ -60,24 +85,38 @@ pub fn link(source: &Utf8Path, target: &Utf8Path) -> io::Result<()> {
     {
         if source.is_dir() {
             // Junctions allow linking directories without Admin rights
-            junction::create(source, target)?;
-        } else {
-            // Hard links allow linking files without Admin rights
-            // Note: Source and Target must be on the same Drive volume.
-            fs::hard_link(source, target)?;
+            retry::io("Linking", target, || junction::create(source, target))?;
+            return Ok(LinkKind::Junction);
+        }
+        // Hard links allow linking files without Admin rights, on the same volume only
+        match retry::io("Linking", target, || fs::hard_link(source, target)) {
+            Ok(()) => Ok(LinkKind::HardLink),
+            Err(e) if is_cross_device(&e) => {
+                retry::io("Linking", target, || {
+                    std::os::windows::fs::symlink_file(source, target)
+                })?;
+                Ok(LinkKind::CrossDeviceSymlink)
+            }
+            Err(e) => Err(e),
         }
     }
     #[cfg(unix)]
     {
-        std::os::unix::fs::symlink(source, target)?;
+        retry::io("Linking", target, || {
+            std::os::unix::fs::symlink(source, target)
+        })?;
+        Ok(LinkKind::Symlink)
     }
-
-    Ok(())
 }
 
 /// Safely removes a link, file, or empty directory.
 /// Handles platform differences between Junctions, Symlinks, and Files.
+/// Removal is retried while the target is transiently locked.
 pub fn unlink(target: &Utf8Path) -> io::Result<()> {
+    retry::io("Unlinking", target, || remove_link(target))
+}
+
+fn remove_link(target: &Utf8Path) -> io::Result<()> {
     // Check if path exists or is a broken symlink
     let meta = match fs::symlink_metadata(target) {
         Ok(m) => m,
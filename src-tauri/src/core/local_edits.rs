@@ -0,0 +1,149 @@
+use crate::core::cache::LibraryCache;
+use crate::core::library::Library;
+use crate::core::mod_backup;
+use crate::core::mod_fs::FileStamp;
+use crate::core::repo_store;
+use crate::models::error::SError;
+use crate::models::paths::LibPathRules;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+/// Stamps the files of a mod as they are in the repo. Unreadable files are left out,
+/// so they are never reported as edited.
+fn stamp(root: &Utf8Path, files: &[Utf8PathBuf]) -> BTreeMap<Utf8PathBuf, FileStamp> {
+    files
+        .iter()
+        .filter_map(|rel| Some((rel.clone(), stamp_of(&root.join(rel)).ok()?)))
+        .collect()
+}
+
+/// Files of each mod that changed in the repo since they were stamped, e.g. a config edited
+/// through its hardlink in the game folder. Only files whose size or modification time moved
+/// are hashed again; deleted files count as edited.
+pub fn detect(
+    cache: &LibraryCache,
+    lib_paths: &LibPathRules,
+) -> BTreeMap<String, Vec<Utf8PathBuf>> {
+    let store = repo_store::open(lib_paths);
+    cache
+        .mods
+        .iter()
+        .map(|(id, mod_fs)| {
+            let root = store.payload_dir(id);
+            let edited: Vec<Utf8PathBuf> = mod_fs
+                .stamps
+                .iter()
+                .filter(|(rel, recorded)| is_edited(&root.join(rel), recorded))
+                .map(|(rel, _)| rel.clone())
+                .collect();
+            (id.clone(), edited)
+        })
+        .filter(|(_, edited)| !edited.is_empty())
+        .collect()
+}
+
+/// Accepts the edits of a mod: its files are stamped again as they are now.
+pub fn keep(library: &mut Library, mod_id: &str) -> Result<(), SError> {
+    library.ensure_writable()?;
+    let root = repo_store::open(&library.lib_paths).payload_dir(mod_id);
+    let mod_fs = library
+        .cache
+        .mods
+        .get_mut(mod_id)
+        .ok_or_else(|| SError::ModNotFound(mod_id.to_string()))?;
+
+    mod_fs.stamps = stamp(&root, &mod_fs.files);
+    library.cache.local_edits.remove(mod_id);
+    library.persist()
+}
+
+/// Puts back the original content of the edited files of a mod, taken from its backups.
+/// Content is written into the existing files, so hardlinks in the game folder stay valid.
+/// Fails with `NoOriginalCopy` before anything is written when a backup holds no copy of
+/// a file with its stamped digest; reinstalling the mod is left as the way back then.
+pub fn revert(library: &mut Library, mod_id: &str) -> Result<(), SError> {
+    library.ensure_writable()?;
+    let mod_fs = library
+        .cache
+        .mods
+        .get(mod_id)
+        .ok_or_else(|| SError::ModNotFound(mod_id.to_string()))?;
+    let Some(edited) = library.cache.local_edits.get(mod_id) else {
+        return Ok(());
+    };
+
+    let backups = mod_backup::list_backups(&library.lib_paths, mod_id)?;
+    let (originals, missing): (Vec<_>, Vec<_>) = edited
+        .iter()
+        .map(|rel| {
+            let original = mod_fs.stamps.get(rel).and_then(|recorded| {
+                backups
+                    .iter()
+                    .map(|backup| backup.path.join(rel))
+                    .find(|copy| stamp_of(copy).is_ok_and(|s| s.digest == recorded.digest))
+            });
+            (rel, original)
+        })
+        .partition(|(_, original)| original.is_some());
+    if !missing.is_empty() {
+        return Err(SError::NoOriginalCopy(
+            missing.iter().map(|(rel, _)| rel.to_string()).collect(),
+        ));
+    }
+
+    let root = repo_store::open(&library.lib_paths).payload_dir(mod_id);
+    let restored = originals
+        .into_iter()
+        .filter_map(|(rel, original)| Some((rel.clone(), original?)))
+        .map(|(rel, original)| {
+            let path = root.join(&rel);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&original, &path)?;
+            Ok((rel, stamp_of(&path)?))
+        })
+        .collect::<Result<Vec<_>, SError>>()?;
+
+    if let Some(mod_fs) = library.cache.mods.get_mut(mod_id) {
+        mod_fs.stamps.extend(restored);
+    }
+    library.cache.local_edits.remove(mod_id);
+    library.persist()
+}
+
+fn is_edited(path: &Utf8Path, recorded: &FileStamp) -> bool {
+    if !path.exists() {
+        return true;
+    }
+    if is_current(path, recorded) {
+        return false;
+    }
+    stamp_of(path).map_or(true, |now| now.digest != recorded.digest)
+}
+
+/// Whether the size and modification time of a file still match its stamp.
+pub(crate) fn is_current(path: &Utf8Path, recorded: &FileStamp) -> bool {
+    fs::metadata(path)
+        .is_ok_and(|meta| meta.len() == recorded.len && modified_nanos(&meta) == recorded.modified)
+}
+
+pub(crate) fn stamp_of(path: &Utf8Path) -> Result<FileStamp, SError> {
+    let meta = fs::metadata(path)?;
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(FileStamp {
+        len: meta.len(),
+        modified: modified_nanos(&meta),
+        digest: hasher.finalize().to_hex().to_string(),
+    })
+}
+
+fn modified_nanos(meta: &fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos() as u64)
+}
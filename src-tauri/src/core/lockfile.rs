@@ -0,0 +1,181 @@
+use crate::core::library::Library;
+use crate::core::mod_fs::ModFS;
+use crate::core::mod_manager;
+use crate::core::repo_store;
+use crate::models::error::SError;
+use crate::models::lockfile::{ActivationFix, LockedMod, Lockfile, LockfileDiff, VersionMismatch};
+use crate::models::mod_dto::{LinkType, ModManifest};
+use crate::models::warning::{OperationWarning, WarningKind};
+use crate::utils::toml::Toml;
+use camino::Utf8PathBuf;
+
+pub const FORMAT: u32 = 1;
+
+/// Locks the active mods of the library, ordered by id.
+pub fn build(library: &Library) -> Result<Lockfile, SError> {
+    let mods = library
+        .mods
+        .values()
+        .filter(|m| m.is_active)
+        .map(|m| {
+            let manifest = library.cache.manifests.get(&m.id);
+            Ok(LockedMod {
+                id: m.id.clone(),
+                name: m.name.clone(),
+                version: manifest.map(|manifest| manifest.version.clone()),
+                content_hash: content_hash(library, &m.id)?,
+                source_url: manifest.and_then(source_url),
+            })
+        })
+        .collect::<Result<Vec<LockedMod>, SError>>()?;
+
+    Ok(Lockfile {
+        format: FORMAT,
+        mods,
+    })
+}
+
+pub fn export(library: &Library, output: &Utf8PathBuf) -> Result<(), SError> {
+    Toml::write(output, &build(library)?)
+}
+
+/// Compares the library with a lockfile shared by another user.
+/// Returns a warning per locked mod that is missing or whose version or content differs.
+pub fn verify(library: &Library, input: &Utf8PathBuf) -> Result<Vec<OperationWarning>, SError> {
+    read(input)?
+        .mods
+        .iter()
+        .map(|locked| mismatch(library, locked))
+        .filter_map(Result::transpose)
+        .collect()
+}
+
+/// Compares the library with a lockfile shared by another player, along with the activation
+/// changes that would make both sides run the same mods. Files are not hashed, see `verify`.
+pub fn check_against(library: &Library, input: &Utf8PathBuf) -> Result<LockfileDiff, SError> {
+    let lockfile = read(input)?;
+    let installed = |locked: &&LockedMod| library.mods.contains_key(&locked.id);
+
+    let missing = lockfile
+        .mods
+        .iter()
+        .filter(|locked| !installed(locked))
+        .cloned()
+        .collect();
+    let version_mismatches = lockfile
+        .mods
+        .iter()
+        .filter(installed)
+        .filter_map(|locked| {
+            let version = installed_version(library, &locked.id);
+            (version != locked.version).then(|| VersionMismatch {
+                id: locked.id.clone(),
+                name: locked.name.clone(),
+                locked: locked.version.clone(),
+                installed: version,
+            })
+        })
+        .collect();
+    let activate = lockfile
+        .mods
+        .iter()
+        .filter(|locked| library.mods.get(&locked.id).is_some_and(|m| !m.is_active))
+        .map(|locked| locked.id.clone())
+        .collect();
+    let extra_active: Vec<String> = library
+        .mods
+        .values()
+        .filter(|m| m.is_active && !lockfile.mods.iter().any(|locked| locked.id == m.id))
+        .map(|m| m.id.clone())
+        .collect();
+
+    Ok(LockfileDiff {
+        missing,
+        version_mismatches,
+        fix: ActivationFix {
+            activate,
+            deactivate: extra_active.clone(),
+        },
+        extra_active,
+    })
+}
+
+/// Applies the activation changes of `check_against`. The library is left dirty, to be synced
+/// like any other toggle.
+pub fn fix_activation(library: &mut Library, input: &Utf8PathBuf) -> Result<ActivationFix, SError> {
+    let fix = check_against(library, input)?.fix;
+    // Deactivating first, so the mods to activate are not held back by a conflict
+    mod_manager::toggle_mods(library, &fix.deactivate, false)?;
+    mod_manager::toggle_mods(library, &fix.activate, true)?;
+    Ok(fix)
+}
+
+fn read(input: &Utf8PathBuf) -> Result<Lockfile, SError> {
+    let lockfile: Lockfile = Toml::read(input)?;
+    if lockfile.format > FORMAT {
+        return Err(SError::ParseError(format!(
+            "Unsupported lockfile format {}",
+            lockfile.format
+        )));
+    }
+    Ok(lockfile)
+}
+
+fn installed_version(library: &Library, id: &str) -> Option<String> {
+    library
+        .cache
+        .manifests
+        .get(id)
+        .map(|manifest| manifest.version.clone())
+}
+
+fn mismatch(library: &Library, locked: &LockedMod) -> Result<Option<OperationWarning>, SError> {
+    if !library.mods.contains_key(&locked.id) {
+        return Ok(Some(OperationWarning::new(
+            WarningKind::LockedModMissing,
+            &locked.name,
+        )));
+    }
+
+    let version = installed_version(library, &locked.id);
+    if version != locked.version {
+        return Ok(Some(
+            OperationWarning::new(WarningKind::LockedVersionMismatch, &locked.name).with_details(
+                &[
+                    locked.version.as_deref().unwrap_or_default(),
+                    version.as_deref().unwrap_or_default(),
+                ],
+            ),
+        ));
+    }
+
+    let hash = content_hash(library, &locked.id)?;
+    if hash != locked.content_hash {
+        return Ok(Some(
+            OperationWarning::new(WarningKind::LockedContentMismatch, &locked.name)
+                .with_details(&[&locked.content_hash, &hash]),
+        ));
+    }
+    Ok(None)
+}
+
+fn content_hash(library: &Library, id: &str) -> Result<String, SError> {
+    let files = library
+        .cache
+        .mods
+        .get(id)
+        .map(|fs| fs.files.as_slice())
+        .unwrap_or_default();
+    let hash = ModFS::content_hash(&repo_store::open(&library.lib_paths).payload_dir(id), files)?;
+    Ok(hash.to_hex().to_string())
+}
+
+/// Where the mod can be downloaded: its website, or failing that its first link.
+pub(crate) fn source_url(manifest: &ModManifest) -> Option<String> {
+    let links = manifest.links.as_ref()?;
+    links
+        .iter()
+        .find(|link| link.link_type == Some(LinkType::Website))
+        .or_else(|| links.first())
+        .map(|link| link.url.clone())
+}
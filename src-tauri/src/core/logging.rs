@@ -0,0 +1,220 @@
+use crate::core::library::Library;
+use crate::models::error::SError;
+use crate::models::logging::{LogLevel, LogStep, LoggedOperation, OperationLog};
+use crate::utils::time::get_unix_timestamp;
+use crate::utils::toml::Toml;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::fs;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::{warn, Event, Level, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter, Layer};
+
+/// Number of operation logs kept per library, older ones are removed as new ones are written.
+pub const MAX_OPERATION_LOGS: usize = 100;
+
+/// Number of daily app logs kept in the data directory.
+const MAX_APP_LOGS: usize = 7;
+
+struct Running {
+    log: OperationLog,
+    start: Instant,
+    /// Names the log file, finer than the timestamp so logs sort in the order they were made.
+    started: Duration,
+}
+
+thread_local! {
+    /// The operation running on this thread, which logged events are added to.
+    static CURRENT: RefCell<Option<Running>> = const { RefCell::new(None) };
+}
+
+/// Installs the app-wide subscriber: the console, a daily app log in `dir` and the log of
+/// whichever operation is running. `RUST_LOG` overrides the default `info` level.
+/// Only the first call installs anything.
+pub fn init(dir: Option<Utf8PathBuf>) {
+    // Nothing is subscribed yet, so a failure to open the app log can only go to stderr
+    let app_log = dir.and_then(|dir| {
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix("mod-keeper")
+            .filename_suffix("log")
+            .max_log_files(MAX_APP_LOGS)
+            .build(dir)
+            .inspect_err(|e| eprintln!("Failed to open the app log: {e}"))
+            .ok()
+    });
+
+    let _ = tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(fmt::layer())
+        .with(app_log.map(|writer| fmt::layer().with_ansi(false).with_writer(writer)))
+        .with(Capture)
+        .try_init();
+}
+
+/// Where the operations of `library` are logged, `None` when nothing may be written to it.
+pub fn dir(library: &Library) -> Option<Utf8PathBuf> {
+    library.is_writable.then(|| library.lib_paths.logs.clone())
+}
+
+/// Runs `f` as `operation`, writing what it logged and how it ended to a file of its own in
+/// `dir`. An operation started while another one runs on the same thread is part of that one.
+pub fn operation<T>(
+    dir: Option<Utf8PathBuf>,
+    operation: LoggedOperation,
+    f: impl FnOnce() -> Result<T, SError>,
+) -> Result<T, SError> {
+    let Some(dir) = dir else {
+        return f();
+    };
+    if CURRENT.with_borrow(Option::is_some) {
+        return f();
+    }
+
+    CURRENT.set(Some(Running {
+        log: OperationLog {
+            id: uuid::Uuid::new_v4().to_string(),
+            operation,
+            timestamp: get_unix_timestamp().to_string(),
+            duration_ms: 0,
+            steps: Vec::new(),
+            error: None,
+        },
+        start: Instant::now(),
+        started: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default(),
+    }));
+    let result = f();
+    let Some(Running {
+        mut log,
+        start,
+        started,
+    }) = CURRENT.take()
+    else {
+        return result;
+    };
+
+    log.duration_ms = start.elapsed().as_millis() as u32;
+    log.error = result.as_ref().err().map(ToString::to_string);
+    let name = format!("{:020}-{}.toml", started.as_nanos(), log.id);
+    if let Err(e) = write(&dir.join(name), &log) {
+        warn!("Failed to write the {operation:?} log: {e}");
+    }
+    result
+}
+
+/// The latest operation logs, newest first. Unreadable ones are skipped.
+pub fn read(dir: &Utf8Path, limit: Option<usize>) -> Result<Vec<OperationLog>, SError> {
+    Ok(log_files(dir)?
+        .iter()
+        .rev()
+        .filter_map(|path| {
+            Toml::read(path)
+                .inspect_err(|e| warn!("Skipping unreadable log {path}: {e}"))
+                .ok()
+        })
+        .take(limit.unwrap_or(usize::MAX))
+        .collect())
+}
+
+fn write(path: &Utf8PathBuf, log: &OperationLog) -> Result<(), SError> {
+    let dir = path.parent().unwrap_or(path);
+    fs::create_dir_all(dir)?;
+    Toml::write(path, log)?;
+    rotate(dir)
+}
+
+/// Removes the oldest logs beyond `MAX_OPERATION_LOGS`.
+fn rotate(dir: &Utf8Path) -> Result<(), SError> {
+    let files = log_files(dir)?;
+    files
+        .iter()
+        .take(files.len().saturating_sub(MAX_OPERATION_LOGS))
+        .try_for_each(fs::remove_file)?;
+    Ok(())
+}
+
+/// Log files in `dir`, oldest first. File names start with the time the operation started.
+fn log_files(dir: &Utf8Path) -> Result<Vec<Utf8PathBuf>, SError> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = dir
+        .read_dir_utf8()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.into_path())
+        .filter(|path| path.extension() == Some("toml"))
+        .collect::<Vec<Utf8PathBuf>>();
+    files.sort();
+    Ok(files)
+}
+
+/// Adds every event to the log of the operation running on its thread.
+struct Capture;
+
+impl<S: Subscriber> Layer<S> for Capture {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        CURRENT.with_borrow_mut(|current| {
+            let Some(running) = current else {
+                return;
+            };
+            let mut message = Message::default();
+            event.record(&mut message);
+            running.log.steps.push(LogStep {
+                elapsed_ms: running.start.elapsed().as_millis() as u32,
+                level: level_of(event.metadata().level()),
+                target: event.metadata().target().to_string(),
+                message: message.into_string(),
+            });
+        });
+    }
+}
+
+fn level_of(level: &Level) -> LogLevel {
+    match *level {
+        Level::ERROR => LogLevel::Error,
+        Level::WARN => LogLevel::Warn,
+        Level::INFO => LogLevel::Info,
+        Level::DEBUG => LogLevel::Debug,
+        _ => LogLevel::Trace,
+    }
+}
+
+/// The message of an event followed by its other fields as `name=value`.
+#[derive(Default)]
+struct Message {
+    text: String,
+    fields: Vec<String>,
+}
+
+impl Message {
+    fn into_string(self) -> String {
+        std::iter::once(self.text)
+            .chain(self.fields)
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+impl Visit for Message {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.text = value.to_string(),
+            name => self.fields.push(format!("{name}={value}")),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        match field.name() {
+            "message" => self.text = format!("{value:?}"),
+            name => self.fields.push(format!("{name}={value:?}")),
+        }
+    }
+}
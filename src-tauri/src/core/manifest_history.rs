@@ -0,0 +1,87 @@
+use crate::core::library::Library;
+use crate::models::error::SError;
+use crate::models::paths::LibPathRules;
+use crate::utils::file::FileUtils;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// Number of manifest generations kept, older ones are removed as new ones are recorded.
+pub const GENERATIONS: usize = 10;
+
+const GENERATION_EXTENSION: &str = "toml";
+
+/// Keeps `content`, just written to the manifest, as its latest generation.
+/// The manifest itself is already safe on disk, so failing to keep a copy is only logged.
+pub fn record(lib_paths: &LibPathRules, content: &str) {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let path = lib_paths
+        .history
+        .join(format!("manifest-{nanos:020}.{GENERATION_EXTENSION}"));
+
+    let kept = fs::create_dir_all(&lib_paths.history)
+        .map_err(SError::from)
+        .and_then(|_| FileUtils::write_atomic(&path, content))
+        .and_then(|_| prune(lib_paths));
+    if let Err(e) = kept {
+        warn!("Failed to keep a manifest generation: {e}");
+    }
+}
+
+/// Kept generations, newest first.
+pub fn generations(lib_paths: &LibPathRules) -> Result<Vec<Utf8PathBuf>, SError> {
+    if !lib_paths.history.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths = lib_paths
+        .history
+        .read_dir_utf8()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.into_path())
+        .filter(|path| path.extension() == Some(GENERATION_EXTENSION))
+        .collect::<Vec<Utf8PathBuf>>();
+    // Names start with the time they were recorded
+    paths.sort_by(|a, b| b.cmp(a));
+    Ok(paths)
+}
+
+/// Restores the manifest of the library at `repo_root` from its latest readable generation.
+/// Returns whether it was restored; a manifest that reads fine is left alone.
+pub fn repair(repo_root: &Utf8Path) -> Result<bool, SError> {
+    let lib_paths = LibPathRules::new(repo_root);
+    let Err(e) = Library::read_library_manifest(repo_root) else {
+        return Ok(false);
+    };
+    warn!("The manifest of {repo_root} is unreadable: {e}");
+
+    let (path, content) = generations(&lib_paths)?
+        .into_iter()
+        .filter_map(|path| {
+            fs::read_to_string(&path)
+                .ok()
+                .map(|content| (path, content))
+        })
+        .find(|(_, content)| Library::parse_library_manifest(content).is_ok())
+        .ok_or_else(|| {
+            SError::InvalidLibrary(
+                repo_root.to_string(),
+                format!("No readable manifest generation to restore ({e})"),
+            )
+        })?;
+
+    FileUtils::write_atomic(&lib_paths.manifest, content)?;
+    info!("Restored the manifest of {repo_root} from {path}");
+    Ok(true)
+}
+
+fn prune(lib_paths: &LibPathRules) -> Result<(), SError> {
+    generations(lib_paths)?
+        .iter()
+        .skip(GENERATIONS)
+        .try_for_each(fs::remove_file)?;
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use crate::models::metrics::{Operation, OperationMetric};
+use crate::utils::toml::Toml;
+use camino::Utf8PathBuf;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Number of samples kept per operation for the rolling average.
+pub const WINDOW: usize = 20;
+
+/// Shortest time between two writes of the samples; `flush` writes the rest on exit.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Recent durations per operation, in microseconds.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct MetricsStore {
+    samples: BTreeMap<Operation, Vec<u64>>,
+}
+
+impl MetricsStore {
+    pub const fn new() -> Self {
+        Self {
+            samples: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, operation: Operation, elapsed: Duration) {
+        let samples = self.samples.entry(operation).or_default();
+        samples.push(elapsed.as_micros() as u64);
+        if samples.len() > WINDOW {
+            samples.drain(..samples.len() - WINDOW);
+        }
+    }
+
+    pub fn summary(&self) -> Vec<OperationMetric> {
+        self.samples
+            .iter()
+            .filter_map(|(operation, samples)| {
+                let last = *samples.last()?;
+                let total: u64 = samples.iter().sum();
+                Some(OperationMetric {
+                    operation: *operation,
+                    samples: samples.len() as u32,
+                    average_ms: total as f64 / samples.len() as f64 / 1000.0,
+                    last_ms: last as f64 / 1000.0,
+                })
+            })
+            .collect()
+    }
+}
+
+struct Recorder {
+    store: MetricsStore,
+    /// Where samples are persisted; unset until `init`, so tests stay in memory.
+    path: Option<Utf8PathBuf>,
+    /// When the samples were last written, see `SAVE_INTERVAL`.
+    saved: Option<Instant>,
+    unsaved: bool,
+}
+
+impl Recorder {
+    fn save(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(e) = Toml::write(path, &self.store) {
+            warn!("Failed to store performance metrics: {e}");
+        }
+        self.saved = Some(Instant::now());
+        self.unsaved = false;
+    }
+}
+
+static RECORDER: Mutex<Recorder> = Mutex::new(Recorder {
+    store: MetricsStore::new(),
+    path: None,
+    saved: None,
+    unsaved: false,
+});
+
+/// Loads previously stored samples and persists new ones to `path` from now on.
+pub fn init(path: Utf8PathBuf) {
+    let mut recorder = RECORDER.lock();
+    recorder.store = Toml::read(&path).unwrap_or_default();
+    recorder.path = Some(path);
+    recorder.saved = Some(Instant::now());
+}
+
+/// Records a sample. Samples are written at most once per `SAVE_INTERVAL`, since timed
+/// operations like cache writes run in bursts.
+pub fn record(operation: Operation, elapsed: Duration) {
+    let mut recorder = RECORDER.lock();
+    recorder.store.record(operation, elapsed);
+    recorder.unsaved = true;
+    debug!("{operation:?} took {elapsed:?}");
+
+    if recorder
+        .saved
+        .is_none_or(|saved| saved.elapsed() >= SAVE_INTERVAL)
+    {
+        recorder.save();
+    }
+}
+
+/// Writes the samples recorded since the last write, e.g. when the app exits.
+pub fn flush() {
+    let mut recorder = RECORDER.lock();
+    if recorder.unsaved {
+        recorder.save();
+    }
+}
+
+pub fn summary() -> Vec<OperationMetric> {
+    RECORDER.lock().store.summary()
+}
+
+/// Records the time until it is dropped, so early returns are measured too.
+pub struct Timer {
+    operation: Operation,
+    start: Instant,
+}
+
+impl Timer {
+    pub fn start(operation: Operation) -> Self {
+        Self {
+            operation,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        record(self.operation, self.start.elapsed());
+    }
+}
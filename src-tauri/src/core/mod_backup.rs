@@ -1,28 +1,140 @@
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 
 use crate::core::library::Library;
 use crate::core::mod_fs::ModFS;
+use crate::core::progress::Progress;
+use crate::core::repo_store;
+use crate::core::search_index;
+use crate::core::sync_index;
 use crate::models::error::SError;
-use crate::models::mod_backup::ModBackup;
-use crate::models::paths::LibPathRules;
+use crate::models::mod_backup::{BackupRetention, BackupUsage, ModBackup, ModBackupUsage};
+use crate::models::paths::{LibPathRules, ModPaths};
+use crate::models::warning::{OperationWarning, WarningKind};
 use crate::utils::file::FileUtils;
+use crate::utils::naming;
+use crate::utils::scan;
 use crate::utils::time::get_unix_timestamp;
 
-/// Creates a backup of a mod at the current timestamp.
-/// Backup is stored at: `backups/{mod_id}/{timestamp}/`
-pub fn create_backup(lib_paths: &LibPathRules, mod_id: &str) -> Result<(), SError> {
-    let mod_dir = lib_paths.mods.join(mod_id);
+const VERSION_SEPARATOR: char = '_';
+/// Marks the folders of backups taken by `restore_backup`.
+const RESTORE_SUFFIX: &str = "-restore";
+/// Backups taken before restores kept per mod, so restoring back and forth cannot pile them up.
+const MAX_RESTORE_SNAPSHOTS: usize = 3;
+const DAY_SECS: u64 = 24 * 60 * 60;
+const MIB: u64 = 1024 * 1024;
 
-    if !mod_dir.exists() {
+/// Creates a backup of a mod at the current timestamp, then prunes the backups of the library
+/// per its retention.
+/// Backup is stored at: `backups/{mod_id}/{version}_{timestamp}/`, or `backups/{mod_id}/{timestamp}/`
+/// when the payload has no manifest version. Fails before copying if a file would exceed `MAX_PATH`.
+pub fn create_backup(library: &Library, mod_id: &str) -> Result<(), SError> {
+    snapshot(&library.lib_paths, mod_id, "")?;
+    prune(&library.lib_paths, &library.backup_retention).map(|_| ())
+}
+
+/// Changes the retention of the library and prunes its backups right away.
+pub fn set_retention(library: &mut Library, retention: BackupRetention) -> Result<(), SError> {
+    library.backup_retention = retention;
+    library.persist_manifest()?;
+    prune(&library.lib_paths, &library.backup_retention).map(|_| ())
+}
+
+/// Removes the backups the retention does not keep: those beyond `max_per_mod` for their mod,
+/// those older than `max_age_days`, then the oldest ones until the rest fits `max_total_mib`.
+/// The newest backup of each mod is always kept, so the last update can still be undone.
+/// Returns the removed backups.
+pub fn prune(
+    lib_paths: &LibPathRules,
+    retention: &BackupRetention,
+) -> Result<Vec<ModBackup>, SError> {
+    let now = get_unix_timestamp();
+    let too_old = |backup: &ModBackup| {
+        let taken = backup.timestamp.parse::<u64>().unwrap_or(now);
+        retention
+            .max_age_days
+            .is_some_and(|days| now.saturating_sub(taken) > u64::from(days) * DAY_SECS)
+    };
+
+    let mut removed = Vec::new();
+    // Candidates for the size limit, oldest removed first
+    let mut removable = Vec::new();
+    let mut total_bytes = 0;
+    for mod_id in backed_up_mods(lib_paths)? {
+        let mut regular = 0;
+        for (index, backup) in list_backups(lib_paths, &mod_id)?.into_iter().enumerate() {
+            regular += u32::from(!backup.pre_restore);
+            let over_count =
+                !backup.pre_restore && retention.max_per_mod.is_some_and(|max| regular > max);
+            if index > 0 && (over_count || too_old(&backup)) {
+                removed.push(backup);
+                continue;
+            }
+            let bytes = size_of(&backup.path);
+            total_bytes += bytes;
+            if index > 0 {
+                removable.push((backup, bytes));
+            }
+        }
+    }
+
+    if let Some(max_mib) = retention.max_total_mib {
+        let max_bytes = u64::from(max_mib) * MIB;
+        removable.sort_by(|(a, _), (b, _)| a.timestamp.cmp(&b.timestamp));
+        for (backup, bytes) in removable {
+            if total_bytes <= max_bytes {
+                break;
+            }
+            total_bytes -= bytes;
+            removed.push(backup);
+        }
+    }
+
+    removed
+        .iter()
+        .try_for_each(|backup| FileUtils::remove_recursive(&backup.path))?;
+    Ok(removed)
+}
+
+/// Space taken by the backups of every mod.
+pub fn disk_usage(lib_paths: &LibPathRules) -> Result<BackupUsage, SError> {
+    let mods = backed_up_mods(lib_paths)?
+        .into_iter()
+        .map(|mod_id| {
+            let backups = list_backups(lib_paths, &mod_id)?;
+            Ok(ModBackupUsage {
+                bytes: backups.iter().map(|backup| size_of(&backup.path)).sum(),
+                backups: backups.len() as u32,
+                mod_id,
+            })
+        })
+        .collect::<Result<Vec<ModBackupUsage>, SError>>()?;
+
+    Ok(BackupUsage {
+        total_bytes: mods.iter().map(|m| m.bytes).sum(),
+        mods,
+    })
+}
+
+fn snapshot(lib_paths: &LibPathRules, mod_id: &str, suffix: &str) -> Result<(), SError> {
+    let store = repo_store::open(lib_paths);
+    if !store.contains(mod_id) {
         return Ok(()); // Nothing to backup
     }
+    let mod_dir = store.payload_dir(mod_id);
 
-    let timestamp = get_unix_timestamp().to_string();
-    let backup_dir = lib_paths.backups.join(mod_id).join(&timestamp);
+    let timestamp = format!("{}{suffix}", get_unix_timestamp());
+    let folder = ModFS::read_manifest(&ModPaths::new(&mod_dir).file)
+        .ok()
+        .map(|manifest| sanitize_version(&manifest.version))
+        .filter(|version| !version.is_empty())
+        .map_or_else(
+            || timestamp.clone(),
+            |version| format!("{version}{VERSION_SEPARATOR}{timestamp}"),
+        );
+    let backup_dir = lib_paths.backups.join(mod_id).join(folder);
+    naming::ensure_fits(&backup_dir, &ModFS::collect_files(&mod_dir).0)?;
 
-    std::fs::create_dir_all(&backup_dir)?;
-    FileUtils::copy_recursive(&mod_dir, &backup_dir)?;
-    Ok(())
+    store.export(mod_id, &backup_dir)
 }
 
 /// Lists all available backups for a given mod.
@@ -38,8 +150,15 @@ pub fn list_backups(lib_paths: &LibPathRules, mod_id: &str) -> Result<Vec<ModBac
     let mut backups: Vec<ModBackup> = entries
         .filter_map(|entry| {
             entry.ok().and_then(|e| {
+                let (version, timestamp) = parse_folder(&e.file_name().into_string().ok()?);
+                let (timestamp, pre_restore) = match timestamp.strip_suffix(RESTORE_SUFFIX) {
+                    Some(timestamp) => (timestamp.to_string(), true),
+                    None => (timestamp, false),
+                };
                 Some(ModBackup {
-                    timestamp: e.file_name().into_string().ok()?,
+                    timestamp,
+                    version,
+                    pre_restore,
                     path: Utf8PathBuf::from_path_buf(e.path()).ok()?,
                 })
             })
@@ -52,36 +171,82 @@ pub fn list_backups(lib_paths: &LibPathRules, mod_id: &str) -> Result<Vec<ModBac
     Ok(backups)
 }
 
+/// Deletes the backup of a mod taken at `timestamp`, along with a snapshot taken before a
+/// restore at that time.
+pub fn delete_backup(
+    lib_paths: &LibPathRules,
+    mod_id: &str,
+    timestamp: &str,
+) -> Result<(), SError> {
+    naming::validate_id(mod_id)?;
+    let backups: Vec<ModBackup> = list_backups(lib_paths, mod_id)?
+        .into_iter()
+        .filter(|backup| backup.timestamp == timestamp)
+        .collect();
+    if backups.is_empty() {
+        return Err(SError::FileOrDirectoryNotFound(format!(
+            "{mod_id}/{timestamp}"
+        )));
+    }
+    backups
+        .iter()
+        .try_for_each(|backup| remove_inside(lib_paths, &backup.path))
+}
+
+/// Deletes every backup of a mod, including the snapshots taken before restores.
+pub fn delete_all_backups(lib_paths: &LibPathRules, mod_id: &str) -> Result<(), SError> {
+    naming::validate_id(mod_id)?;
+    let backup_dir = lib_paths.backups.join(mod_id);
+    if !backup_dir.exists() {
+        return Ok(());
+    }
+    remove_inside(lib_paths, &backup_dir)
+}
+
 /// Restores a mod from a backup.
-/// Creates a backup of the current state before restoring.
-pub fn restore_backup(library: &mut Library, mod_id: &str, timestamp: &str) -> Result<(), SError> {
+/// Creates a backup of the current state before restoring, keeping the last
+/// `MAX_RESTORE_SNAPSHOTS` of those. Warns when the mod is deployed while the library has
+/// unsynced changes, as the game then runs neither the restored nor the planned files.
+pub fn restore_backup(
+    library: &mut Library,
+    mod_id: &str,
+    timestamp: &str,
+) -> Result<Vec<OperationWarning>, SError> {
     // Verify mod exists
     if !library.mods.contains_key(mod_id) {
         return Err(SError::ModNotFound(mod_id.to_string()));
     }
 
-    let backup_dir = library.lib_paths.backups.join(mod_id).join(timestamp);
+    let backup_dir = list_backups(&library.lib_paths, mod_id)?
+        .into_iter()
+        .find(|backup| backup.timestamp == timestamp)
+        .map(|backup| backup.path)
+        .ok_or(SError::Unexpected)?;
 
-    if !backup_dir.exists() {
-        return Err(SError::Unexpected);
-    }
+    let is_deployed = sync_index::read(&library.lib_paths)
+        .is_some_and(|index| index.digests.contains_key(mod_id));
+    let warnings = match library.is_dirty && is_deployed {
+        true => vec![OperationWarning::new(
+            WarningKind::RestoredWhileUnsynced,
+            mod_id,
+        )],
+        false => Vec::new(),
+    };
 
-    let mod_dir = library.lib_paths.mods.join(mod_id);
+    // Snapshot the current payload so the restore can be undone
+    snapshot(&library.lib_paths, mod_id, RESTORE_SUFFIX)?;
 
-    // Remove current mod directory
-    if mod_dir.exists() {
-        std::fs::remove_dir_all(&mod_dir)?;
-    }
-
-    // Restore from backup
-    std::fs::create_dir_all(&mod_dir)?;
-    FileUtils::copy_recursive(&backup_dir, &mod_dir)?;
+    // Restore from backup, the current payload stays in place if that fails
+    let store = repo_store::open(&library.lib_paths);
+    store.put(mod_id, &backup_dir, Progress::silent(), mod_id)?;
+    let mod_dir = store.payload_dir(mod_id);
 
     // Rebuild the ModFS for the restored mod
     let restored_fs = ModFS::new(&mod_dir, &library.spt_rules)?;
 
     // Update cache with restored files
     library.cache.add(&mod_dir, restored_fs.clone());
+    search_index::refresh(library, mod_id);
 
     // Update mod metadata if needed
     if let Some(mod_entry) = library.mods.get_mut(mod_id) {
@@ -90,16 +255,64 @@ pub fn restore_backup(library: &mut Library, mod_id: &str, timestamp: &str) -> R
 
     library.mark_dirty();
     library.persist()?;
-    Ok(())
+
+    // Pruned last, the restored backup may have been one of the oldest snapshots
+    prune_restore_snapshots(&library.lib_paths, mod_id)?;
+    Ok(warnings)
 }
 
-/// Removes all backups for a given mod.
-pub fn remove_all_backups(lib_paths: &LibPathRules, mod_id: &str) -> Result<(), SError> {
-    let backup_dir = lib_paths.backups.join(mod_id);
+fn prune_restore_snapshots(lib_paths: &LibPathRules, mod_id: &str) -> Result<(), SError> {
+    list_backups(lib_paths, mod_id)?
+        .into_iter()
+        .filter(|backup| backup.pre_restore)
+        .skip(MAX_RESTORE_SNAPSHOTS)
+        .try_for_each(|backup| FileUtils::remove_recursive(&backup.path))
+}
 
-    if backup_dir.exists() {
-        std::fs::remove_dir_all(&backup_dir)?;
+/// Removes a backup folder once it resolves to a folder below `lib_paths.backups`,
+/// so neither an id nor a link can point the removal elsewhere.
+fn remove_inside(lib_paths: &LibPathRules, path: &Utf8Path) -> Result<(), SError> {
+    let root = lib_paths.backups.canonicalize_utf8()?;
+    let target = path.canonicalize_utf8()?;
+    if target == root || !target.starts_with(&root) {
+        return Err(SError::InvalidName(format!("{path} is not a backup")));
     }
+    FileUtils::remove_recursive(path)
+}
+
+/// Ids of the mods with a backup folder, sorted.
+fn backed_up_mods(lib_paths: &LibPathRules) -> Result<Vec<String>, SError> {
+    if !lib_paths.backups.exists() {
+        return Ok(Vec::new());
+    }
+    let mut ids: Vec<String> = std::fs::read_dir(&lib_paths.backups)?
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+
+fn size_of(path: &Utf8Path) -> u64 {
+    scan::walk(path)
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Splits a backup folder name into its version and timestamp.
+/// Folders created before versions were recorded hold the timestamp alone.
+fn parse_folder(name: &str) -> (Option<String>, String) {
+    match name.rsplit_once(VERSION_SEPARATOR) {
+        Some((version, timestamp)) => (Some(version.to_string()), timestamp.to_string()),
+        None => (None, name.to_string()),
+    }
+}
 
-    Ok(())
+/// Keeps versions usable as folder names; the separator is reserved.
+fn sanitize_version(version: &str) -> String {
+    naming::sanitize(version.trim(), &['.', '-', '+'])
 }
@@ -1,4 +1,5 @@
 use crate::core::library::Library;
+use crate::core::repo_store;
 use crate::models::error::SError;
 
 /// Reads the documentation file for a mod.
@@ -18,7 +19,9 @@ pub fn read_documentation(library: &Library, mod_id: &str) -> Result<String, SEr
         .ok_or_else(|| SError::ParseError("Documentation not specified in manifest".to_string()))?;
 
     // Build path to documentation file
-    let doc_path = library.lib_paths.mods.join(mod_id).join(doc_filename);
+    let doc_path = repo_store::open(&library.lib_paths)
+        .payload_dir(mod_id)
+        .join(doc_filename);
 
     // Read and return documentation content
     std::fs::read_to_string(&doc_path)
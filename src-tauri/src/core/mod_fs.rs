@@ -2,9 +2,38 @@ use crate::models::error::SError;
 use crate::models::mod_dto::{ModManifest, ModType};
 use crate::models::paths::{ModPaths, SPTPathRules};
 use crate::utils::id::hash_id;
+use crate::utils::scan;
 use camino::{Utf8Path, Utf8PathBuf};
 use serde::{Deserialize, Serialize};
-use walkdir::WalkDir;
+use std::collections::BTreeMap;
+
+/// Version of the scheme turning the names a path id is derived from into its hashed input.
+/// 1: lowercase names concatenated without a divider.
+/// 2: lowercase names escaped and joined with `|`, prefixed with the version.
+/// Mods keep the id of the scheme they were installed with, see `canonical_path_ids`.
+pub const ID_SCHEME: u32 = 2;
+
+/// Hashed input of a path id, kept to tell how an id came about.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CanonicalId {
+    pub scheme: u32,
+    pub input: String,
+}
+
+impl CanonicalId {
+    pub fn hash(&self) -> String {
+        hash_id(&self.input)
+    }
+}
+
+/// A repo file as it was when its mod was cached, see `local_edits`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FileStamp {
+    pub len: u64,
+    /// Modification time in nanoseconds since the epoch.
+    pub modified: u64,
+    pub digest: String,
+}
 
 // Internal cache representation: includes files but NOT sent to frontend
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -13,6 +42,12 @@ pub struct ModFS {
     pub mod_type: ModType,
     pub files: Vec<Utf8PathBuf>,
     pub executables: Vec<Utf8PathBuf>,
+    /// Input of a path-based id; `None` for manifest ids and mods cached before it was kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_id: Option<CanonicalId>,
+    /// Stamp of each file in the repo, filled in the background by `idle_hasher`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stamps: BTreeMap<Utf8PathBuf, FileStamp>,
 }
 
 impl ModFS {
@@ -24,17 +59,43 @@ impl ModFS {
         Ok(serde_json::from_reader(std::fs::File::open(path)?)?)
     }
 
+    /// Returns the id along with its canonical input when it is derived from the paths.
     pub fn resolve_id(
         mod_root: &Utf8Path,
         spt_paths: &SPTPathRules,
         files: &[Utf8PathBuf],
-    ) -> Result<String, SError> {
+    ) -> Result<(String, Option<CanonicalId>), SError> {
         // 1. Priority 1: Manifest check
         let mod_paths = ModPaths::new(mod_root);
         if let Ok(guid) = Self::read_manifest_guid(&mod_paths.file) {
-            return Ok(guid);
+            return Ok((guid, None));
         }
 
+        let canonical = Self::canonical_path_id(spt_paths, files, ID_SCHEME)?;
+        Ok((canonical.hash(), Some(canonical)))
+    }
+
+    /// Derives an id from the mod folders and client DLLs, used when there is no manifest.
+    pub fn path_id(spt_paths: &SPTPathRules, files: &[Utf8PathBuf]) -> Result<String, SError> {
+        Ok(Self::canonical_path_id(spt_paths, files, ID_SCHEME)?.hash())
+    }
+
+    /// The canonical input of the path id under every scheme, the current one first.
+    pub fn canonical_path_ids(
+        spt_paths: &SPTPathRules,
+        files: &[Utf8PathBuf],
+    ) -> Result<Vec<CanonicalId>, SError> {
+        (1..=ID_SCHEME)
+            .rev()
+            .map(|scheme| Self::canonical_path_id(spt_paths, files, scheme))
+            .collect()
+    }
+
+    pub fn canonical_path_id(
+        spt_paths: &SPTPathRules,
+        files: &[Utf8PathBuf],
+        scheme: u32,
+    ) -> Result<CanonicalId, SError> {
         // 2. Single-pass collection using BTreeSet for automatic sorting
         let ids: std::collections::BTreeSet<String> = files
             .iter()
@@ -44,6 +105,15 @@ impl ModFS {
                     return rel.components().next().map(|c| c.as_str().to_string());
                 }
 
+                // Managed roots are laid out like server mods, one folder per mod
+                if let Some(rel) = spt_paths
+                    .managed_roots
+                    .iter()
+                    .find_map(|root| path.strip_prefix(root).ok())
+                {
+                    return rel.components().next().map(|c| c.as_str().to_string());
+                }
+
                 // Client check (DLLs only)
                 if path.extension() == Some("dll") {
                     if let Ok(rel) = path.strip_prefix(&spt_paths.client_plugins) {
@@ -60,9 +130,16 @@ impl ModFS {
             return Err(SError::UnableToDetermineModId);
         }
 
-        // 3. Concatenate sorted IDs and hash the result
-        let concatenated = ids.into_iter().collect::<Vec<_>>().join("").to_lowercase();
-        Ok(hash_id(&concatenated))
+        // 3. Join the sorted names; the result is hashed into the id
+        let names = ids.into_iter().map(|id| id.to_lowercase());
+        let input = match scheme {
+            1 => names.collect::<Vec<_>>().join(""),
+            _ => format!(
+                "v{scheme}:{}",
+                names.map(|n| escape(&n)).collect::<Vec<_>>().join("|")
+            ),
+        };
+        Ok(CanonicalId { scheme, input })
     }
 
     pub fn infer_mod_type(files: &[Utf8PathBuf], config: &SPTPathRules) -> ModType {
@@ -77,11 +154,10 @@ impl ModFS {
         }
     }
 
-    fn collect_files(base: &Utf8Path) -> (Vec<Utf8PathBuf>, Vec<Utf8PathBuf>) {
+    pub(crate) fn collect_files(base: &Utf8Path) -> (Vec<Utf8PathBuf>, Vec<Utf8PathBuf>) {
         let manifest_folder = ModPaths::default().folder;
 
-        WalkDir::new(base)
-            .into_iter()
+        scan::walk(base)
             // 1. Convert Result<DirEntry> to Option<DirEntry>
             .filter_map(Result::ok)
             // 2. Filter for files only
@@ -106,14 +182,43 @@ impl ModFS {
             })
     }
 
+    /// Hashes the manifest and every listed file (path and content) in a stable order,
+    /// so two copies of the same mod release produce the same digest.
+    pub fn content_hash(root: &Utf8Path, files: &[Utf8PathBuf]) -> Result<blake3::Hash, SError> {
+        let mut sorted: Vec<&Utf8PathBuf> = files.iter().collect();
+        sorted.sort();
+
+        let manifest = ModPaths::default().file;
+        let mut hasher = blake3::Hasher::new();
+        for rel in std::iter::once(&manifest).chain(sorted) {
+            let path = root.join(rel);
+            if !path.is_file() {
+                continue;
+            }
+            hasher.update(rel.as_str().replace('\\', "/").as_bytes());
+            hasher.update(&std::fs::metadata(&path)?.len().to_le_bytes());
+            std::io::copy(&mut std::fs::File::open(&path)?, &mut hasher)?;
+        }
+
+        Ok(hasher.finalize())
+    }
+
     pub fn new(root: &Utf8Path, spt_paths: &SPTPathRules) -> Result<Self, SError> {
         let (files, executables) = Self::collect_files(root); // Call once
+        let (id, canonical_id) = Self::resolve_id(root, &spt_paths, &files)?;
 
         Ok(ModFS {
-            id: Self::resolve_id(root, &spt_paths, &files)?,
+            id,
             mod_type: Self::infer_mod_type(&files, &spt_paths),
             files, // Use the same vector
             executables,
+            canonical_id,
+            stamps: BTreeMap::new(),
         })
     }
 }
+
+/// Escapes the divider so names containing it cannot run into each other.
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('|', "\\|")
+}
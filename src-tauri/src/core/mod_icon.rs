@@ -0,0 +1,94 @@
+use crate::core::library::Library;
+use crate::core::repo_store;
+use crate::models::error::SError;
+use crate::models::paths::ModPaths;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::fs;
+
+/// File name of a custom icon in the manifest folder, followed by the image extension.
+const ICON_STEM: &str = "custom-icon";
+
+/// Images larger than this would bloat every DTO the icon is embedded in.
+pub const MAX_ICON_SIZE: u64 = 2 * 1024 * 1024;
+
+const EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "svg", "webp", "gif"];
+
+/// Copies an image into the manifest folder of the mod and shows it instead of the manifest
+/// icon, in both appearances. `None` removes the custom icon.
+/// Library DTOs embed the new icon from the next build on.
+pub fn set_icon(
+    library: &mut Library,
+    mod_id: &str,
+    image: Option<&Utf8Path>,
+) -> Result<(), SError> {
+    if !library.mods.contains_key(mod_id) {
+        return Err(SError::ModNotFound(mod_id.to_string()));
+    }
+    let folder = ModPaths::new(&repo_store::open(&library.lib_paths).payload_dir(mod_id)).folder;
+
+    let icon = image.map(|image| copy_icon(image, &folder)).transpose()?;
+    remove_stale(&folder, icon.as_deref())?;
+
+    if let Some(m) = library.mods.get_mut(mod_id) {
+        m.metadata.icon = icon;
+    }
+    library.persist_manifest()
+}
+
+/// Path of the custom icon of a mod, if it has one.
+pub fn custom_icon_path(library: &Library, mod_id: &str) -> Option<Utf8PathBuf> {
+    let icon = library.mods.get(mod_id)?.metadata.icon.as_ref()?;
+    Some(
+        ModPaths::new(&repo_store::open(&library.lib_paths).payload_dir(mod_id))
+            .folder
+            .join(icon),
+    )
+}
+
+/// Reads the custom icon of an installed mod before an update replaces its folder.
+pub fn take(library: &Library, mod_id: &str) -> Option<(String, Vec<u8>)> {
+    let path = custom_icon_path(library, mod_id)?;
+    Some((path.file_name()?.to_string(), fs::read(&path).ok()?))
+}
+
+/// Writes an icon read by `take` into the updated mod folder.
+pub fn put_back(mod_root: &Utf8Path, icon: Option<(String, Vec<u8>)>) -> Result<(), SError> {
+    let Some((name, bytes)) = icon else {
+        return Ok(());
+    };
+    let folder = ModPaths::new(mod_root).folder;
+    fs::create_dir_all(&folder)?;
+    fs::write(folder.join(name), bytes)?;
+    Ok(())
+}
+
+fn copy_icon(image: &Utf8Path, folder: &Utf8Path) -> Result<String, SError> {
+    let extension = image
+        .extension()
+        .map(str::to_ascii_lowercase)
+        .filter(|extension| EXTENSIONS.contains(&extension.as_str()))
+        .ok_or_else(|| SError::ParseError(format!("Unsupported image format: {image}")))?;
+    if fs::metadata(image)?.len() > MAX_ICON_SIZE {
+        return Err(SError::ParseError(format!(
+            "{image} is larger than {} MiB",
+            MAX_ICON_SIZE / 1024 / 1024
+        )));
+    }
+
+    let name = format!("{ICON_STEM}.{extension}");
+    fs::create_dir_all(folder)?;
+    fs::copy(image, folder.join(&name))?;
+    Ok(name)
+}
+
+/// Removes earlier custom icons, e.g. a png replaced by a webp.
+fn remove_stale(folder: &Utf8Path, keep: Option<&str>) -> Result<(), SError> {
+    EXTENSIONS
+        .iter()
+        .map(|extension| format!("{ICON_STEM}.{extension}"))
+        .filter(|name| Some(name.as_str()) != keep)
+        .map(|name| folder.join(name))
+        .filter(|path| path.is_file())
+        .try_for_each(fs::remove_file)?;
+    Ok(())
+}
@@ -1,47 +1,258 @@
 use crate::core::cleanup;
+use crate::core::compat_notes;
+use crate::core::config_adoption;
+use crate::core::dependency;
 use crate::core::deployment;
+use crate::core::file_overrides;
 use crate::core::library::Library;
+use crate::core::logging;
+use crate::core::metrics;
 use crate::core::mod_backup;
-use crate::core::mod_stager::StagedMod;
+use crate::core::mod_fs::{CanonicalId, ModFS};
+use crate::core::mod_icon;
+use crate::core::mod_stager::{self, StagedMod};
+use crate::core::progress::Progress;
+use crate::core::recommendations;
+use crate::core::repo_history;
+use crate::core::repo_store;
+use crate::core::search_index;
 use crate::models::error::SError;
-use crate::models::mod_dto::Mod;
-use crate::utils::file::FileUtils;
+use crate::models::logging::LoggedOperation;
+use crate::models::metrics::Operation;
+use crate::models::mod_dto::{Mod, ModMetadata, ModMetadataUpdate};
+use crate::models::paths::ModPaths;
+use crate::models::warning::{OperationWarning, WarningKind};
+use crate::utils::naming;
+use camino::Utf8PathBuf;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use tracing::{debug, info};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddOutcome {
+    Installed,
+    Updated,
+    /// The exact same content is already in the repo; nothing was backed up or copied.
+    Unchanged,
+    /// The mod now ships a manifest and replaced the entry installed under its path-based id.
+    Migrated,
+}
+
+/// Adds every staged mod and removes its staging copy, exiting on the first error.
+/// Returns the warnings collected while staging and installing.
+pub fn add_staged(
+    library: &mut Library,
+    staged_mods: Vec<StagedMod>,
+    progress: Progress,
+) -> Result<Vec<OperationWarning>, SError> {
+    logging::operation(logging::dir(library), LoggedOperation::AddMods, || {
+        let total = staged_mods.len();
+        let names = staged_mods
+            .iter()
+            .map(|staged| staged.name.clone())
+            .collect::<Vec<String>>()
+            .join(", ");
+        staged_mods
+            .into_iter()
+            .enumerate()
+            .try_fold(Vec::new(), |mut warnings, (index, mut staged)| {
+                progress.copying(&staged.name, index, total);
+                debug!("current: {:?}", staged);
+                // Extract cleanup data before moving staged into add_mod
+                let is_staging = staged.is_staging;
+                let source_path = staged.source_path.clone();
+                let name = staged.name.clone();
+                let mod_id = staged.fs.id.clone();
+                warnings.append(&mut staged.warnings);
+
+                let outcome = add_mod_reported(library, staged, progress)?;
+                info!("{name}: {outcome:?}");
+                mod_stager::clean_up(is_staging, &source_path)?;
+
+                if outcome == AddOutcome::Unchanged {
+                    warnings.push(OperationWarning::new(WarningKind::UnchangedReinstall, name));
+                    return Ok(warnings);
+                }
+                let suggested = recommendations::for_mod(library, &mod_id)?
+                    .into_iter()
+                    .map(|r| r.name.unwrap_or(r.mod_id))
+                    .collect::<Vec<String>>();
+                if !suggested.is_empty() {
+                    warnings.push(
+                        OperationWarning::new(WarningKind::RecommendedMods, name)
+                            .with_details(&suggested),
+                    );
+                }
+                Ok(warnings)
+            })
+            .inspect(|_| repo_history::record(&library.repo_root, &format!("Add {names}")))
+    })
+}
 
 /// Adds or updates a mod in the library.
-/// Creates a backup if the mod already exists.
-pub fn add_mod(library: &mut Library, staged: StagedMod) -> Result<(), SError> {
-    let mod_id = staged.fs.id.clone();
-    let dst = library.lib_paths.mods.join(&mod_id);
-
-    // Create backup if mod already exists
-    if dst.exists() {
-        mod_backup::create_backup(&library.lib_paths, &mod_id)?;
-    }
+/// Creates a backup if the mod already exists, unless the content is identical.
+/// The id, name and resulting paths are validated before anything is copied.
+pub fn add_mod(library: &mut Library, staged: StagedMod) -> Result<AddOutcome, SError> {
+    add_mod_reported(library, staged, Progress::silent())
+}
+
+/// Like `add_mod`, reporting the copy into the repo with its rate and ETA.
+pub fn add_mod_reported(
+    library: &mut Library,
+    staged: StagedMod,
+    progress: Progress,
+) -> Result<AddOutcome, SError> {
+    logging::operation(logging::dir(library), LoggedOperation::AddMods, || {
+        let _timer = metrics::Timer::start(Operation::AddMod);
+        let staged = keep_installed_id(library, staged);
+        let mod_id = staged.fs.id.clone();
+        naming::validate_id(&mod_id)?;
+        let name = naming::sanitize_name(&staged.name)?;
+        let store = repo_store::open(&library.lib_paths);
+        let dst = store.payload_dir(&mod_id);
+        naming::ensure_fits(&dst, &staged.fs.files)?;
+        let exists = store.contains(&mod_id);
+
+        if exists && is_unchanged(library, &staged)? {
+            return Ok(AddOutcome::Unchanged);
+        }
+
+        let migrated_from = (!exists)
+            .then(|| find_previous_id(library, &staged))
+            .flatten();
+
+        // Create backup if mod already exists
+        if exists {
+            mod_backup::create_backup(library, &mod_id)?;
+        }
+
+        // The custom icon is not part of the mod payload, keep it across the update
+        let icon = mod_icon::take(library, &mod_id);
+        store.put(&mod_id, &staged.source_path, progress, &name)?;
+        mod_icon::put_back(&dst, icon)?;
+
+        // Only retire the previous entry once the new payload is in place
+        let was_active = match &migrated_from {
+            Some(previous) => migrate_previous(library, previous, &mod_id)?,
+            None => false,
+        };
 
-    std::fs::create_dir_all(&dst)?;
-    FileUtils::copy_recursive(&staged.source_path, &dst)?;
+        library
+            .mods
+            .entry(mod_id.clone())
+            .and_modify(|m| {
+                // Preserve existing name when updating - only update mod_type and icon_data
+                m.mod_type = staged.fs.mod_type.clone();
+                m.icon_data = None; // Reset icon_data when updating
+            })
+            .or_insert_with(|| Mod {
+                id: mod_id.clone(),
+                is_active: was_active,
+                mod_type: staged.fs.mod_type.clone(),
+                name,
+                manifest: None,
+                icon_data: None,
+                metadata: ModMetadata::default(),
+                modified_files: Vec::new(),
+                patch_of: None,
+            });
 
-    library
+        library.cache.add(&dst, staged.fs);
+        search_index::refresh(library, &mod_id);
+        library.mark_dirty();
+        library.persist()?;
+
+        Ok(match (exists, migrated_from) {
+            (true, _) => AddOutcome::Updated,
+            (false, Some(_)) => AddOutcome::Migrated,
+            (false, None) => AddOutcome::Installed,
+        })
+    })
+}
+
+/// Keeps the id a manifest-less mod was installed under with an earlier id scheme, so
+/// reinstalling it updates that entry instead of adding a second one, see `ID_SCHEME`.
+fn keep_installed_id(library: &Library, mut staged: StagedMod) -> StagedMod {
+    if staged.fs.canonical_id.is_none() || library.mods.contains_key(&staged.fs.id) {
+        return staged;
+    }
+    let installed = ModFS::canonical_path_ids(&library.spt_rules, &staged.fs.files)
+        .unwrap_or_default()
+        .into_iter()
+        .find(|canonical| library.mods.contains_key(&canonical.hash()));
+    if let Some(canonical) = installed {
+        staged.fs.id = canonical.hash();
+        staged.fs.canonical_id = Some(canonical);
+    }
+    staged
+}
+
+/// Finds the entry a mod was installed as before it shipped a manifest.
+/// That is the manifest-less mod with the path-based id of the same files, or failing that
+/// the one sharing the most files, as long as more than half of them overlap.
+fn find_previous_id(library: &Library, staged: &StagedMod) -> Option<String> {
+    ModFS::read_manifest(&ModPaths::new(&staged.source_path).file).ok()?;
+    let candidates: Vec<(&String, &ModFS)> = library
+        .cache
         .mods
-        .entry(mod_id.clone())
-        .and_modify(|m| {
-            // Preserve existing name when updating - only update mod_type and icon_data
-            m.mod_type = staged.fs.mod_type.clone();
-            m.icon_data = None; // Reset icon_data when updating
+        .iter()
+        .filter(|(id, _)| !library.cache.manifests.contains_key(*id))
+        .filter(|(id, _)| library.mods.contains_key(*id))
+        .collect();
+
+    let path_ids: Vec<String> = ModFS::canonical_path_ids(&library.spt_rules, &staged.fs.files)
+        .unwrap_or_default()
+        .iter()
+        .map(CanonicalId::hash)
+        .collect();
+    if let Some((id, _)) = candidates.iter().find(|(id, _)| path_ids.contains(id)) {
+        return Some(id.to_string());
+    }
+
+    let files: HashSet<&Utf8PathBuf> = staged.fs.files.iter().collect();
+    candidates
+        .into_iter()
+        .map(|(id, fs)| {
+            let shared = fs.files.iter().filter(|f| files.contains(f)).count();
+            (id, shared, files.len().max(fs.files.len()))
         })
-        .or_insert_with(|| Mod {
-            id: mod_id.clone(),
-            is_active: false,
-            mod_type: staged.fs.mod_type.clone(),
-            name: staged.name.clone(),
-            manifest: None,
-            icon_data: None,
-        });
-
-    library.cache.add(&dst, staged.fs);
-    library.mark_dirty();
-    library.persist()?;
-    Ok(())
+        .filter(|(_, shared, total)| shared * 2 > *total)
+        .max_by_key(|(_, shared, _)| *shared)
+        .map(|(id, _, _)| id.clone())
+}
+
+/// Retires the previous entry of a migrated mod: its payload is backed up, its backups move
+/// to the new id, its links are removed and its notes and configs follow the new id.
+/// Returns whether it was active, so the new entry keeps the activation state.
+fn migrate_previous(library: &mut Library, previous: &str, mod_id: &str) -> Result<bool, SError> {
+    info!("Migrating {previous} to {mod_id}");
+    let was_active = library.mods.get(previous).is_some_and(|m| m.is_active);
+
+    mod_backup::create_backup(library, previous)?;
+    let backups = library.lib_paths.backups.join(previous);
+    if backups.exists() {
+        fs::rename(&backups, library.lib_paths.backups.join(mod_id))?;
+    }
+
+    config_adoption::rename_mod(library, previous, mod_id);
+    remove_mod(library, previous)?;
+    compat_notes::rename_mod(library, previous, mod_id);
+    file_overrides::rename_mod(library, previous, mod_id);
+    Ok(was_active)
+}
+
+/// Compares the staged content with the repo copy of an already tracked mod.
+fn is_unchanged(library: &Library, staged: &StagedMod) -> Result<bool, SError> {
+    let Some(cached) = library.cache.mods.get(&staged.fs.id) else {
+        return Ok(false);
+    };
+    if !library.mods.contains_key(&staged.fs.id) {
+        return Ok(false);
+    }
+
+    let repo_root = repo_store::open(&library.lib_paths).payload_dir(&staged.fs.id);
+    Ok(ModFS::content_hash(&staged.source_path, &staged.fs.files)?
+        == ModFS::content_hash(&repo_root, &cached.files)?)
 }
 
 /// Removes a mod from the library.
@@ -49,60 +260,141 @@ pub fn add_mod(library: &mut Library, staged: StagedMod) -> Result<(), SError> {
 /// Always attempts to unlink regardless of active status, as library state may not be synced.
 /// Does not mark library dirty as sync status already reflects unlinked state.
 pub fn remove_mod(library: &mut Library, id: &str) -> Result<(), SError> {
-    // Get mod's ModFS from cache before removing
-    let mod_fs_exists = library.cache.mods.contains_key(id);
-
-    // Always attempt to unlink - active status may not match filesystem state
-    if mod_fs_exists {
-        // Find what paths need to be unlinked (treats mod as active for ownership calculation)
-        let (unlink_paths, shared_dirs) = deployment::find_mod_links(
-            &library.game_root,
-            &library.lib_paths,
-            &library.spt_rules,
-            &library.mods,
-            &library.cache,
-            id,
-        )?;
-
-        // Unlink all paths and shared directories
-        cleanup::unlink_mod(
-            &library.game_root,
-            &library.repo_root,
-            &library.lib_paths,
-            &library.cache,
-            id,
-            &unlink_paths,
-            &shared_dirs,
-            &library.spt_rules,
-        )?;
-    }
+    logging::operation(logging::dir(library), LoggedOperation::RemoveMod, || {
+        info!("Removing {id}");
+        // Get mod's ModFS from cache before removing
+        let mod_fs_exists = library.cache.mods.contains_key(id);
 
-    // Remove all backups for this mod
-    mod_backup::remove_all_backups(&library.lib_paths, id)?;
+        // Always attempt to unlink - active status may not match filesystem state
+        if mod_fs_exists {
+            // Find what paths need to be unlinked (treats mod as active for ownership calculation)
+            let (unlink_paths, shared_dirs) = deployment::find_mod_links(
+                &library.game_root,
+                &library.lib_paths,
+                &library.spt_rules,
+                &library.mods,
+                &library.cache,
+                id,
+            )?;
 
-    // Remove mod directory from filesystem
-    let mod_dir = library.lib_paths.mods.join(id);
-    if mod_dir.exists() {
-        std::fs::remove_dir_all(&mod_dir)?;
-    }
+            // Unlink all paths and shared directories
+            cleanup::unlink_mod(
+                &library.game_root,
+                &library.repo_root,
+                &library.lib_paths,
+                &library.cache,
+                id,
+                &unlink_paths,
+                &shared_dirs,
+                &library.spt_rules,
+            )?;
+        }
+
+        // Remove all backups for this mod
+        mod_backup::delete_all_backups(&library.lib_paths, id)?;
+
+        // Remove mod payload from the repo
+        repo_store::open(&library.lib_paths).remove(id)?;
 
-    // Remove from cache and mods map
-    library.cache.mods.remove(id);
-    library.mods.remove(id);
+        // Remove from cache and mods map
+        library.cache.mods.remove(id);
+        library.mods.remove(id);
+        search_index::refresh(library, id);
+        config_adoption::release(library, id);
+        file_overrides::release(library, id);
 
-    // Do NOT mark dirty - sync status already reflects the unlinked state
-    library.persist()?;
-    Ok(())
+        // Do NOT mark dirty - sync status already reflects the unlinked state
+        library.persist()?;
+        repo_history::record(&library.repo_root, &format!("Remove {id}"));
+        Ok(())
+    })
 }
 
 /// Toggles the active state of a mod.
 pub fn toggle_mod(library: &mut Library, id: &str, is_active: bool) -> Result<(), SError> {
-    let mod_entry = library
+    toggle_mods(library, &[id.to_string()], is_active)
+}
+
+/// Sets the active state of several mods with a single manifest write.
+/// Fails without changing anything if one of the mods is unknown.
+pub fn toggle_mods(library: &mut Library, ids: &[String], is_active: bool) -> Result<(), SError> {
+    if let Some(id) = ids.iter().find(|id| !library.mods.contains_key(*id)) {
+        return Err(SError::ModNotFound(id.to_string()));
+    }
+    if is_active {
+        dependency::check_activation(library, ids)?;
+    }
+
+    let mut targets: Vec<&mut Mod> = library
         .mods
-        .get_mut(id)
-        .ok_or_else(|| SError::ModNotFound(id.to_string()))?;
-    mod_entry.is_active = is_active;
+        .iter_mut()
+        .filter(|(id, m)| ids.contains(id) && m.is_active != is_active)
+        .map(|(_, m)| m)
+        .collect();
+
+    // Nothing to persist when every mod is already in the requested state
+    if targets.is_empty() {
+        return Ok(());
+    }
+    targets.iter_mut().for_each(|m| m.is_active = is_active);
+
     library.mark_dirty();
-    library.persist()?;
-    Ok(())
+    library.persist_manifest()
+}
+
+/// Applies metadata changes to several mods with a single manifest write.
+/// Fails without changing anything if one of the mods is unknown.
+/// The library stays clean since nothing deployed depends on metadata.
+pub fn update_metadata(
+    library: &mut Library,
+    updates: Vec<ModMetadataUpdate>,
+) -> Result<(), SError> {
+    if let Some(update) = updates
+        .iter()
+        .find(|u| !library.mods.contains_key(&u.mod_id))
+    {
+        return Err(SError::ModNotFound(update.mod_id.clone()));
+    }
+
+    updates.into_iter().for_each(|update| {
+        let Some(m) = library.mods.get_mut(&update.mod_id) else {
+            return;
+        };
+        let metadata = &mut m.metadata;
+        if let Some(tags) = update.tags {
+            metadata.tags = normalize_tags(tags);
+        }
+        if let Some(priority) = update.priority {
+            metadata.priority = priority;
+        }
+        if let Some(note) = update.note {
+            metadata.note = non_empty(note);
+        }
+        if let Some(group) = update.group {
+            metadata.group = non_empty(group);
+        }
+        if let Some(categories) = update.categories {
+            metadata.categories = normalize_tags(categories);
+        }
+        if let Some(color) = update.color {
+            metadata.color = non_empty(color.to_lowercase());
+        }
+    });
+
+    library.persist_manifest()
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    tags.iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .collect()
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
 }
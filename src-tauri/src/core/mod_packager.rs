@@ -0,0 +1,198 @@
+use crate::core::library::Library;
+use crate::core::repo_store;
+use crate::models::error::SError;
+use crate::models::mod_dto::{Author, ModManifest, VersionBump};
+use crate::models::paths::{ModPaths, SPTPathRules};
+use camino::{Utf8Path, Utf8PathBuf};
+use semver::Version;
+use std::fs::File;
+use std::io::Write;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+const README_NAME: &str = "README.md";
+
+/// Packages a library mod into a distribution-ready zip.
+/// The archive mirrors the game root layout (`BepInEx/plugins`, `SPT/user/mods`) as stored in the
+/// repo, and always contains a manifest and a README in the mod's own folder (generated when the
+/// mod ships none).
+/// When `bump` is set, the new version is written back to the repo manifest once the archive is
+/// complete.
+/// `output` may be a directory, in which case the file is named `{name}-{version}.zip`.
+pub fn package_mod(
+    library: &mut Library,
+    mod_id: &str,
+    output: &Utf8Path,
+    bump: Option<VersionBump>,
+) -> Result<Utf8PathBuf, SError> {
+    let mod_entry = library
+        .mods
+        .get(mod_id)
+        .ok_or_else(|| SError::ModNotFound(mod_id.to_string()))?;
+    let files = library
+        .cache
+        .mods
+        .get(mod_id)
+        .map(|fs| fs.files.clone())
+        .ok_or_else(|| SError::ModNotFound(mod_id.to_string()))?;
+
+    let mut manifest = library
+        .cache
+        .manifests
+        .get(mod_id)
+        .cloned()
+        .unwrap_or_else(|| ModManifest {
+            id: mod_id.to_string(),
+            name: mod_entry.name.clone(),
+            author: Author::Single("Unknown".to_string()),
+            version: "1.0.0".to_string(),
+            spt_version: format!("~{}", library.spt_version),
+            description: None,
+            icon: None,
+            icon_dark: None,
+            documentation: None,
+            compatibility: None,
+            dependencies: None,
+            conflicts_with: None,
+            effects: None,
+            links: None,
+            prerequisites: None,
+            update_url: None,
+            hub_id: None,
+        });
+
+    let mod_root = repo_store::open(&library.lib_paths).payload_dir(mod_id);
+
+    if let Some(bump) = &bump {
+        manifest.version = bump_version(&manifest.version, bump)?;
+    }
+
+    let archive_path = resolve_output_path(output, &manifest);
+    let readme = readme_path(&files, &library.spt_rules);
+    write_archive(&archive_path, &mod_root, &files, &manifest, &readme).inspect_err(|_| {
+        // A partial archive must not pass for a release
+        let _ = std::fs::remove_file(&archive_path);
+    })?;
+
+    // Kept only once the archive is complete, a failed packaging leaves the version as it was
+    if bump.is_some() {
+        write_manifest(&mod_root, &manifest)?;
+        library.cache.manifests.insert(mod_id.to_string(), manifest);
+        library.persist()?;
+    }
+    Ok(archive_path)
+}
+
+fn write_archive(
+    archive_path: &Utf8Path,
+    mod_root: &Utf8Path,
+    files: &[Utf8PathBuf],
+    manifest: &ModManifest,
+    readme: &Utf8Path,
+) -> Result<(), SError> {
+    let mut zip = ZipWriter::new(File::create(archive_path)?);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    for file in files {
+        zip.start_file(to_entry_name(file), options)?;
+        std::io::copy(&mut File::open(mod_root.join(file))?, &mut zip)?;
+    }
+
+    zip.start_file(to_entry_name(&ModPaths::default().file), options)?;
+    zip.write_all(serde_json::to_string_pretty(manifest)?.as_bytes())?;
+
+    if !files
+        .iter()
+        .any(|f| f.as_str().eq_ignore_ascii_case(readme.as_str()))
+    {
+        zip.start_file(to_entry_name(readme), options)?;
+        zip.write_all(generate_readme(manifest).as_bytes())?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Applies a semver bump, resetting the lower components.
+pub fn bump_version(version: &str, bump: &VersionBump) -> Result<String, SError> {
+    let mut v = Version::parse(version)?;
+    match bump {
+        VersionBump::Major => {
+            v.major += 1;
+            v.minor = 0;
+            v.patch = 0;
+        }
+        VersionBump::Minor => {
+            v.minor += 1;
+            v.patch = 0;
+        }
+        VersionBump::Patch => v.patch += 1,
+    }
+    v.pre = semver::Prerelease::EMPTY;
+    Ok(v.to_string())
+}
+
+fn write_manifest(mod_root: &Utf8Path, manifest: &ModManifest) -> Result<(), SError> {
+    let mod_paths = ModPaths::new(mod_root);
+    std::fs::create_dir_all(&mod_paths.folder)?;
+    std::fs::write(&mod_paths.file, serde_json::to_string_pretty(manifest)?)?;
+    Ok(())
+}
+
+fn resolve_output_path(output: &Utf8Path, manifest: &ModManifest) -> Utf8PathBuf {
+    if !output.is_dir() {
+        return output.to_path_buf();
+    }
+
+    let name: String = manifest
+        .name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    output.join(format!("{}-{}.zip", name, manifest.version))
+}
+
+/// The README goes in the folder the mod owns under a mod root, e.g. `SPT/user/mods/{name}`.
+/// A mod made of loose files only owns no folder, so it goes in the manifest folder instead.
+fn readme_path(files: &[Utf8PathBuf], spt_rules: &SPTPathRules) -> Utf8PathBuf {
+    spt_rules
+        .mod_roots()
+        .find_map(|root| {
+            files.iter().find_map(|file| {
+                let mut components = file.strip_prefix(root).ok()?.components();
+                let folder = components.next()?;
+                // Anything below it makes it a folder rather than a loose file
+                components.next()?;
+                Some(root.join(folder))
+            })
+        })
+        .unwrap_or_else(|| ModPaths::default().folder)
+        .join(README_NAME)
+}
+
+/// Zip entries always use forward slashes regardless of the platform.
+fn to_entry_name(path: &Utf8Path) -> String {
+    path.as_str().replace('\\', "/")
+}
+
+fn generate_readme(manifest: &ModManifest) -> String {
+    let author = match &manifest.author {
+        Author::Single(name) => name.clone(),
+        Author::Multiple(names) => names.join(", "),
+    };
+
+    format!(
+        "# {}\n\n{}\n\n- Version: {}\n- Author: {}\n- SPT version: {}\n\n## Installation\n\nExtract this archive into your SPT game folder.\n",
+        manifest.name,
+        manifest.description.as_deref().unwrap_or_default(),
+        manifest.version,
+        author,
+        manifest.spt_version
+    )
+}
@@ -0,0 +1,89 @@
+use crate::core::dependency;
+use crate::core::library::Library;
+use crate::models::mod_patch::PatchGroup;
+use crate::models::warning::{OperationWarning, WarningKind};
+use std::collections::BTreeMap;
+
+/// Words in a mod name marking it as a patch of another mod, e.g. "X - 4.0 compatibility patch".
+const PATCH_MARKERS: [&str; 2] = ["patch", "compat"];
+
+/// The mod a patch applies to: the required dependency its name mentions, else its first one,
+/// else the installed mod whose name starts its own, the longest one winning.
+/// `None` when the name of the mod does not mark it as a patch.
+pub fn base_of(library: &Library, mod_id: &str) -> Option<String> {
+    let name = library.mods.get(mod_id)?.name.to_lowercase();
+    if !PATCH_MARKERS.iter().any(|marker| name.contains(marker)) {
+        return None;
+    }
+
+    let required: Vec<&str> = library
+        .cache
+        .manifests
+        .get(mod_id)
+        .map(|manifest| dependency::installed_requirements(library, manifest))
+        .unwrap_or_default();
+    let mentioned = required
+        .iter()
+        .find(|id| name_of(library, id).is_some_and(|base| name.contains(&base)));
+    if let Some(id) = mentioned.or(required.first()) {
+        return Some(id.to_string());
+    }
+
+    library
+        .mods
+        .iter()
+        .filter(|(id, _)| id.as_str() != mod_id)
+        .map(|(id, m)| (id, m.name.to_lowercase()))
+        .filter(|(_, base)| !base.is_empty() && starts_word(&name, base))
+        .max_by_key(|(_, base)| base.len())
+        .map(|(id, _)| id.clone())
+}
+
+/// Patches grouped under the mod they apply to, ordered by the id of that mod.
+pub fn groups(library: &Library) -> Vec<PatchGroup> {
+    library
+        .mods
+        .keys()
+        .filter_map(|id| Some((base_of(library, id)?, id.clone())))
+        .fold(
+            BTreeMap::<String, Vec<String>>::new(),
+            |mut acc, (base, patch)| {
+                acc.entry(base).or_default().push(patch);
+                acc
+            },
+        )
+        .into_iter()
+        .map(|(base, patches)| PatchGroup { base, patches })
+        .collect()
+}
+
+/// One `InactivePatches` warning per active mod of `ids` whose patches are not all active,
+/// so enabling a mod offers to enable its patches too.
+pub fn activation_offers(library: &Library, ids: &[String]) -> Vec<OperationWarning> {
+    groups(library)
+        .into_iter()
+        .filter(|group| ids.contains(&group.base))
+        .filter(|group| library.mods.get(&group.base).is_some_and(|m| m.is_active))
+        .filter_map(|group| {
+            let inactive: Vec<&String> = group
+                .patches
+                .iter()
+                .filter(|id| library.mods.get(*id).is_some_and(|m| !m.is_active))
+                .collect();
+            (!inactive.is_empty()).then(|| {
+                let name = name_of(library, &group.base).unwrap_or(group.base.clone());
+                OperationWarning::new(WarningKind::InactivePatches, name).with_details(&inactive)
+            })
+        })
+        .collect()
+}
+
+fn name_of(library: &Library, mod_id: &str) -> Option<String> {
+    library.mods.get(mod_id).map(|m| m.name.to_lowercase())
+}
+
+/// Whether `name` starts with `prefix` followed by a word boundary.
+fn starts_word(name: &str, prefix: &str) -> bool {
+    name.strip_prefix(prefix)
+        .is_some_and(|rest| rest.chars().next().is_some_and(|c| !c.is_alphanumeric()))
+}
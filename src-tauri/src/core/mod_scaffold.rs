@@ -0,0 +1,166 @@
+use crate::core::library::Library;
+use crate::core::mod_fs::ModFS;
+use crate::core::mod_manager;
+use crate::core::mod_stager::StagedMod;
+use crate::models::error::SError;
+use crate::models::mod_dto::{Author, ModManifest};
+use crate::models::paths::{ModPaths, SPTPathRules, SptGeneration};
+use crate::models::scaffold::{ScaffoldKind, ScaffoldOptions};
+use crate::utils::naming;
+use camino::Utf8PathBuf;
+use serde_json::json;
+
+const INITIAL_VERSION: &str = "0.1.0";
+
+/// Entry point of an SPT 3 server mod, loaded as the `main` of its `package.json`.
+const SERVER_ENTRY_STUB: &str = r#""use strict";
+
+class Mod {
+    postDBLoad(container) {
+        // Your code here
+    }
+}
+
+module.exports = { mod: new Mod() };
+"#;
+
+/// SPT 4 server mods are DLLs built by a C# project of their own.
+const SERVER_DLL_README_STUB: &str = r#"Point the build output of your server mod project to {output}.
+
+A minimal entry point:
+
+```csharp
+using SPTarkov.DI.Annotations;
+using SPTarkov.Server.Core.DI;
+
+[Injectable(TypePriority = OnLoadOrder.PostDBModLoader + 1)]
+public class Mod : IOnLoad
+{
+    public Task OnLoad()
+    {
+        // Your code here
+        return Task.CompletedTask;
+    }
+}
+```
+
+SPT also expects a record deriving from `AbstractModMetadata` describing the mod.
+"#;
+
+const CLIENT_README_STUB: &str = "Point the build output of your plugin project to {output}.\n";
+
+/// Kept with the manifest, which is never deployed, and shown as the mod documentation.
+const README_NAME: &str = "README.md";
+
+/// Creates the skeleton of a new mod and registers it in the library.
+/// Returns the mod id and the source folder, which is meant to be dev-linked.
+pub fn create(
+    library: &mut Library,
+    options: &ScaffoldOptions,
+) -> Result<(String, Utf8PathBuf), SError> {
+    let root = write_skeleton(
+        &library.spt_rules,
+        library.spt_generation,
+        &library.spt_version,
+        options,
+    )?;
+    let fs = ModFS::new(&root, &library.spt_rules)?;
+    let mod_id = fs.id.clone();
+
+    mod_manager::add_mod(
+        library,
+        StagedMod {
+            fs,
+            source_path: root.clone(),
+            is_staging: false,
+            name: options.name.clone(),
+            framework_files: Vec::new(),
+            warnings: Vec::new(),
+        },
+    )?;
+
+    Ok((mod_id, root))
+}
+
+/// Writes the manifest and the server/client folder structure into `{directory}/{name}`.
+/// Server mods get a script entry point before SPT 4 and an empty output folder from SPT 4 on.
+pub fn write_skeleton(
+    rules: &SPTPathRules,
+    generation: SptGeneration,
+    spt_version: &str,
+    options: &ScaffoldOptions,
+) -> Result<Utf8PathBuf, SError> {
+    let name = options.name.trim();
+    naming::validate_folder_name(name)?;
+
+    let root = options.directory.join(name);
+    if root.exists() {
+        return Err(SError::AlreadyExists(root.to_string()));
+    }
+
+    let mod_paths = ModPaths::new(&root);
+    let manifest = ModManifest {
+        id: to_mod_id(&options.author, name),
+        name: name.to_string(),
+        author: Author::Single(options.author.clone()),
+        version: INITIAL_VERSION.to_string(),
+        spt_version: format!("~{}", spt_version),
+        description: None,
+        icon: None,
+        icon_dark: None,
+        documentation: Some(ModPaths::default().folder.join(README_NAME).to_string()),
+        compatibility: None,
+        dependencies: None,
+        conflicts_with: None,
+        effects: None,
+        links: None,
+        prerequisites: None,
+        update_url: None,
+        hub_id: None,
+    };
+
+    std::fs::create_dir_all(&mod_paths.folder)?;
+    std::fs::write(&mod_paths.file, serde_json::to_string_pretty(&manifest)?)?;
+
+    let readme = match (&options.kind, generation) {
+        (ScaffoldKind::Server, SptGeneration::Spt4) => {
+            let output = rules.server_mods.join(name);
+            std::fs::create_dir_all(root.join(&output))?;
+            SERVER_DLL_README_STUB.replace("{output}", output.as_str())
+        }
+        (ScaffoldKind::Server, SptGeneration::Spt3 | SptGeneration::Aki) => {
+            let mod_dir = root.join(&rules.server_mods).join(name);
+            let package = json!({
+                "name": name,
+                "version": INITIAL_VERSION,
+                "main": "src/mod.js",
+                "license": "MIT",
+                "author": options.author,
+                "sptVersion": manifest.spt_version,
+            });
+            std::fs::create_dir_all(mod_dir.join("src"))?;
+            std::fs::write(
+                mod_dir.join("package.json"),
+                serde_json::to_string_pretty(&package)?,
+            )?;
+            std::fs::write(mod_dir.join("src/mod.js"), SERVER_ENTRY_STUB)?;
+            format!("Edit {}/src/mod.js.\n", rules.server_mods.join(name))
+        }
+        (ScaffoldKind::Client, _) => {
+            let output = rules.client_plugins.join(name);
+            std::fs::create_dir_all(root.join(&output))?;
+            CLIENT_README_STUB.replace("{output}", output.as_str())
+        }
+    };
+    std::fs::write(mod_paths.folder.join(README_NAME), readme)?;
+
+    Ok(root)
+}
+
+/// Builds a reverse-domain style id (`author.name`) from safe lowercase characters.
+fn to_mod_id(author: &str, name: &str) -> String {
+    naming::sanitize(
+        &format!("{}.{}", author.trim(), name).to_lowercase(),
+        &['.'],
+    )
+}
@@ -1,7 +1,12 @@
-use crate::core::decompression;
+use crate::core::bundled_framework;
+use crate::core::decompression::{self, ArchiveFormat};
 use crate::core::mod_fs::ModFS;
+use crate::core::progress::Progress;
+use crate::core::staging_handler::{self, StagingHandler};
 use crate::models::error::SError;
+use crate::models::global::FrameworkPolicy;
 use crate::models::paths::{ModPaths, SPTPathRules};
+use crate::models::warning::{OperationWarning, WarningKind};
 use crate::utils::file::FileUtils;
 use crate::utils::process::ProcessChecker;
 use camino::{Utf8Path, Utf8PathBuf};
@@ -17,6 +22,8 @@ pub struct StagedMod {
     pub source_path: Utf8PathBuf, // The location in staging (or original folder)
     pub is_staging: bool,         // True if this is a temp folder we need to delete later
     pub name: String,             // The resolved name for the mod
+    pub framework_files: Vec<Utf8PathBuf>, // Bundled BepInEx files removed during staging
+    pub warnings: Vec<OperationWarning>, // Non-fatal conditions met while staging
 }
 
 #[derive(Debug)]
@@ -24,27 +31,55 @@ pub struct StageMaterial {
     pub rules: SPTPathRules,
     pub root: Utf8PathBuf,
     pub name: String, // Translated "Unknown mod" string from frontend for loose files
+    pub quarantine: Utf8PathBuf,
+    pub framework_policy: FrameworkPolicy,
 }
 
 /// Takes raw user inputs and converts them into validated ModFS objects ready for installation.
-/// Uses a functional pipeline to resolve inputs.
+/// Uses a functional pipeline to resolve inputs, reporting each one as it is staged.
 pub fn resolve(
     inputs: &[Utf8PathBuf],
-    StageMaterial { root, rules, name }: &StageMaterial,
+    material: &StageMaterial,
+    progress: Progress,
 ) -> Result<Vec<StagedMod>, SError> {
+    resolve_payloads(inputs, material, progress)?
+        .into_iter()
+        .map(|staged| bundled_framework::strip(staged, material))
+        .collect()
+}
+
+fn resolve_payloads(
+    inputs: &[Utf8PathBuf],
+    material: &StageMaterial,
+    progress: Progress,
+) -> Result<Vec<StagedMod>, SError> {
+    let StageMaterial {
+        root, rules, name, ..
+    } = material;
+
     // 1. Guard Clause: Collective "Loose File" Check
     // If the inputs collectively form a mod root, treat them as one unit immediately.
-    if is_game_root_structure(inputs, &rules) {
-        return stage_loose_files(inputs, &rules, &root, name).map(|staged| vec![staged]);
+    if is_game_root_structure(inputs, rules) {
+        progress.staging(Utf8Path::new(name), 0, 1);
+        return stage_loose_files(inputs, rules, root, name).map(|staged| vec![staged]);
     }
 
     // 2. Functional Pipeline: Process individual inputs
+    let registered = staging_handler::registered();
+    let handlers: Vec<&dyn StagingHandler> =
+        [&DirectoryHandler as &dyn StagingHandler, &ArchiveHandler]
+            .into_iter()
+            .chain(registered.iter().map(|handler| handler.as_ref()))
+            .collect();
     inputs
         .iter()
-        .map(|input| {
-            // Chain strategies: Try Directory -> If None, Try Archive
-            process_as_directory(input, &rules, name)
-                .or_else(|| process_as_archive(input, &rules, &root, name))
+        .enumerate()
+        .map(|(index, input)| {
+            progress.staging(input, index, inputs.len());
+            // Chain strategies: the first handler claiming the input stages it
+            handlers
+                .iter()
+                .find_map(|handler| handler.stage(input, material, progress))
         })
         // Remove inputs that matched no strategy (Option::None)
         .filter_map(|res_opt| res_opt)
@@ -68,13 +103,54 @@ pub fn any_mod_tool_running(sys: &mut System, mods_to_install: &[StagedMod]) ->
 
 // --- Strategy Functions (Option<Result<...>>) ---
 
-/// Strategy A: Input is a directory.
+/// Strategy A: Input is a mod folder, installed in place.
+pub struct DirectoryHandler;
+
+impl StagingHandler for DirectoryHandler {
+    fn name(&self) -> &str {
+        "directory"
+    }
+
+    fn stage(
+        &self,
+        input: &Utf8Path,
+        material: &StageMaterial,
+        _progress: Progress,
+    ) -> Option<Result<StagedMod, SError>> {
+        process_as_directory(input, &material.rules, &material.name)
+    }
+}
+
+/// Strategy B: Input is an archive, unpacked into staging.
+pub struct ArchiveHandler;
+
+impl StagingHandler for ArchiveHandler {
+    fn name(&self) -> &str {
+        "archive"
+    }
+
+    fn stage(
+        &self,
+        input: &Utf8Path,
+        material: &StageMaterial,
+        progress: Progress,
+    ) -> Option<Result<StagedMod, SError>> {
+        process_as_archive(
+            input,
+            &material.rules,
+            &material.root,
+            &material.name,
+            progress,
+        )
+    }
+}
+
 /// Returns:
 /// - Some(Ok): Valid mod found.
 /// - Some(Err): Valid mod structure found but failed to parse (Critical Error).
 /// - None: Not a directory, or not a mod (safe to try next strategy).
 fn process_as_directory(
-    input: &Utf8PathBuf,
+    input: &Utf8Path,
     rules: &SPTPathRules,
     unknown_mod_name: &str,
 ) -> Option<Result<StagedMod, SError>> {
@@ -89,49 +165,61 @@ fn process_as_directory(
     match is_game_structure {
         Ok(true) => {
             // It IS a game structure, so it MUST be a valid mod. Fail if ModFS::new fails.
-            Some(ModFS::new(input, rules).map(|fs| {
-                // Determine name: manifest name (highest priority) or directory name
-                let name = read_manifest_name(input)
-                    .unwrap_or_else(|| input.file_name().unwrap_or(unknown_mod_name).to_string());
-                StagedMod {
-                    fs,
-                    source_path: input.clone(),
-                    is_staging: false,
-                    name,
-                }
-            }))
+            Some(ModFS::new(input, rules).map(|fs| staged_directory(fs, input, unknown_mod_name)))
         }
         Ok(false) => {
             // Sub-strategy A2: Folder is a standard mod folder.
             // We try ModFS::new. If it succeeds, Good. If it fails, we treat it as "Not a mod" (None).
-            ModFS::new(input, rules).ok().map(|fs| {
-                // Determine name: manifest name (highest priority) or directory name
-                let name = read_manifest_name(input)
-                    .unwrap_or_else(|| input.file_name().unwrap_or(unknown_mod_name).to_string());
-                Ok(StagedMod {
-                    fs,
-                    source_path: input.clone(),
-                    is_staging: false,
-                    name,
-                })
-            })
+            ModFS::new(input, rules)
+                .ok()
+                .map(|fs| Ok(staged_directory(fs, input, unknown_mod_name)))
         }
         Err(e) => Some(Err(e)), // Critical IO error reading dir
     }
 }
 
-/// Strategy B: Input is an archive.
+fn staged_directory(fs: ModFS, input: &Utf8Path, unknown_mod_name: &str) -> StagedMod {
+    // Determine name: manifest name (highest priority) or directory name
+    let (name, warnings) = resolve_name(input, || {
+        input.file_name().unwrap_or(unknown_mod_name).to_string()
+    });
+    StagedMod {
+        fs,
+        source_path: input.to_path_buf(),
+        is_staging: false,
+        name,
+        framework_files: Vec::new(),
+        warnings,
+    }
+}
+
 fn process_as_archive(
-    input: &Utf8PathBuf,
+    input: &Utf8Path,
     rules: &SPTPathRules,
     staging_root: &Utf8Path,
     unknown_mod_name: &str,
+    progress: Progress,
 ) -> Option<Result<StagedMod, SError>> {
-    is_archive(input).then(|| stage_archive(input, rules, staging_root, unknown_mod_name))
+    is_archive(input).then(|| stage_archive(input, rules, staging_root, unknown_mod_name, progress))
 }
 
 // --- Internal Helpers ---
 
+/// Resolves the mod name from its manifest, falling back to `infer` with a warning.
+fn resolve_name(
+    mod_root: &Utf8Path,
+    infer: impl FnOnce() -> String,
+) -> (String, Vec<OperationWarning>) {
+    match read_manifest_name(mod_root) {
+        Some(name) => (name, Vec::new()),
+        None => {
+            let name = infer();
+            let warning = OperationWarning::new(WarningKind::InferredManifest, &name);
+            (name, vec![warning])
+        }
+    }
+}
+
 /// Reads the manifest name if a manifest exists at the mod root, otherwise returns None.
 fn read_manifest_name(mod_root: &Utf8Path) -> Option<String> {
     let mod_paths = ModPaths::new(mod_root);
@@ -141,10 +229,7 @@ fn read_manifest_name(mod_root: &Utf8Path) -> Option<String> {
 }
 
 fn is_game_root_structure(inputs: &[Utf8PathBuf], rules: &SPTPathRules) -> bool {
-    let roots = [
-        get_root_component(&rules.server_mods),
-        get_root_component(&rules.client_plugins),
-    ];
+    let roots = root_components(rules);
 
     inputs.iter().any(|path| {
         path.file_name()
@@ -154,10 +239,7 @@ fn is_game_root_structure(inputs: &[Utf8PathBuf], rules: &SPTPathRules) -> bool
 }
 
 fn folder_matches_game_structure(folder: &Utf8Path, rules: &SPTPathRules) -> Result<bool, SError> {
-    let roots = [
-        get_root_component(&rules.server_mods),
-        get_root_component(&rules.client_plugins),
-    ];
+    let roots = root_components(rules);
 
     // Using iterator to avoid manual loop
     let has_match = fs::read_dir(folder)?
@@ -188,13 +270,15 @@ fn stage_loose_files(
     let fs = ModFS::new(&dest_dir, rules)?;
 
     // Determine name: manifest name (highest priority) or translated "Unknown mod" for loose files
-    let name = read_manifest_name(&dest_dir).unwrap_or_else(|| unknown_mod_name.to_string());
+    let (name, warnings) = resolve_name(&dest_dir, || unknown_mod_name.to_string());
 
     Ok(StagedMod {
         fs,
         source_path: dest_dir,
         is_staging: true,
         name,
+        framework_files: Vec::new(),
+        warnings,
     })
 }
 
@@ -203,37 +287,58 @@ fn stage_archive(
     rules: &SPTPathRules,
     staging_root: &Utf8Path,
     unknown_mod_name: &str,
+    progress: Progress,
 ) -> Result<StagedMod, SError> {
     let uuid = Uuid::new_v4().to_string();
     let dest_dir = staging_root.join(uuid);
     fs::create_dir_all(&dest_dir)?;
 
-    decompression::extract(archive, &dest_dir)?;
+    // Abort without leaving a partially unpacked archive behind
+    let report = decompression::extract(archive, &dest_dir, progress)
+        .inspect_err(|_| clean_up(true, &dest_dir).unwrap_or_default())?;
+    debug!(
+        "Extracted {} bytes from {archive}, skipped {} entries",
+        report.extracted_bytes,
+        report.skipped.len()
+    );
 
     let fs = ModFS::new(&dest_dir, rules)?;
 
     // Determine name: manifest name (highest priority) or archive name without extension
-    let name = read_manifest_name(&dest_dir)
-        .unwrap_or_else(|| archive.file_stem().unwrap_or(unknown_mod_name).to_string());
+    let (name, mut warnings) = resolve_name(&dest_dir, || {
+        archive.file_stem().unwrap_or(unknown_mod_name).to_string()
+    });
+    if !report.skipped.is_empty() {
+        warnings.push(
+            OperationWarning::new(WarningKind::SkippedUnsafeEntry, &name)
+                .with_details(&report.skipped),
+        );
+    }
 
     Ok(StagedMod {
         fs,
         source_path: dest_dir,
         is_staging: true,
         name,
+        framework_files: Vec::new(),
+        warnings,
     })
 }
 
-fn is_archive(path: &Utf8Path) -> bool {
-    path.extension()
-        .map(|ext| ext.to_lowercase() == "zip")
-        .unwrap_or(false)
+pub(crate) fn is_archive(path: &Utf8Path) -> bool {
+    ArchiveFormat::from_extension(path)
+        .or_else(|| ArchiveFormat::detect(path))
+        .is_some()
 }
 
 fn get_root_component(path: &Utf8Path) -> Option<&str> {
     path.components().next().map(|c| c.as_str())
 }
 
+fn root_components(rules: &SPTPathRules) -> Vec<Option<&str>> {
+    rules.mod_roots().map(get_root_component).collect()
+}
+
 pub fn clean_up(is_staging: bool, source_path: &Utf8Path) -> Result<(), SError> {
     if !is_staging {
         return Ok(());
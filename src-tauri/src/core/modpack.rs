@@ -0,0 +1,244 @@
+use crate::core::library::Library;
+use crate::core::mod_stager::StageMaterial;
+use crate::core::progress::Progress;
+use crate::core::{decompression, lockfile, mod_manager, mod_stager, profiles, repo_store};
+use crate::models::error::SError;
+use crate::models::mod_dto::ModMetadata;
+use crate::models::profile::ModProfile;
+use crate::models::warning::{OperationWarning, WarningKind};
+use crate::utils::file::FileUtils;
+use crate::utils::scan;
+use crate::utils::toml::Toml;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Write};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+pub const FORMAT: u32 = 1;
+pub const EXTENSION: &str = "mkpack";
+/// Entry holding the `ModPack` description.
+const PACK_MANIFEST: &str = "modpack.toml";
+/// Folder holding the bundled mods, one folder per mod id as stored in the repo.
+const PAYLOAD_DIR: &str = "mods";
+
+/// A mod of the pack with the state it had in the exporting library.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PackedMod {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    pub is_active: bool,
+    /// Whether the files of the mod are in the pack.
+    pub bundled: bool,
+    #[serde(default)]
+    pub metadata: ModMetadata,
+}
+
+/// A shared library setup: every mod with its activation, and the load order of the active
+/// profile.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModPack {
+    pub format: u32,
+    pub name: String,
+    pub spt_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Ids of the active mods, in load order.
+    #[serde(default)]
+    pub load_order: Vec<String>,
+    #[serde(default)]
+    pub mods: Vec<PackedMod>,
+}
+
+/// Describes the library as a pack, marking every mod as bundled when `include_payloads`.
+pub fn build(library: &Library, include_payloads: bool) -> ModPack {
+    let mods = library
+        .mods
+        .values()
+        .map(|m| {
+            let manifest = library.cache.manifests.get(&m.id);
+            PackedMod {
+                id: m.id.clone(),
+                name: m.name.clone(),
+                version: manifest.map(|manifest| manifest.version.clone()),
+                source_url: manifest.and_then(lockfile::source_url),
+                is_active: m.is_active,
+                bundled: include_payloads,
+                metadata: m.metadata.clone(),
+            }
+        })
+        .collect();
+
+    ModPack {
+        format: FORMAT,
+        name: library.name.clone(),
+        spt_version: library.spt_version.clone(),
+        profile: library.active_profile.clone(),
+        load_order: profiles::current_order(library),
+        mods,
+    }
+}
+
+/// Writes the pack of the library to a zip, along with the repo folder of every mod when
+/// `include_payloads`. `output` may be a directory, in which case the file is named after
+/// the library.
+pub fn export(
+    library: &Library,
+    output: &Utf8Path,
+    include_payloads: bool,
+) -> Result<Utf8PathBuf, SError> {
+    let pack = build(library, include_payloads);
+    let path = match output.is_dir() {
+        true => output.join(format!("{}.{EXTENSION}", library.name)),
+        false => output.to_path_buf(),
+    };
+
+    let mut zip = ZipWriter::new(File::create(&path)?);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(PACK_MANIFEST, options)?;
+    zip.write_all(Toml::to_string(&pack)?.as_bytes())?;
+
+    let store = repo_store::open(&library.lib_paths);
+    for packed in pack.mods.iter().filter(|packed| packed.bundled) {
+        let root = store.payload_dir(&packed.id);
+        for entry in scan::walk(&root)
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+        {
+            let Some(file) = Utf8Path::from_path(entry.path()) else {
+                continue;
+            };
+            let rel = file.strip_prefix(&root)?;
+            let name = Utf8Path::new(PAYLOAD_DIR).join(&packed.id).join(rel);
+            zip.start_file(name.as_str().replace('\\', "/"), options)?;
+            std::io::copy(&mut File::open(file)?, &mut zip)?;
+        }
+    }
+
+    zip.finish()?;
+    Ok(path)
+}
+
+/// Reads the description of a pack without extracting it.
+pub fn read(pack: &Utf8Path) -> Result<ModPack, SError> {
+    let mut archive = ZipArchive::new(File::open(pack)?)?;
+    let mut content = String::new();
+    archive
+        .by_name(PACK_MANIFEST)
+        .map_err(|_| SError::CorruptArchive(pack.to_string()))?
+        .read_to_string(&mut content)?;
+    Toml::parse(&content)
+}
+
+/// Installs the bundled mods of a pack through staging, then makes exactly its mods active,
+/// applies their metadata and restores its profile with the load order. Mods neither bundled
+/// nor installed are reported as warnings with their download source. The library is left
+/// dirty, to be synced like any other change of activation.
+pub fn import(
+    library: &mut Library,
+    pack_path: &Utf8Path,
+    material: &StageMaterial,
+    progress: Progress,
+) -> Result<Vec<OperationWarning>, SError> {
+    let pack = read(pack_path)?;
+    let scratch = library
+        .lib_paths
+        .staging
+        .join(format!("modpack-{}", Uuid::new_v4()));
+
+    let result = decompression::extract(pack_path, &scratch, progress)
+        .and_then(|_| install(library, &pack, &scratch, material, progress));
+    if scratch.exists() {
+        FileUtils::remove_recursive(&scratch)?;
+    }
+    let (ids, mut warnings) = result?;
+
+    restore(library, &pack, &ids);
+    warnings.extend(
+        pack.mods
+            .iter()
+            .filter(|packed| !ids.contains_key(&packed.id))
+            .map(|packed| {
+                OperationWarning::new(WarningKind::ModPackModMissing, &packed.name)
+                    .with_details(packed.source_url.as_slice())
+            }),
+    );
+    library.mark_dirty();
+    library.persist_manifest()?;
+    Ok(warnings)
+}
+
+/// Installs the bundled mods. Returns the library id of each mod of the pack that is
+/// installed now, which differs from the packed id when the mod has no manifest.
+fn install(
+    library: &mut Library,
+    pack: &ModPack,
+    scratch: &Utf8Path,
+    material: &StageMaterial,
+    progress: Progress,
+) -> Result<(BTreeMap<String, String>, Vec<OperationWarning>), SError> {
+    pack.mods.iter().try_fold(
+        (BTreeMap::new(), Vec::new()),
+        |(mut ids, mut warnings), packed| {
+            if !packed.bundled {
+                if library.mods.contains_key(&packed.id) {
+                    ids.insert(packed.id.clone(), packed.id.clone());
+                }
+                return Ok((ids, warnings));
+            }
+
+            let folder = scratch.join(PAYLOAD_DIR).join(&packed.id);
+            let mut staged = mod_stager::resolve(&[folder], material, progress)?;
+            staged.iter_mut().for_each(|s| s.name = packed.name.clone());
+            if let Some(first) = staged.first() {
+                ids.insert(packed.id.clone(), first.fs.id.clone());
+            }
+            warnings.extend(mod_manager::add_staged(library, staged, progress)?);
+            Ok((ids, warnings))
+        },
+    )
+}
+
+fn restore(library: &mut Library, pack: &ModPack, ids: &BTreeMap<String, String>) {
+    let active: HashSet<&String> = pack
+        .mods
+        .iter()
+        .filter(|packed| packed.is_active)
+        .filter_map(|packed| ids.get(&packed.id))
+        .collect();
+    library
+        .mods
+        .iter_mut()
+        .for_each(|(id, m)| m.is_active = active.contains(id));
+
+    // Custom icons stay local, their files are not part of the metadata
+    for packed in &pack.mods {
+        let Some(m) = ids.get(&packed.id).and_then(|id| library.mods.get_mut(id)) else {
+            continue;
+        };
+        m.metadata = ModMetadata {
+            icon: m.metadata.icon.take(),
+            ..packed.metadata.clone()
+        };
+    }
+
+    let Some(name) = &pack.profile else {
+        return;
+    };
+    let order = pack
+        .load_order
+        .iter()
+        .filter_map(|id| ids.get(id).cloned())
+        .collect();
+    library
+        .profiles
+        .insert(name.clone(), ModProfile { mods: order });
+    library.active_profile = Some(name.clone());
+}
@@ -0,0 +1,153 @@
+use crate::core::cache::LibraryCache;
+use crate::core::deployment;
+use crate::core::library::Library;
+use crate::core::linker;
+use crate::core::repo_store;
+use crate::models::deployment_plan::SyncScope;
+use crate::models::error::SError;
+use crate::models::ownership::{ForeignLink, ModLinks, OwnershipReport};
+use crate::models::paths::{LibPathRules, SPTPathRules};
+use crate::models::warning::{OperationWarning, WarningKind};
+use crate::utils::scan;
+use camino::{Utf8Path, Utf8PathBuf};
+use file_id::FileId;
+use std::collections::{BTreeMap, HashMap};
+use tracing::warn;
+
+/// Lists the links found in the mod roots of the game root: the ones of each mod of the
+/// library, and the ones other tools made, which sync and purge leave alone.
+pub fn report(library: &Library) -> Result<OwnershipReport, SError> {
+    let owners = hardlink_owners(&library.lib_paths, &library.cache);
+    let mut mods: BTreeMap<String, Vec<Utf8PathBuf>> = BTreeMap::new();
+    let mut foreign = Vec::new();
+
+    for path in entries(&library.game_root, &library.spt_rules, SyncScope::All)? {
+        let rel = path.strip_prefix(&library.game_root)?.to_path_buf();
+        let owner = match linker::read_link_target(&path) {
+            Ok(target) if target.starts_with(&library.repo_root) => {
+                mod_of(&target, &library.lib_paths)
+            }
+            Ok(target) => {
+                foreign.push(ForeignLink { path: rel, target });
+                continue;
+            }
+            Err(_) => linker::get_id(&path)
+                .ok()
+                .and_then(|id| owners.get(&id).cloned()),
+        };
+        if let Some(mod_id) = owner {
+            mods.entry(mod_id).or_default().push(rel);
+        }
+    }
+
+    Ok(OwnershipReport {
+        mods: mods
+            .into_iter()
+            .map(|(mod_id, links)| ModLinks { mod_id, links })
+            .collect(),
+        foreign,
+    })
+}
+
+/// Links in the mod roots within `scope` that point outside the repo.
+pub fn foreign_links(
+    game_root: &Utf8Path,
+    repo_root: &Utf8Path,
+    spt_rules: &SPTPathRules,
+    scope: SyncScope,
+) -> Result<Vec<ForeignLink>, SError> {
+    entries(game_root, spt_rules, scope)?
+        .into_iter()
+        .filter_map(|path| {
+            let target = linker::read_link_target(&path).ok()?;
+            (!target.starts_with(repo_root)).then_some((path, target))
+        })
+        .map(|(path, target)| {
+            Ok(ForeignLink {
+                path: path.strip_prefix(game_root)?.to_path_buf(),
+                target,
+            })
+        })
+        .collect()
+}
+
+/// Whether the path is a junction or symlink pointing outside of `repo`.
+/// Plain files and hardlinks cannot be told apart from what a user put there, so they are not.
+pub fn is_foreign(path: &Utf8Path, repo: &Utf8Path) -> bool {
+    linker::read_link_target(path).is_ok_and(|target| !target.starts_with(repo))
+}
+
+/// Warns about links of another mod manager in the mod roots the sync touched, as both
+/// managers deploying the same mods will conflict.
+pub fn sync_warnings(library: &Library, scope: SyncScope) -> Vec<OperationWarning> {
+    let links = match foreign_links(
+        &library.game_root,
+        &library.repo_root,
+        &library.spt_rules,
+        scope,
+    ) {
+        Ok(links) => links,
+        Err(e) => {
+            warn!("Failed to look for links of other mod managers: {e}");
+            return Vec::new();
+        }
+    };
+    if links.is_empty() {
+        return Vec::new();
+    }
+
+    let paths: Vec<&Utf8PathBuf> = links.iter().map(|link| &link.path).collect();
+    vec![
+        OperationWarning::new(WarningKind::ForeignLinks, library.game_root.as_str())
+            .with_details(&paths),
+    ]
+}
+
+/// Every entry below the mod roots within `scope`, with links yielded as leaves.
+fn entries(
+    game_root: &Utf8Path,
+    spt_rules: &SPTPathRules,
+    scope: SyncScope,
+) -> Result<Vec<Utf8PathBuf>, SError> {
+    let roots = deployment::get_protected_paths(spt_rules)
+        .into_iter()
+        .filter(|rel| scope.covers(rel, spt_rules))
+        .map(|rel| game_root.join(rel))
+        .filter(|root| root.exists());
+
+    let mut paths = Vec::new();
+    for root in roots {
+        for entry in scan::walk(&root) {
+            let entry = entry.map_err(|e| SError::IOError(e.to_string()))?;
+            let path = Utf8Path::from_path(entry.path()).ok_or(SError::Unexpected)?;
+            if path != root {
+                paths.push(path.to_path_buf());
+            }
+        }
+    }
+    Ok(paths)
+}
+
+/// Mod whose folder in the repo a link target lies in.
+fn mod_of(target: &Utf8Path, lib_paths: &LibPathRules) -> Option<String> {
+    let rel = target
+        .strip_prefix(repo_store::open(lib_paths).root())
+        .ok()?;
+    rel.components().next().map(|c| c.as_str().to_string())
+}
+
+pub(crate) fn hardlink_owners(
+    lib_paths: &LibPathRules,
+    cache: &LibraryCache,
+) -> HashMap<FileId, String> {
+    let store = repo_store::open(lib_paths);
+    cache
+        .mods
+        .iter()
+        .flat_map(|(id, fs)| {
+            let root = store.payload_dir(id);
+            fs.files.iter().map(move |f| (id, root.join(f)))
+        })
+        .filter_map(|(id, path)| Some((linker::get_id(&path).ok()?, id.clone())))
+        .collect()
+}
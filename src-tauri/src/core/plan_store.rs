@@ -0,0 +1,98 @@
+use crate::models::deployment_plan::{DeploymentPlan, SavedPlan};
+use crate::models::error::SError;
+use crate::models::paths::LibPathRules;
+use crate::utils::toml::Toml;
+use camino::Utf8PathBuf;
+use std::fs;
+
+/// Writes a plan to `plans/{timestamp}.toml` and returns the timestamp to `load` it by.
+/// Later exports within the same second are numbered, as `{timestamp}-1` and so on, instead of
+/// overwriting the first; the plan takes the numbered timestamp.
+pub fn save(lib_paths: &LibPathRules, plan: &mut DeploymentPlan) -> Result<String, SError> {
+    std::fs::create_dir_all(&lib_paths.plans)?;
+    let mut timestamp = plan.timestamp.clone();
+    for n in 1.. {
+        if !plan_path(lib_paths, &timestamp)?.exists() {
+            break;
+        }
+        timestamp = format!("{}-{n}", plan.timestamp);
+    }
+    plan.timestamp = timestamp.clone();
+    Toml::write(&plan_path(lib_paths, &timestamp)?, plan)?;
+    Ok(timestamp)
+}
+
+/// Lists all exported plans.
+/// Returns timestamps in descending order (newest first).
+pub fn list(lib_paths: &LibPathRules) -> Result<Vec<SavedPlan>, SError> {
+    if !lib_paths.plans.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut plans: Vec<SavedPlan> = std::fs::read_dir(&lib_paths.plans)?
+        .filter_map(|entry| Utf8PathBuf::from_path_buf(entry.ok()?.path()).ok())
+        .filter(|path| path.extension() == Some("toml"))
+        .filter_map(|path| {
+            Some(SavedPlan {
+                timestamp: path.file_stem()?.to_string(),
+                path,
+            })
+        })
+        .collect();
+
+    // Sort descending (newest first)
+    plans.sort_by_key(|plan| std::cmp::Reverse(order(&plan.timestamp)));
+
+    Ok(plans)
+}
+
+/// Loads a previously exported plan by its timestamp.
+pub fn load(lib_paths: &LibPathRules, timestamp: &str) -> Result<DeploymentPlan, SError> {
+    let path = plan_path(lib_paths, timestamp)?;
+
+    if !path.exists() {
+        return Err(SError::FileOrDirectoryNotFound(path.to_string()));
+    }
+
+    Toml::read(&path)
+}
+
+/// Keeps the plan deployed by the last successful sync, read back by `last_sync`.
+pub fn record_last_sync(lib_paths: &LibPathRules, plan: &DeploymentPlan) -> Result<(), SError> {
+    Toml::write(&lib_paths.last_sync_plan, plan)
+}
+
+/// Plan deployed by the last successful sync, `None` before the first one.
+pub fn last_sync(lib_paths: &LibPathRules) -> Result<Option<DeploymentPlan>, SError> {
+    if !fs::exists(&lib_paths.last_sync_plan)? {
+        return Ok(None);
+    }
+    Toml::read(&lib_paths.last_sync_plan).map(Some)
+}
+
+/// Timestamps are used as file names, so anything but digits, with the number `save` may
+/// append, could escape `plans/`.
+fn plan_path(lib_paths: &LibPathRules, timestamp: &str) -> Result<Utf8PathBuf, SError> {
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let is_valid = match timestamp.split_once('-') {
+        Some((secs, n)) => is_number(secs) && is_number(n),
+        None => is_number(timestamp),
+    };
+    if !is_valid {
+        return Err(SError::ParseError(format!(
+            "Invalid plan timestamp: {}",
+            timestamp
+        )));
+    }
+
+    Ok(lib_paths.plans.join(format!("{}.toml", timestamp)))
+}
+
+/// Orders the plans of one second by the number `save` appended, `12` before `12-1`.
+fn order(timestamp: &str) -> (u64, u64) {
+    let (secs, n) = timestamp.split_once('-').unwrap_or((timestamp, "0"));
+    (
+        secs.parse().unwrap_or_default(),
+        n.parse().unwrap_or_default(),
+    )
+}
@@ -0,0 +1,157 @@
+use crate::core::crash_guard::{self, CrashCounter};
+use crate::core::progress::Progress;
+use crate::core::shared_state::SharedState;
+use crate::core::sync_hook;
+use crate::models::events::{GameStarted, GameStopped, RepeatedCrash, ServerCrashed};
+use crate::models::global::CrashGuardPolicy;
+use crate::utils::process::ProcessChecker;
+use std::time::Duration;
+use sysinfo::System;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use tracing::{info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProcessState {
+    pub client: bool,
+    pub server: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LifecycleChange {
+    GameStarted,
+    GameStopped,
+    ServerCrashed(Option<i32>),
+}
+
+/// Derives lifecycle changes between two observations.
+/// Processes started outside of Mod Keeper expose no exit code, so a server is considered
+/// crashed when it disappears while the client is still up; stopping it afterwards is normal.
+pub fn transitions(prev: ProcessState, cur: ProcessState) -> Vec<LifecycleChange> {
+    let client = match (prev.client, cur.client) {
+        (false, true) => Some(LifecycleChange::GameStarted),
+        (true, false) => Some(LifecycleChange::GameStopped),
+        _ => None,
+    };
+    let server = (prev.server && !cur.server && prev.client && cur.client)
+        .then_some(LifecycleChange::ServerCrashed(None));
+
+    client.into_iter().chain(server).collect()
+}
+
+fn observe(sys: &mut System, shared: &SharedState) -> ProcessState {
+    let Some(canonical) =
+        shared.instance(|instance| instance.as_ref().map(|lib| lib.spt_paths_canonical.clone()))
+    else {
+        return ProcessState::default();
+    };
+
+    match ProcessChecker::running_flags(sys, &[canonical.client_exe, canonical.server_exe])[..] {
+        [client, server] => ProcessState { client, server },
+        _ => ProcessState::default(),
+    }
+}
+
+fn emit(app: &AppHandle, change: &LifecycleChange) -> Result<(), tauri::Error> {
+    match change {
+        LifecycleChange::GameStarted => GameStarted.emit(app),
+        LifecycleChange::GameStopped => GameStopped.emit(app),
+        LifecycleChange::ServerCrashed(exit_code) => ServerCrashed {
+            exit_code: *exit_code,
+        }
+        .emit(app),
+    }
+}
+
+/// Counts a server crash against the mod its log blames. Returns the mod once it crashed
+/// repeatedly under `CrashGuardPolicy::Disable`, to be disabled when the game has stopped.
+fn guard(app: &AppHandle, shared: &SharedState, counter: &mut CrashCounter) -> Option<String> {
+    let policy = shared.config(|config| config.crash_guard);
+    if policy == CrashGuardPolicy::Off {
+        return None;
+    }
+    let mod_id = shared.with_lib(crash_guard::attribute).ok().flatten()?;
+    info!("Server crash attributed to {mod_id}");
+    if !counter.count(&mod_id) {
+        return None;
+    }
+    if policy == CrashGuardPolicy::Disable {
+        return Some(mod_id);
+    }
+    if let Err(e) = (RepeatedCrash {
+        mod_id,
+        disabled: false,
+    })
+    .emit(app)
+    {
+        warn!("Failed to emit RepeatedCrash: {e}");
+    }
+    None
+}
+
+/// Disables a mod that crashed the server repeatedly and syncs without it.
+fn disable(app: &AppHandle, shared: &SharedState, mod_id: String) {
+    let (policy, link_policy, hooks) = shared.config(|config| {
+        (
+            config.checklist_policy,
+            config.link_failure_policy,
+            config.sync_hooks.clone(),
+        )
+    });
+    let result = shared
+        .with_lib(|inst| inst.game_root.clone())
+        .and_then(|game_root| {
+            sync_hook::around(&hooks, &game_root, Progress::silent(), || {
+                shared
+                    .with_lib_mut(|inst| crash_guard::disable(inst, &mod_id, policy, link_policy))?
+            })
+        });
+    match result {
+        Ok(_) => {
+            if let Err(e) = (RepeatedCrash {
+                mod_id,
+                disabled: true,
+            })
+            .emit(app)
+            {
+                warn!("Failed to emit RepeatedCrash: {e}");
+            }
+        }
+        Err(e) => warn!("Failed to disable {mod_id} after repeated crashes: {e}"),
+    }
+}
+
+/// Polls the SPT processes of the active library for the lifetime of the app
+/// and emits typed lifecycle events to the frontend.
+/// Server crashes are counted per blamed mod, see `core::crash_guard`.
+pub fn spawn(app: AppHandle, shared: SharedState) {
+    std::thread::spawn(move || {
+        let mut sys = System::new();
+        let mut last = ProcessState::default();
+        let mut crashes = CrashCounter::default();
+        let mut pending = None;
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let current = observe(&mut sys, &shared);
+            for change in transitions(last, current) {
+                info!("Process lifecycle change: {change:?}");
+                if let Err(e) = emit(&app, &change) {
+                    warn!("Failed to emit {change:?}: {e}");
+                }
+                if let LifecycleChange::ServerCrashed(_) = change {
+                    pending = guard(&app, &shared, &mut crashes).or(pending);
+                }
+            }
+            if !current.client && !current.server {
+                if let Some(mod_id) = pending.take() {
+                    disable(&app, &shared, mod_id);
+                }
+            }
+            last = current;
+        }
+    });
+}
@@ -0,0 +1,79 @@
+use crate::core::cache::LibraryCache;
+use crate::core::library::Library;
+use crate::core::sync_index::SyncIndex;
+use crate::models::error::SError;
+use crate::models::mod_dto::{Effect, Mod, ModManifest, PrerequisiteKind};
+use crate::models::warning::{OperationWarning, WarningKind};
+use crate::utils::file::FileUtils;
+use crate::utils::time::get_unix_timestamp;
+use camino::Utf8PathBuf;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+const ADDED: &str = "added";
+const REMOVED: &str = "removed";
+
+/// Flags mods that change the server database (traders, items) or ask for a wipe and were
+/// activated or deactivated since the last successful sync.
+/// Without an index every active mod counts as added, as nothing is known to be deployed.
+pub fn detect(
+    mods: &BTreeMap<String, Mod>,
+    cache: &LibraryCache,
+    index: Option<&SyncIndex>,
+) -> Vec<OperationWarning> {
+    let active: BTreeSet<&str> = mods
+        .values()
+        .filter(|m| m.is_active)
+        .map(|m| m.id.as_str())
+        .collect();
+    let deployed: BTreeSet<&str> = index
+        .map(|index| index.digests.keys().map(String::as_str).collect())
+        .unwrap_or_default();
+
+    let added = active.difference(&deployed).map(|id| (*id, ADDED));
+    let removed = deployed.difference(&active).map(|id| (*id, REMOVED));
+
+    added
+        .chain(removed)
+        .filter(|(id, _)| cache.manifests.get(*id).is_some_and(touches_database))
+        .map(|(id, change)| {
+            let name = mods.get(id).map_or(id, |m| m.name.as_str());
+            OperationWarning::new(WarningKind::WipeRecommended, name).with_details(&[change])
+        })
+        .collect()
+}
+
+/// Copies the server profiles of the library's game to `profile-backups/{timestamp}`,
+/// then deletes them so the server creates fresh ones on the next start.
+/// Returns the backup folder, or None when there were no profiles to reset.
+pub fn reset_profiles(library: &Library) -> Result<Option<Utf8PathBuf>, SError> {
+    let profiles = library.game_root.join(&library.spt_rules.server_profiles);
+    if !profiles.exists() {
+        return Ok(None);
+    }
+
+    let backup = library
+        .lib_paths
+        .profile_backups
+        .join(get_unix_timestamp().to_string());
+    FileUtils::copy_recursive(&profiles, &backup)?;
+
+    fs::remove_dir_all(&profiles)?;
+    fs::create_dir_all(&profiles)?;
+    Ok(Some(backup))
+}
+
+fn touches_database(manifest: &ModManifest) -> bool {
+    let edits_database = manifest
+        .effects
+        .iter()
+        .flatten()
+        .any(|effect| matches!(effect, Effect::Trader | Effect::Item));
+    let requests_wipe = manifest
+        .prerequisites
+        .iter()
+        .flatten()
+        .any(|p| p.kind == PrerequisiteKind::ServerWipe);
+
+    edits_database || requests_wipe
+}
@@ -0,0 +1,115 @@
+use crate::core::library::Library;
+use crate::models::error::SError;
+use crate::models::profile::ModProfile;
+use crate::utils::naming;
+use std::collections::BTreeMap;
+
+/// Saves the active mods as a new profile and makes it the active one.
+pub fn create(library: &mut Library, name: &str) -> Result<(), SError> {
+    let name = new_name(library, name)?;
+    capture(library);
+    let profile = ModProfile {
+        mods: current_order(library),
+    };
+    library.profiles.insert(name.clone(), profile);
+    library.active_profile = Some(name);
+    library.persist_manifest()
+}
+
+/// Activates exactly the mods of the profile. The profile left keeps the activation it had,
+/// and the library turns dirty so the next sync deploys the new set.
+/// Mods removed from the library since the profile was saved are ignored.
+pub fn switch(library: &mut Library, name: &str) -> Result<(), SError> {
+    let profile = find(library, name)?.clone();
+    capture(library);
+
+    library
+        .mods
+        .iter_mut()
+        .for_each(|(id, m)| m.is_active = profile.mods.contains(id));
+    library.active_profile = Some(name.to_string());
+    library.mark_dirty();
+    library.persist_manifest()
+}
+
+/// Removes a profile; the mods stay as they are.
+pub fn delete(library: &mut Library, name: &str) -> Result<(), SError> {
+    find(library, name)?;
+    library.profiles.remove(name);
+    if library.active_profile.as_deref() == Some(name) {
+        library.active_profile = None;
+    }
+    library.persist_manifest()
+}
+
+/// Copies a profile under a new name without switching to it.
+pub fn duplicate(library: &mut Library, source: &str, name: &str) -> Result<(), SError> {
+    let name = new_name(library, name)?;
+    capture(library);
+    let profile = find(library, source)?.clone();
+    library.profiles.insert(name, profile);
+    library.persist_manifest()
+}
+
+/// The profiles with the active one reflecting the mods active right now.
+pub fn snapshot(library: &Library) -> BTreeMap<String, ModProfile> {
+    let mut profiles = library.profiles.clone();
+    if let Some(profile) = library
+        .active_profile
+        .as_ref()
+        .and_then(|name| profiles.get_mut(name))
+    {
+        profile.mods = current_order(library);
+    }
+    profiles
+}
+
+/// Ids of the active mods in load order: the order of the active profile for the mods it
+/// already holds, then the mods activated since, higher priority first.
+pub fn current_order(library: &Library) -> Vec<String> {
+    let is_active = |id: &String| library.mods.get(id).is_some_and(|m| m.is_active);
+    let kept: Vec<String> = library
+        .active_profile
+        .as_ref()
+        .and_then(|name| library.profiles.get(name))
+        .map(|profile| {
+            profile
+                .mods
+                .iter()
+                .filter(|id| is_active(id))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut added: Vec<_> = library
+        .mods
+        .values()
+        .filter(|m| m.is_active && !kept.contains(&m.id))
+        .collect();
+    added.sort_by_key(|m| std::cmp::Reverse(m.metadata.priority));
+
+    kept.into_iter()
+        .chain(added.into_iter().map(|m| m.id.clone()))
+        .collect()
+}
+
+/// Stores the current activation in the active profile before it is left or copied.
+fn capture(library: &mut Library) {
+    library.profiles = snapshot(library);
+}
+
+fn find<'a>(library: &'a Library, name: &str) -> Result<&'a ModProfile, SError> {
+    library
+        .profiles
+        .get(name)
+        .ok_or_else(|| SError::ProfileNotFound(name.to_string()))
+}
+
+fn new_name(library: &Library, name: &str) -> Result<String, SError> {
+    let name = naming::sanitize_name(name)?;
+    match library.profiles.contains_key(&name) {
+        true => Err(SError::AlreadyExists(name)),
+        false => Ok(name),
+    }
+}
@@ -0,0 +1,218 @@
+use crate::models::error::SError;
+use crate::models::events::TaskStatus;
+use crate::models::sync_hook::HookRun;
+use crate::models::warning::OperationWarning;
+use camino::Utf8Path;
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
+
+/// Rate samples closer together than this are too noisy to estimate from.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+/// Weight of the newest sample in the smoothed rate.
+const SMOOTHING: f64 = 0.3;
+
+/// Reports the stages of one task. Commands forward them to the frontend as `TaskStatus`
+/// events; batch mode and tests stay silent.
+#[derive(Clone, Copy)]
+pub struct Progress<'a> {
+    task: &'a str,
+    sink: &'a dyn Fn(TaskStatus),
+}
+
+fn ignore(_: TaskStatus) {}
+
+impl<'a> Progress<'a> {
+    pub fn new(task: &'a str, sink: &'a dyn Fn(TaskStatus)) -> Self {
+        Self { task, sink }
+    }
+
+    pub fn silent() -> Progress<'static> {
+        Progress {
+            task: "",
+            sink: &ignore,
+        }
+    }
+
+    pub fn staging(&self, file: &Utf8Path, index: usize, total: usize) {
+        (self.sink)(TaskStatus::Staging {
+            task: self.task.to_string(),
+            file: file.to_string(),
+            index: index as u32,
+            total: total as u32,
+        });
+    }
+
+    pub fn scanning(&self, folder: &Utf8Path) {
+        (self.sink)(TaskStatus::Scanning {
+            task: self.task.to_string(),
+            folder: folder.to_string(),
+        });
+    }
+
+    pub fn copying(&self, mod_name: &str, index: usize, total: usize) {
+        (self.sink)(TaskStatus::CopyingToRepo {
+            task: self.task.to_string(),
+            mod_name: mod_name.to_string(),
+            index: index as u32,
+            total: total as u32,
+        });
+    }
+
+    /// Starts reporting the bytes of a copy or extraction of `total` bytes.
+    pub fn meter(&self, item: &str, total: u64) -> TransferMeter<'a> {
+        TransferMeter {
+            progress: *self,
+            item: item.to_string(),
+            done: 0,
+            total,
+            throughput: Throughput::start(Instant::now()),
+        }
+    }
+
+    pub fn transfer(&self, item: &str, done: u64, total: u64, throughput: &Throughput) {
+        (self.sink)(TaskStatus::Transfer {
+            task: self.task.to_string(),
+            item: item.to_string(),
+            done_bytes: done,
+            total_bytes: total,
+            bytes_per_sec: throughput.bytes_per_sec(),
+            eta_secs: throughput
+                .eta(done, total)
+                .map(|eta| eta.as_secs().min(u32::MAX as u64) as u32),
+        });
+    }
+
+    pub fn linking(&self, mods: usize) {
+        (self.sink)(TaskStatus::Linking {
+            task: self.task.to_string(),
+            mods: mods as u32,
+        });
+    }
+
+    pub fn warnings(&self, warnings: &[OperationWarning]) {
+        warnings.iter().for_each(|warning| {
+            (self.sink)(TaskStatus::Warning {
+                task: self.task.to_string(),
+                warning: warning.clone(),
+            })
+        });
+    }
+
+    pub fn hook(&self, run: &HookRun) {
+        (self.sink)(TaskStatus::Hook {
+            task: self.task.to_string(),
+            run: run.clone(),
+        });
+    }
+
+    /// Reports how the task ended, passing the result through.
+    pub fn finish<T>(&self, result: Result<T, SError>) -> Result<T, SError> {
+        let status = match &result {
+            Ok(_) => TaskStatus::Done {
+                task: self.task.to_string(),
+            },
+            Err(error) => TaskStatus::Failed {
+                task: self.task.to_string(),
+                error: error.clone(),
+            },
+        };
+        (self.sink)(status);
+        result
+    }
+}
+
+/// Smoothed bytes per second of a transfer, from which its remaining time is estimated.
+/// Computed here so every frontend surface shows the same estimate.
+#[derive(Clone, Debug)]
+pub struct Throughput {
+    sampled_at: Instant,
+    sampled_bytes: u64,
+    rate: Option<f64>,
+}
+
+impl Throughput {
+    pub fn start(now: Instant) -> Self {
+        Self {
+            sampled_at: now,
+            sampled_bytes: 0,
+            rate: None,
+        }
+    }
+
+    /// Takes a rate sample once `SAMPLE_INTERVAL` passed since the last one; returns whether
+    /// it did. `done` is the byte count of the whole transfer so far.
+    pub fn sample(&mut self, done: u64, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.sampled_at);
+        if elapsed < SAMPLE_INTERVAL {
+            return false;
+        }
+        let current = done.saturating_sub(self.sampled_bytes) as f64 / elapsed.as_secs_f64();
+        self.rate = Some(match self.rate {
+            Some(rate) => rate + SMOOTHING * (current - rate),
+            None => current,
+        });
+        self.sampled_at = now;
+        self.sampled_bytes = done;
+        true
+    }
+
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.rate.unwrap_or(0.0) as u64
+    }
+
+    /// Time left for the remaining bytes at the smoothed rate; `None` before the first
+    /// sample and while stalled.
+    pub fn eta(&self, done: u64, total: u64) -> Option<Duration> {
+        let rate = self.rate.filter(|rate| *rate > 0.0)?;
+        Some(Duration::from_secs_f64(
+            total.saturating_sub(done) as f64 / rate,
+        ))
+    }
+}
+
+/// Counts the bytes of one copy or extraction, reported as `TaskStatus::Transfer` at most
+/// once per rate sample.
+pub struct TransferMeter<'a> {
+    progress: Progress<'a>,
+    item: String,
+    done: u64,
+    total: u64,
+    throughput: Throughput,
+}
+
+impl<'a> TransferMeter<'a> {
+    pub fn add(&mut self, bytes: u64) {
+        self.done += bytes;
+        if self.throughput.sample(self.done, Instant::now()) {
+            self.report();
+        }
+    }
+
+    /// Reports the final count, which usually falls between two samples.
+    pub fn finish(&self) {
+        self.report();
+    }
+
+    /// Reader adding what it reads to this meter, for copies done in one `io::copy`.
+    pub fn reader<R: Read>(&mut self, inner: R) -> MeteredReader<'_, 'a, R> {
+        MeteredReader { inner, meter: self }
+    }
+
+    fn report(&self) {
+        self.progress
+            .transfer(&self.item, self.done, self.total, &self.throughput);
+    }
+}
+
+pub struct MeteredReader<'m, 'a, R> {
+    inner: R,
+    meter: &'m mut TransferMeter<'a>,
+}
+
+impl<R: Read> Read for MeteredReader<'_, '_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.meter.add(read as u64);
+        Ok(read)
+    }
+}
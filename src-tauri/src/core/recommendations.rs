@@ -0,0 +1,64 @@
+use crate::core::library::Library;
+use crate::models::compat_note::CompatKind;
+use crate::models::error::SError;
+use crate::models::mod_dto::Dependencies;
+use crate::models::recommendation::{Recommendation, RecommendationSource};
+use std::collections::BTreeMap;
+
+/// Companions of a mod that are not active yet: the optional dependencies of its manifest and
+/// the mods noted as recommended together with it. Ordered by mod id.
+pub fn for_mod(library: &Library, mod_id: &str) -> Result<Vec<Recommendation>, SError> {
+    if !library.mods.contains_key(mod_id) {
+        return Err(SError::ModNotFound(mod_id.to_string()));
+    }
+
+    let from_manifest = library
+        .cache
+        .manifests
+        .get(mod_id)
+        .and_then(|manifest| manifest.dependencies.as_ref())
+        .into_iter()
+        .flat_map(|dependencies| match dependencies {
+            Dependencies::Array(list) => list.as_slice(),
+            Dependencies::Object(_) => &[],
+        })
+        .filter(|dependency| dependency.optional == Some(true))
+        .map(|dependency| (dependency.id.as_str(), RecommendationSource::Manifest, None));
+
+    let from_notes = library
+        .compat_notes
+        .iter()
+        .filter(|note| note.kind == CompatKind::Recommended)
+        .filter_map(|note| match (note.mod_a == mod_id, note.mod_b == mod_id) {
+            (true, _) => Some((note.mod_b.as_str(), note)),
+            (_, true) => Some((note.mod_a.as_str(), note)),
+            _ => None,
+        })
+        .map(|(id, note)| {
+            (
+                id,
+                RecommendationSource::CompatNote,
+                Some(note.note.clone()),
+            )
+        });
+
+    // The manifest comes first, so it wins when both suggest the same mod
+    let recommendations = from_manifest.chain(from_notes).fold(
+        BTreeMap::<&str, Recommendation>::new(),
+        |mut acc, (id, source, note)| {
+            acc.entry(id).or_insert_with(|| Recommendation {
+                mod_id: id.to_string(),
+                name: library.mods.get(id).map(|m| m.name.clone()),
+                source,
+                note,
+            });
+            acc
+        },
+    );
+
+    Ok(recommendations
+        .into_values()
+        .filter(|r| r.mod_id != mod_id)
+        .filter(|r| !library.mods.get(&r.mod_id).is_some_and(|m| m.is_active))
+        .collect())
+}
@@ -1,21 +1,33 @@
 use crate::config::global::GlobalConfig;
-use crate::core::library::Library;
+use crate::core::confirmation::Confirmations;
+use crate::core::dev_watch::WatchHandle;
+use crate::core::drop_queue::DropQueue;
+use crate::core::fs_watch::FsWatch;
 use crate::core::mod_stager::StageMaterial;
+use crate::core::shared_state::SharedState;
 use crate::models::error::SError;
 use crate::utils::process::ProcessChecker;
 use parking_lot::Mutex;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use sysinfo::System;
 
 pub struct AppRegistry {
-    // Arc<Mutex<Option>> allows us to "swap" the entire instance safely
-    pub active_instance: Arc<Mutex<Option<Library>>>,
-    pub global_config: Arc<Mutex<GlobalConfig>>,
+    /// Global config and active library, only locked through the facade
+    pub shared: SharedState,
     pub sys: Mutex<System>,
     /// Tracks whether the init command has been called
     pub init_called: Arc<AtomicBool>,
+    /// Development source folders watched per mod id
+    pub dev_watches: Mutex<HashMap<String, WatchHandle>>,
+    /// Files dropped while a long task runs
+    pub drops: DropQueue,
+    /// Folders of the active library watched for external changes
+    pub fs_watch: FsWatch,
+    /// Tokens for destructive commands, see `request_confirmation`
+    pub confirmations: Confirmations,
 }
 
 impl AppRegistry {
@@ -24,10 +36,8 @@ impl AppRegistry {
     }
 
     pub fn get_canonical_spt_paths(&self) -> Option<Vec<PathBuf>> {
-        self.active_instance
-            .lock()
-            .as_ref()
-            .map(|v| v.spt_canonical_paths())
+        self.shared
+            .instance(|instance| instance.as_ref().map(|v| v.spt_canonical_paths()))
     }
     pub fn is_game_or_server_running(&self) -> bool {
         self.get_canonical_spt_paths()
@@ -36,21 +46,25 @@ impl AppRegistry {
     }
 
     pub fn get_stage_material(&self, unknown_mod_name: String) -> Result<StageMaterial, SError> {
-        self.active_instance
-            .lock()
-            .as_ref()
-            .map(|v| v.stage_material(unknown_mod_name))
-            .ok_or(SError::NoActiveLibrary)
+        self.shared.both(|config, instance| {
+            let library = instance.as_ref().ok_or(SError::NoActiveLibrary)?;
+            // Staging writes into the repo, so a read-only library is refused up front
+            library.ensure_writable()?;
+            Ok(library.stage_material(unknown_mod_name, config.framework_policy))
+        })
     }
 }
 
 impl Default for AppRegistry {
     fn default() -> Self {
         Self {
-            active_instance: Arc::new(Mutex::new(None)),
-            global_config: Arc::new(Mutex::new(GlobalConfig::load())),
+            shared: SharedState::new(GlobalConfig::load(), None),
             sys: Mutex::new(System::new()),
             init_called: Arc::new(AtomicBool::new(false)),
+            dev_watches: Mutex::new(HashMap::new()),
+            drops: DropQueue::default(),
+            fs_watch: FsWatch::default(),
+            confirmations: Confirmations::default(),
         }
     }
 }
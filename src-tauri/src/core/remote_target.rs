@@ -0,0 +1,196 @@
+use crate::core::library::Library;
+use crate::core::repo_store;
+use crate::models::deployment_plan::SyncScope;
+use crate::models::error::SError;
+use crate::models::paths::LibPathRules;
+use crate::models::remote_target::RemoteStatus;
+use crate::utils::msgpack::MsgPack;
+use crate::utils::time::get_unix_timestamp;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+/// Files copied to the remote server, relative to its mods folder, with the digest of the
+/// copied content. A share on another machine cannot hold links into the repo, so server mods
+/// are copied and tracked here to be replaced or removed by the next push.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct RemoteLedger {
+    pub files: BTreeMap<Utf8PathBuf, String>,
+    pub last_push: Option<String>,
+}
+
+/// Sets or clears the remote server mods folder of the library, e.g. a mounted SMB share or
+/// an SFTP endpoint mounted with sshfs. What was pushed to the previous folder is removed
+/// when it is still reachable, and forgotten otherwise.
+pub fn set_target(library: &mut Library, target: Option<Utf8PathBuf>) -> Result<(), SError> {
+    if library.remote_server == target {
+        return Ok(());
+    }
+    if let Some(path) = &target {
+        ensure_reachable(path)?;
+    }
+
+    match library.remote_server.as_deref().filter(|p| p.is_dir()) {
+        Some(previous) => purge_from(previous, &library.lib_paths)?,
+        None => write(&library.lib_paths, &RemoteLedger::default())?,
+    }
+    library.remote_server = target;
+    library.persist_manifest()
+}
+
+/// Copies the server files of the active mods to the remote server and removes what earlier
+/// pushes left there that is no longer active. Files whose content did not change are skipped.
+/// The ledger is written even when a copy fails, so the next push picks up where this one stopped.
+pub fn push(library: &Library) -> Result<RemoteStatus, SError> {
+    let remote = target(library)?;
+    let mut ledger = read(&library.lib_paths)?;
+
+    let result = copy_files(remote, &server_files(library), &mut ledger);
+    if result.is_ok() {
+        ledger.last_push = Some(get_unix_timestamp().to_string());
+    }
+    write(&library.lib_paths, &ledger)?;
+    result?;
+
+    Ok(status_of(remote, &ledger))
+}
+
+/// Removes every file pushed to the remote server.
+pub fn purge(library: &Library) -> Result<RemoteStatus, SError> {
+    let remote = target(library)?;
+    purge_from(remote, &library.lib_paths)?;
+    Ok(status_of(remote, &RemoteLedger::default()))
+}
+
+/// State of the remote server, `None` when the library has none.
+pub fn status(library: &Library) -> Result<Option<RemoteStatus>, SError> {
+    let Some(remote) = &library.remote_server else {
+        return Ok(None);
+    };
+    Ok(Some(status_of(remote, &read(&library.lib_paths)?)))
+}
+
+/// Reads the ledger. A missing one means nothing was pushed.
+pub fn read(lib_paths: &LibPathRules) -> Result<RemoteLedger, SError> {
+    match fs::read(&lib_paths.remote_ledger) {
+        Ok(bytes) => MsgPack::from_slice(&bytes),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(RemoteLedger::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write(lib_paths: &LibPathRules, ledger: &RemoteLedger) -> Result<(), SError> {
+    fs::write(&lib_paths.remote_ledger, MsgPack::to_vec(ledger)?)?;
+    Ok(())
+}
+
+fn target(library: &Library) -> Result<&Utf8Path, SError> {
+    let remote = library
+        .remote_server
+        .as_deref()
+        .ok_or(SError::RemoteNotConfigured)?;
+    ensure_reachable(remote)?;
+    Ok(remote)
+}
+
+fn ensure_reachable(remote: &Utf8Path) -> Result<(), SError> {
+    match remote.is_dir() {
+        true => Ok(()),
+        false => Err(SError::RemoteUnreachable(remote.to_string())),
+    }
+}
+
+fn status_of(remote: &Utf8Path, ledger: &RemoteLedger) -> RemoteStatus {
+    RemoteStatus {
+        path: remote.to_path_buf(),
+        reachable: remote.is_dir(),
+        files: ledger.files.len() as u32,
+        last_push: ledger.last_push.clone(),
+    }
+}
+
+/// Server files of the active mods, relative to the server mods folder -> source in the repo.
+fn server_files(library: &Library) -> BTreeMap<Utf8PathBuf, Utf8PathBuf> {
+    let server_mods = &library.spt_rules.server_mods;
+    let store = repo_store::open(&library.lib_paths);
+    library
+        .mods
+        .values()
+        .filter(|m| m.is_active)
+        .filter_map(|m| library.cache.mods.get(&m.id))
+        .flat_map(|fs| {
+            let root = store.payload_dir(&fs.id);
+            fs.files
+                .iter()
+                .filter(|rel| SyncScope::ServerOnly.covers(rel, &library.spt_rules))
+                .filter_map(move |rel| {
+                    let remote_rel = rel.strip_prefix(server_mods).ok()?;
+                    let src = root.join(rel);
+                    Some((remote_rel.to_path_buf(), src))
+                })
+        })
+        .collect()
+}
+
+fn copy_files(
+    remote: &Utf8Path,
+    files: &BTreeMap<Utf8PathBuf, Utf8PathBuf>,
+    ledger: &mut RemoteLedger,
+) -> Result<(), SError> {
+    let stale: Vec<Utf8PathBuf> = ledger
+        .files
+        .keys()
+        .filter(|rel| !files.contains_key(*rel))
+        .cloned()
+        .collect();
+    for rel in stale {
+        remove_file(remote, &rel)?;
+        ledger.files.remove(&rel);
+    }
+
+    for (rel, src) in files {
+        let digest = digest(src)?;
+        let dst = remote.join(rel);
+        if ledger.files.get(rel) == Some(&digest) && dst.is_file() {
+            continue;
+        }
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(src, &dst)?;
+        ledger.files.insert(rel.clone(), digest);
+    }
+    Ok(())
+}
+
+fn purge_from(remote: &Utf8Path, lib_paths: &LibPathRules) -> Result<(), SError> {
+    let mut ledger = read(lib_paths)?;
+    let result = copy_files(remote, &BTreeMap::new(), &mut ledger);
+    ledger.last_push = None;
+    write(lib_paths, &ledger)?;
+    result
+}
+
+/// Removes a pushed file and the folders it leaves empty, up to the remote mods folder.
+fn remove_file(remote: &Utf8Path, rel: &Utf8Path) -> Result<(), SError> {
+    fs::remove_file(remote.join(rel)).or_else(|e| match e.kind() {
+        io::ErrorKind::NotFound => Ok(()),
+        _ => Err(e),
+    })?;
+
+    // Stops at the first folder still holding something
+    let _ = rel
+        .ancestors()
+        .skip(1)
+        .filter(|dir| !dir.as_str().is_empty())
+        .try_for_each(|dir| fs::remove_dir(remote.join(dir)));
+    Ok(())
+}
+
+fn digest(path: &Utf8Path) -> Result<String, SError> {
+    let mut hasher = blake3::Hasher::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
@@ -0,0 +1,205 @@
+use crate::core::library::Library;
+use crate::models::error::SError;
+use crate::models::repo_history::RepoCommit;
+use camino::Utf8Path;
+use std::process::Command;
+use tracing::{info, warn};
+
+/// Everything describing the game root or in flight stays out of the history, so checking
+/// out an older state never rewinds what is actually deployed.
+const GITIGNORE: &str = "\
+staging/
+quarantine/
+backups/
+profile-backups/
+plans/
+logs/
+.history/
+cache.building/
+cache.replaced/
+sync-index.msgpack
+last-sync-plan.toml
+deploy-ledger.msgpack
+remote-ledger.msgpack
+journal.jsonl
+library.lock
+cache-export.toml
+*.tmp
+";
+/// Field separator of the `git log` format, never found in commit subjects.
+const SEPARATOR: char = '\u{1f}';
+
+/// Whether the repo is tracked with git, see `enable`.
+pub fn is_enabled(repo_root: &Utf8Path) -> bool {
+    repo_root.join(".git").is_dir()
+}
+
+/// Starts tracking the repo with git, committing its current state.
+/// Requires `git` on the PATH; does nothing when already enabled.
+pub fn enable(repo_root: &Utf8Path) -> Result<(), SError> {
+    if is_enabled(repo_root) {
+        return Ok(());
+    }
+    git(repo_root, &["init", "--quiet"])?;
+    std::fs::write(repo_root.join(".gitignore"), GITIGNORE)?;
+    commit(repo_root, "Start tracking the library")?;
+    Ok(())
+}
+
+/// Commits the repo after an operation when history is enabled.
+/// History is a convenience on top of backups, so a failure is only logged.
+pub fn record(repo_root: &Utf8Path, message: &str) {
+    if !is_enabled(repo_root) {
+        return;
+    }
+    if let Err(e) = commit(repo_root, message) {
+        warn!("Failed to record \"{message}\" in the repo history: {e}");
+    }
+}
+
+/// The latest `limit` commits, newest first; empty when history is not enabled.
+pub fn history(repo_root: &Utf8Path, limit: u32) -> Result<Vec<RepoCommit>, SError> {
+    if !is_enabled(repo_root) {
+        return Ok(Vec::new());
+    }
+    let format = format!("--format=%H{SEPARATOR}%ct{SEPARATOR}%s");
+    let count = format!("--max-count={limit}");
+    let log = git(repo_root, &["log", &format, &count])?;
+
+    Ok(log
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, SEPARATOR);
+            Some(RepoCommit {
+                id: fields.next()?.to_string(),
+                timestamp: fields.next()?.to_string(),
+                message: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Brings the mods, manifest and cache back to a recorded state and reloads the library.
+/// The result is committed as a new state, keeping every later one in the history.
+/// The deployment is left as it is and the library marked dirty until the next sync.
+pub fn checkout(library: &mut Library, commit_id: &str) -> Result<(), SError> {
+    let repo_root = library.repo_root.clone();
+    if !is_enabled(&repo_root) {
+        return Err(SError::GitFailed("History is not enabled".to_string()));
+    }
+
+    let revision = format!("{commit_id}^{{commit}}");
+    let id = git(&repo_root, &["rev-parse", "--verify", "--quiet", &revision])?;
+    let id = id.trim();
+    let source = format!("--source={id}");
+    // Older states may still track what is ignored now, which must not be rewound
+    let excluded: Vec<String> = GITIGNORE
+        .lines()
+        .chain([".gitignore"])
+        .map(|entry| format!(":(exclude){}", entry.trim_end_matches('/')))
+        .collect();
+    let mut args = vec!["restore", &source, "--staged", "--worktree", "--", "."];
+    args.extend(excluded.iter().map(String::as_str));
+    git(&repo_root, &args)?;
+
+    library.reload()?;
+    library.mark_dirty();
+    library.persist_manifest()?;
+    info!("Checked out repo state {id}");
+    commit(
+        &repo_root,
+        &format!("Restore state {}", &id[..id.len().min(8)]),
+    )?;
+    Ok(())
+}
+
+/// Stages everything and commits it; returns false when nothing changed.
+fn commit(repo_root: &Utf8Path, message: &str) -> Result<bool, SError> {
+    update_gitignore(repo_root)?;
+    git(repo_root, &["add", "--all"])?;
+    if git(repo_root, &["status", "--porcelain"])?
+        .trim()
+        .is_empty()
+    {
+        return Ok(false);
+    }
+    // The identity is set per call so commits work without any git configuration
+    git(
+        repo_root,
+        &[
+            "-c",
+            "user.name=Mod Keeper",
+            "-c",
+            "user.email=mod-keeper@localhost",
+            "commit",
+            "--quiet",
+            "--message",
+            message,
+        ],
+    )?;
+    Ok(true)
+}
+
+/// Adds the entries of `GITIGNORE` missing from the `.gitignore` of a repo enabled by an
+/// earlier version, and stops tracking what they cover.
+fn update_gitignore(repo_root: &Utf8Path) -> Result<(), SError> {
+    let path = repo_root.join(".gitignore");
+    let mut content = std::fs::read_to_string(&path).unwrap_or_default();
+    let missing: Vec<&str> = GITIGNORE
+        .lines()
+        .filter(|entry| !content.lines().any(|line| line.trim() == *entry))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    missing.iter().for_each(|entry| {
+        content.push_str(entry);
+        content.push('\n');
+    });
+    std::fs::write(&path, content)?;
+    for entry in missing {
+        let pathspec = entry.trim_end_matches('/');
+        git(
+            repo_root,
+            &[
+                "rm",
+                "-r",
+                "--cached",
+                "--quiet",
+                "--ignore-unmatch",
+                "--",
+                pathspec,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Runs git in the repo and returns its standard output.
+fn git(repo_root: &Utf8Path, args: &[&str]) -> Result<String, SError> {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(repo_root).args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| SError::GitFailed(format!("Unable to run git: {e}")))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(SError::GitFailed(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
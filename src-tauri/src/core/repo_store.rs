@@ -0,0 +1,185 @@
+use crate::core::progress::Progress;
+use crate::models::error::SError;
+use crate::models::paths::LibPathRules;
+use crate::utils::file::FileUtils;
+use crate::utils::retry;
+use camino::{Utf8Path, Utf8PathBuf};
+use std::collections::BTreeSet;
+use std::fs;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Staging folders `put` builds a payload in.
+const PROMOTE_PREFIX: &str = "promote-";
+/// Staging folders `put` moves the previous payload to, as `replaced-{uuid}/{mod_id}`.
+const REPLACED_PREFIX: &str = "replaced-";
+
+/// Where the payloads of the mods are kept in the repo.
+/// Deployment links into `payload_dir`, so every store keeps each mod available as a plain
+/// folder there; a deduplicating or compressed store would treat it as the working copy.
+pub trait RepoStore: Send + Sync {
+    /// Folder holding every payload. Links into it are ours.
+    fn root(&self) -> &Utf8Path;
+
+    /// Folder holding the payload of a mod, whether it exists or not.
+    fn payload_dir(&self, mod_id: &str) -> Utf8PathBuf {
+        self.root().join(mod_id)
+    }
+
+    fn contains(&self, mod_id: &str) -> bool {
+        self.payload_dir(mod_id).exists()
+    }
+
+    /// Ids of the mods with a payload in the store, whether the library knows them or not.
+    fn ids(&self) -> Result<BTreeSet<String>, SError> {
+        if !self.root().is_dir() {
+            return Ok(BTreeSet::new());
+        }
+        Ok(self
+            .root()
+            .read_dir_utf8()?
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string())
+            .collect())
+    }
+
+    /// Replaces the payload of a mod with a copy of `source`, e.g. a staged mod or a backup.
+    /// The previous payload stays in place when this fails.
+    fn put(
+        &self,
+        mod_id: &str,
+        source: &Utf8Path,
+        progress: Progress,
+        name: &str,
+    ) -> Result<(), SError>;
+
+    /// Copies the payload of a mod into `dst`, e.g. to back it up.
+    fn export(&self, mod_id: &str, dst: &Utf8Path) -> Result<(), SError>;
+
+    /// Removes the payload of a mod. A missing one is not an error.
+    fn remove(&self, mod_id: &str) -> Result<(), SError>;
+
+    /// Finishes or undoes a `put` interrupted by a crash, so every payload is in place again.
+    fn recover(&self) -> Result<(), SError>;
+}
+
+/// The store of a library. Payloads are plain folders in `mods/` today.
+pub fn open(lib_paths: &LibPathRules) -> Box<dyn RepoStore> {
+    Box::new(LocalDirStore {
+        root: lib_paths.mods.clone(),
+        staging: lib_paths.staging.clone(),
+    })
+}
+
+/// Recovers the store of a library when it is opened, once it is locked.
+/// A failure is only logged; the consistency check reports what is left in staging.
+pub fn recover(lib_paths: &LibPathRules) {
+    if let Err(e) = open(lib_paths).recover() {
+        warn!("Failed to recover the repo store: {e}");
+    }
+}
+
+/// One folder per mod, as installed.
+pub struct LocalDirStore {
+    root: Utf8PathBuf,
+    /// Same volume as the root, so payloads are built there and renamed into place.
+    staging: Utf8PathBuf,
+}
+
+impl RepoStore for LocalDirStore {
+    fn root(&self) -> &Utf8Path {
+        &self.root
+    }
+
+    /// Never exposes a half-populated directory: the full tree is built in staging and renamed
+    /// into place; a pre-existing directory is swapped out first and only removed once the new
+    /// one is in.
+    fn put(
+        &self,
+        mod_id: &str,
+        source: &Utf8Path,
+        progress: Progress,
+        name: &str,
+    ) -> Result<(), SError> {
+        let dst = self.payload_dir(mod_id);
+        let building = self
+            .staging
+            .join(format!("{PROMOTE_PREFIX}{}", Uuid::new_v4()));
+        FileUtils::copy_recursive_reported(source, &building, progress, name)
+            .inspect_err(|_| discard(&building))?;
+
+        // The previous payload keeps its name, so `recover` knows where it goes back to
+        let replaced = dst.exists().then(|| {
+            self.staging
+                .join(format!("{REPLACED_PREFIX}{}", Uuid::new_v4()))
+        });
+        if let Some(replaced) = &replaced {
+            fs::create_dir_all(replaced).inspect_err(|_| discard(&building))?;
+            let previous = replaced.join(mod_id);
+            retry::io("Moving", &dst, || fs::rename(&dst, &previous))
+                .inspect_err(|_| discard(&building))?;
+        }
+
+        if let Err(e) = retry::io("Moving", &building, || fs::rename(&building, &dst)) {
+            if let Some(replaced) = &replaced {
+                // Put the previous version back so the mod stays installed
+                fs::rename(replaced.join(mod_id), &dst)?;
+                discard(replaced);
+            }
+            discard(&building);
+            return Err(e.into());
+        }
+
+        replaced.map_or(Ok(()), |replaced| FileUtils::remove_recursive(&replaced))
+    }
+
+    fn export(&self, mod_id: &str, dst: &Utf8Path) -> Result<(), SError> {
+        fs::create_dir_all(dst)?;
+        FileUtils::copy_recursive(&self.payload_dir(mod_id), dst)?;
+        Ok(())
+    }
+
+    fn remove(&self, mod_id: &str) -> Result<(), SError> {
+        let dir = self.payload_dir(mod_id);
+        match dir.exists() {
+            true => FileUtils::remove_recursive(&dir),
+            false => Ok(()),
+        }
+    }
+
+    /// Moves a previous payload back when `put` stopped before the new one was in place, and
+    /// drops payloads that were still being built.
+    fn recover(&self) -> Result<(), SError> {
+        if !self.staging.is_dir() {
+            return Ok(());
+        }
+        for entry in self.staging.read_dir_utf8()? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if name.starts_with(PROMOTE_PREFIX) {
+                FileUtils::remove_recursive(entry.path())?;
+            } else if name.starts_with(REPLACED_PREFIX) {
+                for previous in entry.path().read_dir_utf8()? {
+                    let previous = previous?;
+                    let dst = self.payload_dir(previous.file_name());
+                    if !dst.exists() {
+                        fs::rename(previous.path(), &dst)?;
+                        info!(
+                            "Restored the payload of {} after an interrupted update",
+                            previous.file_name()
+                        );
+                    }
+                }
+                FileUtils::remove_recursive(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn discard(dir: &Utf8Path) {
+    if let Err(e) = fs::remove_dir_all(dir) {
+        warn!("Failed to remove {dir}: {e}");
+    }
+}
@@ -0,0 +1,191 @@
+use crate::core::library::Library;
+use crate::models::error::SError;
+use crate::models::mod_dto::Author;
+use crate::models::paths::LibPathRules;
+use crate::utils::msgpack::MsgPack;
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use tracing::warn;
+
+/// Lookup tables for searching the library, persisted so the first search after startup does
+/// not rebuild them from the cache, see `file_search`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SearchIndex {
+    /// Mod id -> what its entries were built from, to spot mods changed behind `refresh`.
+    pub mods: BTreeMap<String, IndexedMod>,
+    /// Lower-cased file name -> (mod id, path) of every file with that name.
+    pub names: BTreeMap<String, Vec<(String, Utf8PathBuf)>>,
+    /// Lower-cased word of a mod name, author or description -> mod ids.
+    pub terms: BTreeMap<String, BTreeSet<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct IndexedMod {
+    pub name: String,
+    pub files: usize,
+}
+
+impl SearchIndex {
+    pub fn build(library: &Library) -> Self {
+        let mut index = Self::default();
+        library
+            .cache
+            .mods
+            .keys()
+            .for_each(|id| index.insert(library, id));
+        index
+    }
+
+    fn insert(&mut self, library: &Library, id: &str) {
+        let (Some(m), Some(mod_fs)) = (library.mods.get(id), library.cache.mods.get(id)) else {
+            return;
+        };
+        self.mods.insert(id.to_string(), indexed(library, id));
+
+        for file in &mod_fs.files {
+            let Some(name) = file.file_name() else {
+                continue;
+            };
+            self.names
+                .entry(name.to_lowercase())
+                .or_default()
+                .push((id.to_string(), file.clone()));
+        }
+
+        let manifest = library.cache.manifests.get(id);
+        let authors = manifest.map_or(Vec::new(), |m| match &m.author {
+            Author::Single(author) => vec![author.as_str()],
+            Author::Multiple(authors) => authors.iter().map(String::as_str).collect(),
+        });
+        let text = [m.name.as_str()]
+            .into_iter()
+            .chain(manifest.map(|m| m.name.as_str()))
+            .chain(manifest.and_then(|m| m.description.as_deref()))
+            .chain(authors);
+        for term in text.flat_map(words) {
+            self.terms.entry(term).or_default().insert(id.to_string());
+        }
+    }
+
+    fn remove(&mut self, id: &str) {
+        self.mods.remove(id);
+        self.names.retain(|_, files| {
+            files.retain(|(owner, _)| owner != id);
+            !files.is_empty()
+        });
+        self.terms.retain(|_, ids| {
+            ids.remove(id);
+            !ids.is_empty()
+        });
+    }
+
+    /// Mods added, removed or changed since they were indexed.
+    fn stale(&self, library: &Library) -> BTreeSet<String> {
+        let current: BTreeMap<&str, IndexedMod> = library
+            .cache
+            .mods
+            .keys()
+            .filter(|id| library.mods.contains_key(*id))
+            .map(|id| (id.as_str(), indexed(library, id)))
+            .collect();
+        let changed = current
+            .iter()
+            .filter(|(id, m)| self.mods.get(**id) != Some(m))
+            .map(|(id, _)| id.to_string());
+        let removed = self
+            .mods
+            .keys()
+            .filter(|id| !current.contains_key(id.as_str()))
+            .cloned();
+        changed.chain(removed).collect()
+    }
+}
+
+fn indexed(library: &Library, id: &str) -> IndexedMod {
+    IndexedMod {
+        name: library
+            .mods
+            .get(id)
+            .map(|m| m.name.clone())
+            .unwrap_or_default(),
+        files: library.cache.mods.get(id).map_or(0, |fs| fs.files.len()),
+    }
+}
+
+/// Lower-cased words of a text, split on anything but letters and digits.
+pub fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Runs `f` on the index of the library. The first call reads it from disk and re-indexes the
+/// mods that changed since it was written; without a readable one, it is built from the cache.
+pub fn with_index<R>(library: &Library, f: impl FnOnce(&SearchIndex) -> R) -> R {
+    let mut loaded = library.search_index.borrow_mut();
+    let index = loaded.get_or_insert_with(|| warm_start(library));
+    f(index)
+}
+
+fn warm_start(library: &Library) -> SearchIndex {
+    let Some(mut index) = read(&library.lib_paths) else {
+        let index = SearchIndex::build(library);
+        persist(library, &index);
+        return index;
+    };
+
+    let stale = index.stale(library);
+    if stale.is_empty() {
+        return index;
+    }
+    for id in &stale {
+        index.remove(id);
+        index.insert(library, id);
+    }
+    persist(library, &index);
+    index
+}
+
+/// Builds the index again from scratch, e.g. after the cache was rebuilt.
+pub fn rebuild(library: &Library) {
+    let index = SearchIndex::build(library);
+    persist(library, &index);
+    library.search_index.replace(Some(index));
+}
+
+/// Re-indexes a mod after it was added, removed or restored.
+/// An index not loaded yet is left to catch up on its first use.
+pub fn refresh(library: &Library, id: &str) {
+    let mut loaded = library.search_index.borrow_mut();
+    let Some(index) = loaded.as_mut() else {
+        return;
+    };
+    index.remove(id);
+    index.insert(library, id);
+    persist(library, index);
+}
+
+/// Reads the index, `None` when missing or unreadable.
+pub fn read(lib_paths: &LibPathRules) -> Option<SearchIndex> {
+    let bytes = fs::read(&lib_paths.search_index).ok()?;
+    MsgPack::from_slice(&bytes)
+        .inspect_err(|e| warn!("Ignoring unreadable search index: {e}"))
+        .ok()
+}
+
+pub fn write(lib_paths: &LibPathRules, index: &SearchIndex) -> Result<(), SError> {
+    fs::write(&lib_paths.search_index, MsgPack::to_vec(index)?)?;
+    Ok(())
+}
+
+/// A missing index is rebuilt on the next start, so failing to write it is not fatal.
+fn persist(library: &Library, index: &SearchIndex) {
+    if !library.is_writable {
+        return;
+    }
+    if let Err(e) = write(&library.lib_paths, index) {
+        warn!("Failed to persist the search index: {e}");
+    }
+}
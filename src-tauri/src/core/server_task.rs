@@ -0,0 +1,118 @@
+use crate::core::dedicated_server;
+use crate::core::library::Library;
+use crate::core::sync_index::{self, SyncIndex};
+use crate::models::error::SError;
+use crate::models::server_task::ServerTask;
+use camino::Utf8Path;
+use std::process::Command;
+
+/// Longest command `schtasks /TR` accepts for a task.
+const MAX_ACTION_LEN: usize = 261;
+
+/// Name of the scheduled task of a library; the id keeps tasks of several libraries apart.
+pub fn task_name(library: &Library) -> String {
+    format!("Mod Keeper SPT Server {}", library.id)
+}
+
+/// Fails unless the last sync deployed exactly the active mods. The dirty flag does not
+/// outlive the app, so the index of the last sync is compared instead.
+pub fn ensure_deployed(library: &Library) -> Result<(), SError> {
+    let current = SyncIndex::build(&dedicated_server::deployable_mods(library), &library.cache);
+    match sync_index::read(&library.lib_paths) {
+        Some(index) if index == current => Ok(()),
+        _ => Err(SError::LibraryNotSynced),
+    }
+}
+
+/// Registers a task that starts the server when the user logs on, through `exe --serve`,
+/// so the deployment is checked again before every start. Replaces an earlier task.
+pub fn install(library: &Library, exe: &Utf8Path) -> Result<ServerTask, SError> {
+    ensure_deployed(library)?;
+    schtasks(&create_args(library, exe)?)?;
+    status(library)
+}
+
+pub fn remove(library: &Library) -> Result<ServerTask, SError> {
+    schtasks(&["/Delete", "/F", "/TN", &task_name(library)])?;
+    status(library)
+}
+
+pub fn status(library: &Library) -> Result<ServerTask, SError> {
+    let name = task_name(library);
+    Ok(ServerTask {
+        installed: schtasks(&["/Query", "/TN", &name]).is_ok(),
+        name,
+    })
+}
+
+/// Arguments of `schtasks` creating the task of the library. Fails up front when the command
+/// of the task is longer than `schtasks` accepts.
+pub fn create_args(library: &Library, exe: &Utf8Path) -> Result<Vec<String>, SError> {
+    // A trailing backslash would escape the closing quote; a drive root keeps a forward slash
+    let mut repo_root = library
+        .repo_root
+        .as_str()
+        .trim_end_matches(['/', '\\'])
+        .to_string();
+    if repo_root.ends_with(':') {
+        repo_root.push('/');
+    }
+    let action = format!("\"{exe}\" --serve \"{repo_root}\"");
+    let length = action.chars().count();
+    if length > MAX_ACTION_LEN {
+        return Err(SError::ServerTaskFailed(format!(
+            "The command of the task is {length} characters long, schtasks accepts at most \
+             {MAX_ACTION_LEN}; move the app or the library to a shorter path"
+        )));
+    }
+
+    Ok(["/Create", "/F", "/SC", "ONLOGON", "/TN"]
+        .into_iter()
+        .map(str::to_string)
+        .chain([task_name(library), "/TR".to_string(), action])
+        .collect())
+}
+
+/// Starts the server of a synced library from its folder and waits until it exits.
+pub fn serve(library: &Library) -> Result<(), SError> {
+    ensure_deployed(library)?;
+
+    let server_exe = library.game_root.join(&library.spt_rules.server_exe);
+    let mut command = Command::new(&server_exe);
+    if let Some(dir) = server_exe.parent() {
+        command.current_dir(dir);
+    }
+    let status = command
+        .status()
+        .map_err(|e| SError::ServerTaskFailed(format!("Unable to start {server_exe}: {e}")))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(SError::ServerTaskFailed(format!(
+            "The server exited with {status}"
+        ))),
+    }
+}
+
+#[cfg(windows)]
+fn schtasks<S: AsRef<std::ffi::OsStr>>(args: &[S]) -> Result<(), SError> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = Command::new("schtasks")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| SError::ServerTaskFailed(format!("Unable to run schtasks: {e}")))?;
+    match output.status.success() {
+        true => Ok(()),
+        false => Err(SError::ServerTaskFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+    }
+}
+
+/// Scheduled tasks only exist on Windows.
+#[cfg(not(windows))]
+fn schtasks<S: AsRef<std::ffi::OsStr>>(_args: &[S]) -> Result<(), SError> {
+    Err(SError::UnsupportedPlatform)
+}
@@ -0,0 +1,123 @@
+use crate::config::global::GlobalConfig;
+use crate::core::consistency;
+use crate::core::library::Library;
+use crate::models::error::SError;
+use crate::utils::thread::{with_lib_arc, with_lib_arc_mut};
+use parking_lot::Mutex;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+thread_local! {
+    /// Set while the current thread holds the instance lock.
+    static INSTANCE_HELD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Single access point to the global config and the active library.
+/// Lock order is always config -> instance: `both` takes them in that order, and taking the
+/// config while this thread holds the instance panics in debug builds instead of deadlocking
+/// under contention later.
+#[derive(Clone)]
+pub struct SharedState {
+    config: Arc<Mutex<GlobalConfig>>,
+    // Arc<Mutex<Option>> allows us to "swap" the entire instance safely
+    instance: Arc<Mutex<Option<Library>>>,
+    /// Operations waiting for or holding the instance, so background work can make way.
+    demand: Arc<AtomicUsize>,
+}
+
+impl SharedState {
+    pub fn new(config: GlobalConfig, instance: Option<Library>) -> Self {
+        Self {
+            config: Arc::new(Mutex::new(config)),
+            instance: Arc::new(Mutex::new(instance)),
+            demand: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn config<R>(&self, f: impl FnOnce(&mut GlobalConfig) -> R) -> R {
+        debug_assert!(
+            !INSTANCE_HELD.get(),
+            "global config must be locked before the active instance"
+        );
+        f(&mut self.config.lock())
+    }
+
+    pub fn instance<R>(&self, f: impl FnOnce(&mut Option<Library>) -> R) -> R {
+        let _demand = Demand::register(&self.demand);
+        let _held = HeldMarker::mark();
+        f(&mut self.instance.lock())
+    }
+
+    pub fn both<R>(&self, f: impl FnOnce(&mut GlobalConfig, &mut Option<Library>) -> R) -> R {
+        self.config(|config| self.instance(|instance| f(config, instance)))
+    }
+
+    /// Runs `f` on the active library once its deferred load has finished.
+    pub fn with_lib<R>(&self, f: impl FnOnce(&Library) -> R) -> Result<R, SError> {
+        let _demand = Demand::register(&self.demand);
+        let _held = HeldMarker::mark();
+        with_lib_arc(self.instance.clone(), f)
+    }
+
+    /// Like `with_lib`, for changes; the result is checked by `consistency::after_mutation`.
+    pub fn with_lib_mut<R>(&self, f: impl FnOnce(&mut Library) -> R) -> Result<R, SError> {
+        let _demand = Demand::register(&self.demand);
+        let _held = HeldMarker::mark();
+        with_lib_arc_mut(self.instance.clone(), |lib| {
+            let result = f(lib);
+            consistency::after_mutation(lib);
+            result
+        })
+    }
+
+    /// Whether an operation waits for or holds the active library.
+    pub fn is_in_demand(&self) -> bool {
+        self.demand.load(Ordering::Acquire) > 0
+    }
+
+    /// Runs `f` on the active library for background work, but only when nothing else wants
+    /// it: `None` when the lock is in demand or taken, or the library is not loaded or writable.
+    /// Never finishes a deferred load, which is left to the operations of the user.
+    pub fn try_with_lib_mut<R>(&self, f: impl FnOnce(&mut Library) -> R) -> Option<R> {
+        if self.is_in_demand() {
+            return None;
+        }
+        let mut guard = self.instance.try_lock()?;
+        let lib = guard
+            .as_mut()
+            .filter(|lib| lib.is_loaded() && !lib.is_read_only())?;
+        let _held = HeldMarker::mark();
+        Some(f(lib))
+    }
+}
+
+struct Demand<'a>(&'a AtomicUsize);
+
+impl<'a> Demand<'a> {
+    fn register(demand: &'a AtomicUsize) -> Self {
+        demand.fetch_add(1, Ordering::AcqRel);
+        Self(demand)
+    }
+}
+
+impl Drop for Demand<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+struct HeldMarker;
+
+impl HeldMarker {
+    fn mark() -> Self {
+        INSTANCE_HELD.set(true);
+        Self
+    }
+}
+
+impl Drop for HeldMarker {
+    fn drop(&mut self) {
+        INSTANCE_HELD.set(false);
+    }
+}
@@ -0,0 +1,40 @@
+use crate::core::mod_stager::{StageMaterial, StagedMod};
+use crate::core::progress::Progress;
+use crate::models::error::SError;
+use camino::Utf8Path;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// Turns one user input into a mod ready for installation, see `mod_stager::resolve`.
+/// Handlers are tried in turn until one claims the input, the built-in ones first.
+pub trait StagingHandler: Send + Sync {
+    /// Short name shown in logs.
+    fn name(&self) -> &str;
+
+    /// Returns:
+    /// - Some(Ok): The input was staged.
+    /// - Some(Err): The input is meant for this handler but could not be staged.
+    /// - None: Not an input for this handler, the next one is tried.
+    ///
+    /// Content written under `material.root` must be reported with `is_staging`, so it is
+    /// removed once installed.
+    fn stage(
+        &self,
+        input: &Utf8Path,
+        material: &StageMaterial,
+        progress: Progress,
+    ) -> Option<Result<StagedMod, SError>>;
+}
+
+static REGISTERED: RwLock<Vec<Arc<dyn StagingHandler>>> = RwLock::new(Vec::new());
+
+/// Adds a handler for inputs the built-in ones do not recognise, e.g. other bundle formats.
+/// Meant to be called at startup; handlers are tried in registration order.
+pub fn register(handler: Arc<dyn StagingHandler>) {
+    REGISTERED.write().push(handler);
+}
+
+/// Handlers added with `register`.
+pub(crate) fn registered() -> Vec<Arc<dyn StagingHandler>> {
+    REGISTERED.read().clone()
+}
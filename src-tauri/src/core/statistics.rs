@@ -0,0 +1,68 @@
+use crate::core::library::Library;
+use crate::core::linker;
+use crate::core::repo_store;
+use crate::models::statistics::{LibraryStatistics, ModStatistics};
+use camino::Utf8Path;
+use file_id::FileId;
+use std::collections::HashSet;
+
+/// Computes per-mod disk usage, comparing the repo payload with what is deployed in the game root.
+/// Files sharing a physical ID (hardlinks, or paths resolved through junctions/symlinks)
+/// are counted once in `actual_bytes`, so linked deployments cost no extra space.
+pub fn compute(library: &Library) -> LibraryStatistics {
+    let mut seen: HashSet<FileId> = HashSet::new();
+    let mut stats = LibraryStatistics::default();
+    let store = repo_store::open(&library.lib_paths);
+
+    for (id, m_fs) in &library.cache.mods {
+        let is_active = library.mods.get(id).is_some_and(|m| m.is_active);
+        let repo_root = store.payload_dir(id);
+        let mut entry = ModStatistics {
+            mod_id: id.clone(),
+            ..Default::default()
+        };
+
+        for file in &m_fs.files {
+            let repo_file = repo_root.join(file);
+            let repo_id = linker::get_id(&repo_file).ok();
+            let repo_size = file_size(&repo_file).unwrap_or(0);
+            entry.repo_bytes += repo_size;
+            stats.actual_bytes += count_once(&mut seen, repo_id, repo_size);
+
+            // Inactive mods are not deployed; anything at their paths belongs to someone else
+            if !is_active {
+                continue;
+            }
+
+            let game_file = library.game_root.join(file);
+            let Some(deployed_size) = file_size(&game_file) else {
+                continue;
+            };
+            let game_id = linker::get_id(&game_file).ok();
+
+            entry.deployed_bytes += deployed_size;
+            if game_id.is_none() || game_id != repo_id {
+                entry.duplicated_bytes += deployed_size;
+            }
+            stats.actual_bytes += count_once(&mut seen, game_id, deployed_size);
+        }
+
+        stats.apparent_bytes += entry.repo_bytes + entry.deployed_bytes;
+        stats.mods.push(entry);
+    }
+
+    stats
+}
+
+fn file_size(path: &Utf8Path) -> Option<u64> {
+    std::fs::metadata(path).ok().map(|m| m.len())
+}
+
+/// Returns the size if this physical file has not been counted yet.
+/// Files without a resolvable ID are always counted.
+fn count_once(seen: &mut HashSet<FileId>, id: Option<FileId>, size: u64) -> u64 {
+    match id {
+        Some(id) if !seen.insert(id) => 0,
+        _ => size,
+    }
+}
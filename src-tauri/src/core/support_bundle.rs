@@ -0,0 +1,142 @@
+use crate::core::library::Library;
+use crate::core::mod_fs::{CanonicalId, ModFS};
+use crate::core::{dedicated_server, deployment, file_overrides, plan_store, profiles, volume};
+use crate::models::deployment_plan::DeploymentPlan;
+use crate::models::error::SError;
+use crate::models::mod_dto::{Dependencies, ModManifest};
+use crate::models::profile::ModProfile;
+use crate::models::volume::VolumeStatus;
+use crate::utils::time::get_unix_timestamp;
+use crate::utils::toml::Toml;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+const SNAPSHOT_NAME: &str = "snapshot.json";
+const PLAN_NAME: &str = "plan.toml";
+
+/// Library state needed to reproduce a bug report.
+#[derive(Serialize, Debug)]
+pub struct SupportSnapshot {
+    pub library_id: String,
+    pub library_name: String,
+    pub spt_version: String,
+    pub app_version: String,
+    /// Drives of the game root and the repo; links into removable or network drives break.
+    pub volumes: VolumeStatus,
+    /// Mods in deployment order with their activation state.
+    pub mods: Vec<ModState>,
+    /// Declared dependency ids of every mod, including unresolved ones.
+    pub dependency_graph: BTreeMap<String, Vec<String>>,
+    pub active_profile: Option<String>,
+    /// Activation set of every profile, the active one as it stands right now.
+    pub profiles: BTreeMap<String, ModProfile>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ModState {
+    pub id: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub is_active: bool,
+    /// How a path-based id was derived, see `ModFS::canonical_path_id`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canonical_id: Option<CanonicalId>,
+}
+
+/// Writes a support bundle zip with the library snapshot and the last sync plan.
+/// The plan kept by the last successful sync is used; before any sync, the plan for the
+/// current state is computed.
+/// `output` may be a directory, in which case the file is named `support-{timestamp}.zip`.
+pub fn write(library: &Library, output: &Utf8Path) -> Result<Utf8PathBuf, SError> {
+    let snapshot = snapshot(library);
+    let plan = last_plan(library)?;
+
+    let archive_path = if output.is_dir() {
+        output.join(format!("support-{}.zip", get_unix_timestamp()))
+    } else {
+        output.to_path_buf()
+    };
+
+    let mut zip = ZipWriter::new(File::create(&archive_path)?);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    zip.start_file(SNAPSHOT_NAME, options)?;
+    zip.write_all(serde_json::to_string_pretty(&snapshot)?.as_bytes())?;
+    zip.start_file(PLAN_NAME, options)?;
+    zip.write_all(Toml::to_string(&plan)?.as_bytes())?;
+
+    zip.finish()?;
+    Ok(archive_path)
+}
+
+pub fn snapshot(library: &Library) -> SupportSnapshot {
+    let manifests = &library.cache.manifests;
+
+    SupportSnapshot {
+        library_id: library.id.clone(),
+        library_name: library.name.clone(),
+        spt_version: library.spt_version.clone(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        volumes: volume::status(library),
+        mods: library
+            .mods
+            .values()
+            .map(|m| ModState {
+                id: m.id.clone(),
+                name: m.name.clone(),
+                version: manifests
+                    .get(&m.id)
+                    .map(|manifest| manifest.version.clone()),
+                is_active: m.is_active,
+                canonical_id: canonical_id(library, &m.id),
+            })
+            .collect(),
+        dependency_graph: library
+            .mods
+            .keys()
+            .map(|id| (id.clone(), dependency_ids(manifests.get(id))))
+            .collect(),
+        active_profile: library.active_profile.clone(),
+        profiles: profiles::snapshot(library),
+    }
+}
+
+/// The stored canonical id, or for mods cached before it was kept, the scheme matching the id.
+fn canonical_id(library: &Library, id: &str) -> Option<CanonicalId> {
+    let fs = library.cache.mods.get(id)?;
+    if library.cache.manifests.contains_key(id) {
+        return None;
+    }
+    fs.canonical_id.clone().or_else(|| {
+        ModFS::canonical_path_ids(&library.spt_rules, &fs.files)
+            .ok()?
+            .into_iter()
+            .find(|canonical| canonical.hash() == id)
+    })
+}
+
+fn dependency_ids(manifest: Option<&ModManifest>) -> Vec<String> {
+    match manifest.and_then(|m| m.dependencies.as_ref()) {
+        Some(Dependencies::Object(deps)) => deps.keys().cloned().collect(),
+        Some(Dependencies::Array(deps)) => deps.iter().map(|d| d.id.clone()).collect(),
+        None => Vec::new(),
+    }
+}
+
+fn last_plan(library: &Library) -> Result<DeploymentPlan, SError> {
+    match plan_store::last_sync(&library.lib_paths)? {
+        Some(plan) => Ok(plan),
+        None => deployment::plan(
+            &library.game_root,
+            &library.lib_paths,
+            &library.spt_rules,
+            &dedicated_server::deployable_mods(library),
+            &file_overrides::deployable_cache(library),
+        ),
+    }
+}
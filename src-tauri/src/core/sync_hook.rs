@@ -0,0 +1,169 @@
+use crate::core::progress::Progress;
+use crate::models::error::SError;
+use crate::models::sync_hook::{HookRun, HookStage, SyncHooks};
+use crate::models::warning::{OperationWarning, WarningKind};
+use camino::Utf8Path;
+use parking_lot::Mutex;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Output kept per hook; earlier output is dropped.
+const MAX_OUTPUT: usize = 64 * 1024;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs `sync` between the hooks, whichever way the sync was started.
+/// A failing pre hook cancels the sync; a failing post hook is added to its warnings, since
+/// the mods are deployed by then.
+pub fn around(
+    hooks: &SyncHooks,
+    game_root: &Utf8Path,
+    progress: Progress,
+    sync: impl FnOnce() -> Result<Vec<OperationWarning>, SError>,
+) -> Result<Vec<OperationWarning>, SError> {
+    if let Some(pre) = run(hooks, HookStage::PreSync, game_root)? {
+        progress.hook(&pre);
+        ensure_succeeded(&pre)?;
+    }
+
+    let mut warnings = sync()?;
+
+    if let Some(post) = run(hooks, HookStage::PostSync, game_root)? {
+        progress.hook(&post);
+        if !post.succeeded() {
+            warnings.push(OperationWarning::new(
+                WarningKind::PostSyncHookFailed,
+                &post.command,
+            ));
+        }
+    }
+    Ok(warnings)
+}
+
+/// Runs the hook of `stage` in the game root and waits for it, up to the configured timeout.
+/// Returns `None` when hooks are off or none is set for the stage.
+pub fn run(
+    hooks: &SyncHooks,
+    stage: HookStage,
+    game_root: &Utf8Path,
+) -> Result<Option<HookRun>, SError> {
+    let command = match stage {
+        HookStage::PreSync => &hooks.pre_sync,
+        HookStage::PostSync => &hooks.post_sync,
+    };
+    let Some(command) = command
+        .as_deref()
+        .filter(|c| hooks.enabled && !c.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let mut child = shell(command)
+        .current_dir(game_root)
+        .env("MOD_KEEPER_GAME_ROOT", game_root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| SError::SyncHookFailed(format!("Unable to run {command}: {e}")))?;
+
+    // Read on their own threads so a chatty command cannot block on a full pipe
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+    let (exit_code, timed_out) = wait(&mut child, Duration::from_secs(hooks.timeout_secs.into()))?;
+
+    let output: Vec<u8> = [stdout, stderr]
+        .into_iter()
+        .flatten()
+        .flat_map(|(reader, buffer)| {
+            // Processes the killed shell started may keep the pipe open, so only what was
+            // read so far is kept after a timeout
+            if !timed_out {
+                let _ = reader.join();
+            }
+            buffer.lock().clone()
+        })
+        .collect();
+    let tail = &output[output.len().saturating_sub(MAX_OUTPUT)..];
+
+    Ok(Some(HookRun {
+        stage,
+        command: command.to_string(),
+        exit_code,
+        timed_out,
+        output: String::from_utf8_lossy(tail).into_owned(),
+    }))
+}
+
+/// Fails the sync with the end of the output when the hook did not succeed.
+pub fn ensure_succeeded(run: &HookRun) -> Result<(), SError> {
+    if run.succeeded() {
+        return Ok(());
+    }
+    let reason = match (run.timed_out, run.exit_code) {
+        (true, _) => "timed out".to_string(),
+        (false, Some(code)) => format!("exited with {code}"),
+        (false, None) => "was terminated".to_string(),
+    };
+    let last_line = run.output.lines().last().unwrap_or_default();
+    Err(SError::SyncHookFailed(format!(
+        "{} {reason} {last_line}",
+        run.command
+    )))
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let mut shell = Command::new("cmd");
+    shell
+        .arg("/C")
+        .arg(command)
+        .creation_flags(CREATE_NO_WINDOW);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+/// Waits for the child, killing it once the timeout passed. Returns its exit code and whether
+/// it timed out.
+fn wait(child: &mut Child, timeout: Duration) -> Result<(Option<i32>, bool), SError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status.code(), false));
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            child.wait()?;
+            return Ok((None, true));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+type Reader = (thread::JoinHandle<()>, Arc<Mutex<Vec<u8>>>);
+
+fn drain(mut pipe: impl Read + Send + 'static) -> Reader {
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let sink = buffer.clone();
+    let reader = thread::spawn(move || {
+        let mut chunk = [0u8; 4096];
+        while let Ok(read @ 1..) = pipe.read(&mut chunk) {
+            let mut output = sink.lock();
+            output.extend_from_slice(&chunk[..read]);
+            let excess = output.len().saturating_sub(MAX_OUTPUT);
+            output.drain(..excess);
+        }
+    });
+    (reader, buffer)
+}
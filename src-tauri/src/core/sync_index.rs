@@ -0,0 +1,94 @@
+use crate::core::cache::LibraryCache;
+use crate::models::error::SError;
+use crate::models::mod_dto::Mod;
+use crate::models::paths::LibPathRules;
+use crate::utils::msgpack::MsgPack;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use tracing::warn;
+
+/// Deployed files of the last successful sync.
+/// That set was collision free, so later checks only need to look at mods that changed.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct SyncIndex {
+    /// Digest of the file list of every deployed mod.
+    pub digests: BTreeMap<String, String>,
+    /// Deployed relative path -> owning mod id.
+    pub owners: BTreeMap<Utf8PathBuf, String>,
+}
+
+impl SyncIndex {
+    /// Indexes the active mods.
+    pub fn build(mods: &BTreeMap<String, Mod>, cache: &LibraryCache) -> Self {
+        active(mods, cache).fold(Self::default(), |mut index, (id, files)| {
+            index.digests.insert(id.to_string(), digest(files));
+            index.owners.extend(
+                files
+                    .iter()
+                    .map(|file| (file.to_path_buf(), id.to_string())),
+            );
+            index
+        })
+    }
+
+    /// Active mods whose file set or activation changed since the index was built.
+    pub fn changed_mods(
+        &self,
+        mods: &BTreeMap<String, Mod>,
+        cache: &LibraryCache,
+    ) -> BTreeSet<String> {
+        active(mods, cache)
+            .filter(|(id, files)| self.digests.get(*id) != Some(&digest(files)))
+            .map(|(id, _)| id.to_string())
+            .collect()
+    }
+}
+
+/// Reads the index of the last successful sync.
+/// A missing or unreadable index only means the next check is a full one.
+pub fn read(lib_paths: &LibPathRules) -> Option<SyncIndex> {
+    let bytes = fs::read(&lib_paths.sync_index).ok()?;
+    MsgPack::from_slice(&bytes)
+        .inspect_err(|e| warn!("Ignoring unreadable sync index: {e}"))
+        .ok()
+}
+
+pub fn write(lib_paths: &LibPathRules, index: &SyncIndex) -> Result<(), SError> {
+    fs::write(&lib_paths.sync_index, MsgPack::to_vec(index)?)?;
+    Ok(())
+}
+
+/// Forgets the last sync, so the next check is a full one.
+pub fn clear(lib_paths: &LibPathRules) -> Result<(), SError> {
+    match fs::remove_file(&lib_paths.sync_index) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+fn active<'a>(
+    mods: &'a BTreeMap<String, Mod>,
+    cache: &'a LibraryCache,
+) -> impl Iterator<Item = (&'a str, &'a [Utf8PathBuf])> {
+    cache
+        .mods
+        .iter()
+        .filter(move |(id, _)| mods.get(*id).is_some_and(|m| m.is_active))
+        .map(|(id, fs)| (id.as_str(), fs.files.as_slice()))
+}
+
+fn digest(files: &[Utf8PathBuf]) -> String {
+    let sorted: BTreeSet<&Utf8Path> = files.iter().map(Utf8PathBuf::as_path).collect();
+    sorted
+        .iter()
+        .fold(blake3::Hasher::new(), |mut hasher, file| {
+            hasher.update(file.as_str().as_bytes());
+            hasher.update(&[0]);
+            hasher
+        })
+        .finalize()
+        .to_hex()
+        .to_string()
+}
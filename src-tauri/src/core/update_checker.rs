@@ -0,0 +1,267 @@
+use crate::core::library::Library;
+use crate::core::{download, lockfile, repo_store};
+use crate::models::error::SError;
+use crate::models::mod_dto::ModManifest;
+use crate::models::update_info::UpdateInfo;
+use crate::utils::http;
+use camino::Utf8PathBuf;
+use reqwest::header::ACCEPT;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tracing::warn;
+
+/// How long a query result is reused before the source is asked again.
+pub const TTL_SECS: u64 = 6 * 60 * 60;
+
+const FORGE_API: &str = "https://forge.sp-tarkov.com/api/v0";
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Last query result of a mod, kept with its cache entry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CachedUpdate {
+    pub current: String,
+    pub latest: Option<String>,
+    pub download_url: Option<String>,
+    pub checked_at: u64,
+    /// Files of the installed version that differ from the hashes the source lists.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub corrupted: Vec<Utf8PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redownload_url: Option<String>,
+}
+
+/// A mod whose update source is due for a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingCheck {
+    pub mod_id: String,
+    pub current: String,
+    pub url: String,
+}
+
+/// Newest version announced by an update source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub version: String,
+    pub download_url: Option<String>,
+    /// What the source lists for the installed version, if it still does.
+    pub installed: Option<InstalledRelease>,
+}
+
+/// The installed version as listed by an update source.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstalledRelease {
+    pub download_url: Option<String>,
+    /// Hex encoded SHA-256 of its files, relative to the game root like the cached files.
+    /// Empty when the source publishes no hashes.
+    pub hashes: BTreeMap<Utf8PathBuf, String>,
+}
+
+/// Where the manifest says updates are announced: its `updateUrl`, or the Forge versions
+/// endpoint of its `hubId`.
+pub fn source_url(manifest: &ModManifest) -> Option<String> {
+    manifest
+        .update_url
+        .clone()
+        .or_else(|| manifest.hub_id.map(forge_versions_url))
+}
+
+/// Versions of a Forge mod, newest first.
+pub fn forge_versions_url(hub_id: u64) -> String {
+    format!("{FORGE_API}/mods/{hub_id}/versions")
+}
+
+/// Mods with an update source whose cached result expired, or was made for another version.
+/// `force` ignores the cached results.
+pub fn due(library: &Library, now: u64, force: bool) -> Vec<PendingCheck> {
+    library
+        .mods
+        .keys()
+        .filter_map(|id| library.cache.manifests.get(id))
+        .filter_map(|manifest| {
+            let url = source_url(manifest)?;
+            let fresh = library
+                .cache
+                .updates
+                .get(&manifest.id)
+                .is_some_and(|cached| {
+                    cached.current == manifest.version && now < cached.checked_at + TTL_SECS
+                });
+            (force || !fresh).then(|| PendingCheck {
+                mod_id: manifest.id.clone(),
+                current: manifest.version.clone(),
+                url,
+            })
+        })
+        .collect()
+}
+
+pub fn client() -> Result<reqwest::Client, SError> {
+    http::client(TIMEOUT)
+}
+
+pub async fn fetch(client: &reqwest::Client, check: &PendingCheck) -> Result<Release, SError> {
+    let url = &check.url;
+    let body: Value = client
+        .get(url)
+        .header(ACCEPT, "application/json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let release = parse(&body)
+        .ok_or_else(|| SError::UpdateCheckFailed(format!("No version announced at {url}")))?;
+    Ok(Release {
+        installed: parse_installed(&body, &check.current),
+        ..release
+    })
+}
+
+/// Reads the newest release from a response: either a plain `{ version, downloadUrl }`
+/// document, or a Forge listing whose `data` holds the versions newest first.
+pub fn parse(body: &Value) -> Option<Release> {
+    let release = versions(body).next()?;
+    let version = version_of(release)?;
+    (!version.is_empty()).then(|| Release {
+        version: version.to_string(),
+        download_url: download_url_of(release),
+        installed: None,
+    })
+}
+
+/// Reads the entry of `version` from a response shaped like the ones `parse` reads, with the
+/// file hashes listed under `hashes` or `fileHashes`.
+pub fn parse_installed(body: &Value, version: &str) -> Option<InstalledRelease> {
+    let release = versions(body).find(|release| version_of(release) == Some(version))?;
+    let hashes = ["hashes", "fileHashes"]
+        .iter()
+        .find_map(|key| release.get(key)?.as_object())
+        .into_iter()
+        .flatten()
+        .filter_map(|(path, hash)| Some((Utf8PathBuf::from(path), hash.as_str()?.to_string())))
+        .collect();
+    Some(InstalledRelease {
+        download_url: download_url_of(release),
+        hashes,
+    })
+}
+
+/// Files of a mod in the repo whose SHA-256 differs from `hashes`, or that are missing.
+pub fn corrupted_files(
+    library: &Library,
+    mod_id: &str,
+    hashes: &BTreeMap<Utf8PathBuf, String>,
+) -> Vec<Utf8PathBuf> {
+    let root = repo_store::open(&library.lib_paths).payload_dir(mod_id);
+    hashes
+        .iter()
+        .filter(|(rel, expected)| {
+            download::sha256_of(&root.join(rel))
+                .map_or(true, |actual| !actual.eq_ignore_ascii_case(expected.trim()))
+        })
+        .map(|(rel, _)| rel.clone())
+        .collect()
+}
+
+/// Releases of a response, newest first.
+fn versions(body: &Value) -> Box<dyn Iterator<Item = &Value> + '_> {
+    match body.get("data") {
+        Some(Value::Array(versions)) => Box::new(versions.iter()),
+        Some(data) => Box::new(std::iter::once(data)),
+        None => Box::new(std::iter::once(body)),
+    }
+}
+
+fn version_of(release: &Value) -> Option<&str> {
+    release.get("version")?.as_str().map(str::trim)
+}
+
+fn download_url_of(release: &Value) -> Option<String> {
+    ["downloadUrl", "download_url", "link"]
+        .iter()
+        .find_map(|key| release.get(key)?.as_str())
+        .map(str::to_string)
+}
+
+/// Caches the outcome of a query. A failed one is cached too, so an unreachable source is
+/// not asked again before the TTL runs out.
+/// When the source lists hashes for the installed version, the files in the repo are checked
+/// against them; a corrupted mod can be downloaded again from the source, or failing that
+/// from the website of its manifest.
+pub fn record(
+    library: &mut Library,
+    check: PendingCheck,
+    result: Result<Release, SError>,
+    now: u64,
+) {
+    let release = result
+        .inspect_err(|e| warn!("Update check of {} failed: {e}", check.mod_id))
+        .ok();
+    let installed = release
+        .as_ref()
+        .and_then(|r| r.installed.clone())
+        .unwrap_or_default();
+    let corrupted = corrupted_files(library, &check.mod_id, &installed.hashes);
+    if !corrupted.is_empty() {
+        warn!(
+            "{} file(s) of {} differ from its source",
+            corrupted.len(),
+            check.mod_id
+        );
+    }
+    let redownload_url = installed.download_url.or_else(|| {
+        library
+            .cache
+            .manifests
+            .get(&check.mod_id)
+            .and_then(lockfile::source_url)
+    });
+
+    library.cache.updates.insert(
+        check.mod_id,
+        CachedUpdate {
+            current: check.current,
+            latest: release.as_ref().map(|r| r.version.clone()),
+            download_url: release.and_then(|r| r.download_url),
+            checked_at: now,
+            corrupted,
+            redownload_url,
+        },
+    );
+}
+
+/// Cached results of the installed mods, ordered by id.
+pub fn report(library: &Library) -> Vec<UpdateInfo> {
+    library
+        .cache
+        .updates
+        .iter()
+        .filter(|(id, _)| library.mods.contains_key(*id))
+        .map(|(id, cached)| UpdateInfo {
+            mod_id: id.clone(),
+            current: cached.current.clone(),
+            is_newer: cached
+                .latest
+                .as_deref()
+                .is_some_and(|latest| is_newer(&cached.current, latest)),
+            latest: cached.latest.clone(),
+            download_url: cached.download_url.clone(),
+            checked_at: cached.checked_at.to_string(),
+            corrupted_files: cached.corrupted.clone(),
+            redownload_url: cached.redownload_url.clone(),
+        })
+        .collect()
+}
+
+/// Compares as semver when both parse, ignoring a leading `v`; any other difference counts
+/// as newer since the source only announces its latest release.
+fn is_newer(current: &str, latest: &str) -> bool {
+    let parse = |v: &str| Version::parse(v.trim_start_matches('v')).ok();
+    match (parse(current), parse(latest)) {
+        (Some(current), Some(latest)) => latest > current,
+        _ => current != latest,
+    }
+}
@@ -1,12 +1,26 @@
 use crate::models::error::SError;
-use crate::models::paths::SPTPathRules;
+use crate::models::paths::{SPTPathRules, SptGeneration};
+use camino::Utf8Path;
 use regex;
 use semver::{Version, VersionReq};
 use serde_json::Value;
 use std::fs;
 
-/// Fetches the version from the SPT registry file and validates it against supported ranges.
-pub fn fetch_and_validate(config: &SPTPathRules) -> Result<String, SError> {
+/// Keys holding the version: in the registry of SPT 4, then in `core.json` of SPT 3 and AKI.
+const VERSION_KEYS: [&str; 3] = ["SPT_Version", "sptVersion", "akiVersion"];
+
+/// Fetches and validates the version of the install at the game root, whatever its generation.
+pub fn detect(game_root: &Utf8Path) -> Result<String, SError> {
+    let generation = SptGeneration::detect(game_root);
+    fetch_and_validate(&SPTPathRules::new_for(game_root, generation), generation)
+}
+
+/// Fetches the version from the SPT registry file and validates it against the range of the
+/// generation.
+pub fn fetch_and_validate(
+    config: &SPTPathRules,
+    generation: SptGeneration,
+) -> Result<String, SError> {
     // Read the registry.json file
     let registry_path = &config.server_registry;
     let content = fs::read_to_string(registry_path)
@@ -16,12 +30,11 @@ pub fn fetch_and_validate(config: &SPTPathRules) -> Result<String, SError> {
     let json: Value = serde_json::from_str(&content)
         .map_err(|e| SError::ParseError(format!("Failed to parse registry JSON: {}", e)))?;
 
-    // Look for SPT_Version or SPT_Vesion (handling potential typo)
-    let version_str = json
-        .get("SPT_Version")
-        .and_then(|v| v.as_str())
+    let version_str = VERSION_KEYS
+        .iter()
+        .find_map(|key| json.get(key).and_then(|v| v.as_str()))
         .ok_or_else(|| {
-            SError::ParseError("SPT_Version field not found in registry.json".to_string())
+            SError::ParseError(format!("No version field found in {}", registry_path))
         })?;
 
     // Extract version number from string like "SPT 4.0.11 - 278e72"
@@ -30,7 +43,7 @@ pub fn fetch_and_validate(config: &SPTPathRules) -> Result<String, SError> {
 
     // Parse and validate the version
     let version = parse(&version_number)?;
-    validate(&version)?;
+    validate(&version, generation)?;
 
     Ok(version.to_string())
 }
@@ -53,10 +66,10 @@ fn extract_version_number(version_str: &str) -> Result<String, SError> {
     )))
 }
 
-/// Validates a version string against the supported SPT version requirements.
-pub fn validate_string(version_str: &str) -> Result<(), SError> {
+/// Validates a version string against the supported range of the generation.
+pub fn validate_string(version_str: &str, generation: SptGeneration) -> Result<(), SError> {
     let version = parse(version_str)?;
-    validate(&version)
+    validate(&version, generation)
 }
 
 /// Parses a string into a SemVer Version, handling errors with early exit.
@@ -64,10 +77,10 @@ fn parse(version_str: &str) -> Result<Version, SError> {
     Version::parse(version_str).map_err(|e| SError::ParseError(e.to_string()))
 }
 
-/// Checks if the provided version matches the hardcoded requirement range (>=4, <5).
-fn validate(version: &Version) -> Result<(), SError> {
-    let req_str = "^4";
-    let req = VersionReq::parse(req_str).map_err(|e| SError::ParseError(e.to_string()))?;
+/// Checks if the provided version matches the requirement range of the generation.
+fn validate(version: &Version, generation: SptGeneration) -> Result<(), SError> {
+    let req = VersionReq::parse(generation.version_req())
+        .map_err(|e| SError::ParseError(e.to_string()))?;
 
     if req.matches(version) {
         return Ok(());
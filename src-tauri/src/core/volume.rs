@@ -0,0 +1,157 @@
+use crate::core::library::Library;
+use crate::core::linker;
+use crate::models::library::DeploymentMode;
+use crate::models::volume::{VolumeKind, VolumeStatus};
+use crate::models::warning::{OperationWarning, WarningKind};
+use camino::Utf8Path;
+use file_id::FileId;
+use std::path::Path;
+use sysinfo::Disks;
+
+/// File systems served by another machine.
+#[cfg(target_os = "linux")]
+const NETWORK_FILE_SYSTEMS: [&str; 8] = [
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "fuse.sshfs",
+    "davfs",
+    "afs",
+];
+
+/// Volumes of the game root and the repo of the library.
+pub fn status(library: &Library) -> VolumeStatus {
+    status_of(&library.game_root, &library.repo_root)
+}
+
+pub fn status_of(game_root: &Utf8Path, repo_root: &Utf8Path) -> VolumeStatus {
+    let game = kind_of(game_root);
+    let repo = kind_of(repo_root);
+    let same_volume = is_same_volume(game_root, repo_root);
+    VolumeStatus {
+        game_root: game,
+        repo,
+        same_volume,
+        recommended_mode: recommended_mode(game, repo, same_volume),
+    }
+}
+
+/// Links into a drive that can go away leave the game with dangling mods, so copies are
+/// preferred there. On Windows, links across volumes are symlinks, which need Developer Mode,
+/// see `LinkKind::CrossDeviceSymlink`.
+fn recommended_mode(game: VolumeKind, repo: VolumeKind, same_volume: bool) -> DeploymentMode {
+    let is_fixed = game == VolumeKind::Local && repo == VolumeKind::Local;
+    match is_fixed && (same_volume || !cfg!(windows)) {
+        true => DeploymentMode::Link,
+        false => DeploymentMode::Copy,
+    }
+}
+
+/// A warning when the library links while its volumes call for copies; the subject is the
+/// library name and `details` lists the folders on a removable or network drive, or both
+/// roots when they are on different volumes.
+pub fn sync_warnings(library: &Library) -> Vec<OperationWarning> {
+    if library.deployment_mode != DeploymentMode::Link {
+        return Vec::new();
+    }
+    let status = status(library);
+    if status.recommended_mode == DeploymentMode::Link {
+        return Vec::new();
+    }
+
+    let roots = [
+        (&library.game_root, status.game_root),
+        (&library.repo_root, status.repo),
+    ];
+    let mut folders: Vec<&Utf8Path> = roots
+        .iter()
+        .filter(|(_, kind)| *kind != VolumeKind::Local)
+        .map(|(root, _)| root.as_path())
+        .collect();
+    if folders.is_empty() {
+        folders = roots.iter().map(|(root, _)| root.as_path()).collect();
+    }
+    vec![OperationWarning::new(WarningKind::UnsuitableVolume, &library.name).with_details(&folders)]
+}
+
+/// The kind of drive holding `path`, or the deepest folder above it that exists.
+pub fn kind_of(path: &Utf8Path) -> VolumeKind {
+    let Some(path) = path
+        .ancestors()
+        .find(|dir| dir.exists())
+        .and_then(|dir| dunce::canonicalize(dir).ok())
+    else {
+        return VolumeKind::Local;
+    };
+    if is_network(&path) {
+        return VolumeKind::Network;
+    }
+
+    let disks = Disks::new_with_refreshed_list();
+    let disk = disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len());
+    match disk {
+        Some(disk) if disk.is_removable() => VolumeKind::Removable,
+        Some(_) => VolumeKind::Local,
+        // Only fixed and removable drives are listed on Windows
+        None if cfg!(windows) => VolumeKind::Network,
+        None => VolumeKind::Local,
+    }
+}
+
+/// Whether both paths are on the same volume, assumed when either cannot be read.
+pub fn is_same_volume(a: &Utf8Path, b: &Utf8Path) -> bool {
+    match (volume_id(a), volume_id(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    }
+}
+
+fn volume_id(path: &Utf8Path) -> Option<u64> {
+    let dir = path.ancestors().find(|dir| dir.exists())?;
+    match linker::get_id(dir).ok()? {
+        FileId::Inode { device_id, .. } => Some(device_id),
+        FileId::LowRes {
+            volume_serial_number,
+            ..
+        } => Some(volume_serial_number.into()),
+        FileId::HighRes {
+            volume_serial_number,
+            ..
+        } => Some(volume_serial_number),
+    }
+}
+
+/// Mounts are listed with spaces escaped, as in `/proc/self/mounts`.
+#[cfg(target_os = "linux")]
+fn is_network(path: &Path) -> bool {
+    let Ok(mounts) = std::fs::read_to_string("/proc/self/mounts") else {
+        return false;
+    };
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            let mount_point = fields.next()?.replace("\\040", " ");
+            Some((mount_point, fields.next()?))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .is_some_and(|(_, fs_type)| NETWORK_FILE_SYSTEMS.contains(&fs_type))
+}
+
+/// UNC paths; mapped drives are caught by `kind_of`.
+#[cfg(windows)]
+fn is_network(path: &Path) -> bool {
+    path.to_string_lossy().starts_with(r"\\")
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn is_network(_path: &Path) -> bool {
+    false
+}
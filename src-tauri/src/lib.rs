@@ -4,40 +4,189 @@ pub mod core;
 pub mod models;
 pub mod utils;
 
-use crate::commands::global::{close_library, create_library, init, open_library, remove_library};
+use crate::commands::global::{
+    close_library, compare_libraries, create_library, discover_existing_libraries,
+    get_auto_sync_after_add, get_bepinex_status, get_checklist_policy, get_crash_guard_policy,
+    get_data_dir, get_database_diff_enabled, get_framework_policy, get_install_size_limits,
+    get_link_failure_policy, get_performance_metrics, get_sync_hooks, init, install_bepinex,
+    open_library, register_libraries, remove_library, repair_library, request_confirmation,
+    set_auto_sync_after_add, set_checklist_policy, set_consistency_checks, set_crash_guard_policy,
+    set_database_diff_enabled, set_framework_policy, set_install_size_limits,
+    set_link_failure_policy, set_sync_hooks,
+};
 use crate::commands::library::{
-    add_mods, get_backups, get_library, get_mod_documentation, remove_mods, rename_library,
-    restore_backup, sync_mods, toggle_mod,
+    acknowledge_launch_checklist, add_mods, adopt_mods, analyze_install, batch_import,
+    bulk_update_mod_metadata, check_against_lockfile, check_for_updates, checkout_state,
+    create_profile, delete_all_backups, delete_backup, delete_profile, disable_crashing_mod,
+    drop_files, duplicate_profile, enable_repo_history, export_cache_toml, export_compat_notes,
+    export_lockfile, export_modpack, export_support_bundle, find_mods_by_file,
+    fix_lockfile_activation, get_actionable_issues, get_backup_disk_usage, get_backups,
+    get_consistency_report, get_database_diff, get_database_overlaps, get_launch_checklist,
+    get_library, get_mod_details, get_mod_documentation, get_mod_statistics, get_operation_logs,
+    get_ownership_report, get_patch_groups, get_recommendations, get_remote_server_status,
+    get_repo_history, get_server_health, get_server_task_status, get_volume_status,
+    import_compat_notes, import_modpack, install_from_url, install_server_task, keep_local_edits,
+    list_plans, load_plan, package_mod, preview_sync, purge_remote_server, push_remote_server,
+    query_mods, rebuild_cache, remove_compat_note, remove_mods, remove_server_task, rename_library,
+    reset_profiles, resolve_conflict, resolve_mod_dependencies, restore_backup, revert_local_edits,
+    sandbox_sync, scaffold_mod, scan_game_directory, search_mods, set_backup_retention,
+    set_compat_note, set_deployment_mode, set_library_mode, set_library_read_only,
+    set_managed_roots, set_mod_icon, set_mod_note, set_mod_tags, set_remote_server,
+    switch_active_mods, switch_profile, sync_mods, toggle_mod, toggle_mods, unwatch_mod_source,
+    verify_lockfile, watch_mod_source,
 };
 use crate::core::registry::AppRegistry;
-use parking_lot::Mutex;
+use crate::core::shared_state::SharedState;
+use crate::models::events::{
+    DropQueued, ExternalChangesDetected, GameStarted, GameStopped, LibraryHealthChanged,
+    LibraryReady, QueuedDropInstalled, RepeatedCrash, ServerCrashed, TaskStatus,
+};
 use specta_typescript::Typescript;
 use std::sync::Arc;
-use tauri_specta::{collect_commands, Builder};
+use tauri::Manager;
+use tauri_specta::{collect_commands, collect_events, Builder, Event};
 
-/// Stage 1: Setup command handler with all registered commands
+/// Stage 1: Setup command handler with all registered commands and events
 fn setup_command_handler() -> Builder<tauri::Wry> {
     use crate::commands::test::create_simulation_game_root;
-    Builder::<tauri::Wry>::new().commands(collect_commands![
-        // library
-        add_mods,
-        remove_mods,
-        sync_mods,
-        get_library,
-        toggle_mod,
-        get_backups,
-        restore_backup,
-        get_mod_documentation,
-        rename_library,
-        // global
-        open_library,
-        create_library,
-        close_library,
-        remove_library,
-        init,
-        // test (debug only)
-        create_simulation_game_root,
-    ])
+    Builder::<tauri::Wry>::new()
+        .commands(collect_commands![
+            // library
+            analyze_install,
+            install_from_url,
+            add_mods,
+            drop_files,
+            batch_import,
+            remove_mods,
+            sync_mods,
+            switch_active_mods,
+            disable_crashing_mod,
+            preview_sync,
+            sandbox_sync,
+            list_plans,
+            load_plan,
+            get_library,
+            query_mods,
+            get_mod_details,
+            find_mods_by_file,
+            search_mods,
+            get_actionable_issues,
+            get_mod_statistics,
+            get_ownership_report,
+            get_consistency_report,
+            enable_repo_history,
+            get_repo_history,
+            checkout_state,
+            set_remote_server,
+            push_remote_server,
+            purge_remote_server,
+            get_remote_server_status,
+            toggle_mod,
+            toggle_mods,
+            get_patch_groups,
+            get_database_diff,
+            get_database_overlaps,
+            bulk_update_mod_metadata,
+            set_mod_note,
+            check_for_updates,
+            set_mod_tags,
+            set_mod_icon,
+            get_backups,
+            get_backup_disk_usage,
+            keep_local_edits,
+            revert_local_edits,
+            set_backup_retention,
+            restore_backup,
+            delete_backup,
+            delete_all_backups,
+            get_mod_documentation,
+            get_recommendations,
+            resolve_mod_dependencies,
+            rename_library,
+            set_library_read_only,
+            package_mod,
+            watch_mod_source,
+            unwatch_mod_source,
+            scaffold_mod,
+            export_cache_toml,
+            export_support_bundle,
+            get_launch_checklist,
+            acknowledge_launch_checklist,
+            reset_profiles,
+            set_managed_roots,
+            resolve_conflict,
+            set_compat_note,
+            remove_compat_note,
+            export_compat_notes,
+            export_lockfile,
+            export_modpack,
+            import_modpack,
+            scan_game_directory,
+            adopt_mods,
+            verify_lockfile,
+            set_library_mode,
+            set_deployment_mode,
+            get_volume_status,
+            get_operation_logs,
+            rebuild_cache,
+            create_profile,
+            switch_profile,
+            delete_profile,
+            duplicate_profile,
+            install_server_task,
+            remove_server_task,
+            get_server_task_status,
+            get_server_health,
+            check_against_lockfile,
+            fix_lockfile_activation,
+            import_compat_notes,
+            // global
+            open_library,
+            create_library,
+            close_library,
+            remove_library,
+            repair_library,
+            request_confirmation,
+            compare_libraries,
+            get_framework_policy,
+            set_framework_policy,
+            get_checklist_policy,
+            set_checklist_policy,
+            get_link_failure_policy,
+            set_link_failure_policy,
+            get_crash_guard_policy,
+            set_crash_guard_policy,
+            get_performance_metrics,
+            set_consistency_checks,
+            discover_existing_libraries,
+            register_libraries,
+            get_sync_hooks,
+            get_database_diff_enabled,
+            set_database_diff_enabled,
+            get_bepinex_status,
+            install_bepinex,
+            get_auto_sync_after_add,
+            set_auto_sync_after_add,
+            get_install_size_limits,
+            set_install_size_limits,
+            set_sync_hooks,
+            get_data_dir,
+            init,
+            // test (debug only)
+            create_simulation_game_root,
+        ])
+        .events(collect_events![
+            GameStarted,
+            GameStopped,
+            ServerCrashed,
+            RepeatedCrash,
+            LibraryReady,
+            LibraryHealthChanged,
+            ExternalChangesDetected,
+            DropQueued,
+            QueuedDropInstalled,
+            TaskStatus
+        ])
 }
 
 /// Stage 2: Export TypeScript bindings (debug builds only)
@@ -51,48 +200,55 @@ fn export_typescript_bindings(builder: &Builder<tauri::Wry>) {
 }
 
 /// Stage 3: Initialize application state (AppRegistry and handles)
-fn initialize_app_state() -> (
-    AppRegistry,
-    Arc<Mutex<crate::config::global::GlobalConfig>>,
-    Arc<Mutex<Option<crate::core::library::Library>>>,
-) {
+fn initialize_app_state() -> (AppRegistry, SharedState) {
     let app_registry = AppRegistry::default();
-    let config_handle = app_registry.global_config.clone();
-    let instance_handle = app_registry.active_instance.clone();
+    let shared = app_registry.shared.clone();
 
-    (app_registry, config_handle, instance_handle)
+    (app_registry, shared)
 }
 
 /// Stage 4: Register Tauri plugins
 fn register_plugins() -> tauri::Builder<tauri::Wry> {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .plugin(
-            tauri_plugin_log::Builder::new()
-                .level(tauri_plugin_log::log::LevelFilter::Info)
-                .build(),
-        )
         .plugin(tauri_plugin_dialog::init())
 }
 
-/// Helper: Load the initial library from known libraries in a background thread
-fn load_initial_library(
-    config_handle: Arc<Mutex<crate::config::global::GlobalConfig>>,
-    instance_handle: Arc<Mutex<Option<crate::core::library::Library>>>,
-) {
+/// Helper: Load the initial library from known libraries in a background thread.
+/// The manifest is installed first so the UI can render right away; the cache follows and
+/// `LibraryReady` is emitted once commands no longer have to wait for it.
+fn load_initial_library(app_handle: tauri::AppHandle, shared: SharedState) {
     tauri::async_runtime::spawn_blocking(move || {
-        let first_library_path = config_handle.lock().known_libraries.first().cloned();
+        let first_library_path = shared.config(|config| config.known_libraries.first().cloned());
 
-        if let Some(path) = first_library_path {
-            match crate::core::library::Library::load(&path) {
-                Ok(library) => {
-                    *instance_handle.lock() = Some(library);
-                }
-                Err(e) => {
-                    tracing::error!("Failed to load library from {}: {}", path, e);
-                    // Leave active_instance as None on failure
+        let Some(path) = first_library_path else {
+            return;
+        };
+
+        match crate::core::library::Library::open(&path).and_then(|mut library| {
+            library.lock()?;
+            Ok(library)
+        }) {
+            Ok(library) => {
+                crate::core::library_service::recover_interrupted(&library);
+                shared.instance(|instance| *instance = Some(library));
+            }
+            Err(e) => {
+                tracing::error!("Failed to load library from {}: {}", path, e);
+                // Leave the active instance as None on failure
+                return;
+            }
+        }
+
+        match crate::core::library_service::finish_loading(&shared, &path) {
+            Ok(Some(ready)) => {
+                if let Err(e) = ready.emit(&app_handle) {
+                    tracing::warn!("Failed to emit library readiness: {}", e);
                 }
             }
+            Ok(None) => {}
+            // Commands retry through ensure_loaded and surface the error to the user
+            Err(e) => tracing::error!("Failed to load library cache from {}: {}", path, e),
         }
     });
 }
@@ -115,16 +271,36 @@ fn start_init_timeout_checker(init_called: Arc<std::sync::atomic::AtomicBool>) {
 /// Stage 5: Setup application (mount events and load initial library)
 fn setup_application(
     builder: Builder<tauri::Wry>,
-    config_handle: Arc<Mutex<crate::config::global::GlobalConfig>>,
-    instance_handle: Arc<Mutex<Option<crate::core::library::Library>>>,
+    shared: SharedState,
     init_called: Arc<std::sync::atomic::AtomicBool>,
 ) -> impl FnOnce(&mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     move |app| {
         // Mount events for the command handler
         builder.mount_events(app);
 
+        // Persist operation timings next to the config
+        if let Some(path) = crate::config::global::GlobalConfig::metrics_path() {
+            crate::core::metrics::init(path);
+        }
+
+        // Debug builds always check the library after changes
+        crate::core::consistency::set_enabled(shared.config(|config| config.consistency_checks));
+
+        // Watch the game/server processes of whichever library is active
+        crate::core::process_watch::spawn(app.handle().clone(), shared.clone());
+
+        // Revalidate the library after sleep or when its drives come and go
+        crate::core::health_watch::spawn(app.handle().clone(), shared.clone());
+
+        // Flag the library when other tools change its folders
+        let fs_watch = app.state::<AppRegistry>().fs_watch.clone();
+        crate::core::fs_watch::spawn(app.handle().clone(), shared.clone(), fs_watch);
+
+        // Hash what installs left for later while the app is idle
+        crate::core::idle_hasher::spawn(shared.clone());
+
         // Load the initial library in the background
-        load_initial_library(config_handle, instance_handle);
+        load_initial_library(app.handle().clone(), shared);
 
         // Start timer to check if init was called within 10 seconds
         start_init_timeout_checker(init_called);
@@ -136,6 +312,22 @@ fn setup_application(
 /// Stage 6-7: Main entry point - orchestrates all initialization stages
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Pick where app data lives before anything reads the config
+    let args = crate::config::data_dir::init(std::env::args().skip(1).collect());
+
+    // Log to the console, the app log and the running operation's log
+    crate::core::logging::init(crate::config::global::GlobalConfig::logs_path());
+
+    // Batch mode: run the requested operation without creating the window
+    match crate::core::batch::parse(&args) {
+        Some(Ok(task)) => std::process::exit(crate::core::batch::run(task)),
+        Some(Err(usage)) => {
+            eprintln!("{usage}");
+            std::process::exit(crate::core::batch::EXIT_USAGE);
+        }
+        None => {}
+    }
+
     // Stage 1: Setup command handler
     let builder = setup_command_handler();
 
@@ -143,7 +335,7 @@ pub fn run() {
     export_typescript_bindings(&builder);
 
     // Stage 3: Initialize application state
-    let (app_registry, config_handle, instance_handle) = initialize_app_state();
+    let (app_registry, shared) = initialize_app_state();
     let init_called = app_registry.init_called.clone();
 
     // Stage 4: Register plugins
@@ -153,13 +345,19 @@ pub fn run() {
     let invoke_handler = builder.invoke_handler();
 
     // Stage 6: Configure application setup
-    let setup_fn = setup_application(builder, config_handle, instance_handle, init_called);
+    let setup_fn = setup_application(builder, shared, init_called);
 
     // Stage 7: Build and run the application
     tauri_builder
         .invoke_handler(invoke_handler)
         .manage(app_registry)
         .setup(setup_fn)
-        .run(tauri::generate_context!("tauri.conf.json"))
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!("tauri.conf.json"))
+        .expect("error while running tauri application")
+        .run(|_, event| {
+            // Operation timings are written in batches, keep the last ones
+            if let tauri::RunEvent::Exit = event {
+                crate::core::metrics::flush();
+            }
+        });
 }
@@ -1,8 +1,37 @@
-pub mod config;
+pub mod actionable_issue;
+pub mod batch_import;
+pub mod bepinex;
+pub mod compat_note;
+pub mod confirmation;
+pub mod consistency;
+pub mod database_diff;
+pub mod dedicated_server;
+pub mod deployment_plan;
 pub mod error;
+pub mod events;
+pub mod file_search;
+pub mod game_scan;
 pub mod global;
+pub mod install_size;
+pub mod launch_checklist;
 pub mod library;
+pub mod lockfile;
+pub mod logging;
+pub mod metrics;
 pub mod mod_backup;
 pub mod mod_dto;
+pub mod mod_patch;
+pub mod ownership;
 pub mod paths;
+pub mod profile;
+pub mod recommendation;
+pub mod remote_target;
+pub mod repo_history;
+pub mod scaffold;
+pub mod server_task;
+pub mod statistics;
+pub mod sync_hook;
 pub mod test;
+pub mod update_info;
+pub mod volume;
+pub mod warning;
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Problems worth a banner, most urgent first; the order of the variants is the ranking.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueKind {
+    /// Files of the deployed mods are missing from the game root.
+    BrokenLinks,
+    /// Files of installed mods differ from the hashes their update source lists.
+    CorruptedMods,
+    /// Active mods miss a dependency or conflict with each other, so syncing fails.
+    IncompatibleMods,
+    /// The active mods changed since the last sync.
+    UnsyncedChanges,
+    /// The library drive is below the free space to keep.
+    LowDiskSpace,
+    /// Update sources announced newer versions of installed mods.
+    UpdatesAvailable,
+}
+
+/// What resolves an issue, named after the command the frontend calls for it.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IssueAction {
+    /// `sync_mods`
+    SyncMods,
+    /// `resolve_mod_dependencies`, or deactivating one of the conflicting mods.
+    ResolveModDependencies,
+    /// `install_from_url` with the download of each update, or the redownload of each
+    /// corrupted mod.
+    InstallFromUrl,
+    /// Nothing the app can do; the user has to free space on the drive.
+    FreeDiskSpace,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct ActionableIssue {
+    pub kind: IssueKind,
+    /// Mod ids the issue is about; the library name for `UnsyncedChanges` and the repo
+    /// folder for `LowDiskSpace`.
+    pub subjects: Vec<String>,
+    pub action: IssueAction,
+}
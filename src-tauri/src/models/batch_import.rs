@@ -0,0 +1,37 @@
+use crate::models::error::SError;
+use crate::models::warning::OperationWarning;
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// What became of a mod (or of an archive that could not be staged) in a batch import.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind")]
+pub enum ImportOutcome {
+    Installed,
+    Updated,
+    /// The library already has the mod with identical content.
+    AlreadyInstalled,
+    /// A newer archive of the same mod is part of the batch and was installed instead.
+    Superseded,
+    Failed {
+        error: SError,
+    },
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct ImportEntry {
+    #[specta(type = String)]
+    pub input: Utf8PathBuf,
+    /// `None` when the archive could not be staged.
+    pub mod_name: Option<String>,
+    pub outcome: ImportOutcome,
+}
+
+/// Aggregated result of `batch_import`, one entry per mod in archive order.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BatchImportReport {
+    pub entries: Vec<ImportEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<OperationWarning>,
+}
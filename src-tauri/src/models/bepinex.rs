@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Whether the game root can load client mods, see `bepinex::state`.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BepInExState {
+    Installed,
+    /// A `BepInEx` folder exists, but the loader or its core is missing.
+    Incomplete,
+    /// No `BepInEx` folder, e.g. a fresh SPT install whose client patch never ran.
+    Missing,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct BepInExStatus {
+    pub state: BepInExState,
+    /// BepInEx build matching the SPT version of the game root, `None` when that is unknown.
+    pub recommended: Option<String>,
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CompatKind {
+    Conflicts,
+    Compatible,
+    /// Each mod is suggested as a companion of the other, see `recommendations`.
+    Recommended,
+}
+
+/// A user note about how two mods behave together, e.g. "conflicts unless the loot config is changed".
+/// The pair is unordered; `mod_a` is always the smaller id.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct CompatNote {
+    pub mod_a: String,
+    pub mod_b: String,
+    pub kind: CompatKind,
+    pub note: String,
+    /// Configuration or action that resolves the conflict.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unless: Option<String>,
+}
+
+impl CompatNote {
+    /// Orders the pair so both directions refer to the same note.
+    pub fn normalized(self) -> Self {
+        if self.mod_a <= self.mod_b {
+            return self;
+        }
+        Self {
+            mod_a: self.mod_b,
+            mod_b: self.mod_a,
+            ..self
+        }
+    }
+
+    pub fn is_pair(&self, a: &str, b: &str) -> bool {
+        (self.mod_a == a && self.mod_b == b) || (self.mod_a == b && self.mod_b == a)
+    }
+}
+
+/// File exchanged between users.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompatNotesFile {
+    #[serde(default)]
+    pub notes: Vec<CompatNote>,
+}
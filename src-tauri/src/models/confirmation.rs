@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Commands that cannot be undone and only run with a token from `request_confirmation`,
+/// which the user hands out by accepting a native dialog.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(tag = "kind")]
+pub enum DestructiveAction {
+    /// Deleting a library with its repo.
+    RemoveLibrary { repo_root: String },
+    /// Restoring a backup over a mod whose files were edited in place, see `local_edits`.
+    RestoreBackup { mod_id: String, timestamp: String },
+    /// Deleting one backup of a mod.
+    DeleteBackup { mod_id: String, timestamp: String },
+    /// Deleting every backup of a mod.
+    DeleteAllBackups { mod_id: String },
+}
+
+/// A one-time token for `action`, valid for `expires_in_secs`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct Confirmation {
+    pub token: String,
+    pub action: DestructiveAction,
+    pub expires_in_secs: u32,
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConsistencyIssueKind {
+    /// The cache lists files of a mod whose repo folder is gone.
+    MissingRepoFolder,
+    /// A mod of the manifest has no cache entry, so none of its files would be deployed.
+    MissingCacheEntry,
+    /// A staging folder outlived the task that created it.
+    StagingLeftover,
+}
+
+/// A broken invariant between the manifest, the cache and the repo folders.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ConsistencyIssue {
+    pub kind: ConsistencyIssueKind,
+    /// Mod id, or staging folder name.
+    pub subject: String,
+}
+
+/// Outcome of `library_service::rebuild_cache`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheRebuildReport {
+    /// Mods whose entry was rebuilt from their repo folder.
+    pub rebuilt: Vec<String>,
+    /// Repo folders no mod of the manifest refers to, left out of the cache.
+    pub untracked_folders: Vec<String>,
+    /// Mods of the manifest without a repo folder, left without a cache entry.
+    pub missing_folders: Vec<String>,
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordChangeKind {
+    /// The database has no record with this id.
+    Added,
+    /// The mod sets fields of an existing record.
+    Modified,
+}
+
+/// A field a mod sets to another value than the database.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    /// JSON pointer below the record, e.g. `/_props/Weight`.
+    pub pointer: String,
+    /// JSON of the database value, `None` when the field is new.
+    pub before: Option<String>,
+    /// JSON of the value the mod ships.
+    pub after: String,
+}
+
+/// A database record a server mod ships data for, see `database_diff`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct RecordChange {
+    /// Id of the record, e.g. an item template id.
+    pub id: String,
+    pub kind: RecordChangeKind,
+    /// Database file holding the record, relative to the database folder.
+    pub file: Option<String>,
+    /// File of the mod the data comes from, relative to the mod root.
+    pub source: String,
+    /// Empty for added records.
+    pub fields: Vec<FieldChange>,
+}
+
+/// A database record several active server mods ship data for.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct DatabaseOverlap {
+    pub id: String,
+    /// Ids of the mods, ordered.
+    pub mods: Vec<String>,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Health check of a library deployed to a dedicated Fika server, see `dedicated_server`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServerHealth {
+    /// Active client-only mods, left out of the deploy in dedicated server mode.
+    pub ignored_client_mods: Vec<String>,
+    /// Required server mod folders no active mod provides.
+    pub missing_server_mods: Vec<String>,
+}
@@ -0,0 +1,65 @@
+use crate::models::compat_note::CompatNote;
+use crate::models::paths::SPTPathRules;
+use crate::models::warning::OperationWarning;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PlannedLink {
+    pub mod_id: String,
+    #[specta(type = String)]
+    pub source: Utf8PathBuf,
+    #[specta(type = String)]
+    pub target: Utf8PathBuf,
+}
+
+/// Read-only description of what `sync_mods` would do to the game root.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default)]
+pub struct DeploymentPlan {
+    pub timestamp: String,
+    pub links: Vec<PlannedLink>,
+    /// Links in the game root the sync removes without linking them again.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[specta(type = Vec<String>)]
+    pub unlinks: Vec<Utf8PathBuf>,
+    /// Folders the sync creates in the game root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[specta(type = Vec<String>)]
+    pub created_dirs: Vec<Utf8PathBuf>,
+    pub collisions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<OperationWarning>,
+    /// Compatibility notes about active mod pairs, to read alongside the collisions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compat_notes: Vec<CompatNote>,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug)]
+pub struct SavedPlan {
+    pub timestamp: String,
+    #[specta(type = String)]
+    pub path: Utf8PathBuf,
+}
+
+/// Which side of the install `sync_mods` deploys and purges.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SyncScope {
+    #[default]
+    All,
+    /// Everything outside of the server mods: client plugins, managed roots and external files.
+    ClientOnly,
+    /// The server mods alone.
+    ServerOnly,
+}
+
+impl SyncScope {
+    /// Whether a path relative to the game root belongs to the scope.
+    pub fn covers(self, rel: &Utf8Path, spt_rules: &SPTPathRules) -> bool {
+        match self {
+            SyncScope::All => true,
+            SyncScope::ClientOnly => !rel.starts_with(&spt_rules.server_mods),
+            SyncScope::ServerOnly => rel.starts_with(&spt_rules.server_mods),
+        }
+    }
+}
@@ -2,25 +2,100 @@ use derive_more::Display;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-#[derive(Type, Serialize, Deserialize, Debug, Display)]
+#[derive(Type, Serialize, Deserialize, Clone, Debug, Display, PartialEq, Eq)]
 pub enum SError {
     UnsupportedSPTVersion(String),
     ParseError(String),
     IOError(String),
     GameOrServerRunning,
     ProcessRunning,
+    ChecklistNotAcknowledged,
     UnableToDetermineModId,
     #[display("Mod not found: {}", _0)]
     ModNotFound(String),
+    #[display("Profile not found: {}", _0)]
+    ProfileNotFound(String),
     FileOrDirectoryNotFound(String),
+    #[display("Invalid name: {}", _0)]
+    InvalidName(String),
+    #[display("Path too long: {}", _0)]
+    PathTooLong(String),
+    #[display("Already exists: {}", _0)]
+    AlreadyExists(String),
     #[display("File collisions detected: {}", "_0.join(\", \")")]
     FileCollision(Vec<String>),
+    /// Every link that failed during a deployment, in deployment order.
+    #[display("Failed to link {} path(s)", "_0.len()")]
+    LinkFailed(Vec<LinkFailure>),
     Unexpected,
     UnhandledCompression(String),
+    #[display("Corrupt download: {}", _0)]
+    CorruptArchive(String),
     AsyncRuntimeError(String),
+    #[display("Git failed: {}", _0)]
+    GitFailed(String),
     NoActiveLibrary,
+    RemoteNotConfigured,
+    #[display("Remote server unreachable: {}", _0)]
+    RemoteUnreachable(String),
+    /// Why mods cannot be activated or deployed together, see `dependency`.
+    #[display("Unmet dependencies: {} issue(s)", "_0.len()")]
+    UnmetDependencies(Vec<DependencyIssue>),
+    #[display("Sync hook failed: {}", _0)]
+    SyncHookFailed(String),
+    /// The active mods changed since the last sync.
+    LibraryNotSynced,
+    #[display("Server task failed: {}", _0)]
+    ServerTaskFailed(String),
+    /// The operation relies on a feature of another operating system.
+    UnsupportedPlatform,
+    /// The library is marked read-only or its repo cannot be written.
+    LibraryReadOnly,
     #[display("Invalid library at {}: {}", _0, _1)]
     InvalidLibrary(String, String),
+    #[display("Update check failed: {}", _0)]
+    UpdateCheckFailed(String),
+    #[display("Download failed: {}", _0)]
+    DownloadFailed(String),
+    /// Edited files of a mod whose original content no backup holds.
+    #[display("No original copy of: {}", "_0.join(\", \")")]
+    NoOriginalCopy(Vec<String>),
+    /// An opt-in feature is turned off in the settings.
+    #[display("Feature disabled: {}", _0)]
+    FeatureDisabled(String),
+    /// A destructive command was called without a valid token from `request_confirmation`.
+    #[display("Confirmation required: {}", _0)]
+    ConfirmationRequired(String),
+    /// Another process has the library open, see `library_lock`.
+    #[display("Library in use by another process: {}", _0)]
+    LibraryLocked(String),
+}
+
+/// A path of a mod that could not be linked into the game root.
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LinkFailure {
+    pub mod_id: String,
+    /// Relative to the game root.
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum DependencyIssueKind {
+    /// The required mod is not installed; `other` is the reference of the manifest.
+    Missing,
+    /// The required mod is installed but not active.
+    Inactive,
+    /// Both mods are active and one of them conflicts with the other.
+    Conflict,
+}
+
+/// A dependency or conflict of a mod that keeps it from being activated.
+#[derive(Type, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DependencyIssue {
+    pub mod_id: String,
+    pub other: String,
+    pub kind: DependencyIssueKind,
 }
 
 macro_rules! impl_from {
@@ -40,3 +115,6 @@ impl_from!(std::io::Error, IOError);
 impl_from!(serde_json::Error, ParseError);
 impl_from!(std::path::StripPrefixError, ParseError);
 impl_from!(zip::result::ZipError, UnhandledCompression);
+impl_from!(sevenz_rust::Error, UnhandledCompression);
+impl_from!(unrar::error::UnrarError, UnhandledCompression);
+impl_from!(reqwest::Error, DownloadFailed);
@@ -0,0 +1,164 @@
+use crate::models::error::SError;
+use crate::models::library::LibraryDTO;
+use crate::models::sync_hook::HookRun;
+use crate::models::warning::OperationWarning;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri_specta::Event;
+
+/// The game client process appeared.
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug, PartialEq, Eq)]
+pub struct GameStarted;
+
+/// The game client process exited.
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug, PartialEq, Eq)]
+pub struct GameStopped;
+
+/// The server exited while the client was still running.
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug, PartialEq, Eq)]
+pub struct ServerCrashed {
+    /// Only known for processes started by Mod Keeper.
+    pub exit_code: Option<i32>,
+}
+
+/// The server crashed repeatedly with errors pointing at the same active mod, see `core::crash_guard`.
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug, PartialEq, Eq)]
+pub struct RepeatedCrash {
+    pub mod_id: String,
+    /// Whether the mod was deactivated and the library synced without it, per `CrashGuardPolicy`.
+    pub disabled: bool,
+}
+
+/// Progress of a library operation, tagged by stage so each one can be rendered on its own.
+/// `task` is the command that started the operation, e.g. `add_mods`.
+/// Warnings are also attached to the command result for the post-operation summary.
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "status")]
+pub enum TaskStatus {
+    /// An input is being extracted or inspected; `index` is zero-based.
+    Staging {
+        task: String,
+        file: String,
+        index: u32,
+        total: u32,
+    },
+    /// A folder is being searched, e.g. for existing libraries.
+    Scanning {
+        task: String,
+        folder: String,
+    },
+    /// A staged mod is being copied into the repo.
+    CopyingToRepo {
+        task: String,
+        mod_name: String,
+        index: u32,
+        total: u32,
+    },
+    /// Bytes of a copy or extraction, with the smoothed rate and the estimated time left.
+    /// `eta_secs` is absent until the rate is known and while the transfer stalls.
+    Transfer {
+        task: String,
+        item: String,
+        #[specta(type = f64)]
+        done_bytes: u64,
+        #[specta(type = f64)]
+        total_bytes: u64,
+        #[specta(type = f64)]
+        bytes_per_sec: u64,
+        eta_secs: Option<u32>,
+    },
+    /// The active mods are being linked into the game root.
+    Linking {
+        task: String,
+        mods: u32,
+    },
+    Warning {
+        task: String,
+        warning: OperationWarning,
+    },
+    /// A sync hook ended; its output is kept for the task log.
+    Hook {
+        task: String,
+        run: HookRun,
+    },
+    Done {
+        task: String,
+    },
+    Failed {
+        task: String,
+        error: SError,
+    },
+    /// The full stage tree of a multi-stage task, sent whenever one of its stages changes.
+    Stages {
+        task: String,
+        stages: Vec<StageStatus>,
+    },
+}
+
+/// One stage of a multi-stage task; `children` are its sub-tasks, e.g. one per mod.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct StageStatus {
+    pub name: String,
+    pub state: StageState,
+    pub children: Vec<StageStatus>,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "state")]
+pub enum StageState {
+    Pending,
+    Running {
+        done: u32,
+        total: u32,
+    },
+    Done,
+    /// Every error raised in the stage, including those of sub-tasks it carried on past.
+    Failed {
+        errors: Vec<SError>,
+    },
+    /// An earlier stage failed.
+    Skipped,
+}
+
+/// Files were dropped while a long task runs; they are installed once it ends.
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug, PartialEq, Eq)]
+pub struct DropQueued {
+    pub paths: Vec<String>,
+    /// Drops waiting, this one included.
+    pub pending: u32,
+}
+
+/// A queued drop was installed after the long task it waited for.
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug)]
+pub struct QueuedDropInstalled {
+    pub library: LibraryDTO,
+}
+
+/// The game root or repo of the active library became unavailable or available again, e.g. an
+/// external drive was unplugged, or the system resumed from sleep; see `health_watch`.
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug, PartialEq, Eq)]
+pub struct LibraryHealthChanged {
+    pub game_root_available: bool,
+    pub repo_available: bool,
+    /// Sent because the system resumed, whether or not anything changed.
+    pub resumed: bool,
+    /// Mods whose deployed files are missing, only looked for after a resume or remount.
+    pub broken_links: Vec<String>,
+}
+
+/// Files of the active library changed outside of the app, see `fs_watch`. The library was
+/// flagged dirty; rescanning the mods or syncing brings it back in line.
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug, PartialEq, Eq)]
+pub struct ExternalChangesDetected {
+    /// Mods whose files in the repo no longer match the library, or mod folders it does not know.
+    pub changed_mods: Vec<String>,
+    /// Deployed mods with files missing from the game root.
+    pub broken_mods: Vec<String>,
+}
+
+/// The active library finished loading its cache in the background.
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug, PartialEq, Eq)]
+pub struct LibraryReady {
+    pub id: String,
+    pub mod_count: u32,
+}
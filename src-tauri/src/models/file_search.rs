@@ -0,0 +1,14 @@
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// A mod providing files that match a search pattern.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct FileMatch {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub is_active: bool,
+    /// Matching files, relative to the game root.
+    #[specta(type = Vec<String>)]
+    pub files: Vec<Utf8PathBuf>,
+}
@@ -0,0 +1,20 @@
+use crate::models::mod_dto::ModType;
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// A mod folder or DLL in the game root that the library does not deploy, e.g. one installed
+/// by hand before the library existed.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq)]
+pub struct UnmanagedEntry {
+    /// Relative to the game root.
+    #[specta(type = String)]
+    pub path: Utf8PathBuf,
+    /// `Server` below the server mods folder, `Client` below the plugins folder.
+    pub mod_type: ModType,
+    #[specta(type = f64)]
+    pub bytes: u64,
+    /// Disabled by hand by renaming, e.g. `Mod.disabled` or `!Mod`; adopted as an inactive mod
+    /// under its name without the marker.
+    pub disabled: bool,
+}
@@ -1,7 +1,70 @@
 use crate::models::library::LibraryDTO;
+use camino::Utf8PathBuf;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
+/// What to do with BepInEx framework files bundled inside a mod archive.
+#[derive(Deserialize, Serialize, Type, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FrameworkPolicy {
+    /// Delete them during staging.
+    Strip,
+    /// Move them to the library quarantine folder so they can be inspected.
+    #[default]
+    Quarantine,
+}
+
+/// Whether syncing waits for the launch checklist of the active mods to be acknowledged.
+#[derive(Deserialize, Serialize, Type, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChecklistPolicy {
+    /// Refuse to sync until the current checklist is acknowledged.
+    #[default]
+    Block,
+    /// Only report the checklist.
+    Inform,
+}
+
+/// What a deployment does once a link fails; the failures are reported together either way.
+#[derive(Deserialize, Serialize, Type, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LinkFailurePolicy {
+    /// Stop at the first failed link.
+    #[default]
+    Abort,
+    /// Link everything else before failing, so the report lists every broken path.
+    Continue,
+}
+
+/// What happens to the mod a server crash is blamed on once it crashed the server repeatedly.
+#[derive(Deserialize, Serialize, Type, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CrashGuardPolicy {
+    /// Don't count crashes.
+    Off,
+    /// Tell the user which mod keeps crashing the server.
+    #[default]
+    Suggest,
+    /// Deactivate the mod and deploy without it once the game has stopped.
+    Disable,
+}
+
+/// Why app data lives where it does, see `config::data_dir`.
+#[derive(Deserialize, Serialize, Type, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DataDirSource {
+    /// The platform config folder chosen by `confy`.
+    #[default]
+    Default,
+    /// The `MOD_KEEPER_DATA_DIR` environment variable.
+    Environment,
+    /// The `--portable` flag, storing everything next to the executable.
+    Portable,
+}
+
+#[derive(Deserialize, Serialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DataDirInfo {
+    pub source: DataDirSource,
+    /// Folder holding the global config and metrics, if it could be determined.
+    #[specta(type = Option<String>)]
+    pub path: Option<Utf8PathBuf>,
+}
+
 #[derive(Deserialize, Serialize, Type)]
 pub struct LibrarySwitch {
     pub active: Option<LibraryDTO>,
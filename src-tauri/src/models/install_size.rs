@@ -0,0 +1,43 @@
+use crate::models::warning::OperationWarning;
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// When the analysis before an install warns about its size.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstallSizeLimits {
+    /// Installs unpacking to more than this are reported, in MiB.
+    pub warn_above_mib: u32,
+    /// Free space to keep on the library drive while installing, in MiB.
+    pub min_free_mib: u32,
+}
+
+impl Default for InstallSizeLimits {
+    fn default() -> Self {
+        Self {
+            warn_above_mib: 5 * 1024,
+            min_free_mib: 2 * 1024,
+        }
+    }
+}
+
+/// Unpacked size of one input; `None` when it could not be read.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct InputSize {
+    #[specta(type = String)]
+    pub path: Utf8PathBuf,
+    #[specta(type = Option<f64>)]
+    pub bytes: Option<u64>,
+}
+
+/// What installing the inputs would take, computed before anything is unpacked.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct InstallEstimate {
+    pub inputs: Vec<InputSize>,
+    #[specta(type = f64)]
+    pub total_bytes: u64,
+    /// Free space on the drive holding the library, if it could be determined.
+    #[specta(type = Option<f64>)]
+    pub available_bytes: Option<u64>,
+    pub warnings: Vec<OperationWarning>,
+}
@@ -0,0 +1,19 @@
+use crate::models::mod_dto::PrerequisiteKind;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct ChecklistItem {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub kind: PrerequisiteKind,
+    pub note: Option<String>,
+}
+
+/// Launch prerequisites declared by the active mods.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LaunchChecklist {
+    pub items: Vec<ChecklistItem>,
+    /// True when the current items were acknowledged, or there is nothing to acknowledge.
+    pub acknowledged: bool,
+}
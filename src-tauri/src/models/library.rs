@@ -1,4 +1,9 @@
+use crate::models::compat_note::CompatNote;
+use crate::models::mod_backup::BackupRetention;
 use crate::models::mod_dto::Mod;
+use crate::models::paths::SptGeneration;
+use crate::models::profile::ModProfile;
+use crate::models::warning::OperationWarning;
 use camino::Utf8PathBuf;
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -15,6 +20,70 @@ pub struct LibraryDTO {
     pub spt_version: String,
     pub mods: BTreeMap<String, Mod>,
     pub is_dirty: bool,
+    /// Kept when `mods` is stripped from lightweight summaries.
+    #[serde(default)]
+    pub mod_count: u32,
+    /// Non-fatal conditions met by the command that produced this DTO.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<OperationWarning>,
+    /// Digest of the last launch checklist the user acknowledged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged_checklist: Option<String>,
+    /// Folders managed in addition to the server mods and client plugins, relative to the game root.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[specta(type = Vec<String>)]
+    pub managed_roots: Vec<Utf8PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compat_notes: Vec<CompatNote>,
+    /// Config files (relative to the game root) owned by mods.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[specta(type = BTreeMap<String, String>)]
+    pub config_owners: BTreeMap<Utf8PathBuf, String>,
+    /// Files (relative to the game root) provided by several mods -> the mod deploying them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[specta(type = BTreeMap<String, String>)]
+    pub overrides: BTreeMap<Utf8PathBuf, String>,
+    /// Stored flag in the manifest; for the frontend also true when the repo is not writable.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Server mods folder of a remote server the server mods are copied to, see `remote_target`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[specta(type = Option<String>)]
+    pub remote_server: Option<Utf8PathBuf>,
+    #[serde(default)]
+    pub mode: LibraryMode,
+    /// Named activation sets, see `profiles`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ModProfile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub backup_retention: BackupRetention,
+    /// Libraries written before SPT 3 was supported are SPT 4 ones.
+    #[serde(default)]
+    pub spt_generation: SptGeneration,
+    #[serde(default)]
+    pub deployment_mode: DeploymentMode,
+}
+
+/// What the library deploys to, see `dedicated_server`.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LibraryMode {
+    #[default]
+    Standard,
+    /// A headless Fika server: client-only mods are never deployed.
+    DedicatedServer,
+}
+
+/// How deployment puts mod files into the game root.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeploymentMode {
+    /// Hardlinks or junctions on Windows, symlinks elsewhere.
+    #[default]
+    Link,
+    /// Physical copies, for drives and synced folders that break links. Each copy is recorded
+    /// with its digest in the deploy ledger, see `deploy_ledger::DeployedCopy`.
+    Copy,
 }
 
 #[derive(Serialize, Deserialize, Type, Clone, Debug)]
@@ -25,3 +94,40 @@ pub struct LibraryCreationRequirement {
     pub repo_root: Option<Utf8PathBuf>,
     pub name: String,
 }
+
+/// A library found on disk by `library_discovery`, e.g. after reinstalling the app.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct DiscoveredLibrary {
+    #[specta(type = String)]
+    pub repo_root: Utf8PathBuf,
+    pub name: String,
+    #[specta(type = String)]
+    pub game_root: Utf8PathBuf,
+    pub mod_count: u32,
+    /// Already in the list of known libraries.
+    pub is_known: bool,
+}
+
+/// A mod as seen in one library of a comparison.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct ComparedMod {
+    pub id: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub is_active: bool,
+}
+
+/// A mod present in both libraries whose version or activation differs.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct ModDifference {
+    pub a: ComparedMod,
+    pub b: ComparedMod,
+}
+
+/// Differences between the mod sets of two libraries, by mod id.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LibraryComparison {
+    pub only_in_a: Vec<ComparedMod>,
+    pub only_in_b: Vec<ComparedMod>,
+    pub differences: Vec<ModDifference>,
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Exact state of a mod, so another install can tell whether it runs the same files.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct LockedMod {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Blake3 digest of the manifest and files, see `ModFS::content_hash`.
+    pub content_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+}
+
+/// File shared with a modlist to check that everyone installed byte-identical mods.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Lockfile {
+    pub format: u32,
+    #[serde(default)]
+    pub mods: Vec<LockedMod>,
+}
+
+/// A locked mod installed in another version.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct VersionMismatch {
+    pub id: String,
+    pub name: String,
+    pub locked: Option<String>,
+    pub installed: Option<String>,
+}
+
+/// Activation changes that make the active mods match a lockfile.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ActivationFix {
+    /// Locked mods that are installed but inactive.
+    pub activate: Vec<String>,
+    /// Active mods the lockfile does not pin.
+    pub deactivate: Vec<String>,
+}
+
+/// How the library differs from a lockfile shared by another player.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LockfileDiff {
+    /// Locked mods that are not installed, to download from their source.
+    pub missing: Vec<LockedMod>,
+    pub version_mismatches: Vec<VersionMismatch>,
+    /// Ids of the active mods the lockfile does not pin.
+    pub extra_active: Vec<String>,
+    pub fix: ActivationFix,
+}
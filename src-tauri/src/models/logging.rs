@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Operations that write a log of their own, see `core::logging::operation`.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoggedOperation {
+    Sync,
+    AddMods,
+    RemoveMod,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+/// One event logged while the operation ran.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct LogStep {
+    /// Milliseconds since the operation started.
+    pub elapsed_ms: u32,
+    pub level: LogLevel,
+    /// Module that logged it.
+    pub target: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct OperationLog {
+    pub id: String,
+    pub operation: LoggedOperation,
+    /// Unix seconds, like backup timestamps.
+    pub timestamp: String,
+    pub duration_ms: u32,
+    #[serde(default)]
+    pub steps: Vec<LogStep>,
+    /// Why the operation failed, `None` when it succeeded.
+    #[serde(default)]
+    pub error: Option<String>,
+}
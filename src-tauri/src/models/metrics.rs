@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Core operations whose duration is tracked.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Operation {
+    Deploy,
+    Purge,
+    AddMod,
+    PersistCache,
+}
+
+/// Rolling timing summary of one operation.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq)]
+pub struct OperationMetric {
+    pub operation: Operation,
+    /// Number of samples in the rolling window.
+    pub samples: u32,
+    pub average_ms: f64,
+    pub last_ms: f64,
+}
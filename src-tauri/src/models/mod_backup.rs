@@ -5,6 +5,48 @@ use specta::Type;
 #[derive(Serialize, Deserialize, Type, Clone, Debug)]
 pub struct ModBackup {
     pub timestamp: String,
+    /// Manifest version of the backed up payload, when it had one.
+    pub version: Option<String>,
+    /// Taken automatically right before a restore replaced the mod.
+    pub pre_restore: bool,
     #[specta(type = String)]
     pub path: Utf8PathBuf,
 }
+
+/// Limits on the backups kept in a library, applied after every backup, see
+/// `mod_backup::prune`. `None` leaves that dimension unlimited.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BackupRetention {
+    /// Backups kept per mod, not counting the snapshots taken before restores.
+    pub max_per_mod: Option<u32>,
+    /// Size of all backups together, in MiB.
+    pub max_total_mib: Option<u32>,
+    /// Backups older than this are removed, in days.
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for BackupRetention {
+    fn default() -> Self {
+        Self {
+            max_per_mod: Some(5),
+            max_total_mib: None,
+            max_age_days: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct ModBackupUsage {
+    pub mod_id: String,
+    pub backups: u32,
+    #[specta(type = f64)]
+    pub bytes: u64,
+}
+
+/// Space taken by the backups of a library.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BackupUsage {
+    pub mods: Vec<ModBackupUsage>,
+    #[specta(type = f64)]
+    pub total_bytes: u64,
+}
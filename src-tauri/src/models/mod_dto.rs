@@ -1,3 +1,4 @@
+use camino::Utf8PathBuf;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use std::collections::BTreeMap;
@@ -52,6 +53,22 @@ pub struct Link {
     pub url: String,
 }
 
+/// Environment change a mod needs before the game is launched with it.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PrerequisiteKind {
+    ServerWipe,
+    NewProfile,
+    Other,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct Prerequisite {
+    pub kind: PrerequisiteKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Type, Clone, Debug)]
 #[serde(untagged)]
 pub enum Dependencies {
@@ -72,15 +89,51 @@ pub struct ModManifest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub icon: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "iconDark")]
+    pub icon_dark: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub documentation: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub compatibility: Option<Compatibility>,
+    /// Also read from `dependsOn`; a manifest with both keys fails to parse as a duplicate field.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "dependsOn")]
     pub dependencies: Option<Dependencies>,
+    /// Ids of mods that cannot be active at the same time, see `dependency`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "conflictsWith")]
+    pub conflicts_with: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub effects: Option<Vec<Effect>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub links: Option<Vec<Link>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prerequisites: Option<Vec<Prerequisite>>,
+    /// Document announcing the latest release, see `update_checker::parse`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "updateUrl")]
+    pub update_url: Option<String>,
+    /// Mod id on the SPT Forge hub, queried when there is no `update_url`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "hubId")]
+    #[specta(type = Option<f64>)]
+    pub hub_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub enum VersionBump {
+    Major,
+    Minor,
+    Patch,
+}
+
+/// Frontend theme used to pick between `icon` and `icon_dark`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Appearance {
+    #[default]
+    Light,
+    Dark,
 }
 
 #[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq)]
@@ -100,5 +153,63 @@ pub struct Mod {
     pub manifest: Option<ModManifest>,
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub icon_data: Option<String>,
+    /// Set by the user, never read from the mod itself.
+    #[serde(default)]
+    pub metadata: ModMetadata,
+    /// Files edited in the repo since the mod was installed, relative to its root.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    #[specta(type = Vec<String>)]
+    pub modified_files: Vec<Utf8PathBuf>,
+    /// Id of the mod this one patches, see `mod_patches`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub patch_of: Option<String>,
     // files removed: only needed in cache, not for frontend display
 }
+
+/// User-maintained properties of a mod, used to organize the library view.
+/// Deployment never depends on them.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModMetadata {
+    /// Sorted and unique.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Higher sorts first.
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub group: Option<String>,
+    /// Sorted and unique, groups mods by purpose across groups.
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Color label from the frontend palette, e.g. `red`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub color: Option<String>,
+    /// Custom icon file in the manifest folder, shown instead of the manifest icon.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub icon: Option<String>,
+}
+
+/// Fields to change on one mod; absent fields are left as they are.
+/// An empty `note`, `group` or `color` clears it.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default)]
+pub struct ModMetadataUpdate {
+    pub mod_id: String,
+    pub tags: Option<Vec<String>>,
+    pub priority: Option<i32>,
+    pub note: Option<String>,
+    pub group: Option<String>,
+    pub categories: Option<Vec<String>>,
+    pub color: Option<String>,
+}
+
+/// A page of mods ordered by id.
+#[derive(Serialize, Deserialize, Type, Clone, Debug)]
+pub struct ModPage {
+    pub mods: Vec<Mod>,
+    /// Opaque position after the last mod of this page, None on the last page.
+    pub next_cursor: Option<String>,
+    /// Number of mods in the library when the page was built.
+    pub total: u32,
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// A mod and the installed patches applying to it, see `mod_patches`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct PatchGroup {
+    pub base: String,
+    /// Ids of the patches, ordered.
+    pub patches: Vec<String>,
+}
@@ -0,0 +1,30 @@
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Links of a mod found in the game root.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct ModLinks {
+    pub mod_id: String,
+    /// Relative to the game root.
+    #[specta(type = Vec<String>)]
+    pub links: Vec<Utf8PathBuf>,
+}
+
+/// A link in a mod root pointing outside the repo, likely made by another mod manager.
+/// It is never removed or replaced.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct ForeignLink {
+    /// Relative to the game root.
+    #[specta(type = String)]
+    pub path: Utf8PathBuf,
+    #[specta(type = String)]
+    pub target: Utf8PathBuf,
+}
+
+/// Who owns the links found in the mod roots of the game root.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OwnershipReport {
+    pub mods: Vec<ModLinks>,
+    pub foreign: Vec<ForeignLink>,
+}
@@ -1,18 +1,25 @@
 use crate::models::error::SError;
-use camino::{Utf8Path, Utf8PathBuf};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 use dunce::canonicalize;
+use serde::{Deserialize, Serialize};
+use specta::Type;
 use std::path::PathBuf;
 
 macro_rules! define_paths {
-    ($name:ident { $($field:ident : $default:expr),* $(,)? }) => {
+    (
+        $name:ident { $($field:ident : $default:expr),* $(,)? }
+        $(extra { $($(#[$list_meta:meta])* $list:ident),* $(,)? })?
+    ) => {
         #[derive(Clone, Debug)]
         pub struct $name {
             $(pub $field: Utf8PathBuf,)*
+            $($($(#[$list_meta])* pub $list: Vec<Utf8PathBuf>,)*)?
         }
 
         impl $name {
             pub fn to_absolute(mut self, base: &Utf8Path) -> Self {
                 $(self.$field = base.join(self.$field);)*
+                $($(self.$list = self.$list.iter().map(|path| base.join(path)).collect();)*)?
                 self
             }
 
@@ -25,6 +32,7 @@ macro_rules! define_paths {
             fn default() -> Self {
                 Self {
                     $($field: $default.into(),)*
+                    $($($list: Vec::new(),)*)?
                 }
             }
         }
@@ -40,18 +48,142 @@ define_paths!(SPTPathRules {
     client_plugins: "BepInEx/plugins",
     client_config: "BepInEx/config",
     server_mods: "SPT/user/mods",
+    server_profiles: "SPT/user/profiles",
+    server_logs: "SPT/user/logs",
     server_exe: "SPT/SPT.Server.exe",
     server_registry: "SPT/user/sptRegistry/registry.json",
+    server_database: "SPT/SPT_Data/database",
     client_exe: "EscapeFromTarkov.exe",
     library_default: ".mod_keeper",
+} extra {
+    /// Additional folders registered by the library, managed like `server_mods` and `client_plugins`.
+    managed_roots,
 });
 
+/// Layout and version range of the SPT install a library manages.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SptGeneration {
+    /// SPT 4: the server lives in `SPT/`.
+    #[default]
+    Spt4,
+    /// SPT 3.9 to 3.11: `SPT.Server.exe` and `user/` at the game root.
+    Spt3,
+    /// SPT-AKI 3.0 to 3.8: like `Spt3`, with `Aki.Server.exe` and `Aki_Data`.
+    Aki,
+}
+
+impl SptGeneration {
+    /// Generation of the install at the game root, told by its server executable.
+    /// SPT 4 when none is found, so the usual errors about a missing server apply.
+    pub fn detect(game_root: &Utf8Path) -> Self {
+        [Self::Spt4, Self::Spt3, Self::Aki]
+            .into_iter()
+            .find(|generation| {
+                game_root
+                    .join(SPTPathRules::for_generation(*generation).server_exe)
+                    .is_file()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Supported versions, as a semver requirement.
+    pub fn version_req(&self) -> &'static str {
+        match self {
+            Self::Spt4 => "^4",
+            Self::Spt3 => ">=3.9, <4",
+            Self::Aki => ">=3.0, <3.9",
+        }
+    }
+
+    /// Whether an entry of the plugins folder holds SPT's own client patches:
+    /// the `spt` folder, or the `aki-*.dll` files before it.
+    pub fn is_client_patch(&self, name: &str) -> bool {
+        match self {
+            Self::Spt4 | Self::Spt3 => name.eq_ignore_ascii_case("spt"),
+            Self::Aki => name.to_ascii_lowercase().starts_with("aki-"),
+        }
+    }
+}
+
+impl SPTPathRules {
+    /// Relative rules of a generation; `default` is the SPT 4 layout.
+    pub fn for_generation(generation: SptGeneration) -> Self {
+        let legacy = |data: &str, server_exe: &str| Self {
+            server_mods: "user/mods".into(),
+            server_profiles: "user/profiles".into(),
+            server_logs: "user/logs".into(),
+            server_exe: server_exe.into(),
+            server_registry: format!("{data}/Server/configs/core.json").into(),
+            server_database: format!("{data}/Server/database").into(),
+            ..Self::default()
+        };
+        match generation {
+            SptGeneration::Spt4 => Self::default(),
+            SptGeneration::Spt3 => legacy("SPT_Data", "SPT.Server.exe"),
+            SptGeneration::Aki => legacy("Aki_Data", "Aki.Server.exe"),
+        }
+    }
+
+    /// `for_generation` resolved against the game root.
+    pub fn new_for(base: &Utf8Path, generation: SptGeneration) -> Self {
+        Self::for_generation(generation).to_absolute(base)
+    }
+
+    /// Folders whose content is owned by mods: the built-in roots followed by the managed ones.
+    pub fn mod_roots(&self) -> impl Iterator<Item = &Utf8Path> {
+        [&self.server_mods, &self.client_plugins]
+            .into_iter()
+            .chain(&self.managed_roots)
+            .map(Utf8PathBuf::as_path)
+    }
+
+    /// Validates and installs the managed roots of a library.
+    /// Roots are relative to the game root and may not escape it or overlap the built-in ones.
+    pub fn with_managed_roots(self, roots: Vec<Utf8PathBuf>) -> Result<Self, SError> {
+        let builtin = [&self.server_mods, &self.client_plugins];
+        let invalid = roots.iter().find(|root| {
+            root.as_str().is_empty()
+                || !root
+                    .components()
+                    .all(|c| matches!(c, Utf8Component::Normal(_)))
+                || builtin
+                    .iter()
+                    .any(|b| root.starts_with(b) || b.starts_with(root))
+        });
+        if let Some(root) = invalid {
+            return Err(SError::ParseError(format!(
+                "Invalid managed root: {}",
+                root
+            )));
+        }
+
+        Ok(Self {
+            managed_roots: roots,
+            ..self
+        })
+    }
+}
+
 define_paths!(LibPathRules {
     backups: "backups",
+    profile_backups: "profile-backups",
     mods: "mods",
     staging: "staging",
+    plans: "plans",
+    quarantine: "quarantine",
     manifest: "manifest.toml",
     cache: "cache.toml",
+    cache_export: "cache-export.toml",
+    cache_store: "cache",
+    sync_index: "sync-index.msgpack",
+    last_sync_plan: "last-sync-plan.toml",
+    search_index: "search-index.msgpack",
+    deploy_ledger: "deploy-ledger.msgpack",
+    journal: "journal.jsonl",
+    lock: "library.lock",
+    history: ".history",
+    logs: "logs",
+    remote_ledger: "remote-ledger.msgpack",
 });
 #[derive(Clone, Debug)]
 pub struct SPTPathCanonical {
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// A named activation set of a library, e.g. "hardcore" or "testing", see `profiles`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModProfile {
+    /// Ids of the active mods, in load order.
+    #[serde(default)]
+    pub mods: Vec<String>,
+}
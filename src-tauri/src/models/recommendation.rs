@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecommendationSource {
+    /// An optional dependency declared by the mod's manifest.
+    Manifest,
+    /// A compat note marking both mods as recommended together.
+    CompatNote,
+}
+
+/// A companion suggested for a mod, unlike dependencies never required.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct Recommendation {
+    pub mod_id: String,
+    /// Name in the library, `None` while the mod is not installed.
+    pub name: Option<String>,
+    pub source: RecommendationSource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
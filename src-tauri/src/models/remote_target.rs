@@ -0,0 +1,16 @@
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Where the server mods of a library were last pushed, see `remote_target`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct RemoteStatus {
+    #[specta(type = String)]
+    pub path: Utf8PathBuf,
+    /// False while the share is not mounted or the folder is gone.
+    pub reachable: bool,
+    /// Files currently tracked on the remote server.
+    pub files: u32,
+    /// Unix seconds of the last complete push.
+    pub last_push: Option<String>,
+}
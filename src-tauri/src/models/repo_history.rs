@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// A recorded state of the library repo, newest first in listings.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct RepoCommit {
+    pub id: String,
+    pub message: String,
+    /// Unix seconds, like backup timestamps.
+    pub timestamp: String,
+}
@@ -0,0 +1,19 @@
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub enum ScaffoldKind {
+    Server,
+    Client,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug)]
+pub struct ScaffoldOptions {
+    pub name: String,
+    pub author: String,
+    pub kind: ScaffoldKind,
+    /// Parent directory; the mod source is created in `{directory}/{name}`
+    #[specta(type = String)]
+    pub directory: Utf8PathBuf,
+}
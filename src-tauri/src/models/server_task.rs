@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Scheduled task starting the SPT server of a library, see `server_task`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct ServerTask {
+    pub name: String,
+    pub installed: bool,
+}
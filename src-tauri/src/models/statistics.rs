@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModStatistics {
+    pub mod_id: String,
+    /// Size of the mod payload stored in the repo.
+    #[specta(type = f64)]
+    pub repo_bytes: u64,
+    /// Apparent size of the mod files found in the game root.
+    #[specta(type = f64)]
+    pub deployed_bytes: u64,
+    /// Deployed bytes that are separate copies rather than links to the repo.
+    #[specta(type = f64)]
+    pub duplicated_bytes: u64,
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LibraryStatistics {
+    pub mods: Vec<ModStatistics>,
+    /// Sum of repo and deployed sizes as reported by a naive folder size.
+    #[specta(type = f64)]
+    pub apparent_bytes: u64,
+    /// Real disk usage with every physical file (hardlink) counted once.
+    #[specta(type = f64)]
+    pub actual_bytes: u64,
+}
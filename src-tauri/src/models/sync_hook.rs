@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Shell commands run around every sync, e.g. to stop and restart an SPT server service.
+/// They run with the user's rights, so nothing runs until the user turns them on.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct SyncHooks {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Runs before anything is purged; a failure cancels the sync.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_sync: Option<String>,
+    /// Runs once the sync succeeded; a failure is reported as a warning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_sync: Option<String>,
+    /// The command is killed once it runs longer.
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u32,
+}
+
+fn default_timeout() -> u32 {
+    60
+}
+
+impl Default for SyncHooks {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pre_sync: None,
+            post_sync: None,
+            timeout_secs: default_timeout(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookStage {
+    PreSync,
+    PostSync,
+}
+
+/// How a hook ended, attached to the task log of the sync.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct HookRun {
+    pub stage: HookStage,
+    pub command: String,
+    /// `None` when the command was killed or ended by a signal.
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    /// Standard output followed by standard error, keeping the end of long output.
+    pub output: String,
+}
+
+impl HookRun {
+    pub fn succeeded(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+}
@@ -0,0 +1,24 @@
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Latest release of a mod as reported by its update source, see `update_checker`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct UpdateInfo {
+    pub mod_id: String,
+    /// Installed version the check was made for.
+    pub current: String,
+    /// `None` when the source could not be queried.
+    pub latest: Option<String>,
+    pub download_url: Option<String>,
+    /// Whether `latest` is newer than `current`.
+    pub is_newer: bool,
+    /// Unix timestamp of the query.
+    pub checked_at: String,
+    /// Files of the installed version whose SHA-256 differs from the one the source lists,
+    /// or that are missing; empty when the source lists none.
+    #[specta(type = Vec<String>)]
+    pub corrupted_files: Vec<Utf8PathBuf>,
+    /// Where the installed version can be downloaded again.
+    pub redownload_url: Option<String>,
+}
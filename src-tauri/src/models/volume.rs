@@ -0,0 +1,24 @@
+use crate::models::library::DeploymentMode;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// What kind of drive a folder lives on.
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VolumeKind {
+    Local,
+    /// USB sticks and external drives, which may be unplugged while links point into them.
+    Removable,
+    /// SMB or NFS shares and mapped drives; links into them break while the share is offline.
+    Network,
+}
+
+/// Where the game and the repo of a library live, see `volume::status`.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct VolumeStatus {
+    pub game_root: VolumeKind,
+    pub repo: VolumeKind,
+    /// False when hard links cannot reach from the game root into the repo.
+    pub same_volume: bool,
+    /// The deployment mode these volumes call for.
+    pub recommended_mode: DeploymentMode,
+}
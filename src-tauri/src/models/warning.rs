@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WarningKind {
+    /// No manifest was found, the name was taken from the folder, archive or fallback.
+    InferredManifest,
+    /// Archive entries that could not be extracted safely were skipped; the archive was only partially unpacked.
+    SkippedUnsafeEntry,
+    /// Bundled BepInEx files were stripped or quarantined.
+    BundledFramework,
+    /// The mod was already installed with identical content.
+    UnchangedReinstall,
+    /// Mods changing the server database were added or removed; existing profiles may break.
+    WipeRecommended,
+    /// Files linked by the last deploy were gone right after; an antivirus likely removed them.
+    /// Restore them from quarantine and exclude the game and library folders from scanning.
+    PossibleAntivirusInterference,
+    /// A deployed mod was restored while the library had unsynced changes; the game keeps
+    /// the previous files until the next sync.
+    RestoredWhileUnsynced,
+    /// The installed mod suggests companions that are not active; `details` lists them.
+    RecommendedMods,
+    /// A mod of a lockfile is not installed.
+    LockedModMissing,
+    /// The installed version differs from the lockfile; `details` holds the locked one, then the installed one.
+    LockedVersionMismatch,
+    /// The installed files differ from the lockfile; `details` holds the locked hash, then the installed one.
+    LockedContentMismatch,
+    /// An active client-only mod was not deployed to the dedicated server.
+    ClientModIgnored,
+    /// A server mod a dedicated Fika server needs is not active; the subject is its folder.
+    RequiredServerModMissing,
+    /// The post-sync hook failed after the mods were deployed; the subject is its command.
+    PostSyncHookFailed,
+    /// The game root has no working BepInEx, so client mods will not load; the subject is the
+    /// game root.
+    BepInExMissing,
+    /// A mod of an imported pack was neither bundled nor installed; the subject is its name and
+    /// `details` holds where to download it, if known.
+    ModPackModMissing,
+    /// The install unpacks to more than the configured limit; `details` holds its size, then
+    /// the limit, in bytes.
+    LargeInstall,
+    /// The install would leave less free space on the library drive than configured;
+    /// `details` holds the space needed, then the space available, in bytes.
+    LowDiskSpace,
+    /// Links pointing outside the repo, likely made by another mod manager, were found in the
+    /// mod roots and left alone; both managers deploying mods may conflict. The subject is the
+    /// game root and `details` lists the links.
+    ForeignLinks,
+    /// The enabled mod has patches that are not active; `details` holds their ids, so they
+    /// can be enabled along.
+    InactivePatches,
+    /// Files could not be hard linked because the library is on another drive than the game,
+    /// and were symlinked instead; `details` lists them. Copy deployment avoids links entirely.
+    CrossDeviceLinks,
+    /// The library links mods while the game or the repo is on a removable or network drive,
+    /// or on Windows while they are on different drives; the subject is the library name and
+    /// `details` lists the folders concerned. Copy deployment suits these drives.
+    UnsuitableVolume,
+}
+
+/// A non-fatal condition met during an operation, summarized by the frontend afterwards.
+#[derive(Serialize, Deserialize, Type, Clone, Debug, PartialEq, Eq)]
+pub struct OperationWarning {
+    pub kind: WarningKind,
+    /// Name of the mod (or input) the warning is about.
+    pub subject: String,
+    /// Affected files or archive entries, if any.
+    pub details: Vec<String>,
+}
+
+impl OperationWarning {
+    pub fn new(kind: WarningKind, subject: impl Into<String>) -> Self {
+        Self {
+            kind,
+            subject: subject.into(),
+            details: Vec::new(),
+        }
+    }
+
+    pub fn with_details<T: ToString>(self, details: &[T]) -> Self {
+        Self {
+            details: details.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+}
@@ -1,7 +1,13 @@
+pub mod context;
 pub mod file;
+pub mod http;
 pub mod icon;
 pub mod id;
+pub mod msgpack;
+pub mod naming;
 pub mod process;
+pub mod retry;
+pub mod scan;
 pub mod thread;
 pub mod time;
 pub mod toml;
@@ -0,0 +1,150 @@
+use crate::core::progress::Progress;
+use crate::models::error::SError;
+use crate::models::events::{StageState, StageStatus, TaskStatus};
+use std::cell::RefCell;
+
+/// A task made of named stages run in order, e.g. backup, install and sync.
+/// Each change is reported as the whole stage tree so the frontend can render it as is.
+/// A failed stage skips the rest; sub-tasks may fail without ending their stage.
+pub struct Pipeline<'a> {
+    task: &'a str,
+    sink: &'a dyn Fn(TaskStatus),
+    stages: RefCell<Vec<StageStatus>>,
+}
+
+/// Handle to the running stage, used to report its sub-tasks.
+pub struct Stage<'p, 'a> {
+    pipeline: &'p Pipeline<'a>,
+    index: usize,
+}
+
+impl<'a> Pipeline<'a> {
+    /// Declares every stage up front so the full plan is visible before it starts.
+    pub fn new(task: &'a str, sink: &'a dyn Fn(TaskStatus), stages: &[&str]) -> Self {
+        let stages = stages
+            .iter()
+            .map(|name| StageStatus {
+                name: name.to_string(),
+                state: StageState::Pending,
+                children: Vec::new(),
+            })
+            .collect();
+        Self {
+            task,
+            sink,
+            stages: RefCell::new(stages),
+        }
+    }
+
+    /// Reporter for the per-stage `TaskStatus` events of the same task.
+    pub fn progress(&self) -> Progress<'a> {
+        Progress::new(self.task, self.sink)
+    }
+
+    /// Runs a declared stage. Errors of its sub-tasks are kept on the stage; an error from
+    /// `run` itself fails the stage, skips the remaining ones and is returned.
+    pub fn stage<T>(
+        &self,
+        name: &str,
+        run: impl FnOnce(&Stage<'_, 'a>) -> Result<T, SError>,
+    ) -> Result<T, SError> {
+        let Some(index) = self.stages.borrow().iter().position(|s| s.name == name) else {
+            return Err(SError::Unexpected);
+        };
+        self.update(index, |status| {
+            status.state = StageState::Running { done: 0, total: 0 }
+        });
+
+        let result = run(&Stage {
+            pipeline: self,
+            index,
+        });
+
+        let mut errors = self.stages.borrow()[index]
+            .children
+            .iter()
+            .flat_map(errors_of)
+            .collect::<Vec<SError>>();
+        if let Err(e) = &result {
+            errors.push(e.clone());
+            self.skip_pending();
+        }
+        self.update(index, |status| {
+            status.state = match errors.is_empty() {
+                true => StageState::Done,
+                false => StageState::Failed { errors },
+            }
+        });
+        result
+    }
+
+    /// Every error raised so far, in stage order.
+    pub fn errors(&self) -> Vec<SError> {
+        self.stages.borrow().iter().flat_map(errors_of).collect()
+    }
+
+    pub fn snapshot(&self) -> Vec<StageStatus> {
+        self.stages.borrow().clone()
+    }
+
+    /// Reports how the task ended, passing the result through.
+    pub fn finish<T>(&self, result: Result<T, SError>) -> Result<T, SError> {
+        self.progress().finish(result)
+    }
+
+    fn update(&self, index: usize, change: impl FnOnce(&mut StageStatus)) {
+        change(&mut self.stages.borrow_mut()[index]);
+        self.emit();
+    }
+
+    fn skip_pending(&self) {
+        self.stages
+            .borrow_mut()
+            .iter_mut()
+            .filter(|s| s.state == StageState::Pending)
+            .for_each(|s| s.state = StageState::Skipped);
+    }
+
+    fn emit(&self) {
+        (self.sink)(TaskStatus::Stages {
+            task: self.task.to_string(),
+            stages: self.snapshot(),
+        });
+    }
+}
+
+impl Stage<'_, '_> {
+    /// Updates how many items of the stage are finished.
+    pub fn advance(&self, done: usize, total: usize) {
+        self.pipeline.update(self.index, |status| {
+            status.state = StageState::Running {
+                done: done as u32,
+                total: total as u32,
+            }
+        });
+    }
+
+    /// Records a sub-task and its outcome. A failure is kept on the stage, which carries on
+    /// with the remaining items; the value is only returned on success.
+    pub fn record<T>(&self, name: &str, result: Result<T, SError>) -> Option<T> {
+        let (state, value) = match result {
+            Ok(value) => (StageState::Done, Some(value)),
+            Err(e) => (StageState::Failed { errors: vec![e] }, None),
+        };
+        self.pipeline.update(self.index, |status| {
+            status.children.push(StageStatus {
+                name: name.to_string(),
+                state,
+                children: Vec::new(),
+            })
+        });
+        value
+    }
+}
+
+fn errors_of(status: &StageStatus) -> Vec<SError> {
+    match &status.state {
+        StageState::Failed { errors } => errors.clone(),
+        _ => status.children.iter().flat_map(errors_of).collect(),
+    }
+}
@@ -1,22 +1,87 @@
+use crate::core::progress::Progress;
 use crate::models::error::SError;
+use crate::utils::{retry, scan};
 use camino::Utf8Path;
-use walkdir::WalkDir;
+use std::io::Write;
+use uuid::Uuid;
 
 pub struct FileUtils;
 
 impl FileUtils {
+    /// Whether files can be created in `dir`, probed with a file that is removed right away.
+    /// Permission bits do not reflect share or ACL restrictions, so they are not relied on.
+    pub fn is_writable(dir: &Utf8Path) -> bool {
+        let probe = dir.join(format!(".write-probe-{}", std::process::id()));
+        let created = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&probe)
+            .is_ok();
+        created && std::fs::remove_file(&probe).is_ok()
+    }
+
+    /// Replaces `path` with `contents` through a temporary file next to it, so a crash leaves
+    /// either the previous or the new file and never a partial one.
+    pub fn write_atomic(path: &Utf8Path, contents: impl AsRef<[u8]>) -> Result<(), SError> {
+        let name = path.file_name().unwrap_or_default();
+        let tmp = path.with_file_name(format!(".{name}.{}.tmp", Uuid::new_v4()));
+        let written = std::fs::File::create(&tmp).and_then(|mut file| {
+            file.write_all(contents.as_ref())?;
+            file.sync_all()
+        });
+        let replaced =
+            written.and_then(|_| retry::io("Replacing", path, || std::fs::rename(&tmp, path)));
+        if replaced.is_err() {
+            let _ = std::fs::remove_file(&tmp);
+        }
+        replaced.map_err(Into::into)
+    }
+
     /// Recursively copies a directory tree from source to destination.
     /// Creates all necessary directories and overwrites existing files.
+    /// Each copy is retried while the file is transiently locked.
     pub fn copy_recursive(src: &Utf8Path, dst: &Utf8Path) -> Result<(), SError> {
+        Self::copy_tree(src, dst, |_| {})
+    }
+
+    /// Like `copy_recursive`, reporting the copied bytes under `item` as they are written.
+    pub fn copy_recursive_reported(
+        src: &Utf8Path,
+        dst: &Utf8Path,
+        progress: Progress,
+        item: &str,
+    ) -> Result<(), SError> {
+        let total = scan::walk(src)
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| e.metadata().ok())
+            .map(|meta| meta.len())
+            .sum();
+        let mut meter = progress.meter(item, total);
+        Self::copy_tree(src, dst, |bytes| meter.add(bytes))?;
+        meter.finish();
+        Ok(())
+    }
+
+    fn copy_tree(
+        src: &Utf8Path,
+        dst: &Utf8Path,
+        mut copied: impl FnMut(u64),
+    ) -> Result<(), SError> {
         // 1. Ensure the root destination directory exists
         std::fs::create_dir_all(dst)?;
 
-        for entry in WalkDir::new(src).into_iter().filter_map(|e| e.ok()) {
+        for entry in scan::walk(src).filter_map(|e| e.ok()) {
             // 2. Convert standard Path to Camino Utf8Path
             let src_path = Utf8Path::from_path(entry.path()).ok_or_else(|| {
                 SError::ParseError(format!("Invalid UTF-8 path: {:?}", entry.path()))
             })?;
 
+            // Links to directories are foreign to the payload; linked files are copied by value
+            if entry.path_is_symlink() && !src_path.is_file() {
+                continue;
+            }
+
             // 3. Calculate the relative path from the source root
             let rel_path = src_path.strip_prefix(src)?;
 
@@ -34,10 +99,16 @@ impl FileUtils {
                     }
                 }
                 // 7. Copy the file (Note: This overwrites existing files at the destination)
-                std::fs::copy(src_path, &dst_path)?;
+                let bytes = retry::io("Copying", src_path, || std::fs::copy(src_path, &dst_path))?;
+                copied(bytes);
             }
         }
 
         Ok(())
     }
+
+    /// Removes a directory tree, retrying while a file in it is transiently locked.
+    pub fn remove_recursive(path: &Utf8Path) -> Result<(), SError> {
+        retry::io("Removing", path, || std::fs::remove_dir_all(path)).map_err(Into::into)
+    }
 }
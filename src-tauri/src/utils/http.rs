@@ -0,0 +1,27 @@
+use crate::models::error::SError;
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Longest a download may go without receiving anything before it is given up as stalled.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// HTTP client identifying the app, giving up on a request after `timeout`.
+pub fn client(timeout: Duration) -> Result<reqwest::Client, SError> {
+    Ok(reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent(concat!("ModKeeper/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+/// Blocking client for downloads run on a worker thread. Large archives can take any time to
+/// arrive, so there is no total timeout, only one on connecting and on each read.
+pub fn blocking_client() -> Result<reqwest::blocking::Client, SError> {
+    // The blocking builder has no read timeout of its own, it is set on the async one it wraps
+    let builder = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT);
+    Ok(reqwest::blocking::ClientBuilder::from(builder)
+        .timeout(None)
+        .user_agent(concat!("ModKeeper/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
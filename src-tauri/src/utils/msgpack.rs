@@ -0,0 +1,14 @@
+use crate::models::error::SError;
+
+pub struct MsgPack;
+
+impl MsgPack {
+    /// Encodes with field names so optional and untagged fields round-trip.
+    pub fn to_vec<T: serde::Serialize>(data: &T) -> Result<Vec<u8>, SError> {
+        rmp_serde::to_vec_named(data).map_err(|e| SError::ParseError(e.to_string()))
+    }
+
+    pub fn from_slice<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, SError> {
+        rmp_serde::from_slice(bytes).map_err(|e| SError::ParseError(e.to_string()))
+    }
+}
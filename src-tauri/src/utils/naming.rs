@@ -0,0 +1,100 @@
+use crate::models::error::SError;
+use camino::{Utf8Path, Utf8PathBuf};
+
+/// Longest mod id accepted; ids are nested under `mods/` and `backups/` as folder names.
+pub const MAX_ID_LEN: usize = 128;
+/// Longest display name accepted for mods and libraries.
+pub const MAX_NAME_LEN: usize = 128;
+/// Windows `MAX_PATH`, which Explorer and many tools still enforce.
+pub const MAX_PATH_LEN: usize = 260;
+
+const RESERVED_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+const RESERVED_NAMES: [&str; 4] = ["CON", "PRN", "AUX", "NUL"];
+
+/// Rejects ids that cannot be used as a single folder name on every platform.
+pub fn validate_id(id: &str) -> Result<(), SError> {
+    validate_component(id, MAX_ID_LEN)
+}
+
+/// Rejects names that cannot be used as a single folder name, e.g. for scaffolded mods.
+pub fn validate_folder_name(name: &str) -> Result<(), SError> {
+    validate_component(name, MAX_NAME_LEN)
+}
+
+/// Trims a display name and drops control characters.
+/// Display names never become paths, so only empty and oversized names are rejected.
+pub fn sanitize_name(name: &str) -> Result<String, SError> {
+    let cleaned: String = name.trim().chars().filter(|c| !c.is_control()).collect();
+    match cleaned.chars().count() {
+        0 => Err(SError::InvalidName("Name cannot be empty".to_string())),
+        n if n > MAX_NAME_LEN => Err(SError::InvalidName(format!(
+            "{cleaned} is longer than {MAX_NAME_LEN} characters"
+        ))),
+        _ => Ok(cleaned),
+    }
+}
+
+/// Replaces characters unsafe in folder names with `-`, keeping alphanumerics and `keep`.
+pub fn sanitize(input: &str, keep: &[char]) -> String {
+    input
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || keep.contains(&c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Ensures every file keeps a path within `MAX_PATH_LEN` once placed under `base`.
+pub fn ensure_fits(base: &Utf8Path, files: &[Utf8PathBuf]) -> Result<(), SError> {
+    let Some(longest) = files.iter().max_by_key(|f| f.as_str().len()) else {
+        return Ok(());
+    };
+
+    let path = base.join(longest);
+    if path.as_str().len() > MAX_PATH_LEN {
+        return Err(SError::PathTooLong(path.to_string()));
+    }
+    Ok(())
+}
+
+fn validate_component(value: &str, max_len: usize) -> Result<(), SError> {
+    let invalid = |reason: &str| Err(SError::InvalidName(format!("{value}: {reason}")));
+
+    if value.trim().is_empty() {
+        return Err(SError::InvalidName("Name cannot be empty".to_string()));
+    }
+    if value.chars().count() > max_len {
+        return invalid(&format!("longer than {max_len} characters"));
+    }
+    if let Some(c) = value
+        .chars()
+        .find(|c| c.is_control() || RESERVED_CHARS.contains(c))
+    {
+        return invalid(&format!("contains the reserved character {c:?}"));
+    }
+    if value.ends_with(['.', ' ']) {
+        return invalid("cannot end with a dot or a space");
+    }
+    if is_reserved_name(value) {
+        return invalid("is a reserved device name on Windows");
+    }
+    Ok(())
+}
+
+/// Device names are reserved on Windows regardless of case or extension (`con.txt`).
+fn is_reserved_name(value: &str) -> bool {
+    let stem = value
+        .split('.')
+        .next()
+        .unwrap_or(value)
+        .to_ascii_uppercase();
+    let numbered = |prefix: &str| {
+        stem.strip_prefix(prefix)
+            .is_some_and(|n| n.len() == 1 && n.chars().all(|c| ('1'..='9').contains(&c)))
+    };
+    RESERVED_NAMES.contains(&stem.as_str()) || numbered("COM") || numbered("LPT")
+}
@@ -7,17 +7,24 @@ impl ProcessChecker {
     /// Performs the check. Takes a mutable ref to System to allow
     /// sysinfo to reuse internal buffers for performance.
     pub fn is_running<P: AsRef<Path>>(sys: &mut System, target_paths: &[P]) -> bool {
+        Self::running_flags(sys, target_paths)
+            .into_iter()
+            .any(|running| running)
+    }
+
+    /// Reports for each target whether a matching process exists, with a single refresh.
+    pub fn running_flags<P: AsRef<Path>>(sys: &mut System, target_paths: &[P]) -> Vec<bool> {
         // Refresh only what we need
         sys.refresh_processes();
 
-        sys.processes().values().any(|p| {
-            if let Some(exe_path) = p.exe() {
-                // Check if the current process path matches any of our targets
-                return target_paths
-                    .iter()
-                    .any(|target| exe_path == target.as_ref());
-            }
-            false
-        })
+        target_paths
+            .iter()
+            .map(|target| {
+                sys.processes()
+                    .values()
+                    .filter_map(|p| p.exe())
+                    .any(|exe_path| exe_path == target.as_ref())
+            })
+            .collect()
     }
 }
@@ -0,0 +1,65 @@
+use camino::Utf8Path;
+use std::io;
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
+
+/// Attempts per operation, including the first one.
+const ATTEMPTS: u32 = 4;
+/// Delay before the first retry, doubled on each following one (50, 100, 200ms).
+const BASE_DELAY: Duration = Duration::from_millis(50);
+
+/// Runs a file operation, retrying with exponential backoff while it fails transiently.
+/// Scanners and indexers briefly lock files they just saw being written, which surfaces as
+/// sporadic sharing violations; any other error is returned on the spot.
+pub fn io<T>(
+    action: &str,
+    path: &Utf8Path,
+    mut op: impl FnMut() -> io::Result<T>,
+) -> io::Result<T> {
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Err(e) if is_transient(&e) && attempt < ATTEMPTS => {
+                let delay = BASE_DELAY * 2u32.pow(attempt - 1);
+                warn!(
+                    "{action} {path} is locked ({e}), retry {attempt}/{} in {delay:?}",
+                    ATTEMPTS - 1
+                );
+                thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(e) if is_transient(&e) => {
+                warn!("{action} {path} is still locked after {ATTEMPTS} attempts: {e}");
+                return Err(e);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Errors caused by another process holding the file for a moment, as opposed to missing
+/// files, permissions or collisions that a retry cannot fix.
+pub fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::ResourceBusy
+    ) || is_sharing_violation(e)
+}
+
+/// `ERROR_ACCESS_DENIED` is left out: it is far more often a real permission problem than a
+/// scanner holding the file.
+#[cfg(windows)]
+fn is_sharing_violation(e: &io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    matches!(
+        e.raw_os_error(),
+        Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)
+    )
+}
+
+#[cfg(not(windows))]
+fn is_sharing_violation(_e: &io::Error) -> bool {
+    false
+}
@@ -0,0 +1,40 @@
+use camino::Utf8Path;
+use file_id::{get_file_id, FileId};
+use std::collections::HashSet;
+use std::fs::Metadata;
+use walkdir::{DirEntry, FilterEntry, IntoIter, WalkDir};
+
+/// Walks a directory tree without ever following links.
+/// Symlinks and junctions are yielded as leaves so callers can inspect them, other reparse
+/// point directories are skipped, and a directory reached twice through the same physical ID
+/// is skipped to break cycles.
+pub fn walk(root: &Utf8Path) -> FilterEntry<IntoIter, impl FnMut(&DirEntry) -> bool> {
+    let mut visited: HashSet<FileId> = HashSet::new();
+
+    WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(move |entry| {
+            if !entry.file_type().is_dir() {
+                return true;
+            }
+            if entry.metadata().is_ok_and(|meta| is_reparse_point(&meta)) {
+                return false;
+            }
+            get_file_id(entry.path())
+                .map(|id| visited.insert(id))
+                .unwrap_or(true)
+        })
+}
+
+#[cfg(windows)]
+fn is_reparse_point(meta: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+    meta.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0
+}
+
+#[cfg(not(windows))]
+fn is_reparse_point(_meta: &Metadata) -> bool {
+    false
+}
@@ -9,6 +9,8 @@ where
 {
     let mut guard = handle.lock();
     let lib = guard.as_mut().ok_or(SError::NoActiveLibrary)?;
+    lib.ensure_writable()?;
+    lib.ensure_loaded()?;
     Ok(f(lib))
 }
 
@@ -16,7 +18,9 @@ pub fn with_lib_arc<F, R>(handle: Arc<Mutex<Option<Library>>>, f: F) -> Result<R
 where
     F: FnOnce(&Library) -> R,
 {
-    let guard = handle.lock();
-    let lib = guard.as_ref().ok_or(SError::NoActiveLibrary)?;
+    // Mutable access is only needed to finish a deferred load
+    let mut guard = handle.lock();
+    let lib = guard.as_mut().ok_or(SError::NoActiveLibrary)?;
+    lib.ensure_loaded()?;
     Ok(f(lib))
 }
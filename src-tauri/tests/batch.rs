@@ -0,0 +1,59 @@
+use camino::Utf8PathBuf;
+use mod_keeper_lib::core::batch::{self, BatchTask};
+
+fn args(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn test_parse_batch_tasks() {
+    assert_eq!(
+        batch::parse(&args(&["--sync", "D:/SPT/.mod_keeper"])),
+        Some(Ok(BatchTask::Sync {
+            repo: Utf8PathBuf::from("D:/SPT/.mod_keeper")
+        }))
+    );
+    assert_eq!(
+        batch::parse(&args(&["--serve", "D:/SPT/.mod_keeper"])),
+        Some(Ok(BatchTask::Serve {
+            repo: Utf8PathBuf::from("D:/SPT/.mod_keeper")
+        }))
+    );
+    assert_eq!(
+        batch::parse(&args(&["--add", "a.zip", "b.7z"])),
+        Some(Ok(BatchTask::Add {
+            repo: None,
+            archives: vec!["a.zip".into(), "b.7z".into()],
+        }))
+    );
+    assert_eq!(
+        batch::parse(&args(&["--add", "a.zip", "--repo", "lib"])),
+        Some(Ok(BatchTask::Add {
+            repo: Some("lib".into()),
+            archives: vec!["a.zip".into()],
+        }))
+    );
+}
+
+#[test]
+fn test_parse_without_batch_flag_starts_the_app() {
+    assert_eq!(batch::parse(&[]), None);
+    assert_eq!(batch::parse(&args(&["-psn_0_12345"])), None);
+}
+
+#[test]
+fn test_parse_rejects_incomplete_arguments() {
+    for invalid in [
+        vec!["--sync"],
+        vec!["--sync", "a", "b"],
+        vec!["--serve"],
+        vec!["--add"],
+        vec!["--add", "--repo", "lib"],
+        vec!["--add", "a.zip", "--repo"],
+    ] {
+        assert!(
+            matches!(batch::parse(&args(&invalid)), Some(Err(_))),
+            "{invalid:?} should be rejected"
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use mod_keeper_lib::models::error::SError;
+use mod_keeper_lib::models::events::{StageState, TaskStatus};
+use mod_keeper_lib::utils::context::Pipeline;
+use std::cell::RefCell;
+
+#[test]
+fn test_pipeline_aggregates_errors_per_stage() {
+    let events = RefCell::new(Vec::new());
+    let sink = |status: TaskStatus| events.borrow_mut().push(status);
+    let pipeline = Pipeline::new("update_all", &sink, &["download", "install", "sync"]);
+
+    // 1. A failing sub-task is kept on its stage while the others carry on
+    let downloaded = pipeline
+        .stage("download", |stage| {
+            let ok = stage.record("a", Ok::<_, SError>("a.zip"));
+            let failed = stage.record::<&str>("b", Err(SError::CorruptArchive("b.zip".into())));
+            stage.advance(2, 2);
+            Ok(ok.into_iter().chain(failed).collect::<Vec<_>>())
+        })
+        .unwrap();
+    assert_eq!(downloaded, vec!["a.zip"]);
+
+    // 2. A failing stage skips the rest and returns its error
+    let result = pipeline.stage("install", |_| Err::<(), _>(SError::Unexpected));
+    assert_eq!(result, Err(SError::Unexpected));
+
+    let stages = pipeline.snapshot();
+    assert_eq!(
+        stages[0].state,
+        StageState::Failed {
+            errors: vec![SError::CorruptArchive("b.zip".into())]
+        }
+    );
+    assert_eq!(stages[0].children[0].state, StageState::Done);
+    assert_eq!(
+        stages[1].state,
+        StageState::Failed {
+            errors: vec![SError::Unexpected]
+        }
+    );
+    assert_eq!(stages[2].state, StageState::Skipped);
+    assert_eq!(pipeline.errors().len(), 2);
+
+    // 3. Every change was reported as the full tree, ending with the task outcome
+    let _ = pipeline.finish(result);
+    let events = events.into_inner();
+    assert!(matches!(
+        events.first(),
+        Some(TaskStatus::Stages { stages, .. }) if stages.len() == 3
+    ));
+    assert!(matches!(
+        events.last(),
+        Some(TaskStatus::Failed { task, error: SError::Unexpected }) if task == "update_all"
+    ));
+}
@@ -2,15 +2,83 @@ mod common;
 
 use camino::{Utf8Path, Utf8PathBuf};
 use common::{create_test_mod, setup_test_env};
+use mod_keeper_lib::config::data_dir;
 use mod_keeper_lib::config::global::GlobalConfig;
+use mod_keeper_lib::core::confirmation::Confirmations;
+use mod_keeper_lib::core::decompression::{ArchiveFormat, SkipReason, SkippedEntry};
+use mod_keeper_lib::core::deploy_journal::Action;
+use mod_keeper_lib::core::drop_queue::{DropQueue, Offer, QueuedDrop};
 use mod_keeper_lib::core::library::Library;
+use mod_keeper_lib::core::linker::LinkKind;
 use mod_keeper_lib::core::mod_fs::ModFS;
-use mod_keeper_lib::core::mod_stager::StagedMod;
-use mod_keeper_lib::core::{cleanup, deployment, dto_builder, library_service, mod_manager};
-use mod_keeper_lib::models::error::SError;
-use mod_keeper_lib::models::library::LibraryCreationRequirement;
-use mod_keeper_lib::models::paths::{ModPaths, SPTPathRules};
+use mod_keeper_lib::core::mod_manager::AddOutcome;
+use mod_keeper_lib::core::mod_stager::{StageMaterial, StagedMod};
+use mod_keeper_lib::core::progress::{Progress, Throughput};
+use mod_keeper_lib::core::search_index::SearchIndex;
+use mod_keeper_lib::core::shared_state::SharedState;
+use mod_keeper_lib::core::staging_handler::StagingHandler;
+use mod_keeper_lib::core::update_checker::{PendingCheck, Release};
+use mod_keeper_lib::core::{
+    actionable_issues, batch_import, bepinex, bundled_framework, cache_store, cleanup,
+    compat_notes, confirmation, consistency, crash_guard, database_diff, decompression,
+    dedicated_server, dependency, deploy_journal, deploy_ledger, deployment, dev_watch, download,
+    dto_builder, file_overrides, file_search, fs_watch, game_scan, health_watch, idle_hasher,
+    install_size, launch_checklist, library_discovery, library_service, linker, local_edits,
+    lockfile, logging, manifest_history, mod_backup, mod_documentation, mod_icon, mod_manager,
+    mod_packager, mod_patches, mod_scaffold, mod_stager, modpack, ownership, plan_store,
+    profile_wipe, profiles, recommendations, remote_target, repo_history, repo_store, search_index,
+    server_task, staging_handler, statistics, support_bundle, sync_hook, sync_index,
+    update_checker, version, volume,
+};
+use mod_keeper_lib::models::actionable_issue::{IssueAction, IssueKind};
+use mod_keeper_lib::models::batch_import::ImportOutcome;
+use mod_keeper_lib::models::bepinex::BepInExState;
+use mod_keeper_lib::models::compat_note::{CompatKind, CompatNote};
+use mod_keeper_lib::models::confirmation::DestructiveAction;
+use mod_keeper_lib::models::consistency::{ConsistencyIssue, ConsistencyIssueKind};
+use mod_keeper_lib::models::database_diff::{DatabaseOverlap, FieldChange, RecordChangeKind};
+use mod_keeper_lib::models::deployment_plan::{DeploymentPlan, SyncScope};
+use mod_keeper_lib::models::error::{DependencyIssueKind, SError};
+use mod_keeper_lib::models::events::TaskStatus;
+use mod_keeper_lib::models::global::{
+    ChecklistPolicy, DataDirSource, FrameworkPolicy, LinkFailurePolicy,
+};
+use mod_keeper_lib::models::install_size::InstallSizeLimits;
+use mod_keeper_lib::models::library::{
+    ComparedMod, DeploymentMode, LibraryCreationRequirement, LibraryMode,
+};
+use mod_keeper_lib::models::lockfile::{ActivationFix, LockedMod, Lockfile};
+use mod_keeper_lib::models::logging::{LogLevel, LoggedOperation};
+use mod_keeper_lib::models::mod_backup::BackupRetention;
+use mod_keeper_lib::models::mod_dto::{
+    Appearance, ModManifest, ModMetadata, ModMetadataUpdate, ModPage, PrerequisiteKind, VersionBump,
+};
+use mod_keeper_lib::models::mod_patch::PatchGroup;
+use mod_keeper_lib::models::paths::{ModPaths, SPTPathRules, SptGeneration};
+use mod_keeper_lib::models::recommendation::RecommendationSource;
+use mod_keeper_lib::models::scaffold::{ScaffoldKind, ScaffoldOptions};
+use mod_keeper_lib::models::sync_hook::{HookStage, SyncHooks};
+use mod_keeper_lib::models::volume::VolumeKind;
+use mod_keeper_lib::models::warning::{OperationWarning, WarningKind};
+use mod_keeper_lib::utils::file::FileUtils;
+use mod_keeper_lib::utils::naming;
+use mod_keeper_lib::utils::time::get_unix_timestamp;
+use mod_keeper_lib::utils::toml::Toml;
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+// Helper function to create a library with default settings for testing
+fn new_library(game_root: &Utf8Path, repo_root: &Utf8Path) -> Library {
+    Library::create(LibraryCreationRequirement {
+        repo_root: Some(repo_root.to_path_buf()),
+        game_root: game_root.to_path_buf(),
+        name: "Test Library".to_string(),
+    })
+    .unwrap()
+}
 
 // Helper function to create a StagedMod from a path and ModFS for testing
 fn create_staged_mod_for_test(mod_root: &Utf8Path, fs: ModFS) -> StagedMod {
@@ -24,6 +92,8 @@ fn create_staged_mod_for_test(mod_root: &Utf8Path, fs: ModFS) -> StagedMod {
         source_path: mod_root.to_path_buf(),
         is_staging: false,
         name,
+        framework_files: Vec::new(),
+        warnings: Vec::new(),
     }
 }
 
@@ -112,6 +182,8 @@ fn test_collision_detection() {
             &lib.spt_rules,
             &lib.mods,
             &lib.cache,
+            LinkFailurePolicy::Abort,
+            DeploymentMode::Link,
         )
     });
 
@@ -146,77 +218,4291 @@ fn test_recursive_linking_logic() {
     let setup_mod = |lib: &mut Library, mod_id: &str, file_name: &str| {
         let p = repo_root.join(mod_id);
 
-        // 1. Force the ID using a manifest
-        let manifest_dir = p.join("manifest");
-        fs::create_dir_all(&manifest_dir).unwrap();
-        let manifest_json = format!(
-            r#"{{"id": "{}", "name": "{}", "version": "1", "author": "t", "sptVersion": "3.9.0"}}"#,
-            mod_id, mod_id
-        );
-        fs::write(manifest_dir.join("manifest.json"), manifest_json).unwrap();
+        // 1. Force the ID using a manifest
+        let manifest_dir = p.join("manifest");
+        fs::create_dir_all(&manifest_dir).unwrap();
+        let manifest_json = format!(
+            r#"{{"id": "{}", "name": "{}", "version": "1", "author": "t", "sptVersion": "3.9.0"}}"#,
+            mod_id, mod_id
+        );
+        fs::write(manifest_dir.join("manifest.json"), manifest_json).unwrap();
+
+        // 2. Create the overlapping directory structure
+        let file_path = p.join(&rules.server_mods).join("CommonDir").join(file_name);
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        fs::write(file_path, "data").unwrap();
+
+        // 3. New ModFS will now resolve ID to mod_id ("ModA" or "ModB")
+        let fs = ModFS::new(&p, &rules).unwrap();
+        let staged = create_staged_mod_for_test(&p, fs);
+        mod_manager::add_mod(lib, staged).unwrap();
+
+        // 4. This will no longer panic
+        lib.mods.get_mut(mod_id).unwrap().is_active = true;
+    };
+
+    setup_mod(&mut lib, "ModA", "A.txt");
+    setup_mod(&mut lib, "ModB", "B.txt");
+
+    // Sync using standalone functions
+    cleanup::purge(
+        &lib.game_root,
+        &lib.repo_root,
+        &lib.spt_rules,
+        &lib.lib_paths,
+        &lib.cache,
+    )
+    .unwrap();
+    deployment::deploy(
+        &lib.game_root,
+        &lib.lib_paths,
+        &lib.spt_rules,
+        &lib.mods,
+        &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
+    )
+    .expect("Sync failed");
+    lib.mark_clean();
+    lib.persist().unwrap();
+
+    // ... rest of your assertions ...
+    let common_dir_in_game = game_root.join(&rules.server_mods).join("CommonDir");
+    assert!(common_dir_in_game.exists());
+    assert!(common_dir_in_game.is_dir());
+}
+
+#[test]
+fn test_purge_removes_deactivated_mods() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let requirement = LibraryCreationRequirement {
+        repo_root: Some(repo_root.clone()),
+        game_root: game_root.clone(),
+        name: "Test Library".to_string(),
+    };
+    let mut lib = Library::create(requirement).unwrap();
+    let rules = SPTPathRules::default();
+
+    // 1. Add and activate mod
+    create_test_mod(&repo_root.join("src"), "DeleteMe", true);
+    let fs = ModFS::new(&repo_root.join("src"), &rules).unwrap();
+    let staged = create_staged_mod_for_test(&repo_root.join("src"), fs);
+    mod_manager::add_mod(&mut lib, staged).unwrap();
+    lib.mods.get_mut("DeleteMe").unwrap().is_active = true;
+
+    // Sync
+    cleanup::purge(
+        &lib.game_root,
+        &lib.repo_root,
+        &lib.spt_rules,
+        &lib.lib_paths,
+        &lib.cache,
+    )
+    .unwrap();
+    deployment::deploy(
+        &lib.game_root,
+        &lib.lib_paths,
+        &lib.spt_rules,
+        &lib.mods,
+        &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
+    )
+    .unwrap();
+    lib.mark_clean();
+    lib.persist().unwrap();
+
+    let target_path = game_root.join(&rules.server_mods).join("DeleteMe");
+    assert!(target_path.exists());
+
+    // 2. Deactivate and sync
+    lib.mods.get_mut("DeleteMe").unwrap().is_active = false;
+    cleanup::purge(
+        &lib.game_root,
+        &lib.repo_root,
+        &lib.spt_rules,
+        &lib.lib_paths,
+        &lib.cache,
+    )
+    .unwrap();
+    deployment::deploy(
+        &lib.game_root,
+        &lib.lib_paths,
+        &lib.spt_rules,
+        &lib.mods,
+        &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
+    )
+    .unwrap();
+    lib.mark_clean();
+    lib.persist().unwrap();
+
+    // 3. Verify it's gone from game but exists in repo
+    assert!(!target_path.exists());
+    assert!(lib.lib_paths.mods.join("DeleteMe").exists());
+}
+
+#[test]
+fn test_to_frontend_dto_enrichment() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let requirement = LibraryCreationRequirement {
+        repo_root: Some(repo_root.clone()),
+        game_root: game_root.clone(),
+        name: "Test Library".to_string(),
+    };
+    let mut lib = Library::create(requirement).expect("Failed to create library");
+
+    // 1. Prepare a mod with a real manifest file on disk
+    let mod_src = _tmp.path().join("source_mod");
+    let mod_src_utf8 = Utf8Path::from_path(&mod_src).unwrap();
+
+    let manifest_path = mod_src_utf8.join("manifest/manifest.json");
+    std::fs::create_dir_all(manifest_path.parent().unwrap()).unwrap();
+
+    let manifest_data = r#"{
+        "id": "test-mod-id",
+        "name": "Test Mod Name",
+        "version": "1.0.0",
+        "author": "someone",
+        "sptVersion": "3.9.0"
+    }"#;
+    std::fs::write(&manifest_path, manifest_data).unwrap();
+
+    // 2. Mock some files inside the mod so ModFS::new works
+    let rules = SPTPathRules::default();
+    let dummy_dll = mod_src_utf8
+        .join(&rules.server_mods)
+        .join("TestMod/mod.dll");
+    std::fs::create_dir_all(dummy_dll.parent().unwrap()).unwrap();
+    std::fs::write(dummy_dll, "").unwrap();
+
+    // 3. Add mod to library
+    let fs = ModFS::new(mod_src_utf8, &rules).unwrap();
+    let staged = create_staged_mod_for_test(mod_src_utf8, fs);
+    mod_manager::add_mod(&mut lib, staged).expect("Add mod failed");
+
+    // 4. Check Frontend DTO
+    let dto = dto_builder::build_frontend_dto(&lib);
+    let m = dto.mods.get("test-mod-id").expect("Mod not found in DTO");
+
+    // Assert the manifest was successfully pulled from cache into the DTO
+    assert!(m.manifest.is_some());
+    assert_eq!(m.manifest.as_ref().unwrap().name, "Test Mod Name");
+}
+
+#[test]
+fn test_mod_details_icon_appearance() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    // 1. Prepare a mod declaring both icon variants
+    let mod_src = repo_root.join("src_icons");
+    let manifest_path = mod_src.join("manifest/manifest.json");
+    fs::create_dir_all(manifest_path.parent().unwrap()).unwrap();
+    let manifest_data = r#"{
+        "id": "icon-mod",
+        "name": "Icon Mod",
+        "version": "1.0.0",
+        "author": "someone",
+        "sptVersion": "4.0.0",
+        "icon": "icon.png",
+        "iconDark": "icon_dark.svg"
+    }"#;
+    fs::write(&manifest_path, manifest_data).unwrap();
+    fs::write(mod_src.join("icon.png"), "light").unwrap();
+    fs::write(mod_src.join("icon_dark.svg"), "dark").unwrap();
+
+    let dll = mod_src.join(&rules.server_mods).join("IconMod/mod.dll");
+    fs::create_dir_all(dll.parent().unwrap()).unwrap();
+    fs::write(dll, "").unwrap();
+
+    let mod_fs = ModFS::new(&mod_src, &rules).unwrap();
+    let staged = create_staged_mod_for_test(&mod_src, mod_fs);
+    mod_manager::add_mod(&mut lib, staged).unwrap();
+
+    // 2. Each appearance picks its own variant
+    let light = dto_builder::build_mod_details(&lib, "icon-mod", &Appearance::Light).unwrap();
+    assert!(light.icon_data.unwrap().starts_with("data:image/png"));
+
+    let dark = dto_builder::build_mod_details(&lib, "icon-mod", &Appearance::Dark).unwrap();
+    assert!(dark.icon_data.unwrap().starts_with("data:image/svg+xml"));
+
+    // 3. Dark falls back to the light icon when the dark file is missing
+    fs::remove_file(lib.lib_paths.mods.join("icon-mod/icon_dark.svg")).unwrap();
+    let fallback = dto_builder::build_mod_details(&lib, "icon-mod", &Appearance::Dark).unwrap();
+    assert!(fallback.icon_data.unwrap().starts_with("data:image/png"));
+}
+
+#[test]
+fn test_custom_mod_icon_overrides_manifest_icon() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    let mod_src = repo_root.join("src_custom_icon");
+    create_test_mod(&mod_src, "IconMod", true);
+    let mod_fs = ModFS::new(&mod_src, &rules).unwrap();
+    let mod_id = mod_fs.id.clone();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&mod_src, mod_fs)).unwrap();
+
+    // 1. Unsupported formats are rejected
+    let text = repo_root.join("icon.txt");
+    fs::write(&text, "not an image").unwrap();
+    assert!(mod_icon::set_icon(&mut lib, &mod_id, Some(&text)).is_err());
+
+    // 2. The copied image is shown instead of the manifest icon
+    let image = repo_root.join("artwork.PNG");
+    fs::write(&image, "png").unwrap();
+    mod_icon::set_icon(&mut lib, &mod_id, Some(&image)).unwrap();
+    let details = dto_builder::build_mod_details(&lib, &mod_id, &Appearance::Light).unwrap();
+    assert!(details.icon_data.unwrap().starts_with("data:image/png"));
+
+    // 3. Updating the mod keeps the icon
+    let content = mod_src.join(&rules.server_mods).join("IconMod/content.txt");
+    fs::write(content, "v2").unwrap();
+    let mod_fs = ModFS::new(&mod_src, &rules).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&mod_src, mod_fs)).unwrap();
+    let icon = mod_icon::custom_icon_path(&lib, &mod_id).unwrap();
+    assert!(icon.is_file());
+    assert!(!mod_src.join("manifest/custom-icon.png").exists());
+
+    // 4. Clearing it removes the copy
+    mod_icon::set_icon(&mut lib, &mod_id, None).unwrap();
+    assert!(!icon.exists());
+    assert!(lib.mods[&mod_id].metadata.icon.is_none());
+}
+
+#[test]
+fn test_mod_statistics_counts_links_once() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    create_test_mod(&repo_root.join("src"), "StatMod", true);
+    let fs = ModFS::new(&repo_root.join("src"), &rules).unwrap();
+    let staged = create_staged_mod_for_test(&repo_root.join("src"), fs);
+    mod_manager::add_mod(&mut lib, staged).unwrap();
+    lib.mods.get_mut("StatMod").unwrap().is_active = true;
+
+    deployment::deploy(
+        &lib.game_root,
+        &lib.lib_paths,
+        &lib.spt_rules,
+        &lib.mods,
+        &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
+    )
+    .unwrap();
+
+    // 1. Linked deployment costs no extra space
+    let payload = "StatMod".len() as u64;
+    let stats = statistics::compute(&lib);
+    let entry = stats.mods.iter().find(|m| m.mod_id == "StatMod").unwrap();
+    assert_eq!(entry.repo_bytes, payload);
+    assert_eq!(entry.deployed_bytes, payload);
+    assert_eq!(entry.duplicated_bytes, 0);
+    assert_eq!(stats.apparent_bytes, payload * 2);
+    assert_eq!(stats.actual_bytes, payload);
+
+    // 2. A physical copy in the game root is reported as duplication
+    let deployed_dir = game_root.join(&rules.server_mods).join("StatMod");
+    linker::unlink(&deployed_dir).unwrap();
+    fs::create_dir_all(&deployed_dir).unwrap();
+    fs::write(deployed_dir.join("content.txt"), "StatMod").unwrap();
+
+    let stats = statistics::compute(&lib);
+    let entry = stats.mods.iter().find(|m| m.mod_id == "StatMod").unwrap();
+    assert_eq!(entry.duplicated_bytes, payload);
+    assert_eq!(stats.actual_bytes, payload * 2);
+}
+
+#[test]
+fn test_preview_sync_lists_unlinks_and_created_folders() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for (name, is_server) in [("Alpha", true), ("Beta", false)] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, is_server);
+        let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+        mod_manager::add_mod(&mut lib, staged).unwrap();
+        mod_manager::toggle_mod(&mut lib, name, true).unwrap();
+    }
+
+    // 1. Before the first sync, the mod roots are folders to create
+    let plan = library_service::preview_sync(&lib).unwrap();
+    assert_eq!(plan.links.len(), 2);
+    assert!(plan.unlinks.is_empty());
+    assert!(plan
+        .created_dirs
+        .contains(&game_root.join(&rules.server_mods)));
+    assert!(plan
+        .created_dirs
+        .contains(&game_root.join(&rules.client_plugins)));
+    assert!(!game_root.join(&rules.client_plugins).exists());
+
+    // 2. After a sync, deactivating a mod plans its removal and leaves the game root as is
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    mod_manager::toggle_mod(&mut lib, "Beta", false).unwrap();
+    let plan = library_service::preview_sync(&lib).unwrap();
+    let beta = game_root.join(&rules.client_plugins).join("Beta");
+    assert_eq!(plan.unlinks, vec![beta.clone()]);
+    assert!(plan.created_dirs.is_empty());
+    assert_eq!(plan.links.len(), 1);
+    assert!(fs::symlink_metadata(&beta).is_ok());
+}
+
+#[test]
+fn test_preview_sync_plan_export_roundtrip() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    create_test_mod(&repo_root.join("src"), "PlanMod", true);
+    let fs = ModFS::new(&repo_root.join("src"), &rules).unwrap();
+    let staged = create_staged_mod_for_test(&repo_root.join("src"), fs);
+    mod_manager::add_mod(&mut lib, staged).unwrap();
+    lib.mods.get_mut("PlanMod").unwrap().is_active = true;
+
+    // 1. Planning does not touch the game root
+    let plan = deployment::plan(
+        &lib.game_root,
+        &lib.lib_paths,
+        &lib.spt_rules,
+        &lib.mods,
+        &lib.cache,
+    )
+    .unwrap();
+    let target = game_root.join(&rules.server_mods).join("PlanMod");
+    assert!(!target.exists());
+    assert!(plan.collisions.is_empty());
+    assert_eq!(plan.links.len(), 1);
+    assert_eq!(plan.links[0].target, target);
+
+    // 2. Exported plans can be listed and loaded back
+    let ts = plan.timestamp.clone();
+    let mut first = plan.clone();
+    assert_eq!(plan_store::save(&lib.lib_paths, &mut first).unwrap(), ts);
+    let saved = plan_store::list(&lib.lib_paths).unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].timestamp, ts);
+
+    let loaded = plan_store::load(&lib.lib_paths, &first.timestamp).unwrap();
+    assert_eq!(loaded.links, plan.links);
+
+    // 3. Exports within the same second are numbered instead of overwriting each other, and
+    // load back through the timestamp they were given
+    let mut second = plan.clone();
+    let mut third = plan.clone();
+    third.unlinks = vec![target.clone()];
+    assert_eq!(
+        plan_store::save(&lib.lib_paths, &mut second).unwrap(),
+        format!("{ts}-1")
+    );
+    plan_store::save(&lib.lib_paths, &mut third).unwrap();
+    assert_eq!(third.timestamp, format!("{ts}-2"));
+    let timestamps: Vec<String> = plan_store::list(&lib.lib_paths)
+        .unwrap()
+        .into_iter()
+        .map(|saved| saved.timestamp)
+        .collect();
+    assert_eq!(
+        timestamps,
+        vec![format!("{ts}-2"), format!("{ts}-1"), ts.clone()]
+    );
+    let loaded = plan_store::load(&lib.lib_paths, &third.timestamp).unwrap();
+    assert_eq!(loaded.timestamp, third.timestamp);
+    assert_eq!(loaded.unlinks, vec![target]);
+
+    // 4. Timestamps cannot escape the plans directory
+    assert!(plan_store::load(&lib.lib_paths, "../manifest").is_err());
+    assert!(plan_store::load(&lib.lib_paths, "1-../manifest").is_err());
+}
+
+#[test]
+fn test_dev_source_reimport() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    let source = repo_root.join("dev_build");
+    create_test_mod(&source, "DevMod", true);
+    let fs = ModFS::new(&source, &rules).unwrap();
+    let staged = create_staged_mod_for_test(&source, fs);
+    mod_manager::add_mod(&mut lib, staged).unwrap();
+    lib.mark_clean();
+
+    // 1. A new build output changes the snapshot
+    let before = dev_watch::snapshot(&source);
+    let new_file = source.join(&rules.server_mods).join("DevMod/extra.js");
+    fs::write(&new_file, "console.log('hi')").unwrap();
+    assert_ne!(before, dev_watch::snapshot(&source));
+
+    // 2. Reimport mirrors the source into the repo without creating backups
+    dev_watch::reimport(&mut lib, "DevMod", &source).unwrap();
+    let repo_file = lib
+        .lib_paths
+        .mods
+        .join("DevMod")
+        .join(&rules.server_mods)
+        .join("DevMod/extra.js");
+    assert!(repo_file.exists());
+    assert!(lib.cache.mods["DevMod"]
+        .files
+        .iter()
+        .any(|f| f.ends_with("extra.js")));
+    assert!(!lib.lib_paths.backups.join("DevMod").exists());
+    assert!(lib.to_dto().is_dirty);
+
+    // 3. Unknown mods are rejected
+    assert!(matches!(
+        dev_watch::reimport(&mut lib, "Missing", &source),
+        Err(SError::ModNotFound(_))
+    ));
+}
+
+#[test]
+fn test_package_mod_with_version_bump() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    create_test_mod(&repo_root.join("src"), "PackMod", true);
+    let fs = ModFS::new(&repo_root.join("src"), &rules).unwrap();
+    let staged = create_staged_mod_for_test(&repo_root.join("src"), fs);
+    mod_manager::add_mod(&mut lib, staged).unwrap();
+
+    // 1. Package into a directory with a patch bump
+    let out_dir = repo_root.join("dist");
+    fs::create_dir_all(&out_dir).unwrap();
+    let archive =
+        mod_packager::package_mod(&mut lib, "PackMod", &out_dir, Some(VersionBump::Patch))
+            .expect("Failed to package mod");
+    assert_eq!(archive, out_dir.join("PackMod-1.0.1.zip"));
+
+    // 2. The archive keeps the game layout and ships a manifest and README
+    let mut zip = zip::ZipArchive::new(fs::File::open(&archive).unwrap()).unwrap();
+    let names: Vec<String> = zip.file_names().map(String::from).collect();
+    assert!(names.contains(&"SPT/user/mods/PackMod/content.txt".to_string()));
+    assert!(names.contains(&"SPT/user/mods/PackMod/README.md".to_string()));
+    assert!(!names.contains(&"README.md".to_string()));
+
+    let manifest: serde_json::Value =
+        serde_json::from_reader(zip.by_name("manifest/manifest.json").unwrap()).unwrap();
+    assert_eq!(manifest["version"], "1.0.1");
+
+    // 3. The bump is written back to the repo
+    assert_eq!(lib.cache.manifests["PackMod"].version, "1.0.1");
+    let repo_manifest =
+        ModFS::read_manifest(&ModPaths::new(&lib.lib_paths.mods.join("PackMod")).file).unwrap();
+    assert_eq!(repo_manifest.version, "1.0.1");
+
+    // 4. A packaging that fails keeps the version as it was
+    let unwritable = repo_root.join("missing/PackMod.zip");
+    assert!(
+        mod_packager::package_mod(&mut lib, "PackMod", &unwritable, Some(VersionBump::Minor))
+            .is_err()
+    );
+    assert_eq!(lib.cache.manifests["PackMod"].version, "1.0.1");
+    let repo_manifest =
+        ModFS::read_manifest(&ModPaths::new(&lib.lib_paths.mods.join("PackMod")).file).unwrap();
+    assert_eq!(repo_manifest.version, "1.0.1");
+}
+
+#[test]
+fn test_bump_version() {
+    assert_eq!(
+        mod_packager::bump_version("1.2.3", &VersionBump::Major).unwrap(),
+        "2.0.0"
+    );
+    assert_eq!(
+        mod_packager::bump_version("1.2.3", &VersionBump::Minor).unwrap(),
+        "1.3.0"
+    );
+    assert_eq!(
+        mod_packager::bump_version("1.2.3-beta", &VersionBump::Patch).unwrap(),
+        "1.2.4"
+    );
+}
+
+#[test]
+fn test_scaffold_mod_registers_skeleton() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let workspace = repo_root.join("workspace");
+    fs::create_dir_all(&workspace).unwrap();
+
+    let options = ScaffoldOptions {
+        name: "My Server Mod".to_string(),
+        author: "Dev".to_string(),
+        kind: ScaffoldKind::Server,
+        directory: workspace.clone(),
+    };
+
+    // 1. An SPT 4 server skeleton is an output folder for the DLL, registered under the
+    // manifest id, with its README kept out of the deployed files
+    let (mod_id, root) = mod_scaffold::create(&mut lib, &options).unwrap();
+    assert_eq!(mod_id, "dev.my-server-mod");
+    assert_eq!(root, workspace.join("My Server Mod"));
+    let server_dir = root.join(&rules.server_mods).join("My Server Mod");
+    assert!(server_dir.is_dir());
+    assert!(!server_dir.join("package.json").exists());
+    assert!(lib.mods.contains_key(&mod_id));
+    assert!(lib.cache.mods[&mod_id].files.is_empty());
+    assert!(mod_documentation::read_documentation(&lib, &mod_id)
+        .unwrap()
+        .contains("SPT/user/mods/My Server Mod"));
+
+    // 2. Existing folders are never overwritten
+    assert!(matches!(
+        mod_scaffold::create(&mut lib, &options),
+        Err(SError::AlreadyExists(_))
+    ));
+
+    // 3. Client skeleton goes to the plugins folder
+    let client = ScaffoldOptions {
+        name: "ClientPlugin".to_string(),
+        kind: ScaffoldKind::Client,
+        ..options
+    };
+    let (_, client_root) = mod_scaffold::create(&mut lib, &client).unwrap();
+    let plugin_dir = client_root.join(&rules.client_plugins).join("ClientPlugin");
+    assert!(plugin_dir.is_dir());
+    assert_eq!(fs::read_dir(&plugin_dir).unwrap().count(), 0);
+
+    // 4. Names escaping the chosen directory are rejected
+    let invalid = ScaffoldOptions {
+        name: "../outside".to_string(),
+        ..client.clone()
+    };
+    assert!(mod_scaffold::create(&mut lib, &invalid).is_err());
+
+    // 5. An SPT 3 server skeleton is a script mod whose entry point exists
+    let legacy = ScaffoldOptions {
+        name: "Legacy".to_string(),
+        kind: ScaffoldKind::Server,
+        ..client
+    };
+    let legacy_rules = SPTPathRules::for_generation(SptGeneration::Spt3);
+    let legacy_root =
+        mod_scaffold::write_skeleton(&legacy_rules, SptGeneration::Spt3, "3.11.0", &legacy)
+            .unwrap();
+    let legacy_dir = legacy_root.join(&legacy_rules.server_mods).join("Legacy");
+    let package: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(legacy_dir.join("package.json")).unwrap())
+            .unwrap();
+    assert!(legacy_dir.join(package["main"].as_str().unwrap()).is_file());
+}
+
+#[test]
+fn test_bundled_framework_is_quarantined() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    // A plugin folder that also ships the BepInEx runtime and loader
+    let source = repo_root.join("downloads/FullPack");
+    create_test_mod(&source, "FullPack", false);
+    fs::create_dir_all(source.join("BepInEx/core")).unwrap();
+    fs::write(source.join("BepInEx/core/BepInEx.dll"), "runtime").unwrap();
+    fs::write(source.join("winhttp.dll"), "loader").unwrap();
+    fs::write(source.join("changelog.txt"), "framework changelog").unwrap();
+
+    // 1. Detection needs the runtime; a lone changelog is not a framework
+    assert!(bundled_framework::detect(&[Utf8PathBuf::from("changelog.txt")]).is_empty());
+
+    // 2. Staging keeps only the plugin payload and quarantines the rest
+    let material = lib.stage_material("Unknown".to_string(), FrameworkPolicy::Quarantine);
+    let staged =
+        mod_stager::resolve(std::slice::from_ref(&source), &material, Progress::silent()).unwrap();
+    assert_eq!(staged.len(), 1);
+    let staged = &staged[0];
+    assert_eq!(staged.framework_files.len(), 3);
+    assert!(staged.is_staging);
+    assert!(staged
+        .fs
+        .files
+        .iter()
+        .all(|f| f.starts_with(&rules.client_plugins)));
+    assert!(lib
+        .lib_paths
+        .quarantine
+        .join("FullPack/BepInEx/core/BepInEx.dll")
+        .exists());
+
+    // 3. The user's folder is never modified
+    assert!(source.join("winhttp.dll").exists());
+    mod_stager::clean_up(staged.is_staging, &staged.source_path).unwrap();
+
+    // 4. Strip policy deletes without quarantining
+    let material = lib.stage_material("Unknown".to_string(), FrameworkPolicy::Strip);
+    fs::remove_dir_all(&lib.lib_paths.quarantine).unwrap();
+    let staged =
+        mod_stager::resolve(std::slice::from_ref(&source), &material, Progress::silent()).unwrap();
+    assert_eq!(staged[0].framework_files.len(), 3);
+    assert!(!lib.lib_paths.quarantine.exists());
+}
+
+#[test]
+fn test_purge_keeps_foreign_links() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let lib = new_library(&game_root, &repo_root);
+
+    // User-owned junctions inside the plugins folder, one of them looping back
+    let plugins = game_root.join(&lib.spt_rules.client_plugins);
+    let external = game_root.parent().unwrap().join("external");
+    fs::create_dir_all(&plugins).unwrap();
+    fs::create_dir_all(&external).unwrap();
+    fs::write(external.join("user.dll"), "").unwrap();
+    linker::link(&external, &plugins.join("UserTools")).unwrap();
+    linker::link(&game_root, &plugins.join("Loop")).unwrap();
+
+    cleanup::purge(
+        &lib.game_root,
+        &lib.repo_root,
+        &lib.spt_rules,
+        &lib.lib_paths,
+        &lib.cache,
+    )
+    .unwrap();
+
+    assert!(plugins.join("UserTools/user.dll").exists());
+    assert!(plugins.join("Loop").is_symlink());
+    assert!(external.join("user.dll").exists());
+}
+
+#[test]
+fn test_deferred_library_loading() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let src = repo_root.join("src");
+    create_test_mod(&src, "LazyMod", true);
+    let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+    mod_manager::add_mod(&mut lib, staged).unwrap();
+
+    // 1. Opening only reads the manifest
+    let opened = Library::open(&repo_root).unwrap();
+    assert!(!opened.is_loaded());
+    assert!(opened.cache.mods.is_empty());
+    assert_eq!(opened.to_dto().mod_count, 1);
+
+    // 2. Persisting before the cache is loaded must not wipe it
+    opened.persist().unwrap();
+    assert!(Library::load(&repo_root)
+        .unwrap()
+        .cache
+        .mods
+        .contains_key("LazyMod"));
+
+    // 3. The first operation completes the load
+    let shared = SharedState::new(
+        GlobalConfig::default(),
+        Some(Library::open(&repo_root).unwrap()),
+    );
+    let cached = shared.with_lib(|l| l.cache.mods.len()).unwrap();
+    assert_eq!(cached, 1);
+
+    // 4. Background completion reports readiness for the active library only
+    shared.instance(|instance| *instance = Some(Library::open(&repo_root).unwrap()));
+    let ready = library_service::finish_loading(&shared, &repo_root)
+        .unwrap()
+        .unwrap();
+    assert_eq!(ready.mod_count, 1);
+    assert!(shared.instance(|instance| instance.as_ref().unwrap().is_loaded()));
+    assert!(library_service::finish_loading(&shared, &game_root)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_cache_store_migration_and_incremental_writes() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["StoreA", "StoreB"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+        mod_manager::add_mod(&mut lib, staged).unwrap();
+    }
+
+    // 1. A legacy TOML cache is migrated into the store on load, past a partial earlier attempt
+    let export = cache_store::export_toml(&lib.lib_paths, &lib.cache, None).unwrap();
+    assert_eq!(export, lib.lib_paths.cache_export);
+    cache_store::export_toml(&lib.lib_paths, &lib.cache, Some(&lib.lib_paths.cache)).unwrap();
+    fs::remove_dir_all(&lib.lib_paths.cache_store).unwrap();
+    let partial = repo_root.join("cache.building");
+    fs::create_dir_all(&partial).unwrap();
+    fs::write(partial.join("partial.msgpack"), b"torn").unwrap();
+    let mut lib = Library::load(&repo_root).unwrap();
+    assert_eq!(lib.cache.mods.len(), 2);
+    assert!(lib.cache.manifests.contains_key("StoreA"));
+    assert!(!lib.lib_paths.cache.exists());
+    assert!(!partial.exists());
+    assert_eq!(fs::read_dir(&lib.lib_paths.cache_store).unwrap().count(), 2);
+
+    // 2. Unchanged entries are not rewritten
+    let store = lib.lib_paths.cache_store.clone();
+    let entry_times = || -> Vec<std::time::SystemTime> {
+        fs::read_dir(store.as_path())
+            .unwrap()
+            .map(|e| e.unwrap().metadata().unwrap().modified().unwrap())
+            .collect()
+    };
+    let before = entry_times();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    lib.persist().unwrap();
+    assert_eq!(before, entry_times());
+
+    // 3. Removed mods drop their entry
+    mod_manager::remove_mod(&mut lib, "StoreB").unwrap();
+    let reloaded = Library::load(&repo_root).unwrap();
+    assert_eq!(
+        reloaded.cache.mods.keys().collect::<Vec<_>>(),
+        vec!["StoreA"]
+    );
+    assert_eq!(fs::read_dir(&lib.lib_paths.cache_store).unwrap().count(), 1);
+}
+
+#[test]
+fn test_toggle_persists_only_changes() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["ToggleA", "ToggleB"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+        mod_manager::add_mod(&mut lib, staged).unwrap();
+    }
+
+    let modified = |path: &Utf8Path| fs::metadata(path).unwrap().modified().unwrap();
+    let store_entry = fs::read_dir(lib.lib_paths.cache_store.as_path())
+        .unwrap()
+        .map(|e| Utf8PathBuf::from_path_buf(e.unwrap().path()).unwrap())
+        .next()
+        .unwrap();
+    let cache_before = modified(&store_entry);
+    let manifest_before = modified(&lib.lib_paths.manifest);
+    std::thread::sleep(std::time::Duration::from_millis(20));
+
+    // 1. Batch toggle writes the manifest once and leaves the cache alone
+    let ids = vec!["ToggleA".to_string(), "ToggleB".to_string()];
+    mod_manager::toggle_mods(&mut lib, &ids, true).unwrap();
+    assert!(lib.mods.values().all(|m| m.is_active));
+    assert_ne!(manifest_before, modified(&lib.lib_paths.manifest));
+    assert_eq!(cache_before, modified(&store_entry));
+    let reloaded = Library::load(&repo_root).unwrap();
+    assert!(reloaded.mods.values().all(|m| m.is_active));
+
+    // 2. No-op toggles and unchanged persists do not touch the disk
+    let manifest_after = modified(&lib.lib_paths.manifest);
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    lib.mark_clean();
+    mod_manager::toggle_mod(&mut lib, "ToggleA", true).unwrap();
+    assert!(!lib.to_dto().is_dirty);
+    lib.mark_dirty();
+    lib.persist().unwrap();
+    assert_eq!(manifest_after, modified(&lib.lib_paths.manifest));
+
+    // 3. Unknown ids reject the whole batch
+    let ids = vec!["ToggleA".to_string(), "Missing".to_string()];
+    assert!(matches!(
+        mod_manager::toggle_mods(&mut lib, &ids, false),
+        Err(SError::ModNotFound(_))
+    ));
+    assert!(lib.mods["ToggleA"].is_active);
+}
+
+#[test]
+fn test_add_mod_skips_identical_reinstall() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let src = repo_root.join("src");
+    create_test_mod(&src, "SameMod", true);
+    let add = |lib: &mut Library| {
+        let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+        mod_manager::add_mod(lib, staged).unwrap()
+    };
+
+    assert_eq!(add(&mut lib), AddOutcome::Installed);
+
+    // 1. Re-adding the same content creates no backup
+    assert_eq!(add(&mut lib), AddOutcome::Unchanged);
+    assert!(!lib.lib_paths.backups.join("SameMod").exists());
+
+    // 2. Any content change goes through the regular update path
+    fs::write(
+        src.join(&rules.server_mods).join("SameMod/content.txt"),
+        "v2",
+    )
+    .unwrap();
+    assert_eq!(add(&mut lib), AddOutcome::Updated);
+    assert!(lib.lib_paths.backups.join("SameMod").exists());
+}
+
+#[test]
+fn test_add_mod_migrates_id_when_manifest_appears() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let add = |lib: &mut Library, src: &Utf8Path| {
+        let staged = create_staged_mod_for_test(src, ModFS::new(src, &rules).unwrap());
+        mod_manager::add_mod(lib, staged).unwrap()
+    };
+
+    // Two manifest-less mods, identified by their folder names
+    for name in ["Weather", "Other"] {
+        let src = repo_root.join("v1").join(name);
+        let file = src.join(&rules.server_mods).join(name).join("content.txt");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, "v1").unwrap();
+        assert_eq!(add(&mut lib, &src), AddOutcome::Installed);
+    }
+    let old_id = ModFS::path_id(&rules, &["SPT/user/mods/Weather/content.txt".into()]).unwrap();
+    let other_id = ModFS::path_id(&rules, &["SPT/user/mods/Other/content.txt".into()]).unwrap();
+    lib.mods.get_mut(&old_id).unwrap().is_active = true;
+    compat_notes::upsert(
+        &mut lib,
+        CompatNote {
+            mod_a: old_id.clone(),
+            mod_b: other_id.clone(),
+            kind: CompatKind::Compatible,
+            note: "Fine together".to_string(),
+            unless: None,
+        },
+    )
+    .unwrap();
+
+    // A later release ships a manifest with a proper id
+    let src = repo_root.join("v2");
+    create_test_mod(&src, "Weather", true);
+    assert_eq!(add(&mut lib, &src), AddOutcome::Migrated);
+
+    // 1. The previous entry is replaced, keeping activation, backups and notes
+    assert!(!lib.mods.contains_key(&old_id));
+    assert!(!lib.lib_paths.mods.join(&old_id).exists());
+    assert!(lib.mods["Weather"].is_active);
+    assert_eq!(
+        mod_backup::list_backups(&lib.lib_paths, "Weather")
+            .unwrap()
+            .len(),
+        1
+    );
+    assert!(lib.compat_notes[0].is_pair("Weather", &other_id));
+
+    // 2. Unrelated manifest-less mods are left alone
+    assert!(lib.mods.contains_key(&other_id));
+}
+
+#[test]
+fn test_reinstall_keeps_id_of_earlier_scheme() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let release = |version: &str| {
+        let src = repo_root.join(version);
+        let file = src.join(&rules.server_mods).join("Weather/content.txt");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, version).unwrap();
+        create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap())
+    };
+
+    // Installed by a version using the first id scheme
+    let mut staged = release("v1");
+    let legacy = ModFS::canonical_path_ids(&rules, &staged.fs.files)
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(legacy.scheme, 1);
+    staged.fs.id = legacy.hash();
+    staged.fs.canonical_id = None;
+    mod_manager::add_mod(&mut lib, staged).unwrap();
+
+    // 1. The new release updates that entry instead of adding one under the current scheme
+    let staged = release("v2");
+    assert_ne!(staged.fs.id, legacy.hash());
+    assert_eq!(
+        mod_manager::add_mod(&mut lib, staged).unwrap(),
+        AddOutcome::Updated
+    );
+    assert_eq!(lib.mods.keys().collect::<Vec<_>>(), vec![&legacy.hash()]);
+
+    // 2. Diagnostics show how the id was derived
+    let snapshot = support_bundle::snapshot(&lib);
+    assert_eq!(snapshot.mods[0].canonical_id, Some(legacy));
+}
+
+#[test]
+fn test_backups_labelled_with_version() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let src = repo_root.join("src");
+    create_test_mod(&src, "Versioned", true);
+    let content = src.join(&rules.server_mods).join("Versioned/content.txt");
+    let add = |lib: &mut Library| {
+        let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+        mod_manager::add_mod(lib, staged).unwrap()
+    };
+
+    add(&mut lib);
+    fs::write(&content, "v2").unwrap();
+    assert_eq!(add(&mut lib), AddOutcome::Updated);
+
+    // 1. The backup folder carries the manifest version of the replaced payload
+    let backups = mod_backup::list_backups(&lib.lib_paths, "Versioned").unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(backups[0].version.as_deref(), Some("1.0.0"));
+    assert_eq!(
+        backups[0].path.file_name().unwrap(),
+        format!("1.0.0_{}", backups[0].timestamp)
+    );
+
+    // 2. Folders from before versions were recorded are still listed
+    fs::create_dir_all(lib.lib_paths.backups.join("Versioned/100")).unwrap();
+    let backups = mod_backup::list_backups(&lib.lib_paths, "Versioned").unwrap();
+    assert_eq!(backups[1].timestamp, "100");
+    assert_eq!(backups[1].version, None);
+
+    // 3. Restoring still addresses backups by timestamp
+    let timestamp = backups[0].timestamp.clone();
+    mod_backup::restore_backup(&mut lib, "Versioned", &timestamp).unwrap();
+    let restored = lib
+        .lib_paths
+        .mods
+        .join("Versioned")
+        .join(&rules.server_mods)
+        .join("Versioned/content.txt");
+    assert_eq!(fs::read_to_string(restored).unwrap(), "Versioned");
+}
+
+#[test]
+fn test_restore_takes_a_capped_snapshot_first() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let src = repo_root.join("src");
+    create_test_mod(&src, "Versioned", true);
+    let content = src.join(&rules.server_mods).join("Versioned/content.txt");
+    let add = |lib: &mut Library| {
+        let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+        mod_manager::add_mod(lib, staged).unwrap()
+    };
+    add(&mut lib);
+    mod_manager::toggle_mod(&mut lib, "Versioned", true).unwrap();
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    fs::write(&content, "v2").unwrap();
+    add(&mut lib);
+    let backups = lib.lib_paths.backups.join("Versioned");
+    for old in ["100", "101", "102"] {
+        fs::create_dir_all(backups.join(format!("1.0.0_{old}-restore"))).unwrap();
+    }
+
+    // 1. Restoring into a deployment with unsynced changes warns about it
+    let timestamp = mod_backup::list_backups(&lib.lib_paths, "Versioned").unwrap()[0]
+        .timestamp
+        .clone();
+    let warnings = mod_backup::restore_backup(&mut lib, "Versioned", &timestamp).unwrap();
+    assert_eq!(
+        warnings,
+        vec![OperationWarning::new(
+            WarningKind::RestoredWhileUnsynced,
+            "Versioned"
+        )]
+    );
+
+    // 2. The replaced payload was kept, and only the newest snapshots remain
+    let listed = mod_backup::list_backups(&lib.lib_paths, "Versioned").unwrap();
+    let snapshots: Vec<&str> = listed
+        .iter()
+        .filter(|b| b.pre_restore)
+        .map(|b| b.timestamp.as_str())
+        .collect();
+    assert_eq!(snapshots, vec![timestamp.as_str(), "102", "101"]);
+    assert_eq!(listed.iter().filter(|b| !b.pre_restore).count(), 1);
+    let snapshot = listed.iter().find(|b| b.pre_restore).unwrap();
+    let kept = snapshot
+        .path
+        .join(&rules.server_mods)
+        .join("Versioned/content.txt");
+    assert_eq!(fs::read_to_string(kept).unwrap(), "v2");
+}
+
+#[test]
+fn test_consistency_check_reports_drift() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["Alpha", "Beta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+
+    // 1. A library kept in step by its operations has no issues
+    assert!(consistency::check(&lib).is_empty());
+
+    // 2. Drift between repo, cache and manifest is reported, as are stale staging folders
+    FileUtils::remove_recursive(&lib.lib_paths.mods.join("Alpha")).unwrap();
+    lib.cache.mods.remove("Beta");
+    let stale = fs::File::create(lib.lib_paths.staging.join("stale")).unwrap();
+    stale
+        .set_modified(SystemTime::now() - Duration::from_secs(2 * 60 * 60))
+        .unwrap();
+    fs::create_dir_all(lib.lib_paths.staging.join("running")).unwrap();
+
+    let issue = |kind, subject: &str| ConsistencyIssue {
+        kind,
+        subject: subject.to_string(),
+    };
+    assert_eq!(
+        consistency::check(&lib),
+        vec![
+            issue(ConsistencyIssueKind::MissingRepoFolder, "Alpha"),
+            issue(ConsistencyIssueKind::MissingCacheEntry, "Beta"),
+            issue(ConsistencyIssueKind::StagingLeftover, "stale"),
+        ]
+    );
+}
+
+#[test]
+fn test_repo_history_records_and_restores_states() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    repo_history::enable(&lib.repo_root).unwrap();
+    let src = repo_root.join("src").join("Alpha");
+    create_test_mod(&src, "Alpha", true);
+    let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+    mod_manager::add_staged(&mut lib, vec![staged], Progress::silent()).unwrap();
+    mod_manager::remove_mod(&mut lib, "Alpha").unwrap();
+
+    // 1. Each operation is committed with a message naming it, newest first
+    let history = repo_history::history(&lib.repo_root, 10).unwrap();
+    let messages: Vec<&str> = history.iter().map(|c| c.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec!["Remove Alpha", "Add Alpha", "Start tracking the library"]
+    );
+
+    // 2. Checking out an older state brings the mod back and records the restore
+    repo_history::checkout(&mut lib, &history[1].id).unwrap();
+    assert!(lib.mods.contains_key("Alpha"));
+    assert!(lib.cache.mods.contains_key("Alpha"));
+    assert!(lib.lib_paths.mods.join("Alpha").is_dir());
+    assert!(lib.to_dto().is_dirty);
+    let history = repo_history::history(&lib.repo_root, 10).unwrap();
+    assert_eq!(history.len(), 4);
+    assert!(history[0].message.starts_with("Restore state"));
+
+    // 3. A repo enabled before `.history/` was ignored has it untracked on the next record,
+    // and checking out its older states leaves the generations alone
+    let git = |args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&repo_root)
+            .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    fs::write(repo_root.join(".gitignore"), "staging/\n").unwrap();
+    git(&["add", "--all"]);
+    git(&["commit", "--quiet", "--message", "Older version"]);
+    assert!(!git(&["ls-files", ".history"]).is_empty());
+    let older = repo_history::history(&lib.repo_root, 1).unwrap().remove(0);
+
+    mod_manager::remove_mod(&mut lib, "Alpha").unwrap();
+    assert!(git(&["ls-files", ".history"]).is_empty());
+    assert!(fs::read_to_string(repo_root.join(".gitignore"))
+        .unwrap()
+        .contains(".history/"));
+
+    let generations = manifest_history::generations(&lib.lib_paths).unwrap();
+    repo_history::checkout(&mut lib, &older.id).unwrap();
+    assert!(generations.iter().all(|path| path.exists()));
+    assert!(git(&["ls-files", ".history"]).is_empty());
+}
+
+#[test]
+fn test_mod_pages_stay_stable_across_mutations() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let add = |lib: &mut Library, name: &str| {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    };
+    for name in ["A", "C", "E", "G"] {
+        add(&mut lib, name);
+    }
+    let ids = |page: &ModPage| page.mods.iter().map(|m| m.id.clone()).collect::<Vec<_>>();
+
+    let first = dto_builder::build_mod_page(&lib, None, 2, &Appearance::Light).unwrap();
+    assert_eq!(ids(&first), vec!["A", "C"]);
+    assert_eq!(first.total, 4);
+
+    // 1. Mods added before the cursor and removed after it do not shift the next page
+    add(&mut lib, "B");
+    mod_manager::remove_mod(&mut lib, "E").unwrap();
+    let cursor = first.next_cursor.as_deref();
+    let second = dto_builder::build_mod_page(&lib, cursor, 2, &Appearance::Light).unwrap();
+    assert_eq!(ids(&second), vec!["G"]);
+    assert_eq!(second.next_cursor, None);
+
+    // 2. Cursors are opaque but validated
+    assert!(matches!(
+        dto_builder::build_mod_page(&lib, Some("not base64!"), 2, &Appearance::Light),
+        Err(SError::ParseError(_))
+    ));
+}
+
+#[test]
+fn test_add_mod_rejects_unsafe_ids_and_long_paths() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    // 1. Ids are folder names, so reserved characters and device names are refused up front
+    let src = repo_root.join("src").join("reserved");
+    create_test_mod(&src, "bad:id", true);
+    let fs = ModFS::new(&src, &rules).unwrap();
+    assert!(matches!(
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)),
+        Err(SError::InvalidName(_))
+    ));
+    assert!(naming::validate_id("com1.txt").is_err());
+    assert!(naming::validate_id("trailing.").is_err());
+    assert!(naming::validate_id("author.mod-name").is_ok());
+
+    // 2. Payloads that would exceed MAX_PATH once nested in the repo are refused before copying
+    let src = repo_root.join("src").join("deep");
+    create_test_mod(&src, "Deep", true);
+    let deep = (0..6).fold(src.join(&rules.server_mods).join("Deep"), |dir, i| {
+        dir.join(format!("{i}{}", "x".repeat(50)))
+    });
+    std::fs::create_dir_all(&deep).unwrap();
+    std::fs::write(deep.join("file.txt"), "deep").unwrap();
+    let fs = ModFS::new(&src, &rules).unwrap();
+    assert!(matches!(
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)),
+        Err(SError::PathTooLong(_))
+    ));
+    assert!(!lib.lib_paths.mods.join("Deep").exists());
+
+    // 3. Display names are trimmed and stripped of control characters
+    assert_eq!(naming::sanitize_name("  My\tMod \n").unwrap(), "MyMod");
+    assert!(matches!(
+        naming::sanitize_name(" "),
+        Err(SError::InvalidName(_))
+    ));
+}
+
+#[test]
+fn test_deploy_reports_files_removed_after_linking() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["Alpha", "Beta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        mod_manager::toggle_mod(&mut lib, name, true).unwrap();
+    }
+
+    // 1. A clean deploy verifies every linked file
+    let warnings = deployment::deploy(
+        &lib.game_root,
+        &lib.lib_paths,
+        &lib.spt_rules,
+        &lib.mods,
+        &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
+    )
+    .unwrap();
+    assert!(warnings.is_empty());
+
+    // 2. A scanner deleting a payload file is reported per mod with the missing files
+    let removed = rules.server_mods.join("Alpha").join("content.txt");
+    fs::remove_file(lib.lib_paths.mods.join("Alpha").join(&removed)).unwrap();
+    let warnings = deployment::verify(&lib.game_root, &lib.mods, &lib.cache);
+    assert_eq!(
+        warnings,
+        vec![
+            OperationWarning::new(WarningKind::PossibleAntivirusInterference, "Alpha")
+                .with_details(&[removed])
+        ]
+    );
+}
+
+#[test]
+fn test_scoped_sync_only_touches_its_side() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for (name, is_server) in [("Server", true), ("Client", false)] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, is_server);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        mod_manager::toggle_mod(&mut lib, name, true).unwrap();
+    }
+    let server = game_root.join(&rules.server_mods).join("Server");
+    let client = game_root.join(&rules.client_plugins).join("Client");
+    let sync = |lib: &mut Library, scope| {
+        library_service::sync_scoped(
+            lib,
+            ChecklistPolicy::Inform,
+            LinkFailurePolicy::Abort,
+            scope,
+        )
+        .unwrap()
+    };
+
+    // 1. Server only links the server mods and leaves the library dirty
+    sync(&mut lib, SyncScope::ServerOnly);
+    assert!(server.join("content.txt").exists());
+    assert!(!client.exists());
+    assert!(lib.to_dto().is_dirty);
+    assert!(sync_index::read(&lib.lib_paths).is_none());
+
+    // 2. Client only leaves the deployed server mods alone, even once deactivated
+    mod_manager::toggle_mod(&mut lib, "Server", false).unwrap();
+    sync(&mut lib, SyncScope::ClientOnly);
+    assert!(client.join("content.txt").exists());
+    assert!(server.join("content.txt").exists());
+
+    // 3. A full sync catches up with both sides
+    sync(&mut lib, SyncScope::All);
+    assert!(!server.exists());
+    assert!(client.join("content.txt").exists());
+    assert!(!lib.to_dto().is_dirty);
+}
+
+#[test]
+fn test_sync_hooks_run_only_when_enabled() {
+    let (_tmp, game_root, _repo_root) = setup_test_env();
+    let mut hooks = SyncHooks {
+        pre_sync: Some("echo stopping server".to_string()),
+        post_sync: Some("exit 3".to_string()),
+        ..SyncHooks::default()
+    };
+
+    // 1. Nothing runs before the user opts in
+    assert!(sync_hook::run(&hooks, HookStage::PreSync, &game_root)
+        .unwrap()
+        .is_none());
+
+    // 2. The output is captured and a successful hook lets the sync go on
+    hooks.enabled = true;
+    let pre = sync_hook::run(&hooks, HookStage::PreSync, &game_root)
+        .unwrap()
+        .unwrap();
+    assert!(pre.succeeded());
+    assert!(pre.output.contains("stopping server"));
+    sync_hook::ensure_succeeded(&pre).unwrap();
+
+    // 3. A failing hook reports its exit code
+    let post = sync_hook::run(&hooks, HookStage::PostSync, &game_root)
+        .unwrap()
+        .unwrap();
+    assert_eq!(post.exit_code, Some(3));
+    assert!(matches!(
+        sync_hook::ensure_succeeded(&post),
+        Err(SError::SyncHookFailed(_))
+    ));
+
+    // 4. Around a sync, a failing post hook only warns once the sync ran
+    let warnings =
+        sync_hook::around(&hooks, &game_root, Progress::silent(), || Ok(Vec::new())).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].kind, WarningKind::PostSyncHookFailed);
+
+    // 5. A failing pre hook cancels the sync
+    hooks.pre_sync = Some("exit 1".to_string());
+    let mut synced = false;
+    let result = sync_hook::around(&hooks, &game_root, Progress::silent(), || {
+        synced = true;
+        Ok(Vec::new())
+    });
+    assert!(matches!(result, Err(SError::SyncHookFailed(_))));
+    assert!(!synced);
+
+    // 6. Only the end of a long output is kept
+    #[cfg(unix)]
+    {
+        hooks.pre_sync = Some("head -c 300000 /dev/zero | tr '\\0' x; echo done".to_string());
+        let pre = sync_hook::run(&hooks, HookStage::PreSync, &game_root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(pre.output.len(), 64 * 1024);
+        assert!(pre.output.ends_with("xdone\n"));
+    }
+}
+
+#[test]
+fn test_dedicated_server_skips_client_mods() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for (name, is_server) in [("fika-server", true), ("Client", false)] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, is_server);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        mod_manager::toggle_mod(&mut lib, name, true).unwrap();
+    }
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    let client = game_root.join(&rules.client_plugins).join("Client");
+    assert!(client.join("content.txt").exists());
+
+    // 1. A dedicated server removes the client mod and reports it
+    dedicated_server::set_mode(&mut lib, LibraryMode::DedicatedServer).unwrap();
+    assert!(lib.to_dto().is_dirty);
+    let warnings =
+        library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    assert!(!client.exists());
+    assert!(game_root
+        .join(&rules.server_mods)
+        .join("fika-server/content.txt")
+        .exists());
+    assert_eq!(
+        warnings.iter().map(|w| w.kind).collect::<Vec<_>>(),
+        vec![WarningKind::ClientModIgnored]
+    );
+
+    // 2. The health check asks for the Fika server mod once it is deactivated
+    mod_manager::toggle_mod(&mut lib, "fika-server", false).unwrap();
+    let health = dedicated_server::check(&lib);
+    assert_eq!(health.ignored_client_mods, vec!["Client"]);
+    assert_eq!(health.missing_server_mods, vec!["fika-server"]);
+}
+
+#[test]
+fn test_server_task_requires_a_synced_library() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let src = repo_root.join("src").join("Server");
+    create_test_mod(&src, "Server", true);
+    let fs = ModFS::new(&src, &rules).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    mod_manager::toggle_mod(&mut lib, "Server", true).unwrap();
+
+    // 1. Nothing was deployed yet
+    assert_eq!(
+        server_task::ensure_deployed(&lib),
+        Err(SError::LibraryNotSynced)
+    );
+    assert_eq!(
+        server_task::install(&lib, Utf8Path::new("mod_keeper.exe")),
+        Err(SError::LibraryNotSynced)
+    );
+
+    // 2. A sync brings the deployment up to date, reopening keeps it
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    let reopened = Library::load(&repo_root).unwrap();
+    server_task::ensure_deployed(&reopened).unwrap();
+
+    // 3. Toggling a mod without syncing is caught again
+    mod_manager::toggle_mod(&mut lib, "Server", false).unwrap();
+    assert_eq!(
+        server_task::ensure_deployed(&lib),
+        Err(SError::LibraryNotSynced)
+    );
+
+    // The task starts the app in serve mode for this library
+    let exe = Utf8Path::new("C:/Mod Keeper/mod_keeper.exe");
+    let args = server_task::create_args(&lib, exe).unwrap();
+    assert!(args.contains(&server_task::task_name(&lib)));
+    assert_eq!(
+        args.last().unwrap(),
+        &format!("\"C:/Mod Keeper/mod_keeper.exe\" --serve \"{repo_root}\"")
+    );
+
+    // A trailing separator is dropped so it cannot escape the closing quote
+    lib.repo_root = Utf8PathBuf::from(format!("{repo_root}\\"));
+    let args = server_task::create_args(&lib, exe).unwrap();
+    assert!(args
+        .last()
+        .unwrap()
+        .ends_with(&format!("--serve \"{repo_root}\"")));
+
+    // A command longer than schtasks accepts is refused before running it
+    lib.repo_root = repo_root.join("a".repeat(300));
+    assert!(matches!(
+        server_task::create_args(&lib, exe),
+        Err(SError::ServerTaskFailed(_))
+    ));
+}
+
+#[test]
+fn test_remote_server_push_tracks_copied_files() {
+    let (tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for (name, is_server) in [("Alpha", true), ("Beta", true), ("Client", false)] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, is_server);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        mod_manager::toggle_mod(&mut lib, name, true).unwrap();
+    }
+    let remote = Utf8PathBuf::from_path_buf(tmp.path().join("remote")).unwrap();
+
+    // 1. Without a reachable folder nothing is configured
+    assert_eq!(remote_target::push(&lib), Err(SError::RemoteNotConfigured));
+    assert_eq!(
+        remote_target::set_target(&mut lib, Some(remote.clone())),
+        Err(SError::RemoteUnreachable(remote.to_string()))
+    );
+
+    // 2. Only server files are copied, relative to the remote mods folder
+    fs::create_dir_all(&remote).unwrap();
+    remote_target::set_target(&mut lib, Some(remote.clone())).unwrap();
+    let status = remote_target::push(&lib).unwrap();
+    assert_eq!(status.files, 2);
+    assert!(status.last_push.is_some());
+    assert!(!fs::symlink_metadata(remote.join("Alpha/content.txt"))
+        .unwrap()
+        .is_symlink());
+    assert!(remote.join("Beta/content.txt").exists());
+    assert!(!remote.join("Client").exists());
+
+    // 3. Deactivated mods are removed along with their folders
+    mod_manager::toggle_mod(&mut lib, "Beta", false).unwrap();
+    assert_eq!(remote_target::push(&lib).unwrap().files, 1);
+    assert!(!remote.join("Beta").exists());
+
+    // 4. Clearing the target removes what was pushed
+    remote_target::set_target(&mut lib, None).unwrap();
+    assert!(!remote.join("Alpha").exists());
+    assert!(remote.exists());
+    assert_eq!(remote_target::status(&lib).unwrap(), None);
+}
+
+#[test]
+fn test_compare_libraries_by_mod_id() {
+    let rules = SPTPathRules::default();
+    let create = |names: [&str; 2]| {
+        let (tmp, game_root, repo_root) = setup_test_env();
+        let mut lib = new_library(&game_root, &repo_root);
+        for name in names {
+            let src = repo_root.join("src").join(name);
+            create_test_mod(&src, name, true);
+            let fs = ModFS::new(&src, &rules).unwrap();
+            mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        }
+        (tmp, lib)
+    };
+    let (_tmp_a, lib_a) = create(["Alpha", "Shared"]);
+    let (_tmp_b, mut lib_b) = create(["Shared", "Gamma"]);
+
+    // 1. Identical shared mods are not reported
+    let comparison =
+        library_service::compare_libraries(&lib_a.repo_root, &lib_b.repo_root).unwrap();
+    let ids = |mods: &[ComparedMod]| mods.iter().map(|m| m.id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(&comparison.only_in_a), vec!["Alpha"]);
+    assert_eq!(ids(&comparison.only_in_b), vec!["Gamma"]);
+    assert!(comparison.differences.is_empty());
+
+    // 2. Version and activation differences of shared mods are
+    mod_manager::toggle_mod(&mut lib_b, "Shared", true).unwrap();
+    let manifest = lib_b.cache.manifests.get_mut("Shared").unwrap();
+    manifest.version = "2.0.0".to_string();
+    lib_b.persist().unwrap();
+
+    let comparison =
+        library_service::compare_libraries(&lib_a.repo_root, &lib_b.repo_root).unwrap();
+    let [difference] = comparison.differences.as_slice() else {
+        panic!("expected one difference, got {:?}", comparison.differences);
+    };
+    assert_eq!(
+        (difference.a.version.as_deref(), difference.a.is_active),
+        (Some("1.0.0"), false)
+    );
+    assert_eq!(
+        (difference.b.version.as_deref(), difference.b.is_active),
+        (Some("2.0.0"), true)
+    );
+}
+
+#[test]
+fn test_link_failures_are_reported_in_deploy_order() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["Gamma", "Alpha", "Beta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        mod_manager::toggle_mod(&mut lib, name, true).unwrap();
+    }
+    // Unmanaged files already sit where Alpha and Gamma would be linked
+    for name in ["Gamma", "Alpha"] {
+        let blocker = game_root.join(&rules.server_mods).join(name);
+        fs::create_dir_all(blocker.parent().unwrap()).unwrap();
+        fs::write(&blocker, "user file").unwrap();
+    }
+    let deploy = |policy| {
+        deployment::deploy(
+            &lib.game_root,
+            &lib.lib_paths,
+            &lib.spt_rules,
+            &lib.mods,
+            &lib.cache,
+            policy,
+            DeploymentMode::Link,
+        )
+    };
+    let failed = |result: Result<_, SError>| match result {
+        Err(SError::LinkFailed(failures)) => failures
+            .into_iter()
+            .map(|f| (f.mod_id, f.path))
+            .collect::<Vec<_>>(),
+        other => panic!("expected link failures, got {other:?}"),
+    };
+    let beta = game_root.join(&rules.server_mods).join("Beta");
+
+    // 1. Abort stops at the first failure in mod order
+    assert_eq!(
+        failed(deploy(LinkFailurePolicy::Abort)),
+        vec![(
+            "Alpha".to_string(),
+            rules.server_mods.join("Alpha").to_string()
+        )]
+    );
+    assert!(!beta.exists());
+
+    // 2. Continue links the rest and reports every failure, the same way on every run
+    for _ in 0..2 {
+        assert_eq!(
+            failed(deploy(LinkFailurePolicy::Continue)),
+            vec![
+                (
+                    "Alpha".to_string(),
+                    rules.server_mods.join("Alpha").to_string()
+                ),
+                (
+                    "Gamma".to_string(),
+                    rules.server_mods.join("Gamma").to_string()
+                ),
+            ]
+        );
+    }
+    assert!(beta.exists());
+}
+
+#[test]
+fn test_first_sync_adopts_existing_configs() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let src = repo_root.join("src").join("Alpha");
+    create_test_mod(&src, "Alpha", false);
+    let fs = ModFS::new(&src, &rules).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+
+    let config_dir = game_root.join(&rules.client_config);
+    fs::create_dir_all(&config_dir).unwrap();
+    for name in ["com.someone.alpha.cfg", "BepInEx.cfg", "unrelated.cfg"] {
+        fs::write(config_dir.join(name), "[General]").unwrap();
+    }
+
+    // 1. Configs matching a mod by GUID segment are adopted; the framework's and strangers' are not
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    let adopted = rules.client_config.join("com.someone.alpha.cfg");
+    assert_eq!(
+        lib.config_owners.iter().collect::<Vec<_>>(),
+        vec![(&adopted, &"Alpha".to_string())]
+    );
+    let reopened = Library::load(&repo_root).unwrap();
+    assert_eq!(reopened.config_owners, lib.config_owners);
+
+    // 2. Only the first sync adopts
+    fs::write(config_dir.join("Alpha.cfg"), "[General]").unwrap();
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    assert_eq!(lib.config_owners.len(), 1);
+
+    // 3. Removing the mod releases its configs but keeps the files
+    mod_manager::remove_mod(&mut lib, "Alpha").unwrap();
+    assert!(lib.config_owners.is_empty());
+    assert!(game_root.join(&adopted).exists());
+}
+
+#[test]
+fn test_bulk_metadata_update_is_all_or_nothing() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["Alpha", "Beta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    lib.mark_clean();
+
+    // 1. Partial updates only touch the given fields, tags are normalized
+    let updates = vec![
+        ModMetadataUpdate {
+            mod_id: "Alpha".to_string(),
+            tags: Some(vec![
+                " weapons ".into(),
+                "ai".into(),
+                "weapons".into(),
+                "".into(),
+            ]),
+            priority: Some(5),
+            group: Some("Core".into()),
+            categories: Some(vec!["Gameplay".into(), "AI".into(), "AI".into()]),
+            color: Some(" Red ".into()),
+            ..Default::default()
+        },
+        ModMetadataUpdate {
+            mod_id: "Beta".to_string(),
+            note: Some("Needs a fresh profile".into()),
+            ..Default::default()
+        },
+    ];
+    mod_manager::update_metadata(&mut lib, updates).unwrap();
+    assert_eq!(
+        lib.mods["Alpha"].metadata,
+        ModMetadata {
+            tags: vec!["ai".into(), "weapons".into()],
+            priority: 5,
+            note: None,
+            group: Some("Core".into()),
+            categories: vec!["AI".into(), "Gameplay".into()],
+            color: Some("red".into()),
+            icon: None,
+        }
+    );
+    assert_eq!(
+        lib.mods["Beta"].metadata.note.as_deref(),
+        Some("Needs a fresh profile")
+    );
+    assert!(!lib.to_dto().is_dirty);
+
+    // 2. An unknown mod rejects the whole batch
+    let updates = vec![
+        ModMetadataUpdate {
+            mod_id: "Alpha".to_string(),
+            group: Some("".into()),
+            color: Some("".into()),
+            ..Default::default()
+        },
+        ModMetadataUpdate {
+            mod_id: "Missing".to_string(),
+            ..Default::default()
+        },
+    ];
+    assert!(matches!(
+        mod_manager::update_metadata(&mut lib, updates),
+        Err(SError::ModNotFound(id)) if id == "Missing"
+    ));
+    assert_eq!(lib.mods["Alpha"].metadata.group.as_deref(), Some("Core"));
+    assert_eq!(lib.mods["Alpha"].metadata.color.as_deref(), Some("red"));
+
+    // 3. Metadata survives reopening the library
+    let reopened = Library::open(&repo_root).unwrap();
+    assert_eq!(reopened.mods["Alpha"].metadata.priority, 5);
+    assert_eq!(
+        reopened.mods["Alpha"].metadata.categories,
+        vec!["AI", "Gameplay"]
+    );
+}
+
+#[test]
+fn test_sandbox_sync_leaves_the_install_alone() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let src = repo_root.join("src").join("Alpha");
+    create_test_mod(&src, "Alpha", true);
+    let fs = ModFS::new(&src, &rules).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    mod_manager::toggle_mod(&mut lib, "Alpha", true).unwrap();
+
+    // 1. A valid plan reports what a sync would, without deploying anything
+    let warnings =
+        library_service::sandbox_sync(&lib, ChecklistPolicy::Inform, LinkFailurePolicy::Continue)
+            .unwrap();
+    assert!(warnings.is_empty());
+    assert!(!game_root.join(&rules.server_mods).join("Alpha").exists());
+    assert!(sync_index::read(&lib.lib_paths).is_none());
+    assert!(lib.to_dto().is_dirty);
+
+    // 2. The scratch folder is gone and the repo still holds the mod
+    assert_eq!(fs::read_dir(&lib.lib_paths.staging).unwrap().count(), 0);
+    assert!(lib.lib_paths.mods.join("Alpha").exists());
+}
+
+#[test]
+fn test_read_only_library_refuses_changes() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let src = repo_root.join("src").join("Alpha");
+    create_test_mod(&src, "Alpha", true);
+    let fs = ModFS::new(&src, &rules).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    lib.set_read_only(true).unwrap();
+
+    // 1. The flag survives reopening and is reported to the frontend; what an interrupted
+    // operation of the owner left behind stays for the owner to recover
+    let promoting = lib.lib_paths.staging.join("promote-owner");
+    fs::create_dir_all(&promoting).unwrap();
+    fs::write(&lib.lib_paths.journal, "").unwrap();
+    let journal = lib.lib_paths.journal.clone();
+    drop(lib);
+    library_service::open_library(&mut GlobalConfig::default(), &repo_root).unwrap();
+    assert!(promoting.exists() && journal.exists());
+
+    let lib = Library::open(&repo_root).unwrap();
+    assert!(lib.is_read_only());
+    assert!(dto_builder::build_frontend_dto(&lib).read_only);
+
+    // 2. Mutating access is refused while reading still works
+    let shared = SharedState::new(GlobalConfig::default(), Some(lib));
+    assert_eq!(
+        shared.with_lib_mut(|lib| mod_manager::toggle_mod(lib, "Alpha", true)),
+        Err(SError::LibraryReadOnly)
+    );
+    assert_eq!(shared.with_lib(|lib| lib.mods.len()), Ok(1));
+
+    // 3. Lifting the flag makes the library editable again
+    shared.instance(|instance| instance.as_mut().unwrap().set_read_only(false).unwrap());
+    assert_eq!(
+        shared.with_lib_mut(|lib| mod_manager::toggle_mod(lib, "Alpha", true)),
+        Ok(Ok(()))
+    );
+}
+
+#[test]
+fn test_incremental_sync_deploys_installed_updates() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let install = |lib: &mut Library, name: &str, extra: Option<&str>| {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, false);
+        if let Some(extra) = extra {
+            fs::write(src.join(&rules.client_plugins).join(name).join(extra), name).unwrap();
+        }
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    };
+    let sync = |lib: &mut Library| {
+        library_service::sync_incremental(lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort)
+            .unwrap()
+    };
+    let deployed = |name: &str, file: &str| {
+        game_root
+            .join(&rules.client_plugins)
+            .join(name)
+            .join(file)
+            .exists()
+    };
+
+    // 1. Without a previous sync it falls back to a full one
+    install(&mut lib, "Alpha", None);
+    mod_manager::toggle_mod(&mut lib, "Alpha", true).unwrap();
+    sync(&mut lib);
+    assert!(deployed("Alpha", "content.txt"));
+    assert!(!lib.to_dto().is_dirty);
+
+    // 2. A new inactive mod leaves the deployment as it was
+    install(&mut lib, "Beta", None);
+    sync(&mut lib);
+    assert!(!deployed("Beta", "content.txt"));
+    assert!(!lib.to_dto().is_dirty);
+
+    // 3. An update of an active mod with new files is deployed as well
+    install(&mut lib, "Alpha", Some("extra.dll"));
+    sync(&mut lib);
+    assert!(deployed("Alpha", "extra.dll"));
+    assert!(!lib.to_dto().is_dirty);
+}
+
+#[test]
+fn test_switch_active_only_relinks_the_difference() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let add = |lib: &mut Library, name: &str, file: Option<&str>| {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        if let Some(file) = file {
+            let common = src.join(&rules.client_plugins).join("Common");
+            fs::create_dir_all(&common).unwrap();
+            fs::write(common.join(file), name).unwrap();
+        }
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    };
+    add(&mut lib, "Alpha", None);
+    add(&mut lib, "Beta", Some("beta.dll"));
+    add(&mut lib, "Gamma", Some("gamma.dll"));
+    let ids = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    let common = game_root.join(&rules.client_plugins).join("Common");
+    let link_id =
+        |rel: &str| linker::get_id(&game_root.join(&rules.server_mods).join(rel)).unwrap();
+
+    library_service::switch_active(
+        &mut lib,
+        &ids(&["Alpha", "Beta"]),
+        ChecklistPolicy::Inform,
+        LinkFailurePolicy::Abort,
+    )
+    .unwrap();
+    let alpha = link_id("Alpha");
+    assert!(common.is_symlink());
+
+    // 1. Kept links stay untouched while added and removed ones are applied
+    library_service::switch_active(
+        &mut lib,
+        &ids(&["Alpha", "Gamma"]),
+        ChecklistPolicy::Inform,
+        LinkFailurePolicy::Abort,
+    )
+    .unwrap();
+    assert_eq!(link_id("Alpha"), alpha);
+    assert!(!game_root.join(&rules.server_mods).join("Beta").exists());
+    assert!(common.join("gamma.dll").exists() && !common.join("beta.dll").exists());
+    assert!(!lib.to_dto().is_dirty);
+
+    // 2. A folder link becomes a shared folder of file links and back
+    library_service::switch_active(
+        &mut lib,
+        &ids(&["Alpha", "Beta", "Gamma"]),
+        ChecklistPolicy::Inform,
+        LinkFailurePolicy::Abort,
+    )
+    .unwrap();
+    assert!(!common.is_symlink());
+    assert!(common.join("beta.dll").exists() && common.join("gamma.dll").exists());
+    library_service::switch_active(
+        &mut lib,
+        &ids(&["Beta"]),
+        ChecklistPolicy::Inform,
+        LinkFailurePolicy::Abort,
+    )
+    .unwrap();
+    assert!(common.is_symlink() && common.join("beta.dll").exists());
+    assert!(!game_root.join(&rules.server_mods).join("Alpha").exists());
+
+    // 3. Without the index of the last sync, a full sync is used instead
+    fs::remove_file(&lib.lib_paths.sync_index).unwrap();
+    library_service::switch_active(
+        &mut lib,
+        &ids(&["Gamma"]),
+        ChecklistPolicy::Inform,
+        LinkFailurePolicy::Abort,
+    )
+    .unwrap();
+    assert!(common.is_symlink() && common.join("gamma.dll").exists());
+    assert!(matches!(
+        library_service::switch_active(
+            &mut lib,
+            &ids(&["Missing"]),
+            ChecklistPolicy::Inform,
+            LinkFailurePolicy::Abort
+        ),
+        Err(SError::ModNotFound(_))
+    ));
+}
+
+#[test]
+fn test_find_mods_by_file() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for (name, active) in [("Alpha", false), ("Beta", true), ("Gamma", false)] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, false);
+        if name != "Gamma" {
+            let plugin = src.join(&rules.client_plugins).join(name);
+            fs::write(plugin.join("ConfigurationManager.dll"), name).unwrap();
+        }
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        mod_manager::toggle_mod(&mut lib, name, active).unwrap();
+    }
+    let providers = |pattern: &str| {
+        file_search::find_mods_by_file(&lib, pattern)
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.mod_id, m.is_active))
+            .collect::<Vec<_>>()
+    };
+
+    // 1. Plain names match file names case-insensitively, active providers first
+    let expected = vec![("Beta".to_string(), true), ("Alpha".to_string(), false)];
+    assert_eq!(providers("configurationmanager.dll"), expected);
+    assert_eq!(providers("Configuration*.dll"), expected);
+
+    // 2. Patterns with a folder match the relative path; `*` stays within a folder
+    assert_eq!(providers("BepInEx/plugins/*/content.txt").len(), 3);
+    assert!(providers("BepInEx/*/content.txt").is_empty());
+    assert_eq!(
+        providers("**/Gamma/*.txt"),
+        vec![("Gamma".to_string(), false)]
+    );
+    assert!(providers("").is_empty());
+}
+
+#[test]
+fn test_search_index_persists() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["Alpha", "Beta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, false);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+
+    // 1. The first search builds the index and writes it
+    assert!(search_index::read(&lib.lib_paths).is_none());
+    assert_eq!(file_search::find_mods(&lib, "alp"), vec!["Alpha"]);
+    let written = search_index::read(&lib.lib_paths).unwrap();
+    assert_eq!(written, SearchIndex::build(&lib));
+
+    // 2. Removing a mod updates the written index
+    mod_manager::remove_mod(&mut lib, "Alpha").unwrap();
+    let written = search_index::read(&lib.lib_paths).unwrap();
+    assert!(!written.mods.contains_key("Alpha"));
+    assert!(file_search::find_mods(&lib, "alpha").is_empty());
+    let providers = file_search::find_mods_by_file(&lib, "content.txt").unwrap();
+    assert_eq!(providers.len(), 1);
+
+    // 3. A restart reads the index instead of rebuilding it
+    let mut doctored = written;
+    doctored
+        .terms
+        .insert("phantom".into(), ["Beta".to_string()].into());
+    search_index::write(&lib.lib_paths, &doctored).unwrap();
+    let mut reopened = Library::load(&repo_root).unwrap();
+    reopened.ensure_loaded().unwrap();
+    assert_eq!(file_search::find_mods(&reopened, "phantom"), vec!["Beta"]);
+
+    // 4. Mods changed behind its back are indexed again
+    let mut restarted = Library::load(&repo_root).unwrap();
+    restarted.ensure_loaded().unwrap();
+    restarted.mods.get_mut("Beta").unwrap().name = "Renamed".into();
+    assert_eq!(file_search::find_mods(&restarted, "renamed"), vec!["Beta"]);
+    assert!(file_search::find_mods(&restarted, "phantom").is_empty());
+}
+
+#[test]
+fn test_global_config_migrates_legacy_keys() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = Utf8PathBuf::from_path_buf(tmp.path().join("config.toml")).unwrap();
+    let root = |name: &str| Utf8PathBuf::from_path_buf(tmp.path().join(name)).unwrap();
+    fs::write(
+        &path,
+        format!(
+            "last_opened_instance = {:?}\nknown_instance_paths = [{:?}, {:?}, \"relative/lib\"]\n",
+            root("b"),
+            root("a"),
+            root("b/"),
+        ),
+    )
+    .unwrap();
+
+    // 1. Configs without `known_libraries` still load, with the legacy list moved over
+    let mut config: GlobalConfig = Toml::read(&path).unwrap();
+    assert!(config.migrate());
+    assert_eq!(config.known_libraries, vec![root("b"), root("a")]);
+
+    // 2. Legacy keys are not written back, so the migration runs once
+    Toml::write(&path, &config).unwrap();
+    let mut reloaded: GlobalConfig = Toml::read(&path).unwrap();
+    assert!(!reloaded.migrate());
+    assert_eq!(reloaded.known_libraries, config.known_libraries);
+}
+
+#[test]
+fn test_data_dir_overrides() {
+    let exe_dir = Some(Utf8PathBuf::from("/opt/mod_keeper"));
+
+    // 1. The portable flag wins and keeps data next to the executable
+    let portable = data_dir::resolve(true, Some("/elsewhere"), exe_dir.clone());
+    assert_eq!(portable.source, DataDirSource::Portable);
+    assert_eq!(
+        portable.path,
+        Some(Utf8PathBuf::from("/opt/mod_keeper/data"))
+    );
+
+    // 2. The environment variable applies when set to something
+    let env = data_dir::resolve(false, Some("/elsewhere"), exe_dir.clone());
+    assert_eq!(env.source, DataDirSource::Environment);
+    assert_eq!(env.path, Some(Utf8PathBuf::from("/elsewhere")));
+    assert_eq!(
+        data_dir::resolve(false, Some("  "), exe_dir).source,
+        DataDirSource::Default
+    );
+}
+
+#[test]
+fn test_add_reports_stage_progress() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let inputs: Vec<Utf8PathBuf> = ["Alpha", "Beta"]
+        .iter()
+        .map(|name| {
+            let src = repo_root.join("src").join(name);
+            create_test_mod(&src, name, true);
+            src
+        })
+        .collect();
+    let material = lib.stage_material("Unknown".to_string(), FrameworkPolicy::Quarantine);
+
+    let statuses = std::cell::RefCell::new(Vec::new());
+    let sink = |status: TaskStatus| statuses.borrow_mut().push(status);
+    let progress = Progress::new("add_mods", &sink);
+
+    // 1. Every input is reported while staging, every mod while copying, then the outcome
+    let result = mod_stager::resolve(&inputs, &material, progress)
+        .and_then(|staged| mod_manager::add_staged(&mut lib, staged, progress));
+    progress.finish(result).unwrap();
+    let task = "add_mods".to_string();
+    let (transfers, stages): (Vec<TaskStatus>, Vec<TaskStatus>) = statuses
+        .take()
+        .into_iter()
+        .partition(|status| matches!(status, TaskStatus::Transfer { .. }));
+    assert_eq!(
+        stages,
+        vec![
+            TaskStatus::Staging {
+                task: task.clone(),
+                file: inputs[0].to_string(),
+                index: 0,
+                total: 2
+            },
+            TaskStatus::Staging {
+                task: task.clone(),
+                file: inputs[1].to_string(),
+                index: 1,
+                total: 2
+            },
+            TaskStatus::CopyingToRepo {
+                task: task.clone(),
+                mod_name: "Alpha".to_string(),
+                index: 0,
+                total: 2
+            },
+            TaskStatus::CopyingToRepo {
+                task: task.clone(),
+                mod_name: "Beta".to_string(),
+                index: 1,
+                total: 2
+            },
+            TaskStatus::Done { task: task.clone() },
+        ]
+    );
+    // Each copy into the repo ends with its full byte count
+    let copied: Vec<(&str, bool)> = transfers
+        .iter()
+        .filter_map(|status| match status {
+            TaskStatus::Transfer {
+                item,
+                done_bytes,
+                total_bytes,
+                ..
+            } => Some((item.as_str(), done_bytes == total_bytes && *total_bytes > 0)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(copied.last(), Some(&("Beta", true)));
+    assert!(copied.contains(&("Alpha", true)));
+
+    // 2. Failures carry the typed error
+    let _ = progress.finish::<()>(Err(SError::NoActiveLibrary));
+    assert_eq!(
+        statuses.take(),
+        vec![TaskStatus::Failed {
+            task,
+            error: SError::NoActiveLibrary
+        }]
+    );
+}
+
+#[test]
+fn test_batch_import_skips_duplicates() {
+    let (tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let material = lib.stage_material("Unknown".to_string(), FrameworkPolicy::Quarantine);
+    let downloads = Utf8PathBuf::from_path_buf(tmp.path().join("downloads")).unwrap();
+    fs::create_dir_all(&downloads).unwrap();
+    // Archives are imported by age, oldest first
+    let archive = |file: &str, folder: &str, content: &[u8], age: u64| {
+        let path = downloads.join(file);
+        let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        zip.start_file(
+            format!("SPT/user/mods/{folder}/content.txt"),
+            zip::write::SimpleFileOptions::default(),
+        )
+        .unwrap();
+        zip.write_all(content).unwrap();
+        zip.finish()
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(age))
+            .unwrap();
+    };
+    let import = |lib: &mut Library| {
+        let archives = batch_import::find_archives(&downloads).unwrap();
+        let staged = batch_import::stage(&archives, &material, Progress::silent());
+        batch_import::install(lib, staged, Progress::silent())
+            .entries
+            .into_iter()
+            .map(|e| {
+                (
+                    e.input.file_name().unwrap().to_string(),
+                    e.mod_name,
+                    e.outcome,
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    archive("Beta.zip", "Beta", b"beta", 2);
+    let entries = import(&mut lib);
+    assert_eq!(entries[0].2, ImportOutcome::Installed);
+
+    archive("Alpha-1.zip", "Alpha", b"v1", 1);
+    archive("Alpha-2.zip", "Alpha", b"v2", 4);
+    fs::write(downloads.join("Broken.zip"), "not a zip").unwrap();
+    fs::File::options()
+        .write(true)
+        .open(downloads.join("Broken.zip"))
+        .unwrap()
+        .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(3))
+        .unwrap();
+    fs::write(downloads.join("readme.txt"), "notes").unwrap();
+
+    // 1. Installed mods and older downloads of a mod are skipped, broken ones reported
+    let entries = import(&mut lib);
+    let summary: Vec<(&str, Option<&str>)> = entries
+        .iter()
+        .map(|(file, name, _)| (file.as_str(), name.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("Alpha-1.zip", Some("Alpha-1")),
+            ("Beta.zip", Some("Beta")),
+            ("Broken.zip", None),
+            ("Alpha-2.zip", Some("Alpha-2")),
+        ]
+    );
+    assert_eq!(entries[0].2, ImportOutcome::Superseded);
+    assert_eq!(entries[1].2, ImportOutcome::AlreadyInstalled);
+    assert!(matches!(entries[2].2, ImportOutcome::Failed { .. }));
+    assert_eq!(entries[3].2, ImportOutcome::Installed);
+
+    // 2. Only the newest download is in the library, and nothing is left in staging
+    assert_eq!(lib.mods.len(), 2);
+    let alpha = lib.mods.values().find(|m| m.name == "Alpha-2").unwrap();
+    let content = lib
+        .lib_paths
+        .mods
+        .join(&alpha.id)
+        .join("SPT/user/mods/Alpha/content.txt");
+    assert_eq!(fs::read_to_string(content).unwrap(), "v2");
+    assert_eq!(fs::read_dir(&lib.lib_paths.staging).unwrap().count(), 0);
+}
+
+#[test]
+fn test_staging_reports_warnings() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let lib = new_library(&game_root, &repo_root);
+
+    // An archive without manifest that also tries to escape the extraction folder
+    let archive = repo_root.join("downloads/Loose.zip");
+    fs::create_dir_all(archive.parent().unwrap()).unwrap();
+    let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file("BepInEx/plugins/Loose/loose.dll", options)
+        .unwrap();
+    zip.write_all(b"plugin").unwrap();
+    zip.start_file("../escape.dll", options).unwrap();
+    zip.write_all(b"evil").unwrap();
+    zip.add_symlink("BepInEx/plugins/Loose/link.dll", "/etc/passwd", options)
+        .unwrap();
+    zip.finish().unwrap();
+
+    // 1. Extraction reports skipped entries with a reason and the unpacked size
+    let report =
+        decompression::extract(&archive, &repo_root.join("extracted"), Progress::silent()).unwrap();
+    assert_eq!(report.extracted_bytes, 6);
+    assert_eq!(
+        report.skipped,
+        vec![
+            SkippedEntry {
+                name: "../escape.dll".to_string(),
+                reason: SkipReason::UnsafePath,
+            },
+            SkippedEntry {
+                name: "BepInEx/plugins/Loose/link.dll".to_string(),
+                reason: SkipReason::Symlink,
+            },
+        ]
+    );
+    assert!(!repo_root
+        .join("extracted/BepInEx/plugins/Loose/link.dll")
+        .exists());
+
+    // 2. Staging infers the name and reports the skipped entries
+    let material = lib.stage_material("Unknown".to_string(), FrameworkPolicy::Quarantine);
+    let mut staged = mod_stager::resolve(
+        std::slice::from_ref(&archive),
+        &material,
+        Progress::silent(),
+    )
+    .unwrap();
+    let staged = staged.remove(0);
+    assert_eq!(staged.name, "Loose");
+    assert!(!repo_root.join("escape.dll").exists());
+    assert_eq!(
+        staged.warnings,
+        vec![
+            OperationWarning::new(WarningKind::InferredManifest, "Loose"),
+            OperationWarning::new(WarningKind::SkippedUnsafeEntry, "Loose").with_details(&[
+                "../escape.dll (path escapes the destination)",
+                "BepInEx/plugins/Loose/link.dll (symbolic link)",
+            ]),
+        ]
+    );
+
+    // 3. A manifest resolves the name without warnings
+    let source = repo_root.join("downloads/Named");
+    create_test_mod(&source, "Named", false);
+    let staged =
+        mod_stager::resolve(std::slice::from_ref(&source), &material, Progress::silent()).unwrap();
+    assert!(staged[0].warnings.is_empty());
+}
+
+#[test]
+fn test_7z_archives_are_staged_by_signature() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let lib = new_library(&game_root, &repo_root);
+
+    let mod_src = repo_root.join("src_seven");
+    create_test_mod(&mod_src, "SevenMod", true);
+    let archive = repo_root.join("downloads/SevenMod.7z");
+    sevenz_rust::compress_to_path(&mod_src, &archive).unwrap();
+    // Some downloads are named after the wrong format
+    let misnamed = repo_root.join("downloads/SevenMod-renamed.zip");
+    fs::copy(&archive, &misnamed).unwrap();
+
+    assert_eq!(
+        ArchiveFormat::detect(&misnamed),
+        Some(ArchiveFormat::SevenZip)
+    );
+    assert_eq!(ArchiveFormat::detect(&mod_src), None);
+
+    let material = lib.stage_material("Unknown".to_string(), FrameworkPolicy::Quarantine);
+    let staged = mod_stager::resolve(&[archive, misnamed], &material, Progress::silent()).unwrap();
+    assert_eq!(staged.len(), 2);
+    for mod_ in &staged {
+        assert_eq!(mod_.name, "SevenMod");
+        let content = mod_.source_path.join("SPT/user/mods/SevenMod/content.txt");
+        assert_eq!(fs::read_to_string(content).unwrap(), "SevenMod");
+    }
+}
+
+#[test]
+fn test_corrupt_archive_aborts_staging() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let lib = new_library(&game_root, &repo_root);
+
+    let archive = repo_root.join("downloads/Broken.zip");
+    fs::create_dir_all(archive.parent().unwrap()).unwrap();
+    let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+    let options =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    zip.start_file("BepInEx/plugins/Broken/broken.dll", options)
+        .unwrap();
+    zip.write_all(b"plugin payload").unwrap();
+    zip.finish().unwrap();
+
+    // Flip a byte of the stored payload, as a truncated or damaged download would
+    let mut bytes = fs::read(&archive).unwrap();
+    let at = bytes
+        .windows(b"payload".len())
+        .position(|w| w == b"payload")
+        .unwrap();
+    bytes[at] = b'X';
+    fs::write(&archive, bytes).unwrap();
+
+    let material = lib.stage_material("Unknown".to_string(), FrameworkPolicy::Quarantine);
+    let result = mod_stager::resolve(
+        std::slice::from_ref(&archive),
+        &material,
+        Progress::silent(),
+    );
+    assert!(matches!(result, Err(SError::CorruptArchive(_))));
+
+    // Nothing is left in staging
+    let leftovers = fs::read_dir(&material.root)
+        .map(|entries| entries.count())
+        .unwrap_or(0);
+    assert_eq!(leftovers, 0);
+}
+
+#[test]
+fn test_support_bundle_contents() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    for (name, manifest) in [
+        (
+            "Base",
+            r#"{"id": "Base", "name": "Base", "version": "1.2.0", "author": "test", "sptVersion": "4.0.0"}"#,
+        ),
+        (
+            "Addon",
+            r#"{"id": "Addon", "name": "Addon", "version": "0.1.0", "author": "test", "sptVersion": "4.0.0", "dependencies": {"Base": "^1.0.0", "Missing": "*"}}"#,
+        ),
+    ] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        fs::write(ModPaths::new(&src).file, manifest).unwrap();
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    profiles::create(&mut lib, "Vanilla").unwrap();
+    profiles::create(&mut lib, "Testing").unwrap();
+    lib.mods.get_mut("Base").unwrap().is_active = true;
+
+    // 1. The snapshot lists mod states and declared dependencies, resolved or not
+    let snapshot = support_bundle::snapshot(&lib);
+    let states: Vec<(&str, Option<&str>, bool)> = snapshot
+        .mods
+        .iter()
+        .map(|m| (m.id.as_str(), m.version.as_deref(), m.is_active))
+        .collect();
+    assert_eq!(
+        states,
+        vec![
+            ("Addon", Some("0.1.0"), false),
+            ("Base", Some("1.2.0"), true)
+        ]
+    );
+    assert_eq!(snapshot.dependency_graph["Addon"], vec!["Base", "Missing"]);
+    assert!(snapshot.dependency_graph["Base"].is_empty());
+    // Profiles with their activation sets, the active one as it stands
+    assert_eq!(snapshot.active_profile.as_deref(), Some("Testing"));
+    assert_eq!(snapshot.profiles["Testing"].mods, vec!["Base"]);
+    assert!(snapshot.profiles["Vanilla"].mods.is_empty());
+
+    // 2. The plan kept by the last sync is bundled, not an exported plan nor the pending state
+    assert!(plan_store::last_sync(&lib.lib_paths).unwrap().is_none());
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    let synced = plan_store::last_sync(&lib.lib_paths).unwrap().unwrap();
+    assert!(!synced.links.is_empty());
+    assert!(synced.links.iter().all(|link| link.mod_id == "Base"));
+
+    let mut plan = DeploymentPlan {
+        timestamp: "99999999999".to_string(),
+        ..Default::default()
+    };
+    plan_store::save(&lib.lib_paths, &mut plan).unwrap();
+    lib.mods.get_mut("Base").unwrap().is_active = false;
+
+    let out_dir = repo_root.join("support");
+    fs::create_dir_all(&out_dir).unwrap();
+    let archive = support_bundle::write(&lib, &out_dir).unwrap();
+    assert!(archive.starts_with(&out_dir));
+
+    let mut zip = zip::ZipArchive::new(fs::File::open(&archive).unwrap()).unwrap();
+    let snapshot: serde_json::Value =
+        serde_json::from_reader(zip.by_name("snapshot.json").unwrap()).unwrap();
+    assert_eq!(snapshot["library_name"], "Test Library");
+    assert_eq!(snapshot["mods"].as_array().unwrap().len(), 2);
+
+    let mut bundled_plan = String::new();
+    zip.by_name("plan.toml")
+        .unwrap()
+        .read_to_string(&mut bundled_plan)
+        .unwrap();
+    let bundled_plan: DeploymentPlan = Toml::parse(&bundled_plan).unwrap();
+    assert_eq!(bundled_plan.timestamp, synced.timestamp);
+    assert_eq!(bundled_plan.links.len(), synced.links.len());
+}
+
+#[test]
+fn test_update_replaces_mod_dir_atomically() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    let src = repo_root.join("src");
+    create_test_mod(&src, "SwapMod", false);
+    let legacy = src.join(&rules.client_plugins).join("SwapMod/legacy.dll");
+    fs::write(&legacy, "old").unwrap();
+    let fs = ModFS::new(&src, &rules).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+
+    let installed = lib.lib_paths.mods.join("SwapMod");
+    assert!(installed
+        .join(&rules.client_plugins)
+        .join("SwapMod/legacy.dll")
+        .exists());
+
+    // The new version drops a file; the repo copy must match it exactly
+    fs::remove_file(&legacy).unwrap();
+    let fs = ModFS::new(&src, &rules).unwrap();
+    let outcome = mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    assert_eq!(outcome, AddOutcome::Updated);
+    assert!(!installed
+        .join(&rules.client_plugins)
+        .join("SwapMod/legacy.dll")
+        .exists());
+    assert!(installed
+        .join(&rules.client_plugins)
+        .join("SwapMod/content.txt")
+        .exists());
+
+    // Neither the build dir nor the swapped-out copy is left behind
+    assert_eq!(fs::read_dir(&lib.lib_paths.staging).unwrap().count(), 0);
+}
+
+#[test]
+fn test_collision_check_scoped_to_changed_mods() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    // Alpha and Beta ship different plugin folders; Gamma reuses Alpha's file
+    let add = |lib: &mut Library, id: &str, folder: &str| {
+        let src = repo_root.join("src").join(id);
+        create_test_mod(&src, folder, false);
+        fs::write(
+            ModPaths::new(&src).file,
+            format!(r#"{{"id": "{id}", "name": "{id}", "version": "1.0.0", "author": "test", "sptVersion": "4.0.0"}}"#),
+        )
+        .unwrap();
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        lib.mods.get_mut(id).unwrap().is_active = true;
+    };
+    let plan_collisions = |lib: &Library| {
+        deployment::plan(
+            &lib.game_root,
+            &lib.lib_paths,
+            &lib.spt_rules,
+            &lib.mods,
+            &lib.cache,
+        )
+        .unwrap()
+        .collisions
+    };
+
+    add(&mut lib, "Alpha", "Shared");
+    add(&mut lib, "Beta", "Other");
+    deployment::deploy(
+        &lib.game_root,
+        &lib.lib_paths,
+        &lib.spt_rules,
+        &lib.mods,
+        &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
+    )
+    .unwrap();
+
+    // 1. A successful sync records the deployed set
+    let index = sync_index::read(&lib.lib_paths).unwrap();
+    assert_eq!(index.digests.len(), 2);
+    assert!(index.changed_mods(&lib.mods, &lib.cache).is_empty());
+
+    // 2. A new mod is checked against the indexed ones
+    add(&mut lib, "Gamma", "Shared");
+    let scoped = plan_collisions(&lib);
+    assert_eq!(scoped.len(), 1);
+    assert!(scoped[0].contains("'Alpha' and 'Gamma'"));
+
+    // 3. Same result as a full check
+    fs::remove_file(&lib.lib_paths.sync_index).unwrap();
+    assert_eq!(plan_collisions(&lib), scoped);
+
+    // 4. Indexed owners that were deactivated no longer collide
+    deployment::deploy(
+        &lib.game_root,
+        &lib.lib_paths,
+        &lib.spt_rules,
+        &lib.mods,
+        &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
+    )
+    .unwrap_err();
+    lib.mods.get_mut("Gamma").unwrap().is_active = false;
+    deployment::deploy(
+        &lib.game_root,
+        &lib.lib_paths,
+        &lib.spt_rules,
+        &lib.mods,
+        &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
+    )
+    .unwrap();
+    lib.mods.get_mut("Alpha").unwrap().is_active = false;
+    lib.mods.get_mut("Gamma").unwrap().is_active = true;
+    assert!(plan_collisions(&lib).is_empty());
+}
+
+#[test]
+fn test_launch_checklist_acknowledgement() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    for (name, manifest) in [
+        (
+            "Wipe",
+            r#"{"id": "Wipe", "name": "Wipe", "version": "1.0.0", "author": "test", "sptVersion": "4.0.0", "prerequisites": [{"kind": "serverWipe", "note": "Items changed"}]}"#,
+        ),
+        (
+            "Profile",
+            r#"{"id": "Profile", "name": "Profile", "version": "1.0.0", "author": "test", "sptVersion": "4.0.0", "prerequisites": [{"kind": "newProfile"}]}"#,
+        ),
+    ] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        fs::write(ModPaths::new(&src).file, manifest).unwrap();
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+
+    // 1. Only active mods contribute, so an empty active set needs no acknowledgement
+    assert!(launch_checklist::build(&lib).acknowledged);
+    lib.mods.get_mut("Wipe").unwrap().is_active = true;
+
+    let checklist = launch_checklist::build(&lib);
+    let items: Vec<(&str, PrerequisiteKind, Option<&str>)> = checklist
+        .items
+        .iter()
+        .map(|item| {
+            (
+                item.mod_id.as_str(),
+                item.kind.clone(),
+                item.note.as_deref(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        items,
+        vec![("Wipe", PrerequisiteKind::ServerWipe, Some("Items changed"))]
+    );
+    assert!(!checklist.acknowledged);
+    assert!(matches!(
+        launch_checklist::ensure_acknowledged(&lib, ChecklistPolicy::Block),
+        Err(SError::ChecklistNotAcknowledged)
+    ));
+    assert!(launch_checklist::ensure_acknowledged(&lib, ChecklistPolicy::Inform).is_ok());
+
+    // 2. The acknowledgement survives reopening the library
+    assert!(
+        launch_checklist::acknowledge(&mut lib)
+            .unwrap()
+            .acknowledged
+    );
+    let reopened = Library::open(&repo_root).unwrap();
+    assert_eq!(reopened.acknowledged_checklist, lib.acknowledged_checklist);
+    assert!(launch_checklist::ensure_acknowledged(&lib, ChecklistPolicy::Block).is_ok());
+
+    // 3. A changed checklist has to be acknowledged again
+    lib.mods.get_mut("Profile").unwrap().is_active = true;
+    let checklist = launch_checklist::build(&lib);
+    assert_eq!(checklist.items.len(), 2);
+    assert!(!checklist.acknowledged);
+
+    // 4. A switch the checklist blocks leaves the activation and the manifest as they were
+    lib.mods.get_mut("Profile").unwrap().is_active = false;
+    assert!(matches!(
+        library_service::switch_active(
+            &mut lib,
+            &["Wipe".to_string(), "Profile".to_string()],
+            ChecklistPolicy::Block,
+            LinkFailurePolicy::Abort,
+        ),
+        Err(SError::ChecklistNotAcknowledged)
+    ));
+    assert!(!lib.mods["Profile"].is_active);
+    let stored = Library::read_library_manifest(&repo_root).unwrap();
+    assert!(stored.mods["Wipe"].is_active && !stored.mods["Profile"].is_active);
+}
+
+#[test]
+fn test_wipe_detection_and_profile_reset() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    for (name, manifest) in [
+        (
+            "Traders",
+            r#"{"id": "Traders", "name": "Traders", "version": "1.0.0", "author": "test", "sptVersion": "4.0.0", "effects": ["trader"]}"#,
+        ),
+        (
+            "Cosmetic",
+            r#"{"id": "Cosmetic", "name": "Cosmetic", "version": "1.0.0", "author": "test", "sptVersion": "4.0.0", "effects": ["other"]}"#,
+        ),
+    ] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        fs::write(ModPaths::new(&src).file, manifest).unwrap();
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    for m in lib.mods.values_mut() {
+        m.is_active = true;
+    }
+
+    // 1. Only the database mod is flagged when it is first deployed
+    let expected_added = vec![
+        OperationWarning::new(WarningKind::WipeRecommended, "Traders").with_details(&["added"]),
+    ];
+    assert_eq!(
+        profile_wipe::detect(&lib.mods, &lib.cache, None),
+        expected_added
+    );
+
+    // 2. Once synced, removing it is flagged as well
+    let index = sync_index::SyncIndex::build(&lib.mods, &lib.cache);
+    assert!(profile_wipe::detect(&lib.mods, &lib.cache, Some(&index)).is_empty());
+    lib.mods.get_mut("Traders").unwrap().is_active = false;
+    assert_eq!(
+        profile_wipe::detect(&lib.mods, &lib.cache, Some(&index)),
+        vec![
+            OperationWarning::new(WarningKind::WipeRecommended, "Traders")
+                .with_details(&["removed"])
+        ]
+    );
+
+    // 3. Resetting backs up the profiles before emptying the folder
+    assert_eq!(profile_wipe::reset_profiles(&lib).unwrap(), None);
+    let profiles = SPTPathRules::new(&game_root).server_profiles;
+    fs::create_dir_all(&profiles).unwrap();
+    fs::write(profiles.join("abc.json"), "{}").unwrap();
+
+    let backup = profile_wipe::reset_profiles(&lib).unwrap().unwrap();
+    assert!(backup.starts_with(&lib.lib_paths.profile_backups));
+    assert_eq!(fs::read_to_string(backup.join("abc.json")).unwrap(), "{}");
+    assert!(profiles.exists());
+    assert!(!profiles.join("abc.json").exists());
+}
+
+#[test]
+fn test_managed_roots_deploy_and_purge() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    lib.set_managed_roots(vec!["user/launcher".into()]).unwrap();
+
+    let src = repo_root.join("src/Tool");
+    fs::create_dir_all(src.join("user/launcher/Tool")).unwrap();
+    fs::write(src.join("user/launcher/Tool/config.json"), "{}").unwrap();
+    let fs = ModFS::new(&src, &lib.spt_rules).unwrap();
+    let mod_id = fs.id.clone();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    lib.mods.get_mut(&mod_id).unwrap().is_active = true;
+
+    // The user's own launcher settings live next to the managed content
+    let launcher = game_root.join("user/launcher");
+    fs::create_dir_all(&launcher).unwrap();
+    fs::write(launcher.join("settings.json"), "mine").unwrap();
+
+    deployment::deploy(
+        &lib.game_root,
+        &lib.lib_paths,
+        &lib.spt_rules,
+        &lib.mods,
+        &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
+    )
+    .unwrap();
+    assert!(launcher.join("Tool/config.json").exists());
+
+    // 1. Cleanup reaches into the managed root and leaves foreign files alone
+    cleanup::purge(
+        &lib.game_root,
+        &lib.repo_root,
+        &lib.spt_rules,
+        &lib.lib_paths,
+        &lib.cache,
+    )
+    .unwrap();
+    assert!(!launcher.join("Tool").exists());
+    assert_eq!(
+        fs::read_to_string(launcher.join("settings.json")).unwrap(),
+        "mine"
+    );
+
+    // 2. The roots are part of the library manifest
+    let reopened = Library::open(&repo_root).unwrap();
+    assert_eq!(
+        reopened.spt_rules.managed_roots,
+        vec![Utf8PathBuf::from("user/launcher")]
+    );
+}
+
+#[test]
+fn test_external_tool_uninstall_restores_game_root() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    // Two tools scatter files outside of the mod roots and share a folder
+    for (id, files) in [
+        ("ToolA", vec!["Tools/a.txt", "ToolA.lnk"]),
+        ("ToolB", vec!["Tools/b.txt"]),
+    ] {
+        let src = repo_root.join("src").join(id);
+        fs::create_dir_all(src.join("manifest")).unwrap();
+        fs::write(
+            ModPaths::new(&src).file,
+            format!(r#"{{"id": "{id}", "name": "{id}", "version": "1.0.0", "author": "test", "sptVersion": "4.0.0"}}"#),
+        )
+        .unwrap();
+        for file in files {
+            let path = src.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, id).unwrap();
+        }
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        lib.mods.get_mut(id).unwrap().is_active = true;
+    }
+
+    let deploy = |lib: &Library| {
+        deployment::deploy(
+            &lib.game_root,
+            &lib.lib_paths,
+            &lib.spt_rules,
+            &lib.mods,
+            &lib.cache,
+            LinkFailurePolicy::Abort,
+            DeploymentMode::Link,
+        )
+        .unwrap()
+    };
+    let purge = |lib: &Library| {
+        cleanup::purge(
+            &lib.game_root,
+            &lib.repo_root,
+            &lib.spt_rules,
+            &lib.lib_paths,
+            &lib.cache,
+        )
+        .unwrap()
+    };
+
+    deploy(&lib);
+    assert!(game_root.join("Tools/a.txt").exists());
+    assert!(game_root.join("ToolA.lnk").exists());
+    let ledger = deploy_ledger::read(&lib.lib_paths).unwrap();
+    assert_eq!(ledger.links.len(), 3);
+    assert!(ledger.created_dirs.contains(Utf8Path::new("Tools")));
+
+    // 1. Removal brings the game root back to what it was
+    purge(&lib);
+    assert!(!game_root.join("Tools").exists());
+    assert!(!game_root.join("ToolA.lnk").exists());
+    assert!(!lib.lib_paths.deploy_ledger.exists());
+
+    // 2. Files the user added are kept, and the folder stays recorded as a leftover
+    deploy(&lib);
+    fs::write(game_root.join("Tools/notes.txt"), "mine").unwrap();
+    purge(&lib);
+    assert_eq!(
+        fs::read_dir(game_root.join("Tools")).unwrap().count(),
+        1,
+        "only the user's file remains"
+    );
+    let ledger = deploy_ledger::read(&lib.lib_paths).unwrap();
+    assert!(ledger.links.is_empty());
+    assert!(ledger.created_dirs.contains(Utf8Path::new("Tools")));
+}
+
+#[test]
+fn test_recommendations_after_install() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let staged = |name: &str| {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap())
+    };
+    mod_manager::add_staged(&mut lib, vec![staged("Sounds")], Progress::silent()).unwrap();
+    compat_notes::upsert(
+        &mut lib,
+        CompatNote {
+            mod_a: "Sounds".to_string(),
+            mod_b: "Weapons".to_string(),
+            kind: CompatKind::Recommended,
+            note: "Adds firing sounds".to_string(),
+            unless: None,
+        },
+    )
+    .unwrap();
+
+    // Only optional dependencies are suggestions
+    let weapons = staged("Weapons");
+    let manifest = ModPaths::new(&weapons.source_path).file;
+    fs::write(
+        &manifest,
+        r#"{"id": "Weapons", "name": "Weapons", "version": "1.0.0", "author": "test",
+            "sptVersion": "3.9.0", "dependencies": [
+                {"id": "Core", "version": "1.0.0"},
+                {"id": "Ballistics", "version": "1.0.0", "optional": true}
+            ]}"#,
+    )
+    .unwrap();
+
+    // 1. Installing reports the companions that are not active
+    let warnings = mod_manager::add_staged(&mut lib, vec![weapons], Progress::silent()).unwrap();
+    assert_eq!(
+        warnings,
+        vec![
+            OperationWarning::new(WarningKind::RecommendedMods, "Weapons")
+                .with_details(&["Ballistics", "Sounds"])
+        ]
+    );
+    let recommendations = recommendations::for_mod(&lib, "Weapons").unwrap();
+    assert_eq!(
+        recommendations
+            .iter()
+            .map(|r| (r.mod_id.as_str(), r.name.as_deref(), r.source))
+            .collect::<Vec<_>>(),
+        vec![
+            ("Ballistics", None, RecommendationSource::Manifest),
+            ("Sounds", Some("Sounds"), RecommendationSource::CompatNote),
+        ]
+    );
+
+    // 2. Active companions are no longer suggested
+    mod_manager::toggle_mod(&mut lib, "Sounds", true).unwrap();
+    assert_eq!(recommendations::for_mod(&lib, "Weapons").unwrap().len(), 1);
+    assert_eq!(
+        recommendations::for_mod(&lib, "Missing"),
+        Err(SError::ModNotFound("Missing".to_string()))
+    );
+}
+
+#[test]
+fn test_lockfile_reports_differences() {
+    let rules = SPTPathRules::default();
+    // Beta is patched locally in the second install
+    let create = |names: &[&str], patched: bool| {
+        let (tmp, game_root, repo_root) = setup_test_env();
+        let mut lib = new_library(&game_root, &repo_root);
+        for name in names {
+            let src = repo_root.join("src").join(name);
+            create_test_mod(&src, name, true);
+            if *name == "Beta" && patched {
+                fs::write(src.join("SPT/user/mods/Beta/content.txt"), "patched").unwrap();
+            }
+            let fs = ModFS::new(&src, &rules).unwrap();
+            mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+            mod_manager::toggle_mod(&mut lib, name, true).unwrap();
+        }
+        (tmp, lib)
+    };
+    let (tmp, shared) = create(&["Alpha", "Beta", "Gamma"], false);
+    let lock_path = Utf8PathBuf::from_path_buf(tmp.path().join("modlist.lock")).unwrap();
+    lockfile::export(&shared, &lock_path).unwrap();
+
+    // 1. The lockfile pins every active mod
+    let locked = lockfile::build(&shared).unwrap();
+    assert_eq!(locked.mods.len(), 3);
+    assert_eq!(locked.mods[0].version.as_deref(), Some("1.0.0"));
+    assert!(lockfile::verify(&shared, &lock_path).unwrap().is_empty());
+
+    // 2. Another install reports what differs, in lockfile order
+    let (_tmp, mut other) = create(&["Alpha", "Beta"], true);
+    other.cache.manifests.get_mut("Alpha").unwrap().version = "2.0.0".to_string();
+    let warnings = lockfile::verify(&other, &lock_path).unwrap();
+    assert_eq!(
+        warnings
+            .iter()
+            .map(|w| (w.kind, w.subject.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (WarningKind::LockedVersionMismatch, "Alpha"),
+            (WarningKind::LockedContentMismatch, "Beta"),
+            (WarningKind::LockedModMissing, "Gamma"),
+        ]
+    );
+    assert_eq!(warnings[0].details, vec!["1.0.0", "2.0.0"]);
+}
+
+#[test]
+fn test_lockfile_check_plans_activation_fix() {
+    let rules = SPTPathRules::default();
+    let (tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    for name in ["Alpha", "Beta", "Gamma", "Delta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    mod_manager::toggle_mods(&mut lib, &["Alpha".into(), "Beta".into()], true).unwrap();
+    let lock_path = Utf8PathBuf::from_path_buf(tmp.path().join("friend.lock")).unwrap();
+    lockfile::export(&lib, &lock_path).unwrap();
+
+    // The friend runs Alpha and Beta; locally Beta is off, Gamma is on and Alpha is older
+    mod_manager::toggle_mod(&mut lib, "Beta", false).unwrap();
+    mod_manager::toggle_mod(&mut lib, "Gamma", true).unwrap();
+    mod_manager::remove_mod(&mut lib, "Delta").unwrap();
+    lib.cache.manifests.get_mut("Alpha").unwrap().version = "0.9.0".to_string();
+    let mut locked: Lockfile = Toml::read(&lock_path).unwrap();
+    locked.mods.push(LockedMod {
+        id: "Delta".to_string(),
+        name: "Delta".to_string(),
+        version: Some("1.0.0".to_string()),
+        content_hash: String::new(),
+        source_url: None,
+    });
+    Toml::write(&lock_path, &locked).unwrap();
+
+    // 1. The diff lists every difference and the activation fix
+    let diff = lockfile::check_against(&lib, &lock_path).unwrap();
+    assert_eq!(diff.missing.len(), 1);
+    assert_eq!(diff.missing[0].id, "Delta");
+    assert_eq!(diff.version_mismatches.len(), 1);
+    assert_eq!(diff.version_mismatches[0].id, "Alpha");
+    assert_eq!(
+        diff.version_mismatches[0].installed.as_deref(),
+        Some("0.9.0")
+    );
+    assert_eq!(diff.extra_active, vec!["Gamma"]);
+    assert_eq!(diff.fix.activate, vec!["Beta"]);
+    assert_eq!(diff.fix.deactivate, vec!["Gamma"]);
+
+    // 2. Applying the fix leaves nothing to change
+    lockfile::fix_activation(&mut lib, &lock_path).unwrap();
+    assert!(lib.mods["Beta"].is_active);
+    assert!(!lib.mods["Gamma"].is_active);
+    let diff = lockfile::check_against(&lib, &lock_path).unwrap();
+    assert_eq!(diff.fix, ActivationFix::default());
+}
+
+#[test]
+fn test_activation_requires_dependencies() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let relations = [
+        (
+            "Client",
+            r#""dependsOn": [{"id": "Server", "version": "1.0.0"}]"#,
+        ),
+        ("Server", r#""dependsOn": {"Core": "1.0.0"}"#),
+        ("Core", r#""conflictsWith": ["Rival"]"#),
+        ("Rival", r#""dependsOn": []"#),
+    ];
+    for (name, relation) in relations {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, name != "Client");
+        let manifest = format!(
+            r#"{{"id": "{name}", "name": "{name}", "version": "1.0.0", "author": "test", "sptVersion": "3.9.0", {relation}}}"#
+        );
+        fs::write(
+            src.join(ModPaths::default().folder).join("manifest.json"),
+            manifest,
+        )
+        .unwrap();
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+
+    // 1. A mod whose dependency is inactive is refused
+    let Err(SError::UnmetDependencies(issues)) = mod_manager::toggle_mod(&mut lib, "Client", true)
+    else {
+        panic!("expected unmet dependencies");
+    };
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].other, "Server");
+    assert_eq!(issues[0].kind, DependencyIssueKind::Inactive);
+    assert!(!lib.mods["Client"].is_active);
+
+    // 2. The resolution enables the whole chain
+    let ids = dependency::resolve_activation(&lib, "Client").unwrap();
+    assert_eq!(ids, vec!["Client", "Core", "Server"]);
+    mod_manager::toggle_mods(&mut lib, &ids, true).unwrap();
+
+    // 3. Conflicts are refused in both directions
+    let Err(SError::UnmetDependencies(issues)) = mod_manager::toggle_mod(&mut lib, "Rival", true)
+    else {
+        panic!("expected a conflict");
+    };
+    assert_eq!(issues[0].kind, DependencyIssueKind::Conflict);
+
+    // 4. A sync refuses an active set that lost a dependency
+    lib.mods.get_mut("Core").unwrap().is_active = false;
+    assert!(matches!(
+        library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort),
+        Err(SError::UnmetDependencies(_))
+    ));
+
+    // 5. `dependsOn` and `dependencies` name the same field, so a manifest can't use both
+    let both = r#"{"id": "Both", "name": "Both", "version": "1.0.0", "author": "test", "sptVersion": "3.9.0", "dependencies": {"Core": "1.0.0"}, "dependsOn": {"Server": "1.0.0"}}"#;
+    let err = serde_json::from_str::<ModManifest>(both).unwrap_err();
+    assert!(err.to_string().contains("duplicate field `dependencies`"));
+}
+
+#[test]
+fn test_profiles_keep_separate_activation_sets() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["Alpha", "Beta", "Gamma"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    let active = |lib: &Library| {
+        lib.mods
+            .values()
+            .filter(|m| m.is_active)
+            .map(|m| m.id.clone())
+            .collect::<Vec<_>>()
+    };
+    mod_manager::toggle_mods(&mut lib, &["Alpha".into(), "Beta".into()], true).unwrap();
+    profiles::create(&mut lib, "hardcore").unwrap();
+    profiles::create(&mut lib, "casual").unwrap();
+    assert!(matches!(
+        profiles::create(&mut lib, " casual "),
+        Err(SError::AlreadyExists(_))
+    ));
+
+    // 1. Changes made while a profile is active stay with it
+    mod_manager::toggle_mod(&mut lib, "Alpha", false).unwrap();
+    mod_manager::toggle_mod(&mut lib, "Gamma", true).unwrap();
+    lib.mark_clean();
+    profiles::switch(&mut lib, "hardcore").unwrap();
+    assert_eq!(active(&lib), vec!["Alpha", "Beta"]);
+    assert!(lib.to_dto().is_dirty);
+
+    // 2. Profiles survive a reload, in load order
+    let mut lib = Library::load(&repo_root).unwrap();
+    assert_eq!(lib.active_profile.as_deref(), Some("hardcore"));
+    assert_eq!(lib.profiles["casual"].mods, vec!["Beta", "Gamma"]);
+    profiles::switch(&mut lib, "casual").unwrap();
+    assert_eq!(active(&lib), vec!["Beta", "Gamma"]);
+
+    // 3. Copies are independent, deleting the active profile leaves the mods alone
+    profiles::duplicate(&mut lib, "casual", "testing").unwrap();
+    assert_eq!(lib.profiles["testing"].mods, vec!["Beta", "Gamma"]);
+    profiles::delete(&mut lib, "casual").unwrap();
+    assert_eq!(lib.active_profile, None);
+    assert_eq!(active(&lib), vec!["Beta", "Gamma"]);
+    assert_eq!(
+        profiles::switch(&mut lib, "casual"),
+        Err(SError::ProfileNotFound("casual".to_string()))
+    );
+}
+
+#[test]
+fn test_drop_queue_holds_drops_while_tasks_run() {
+    let queue = DropQueue::default();
+    let drop = |name: &str| QueuedDrop {
+        paths: vec![Utf8PathBuf::from(format!("/downloads/{name}.zip"))],
+        unknown_mod_name: "Unknown".into(),
+    };
+
+    // 1. Nothing runs, the drop goes through
+    assert_eq!(queue.offer(drop("a")), Offer::Ready(drop("a")));
+
+    // 2. Drops wait for every running task, in order
+    queue.begin();
+    queue.begin();
+    assert_eq!(queue.offer(drop("b")), Offer::Queued { pending: 1 });
+    assert_eq!(queue.offer(drop("c")), Offer::Queued { pending: 2 });
+    assert!(queue.end().is_empty());
+    assert_eq!(queue.end(), vec![drop("b"), drop("c")]);
+
+    // 3. The queue is empty afterwards
+    assert_eq!(queue.offer(drop("d")), Offer::Ready(drop("d")));
+}
+
+#[test]
+fn test_throughput_smooths_rate_for_eta() {
+    let start = Instant::now();
+    let at = |millis: u64| start + Duration::from_millis(millis);
+    let mut throughput = Throughput::start(start);
+
+    // 1. No estimate before the first sample, and samples are spaced out
+    assert_eq!(throughput.eta(0, 1000), None);
+    assert!(!throughput.sample(100, at(100)));
+
+    // 2. The first sample sets the rate: 500 bytes in 500 ms
+    assert!(throughput.sample(500, at(500)));
+    assert_eq!(throughput.bytes_per_sec(), 1000);
+    assert_eq!(throughput.eta(500, 1500), Some(Duration::from_secs(1)));
+
+    // 3. A burst only moves the rate part of the way
+    assert!(throughput.sample(1500, at(1000)));
+    assert_eq!(throughput.bytes_per_sec(), 1300);
+
+    // 4. A stall brings the rate down without dividing by zero
+    (1..20).for_each(|step| {
+        throughput.sample(1500, at(1000 + step * 500));
+    });
+    assert!(throughput.bytes_per_sec() < 10);
+    assert!(throughput.eta(1500, 1500).is_some_and(|eta| eta.is_zero()));
+}
+
+#[test]
+fn test_install_estimate_warns_about_huge_installs() {
+    let (_tmp, _game_root, repo_root) = setup_test_env();
+    let downloads = repo_root.join("downloads");
+    let folder = downloads.join("Folder");
+    fs::create_dir_all(folder.join("nested")).unwrap();
+    fs::write(folder.join("a.dll"), vec![0u8; 300]).unwrap();
+    fs::write(folder.join("nested/b.json"), vec![0u8; 200]).unwrap();
+
+    let archive = downloads.join("Archive.zip");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+    zip.start_file(
+        "BepInEx/plugins/big.dll",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    zip.write_all(&vec![7u8; 4096]).unwrap();
+    zip.finish().unwrap();
+
+    // 1. Folders are walked and archives measured unpacked, even when compressed smaller
+    let missing = downloads.join("Missing.zip");
+    let estimate = install_size::estimate(
+        &[folder.clone(), archive.clone(), missing.clone()],
+        &repo_root,
+        InstallSizeLimits::default(),
+    );
+    let sizes: Vec<_> = estimate.inputs.iter().map(|i| i.bytes).collect();
+    assert_eq!(sizes, vec![Some(500), Some(4096), None]);
+    assert_eq!(estimate.total_bytes, 4596);
+    assert!(estimate.warnings.is_empty());
+
+    // 2. Above the size limit, and below the free space to keep, both warn
+    let mib = 1024 * 1024;
+    let limits = InstallSizeLimits {
+        warn_above_mib: 1,
+        min_free_mib: 1,
+    };
+    let warnings = install_size::check(&archive, 2 * mib, Some(4 * mib), limits);
+    let kinds: Vec<_> = warnings.iter().map(|w| w.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![WarningKind::LargeInstall, WarningKind::LowDiskSpace]
+    );
+    assert_eq!(
+        warnings[1].details,
+        vec![(5 * mib).to_string(), (4 * mib).to_string()]
+    );
+
+    // 3. Unknown free space only checks the size
+    assert_eq!(
+        install_size::check(&archive, 2 * mib, None, limits).len(),
+        1
+    );
+    assert!(install_size::check(&archive, mib / 2, Some(4 * mib), limits).is_empty());
+}
+
+#[test]
+fn test_update_checks_are_cached_per_version() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let install = |lib: &mut Library, name: &str, version: &str, source: &str| {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let manifest = format!(
+            r#"{{"id": "{name}", "name": "{name}", "version": "{version}", "author": "test", "sptVersion": "3.9.0"{source}}}"#
+        );
+        fs::write(
+            src.join(ModPaths::default().folder).join("manifest.json"),
+            manifest,
+        )
+        .unwrap();
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    };
+    install(
+        &mut lib,
+        "Alpha",
+        "1.0.0",
+        r#", "updateUrl": "https://example.com/alpha.json""#,
+    );
+    install(&mut lib, "Beta", "2.0.0", r#", "hubId": 42"#);
+    install(&mut lib, "Gamma", "1.0.0", "");
+
+    // 1. Both response shapes are understood
+    let plain = serde_json::json!({"version": "1.2.0", "downloadUrl": "https://example.com/a.zip"});
+    let forge = serde_json::json!({"data": [{"version": "2.0.0", "link": "https://forge/b"}, {"version": "1.0.0"}]});
+    let release = update_checker::parse(&plain).unwrap();
+    assert_eq!(
+        release.download_url.as_deref(),
+        Some("https://example.com/a.zip")
+    );
+    assert_eq!(update_checker::parse(&forge).unwrap().version, "2.0.0");
+    assert!(update_checker::parse(&serde_json::json!({"data": []})).is_none());
+
+    // 2. Only mods with a source are due, Forge ids resolve to its API
+    let now = 1_000_000;
+    let due = update_checker::due(&lib, now, false);
+    let urls: Vec<_> = due.iter().map(|c| c.url.as_str()).collect();
+    assert_eq!(urls.len(), 2);
+    assert_eq!(urls[0], "https://example.com/alpha.json");
+    assert!(urls[1].ends_with("/mods/42/versions"));
+
+    // 3. Results, failures included, are cached until the TTL runs out
+    let mut due = due.into_iter();
+    update_checker::record(&mut lib, due.next().unwrap(), Ok(release), now);
+    update_checker::record(
+        &mut lib,
+        due.next().unwrap(),
+        Err(SError::UpdateCheckFailed("offline".into())),
+        now,
+    );
+    assert!(update_checker::due(&lib, now + 60, false).is_empty());
+    assert_eq!(update_checker::due(&lib, now + 60, true).len(), 2);
+    assert_eq!(
+        update_checker::due(&lib, now + update_checker::TTL_SECS, false).len(),
+        2
+    );
+    let report = update_checker::report(&lib);
+    assert!(report[0].is_newer && report[0].latest.as_deref() == Some("1.2.0"));
+    assert!(!report[1].is_newer && report[1].latest.is_none());
+
+    // 4. The cache survives reopening, and a reinstalled version is checked again
+    lib.persist().unwrap();
+    let mut lib = Library::load(&repo_root).unwrap();
+    assert_eq!(update_checker::report(&lib).len(), 2);
+    install(
+        &mut lib,
+        "Alpha",
+        "1.2.0",
+        r#", "updateUrl": "https://example.com/alpha.json""#,
+    );
+    let due = update_checker::due(&lib, now + 60, false);
+    assert_eq!(
+        due,
+        vec![PendingCheck {
+            mod_id: "Alpha".into(),
+            current: "1.2.0".into(),
+            url: "https://example.com/alpha.json".into(),
+        }]
+    );
+}
+
+#[test]
+fn test_missing_bepinex_is_detected_and_installed() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    assert_eq!(bepinex::state(&game_root), BepInExState::Missing);
+    assert_eq!(
+        bepinex::status(&game_root).recommended.as_deref(),
+        Some("5.4.23.2")
+    );
+    assert!(bepinex::release_for("3.11.0").is_err());
+
+    // 1. Syncing plugins without BepInEx warns, server mods alone do not
+    for (name, is_server) in [("Server", true), ("Client", false)] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, is_server);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    let sync = |lib: &mut Library| {
+        library_service::sync(lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort)
+            .unwrap()
+            .into_iter()
+            .filter(|w| w.kind == WarningKind::BepInExMissing)
+            .count()
+    };
+    mod_manager::toggle_mod(&mut lib, "Server", true).unwrap();
+    assert_eq!(sync(&mut lib), 0);
+    mod_manager::toggle_mod(&mut lib, "Client", true).unwrap();
+    assert_eq!(sync(&mut lib), 1);
+    assert_eq!(bepinex::state(&game_root), BepInExState::Incomplete);
+
+    // 2. Installing adds the missing files and keeps what is there
+    let config = game_root.join("BepInEx/config/BepInEx.cfg");
+    fs::create_dir_all(config.parent().unwrap()).unwrap();
+    fs::write(&config, "tuned").unwrap();
+    let archive = repo_root.join("BepInEx.zip");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+    let options = zip::write::SimpleFileOptions::default();
+    for (path, content) in [
+        ("BepInEx/core/BepInEx.dll", "core"),
+        ("BepInEx/config/BepInEx.cfg", "default"),
+        ("winhttp.dll", "proxy"),
+    ] {
+        zip.start_file(path, options).unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+
+    let state = bepinex::install(&game_root, &archive, &lib.lib_paths.staging).unwrap();
+    assert_eq!(state, BepInExState::Installed);
+    assert_eq!(fs::read_to_string(&config).unwrap(), "tuned");
+    assert!(fs::read_dir(&lib.lib_paths.staging)
+        .unwrap()
+        .next()
+        .is_none());
+    assert_eq!(sync(&mut lib), 0);
+
+    // 3. An archive without the BepInEx core is rejected
+    let bogus = repo_root.join("Bogus.zip");
+    let mut zip = zip::ZipWriter::new(fs::File::create(&bogus).unwrap());
+    zip.start_file("readme.txt", options).unwrap();
+    zip.finish().unwrap();
+    assert!(matches!(
+        bepinex::install(&game_root, &bogus, &lib.lib_paths.staging),
+        Err(SError::CorruptArchive(_))
+    ));
+}
+
+#[test]
+fn test_downloads_are_named_after_the_url_and_verified() {
+    let (_tmp, _game_root, repo_root) = setup_test_env();
+    let body = b"PK\x05\x06archive";
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = std::thread::spawn(move || {
+        for _ in 0..2 {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len());
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(body).unwrap();
+        }
+    });
+    let client = reqwest::blocking::Client::new();
+    let url = format!("http://127.0.0.1:{port}/files/Some%20Mod.zip?token=1");
+    let dir = repo_root.join("downloads");
+
+    // 1. The file is named after the URL and kept when it matches
+    let expected = download::Expected {
+        size: Some(body.len() as u64),
+        sha256: Some("ab7e3c5ee7a4d2a8dff39fbf8e9b85b5a1bd31ab2bb7fa6d2f7a5d2b0a0a5ff0".into()),
+    };
+    let result = download::fetch(&client, &url, &dir, &expected, Progress::silent());
+    assert!(matches!(result, Err(SError::CorruptArchive(_))));
+    assert!(!dir.join("Some-20Mod.zip").exists());
+
+    let expected = download::Expected {
+        size: Some(body.len() as u64),
+        sha256: None,
+    };
+    let path = download::fetch(&client, &url, &dir, &expected, Progress::silent()).unwrap();
+    assert_eq!(path, dir.join("Some-20Mod.zip"));
+    assert_eq!(fs::read(&path).unwrap(), body);
+    server.join().unwrap();
+
+    // 2. Sizes and digests are compared as published
+    let sha = |bytes: &[u8]| {
+        use sha2::Digest;
+        format!("{:x}", sha2::Sha256::digest(bytes))
+    };
+    let verified = download::Expected {
+        size: None,
+        sha256: Some(sha(body).to_uppercase()),
+    };
+    assert!(download::verify(&path, &verified).is_ok());
+    let short = download::Expected {
+        size: Some(3),
+        sha256: None,
+    };
+    assert!(download::verify(&path, &short).is_err());
+
+    // 3. Forge mod pages resolve through the hub
+    assert_eq!(
+        download::forge_id("https://forge.sp-tarkov.com/mod/1234/some-mod"),
+        Some(1234)
+    );
+    assert_eq!(download::forge_id("https://example.com/mod/1234"), None);
+}
+
+#[test]
+fn test_modpack_recreates_the_setup_in_another_library() {
+    let rules = SPTPathRules::default();
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    for (name, is_server) in [("Alpha", true), ("Beta", false), ("Gamma", true)] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, is_server);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    mod_manager::toggle_mods(&mut lib, &["Gamma".into(), "Alpha".into()], true).unwrap();
+    profiles::create(&mut lib, "Raid").unwrap();
+    let update = ModMetadataUpdate {
+        mod_id: "Beta".to_string(),
+        note: Some("Breaks the hideout".into()),
+        ..Default::default()
+    };
+    mod_manager::update_metadata(&mut lib, vec![update]).unwrap();
+
+    // 1. The pack describes every mod and the load order of the active profile
+    let exports = repo_root.join("exports");
+    fs::create_dir_all(&exports).unwrap();
+    let full = modpack::export(&lib, &exports, true).unwrap();
+    assert_eq!(full, exports.join("Test Library.mkpack"));
+    let pack = modpack::read(&full).unwrap();
+    assert_eq!(pack.profile.as_deref(), Some("Raid"));
+    assert_eq!(pack.load_order, lib.profiles["Raid"].mods);
+    assert_eq!(pack.mods.len(), 3);
+    let bare = modpack::export(&lib, &exports.join("bare.mkpack"), false).unwrap();
+
+    // 2. Another library installs the bundled mods with the same activation and metadata
+    let (_tmp2, game_root2, repo_root2) = setup_test_env();
+    let mut other = new_library(&game_root2, &repo_root2);
+    let material = other.stage_material("Unknown".to_string(), FrameworkPolicy::Quarantine);
+    let warnings = modpack::import(&mut other, &full, &material, Progress::silent()).unwrap();
+    assert!(warnings.is_empty());
+    let active: Vec<_> = other
+        .mods
+        .values()
+        .filter(|m| m.is_active)
+        .map(|m| m.id.as_str())
+        .collect();
+    assert_eq!(active, vec!["Alpha", "Gamma"]);
+    assert_eq!(
+        other.mods["Beta"].metadata.note.as_deref(),
+        Some("Breaks the hideout")
+    );
+    assert_eq!(other.profiles["Raid"].mods, pack.load_order);
+    assert_eq!(other.active_profile.as_deref(), Some("Raid"));
+    assert!(other.to_dto().is_dirty);
+    assert!(fs::read_dir(&other.lib_paths.staging)
+        .unwrap()
+        .next()
+        .is_none());
+
+    // 3. Without payloads, mods that are not installed are reported
+    let (_tmp3, game_root3, repo_root3) = setup_test_env();
+    let mut empty = new_library(&game_root3, &repo_root3);
+    let material = empty.stage_material("Unknown".to_string(), FrameworkPolicy::Quarantine);
+    let warnings = modpack::import(&mut empty, &bare, &material, Progress::silent()).unwrap();
+    let missing: Vec<_> = warnings
+        .iter()
+        .filter(|w| w.kind == WarningKind::ModPackModMissing)
+        .map(|w| w.subject.as_str())
+        .collect();
+    assert_eq!(missing, vec!["Alpha", "Beta", "Gamma"]);
+    assert!(empty.mods.is_empty());
+}
+
+#[test]
+fn test_links_of_other_managers_are_left_alone_and_reported() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["Alpha", "Beta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    let ids = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    library_service::switch_active(
+        &mut lib,
+        &ids(&["Alpha", "Beta"]),
+        ChecklistPolicy::Inform,
+        LinkFailurePolicy::Abort,
+    )
+    .unwrap();
+
+    // Another manager took over Beta's folder and added a plugin of its own
+    let other = game_root.parent().unwrap().join("other-manager");
+    fs::create_dir_all(other.join("Beta")).unwrap();
+    fs::create_dir_all(other.join("Tools")).unwrap();
+    let beta = game_root.join(&rules.server_mods).join("Beta");
+    let tools = game_root.join(&rules.client_plugins).join("Tools");
+    linker::unlink(&beta).unwrap();
+    linker::link(&other.join("Beta"), &beta).unwrap();
+    linker::link(&other.join("Tools"), &tools).unwrap();
+
+    // 1. Deactivating Beta keeps the other manager's link
+    let warnings = library_service::switch_active(
+        &mut lib,
+        &ids(&["Alpha"]),
+        ChecklistPolicy::Inform,
+        LinkFailurePolicy::Abort,
+    )
+    .unwrap();
+    assert_eq!(linker::read_link_target(&beta).unwrap(), other.join("Beta"));
+    let foreign = warnings
+        .iter()
+        .find(|w| w.kind == WarningKind::ForeignLinks)
+        .unwrap();
+    assert_eq!(
+        foreign.details,
+        vec!["SPT/user/mods/Beta", "BepInEx/plugins/Tools"]
+    );
+
+    // 2. Neither a full sync nor a purge touches them
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    cleanup::purge(
+        &lib.game_root,
+        &lib.repo_root,
+        &lib.spt_rules,
+        &lib.lib_paths,
+        &lib.cache,
+    )
+    .unwrap();
+    assert!(beta.is_symlink() && tools.is_symlink());
+
+    // 3. The report tells the links of the library apart from the foreign ones
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    let report = ownership::report(&lib).unwrap();
+    assert_eq!(report.mods.len(), 1);
+    assert_eq!(report.mods[0].mod_id, "Alpha");
+    assert_eq!(
+        report.mods[0].links,
+        vec![Utf8PathBuf::from("SPT/user/mods/Alpha")]
+    );
+    let targets: Vec<_> = report.foreign.iter().map(|l| l.target.clone()).collect();
+    assert_eq!(targets, vec![other.join("Beta"), other.join("Tools")]);
+}
+
+#[test]
+fn test_actionable_issues_are_ranked() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let extras = [
+        ("Alpha", r#""updateUrl": "https://example.com/alpha.json""#),
+        ("Beta", r#""conflictsWith": ["Alpha"]"#),
+    ];
+    for (name, extra) in extras {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let manifest = format!(
+            r#"{{"id": "{name}", "name": "{name}", "version": "1.0.0", "author": "test", "sptVersion": "3.9.0", {extra}}}"#
+        );
+        fs::write(
+            src.join(ModPaths::default().folder).join("manifest.json"),
+            manifest,
+        )
+        .unwrap();
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    let limits = InstallSizeLimits {
+        min_free_mib: 0,
+        ..Default::default()
+    };
+    let kinds = |lib: &Library| {
+        actionable_issues::collect(lib, limits)
+            .into_iter()
+            .map(|issue| (issue.kind, issue.subjects))
+            .collect::<Vec<_>>()
+    };
+
+    // 1. A synced library has nothing to report
+    mod_manager::toggle_mod(&mut lib, "Alpha", true).unwrap();
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    assert!(kinds(&lib).is_empty());
+
+    // 2. Removed links and announced updates show up
+    linker::unlink(&game_root.join(&rules.server_mods).join("Alpha")).unwrap();
+    let check = update_checker::due(&lib, 0, false).pop().unwrap();
+    let release = Release {
+        version: "1.1.0".into(),
+        download_url: None,
+        installed: None,
+    };
+    update_checker::record(&mut lib, check, Ok(release), 0);
+    assert_eq!(
+        kinds(&lib),
+        vec![
+            (IssueKind::BrokenLinks, vec!["Alpha".to_string()]),
+            (IssueKind::UpdatesAvailable, vec!["Alpha".to_string()]),
+        ]
+    );
+
+    // 3. Unsynced conflicting mods rank above updates; missing links are expected until the sync
+    lib.mods.get_mut("Beta").unwrap().is_active = true;
+    lib.mark_dirty();
+    let issues = actionable_issues::collect(&lib, limits);
+    assert_eq!(issues[0].kind, IssueKind::IncompatibleMods);
+    assert_eq!(issues[0].subjects, vec!["Alpha", "Beta"]);
+    assert_eq!(issues[0].action, IssueAction::ResolveModDependencies);
+    assert_eq!(issues[1].kind, IssueKind::UnsyncedChanges);
+    assert_eq!(issues[1].action, IssueAction::SyncMods);
+    assert_eq!(issues[2].kind, IssueKind::UpdatesAvailable);
+    assert_eq!(issues.len(), 3);
+}
+
+#[test]
+fn test_file_override_resolves_collision() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let shared = rules.client_plugins.join("shared.dll");
+    for name in ["Alpha", "Beta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, false);
+        fs::write(src.join(&shared), name).unwrap();
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    let ids = vec!["Alpha".to_string(), "Beta".to_string()];
+    mod_manager::toggle_mods(&mut lib, &ids, true).unwrap();
+    let sync = |lib: &mut Library| {
+        library_service::sync(lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort)
+    };
+    assert!(matches!(sync(&mut lib), Err(SError::FileCollision(_))));
+
+    // 1. Only installed mods providing the file can win it
+    assert!(matches!(
+        file_overrides::resolve(&mut lib, shared.clone(), Some("Gamma".into())),
+        Err(SError::ModNotFound(_))
+    ));
+    assert!(matches!(
+        file_overrides::resolve(
+            &mut lib,
+            "BepInEx/plugins/other.dll".into(),
+            Some("Beta".into())
+        ),
+        Err(SError::FileOrDirectoryNotFound(_))
+    ));
+
+    // 2. The winner is linked for that path, the rest of the loser still deploys
+    file_overrides::resolve(&mut lib, shared.clone(), Some("Beta".into())).unwrap();
+    sync(&mut lib).unwrap();
+    assert_eq!(fs::read_to_string(game_root.join(&shared)).unwrap(), "Beta");
+    let alpha = game_root
+        .join(&rules.client_plugins)
+        .join("Alpha/content.txt");
+    assert_eq!(fs::read_to_string(alpha).unwrap(), "Alpha");
+    let reloaded = Library::load(&repo_root).unwrap();
+    assert_eq!(
+        reloaded.overrides.get(&shared).map(String::as_str),
+        Some("Beta")
+    );
+
+    // 3. Dropping the override brings the collision back, removing the winner drops it
+    file_overrides::resolve(&mut lib, shared.clone(), None).unwrap();
+    assert!(matches!(sync(&mut lib), Err(SError::FileCollision(_))));
+    file_overrides::resolve(&mut lib, shared.clone(), Some("Alpha".into())).unwrap();
+    mod_manager::remove_mod(&mut lib, "Alpha").unwrap();
+    assert!(lib.overrides.is_empty());
+}
+
+#[test]
+fn test_scan_game_directory_adopts_manual_installs() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let src = repo_root.join("src").join("Alpha");
+    create_test_mod(&src, "Alpha", true);
+    let fs = ModFS::new(&src, &rules).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    mod_manager::toggle_mod(&mut lib, "Alpha", true).unwrap();
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+
+    // Installed by hand, next to SPT's own client patches
+    let manual = game_root.join(&rules.server_mods).join("Manual");
+    let plugins = game_root.join(&rules.client_plugins);
+    fs::create_dir_all(&manual).unwrap();
+    fs::write(manual.join("package.json"), "{}").unwrap();
+    fs::create_dir_all(plugins.join("spt")).unwrap();
+    fs::write(plugins.join("spt/spt-core.dll"), "spt").unwrap();
+    fs::write(plugins.join("Loose.dll"), "loose").unwrap();
+
+    // 1. Only what the library did not link is listed
+    let entries = game_scan::scan(&lib).unwrap();
+    let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        vec!["BepInEx/plugins/Loose.dll", "SPT/user/mods/Manual"]
+    );
+    assert_eq!(entries[0].bytes, 5);
+    assert!(matches!(
+        game_scan::adopt(
+            &mut lib,
+            &["SPT/user/mods/Alpha".into()],
+            Progress::silent()
+        ),
+        Err(SError::FileOrDirectoryNotFound(_))
+    ));
+
+    // 2. Adopted entries become active mods and are linked back by the next sync
+    let paths: Vec<Utf8PathBuf> = entries.into_iter().map(|e| e.path).collect();
+    game_scan::adopt(&mut lib, &paths, Progress::silent()).unwrap();
+    assert_eq!(lib.mods.len(), 3);
+    assert!(lib.mods.values().all(|m| m.is_active));
+    assert!(!manual.exists() && !plugins.join("Loose.dll").exists());
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    assert_eq!(
+        fs::read_to_string(plugins.join("Loose.dll")).unwrap(),
+        "loose"
+    );
+    assert!(manual.join("package.json").exists());
+    assert!(game_scan::scan(&lib).unwrap().is_empty());
+    assert!(plugins.join("spt/spt-core.dll").exists());
+}
+
+#[test]
+fn test_scan_game_directory_keeps_renamed_mods_disabled() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    // Disabled by hand, next to an enabled one
+    let server_mods = game_root.join(&rules.server_mods);
+    let plugins = game_root.join(&rules.client_plugins);
+    fs::create_dir_all(server_mods.join("Renamed.DISABLED")).unwrap();
+    fs::write(server_mods.join("Renamed.DISABLED/package.json"), "{}").unwrap();
+    fs::create_dir_all(&plugins).unwrap();
+    fs::write(plugins.join("!Prefixed.dll"), "prefixed").unwrap();
+    fs::write(plugins.join("Enabled.dll"), "enabled").unwrap();
+    assert_eq!(game_scan::enabled_name("Mod.disabled"), Some("Mod"));
+    assert_eq!(game_scan::enabled_name(".disabled"), None);
+    assert_eq!(game_scan::enabled_name("Mod"), None);
+
+    // 1. The scan tells them apart
+    let entries = game_scan::scan(&lib).unwrap();
+    let listed: Vec<(&str, bool)> = entries
+        .iter()
+        .map(|e| (e.path.as_str(), e.disabled))
+        .collect();
+    assert_eq!(
+        listed,
+        vec![
+            ("BepInEx/plugins/!Prefixed.dll", true),
+            ("BepInEx/plugins/Enabled.dll", false),
+            ("SPT/user/mods/Renamed.DISABLED", true),
+        ]
+    );
+
+    // 2. Disabled ones are adopted inactive, without their marker
+    let paths: Vec<Utf8PathBuf> = entries.into_iter().map(|e| e.path).collect();
+    game_scan::adopt(&mut lib, &paths, Progress::silent()).unwrap();
+    let states: BTreeMap<&str, bool> = lib
+        .mods
+        .values()
+        .map(|m| (m.name.as_str(), m.is_active))
+        .collect();
+    assert_eq!(
+        states,
+        BTreeMap::from([("Enabled", true), ("Prefixed", false), ("Renamed", false)])
+    );
+    assert!(lib.cache.mods.values().any(|fs| fs
+        .files
+        .contains(&rules.client_plugins.join("Prefixed.dll"))));
+
+    // 3. Only the enabled one is linked back
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    assert!(plugins.join("Enabled.dll").exists());
+    assert!(!plugins.join("Prefixed.dll").exists());
+    assert!(!plugins.join("!Prefixed.dll").exists());
+    assert!(!server_mods.join("Renamed").exists());
+}
+
+#[test]
+fn test_update_check_verifies_installed_files() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let src = repo_root.join("src").join("Alpha");
+    create_test_mod(&src, "Alpha", true);
+    let manifest = r#"{"id": "Alpha", "name": "Alpha", "version": "1.0.0", "author": "test", "sptVersion": "3.9.0", "updateUrl": "https://example.com/alpha.json", "links": [{"url": "https://example.com/alpha"}]}"#;
+    fs::write(
+        src.join(ModPaths::default().folder).join("manifest.json"),
+        manifest,
+    )
+    .unwrap();
+    let fs = ModFS::new(&src, &rules).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+
+    let content = "SPT/user/mods/Alpha/content.txt";
+    let good = "b1a96dd646bccaa24cef7a3db22a6f995f05658f4f1c3272913e258c03e6fb24";
+    let body = |hash: &str| {
+        serde_json::json!({"data": [
+            {"version": "1.1.0", "link": "https://example.com/1.1.zip"},
+            {"version": "1.0.0", "link": "https://example.com/1.0.zip", "hashes": {content: hash}},
+        ]})
+    };
+    let record = |lib: &mut Library, hash: &str| {
+        let check = update_checker::due(lib, 0, true).pop().unwrap();
+        let release = Release {
+            installed: update_checker::parse_installed(&body(hash), "1.0.0"),
+            ..update_checker::parse(&body(hash)).unwrap()
+        };
+        update_checker::record(lib, check, Ok(release), 0);
+        update_checker::report(lib).pop().unwrap()
+    };
+
+    // 1. Matching hashes flag nothing
+    let info = record(&mut lib, good);
+    assert!(info.corrupted_files.is_empty());
+    assert_eq!(
+        info.redownload_url.as_deref(),
+        Some("https://example.com/1.0.zip")
+    );
+
+    // 2. A changed file is flagged and raised as an issue
+    fs::write(lib.lib_paths.mods.join("Alpha").join(content), "patched").unwrap();
+    let info = record(&mut lib, good);
+    assert_eq!(info.corrupted_files, vec![Utf8PathBuf::from(content)]);
+    let limits = InstallSizeLimits {
+        min_free_mib: 0,
+        ..Default::default()
+    };
+    let issues = actionable_issues::collect(&lib, limits);
+    assert_eq!(issues[0].kind, IssueKind::CorruptedMods);
+    assert_eq!(issues[0].subjects, vec!["Alpha"]);
+
+    // 3. Without a listing of the installed version, the manifest website is offered
+    let check = update_checker::due(&lib, 0, true).pop().unwrap();
+    let release = update_checker::parse(&serde_json::json!({"version": "1.1.0"})).unwrap();
+    update_checker::record(&mut lib, check, Ok(release), 0);
+    let info = update_checker::report(&lib).pop().unwrap();
+    assert!(info.corrupted_files.is_empty());
+    assert_eq!(
+        info.redownload_url.as_deref(),
+        Some("https://example.com/alpha")
+    );
+}
+
+#[test]
+fn test_backup_retention_prunes() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let lib = new_library(&game_root, &repo_root);
+    assert_eq!(lib.backup_retention.max_per_mod, Some(5));
+
+    let now = get_unix_timestamp();
+    let day = 24 * 60 * 60;
+    // ModA: 4 backups a day apart, ModB: a single backup from long ago
+    for age in 0..4 {
+        let dir = lib
+            .lib_paths
+            .backups
+            .join("ModA")
+            .join((now - age * day).to_string());
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("data.bin"), vec![0u8; 1024]).unwrap();
+    }
+    let old = lib
+        .lib_paths
+        .backups
+        .join("ModB")
+        .join((now - 100 * day).to_string());
+    fs::create_dir_all(&old).unwrap();
+    fs::write(old.join("data.bin"), vec![0u8; 1024]).unwrap();
+
+    let usage = mod_backup::disk_usage(&lib.lib_paths).unwrap();
+    assert_eq!(usage.total_bytes, 5 * 1024);
+    assert_eq!(usage.mods.len(), 2);
+
+    let retention = BackupRetention {
+        max_per_mod: Some(3),
+        max_total_mib: None,
+        max_age_days: Some(30),
+    };
+    let removed = mod_backup::prune(&lib.lib_paths, &retention).unwrap();
+
+    // The 4th of ModA goes, ModB's only backup is kept despite its age
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].timestamp, (now - 3 * day).to_string());
+    assert_eq!(
+        mod_backup::list_backups(&lib.lib_paths, "ModA")
+            .unwrap()
+            .len(),
+        3
+    );
+    assert!(old.exists());
+
+    let tight = BackupRetention {
+        max_per_mod: None,
+        max_total_mib: Some(0),
+        max_age_days: None,
+    };
+    mod_backup::prune(&lib.lib_paths, &tight).unwrap();
+    let usage = mod_backup::disk_usage(&lib.lib_paths).unwrap();
+    assert_eq!(
+        usage.total_bytes,
+        2 * 1024,
+        "newest backup of each mod is kept"
+    );
+}
+
+#[test]
+fn test_local_edits_detected_kept_and_reverted() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    let src = repo_root.join("src");
+    create_test_mod(&src, "Tweaked", true);
+    let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+    mod_manager::add_mod(&mut lib, staged).unwrap();
+    mod_backup::create_backup(&lib, "Tweaked").unwrap();
+
+    let rel = rules.server_mods.join("Tweaked").join("content.txt");
+    let file = lib.lib_paths.mods.join("Tweaked").join(&rel);
+    let shared = SharedState::new(GlobalConfig::default(), Some(lib));
+    while idle_hasher::run_once(&shared) > 0 {}
+    fs::write(&file, "tweaked by hand").unwrap();
+
+    // Detected on open and shown in the DTO
+    let mut lib = Library::load(&repo_root).unwrap();
+    let dto = dto_builder::build_frontend_dto(&lib);
+    assert_eq!(dto.mods["Tweaked"].modified_files, vec![rel.clone()]);
+
+    // Revert takes the original back from the backup
+    local_edits::revert(&mut lib, "Tweaked").unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "Tweaked");
+    assert!(Library::load(&repo_root)
+        .unwrap()
+        .cache
+        .local_edits
+        .is_empty());
+
+    // Without a backup holding the original, only keeping is possible
+    fs::write(&file, "tweaked again").unwrap();
+    fs::remove_dir_all(lib.lib_paths.backups.join("Tweaked")).unwrap();
+    let mut lib = Library::load(&repo_root).unwrap();
+    assert_eq!(
+        local_edits::revert(&mut lib, "Tweaked"),
+        Err(SError::NoOriginalCopy(vec![rel.to_string()]))
+    );
+    local_edits::keep(&mut lib, "Tweaked").unwrap();
+    let lib = Library::load(&repo_root).unwrap();
+    assert!(lib.cache.local_edits.is_empty());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "tweaked again");
+}
+
+#[test]
+fn test_patches_grouped_with_their_mod() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let mods = [
+        ("Sain", "SAIN", ""),
+        ("SainPatch", "SAIN - 4.0 Compatibility Patch", ""),
+        ("Saintly", "SAINTly patch", ""),
+        ("Bots", "Questing Bots", ""),
+        (
+            "BotsFix",
+            "Pathing patch",
+            r#", "dependsOn": [{"id": "Bots", "version": "1.0.0"}]"#,
+        ),
+    ];
+    for (id, name, relation) in mods {
+        let src = repo_root.join("src").join(id);
+        create_test_mod(&src, id, true);
+        let manifest = format!(
+            r#"{{"id": "{id}", "name": "{name}", "version": "1.0.0", "author": "test", "sptVersion": "3.9.0"{relation}}}"#
+        );
+        fs::write(
+            src.join(ModPaths::default().folder).join("manifest.json"),
+            manifest,
+        )
+        .unwrap();
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+
+    // The dependency wins for BotsFix, the name for SainPatch; SAINTly is not a SAIN patch
+    assert_eq!(
+        mod_patches::groups(&lib),
+        vec![
+            PatchGroup {
+                base: "Bots".to_string(),
+                patches: vec!["BotsFix".to_string()],
+            },
+            PatchGroup {
+                base: "Sain".to_string(),
+                patches: vec!["SainPatch".to_string()],
+            },
+        ]
+    );
+    let dto = dto_builder::build_frontend_dto(&lib);
+    assert_eq!(dto.mods["SainPatch"].patch_of.as_deref(), Some("Sain"));
+    assert_eq!(dto.mods["Saintly"].patch_of, None);
+
+    // Enabling the base offers its inactive patches
+    mod_manager::toggle_mods(&mut lib, &["Bots".to_string()], true).unwrap();
+    let offers = mod_patches::activation_offers(&lib, &["Bots".to_string()]);
+    assert_eq!(offers.len(), 1);
+    assert_eq!(offers[0].kind, WarningKind::InactivePatches);
+    assert_eq!(offers[0].details, vec!["BotsFix"]);
+
+    mod_manager::toggle_mods(&mut lib, &["BotsFix".to_string()], true).unwrap();
+    assert!(mod_patches::activation_offers(&lib, &["Bots".to_string()]).is_empty());
+}
+
+#[test]
+fn test_delete_backups() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let lib = new_library(&game_root, &repo_root);
+    for timestamp in ["100", "200", "300"] {
+        fs::create_dir_all(lib.lib_paths.backups.join("Kept").join(timestamp)).unwrap();
+    }
+    fs::create_dir_all(lib.lib_paths.backups.join("Other").join("100")).unwrap();
+
+    mod_backup::delete_backup(&lib.lib_paths, "Kept", "200").unwrap();
+    let left: Vec<String> = mod_backup::list_backups(&lib.lib_paths, "Kept")
+        .unwrap()
+        .into_iter()
+        .map(|backup| backup.timestamp)
+        .collect();
+    assert_eq!(left, vec!["300", "100"]);
+    assert!(matches!(
+        mod_backup::delete_backup(&lib.lib_paths, "Kept", "200"),
+        Err(SError::FileOrDirectoryNotFound(_))
+    ));
+
+    // Ids cannot reach outside the backups
+    assert!(mod_backup::delete_all_backups(&lib.lib_paths, "..").is_err());
+    assert!(mod_backup::delete_backup(&lib.lib_paths, "../mods", "100").is_err());
+    assert!(lib.lib_paths.mods.exists());
+
+    mod_backup::delete_all_backups(&lib.lib_paths, "Kept").unwrap();
+    assert!(!lib.lib_paths.backups.join("Kept").exists());
+    assert!(lib.lib_paths.backups.join("Other").join("100").exists());
+}
+
+#[test]
+fn test_database_diff_of_server_mods() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    let rifle = "5447a9cd4bdc2dbd208b4567";
+    let ammo = "5656d7c34bdc2d9d198b4587";
+    let new_item = "aaaaaaaaaaaaaaaaaaaaaaaa";
+
+    let database = game_root.join(&rules.server_database).join("templates");
+    fs::create_dir_all(&database).unwrap();
+    fs::write(
+        database.join("items.json"),
+        format!(
+            r#"{{"{rifle}": {{"_id": "{rifle}", "_props": {{"Weight": 3.5, "Ergonomics": 40}}}},
+                "{ammo}": {{"_id": "{ammo}", "_props": {{"Damage": 50}}}}}}"#
+        ),
+    )
+    .unwrap();
+    fs::write(
+        database.join("prices.json"),
+        format!(r#"{{"{rifle}": 30000}}"#),
+    )
+    .unwrap();
+
+    let shipped = [
+        (
+            "Lighter",
+            format!(
+                r#"{{"items": {{"{rifle}": {{"_props": {{"Weight": 2.0, "Ergonomics": 40}}}}, "{new_item}": {{"_props": {{}}}}}}}}"#
+            ),
+        ),
+        (
+            "Ammo",
+            format!(
+                r#"[{{"_id": "{ammo}", "_props": {{"Damage": 50}}}}, {{"_id": "{rifle}", "_props": {{"Ergonomics": 60}}}}]"#
+            ),
+        ),
+    ];
+    for (id, data) in shipped {
+        let src = repo_root.join("src").join(id);
+        create_test_mod(&src, id, true);
+        fs::write(
+            src.join(&rules.server_mods).join(id).join("items.json"),
+            data,
+        )
+        .unwrap();
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+
+    // Only the fields set to other values show up, against the item rather than its price
+    let changes = database_diff::diff(&lib, "Lighter").unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].id, rifle);
+    assert_eq!(changes[0].kind, RecordChangeKind::Modified);
+    assert_eq!(changes[0].file.as_deref(), Some("templates/items.json"));
+    assert_eq!(
+        changes[0].fields,
+        vec![FieldChange {
+            pointer: "/_props/Weight".to_string(),
+            before: Some("3.5".to_string()),
+            after: "2.0".to_string(),
+        }]
+    );
+    assert_eq!(changes[1].id, new_item);
+    assert_eq!(changes[1].kind, RecordChangeKind::Added);
+
+    // Unchanged records are left out
+    let changes = database_diff::diff(&lib, "Ammo").unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].fields[0].pointer, "/_props/Ergonomics");
+
+    mod_manager::toggle_mods(&mut lib, &["Lighter".to_string(), "Ammo".to_string()], true).unwrap();
+    assert_eq!(
+        database_diff::overlaps(&lib).unwrap(),
+        vec![DatabaseOverlap {
+            id: rifle.to_string(),
+            mods: vec!["Ammo".to_string(), "Lighter".to_string()],
+        }]
+    );
+}
+
+#[test]
+fn test_idle_hasher_stamps_installed_files() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["First", "Second"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+        mod_manager::add_mod(&mut lib, staged).unwrap();
+    }
+    // Installs leave the hashing for later
+    assert!(lib.cache.mods.values().all(|m| m.stamps.is_empty()));
+
+    let shared = SharedState::new(GlobalConfig::default(), Some(lib));
 
-        // 2. Create the overlapping directory structure
-        let file_path = p.join(&rules.server_mods).join("CommonDir").join(file_name);
-        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
-        fs::write(file_path, "data").unwrap();
+    // An operation wanting the library holds the work off
+    assert_eq!(
+        shared.with_lib(|_| idle_hasher::run_once(&shared)).unwrap(),
+        0
+    );
 
-        // 3. New ModFS will now resolve ID to mod_id ("ModA" or "ModB")
-        let fs = ModFS::new(&p, &rules).unwrap();
-        let staged = create_staged_mod_for_test(&p, fs);
-        mod_manager::add_mod(lib, staged).unwrap();
+    assert_eq!(idle_hasher::run_once(&shared), 2);
+    assert_eq!(idle_hasher::run_once(&shared), 0);
 
-        // 4. This will no longer panic
-        lib.mods.get_mut(mod_id).unwrap().is_active = true;
-    };
+    // Stamps are persisted with the cache
+    let lib = Library::load(&repo_root).unwrap();
+    let first = &lib.cache.mods["First"];
+    assert_eq!(first.stamps.len(), first.files.len());
+    assert!(first.stamps.values().all(|stamp| !stamp.digest.is_empty()));
+}
 
-    setup_mod(&mut lib, "ModA", "A.txt");
-    setup_mod(&mut lib, "ModB", "B.txt");
+#[test]
+fn test_spt3_library_layout() {
+    let (tmp, _, repo_root) = setup_test_env();
+    let game_root = Utf8PathBuf::from_path_buf(tmp.path().join("spt311")).unwrap();
+    let rules = SPTPathRules::for_generation(SptGeneration::Spt3);
+    for file in [&rules.server_exe, &rules.client_exe] {
+        fs::create_dir_all(game_root.join(file).parent().unwrap()).unwrap();
+        fs::write(game_root.join(file), "dummy").unwrap();
+    }
+    let core = game_root.join(&rules.server_registry);
+    fs::create_dir_all(core.parent().unwrap()).unwrap();
+    fs::write(&core, r#"{"sptVersion": "3.11.3", "projectName": "SPT"}"#).unwrap();
 
-    // Sync using standalone functions
-    cleanup::purge(
-        &lib.game_root,
-        &lib.repo_root,
-        &lib.spt_rules,
-        &lib.lib_paths,
-        &lib.cache,
-    )
+    let mut lib = Library::create(LibraryCreationRequirement {
+        repo_root: Some(repo_root.clone()),
+        game_root: game_root.clone(),
+        name: "Legacy".to_string(),
+    })
     .unwrap();
-    deployment::deploy(
-        &lib.game_root,
-        &lib.lib_paths,
-        &lib.spt_rules,
-        &lib.mods,
-        &lib.cache,
-    )
-    .expect("Sync failed");
-    lib.mark_clean();
-    lib.persist().unwrap();
+    assert_eq!(lib.spt_generation, SptGeneration::Spt3);
+    assert_eq!(lib.spt_version, "3.11.3");
+    assert_eq!(lib.spt_rules.server_mods, Utf8PathBuf::from("user/mods"));
 
-    // ... rest of your assertions ...
-    let common_dir_in_game = game_root.join(&rules.server_mods).join("CommonDir");
-    assert!(common_dir_in_game.exists());
-    assert!(common_dir_in_game.is_dir());
+    // Server mods deploy to the 3.x folder
+    let src = repo_root.join("src");
+    fs::create_dir_all(src.join(&rules.server_mods).join("OldMod")).unwrap();
+    fs::write(
+        src.join(&rules.server_mods).join("OldMod").join("mod.js"),
+        "x",
+    )
+    .unwrap();
+    let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+    let mod_id = staged.fs.id.clone();
+    mod_manager::add_mod(&mut lib, staged).unwrap();
+    mod_manager::toggle_mod(&mut lib, &mod_id, true).unwrap();
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    assert!(game_root.join("user/mods/OldMod/mod.js").is_file());
+
+    // The generation is kept, and gates the version
+    let lib = Library::load(&repo_root).unwrap();
+    assert_eq!(lib.spt_generation, SptGeneration::Spt3);
+    assert!(version::validate_string("3.11.3", SptGeneration::Spt4).is_err());
+    assert!(version::validate_string("3.8.3", SptGeneration::Spt3).is_err());
+    assert!(version::validate_string("3.8.3", SptGeneration::Aki).is_ok());
 }
 
 #[test]
-fn test_purge_removes_deactivated_mods() {
+fn test_copy_deployment_mode() {
     let (_tmp, game_root, repo_root) = setup_test_env();
-    let requirement = LibraryCreationRequirement {
-        repo_root: Some(repo_root.clone()),
-        game_root: game_root.clone(),
-        name: "Test Library".to_string(),
-    };
-    let mut lib = Library::create(requirement).unwrap();
+    let mut lib = new_library(&game_root, &repo_root);
     let rules = SPTPathRules::default();
 
-    // 1. Add and activate mod
-    create_test_mod(&repo_root.join("src"), "DeleteMe", true);
-    let fs = ModFS::new(&repo_root.join("src"), &rules).unwrap();
-    let staged = create_staged_mod_for_test(&repo_root.join("src"), fs);
-    mod_manager::add_mod(&mut lib, staged).unwrap();
-    lib.mods.get_mut("DeleteMe").unwrap().is_active = true;
+    for name in ["Alpha", "Beta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        lib.mods.get_mut(name).unwrap().is_active = true;
+    }
+    library_service::set_deployment_mode(&mut lib, DeploymentMode::Copy).unwrap();
+    assert_eq!(
+        Library::open(&repo_root).unwrap().deployment_mode,
+        DeploymentMode::Copy
+    );
 
-    // Sync
+    // 1. Files are copied, not linked, and recorded with their digest
+    let sync = |lib: &mut Library| {
+        library_service::sync(lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap()
+    };
+    sync(&mut lib);
+    let alpha = game_root.join(&rules.server_mods).join("Alpha");
+    let beta = game_root.join(&rules.server_mods).join("Beta");
+    let alpha_file = alpha.join("content.txt");
+    assert!(!fs::symlink_metadata(&alpha)
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert!(linker::read_link_target(&alpha).is_err());
+    assert_eq!(fs::read_to_string(&alpha_file).unwrap(), "Alpha");
+    let ledger = deploy_ledger::read(&lib.lib_paths).unwrap();
+    assert_eq!(ledger.copies.len(), 2);
+
+    // 2. Switching only removes the copies of the deactivated mod, folder included
+    lib.mods.get_mut("Beta").unwrap().is_active = false;
+    library_service::sync_incremental(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort)
+        .unwrap();
+    assert!(!beta.exists());
+    assert!(alpha_file.exists());
+
+    // 3. An edited copy is the user's now and survives the purge
+    fs::write(&alpha_file, "tweaked").unwrap();
     cleanup::purge(
         &lib.game_root,
         &lib.repo_root,
@@ -225,22 +4511,18 @@ fn test_purge_removes_deactivated_mods() {
         &lib.cache,
     )
     .unwrap();
-    deployment::deploy(
-        &lib.game_root,
-        &lib.lib_paths,
-        &lib.spt_rules,
-        &lib.mods,
-        &lib.cache,
-    )
-    .unwrap();
-    lib.mark_clean();
-    lib.persist().unwrap();
-
-    let target_path = game_root.join(&rules.server_mods).join("DeleteMe");
-    assert!(target_path.exists());
-
-    // 2. Deactivate and sync
-    lib.mods.get_mut("DeleteMe").unwrap().is_active = false;
+    assert_eq!(fs::read_to_string(&alpha_file).unwrap(), "tweaked");
+    assert!(deploy_ledger::read(&lib.lib_paths)
+        .unwrap()
+        .copies
+        .is_empty());
+
+    // 4. A file deployment did not copy is never overwritten
+    let err = library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort)
+        .unwrap_err();
+    assert!(matches!(err, SError::LinkFailed(_)));
+    fs::remove_file(&alpha_file).unwrap();
+    sync(&mut lib);
     cleanup::purge(
         &lib.game_root,
         &lib.repo_root,
@@ -249,68 +4531,743 @@ fn test_purge_removes_deactivated_mods() {
         &lib.cache,
     )
     .unwrap();
-    deployment::deploy(
-        &lib.game_root,
-        &lib.lib_paths,
-        &lib.spt_rules,
-        &lib.mods,
-        &lib.cache,
+    assert!(!alpha.exists());
+    let source = lib.lib_paths.mods.join("Alpha").join(&rules.server_mods);
+    assert!(source.join("Alpha/content.txt").exists());
+}
+
+/// Stages a `.pak` file holding the path of a mod folder, as a stand-in for a bundle format.
+struct PakHandler;
+
+impl StagingHandler for PakHandler {
+    fn name(&self) -> &str {
+        "pak"
+    }
+
+    fn stage(
+        &self,
+        input: &Utf8Path,
+        material: &StageMaterial,
+        _progress: Progress,
+    ) -> Option<Result<StagedMod, SError>> {
+        if input.extension() != Some("pak") {
+            return None;
+        }
+        let staged = fs::read_to_string(input)
+            .map_err(SError::from)
+            .and_then(|source| {
+                let dest = material.root.join(input.file_stem().unwrap());
+                FileUtils::copy_recursive(Utf8Path::new(source.trim()), &dest)?;
+                let fs = ModFS::new(&dest, &material.rules)?;
+                Ok(StagedMod {
+                    is_staging: true,
+                    ..create_staged_mod_for_test(&dest, fs)
+                })
+            });
+        Some(staged)
+    }
+}
+
+#[test]
+fn test_custom_staging_handler() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let material = lib.stage_material("Unknown".to_string(), FrameworkPolicy::Quarantine);
+
+    let src = repo_root.join("src").join("Packed");
+    create_test_mod(&src, "Packed", true);
+    let pak = repo_root.join("Packed.pak");
+    fs::write(&pak, src.as_str()).unwrap();
+
+    // 1. Nothing built in recognises the format
+    let staged = mod_stager::resolve(std::slice::from_ref(&pak), &material, Progress::silent());
+    assert!(staged.unwrap().is_empty());
+
+    // 2. A registered handler picks it up, next to the built-in ones
+    staging_handler::register(Arc::new(PakHandler));
+    let staged =
+        mod_stager::resolve(&[pak.clone(), src.clone()], &material, Progress::silent()).unwrap();
+    assert_eq!(staged.len(), 2);
+    assert!(staged[0].is_staging && staged[0].source_path.starts_with(&material.root));
+    assert!(!staged[1].is_staging);
+
+    // 3. The staged copy installs like any other mod and is removed afterwards
+    let source = staged[0].source_path.clone();
+    let packed = staged.into_iter().take(1).collect();
+    mod_manager::add_staged(&mut lib, packed, Progress::silent()).unwrap();
+    assert!(lib.mods.contains_key("Packed"));
+    assert!(!source.exists());
+}
+
+#[test]
+fn test_local_repo_store() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let lib = new_library(&game_root, &repo_root);
+    let store = repo_store::open(&lib.lib_paths);
+
+    let v1 = repo_root.join("src/v1");
+    let v2 = repo_root.join("src/v2");
+    create_test_mod(&v1, "Stored", true);
+    create_test_mod(&v2, "Stored", true);
+    fs::write(v1.join("old.txt"), "v1").unwrap();
+
+    // 1. Payloads are plain folders under the mods folder
+    assert!(!store.contains("Stored"));
+    store
+        .put("Stored", &v1, Progress::silent(), "Stored")
+        .unwrap();
+    assert_eq!(
+        store.payload_dir("Stored"),
+        lib.lib_paths.mods.join("Stored")
+    );
+    assert!(store.payload_dir("Stored").join("old.txt").exists());
+
+    // 2. A new payload replaces the previous one entirely, leaving nothing in staging
+    store
+        .put("Stored", &v2, Progress::silent(), "Stored")
+        .unwrap();
+    assert!(!store.payload_dir("Stored").join("old.txt").exists());
+    assert_eq!(fs::read_dir(&lib.lib_paths.staging).unwrap().count(), 0);
+
+    // 3. Exported copies are independent of the payload
+    let exported = repo_root.join("exported");
+    store.export("Stored", &exported).unwrap();
+    store.remove("Stored").unwrap();
+    store.remove("Stored").unwrap();
+    assert!(!store.contains("Stored"));
+    assert!(exported.join(ModPaths::default().file).exists());
+
+    // 4. After a crash between the two renames of a put, the previous payload is moved back
+    // and unfinished builds are dropped
+    store
+        .put("Stored", &v1, Progress::silent(), "Stored")
+        .unwrap();
+    let replaced = lib.lib_paths.staging.join("replaced-crash");
+    fs::create_dir_all(&replaced).unwrap();
+    fs::rename(store.payload_dir("Stored"), replaced.join("Stored")).unwrap();
+    create_test_mod(&lib.lib_paths.staging.join("promote-crash"), "Stored", true);
+
+    repo_store::recover(&lib.lib_paths);
+    assert!(store.payload_dir("Stored").join("old.txt").exists());
+    assert_eq!(fs::read_dir(&lib.lib_paths.staging).unwrap().count(), 0);
+}
+
+#[test]
+fn test_link_reports_its_kind() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = Utf8Path::from_path(tmp.path()).unwrap();
+    let source = root.join("repo/file.dll");
+    let target = root.join("game/file.dll");
+    fs::create_dir_all(source.parent().unwrap()).unwrap();
+    fs::write(&source, "payload").unwrap();
+
+    // 1. A second link to the same source is recognised rather than recreated
+    let kind = linker::link(&source, &target).unwrap();
+    assert_ne!(kind, LinkKind::Existing);
+    assert_eq!(linker::link(&source, &target).unwrap(), LinkKind::Existing);
+
+    // 2. Hard links across volumes fail with the errors the fallback is taken for
+    #[cfg(unix)]
+    let (cross_device, other) = (18, 17);
+    #[cfg(windows)]
+    let (cross_device, other) = (17, 5);
+    assert!(linker::is_cross_device(&io::Error::from_raw_os_error(
+        cross_device
+    )));
+    assert!(!linker::is_cross_device(&io::Error::from_raw_os_error(
+        other
+    )));
+}
+
+#[test]
+fn test_health_watch_revalidation() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+
+    let src = repo_root.join("src").join("Watched");
+    create_test_mod(&src, "Watched", true);
+    let fs = ModFS::new(&src, &rules).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    lib.mods.get_mut("Watched").unwrap().is_active = true;
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+
+    // 1. A late check is only taken for a resume past the allowed delay
+    let planned = SystemTime::now();
+    assert!(!health_watch::has_resumed(
+        planned,
+        planned + Duration::from_secs(5)
+    ));
+    assert!(health_watch::has_resumed(
+        planned,
+        planned + Duration::from_secs(3600)
+    ));
+    assert!(!health_watch::has_resumed(
+        planned + Duration::from_secs(5),
+        planned
+    ));
+
+    // 2. Deployed files are only checked on deep runs
+    linker::unlink(&game_root.join(&rules.server_mods).join("Watched")).unwrap();
+    assert!(health_watch::check(&lib, false, false)
+        .broken_links
+        .is_empty());
+    let health = health_watch::check(&lib, true, true);
+    assert!(health.resumed && health.game_root_available && health.repo_available);
+    assert_eq!(health.broken_links, vec!["Watched".to_string()]);
+
+    // 3. An unplugged game drive is reported without looking for files
+    let moved = game_root.with_file_name("unplugged");
+    fs::rename(&game_root, &moved).unwrap();
+    let health = health_watch::check(&lib, false, true);
+    assert!(!health.game_root_available && health.repo_available);
+    assert!(health.broken_links.is_empty());
+    fs::rename(&moved, &game_root).unwrap();
+    assert_eq!(
+        health_watch::Availability::of(&lib),
+        health_watch::Availability {
+            game_root: true,
+            repo: true
+        }
+    );
+}
+
+#[test]
+fn test_failed_sync_rolls_back() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["Alpha", "Beta", "Delta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    mod_manager::toggle_mod(&mut lib, "Alpha", true).unwrap();
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    let link = |name: &str| game_root.join(&rules.server_mods).join(name);
+    let source = lib
+        .lib_paths
+        .mods
+        .join("Alpha")
+        .join(&rules.server_mods)
+        .join("Alpha");
+    assert_eq!(linker::read_link_target(&link("Alpha")).unwrap(), source);
+
+    // 1. Beta is blocked by a user file, while Delta links fine after it
+    fs::write(link("Beta"), "user file").unwrap();
+    mod_manager::toggle_mod(&mut lib, "Beta", true).unwrap();
+    mod_manager::toggle_mod(&mut lib, "Delta", true).unwrap();
+    let err = library_service::sync(
+        &mut lib,
+        ChecklistPolicy::Inform,
+        LinkFailurePolicy::Continue,
     )
-    .unwrap();
+    .unwrap_err();
+    assert!(matches!(err, SError::LinkFailed(_)));
+
+    // 2. The game root is back to what the previous sync left
+    assert_eq!(linker::read_link_target(&link("Alpha")).unwrap(), source);
+    assert_eq!(fs::read_to_string(link("Beta")).unwrap(), "user file");
+    assert!(fs::symlink_metadata(link("Delta")).is_err());
+    assert!(!lib.lib_paths.journal.exists());
+
+    // 3. An interrupted sync is rolled back when the library is opened, despite a torn entry
+    let stray = game_root.join(&rules.server_mods).join("Stray");
+    let interrupt = |path: &Utf8Path| {
+        fs::write(&lib.lib_paths.journal, "").unwrap();
+        deploy_journal::record(
+            &lib.lib_paths,
+            Action::CreateDir {
+                path: path.to_path_buf(),
+            },
+        )
+        .unwrap();
+        fs::create_dir(path).unwrap();
+    };
+    interrupt(&stray);
+    let mut journal = fs::OpenOptions::new()
+        .append(true)
+        .open(&lib.lib_paths.journal)
+        .unwrap();
+    journal.write_all(b"{\"action\":\"link\",\"pa").unwrap();
+    assert_eq!(
+        deploy_journal::read(&lib.lib_paths).unwrap().actions.len(),
+        1
+    );
+    deploy_journal::recover(&lib.lib_paths);
+    assert!(!stray.exists());
+    assert!(!lib.lib_paths.journal.exists());
+
+    // 4. And by the next sync
+    interrupt(&stray);
+    fs::remove_file(link("Beta")).unwrap();
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    assert!(!stray.exists());
+    assert!(link("Delta").exists());
+    assert!(!lib.lib_paths.journal.exists());
+}
+
+#[test]
+fn test_volume_status() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+
+    // 1. Both roots share a local drive, so the library links
+    let status = volume::status(&lib);
+    assert_eq!(status.game_root, VolumeKind::Local);
+    assert_eq!(status.repo, VolumeKind::Local);
+    assert!(status.same_volume);
+    assert_eq!(status.recommended_mode, DeploymentMode::Link);
+    assert_eq!(lib.deployment_mode, DeploymentMode::Link);
+    assert_eq!(support_bundle::snapshot(&lib).volumes, status);
+
+    // 2. Folders not created yet are judged by the drive above them
+    assert_eq!(
+        volume::kind_of(&repo_root.join("not/yet")),
+        VolumeKind::Local
+    );
+    assert!(volume::is_same_volume(
+        &game_root,
+        &repo_root.join("not/yet")
+    ));
+
+    // 3. Nothing to warn about on sync
+    let warnings =
+        library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    assert!(!warnings
+        .iter()
+        .any(|w| w.kind == WarningKind::UnsuitableVolume));
+}
+
+#[test]
+fn test_fs_watch_detects_external_changes() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["Alpha", "Beta"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        mod_manager::toggle_mod(&mut lib, name, true).unwrap();
+        local_edits::keep(&mut lib, name).unwrap();
+    }
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+    let roots = fs_watch::watched_roots(&lib);
+    assert!(roots.contains(&lib.lib_paths.mods));
+    assert!(roots.contains(&game_root.join(&rules.server_mods)));
+
+    // 1. What the app did itself leaves nothing to report
+    let alpha_dir = lib.lib_paths.mods.join("Alpha");
+    let alpha_files: Vec<Utf8PathBuf> = lib.cache.mods["Alpha"]
+        .files
+        .iter()
+        .map(|rel| alpha_dir.join(rel))
+        .collect();
+    assert!(fs_watch::flag(&mut lib, &alpha_files).is_none());
+    assert!(!lib.to_dto().is_dirty);
+
+    // 2. Another tool rewrites a file of Alpha and drops a new mod folder
+    fs::write(&alpha_files[0], "updated elsewhere").unwrap();
+    let stray = lib.lib_paths.mods.join("Stray");
+    fs::create_dir_all(&stray).unwrap();
+    let changes = fs_watch::flag(&mut lib, &[alpha_files[0].clone(), stray]).unwrap();
+    assert_eq!(changes.changed_mods, vec!["Alpha", "Stray"]);
+    assert!(changes.broken_mods.is_empty());
+    assert!(lib.to_dto().is_dirty);
+
+    // 3. A plugin deleted by hand in the game root breaks the deployed mod
     lib.mark_clean();
-    lib.persist().unwrap();
+    let beta = game_root.join(&rules.server_mods).join("Beta");
+    linker::unlink(&beta).unwrap();
+    let changes = fs_watch::detect(&lib, &[beta]);
+    assert!(changes.changed_mods.is_empty());
+    assert_eq!(changes.broken_mods, vec!["Beta"]);
+}
 
-    // 3. Verify it's gone from game but exists in repo
-    assert!(!target_path.exists());
-    assert!(lib.lib_paths.mods.join("DeleteMe").exists());
+#[test]
+fn test_operation_logs() {
+    logging::init(None);
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let src = repo_root.join("src").join("Alpha");
+    create_test_mod(&src, "Alpha", false);
+    let fs = ModFS::new(&src, &SPTPathRules::default()).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    library_service::sync(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort).unwrap();
+
+    // 1. A failing operation is logged with its error
+    let mut fs = ModFS::new(&src, &SPTPathRules::default()).unwrap();
+    fs.id = "com1.txt".to_string();
+    assert!(mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).is_err());
+
+    // 2. Newest first, each with what it logged while running
+    let logs = logging::read(&lib.lib_paths.logs, None).unwrap();
+    let operations: Vec<LoggedOperation> = logs.iter().map(|log| log.operation).collect();
+    assert_eq!(
+        operations,
+        vec![
+            LoggedOperation::AddMods,
+            LoggedOperation::Sync,
+            LoggedOperation::AddMods
+        ]
+    );
+    assert!(logs[0].error.is_some());
+    assert!(logs[1].error.is_none());
+    assert!(logs[1]
+        .steps
+        .iter()
+        .any(|step| step.level == LogLevel::Info && step.message.contains("Sync 0 active mod")));
+    assert_eq!(
+        logging::read(&lib.lib_paths.logs, Some(1)).unwrap(),
+        logs[..1]
+    );
+
+    // 3. Only the latest logs are kept
+    for _ in 0..logging::MAX_OPERATION_LOGS {
+        mod_manager::remove_mod(&mut lib, "Missing").unwrap();
+    }
+    let logs = logging::read(&lib.lib_paths.logs, None).unwrap();
+    assert_eq!(logs.len(), logging::MAX_OPERATION_LOGS);
+    assert!(logs
+        .iter()
+        .all(|log| log.operation == LoggedOperation::RemoveMod));
 }
 
 #[test]
-fn test_to_frontend_dto_enrichment() {
+fn test_repair_library_restores_manifest() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let src = repo_root.join("src").join("Alpha");
+    create_test_mod(&src, "Alpha", false);
+    let fs = ModFS::new(&src, &SPTPathRules::default()).unwrap();
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+
+    // 1. Writes leave no temporary files behind
+    assert!(std::fs::read_dir(&repo_root)
+        .unwrap()
+        .flatten()
+        .all(|entry| !entry.file_name().to_string_lossy().ends_with(".tmp")));
+
+    // 2. Only the latest generations are kept
+    for i in 0..manifest_history::GENERATIONS + 2 {
+        mod_manager::toggle_mod(&mut lib, "Alpha", i % 2 == 0).unwrap();
+    }
+    let generations = manifest_history::generations(&lib.lib_paths).unwrap();
+    assert_eq!(generations.len(), manifest_history::GENERATIONS);
+    let latest = std::fs::read_to_string(&generations[0]).unwrap();
+    assert_eq!(
+        latest,
+        std::fs::read_to_string(&lib.lib_paths.manifest).unwrap()
+    );
+
+    // 3. A readable manifest is left alone
+    assert!(!manifest_history::repair(&repo_root).unwrap());
+
+    // 4. A torn manifest is restored from the latest generation
+    std::fs::write(&lib.lib_paths.manifest, "name = \"Test Lib").unwrap();
+    assert!(Library::open(&repo_root).is_err());
+    assert!(manifest_history::repair(&repo_root).unwrap());
+    let reopened = Library::open(&repo_root).unwrap();
+    assert!(!reopened.mods["Alpha"].is_active);
+
+    // 5. Without a readable generation there is nothing to restore
+    std::fs::write(&lib.lib_paths.manifest, "").unwrap();
+    std::fs::remove_dir_all(&lib.lib_paths.history).unwrap();
+    assert!(matches!(
+        manifest_history::repair(&repo_root),
+        Err(SError::InvalidLibrary(..))
+    ));
+}
+
+#[test]
+fn test_confirmation_tokens() {
+    let confirmations = Confirmations::default();
+    let remove = DestructiveAction::RemoveLibrary {
+        repo_root: "/libraries/a".to_string(),
+    };
+    let other = DestructiveAction::RemoveLibrary {
+        repo_root: "/libraries/b".to_string(),
+    };
+
+    // 1. No token, or an unknown one, is refused
+    assert!(matches!(
+        confirmations.consume(None, &remove),
+        Err(SError::ConfirmationRequired(_))
+    ));
+    assert!(confirmations.consume(Some("guess"), &remove).is_err());
+
+    // 2. A token works once, for its own action only
+    assert!(confirmation::prompt(&remove)
+        .message
+        .contains("/libraries/a"));
+    let confirmation = confirmations.issue(remove.clone());
+    assert_eq!(confirmation.action, remove);
+    assert!(confirmations
+        .consume(Some(&confirmation.token), &other)
+        .is_err());
+    let confirmation = confirmations.issue(remove.clone());
+    confirmations
+        .consume(Some(&confirmation.token), &remove)
+        .unwrap();
+    assert!(confirmations
+        .consume(Some(&confirmation.token), &remove)
+        .is_err());
+
+    // 3. Expired tokens are refused
+    let confirmations = Confirmations::with_ttl(Duration::ZERO);
+    let confirmation = confirmations.issue(remove.clone());
+    assert!(confirmations
+        .consume(Some(&confirmation.token), &remove)
+        .is_err());
+}
+
+#[test]
+fn test_rebuild_cache() {
     let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    for name in ["Alpha", "Beta", "Gamma"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, false);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+    }
+    let files = lib.cache.mods["Alpha"].files.clone();
+    // A mod without manifest, known by its path id
+    let loose_src = repo_root.join("src/Loose");
+    let loose_dir = loose_src.join(&rules.client_plugins).join("Loose");
+    fs::create_dir_all(&loose_dir).unwrap();
+    fs::write(loose_dir.join("loose.dll"), "loose").unwrap();
+    let loose = ModFS::new(&loose_src, &rules).unwrap();
+    let (loose_id, loose_canonical) = (loose.id.clone(), loose.canonical_id.clone());
+    assert!(loose_canonical.is_some());
+    mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&loose_src, loose)).unwrap();
+
+    // 1. Without its cache the library no longer loads
+    fs::remove_dir_all(&lib.lib_paths.cache_store).unwrap();
+    fs::remove_dir_all(lib.lib_paths.mods.join("Gamma")).unwrap();
+    fs::create_dir_all(lib.lib_paths.mods.join("Stray")).unwrap();
+    fs::write(lib.lib_paths.mods.join("Stray").join("file.txt"), "stray").unwrap();
+    assert!(Library::load(&repo_root).is_err());
+
+    // 2. The rebuild reports orphans both ways and tracks only the mods of the manifest
+    let mut reopened = Library::open(&repo_root).unwrap();
+    let report = library_service::rebuild_cache(&mut reopened).unwrap();
+    let mut rebuilt = vec!["Alpha".to_string(), "Beta".to_string(), loose_id.clone()];
+    rebuilt.sort();
+    assert_eq!(report.rebuilt, rebuilt);
+    assert_eq!(report.untracked_folders, vec!["Stray"]);
+    assert_eq!(report.missing_folders, vec!["Gamma"]);
+    assert_eq!(reopened.cache.mods["Alpha"].files, files);
+    assert_eq!(reopened.cache.mods["Alpha"].canonical_id, None);
+    assert_eq!(reopened.cache.mods[&loose_id].canonical_id, loose_canonical);
+    assert_eq!(file_search::find_mods(&reopened, "beta"), vec!["Beta"]);
+
+    // 3. The fresh cache is persisted
+    let loaded = Library::load(&repo_root).unwrap();
+    let ids: Vec<&String> = loaded.cache.mods.keys().collect();
+    assert_eq!(ids, rebuilt.iter().collect::<Vec<_>>());
+
+    // 4. A rebuild over an existing store replaces it whole
+    let stale = lib.lib_paths.cache_store.join("stale.msgpack");
+    fs::write(&stale, "stale").unwrap();
+    let mut loaded = loaded;
+    library_service::rebuild_cache(&mut loaded).unwrap();
+    assert!(!stale.exists());
+    assert_eq!(
+        fs::read_dir(&lib.lib_paths.cache_store).unwrap().count(),
+        rebuilt.len()
+    );
+    assert!(!repo_root.join("cache.building").exists());
+    assert!(!repo_root.join("cache.replaced").exists());
+}
+
+#[test]
+fn test_library_lock() {
+    let (_temp, game_root, _) = setup_test_env();
+    let mut config = GlobalConfig::default();
     let requirement = LibraryCreationRequirement {
-        repo_root: Some(repo_root.clone()),
-        game_root: game_root.clone(),
-        name: "Test Library".to_string(),
+        repo_root: None,
+        game_root,
+        name: "Locked".to_string(),
     };
-    let mut lib = Library::create(requirement).expect("Failed to create library");
+    let library = library_service::create_library(&mut config, requirement).unwrap();
+    let repo_root = library.repo_root.clone();
+
+    // 1. The process holding a library can open it again
+    let again = library_service::open_library(&mut config, &repo_root).unwrap();
+    let lock_path = library.lib_paths.lock.clone();
+    drop((library, again));
+
+    // 2. Another holder, like a headless task, keeps it from being opened
+    let other = fs::File::open(&lock_path).unwrap();
+    other.lock().unwrap();
+    assert!(matches!(
+        library_service::open_library(&mut config, &repo_root),
+        Err(SError::LibraryLocked(_))
+    ));
+
+    // 3. It opens once the other holder is gone
+    other.unlock().unwrap();
+    let mut owner = library_service::open_library(&mut config, &repo_root).unwrap();
+
+    // 4. A read-only library takes no lock, so guests open it while another process holds it,
+    // and it cannot be made editable again until that one is gone
+    owner.set_read_only(true).unwrap();
+    other.lock().unwrap();
+    let guest = library_service::open_library(&mut config, &repo_root).unwrap();
+    assert!(guest.is_read_only());
+    assert!(matches!(
+        owner.set_read_only(false),
+        Err(SError::LibraryLocked(_))
+    ));
+    assert!(owner.is_read_only());
+    other.unlock().unwrap();
+    owner.set_read_only(false).unwrap();
+}
 
-    // 1. Prepare a mod with a real manifest file on disk
-    let mod_src = _tmp.path().join("source_mod");
-    let mod_src_utf8 = Utf8Path::from_path(&mod_src).unwrap();
+#[cfg(unix)]
+#[test]
+fn test_rar_extraction_skips_symlinks() {
+    let tmp = tempfile::tempdir().unwrap();
+    let destination = Utf8PathBuf::from_path_buf(tmp.path().join("extracted")).unwrap();
+    // Holds real.dll and link.dll, a Unix link to it
+    let archive = Utf8Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/symlink.rar");
+
+    let report = decompression::extract(&archive, &destination, Progress::silent()).unwrap();
+    assert_eq!(report.extracted_bytes, 6);
+    assert_eq!(
+        report.skipped,
+        vec![SkippedEntry {
+            name: "link.dll".to_string(),
+            reason: SkipReason::Symlink,
+        }]
+    );
+    assert_eq!(fs::read(destination.join("real.dll")).unwrap(), b"plugin");
+    assert!(fs::symlink_metadata(destination.join("link.dll")).is_err());
+}
 
-    let manifest_path = mod_src_utf8.join("manifest/manifest.json");
-    std::fs::create_dir_all(manifest_path.parent().unwrap()).unwrap();
+#[test]
+fn test_crash_guard_blames_and_disables_mod() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
+    let rules = SPTPathRules::default();
+    repo_history::enable(&lib.repo_root).unwrap();
+    for name in ["Loot", "Maps"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let staged = create_staged_mod_for_test(&src, ModFS::new(&src, &rules).unwrap());
+        mod_manager::add_staged(&mut lib, vec![staged], Progress::silent()).unwrap();
+        lib.mods.get_mut(name).unwrap().is_active = true;
+    }
+    library_service::sync_incremental(&mut lib, ChecklistPolicy::Inform, LinkFailurePolicy::Abort)
+        .unwrap();
+    let logs = game_root.join(&rules.server_logs).join("spt");
+    fs::create_dir_all(&logs).unwrap();
+    let log = logs.join("spt.log");
+
+    // 1. No log, or a log without errors, blames nobody
+    assert_eq!(crash_guard::attribute(&lib), None);
+    fs::write(&log, "[Info] Loading user\\mods\\Loot\\src\\mod.js\n").unwrap();
+    assert_eq!(crash_guard::attribute(&lib), None);
+
+    // 2. Errors naming the folder of one active mod blame it
+    fs::write(
+        &log,
+        "[Info] Server starting\n\
+         [Error] TypeError: undefined at C:\\SPT\\user\\mods\\Loot\\src\\mod.js:12\n",
+    )
+    .unwrap();
+    assert_eq!(crash_guard::attribute(&lib), Some("Loot".to_string()));
 
-    let manifest_data = r#"{
-        "id": "test-mod-id",
-        "name": "Test Mod Name",
-        "version": "1.0.0",
-        "author": "someone",
-        "sptVersion": "3.9.0"
-    }"#;
-    std::fs::write(&manifest_path, manifest_data).unwrap();
+    // 3. Errors naming several mods are not attributed
+    fs::write(
+        &log,
+        "[Error] user/mods/Loot/src/mod.js failed\n[Error] user/mods/Maps/src/mod.js failed\n",
+    )
+    .unwrap();
+    assert_eq!(crash_guard::attribute(&lib), None);
+
+    // 4. Only a repeated crash counts
+    let mut counter = crash_guard::CrashCounter::default();
+    assert!(!counter.count("Loot"));
+    assert!(!counter.count("Maps"));
+    assert!(counter.count("Loot"));
+    assert!(!counter.count("Loot"));
+
+    // 5. Disabling deactivates the mod, records it and deploys without it
+    crash_guard::disable(
+        &mut lib,
+        "Loot",
+        ChecklistPolicy::Inform,
+        LinkFailurePolicy::Abort,
+    )
+    .unwrap();
+    assert!(!lib.mods["Loot"].is_active);
+    assert!(!game_root.join(&rules.server_mods).join("Loot").exists());
+    assert!(game_root.join(&rules.server_mods).join("Maps").exists());
+    let history = repo_history::history(&lib.repo_root, 10).unwrap();
+    assert!(history
+        .iter()
+        .any(|c| c.message == "Disable Loot after repeated crashes"));
+}
 
-    // 2. Mock some files inside the mod so ModFS::new works
+#[test]
+fn test_compat_notes_roundtrip() {
+    let (_tmp, game_root, repo_root) = setup_test_env();
+    let mut lib = new_library(&game_root, &repo_root);
     let rules = SPTPathRules::default();
-    let dummy_dll = mod_src_utf8
-        .join(&rules.server_mods)
-        .join("TestMod/mod.dll");
-    std::fs::create_dir_all(dummy_dll.parent().unwrap()).unwrap();
-    std::fs::write(dummy_dll, "").unwrap();
 
-    // 3. Add mod to library
-    let fs = ModFS::new(mod_src_utf8, &rules).unwrap();
-    let staged = create_staged_mod_for_test(mod_src_utf8, fs);
-    mod_manager::add_mod(&mut lib, staged).expect("Add mod failed");
+    for name in ["Loot", "Economy", "Maps"] {
+        let src = repo_root.join("src").join(name);
+        create_test_mod(&src, name, true);
+        let fs = ModFS::new(&src, &rules).unwrap();
+        mod_manager::add_mod(&mut lib, create_staged_mod_for_test(&src, fs)).unwrap();
+        lib.mods.get_mut(name).unwrap().is_active = name != "Maps";
+    }
 
-    // 4. Check Frontend DTO
-    let dto = dto_builder::build_frontend_dto(&lib);
-    let m = dto.mods.get("test-mod-id").expect("Mod not found in DTO");
+    let note = |a: &str, b: &str, kind| CompatNote {
+        mod_a: a.to_string(),
+        mod_b: b.to_string(),
+        kind,
+        note: "Prices double".to_string(),
+        unless: Some("Disable the economy multiplier".to_string()),
+    };
 
-    // Assert the manifest was successfully pulled from cache into the DTO
-    assert!(m.manifest.is_some());
-    assert_eq!(m.manifest.as_ref().unwrap().name, "Test Mod Name");
+    // 1. The pair is unordered, so a second note for it replaces the first
+    compat_notes::upsert(&mut lib, note("Loot", "Economy", CompatKind::Conflicts)).unwrap();
+    compat_notes::upsert(&mut lib, note("Economy", "Loot", CompatKind::Compatible)).unwrap();
+    compat_notes::upsert(&mut lib, note("Maps", "Loot", CompatKind::Conflicts)).unwrap();
+    assert_eq!(lib.compat_notes.len(), 2);
+    assert!(compat_notes::upsert(&mut lib, note("Loot", "Loot", CompatKind::Conflicts)).is_err());
+
+    // 2. Only notes about two active mods are surfaced
+    assert_eq!(
+        compat_notes::for_active(&lib),
+        vec![note("Economy", "Loot", CompatKind::Compatible)]
+    );
+
+    // 3. Notes survive reopening and can be shared with another library
+    assert_eq!(
+        Library::open(&repo_root).unwrap().compat_notes,
+        lib.compat_notes
+    );
+    let exported = repo_root.join("notes.toml");
+    compat_notes::export(&lib, &exported).unwrap();
+
+    let (_tmp2, other_game, other_repo) = setup_test_env();
+    let mut other = Library::create(LibraryCreationRequirement {
+        repo_root: Some(other_repo),
+        game_root: other_game,
+        name: "Friend".to_string(),
+    })
+    .unwrap();
+    assert_eq!(compat_notes::import(&mut other, &exported).unwrap(), 2);
+    assert_eq!(other.compat_notes, lib.compat_notes);
+    assert!(compat_notes::remove(&mut other, "Loot", "Maps").unwrap());
+    assert_eq!(other.compat_notes.len(), 1);
 }
 
 #[test]
@@ -429,6 +5386,8 @@ fn test_untracked_file_safety_in_shared_folder() {
         &lib.spt_rules,
         &lib.mods,
         &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
     )
     .unwrap();
     lib.mark_clean();
@@ -458,6 +5417,8 @@ fn test_untracked_file_safety_in_shared_folder() {
         &lib.spt_rules,
         &lib.mods,
         &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
     )
     .unwrap();
     lib.mark_clean();
@@ -522,6 +5483,8 @@ fn test_persistence_cycle() {
         &lib.spt_rules,
         &lib.mods,
         &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
     )
     .unwrap();
     lib.mark_clean();
@@ -638,6 +5601,36 @@ fn test_validate_library_structure_missing_directory() {
     }
 }
 
+#[test]
+fn test_discover_existing_libraries() {
+    let (tmp, game_root, _repo_root) = setup_test_env();
+    let root = Utf8PathBuf::from_path_buf(tmp.path().to_path_buf()).unwrap();
+    let requirement = LibraryCreationRequirement {
+        repo_root: None,
+        game_root: game_root.clone(),
+        name: "Found Library".to_string(),
+    };
+    library_service::create_library(&mut GlobalConfig::default(), requirement).unwrap();
+    let expected = game_root.join(".mod_keeper");
+
+    // 1. A fresh config does not know the library, the scan finds it
+    let mut config = GlobalConfig::default();
+    let found =
+        library_discovery::discover(std::slice::from_ref(&root), &config, Progress::silent());
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].repo_root, expected);
+    assert_eq!(found[0].name, "Found Library");
+    assert!(!found[0].is_known);
+
+    // 2. Registering keeps only valid libraries and does not duplicate them
+    let summary = library_discovery::register(&mut config, &[expected.clone(), root.clone()]);
+    assert_eq!(summary.len(), 1);
+    library_discovery::register(&mut config, std::slice::from_ref(&expected));
+    assert_eq!(config.known_libraries, vec![expected]);
+    let found = library_discovery::discover(&[root], &config, Progress::silent());
+    assert!(found[0].is_known);
+}
+
 #[test]
 fn test_create_library_when_mod_keeper_not_exists() {
     let (_tmp, game_root, _repo_root) = setup_test_env();
@@ -990,6 +5983,8 @@ fn test_remove_library_with_mods() {
         &lib.spt_rules,
         &lib.mods,
         &lib.cache,
+        LinkFailurePolicy::Abort,
+        DeploymentMode::Link,
     )
     .expect("Failed to deploy");
 
@@ -0,0 +1,65 @@
+use camino::Utf8PathBuf;
+use mod_keeper_lib::core::metrics::{self, MetricsStore, WINDOW};
+use mod_keeper_lib::models::metrics::Operation;
+use mod_keeper_lib::utils::toml::Toml;
+use std::time::Duration;
+
+#[test]
+fn test_rolling_average_keeps_last_window() {
+    let mut store = MetricsStore::new();
+    for ms in 0..(WINDOW as u64 + 10) {
+        store.record(Operation::Deploy, Duration::from_millis(ms));
+    }
+    store.record(Operation::Purge, Duration::from_micros(1500));
+
+    let summary = store.summary();
+    assert_eq!(summary.len(), 2);
+
+    // Only the last WINDOW samples (10..30) count
+    let deploy = &summary[0];
+    assert_eq!(deploy.operation, Operation::Deploy);
+    assert_eq!(deploy.samples, WINDOW as u32);
+    assert_eq!(deploy.average_ms, 19.5);
+    assert_eq!(deploy.last_ms, 29.0);
+    assert_eq!(summary[1].last_ms, 1.5);
+}
+
+#[test]
+fn test_metrics_store_roundtrip() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = Utf8PathBuf::from_path_buf(tmp.path().join("metrics.toml")).unwrap();
+
+    let mut store = MetricsStore::new();
+    store.record(Operation::AddMod, Duration::from_millis(12));
+    Toml::write(&path, &store).unwrap();
+
+    let loaded: MetricsStore = Toml::read(&path).unwrap();
+    assert_eq!(loaded.summary(), store.summary());
+}
+
+#[test]
+fn test_timer_records_on_drop() {
+    drop(metrics::Timer::start(Operation::PersistCache));
+
+    assert!(metrics::summary()
+        .iter()
+        .any(|m| m.operation == Operation::PersistCache && m.samples > 0));
+}
+
+#[test]
+fn test_samples_are_written_in_batches() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = Utf8PathBuf::from_path_buf(tmp.path().join("metrics.toml")).unwrap();
+    metrics::init(path.clone());
+
+    // A sample is kept in memory until the next write
+    metrics::record(Operation::Deploy, Duration::from_millis(5));
+    assert!(!path.exists());
+
+    metrics::flush();
+    let stored: MetricsStore = Toml::read(&path).unwrap();
+    assert!(stored
+        .summary()
+        .iter()
+        .any(|m| m.operation == Operation::Deploy));
+}
@@ -1,5 +1,6 @@
 use camino::Utf8PathBuf;
-use mod_keeper_lib::core::mod_fs::ModFS;
+use mod_keeper_lib::core::linker;
+use mod_keeper_lib::core::mod_fs::{CanonicalId, ModFS, ID_SCHEME};
 use mod_keeper_lib::models::error::SError;
 use mod_keeper_lib::models::mod_dto::ModType;
 use mod_keeper_lib::models::paths::{ModPaths, SPTPathRules};
@@ -22,7 +23,7 @@ fn test_resolve_id_server_only() {
     let mod_fs = ModFS::new(&root, &rules).unwrap();
 
     // Server ID should be hashed version of "weathermod"
-    let expected_id = hash_id("weathermod");
+    let expected_id = hash_id("v2:weathermod");
     assert_eq!(mod_fs.id, expected_id);
     assert_eq!(mod_fs.mod_type, ModType::Server);
 }
@@ -43,11 +44,46 @@ fn test_resolve_id_client_only() {
     let mod_fs = ModFS::new(&root, &rules).unwrap();
 
     // Client ID should be hashed version of "authorname/logic.dll" (lowercase)
-    let expected_id = hash_id("authorname/logic.dll");
+    let expected_id = hash_id("v2:authorname/logic.dll");
     assert_eq!(mod_fs.id, expected_id);
     assert_eq!(mod_fs.mod_type, ModType::Client);
 }
 
+#[test]
+fn test_resolve_id_managed_root() {
+    let temp = tempdir().unwrap();
+    let root = Utf8PathBuf::from_path_buf(temp.path().to_path_buf()).unwrap();
+    let rules = SPTPathRules::default()
+        .with_managed_roots(vec!["user/launcher".into()])
+        .unwrap();
+
+    // Managed root structure: user/launcher/Themes/dark.json
+    let path = root.join("user/launcher/Themes/dark.json");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, "").unwrap();
+
+    let mod_fs = ModFS::new(&root, &rules).unwrap();
+    assert_eq!(mod_fs.id, hash_id("v2:themes"));
+
+    // Without the root the files are not attributed to any mod
+    assert!(matches!(
+        ModFS::new(&root, &SPTPathRules::default()),
+        Err(SError::UnableToDetermineModId)
+    ));
+}
+
+#[test]
+fn test_managed_roots_validation() {
+    for root in ["", "../outside", "/abs", "SPT/user", "BepInEx/plugins/Sub"] {
+        assert!(
+            SPTPathRules::default()
+                .with_managed_roots(vec![root.into()])
+                .is_err(),
+            "{root} should be rejected"
+        );
+    }
+}
+
 #[test]
 fn test_resolve_id_combined() {
     let temp = tempdir().unwrap();
@@ -67,9 +103,16 @@ fn test_resolve_id_combined() {
 
     // BTreeSet sorts alphabetically:
     // "CoreMod" vs "Fixes.dll"
-    // Result: "coremodfixes.dll" (lowercase, no divider) -> hashed
-    let expected_id = hash_id("coremodfixes.dll");
+    // Result: "v2:coremod|fixes.dll" (lowercase, escaped, divided) -> hashed
+    let expected_id = hash_id("v2:coremod|fixes.dll");
     assert_eq!(mod_fs.id, expected_id);
+    assert_eq!(
+        mod_fs.canonical_id,
+        Some(CanonicalId {
+            scheme: ID_SCHEME,
+            input: "v2:coremod|fixes.dll".to_string(),
+        })
+    );
     assert_eq!(mod_fs.mod_type, ModType::Both);
 }
 
@@ -92,7 +135,7 @@ fn test_resolve_id_ignores_non_dll_in_client_plugins() {
     let mod_fs = ModFS::new(&root, &rules).unwrap();
 
     // ID should be hashed version of "validmod" (lowercase), the readme.txt is ignored
-    let expected_id = hash_id("validmod");
+    let expected_id = hash_id("v2:validmod");
     assert_eq!(mod_fs.id, expected_id);
 }
 
@@ -210,7 +253,62 @@ fn test_deterministic_id_sorting() {
 
     let mod_fs = ModFS::new(&root, &rules).unwrap();
 
-    // BTreeSet should have forced: a_modm_modz_mod (lowercase, no divider) -> hashed
-    let expected_id = hash_id("a_modm_modz_mod");
+    // BTreeSet should have forced: a_mod|m_mod|z_mod (lowercase, divided) -> hashed
+    let expected_id = hash_id("v2:a_mod|m_mod|z_mod");
     assert_eq!(mod_fs.id, expected_id);
 }
+
+#[test]
+fn test_path_id_divider_is_escaped() {
+    let rules = SPTPathRules::default();
+    let id = |names: &[&str]| {
+        let files: Vec<Utf8PathBuf> = names
+            .iter()
+            .map(|n| rules.server_mods.join(n).join("mod.dll"))
+            .collect();
+        ModFS::path_id(&rules, &files).unwrap()
+    };
+
+    // Concatenated, both sets read "ab" under the first scheme
+    assert_ne!(id(&["a", "b"]), id(&["ab"]));
+    assert_ne!(id(&["a", "b"]), id(&["a|b"]));
+    let legacy = ModFS::canonical_path_ids(&rules, &[rules.server_mods.join("a|b/mod.dll")])
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!((legacy.scheme, legacy.input.as_str()), (1, "a|b"));
+}
+
+#[test]
+fn test_scan_ignores_foreign_links_and_loops() {
+    let temp = tempdir().unwrap();
+    let root = Utf8PathBuf::from_path_buf(temp.path().to_path_buf()).unwrap();
+    let rules = SPTPathRules::default();
+    let src = root.join("src");
+    let outside = root.join("outside");
+
+    let mod_dir = src.join(&rules.server_mods).join("LinkedMod");
+    fs::create_dir_all(&mod_dir).unwrap();
+    fs::write(mod_dir.join("mod.js"), "").unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(outside.join("foreign.txt"), "").unwrap();
+
+    // A link back to an ancestor (loop) and a link to an unrelated folder
+    linker::link(&src, &mod_dir.join("loop")).unwrap();
+    linker::link(&outside, &mod_dir.join("foreign")).unwrap();
+
+    // 1. Collection terminates and only reports the real payload
+    let fs_info = ModFS::new(&src, &rules).unwrap();
+    assert_eq!(
+        fs_info.files,
+        vec![rules.server_mods.join("LinkedMod/mod.js")]
+    );
+
+    // 2. Copying skips directory links instead of failing on them
+    let dst = root.join("dst");
+    FileUtils::copy_recursive(&src, &dst).unwrap();
+    let copied = dst.join(&rules.server_mods).join("LinkedMod");
+    assert!(copied.join("mod.js").exists());
+    assert!(!copied.join("loop").exists());
+    assert!(!copied.join("foreign").exists());
+}
@@ -0,0 +1,46 @@
+use mod_keeper_lib::core::process_watch::{transitions, LifecycleChange, ProcessState};
+
+const IDLE: ProcessState = ProcessState {
+    client: false,
+    server: false,
+};
+const SERVER_ONLY: ProcessState = ProcessState {
+    client: false,
+    server: true,
+};
+const PLAYING: ProcessState = ProcessState {
+    client: true,
+    server: true,
+};
+const CLIENT_ONLY: ProcessState = ProcessState {
+    client: true,
+    server: false,
+};
+
+#[test]
+fn test_lifecycle_transitions() {
+    assert!(transitions(IDLE, SERVER_ONLY).is_empty());
+    assert_eq!(
+        transitions(SERVER_ONLY, PLAYING),
+        vec![LifecycleChange::GameStarted]
+    );
+
+    // Server dying under a running client is a crash
+    assert_eq!(
+        transitions(PLAYING, CLIENT_ONLY),
+        vec![LifecycleChange::ServerCrashed(None)]
+    );
+
+    // Regular shutdown: client first, then server
+    assert_eq!(
+        transitions(PLAYING, SERVER_ONLY),
+        vec![LifecycleChange::GameStopped]
+    );
+    assert!(transitions(SERVER_ONLY, IDLE).is_empty());
+
+    // Both gone at once is treated as a normal stop
+    assert_eq!(
+        transitions(PLAYING, IDLE),
+        vec![LifecycleChange::GameStopped]
+    );
+}
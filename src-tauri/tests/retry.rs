@@ -0,0 +1,45 @@
+use camino::Utf8Path;
+use mod_keeper_lib::utils::retry;
+use std::io;
+
+#[test]
+fn test_transient_errors_are_retried() {
+    let path = Utf8Path::new("locked.dll");
+    let mut calls = 0;
+
+    // 1. A lock released before the attempts run out succeeds
+    let result = retry::io("Linking", path, || {
+        calls += 1;
+        match calls {
+            1 | 2 => Err(io::Error::from(io::ErrorKind::ResourceBusy)),
+            _ => Ok(calls),
+        }
+    });
+    assert_eq!(result.unwrap(), 3);
+
+    // 2. A lock that is never released fails after the bounded attempts
+    calls = 0;
+    let result: io::Result<()> = retry::io("Linking", path, || {
+        calls += 1;
+        Err(io::Error::from(io::ErrorKind::ResourceBusy))
+    });
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::ResourceBusy);
+    assert_eq!(calls, 4);
+}
+
+#[test]
+fn test_persistent_errors_fail_immediately() {
+    let mut calls = 0;
+    let result: io::Result<()> = retry::io("Removing", Utf8Path::new("missing"), || {
+        calls += 1;
+        Err(io::Error::from(io::ErrorKind::NotFound))
+    });
+
+    assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+    assert_eq!(calls, 1);
+    assert!(!retry::is_transient(&io::Error::from(
+        io::ErrorKind::PermissionDenied
+    )));
+    // ERROR_ACCESS_DENIED on Windows
+    assert!(!retry::is_transient(&io::Error::from_raw_os_error(5)));
+}
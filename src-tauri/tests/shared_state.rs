@@ -0,0 +1,31 @@
+use mod_keeper_lib::config::global::GlobalConfig;
+use mod_keeper_lib::core::shared_state::SharedState;
+use mod_keeper_lib::models::error::SError;
+use mod_keeper_lib::models::global::FrameworkPolicy;
+
+#[test]
+fn test_config_then_instance_is_allowed() {
+    let shared = SharedState::new(GlobalConfig::default(), None);
+
+    let policy = shared.both(|config, instance| {
+        assert!(instance.is_none());
+        config.framework_policy = FrameworkPolicy::Strip;
+        config.framework_policy
+    });
+    assert_eq!(policy, FrameworkPolicy::Strip);
+
+    // Nested in the documented order
+    let has_library = shared.config(|_| shared.instance(|instance| instance.is_some()));
+    assert!(!has_library);
+    assert!(matches!(
+        shared.with_lib(|_| ()),
+        Err(SError::NoActiveLibrary)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "global config must be locked before the active instance")]
+fn test_instance_then_config_is_rejected() {
+    let shared = SharedState::new(GlobalConfig::default(), None);
+    shared.instance(|_| shared.config(|_| ()));
+}
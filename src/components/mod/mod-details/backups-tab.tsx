@@ -27,13 +27,21 @@ export function BackupsTab({ backups, onRestore }: BackupsTabProps) {
         <Trans>Available Backups</Trans>
       </h3>
       <div className="space-y-2">
-        {backups.map(({ timestamp, path }) => (
+        {backups.map(({ timestamp, version, path }) => (
           <div
             key={timestamp}
             className="flex items-center justify-between p-3 border rounded-lg"
           >
             <div>
-              <p className="font-medium">{formatTimestamp(timestamp)}</p>
+              <p className="font-medium">
+                {version ? (
+                  <Trans>
+                    Version {version} · {formatTimestamp(timestamp)}
+                  </Trans>
+                ) : (
+                  formatTimestamp(timestamp)
+                )}
+              </p>
               <p className="text-sm text-muted-foreground font-mono">
                 {timestamp}
               </p>
@@ -15,10 +15,26 @@ export function translateError(error: SError): string {
         return t(
           msg`Mod related process is currently running. Please close it and try again.`,
         )
+      case 'ChecklistNotAcknowledged':
+        return t(
+          msg`Mods in this library need attention before launch. Review and acknowledge the launch checklist, then sync again.`,
+        )
       case 'UnableToDetermineModId':
         return t(
           msg`Unable to determine the mod ID. Please check the mod files and try again.`,
         )
+      case 'LibraryReadOnly':
+        return t(
+          msg`This library is read-only. Turn off read-only mode or copy it to a writable folder to make changes.`,
+        )
+      case 'RemoteNotConfigured':
+        return t(msg`No remote server is set up for this library.`)
+      case 'LibraryNotSynced':
+        return t(
+          msg`The library has changes that are not synced yet. Sync it, then try again.`,
+        )
+      case 'UnsupportedPlatform':
+        return t(msg`This feature is not available on this operating system.`)
       case 'NoActiveLibrary':
       case 'Unexpected':
         return t(msg`An unexpected error occurred. Please try again.`)
@@ -58,12 +74,77 @@ export function translateError(error: SError): string {
     )
   }
 
+  if ('LinkFailed' in error) {
+    const count = error.LinkFailed.length
+    return t(
+      msg`${count} file(s) could not be linked into the game folder. Close anything using them and sync again.`,
+    )
+  }
+
+  if ('UnmetDependencies' in error) {
+    const count = error.UnmetDependencies.length
+    return t(
+      msg`${count} dependency problem(s) keep these mods from running together. Activate the required mods or turn off the conflicting ones.`,
+    )
+  }
+
+  if ('GitFailed' in error) {
+    const reason = error.GitFailed
+    return t(msg`The library history could not be updated: ${reason}`)
+  }
+
+  if ('RemoteUnreachable' in error) {
+    const path = error.RemoteUnreachable
+    return t(
+      msg`The remote server folder ${path} cannot be reached. Check that the share is mounted and try again.`,
+    )
+  }
+
+  if ('SyncHookFailed' in error) {
+    const reason = error.SyncHookFailed
+    return t(msg`The sync was cancelled because the pre-sync command failed: ${reason}`)
+  }
+
+  if ('UpdateCheckFailed' in error) {
+    const reason = error.UpdateCheckFailed
+    return t(msg`Checking for mod updates failed: ${reason}`)
+  }
+
+  if ('DownloadFailed' in error) {
+    const reason = error.DownloadFailed
+    return t(msg`The download failed: ${reason}`)
+  }
+
+  if ('ServerTaskFailed' in error) {
+    const reason = error.ServerTaskFailed
+    return t(msg`The server task could not be run: ${reason}`)
+  }
+
+  if ('InvalidName' in error) {
+    const reason = error.InvalidName
+    return t(
+      msg`The name or ID is not valid: ${reason}. Please rename it and try again.`,
+    )
+  }
+
+  if ('PathTooLong' in error) {
+    return t(
+      msg`A file path would exceed the Windows path length limit. Move the library to a shorter folder or shorten the mod folder names.`,
+    )
+  }
+
   if ('UnhandledCompression' in error) {
     return t(
       msg`The archive format is not supported. Please extract them to a folder manually.`,
     )
   }
 
+  if ('CorruptArchive' in error) {
+    return t(
+      msg`The archive is corrupted. Please download it again and retry.`,
+    )
+  }
+
   if ('AsyncRuntimeError' in error) {
     return t(msg`An internal error occurred. Please try again.`)
   }
@@ -36,7 +36,7 @@ function RouteComponent() {
         filters: [
           {
             name: 'Archive',
-            extensions: ['zip'],
+            extensions: ['zip', '7z', 'rar'],
           },
         ],
         title: 'Select Mod Files',
@@ -72,7 +72,7 @@ function RouteComponent() {
   const handleSync = async () => {
     setIsSyncing(true)
     try {
-      await sync()
+      await sync('All')
     } catch (err) {
       console.error('Failed to sync mods:', err)
     } finally {
@@ -127,7 +127,7 @@ function RouteComponent() {
                 <DropdownMenuContent>
                   <DropdownMenuItem onClick={handleAddModFiles}>
                     <FileArchive className="size-4 mr-2" />
-                    <Trans>Add Mod Files (.zip)</Trans>
+                    <Trans>Add Mod Files (.zip, .7z, .rar)</Trans>
                   </DropdownMenuItem>
                   <DropdownMenuItem onClick={handleAddModFolder}>
                     <FolderOpen className="size-4 mr-2" />
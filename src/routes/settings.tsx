@@ -1,8 +1,8 @@
 import { createFileRoute } from '@tanstack/react-router'
 import { Button } from '@comps/button'
 import { Trans } from '@lingui/react/macro'
-import { useState } from 'react'
-import { commands } from '@gen/bindings'
+import { useEffect, useState } from 'react'
+import { commands, type DataDirInfo } from '@gen/bindings'
 import { ur } from '@/utils/result'
 import { msg, t } from '@lingui/core/macro'
 import { Loader2, Copy, Check } from 'lucide-react'
@@ -19,6 +19,11 @@ function RouteComponent() {
   const [loading, setLoading] = useState(false)
   const [error, setError] = useState<string | null>(null)
   const [copiedPath, setCopiedPath] = useState<string | null>(null)
+  const [dataDir, setDataDir] = useState<DataDirInfo | null>(null)
+
+  useEffect(() => {
+    ur(commands.getDataDir()).then(setDataDir, console.error)
+  }, [])
 
   const handleCreateSimulationGameRoot = async () => {
     setLoading(true)
@@ -73,6 +78,31 @@ function RouteComponent() {
       </div>
 
       <div className="space-y-4">
+        {dataDir && (
+          <div className="border rounded-lg p-6 space-y-2">
+            <h2 className="text-lg font-semibold">
+              <Trans>Data Directory</Trans>
+            </h2>
+            <p className="text-sm text-muted-foreground">
+              {dataDir.source === 'Portable' && (
+                <Trans>Portable mode: data is stored next to the executable.</Trans>
+              )}
+              {dataDir.source === 'Environment' && (
+                <Trans>Set by the MOD_KEEPER_DATA_DIR environment variable.</Trans>
+              )}
+              {dataDir.source === 'Default' && (
+                <Trans>
+                  Default location. Start with --portable or set
+                  MOD_KEEPER_DATA_DIR to change it.
+                </Trans>
+              )}
+            </p>
+            <p className="text-sm text-muted-foreground font-mono break-all">
+              {dataDir.path ?? t(msg`Unknown`)}
+            </p>
+          </div>
+        )}
+
         <div className="border rounded-lg p-6 space-y-4">
           <div>
             <h2 className="text-lg font-semibold mb-2">